@@ -0,0 +1,253 @@
+//! Upfront permission sweep for the commands that modify a cluster.
+//!
+//! Without cluster-admin, missing permissions otherwise surface one at a time
+//! as a 403 deep into a deploy, or as a wait that times out. Each command's
+//! requirements are listed here and checked with SelfSubjectAccessReviews
+//! before it starts; anything denied is reported together with the Roles and
+//! ClusterRole an admin can apply to grant it.
+
+use anyhow::{Context, Result};
+use futures::future::join_all;
+use k8s_openapi::api::authorization::v1::SelfSubjectAccessReview;
+use kube::api::{Api, PostParams};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use crate::{exec, k8s, registry};
+
+/// Commands whose permissions can be checked (`check --permissions`).
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    /// Auto-setup: registry, namespace, operator install, TektonConfig
+    Setup,
+    /// Patch the operator and re-render the components
+    Deploy,
+    /// Run release-tests against the cluster
+    Test,
+    /// Start in-cluster Jobs (ServiceAccount, binding, image push)
+    Run,
+    /// Remove the streamstress footprint
+    Uninstall,
+}
+
+/// One verb on a resource, and why it is needed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Requirement {
+    pub group: &'static str,
+    /// Resource, with a subresource after '/' (e.g. "imagestreams/layers")
+    pub resource: &'static str,
+    pub verb: &'static str,
+    /// Namespace the access is needed in; None for cluster scope or any namespace
+    pub namespace: Option<&'static str>,
+    pub reason: &'static str,
+}
+
+const OPERATOR_NS: &str = "openshift-operators";
+const PIPELINES_NS: &str = "openshift-pipelines";
+
+type Rule = (&'static str, &'static str, &'static [&'static str], Option<&'static str>, &'static str);
+
+const SETUP: &[Rule] = &[
+    ("imageregistry.operator.openshift.io", "configs", &["get", "patch"], None, "image registry route and storage"),
+    ("storage.k8s.io", "storageclasses", &["list"], None, "image registry PVC storage"),
+    ("", "namespaces", &["get", "create"], None, "image namespace"),
+    ("rbac.authorization.k8s.io", "rolebindings", &["get", "create"], Some(registry::DEFAULT_NAMESPACE), "image-puller RBAC"),
+    ("operators.coreos.com", "subscriptions", &["get", "create"], Some(OPERATOR_NS), "operator install"),
+    ("operators.coreos.com", "installplans", &["list", "patch"], Some(OPERATOR_NS), "InstallPlan approval"),
+    ("apps", "deployments", &["get"], Some(OPERATOR_NS), "operator readiness"),
+    ("operator.tekton.dev", "tektonconfigs", &["get", "create"], None, "TektonConfig"),
+];
+
+const DEPLOY: &[Rule] = &[
+    ("operator.tekton.dev", "tektonconfigs", &["get"], None, "operator check"),
+    ("apps", "deployments", &["get", "update"], Some(OPERATOR_NS), "operator IMAGE_ env vars"),
+    ("operator.tekton.dev", "tektoninstallersets", &["list", "delete"], None, "re-rendering components"),
+    ("rbac.authorization.k8s.io", "rolebindings", &["get", "create"], Some(registry::DEFAULT_NAMESPACE), "image-puller RBAC"),
+    ("", "pods", &["list"], Some(PIPELINES_NS), "waiting for reconciliation"),
+];
+
+const TEST: &[Rule] = &[
+    ("", "namespaces", &["create", "delete"], None, "test namespaces"),
+    ("", "pods", &["get", "list", "create", "delete"], None, "release-tests workloads"),
+    ("", "pods/log", &["get"], None, "release-tests workloads"),
+    ("", "secrets", &["get", "create"], None, "release-tests workloads"),
+    ("", "configmaps", &["get", "create"], None, "release-tests workloads"),
+    ("", "serviceaccounts", &["get", "create", "patch"], None, "release-tests workloads"),
+    ("rbac.authorization.k8s.io", "rolebindings", &["create"], None, "release-tests workloads"),
+    ("tekton.dev", "pipelines", &["create", "get", "delete"], None, "release-tests Tekton resources"),
+    ("tekton.dev", "pipelineruns", &["create", "get", "list", "delete"], None, "release-tests Tekton resources"),
+    ("tekton.dev", "tasks", &["create", "get", "delete"], None, "release-tests Tekton resources"),
+    ("tekton.dev", "taskruns", &["get", "list"], None, "release-tests Tekton resources"),
+    ("triggers.tekton.dev", "eventlisteners", &["create", "get", "delete"], None, "triggers specs"),
+    ("route.openshift.io", "routes", &["create", "get"], None, "triggers specs"),
+];
+
+const RUN: &[Rule] = &[
+    ("", "serviceaccounts", &["get", "create"], Some(PIPELINES_NS), "Job ServiceAccount"),
+    ("rbac.authorization.k8s.io", "clusterrolebindings", &["get", "create", "delete"], None, "Job ServiceAccount binding"),
+    ("rbac.authorization.k8s.io", "clusterroles", &["bind"], None, "Job ServiceAccount binding"),
+    ("batch", "jobs", &["create", "get", "list"], Some(PIPELINES_NS), "in-cluster Job"),
+    ("", "configmaps", &["get", "list"], Some(PIPELINES_NS), "Job status"),
+    ("", "secrets", &["create", "patch"], Some(PIPELINES_NS), "publish token for the Job"),
+    ("", "pods/log", &["get"], Some(PIPELINES_NS), "Job logs"),
+    ("image.openshift.io", "imagestreams/layers", &["update"], Some(registry::DEFAULT_NAMESPACE), "pushing images"),
+];
+
+const UNINSTALL: &[Rule] = &[
+    ("apps", "deployments", &["get", "update"], Some(OPERATOR_NS), "reverting operator images"),
+    ("operators.coreos.com", "clusterserviceversions", &["list"], Some(OPERATOR_NS), "reverting operator images"),
+    ("operator.tekton.dev", "tektoninstallersets", &["list", "delete"], None, "reverting operator images"),
+    ("", "namespaces", &["delete"], None, "image namespace"),
+    ("batch", "jobs", &["list", "delete"], Some(PIPELINES_NS), "in-cluster Jobs"),
+    ("", "configmaps", &["list", "delete"], Some(PIPELINES_NS), "in-cluster Jobs"),
+    ("", "serviceaccounts", &["delete"], Some(PIPELINES_NS), "in-cluster Jobs"),
+    ("", "secrets", &["delete"], Some(PIPELINES_NS), "in-cluster Jobs"),
+    ("", "persistentvolumeclaims", &["delete"], Some(PIPELINES_NS), "in-cluster Jobs"),
+    ("rbac.authorization.k8s.io", "clusterrolebindings", &["delete"], None, "in-cluster Jobs"),
+    ("rbac.authorization.k8s.io", "clusterroles", &["delete"], None, "in-cluster Jobs"),
+    ("tekton.dev", "pipelines", &["list", "delete"], None, "Konflux standalone pipeline"),
+    ("tekton.dev", "pipelineruns", &["list", "delete"], None, "Konflux standalone pipeline"),
+];
+
+/// Requirements of `commands`, without duplicates.
+pub fn requirements(commands: &[Command]) -> Vec<Requirement> {
+    let mut out: Vec<Requirement> = Vec::new();
+    for command in commands {
+        let rules = match command {
+            Command::Setup => SETUP,
+            Command::Deploy => DEPLOY,
+            Command::Test => TEST,
+            Command::Run => RUN,
+            Command::Uninstall => UNINSTALL,
+        };
+        for &(group, resource, verbs, namespace, reason) in rules {
+            for &verb in verbs {
+                if !out.iter().any(|r| r.group == group && r.resource == resource && r.verb == verb && r.namespace == namespace) {
+                    out.push(Requirement { group, resource, verb, namespace, reason });
+                }
+            }
+        }
+    }
+    out
+}
+
+/// The requirements the current user is denied, one SelfSubjectAccessReview each.
+/// Blocking: creates its own runtime.
+pub fn missing(reqs: &[Requirement]) -> Result<Vec<Requirement>> {
+    let (rt, client) = k8s::create_kube_client()?;
+    let api: Api<SelfSubjectAccessReview> = Api::all(client);
+    let pp = PostParams::default();
+    let reviews = reqs.iter().map(|r| {
+        let (resource, subresource) = r.resource.split_once('/').unwrap_or((r.resource, ""));
+        let review: SelfSubjectAccessReview = serde_json::from_value(json!({
+            "apiVersion": "authorization.k8s.io/v1",
+            "kind": "SelfSubjectAccessReview",
+            "spec": {"resourceAttributes": {
+                "group": r.group, "resource": resource, "subresource": subresource,
+                "verb": r.verb, "namespace": r.namespace.unwrap_or("")
+            }}
+        }))
+        .expect("valid SelfSubjectAccessReview");
+        let (api, pp) = (&api, &pp);
+        async move { k8s::retry("create SelfSubjectAccessReview", || api.create(pp, &review)).await }
+    });
+    let results = rt.block_on(join_all(reviews));
+
+    let mut denied = Vec::new();
+    for (req, result) in reqs.iter().zip(results) {
+        let review = result.context("Failed to create SelfSubjectAccessReview")?;
+        if !review.status.is_some_and(|s| s.allowed) {
+            denied.push(req.clone());
+        }
+    }
+    Ok(denied)
+}
+
+/// Print the denied permissions, grouped by what they are needed for.
+pub fn print_report(missing: &[Requirement]) {
+    eprintln!("Missing permissions ({}):", missing.len());
+    eprintln!("  {:<44} {:<10} {:<24} NEEDED FOR", "RESOURCE", "VERB", "NAMESPACE");
+    for r in missing {
+        let resource = if r.group.is_empty() { r.resource.to_string() } else { format!("{}.{}", r.resource, r.group) };
+        eprintln!("  {:<44} {:<10} {:<24} {}", resource, r.verb, r.namespace.unwrap_or("(cluster)"), r.reason);
+    }
+}
+
+/// Roles (per namespace) and a ClusterRole granting `missing`, each bound to `user`.
+pub fn grant_manifests(missing: &[Requirement], user: &str) -> Vec<Value> {
+    // namespace (None: cluster) -> (group, resource) -> verbs
+    type Rules<'a> = BTreeMap<(&'a str, &'a str), Vec<&'a str>>;
+    let mut scopes: BTreeMap<Option<&str>, Rules> = BTreeMap::new();
+    for r in missing {
+        let verbs = scopes.entry(r.namespace).or_default().entry((r.group, r.resource)).or_default();
+        if !verbs.contains(&r.verb) {
+            verbs.push(r.verb);
+        }
+    }
+    let mut docs = Vec::new();
+    for (namespace, rules) in scopes {
+        let rules: Vec<Value> = rules
+            .into_iter()
+            .map(|((group, resource), verbs)| json!({"apiGroups": [group], "resources": [resource], "verbs": verbs}))
+            .collect();
+        let (kind, binding_kind) = if namespace.is_some() { ("Role", "RoleBinding") } else { ("ClusterRole", "ClusterRoleBinding") };
+        let mut metadata = json!({"name": "streamstress-user", "labels": {"app": "streamstress"}});
+        if let Some(ns) = namespace {
+            metadata["namespace"] = json!(ns);
+        }
+        docs.push(json!({"apiVersion": "rbac.authorization.k8s.io/v1", "kind": kind, "metadata": metadata, "rules": rules}));
+        docs.push(json!({
+            "apiVersion": "rbac.authorization.k8s.io/v1",
+            "kind": binding_kind,
+            "metadata": metadata,
+            "roleRef": {"apiGroup": "rbac.authorization.k8s.io", "kind": kind, "name": "streamstress-user"},
+            "subjects": [{"apiGroup": "rbac.authorization.k8s.io", "kind": "User", "name": user}]
+        }));
+    }
+    docs
+}
+
+/// Name of the logged-in user, for the bindings.
+pub fn current_user() -> String {
+    exec::run_cmd_timeout("oc", &["whoami"], exec::API_TIMEOUT)
+        .map(|r| r.stdout.trim().to_string())
+        .ok()
+        .filter(|u| !u.is_empty())
+        .unwrap_or_else(|| "<user>".to_string())
+}
+
+/// `grant_manifests` as a multi-document YAML stream.
+pub fn render_grant_yaml(missing: &[Requirement], user: &str) -> Result<String> {
+    let mut out = String::new();
+    for doc in grant_manifests(missing, user) {
+        out.push_str("---\n");
+        out.push_str(&serde_yaml::to_string(&doc).context("Failed to render RBAC manifest")?);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grant_manifests() {
+        let reqs = requirements(&[Command::Setup, Command::Deploy]);
+        // rolebindings get/create in tekton-upstream appear in both, once
+        let rb = reqs.iter().filter(|r| r.resource == "rolebindings").count();
+        assert_eq!(rb, 2);
+
+        let missing: Vec<Requirement> = reqs
+            .into_iter()
+            .filter(|r| (r.resource == "deployments" && r.verb == "update") || r.resource == "tektoninstallersets" || r.resource == "namespaces")
+            .collect();
+        let docs = grant_manifests(&missing, "alice");
+        let kinds: Vec<&str> = docs.iter().filter_map(|d| d["kind"].as_str()).collect();
+        assert_eq!(kinds, vec!["ClusterRole", "ClusterRoleBinding", "Role", "RoleBinding"]);
+        assert_eq!(docs[0]["rules"][0], json!({"apiGroups": [""], "resources": ["namespaces"], "verbs": ["get", "create"]}));
+        assert_eq!(docs[0]["rules"][1], json!({"apiGroups": ["operator.tekton.dev"], "resources": ["tektoninstallersets"], "verbs": ["list", "delete"]}));
+        assert_eq!(docs[2]["metadata"]["namespace"], "openshift-operators");
+        assert_eq!(docs[2]["rules"], json!([{"apiGroups": ["apps"], "resources": ["deployments"], "verbs": ["update"]}]));
+        assert_eq!(docs[3]["subjects"][0]["name"], "alice");
+    }
+}
@@ -0,0 +1,202 @@
+//! Pipelines webhook admission readiness, checked before the tests.
+//!
+//! After a deploy re-creates the InstallerSets, the pipelines webhook can take
+//! a while to serve again, and specs that create PipelineRuns in that window
+//! fail with "failed calling webhook". Before the tests, a run waits until the
+//! `tekton-pipelines-webhook` Service has ready endpoints and a server-side dry
+//! run create of a minimal PipelineRun passes admission, which goes through
+//! the same webhooks without leaving anything behind. The time it took, the
+//! number of attempts and the age of the webhook certificate are recorded under
+//! `admission` in `results/metadata.json`. A webhook that never becomes ready
+//! is reported as a synthetic test and the suite is skipped.
+
+use anyhow::Result;
+use k8s_openapi::api::core::v1::Secret;
+use k8s_openapi::api::discovery::v1::EndpointSlice;
+use kube::api::{Api, ApiResource, DynamicObject, ListParams, PostParams};
+use serde::Serialize;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+use crate::{k8s, results, timestamp};
+
+/// Spec name of the synthetic test reported when the webhook is not ready.
+pub const SPEC: &str = "Pipelines webhook admission readiness";
+
+const WEBHOOK_NAMESPACE: &str = "openshift-pipelines";
+
+const WEBHOOK_SERVICE: &str = "tekton-pipelines-webhook";
+
+/// Secret the webhook keeps its serving certificate in.
+const WEBHOOK_CERT_SECRET: &str = "webhook-certs";
+
+/// Namespace the dry-run PipelineRun is admitted in; nothing is created there.
+const DRY_RUN_NAMESPACE: &str = "default";
+
+/// How long the webhook gets to become ready.
+const READY_TIMEOUT: Duration = Duration::from_secs(180);
+
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Outcome of the readiness wait, recorded under `admission` in the run metadata.
+#[derive(Debug, Clone, Serialize)]
+pub struct AdmissionReadiness {
+    pub ready: bool,
+    /// Seconds until admission passed, or until giving up
+    pub time_to_ready_secs: f64,
+    pub attempts: u32,
+    /// Ready endpoints of the webhook Service at the last attempt
+    pub webhook_endpoints: usize,
+    /// Seconds since the webhook certificate Secret was last written
+    pub cert_age_secs: Option<i64>,
+    /// Why the last attempt failed, when not ready
+    pub error: Option<String>,
+}
+
+impl AdmissionReadiness {
+    /// The readiness as a synthetic test, reported when the webhook did not become ready.
+    pub fn as_test(&self) -> results::TestCaseResult {
+        results::TestCaseResult {
+            spec: SPEC.to_string(),
+            scenario: "PipelineRun creation passes webhook admission".to_string(),
+            passed: self.ready,
+            duration_secs: self.time_to_ready_secs,
+            error_message: self.error.clone(),
+            components: vec!["pipeline".to_string()],
+            log_excerpts: Vec::new(),
+        }
+    }
+}
+
+/// Wait for the pipelines webhook to admit PipelineRuns.
+pub fn wait_ready() -> Result<AdmissionReadiness> {
+    let (rt, client) = k8s::create_kube_client()?;
+    let slices: Api<EndpointSlice> = Api::namespaced(client.clone(), WEBHOOK_NAMESPACE);
+    let secrets: Api<Secret> = Api::namespaced(client.clone(), WEBHOOK_NAMESPACE);
+    let pipelineruns: Api<DynamicObject> = Api::namespaced_with(client, DRY_RUN_NAMESPACE, &pipelinerun_resource());
+
+    let cert_age_secs = match rt.block_on(secrets.get_opt(WEBHOOK_CERT_SECRET)) {
+        Ok(Some(secret)) => last_written(&secret).map(|t| timestamp::unix_now() - t),
+        _ => None,
+    };
+    let lp = ListParams::default().labels(&format!("kubernetes.io/service-name={}", WEBHOOK_SERVICE));
+    let pp = PostParams { dry_run: true, ..Default::default() };
+    let pipelinerun: DynamicObject = serde_json::from_value(dry_run_pipelinerun())?;
+
+    let started = Instant::now();
+    let mut attempts = 0;
+    let mut webhook_endpoints = 0;
+    loop {
+        attempts += 1;
+        // Single calls: the loop is the retry, so a failed list is polled again too
+        let outcome = match rt.block_on(slices.list(&lp)) {
+            Err(e) => Err(format!("Failed to list the webhook EndpointSlices: {e}")),
+            Ok(list) => {
+                webhook_endpoints = ready_endpoints(&list.items);
+                if webhook_endpoints == 0 {
+                    Err(format!("Service {}/{} has no ready endpoints", WEBHOOK_NAMESPACE, WEBHOOK_SERVICE))
+                } else {
+                    rt.block_on(pipelineruns.create(&pp, &pipelinerun))
+                        .map(|_| ())
+                        .map_err(|e| format!("Dry-run PipelineRun was not admitted: {e}"))
+                }
+            }
+        };
+        let readiness = |ready: bool, error: Option<String>| AdmissionReadiness {
+            ready,
+            time_to_ready_secs: started.elapsed().as_secs_f64(),
+            attempts,
+            webhook_endpoints,
+            cert_age_secs,
+            error,
+        };
+        match outcome {
+            Ok(()) => return Ok(readiness(true, None)),
+            Err(e) if started.elapsed() >= READY_TIMEOUT => return Ok(readiness(false, Some(e))),
+            Err(_) => std::thread::sleep(POLL_INTERVAL),
+        }
+    }
+}
+
+fn pipelinerun_resource() -> ApiResource {
+    ApiResource {
+        group: "tekton.dev".into(),
+        version: "v1".into(),
+        api_version: "tekton.dev/v1".into(),
+        kind: "PipelineRun".into(),
+        plural: "pipelineruns".into(),
+    }
+}
+
+/// Smallest PipelineRun the webhooks default and validate.
+fn dry_run_pipelinerun() -> serde_json::Value {
+    json!({
+        "apiVersion": "tekton.dev/v1",
+        "kind": "PipelineRun",
+        "metadata": {"generateName": "streamstress-admission-", "labels": {"app": "streamstress"}},
+        "spec": {"pipelineSpec": {"tasks": [{
+            "name": "noop",
+            "taskSpec": {"steps": [{"name": "noop", "image": "registry.access.redhat.com/ubi9/ubi-minimal", "script": "true"}]}
+        }]}}
+    })
+}
+
+/// Endpoints across `slices` that are ready (a missing condition counts as ready).
+fn ready_endpoints(slices: &[EndpointSlice]) -> usize {
+    slices
+        .iter()
+        .flat_map(|s| s.endpoints.iter())
+        .filter(|e| e.conditions.as_ref().and_then(|c| c.ready).unwrap_or(true))
+        .count()
+}
+
+/// When the Secret was last written (unix seconds): the latest managed-fields
+/// update, as rotating the certificate rewrites the Secret in place.
+fn last_written(secret: &Secret) -> Option<i64> {
+    let created = secret.metadata.creation_timestamp.as_ref().map(|t| t.0.as_second());
+    let updated = secret
+        .metadata
+        .managed_fields
+        .iter()
+        .flatten()
+        .filter_map(|f| f.time.as_ref().map(|t| t.0.as_second()))
+        .max();
+    updated.max(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ready_endpoints() {
+        let slice: EndpointSlice = serde_json::from_value(json!({
+            "metadata": {"name": "tekton-pipelines-webhook-abc"},
+            "addressType": "IPv4",
+            "endpoints": [
+                {"addresses": ["10.0.0.1"], "conditions": {"ready": true}},
+                {"addresses": ["10.0.0.2"], "conditions": {"ready": false}},
+                {"addresses": ["10.0.0.3"]}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(ready_endpoints(&[slice]), 2);
+        assert_eq!(ready_endpoints(&[]), 0);
+    }
+
+    #[test]
+    fn test_last_written() {
+        let secret: Secret = serde_json::from_value(json!({
+            "metadata": {
+                "name": "webhook-certs",
+                "creationTimestamp": "2025-01-01T00:00:00Z",
+                "managedFields": [
+                    {"manager": "webhook", "operation": "Update", "time": "2025-01-02T00:00:00Z"},
+                    {"manager": "kubectl", "operation": "Update", "time": "2025-01-01T12:00:00Z"}
+                ]
+            }
+        }))
+        .unwrap();
+        assert_eq!(last_written(&secret), Some(1735776000));
+    }
+}
@@ -0,0 +1,353 @@
+//! Pre-computed dashboard index files, maintained incrementally by `publish`.
+//!
+//! The dashboard would otherwise have to fetch every run file to draw trends.
+//! Each publish folds the new run into three small files under `runs/index/`:
+//!
+//! - `test-history.json`: per-test pass counts and recent outcomes
+//! - `categories-weekly.json`: failure counts per category by ISO week
+//! - `latest.json`: summaries of the most recent runs (enough for trend charts)
+//!
+//! Folding a run in is idempotent, so re-merging after a push conflict or
+//! republishing the same run id never double-counts.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::results;
+
+/// Directory (relative to the gh-pages root) holding the index files.
+pub const INDEX_DIR: &str = "runs/index";
+
+const TEST_HISTORY_FILE: &str = "test-history.json";
+const CATEGORIES_WEEKLY_FILE: &str = "categories-weekly.json";
+const LATEST_FILE: &str = "latest.json";
+
+/// Outcomes kept per test in `test-history.json` (totals cover every run).
+const TEST_HISTORY_MAX: usize = 50;
+
+/// Run summaries kept in `latest.json`.
+const LATEST_MAX: usize = 200;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TestHistory {
+    /// Keyed by `spec::scenario`
+    pub tests: BTreeMap<String, TestRecord>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TestRecord {
+    pub runs: u32,
+    pub passed: u32,
+    pub pass_rate: f64,
+    /// Most recent first
+    pub history: Vec<TestOutcome>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TestOutcome {
+    pub id: String,
+    pub timestamp: String,
+    pub passed: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CategoriesWeekly {
+    /// Keyed by ISO week, e.g. `2025-W09`
+    pub weeks: BTreeMap<String, WeekCounts>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WeekCounts {
+    pub run_ids: Vec<String>,
+    pub categories: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Latest {
+    /// Most recent first
+    pub runs: Vec<RunSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub id: String,
+    pub timestamp: String,
+    #[serde(default)]
+    pub label: String,
+    pub total: u64,
+    pub passed: u64,
+    pub failed: u64,
+    pub pass_rate: f64,
+    pub categories: BTreeMap<String, u32>,
+    pub file: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_id: Option<String>,
+    /// The run's `--meta` entries the dashboard filters on
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub meta: BTreeMap<String, String>,
+}
+
+/// All index files for a gh-pages tree.
+#[derive(Debug, Default)]
+pub struct Indexes {
+    pub test_history: TestHistory,
+    pub categories_weekly: CategoriesWeekly,
+    pub latest: Latest,
+}
+
+impl Indexes {
+    /// Build from existing file contents (`None` or unparsable: start empty).
+    pub fn from_contents(test_history: Option<&str>, categories_weekly: Option<&str>, latest: Option<&str>) -> Self {
+        fn parse<T: for<'de> Deserialize<'de> + Default>(s: Option<&str>) -> T {
+            s.and_then(|s| serde_json::from_str(s).ok()).unwrap_or_default()
+        }
+        Self {
+            test_history: parse(test_history),
+            categories_weekly: parse(categories_weekly),
+            latest: parse(latest),
+        }
+    }
+
+    /// Load the index files from a gh-pages tree, backfilling from the run
+    /// files already there when the indexes don't exist yet.
+    pub fn load(root: &Path) -> Result<Self> {
+        let dir = root.join(INDEX_DIR);
+        let read = |name: &str| fs::read_to_string(dir.join(name)).ok();
+        if dir.join(LATEST_FILE).exists() {
+            let (h, c, l) = (read(TEST_HISTORY_FILE), read(CATEGORIES_WEEKLY_FILE), read(LATEST_FILE));
+            return Ok(Self::from_contents(h.as_deref(), c.as_deref(), l.as_deref()));
+        }
+
+        let manifest: serde_json::Value = match fs::read_to_string(root.join("runs/manifest.json")) {
+            Ok(s) => serde_json::from_str(&s).unwrap_or_default(),
+            Err(_) => return Ok(Self::default()),
+        };
+        Self::backfill(&manifest, |file| Ok(fs::read_to_string(root.join(file)).ok()))
+    }
+
+    /// Build the indexes from the runs listed in `manifest`, reading each run file
+    /// (path relative to the gh-pages root) with `read`. Unreadable runs are skipped.
+    pub fn backfill(manifest: &serde_json::Value, mut read: impl FnMut(&str) -> Result<Option<String>>) -> Result<Self> {
+        let mut indexes = Self::default();
+        let entries = manifest["runs"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+        let mut backfilled = 0;
+        // Manifest is newest first; fold oldest first so histories end up ordered
+        for entry in entries.iter().rev() {
+            let (Some(id), Some(file)) = (entry["id"].as_str(), entry["file"].as_str()) else {
+                continue;
+            };
+            // Script-published manifests list files relative to runs/
+            let file = if file.starts_with("runs/") { file.to_string() } else { format!("runs/{file}") };
+            let Some(content) = read(&file)? else {
+                continue;
+            };
+            let Ok(mut run) = serde_json::from_str::<serde_json::Value>(&content) else {
+                continue;
+            };
+            for key in ["timestamp", "label"] {
+                if run.get(key).is_none() {
+                    run[key] = entry[key].clone();
+                }
+            }
+            indexes.add_run(id, &file, &run);
+            backfilled += 1;
+        }
+        if backfilled > 0 {
+            eprintln!("Backfilled dashboard indexes from {} existing run(s)", backfilled);
+        }
+        Ok(indexes)
+    }
+
+    /// Change the label of an indexed run.
+    pub fn relabel_run(&mut self, run_id: &str, label: &str) {
+        if let Some(r) = self.latest.runs.iter_mut().find(|r| r.id == run_id) {
+            r.label = label.to_string();
+        }
+    }
+
+    /// Fold a run (stored at `file` under the gh-pages root) into every index.
+    /// Safe to call again for the same id.
+    pub fn add_run(&mut self, run_id: &str, file: &str, run: &serde_json::Value) {
+        let timestamp = run_timestamp(run);
+        let categories = run_categories(run);
+
+        for test in run["tests"].as_array().map(Vec::as_slice).unwrap_or(&[]) {
+            let key = format!(
+                "{}::{}",
+                test["spec"].as_str().unwrap_or(""),
+                test["scenario"].as_str().unwrap_or("")
+            );
+            let record = self.test_history.tests.entry(key).or_default();
+            if record.history.iter().any(|o| o.id == run_id) {
+                continue;
+            }
+            let passed = test["passed"].as_bool().unwrap_or(false);
+            record.runs += 1;
+            record.passed += passed as u32;
+            record.pass_rate = record.passed as f64 / record.runs as f64 * 100.0;
+            record.history.insert(0, TestOutcome { id: run_id.to_string(), timestamp: timestamp.clone(), passed });
+            record.history.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+            record.history.truncate(TEST_HISTORY_MAX);
+        }
+
+        if let Some(week) = iso_week(&timestamp) {
+            let counts = self.categories_weekly.weeks.entry(week).or_default();
+            if !counts.run_ids.iter().any(|id| id == run_id) {
+                counts.run_ids.push(run_id.to_string());
+                for (category, count) in &categories {
+                    *counts.categories.entry(category.clone()).or_default() += count;
+                }
+            }
+        }
+
+        let total = run["total"].as_u64().unwrap_or(0);
+        let passed = run["passed"].as_u64().unwrap_or(0);
+        let summary = RunSummary {
+            id: run_id.to_string(),
+            timestamp,
+            label: run["label"].as_str().unwrap_or("").to_string(),
+            total,
+            passed,
+            failed: run["failed"].as_u64().unwrap_or(0),
+            pass_rate: if total > 0 { passed as f64 / total as f64 * 100.0 } else { 0.0 },
+            categories,
+            file: file.to_string(),
+            batch_id: run["batch_id"].as_str().map(str::to_string),
+            meta: serde_json::from_value(run["meta"].clone())
+                .map(|meta| crate::runmeta::select(&meta, &crate::runmeta::manifest_keys()))
+                .unwrap_or_default(),
+        };
+        self.latest.runs.retain(|r| r.id != run_id);
+        self.latest.runs.push(summary);
+        self.latest.runs.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        self.latest.runs.truncate(LATEST_MAX);
+    }
+
+    /// Index files as (path relative to the gh-pages root, content).
+    pub fn files(&self) -> Result<Vec<(String, String)>> {
+        Ok(vec![
+            (format!("{INDEX_DIR}/{TEST_HISTORY_FILE}"), serde_json::to_string(&self.test_history)?),
+            (format!("{INDEX_DIR}/{CATEGORIES_WEEKLY_FILE}"), serde_json::to_string_pretty(&self.categories_weekly)?),
+            (format!("{INDEX_DIR}/{LATEST_FILE}"), serde_json::to_string_pretty(&self.latest)?),
+        ])
+    }
+
+    /// Write the index files into a gh-pages tree.
+    pub fn write(&self, root: &Path) -> Result<()> {
+        let dir = root.join(INDEX_DIR);
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        for (path, content) in self.files()? {
+            fs::write(root.join(&path), content).with_context(|| format!("Failed to write {}", path))?;
+        }
+        Ok(())
+    }
+}
+
+/// Paths of the index files relative to the gh-pages root, in `from_contents` order.
+pub fn index_paths() -> [String; 3] {
+    [TEST_HISTORY_FILE, CATEGORIES_WEEKLY_FILE, LATEST_FILE].map(|f| format!("{INDEX_DIR}/{f}"))
+}
+
+/// When the run's results are from: the as-of date for historical runs,
+/// otherwise the publish timestamp.
+fn run_timestamp(run: &serde_json::Value) -> String {
+    match run["as_of_date"].as_str() {
+        Some(date) if date.len() == 10 => format!("{}T23:59:59Z", date),
+        Some(date) => date.to_string(),
+        None => run["timestamp"].as_str().unwrap_or("").to_string(),
+    }
+}
+
+/// Failure counts per category, from the run's categorized groups or, for
+/// uncategorized results, by categorizing the failed tests' errors.
+fn run_categories(run: &serde_json::Value) -> BTreeMap<String, u32> {
+    let mut counts = BTreeMap::new();
+    if let Some(groups) = run["categories"].as_array() {
+        for group in groups {
+            if let Some(category) = group["category"].as_str() {
+                *counts.entry(category.to_string()).or_default() += group["count"].as_u64().unwrap_or(0) as u32;
+            }
+        }
+        return counts;
+    }
+    for test in run["tests"].as_array().map(Vec::as_slice).unwrap_or(&[]) {
+        if test["passed"].as_bool() == Some(false) {
+            let category = results::categorize_failure(test["error_message"].as_str().unwrap_or(""));
+            *counts.entry(category.to_string()).or_default() += 1;
+        }
+    }
+    counts
+}
+
+/// ISO week (`2025-W09`) of an RFC 3339 timestamp.
+fn iso_week(timestamp: &str) -> Option<String> {
+    let date = chrono::DateTime::parse_from_rfc3339(timestamp).ok()?.date_naive();
+    Some(date.format("%G-W%V").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn run(timestamp: &str, first_passed: bool) -> serde_json::Value {
+        json!({
+            "timestamp": timestamp,
+            "label": "nightly",
+            "total": 2,
+            "passed": 1 + first_passed as u64,
+            "failed": 1 - first_passed as u64,
+            "tests": [
+                {"spec": "S", "scenario": "a", "passed": first_passed, "error_message": if first_passed { None } else { Some("secret not found") }},
+                {"spec": "S", "scenario": "b", "passed": true}
+            ]
+        })
+    }
+
+    #[test]
+    fn test_add_run_is_incremental_and_idempotent() {
+        let mut indexes = Indexes::default();
+        indexes.add_run("run-1", "runs/run-1.json", &run("2025-03-03T10:00:00Z", false));
+        indexes.add_run("run-2", "runs/run-2.json", &run("2025-03-04T10:00:00Z", true));
+        indexes.add_run("run-2", "runs/run-2.json", &run("2025-03-04T10:00:00Z", true));
+
+        let a = &indexes.test_history.tests["S::a"];
+        assert_eq!((a.runs, a.passed), (2, 1));
+        assert_eq!(a.pass_rate, 50.0);
+        assert_eq!(a.history[0].id, "run-2");
+
+        let week = &indexes.categories_weekly.weeks["2025-W10"];
+        assert_eq!(week.run_ids, vec!["run-1", "run-2"]);
+        assert_eq!(week.categories["ConfigGap"], 1);
+
+        assert_eq!(indexes.latest.runs.len(), 2);
+        assert_eq!(indexes.latest.runs[0].id, "run-2");
+        assert_eq!(indexes.latest.runs[1].categories["ConfigGap"], 1);
+    }
+
+    #[test]
+    fn test_load_backfills_and_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("runs")).unwrap();
+        fs::write(root.join("runs/run-1.json"), run("2025-03-03T10:00:00Z", false).to_string()).unwrap();
+        fs::write(
+            root.join("runs/manifest.json"),
+            json!({"runs": [{"id": "run-1", "timestamp": "2025-03-03T10:00:00Z", "file": "runs/run-1.json"}]}).to_string(),
+        )
+        .unwrap();
+
+        let mut indexes = Indexes::load(root).unwrap();
+        assert_eq!(indexes.latest.runs.len(), 1);
+        indexes.add_run("run-2", "runs/run-2.json", &run("2025-03-20T10:00:00Z", true));
+        indexes.write(root).unwrap();
+
+        let reloaded = Indexes::load(root).unwrap();
+        assert_eq!(reloaded.latest.runs.len(), 2);
+        assert_eq!(reloaded.test_history.tests["S::b"].runs, 2);
+        assert_eq!(reloaded.categories_weekly.weeks.len(), 2);
+    }
+}
@@ -0,0 +1,145 @@
+//! Audit log of external commands and Kubernetes mutations.
+//!
+//! When enabled via `--audit-log <path>`, every shelled command and every
+//! mutating API call is appended to the file as one JSON object per line,
+//! so cluster admins can review exactly what a run changed.
+
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::{cassette, exec, logging, timestamp};
+use crate::warnings;
+
+struct AuditLog {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+static AUDIT: OnceLock<AuditLog> = OnceLock::new();
+
+/// Open the audit log for appending. Subsequent records are written to `path`.
+/// Append mode lets batch subprocesses share the parent's log file.
+pub fn init(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() && !parent.as_os_str().is_empty() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create audit log directory {}", parent.display()))?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open audit log {}", path.display()))?;
+    let _ = AUDIT.set(AuditLog {
+        path: path.to_path_buf(),
+        file: Mutex::new(file),
+    });
+    Ok(())
+}
+
+/// Path of the active audit log, if enabled.
+pub fn path() -> Option<&'static Path> {
+    AUDIT.get().map(|a| a.path.as_path())
+}
+
+/// Record an executed command (and log it at debug level). Arguments are
+/// redacted before being written.
+pub fn record_command(program: &str, args: &[String], exit_code: i32, duration: Duration) {
+    let args: Vec<String> = args.iter().map(|a| exec::redact(a)).collect();
+    tracing::debug!(target: logging::COMMAND_TARGET, "{} {} (exit {}, {:.1}s)", program, args.join(" "), exit_code, duration.as_secs_f64());
+    if AUDIT.get().is_none() {
+        return;
+    }
+    write_entry(json!({
+        "timestamp": timestamp::now_rfc3339(),
+        "type": "command",
+        "binary": program,
+        "args": args,
+        "exit_code": exit_code,
+        "duration_ms": duration.as_millis() as u64,
+    }));
+}
+
+/// Record a mutating Kubernetes API call (create, patch, replace, delete).
+pub fn record_api(verb: &str, resource: &str, namespace: Option<&str>, name: &str, success: bool) {
+    let qualified = match namespace {
+        Some(ns) => format!("{}/{}", ns, name),
+        None => name.to_string(),
+    };
+    if success {
+        tracing::info!(target: logging::API_TARGET, "{} {} {}", verb, resource, qualified);
+    } else {
+        tracing::warn!(target: logging::API_TARGET, "{} {} {} failed", verb, resource, qualified);
+    }
+    write_entry(json!({
+        "timestamp": timestamp::now_rfc3339(),
+        "type": "api",
+        "verb": verb,
+        "resource": resource,
+        "namespace": namespace,
+        "name": name,
+        "success": success,
+    }));
+}
+
+/// Run `cmd.status()` and record it in the audit log (and a `--record` cassette).
+/// When replaying a cassette, the recorded status is returned instead.
+pub fn status(cmd: &mut Command) -> std::io::Result<ExitStatus> {
+    if cassette::replaying() {
+        let recorded = cassette::replay_process(cmd).map_err(std::io::Error::other)?;
+        return Ok(cassette::exit_status(recorded.exit_code));
+    }
+    let start = Instant::now();
+    let result = cmd.status();
+    record_process(cmd, result.as_ref().ok().and_then(|s| s.code()), start.elapsed());
+    if let Ok(status) = &result {
+        cassette::record_process(cmd, status.code(), b"", b"");
+    }
+    result
+}
+
+/// Run `cmd.output()` and record it in the audit log (and a `--record` cassette).
+/// When replaying a cassette, the recorded output is returned instead.
+pub fn output(cmd: &mut Command) -> std::io::Result<Output> {
+    if cassette::replaying() {
+        let recorded = cassette::replay_process(cmd).map_err(std::io::Error::other)?;
+        return Ok(Output {
+            status: cassette::exit_status(recorded.exit_code),
+            stdout: recorded.stdout.into_bytes(),
+            stderr: recorded.stderr.into_bytes(),
+        });
+    }
+    let start = Instant::now();
+    let result = cmd.output();
+    record_process(cmd, result.as_ref().ok().and_then(|o| o.status.code()), start.elapsed());
+    if let Ok(output) = &result {
+        cassette::record_process(cmd, output.status.code(), &output.stdout, &output.stderr);
+    }
+    result
+}
+
+/// Record a `std::process::Command` that was run outside of `exec`.
+/// A missing exit code (spawn failure or signal) is logged as -1.
+pub fn record_process(cmd: &Command, exit_code: Option<i32>, duration: Duration) {
+    let program = cmd.get_program().to_string_lossy().into_owned();
+    let args: Vec<String> = cmd
+        .get_args()
+        .map(|a| a.to_string_lossy().into_owned())
+        .collect();
+    record_command(&program, &args, exit_code.unwrap_or(-1), duration);
+}
+
+fn write_entry(entry: serde_json::Value) {
+    let Some(audit) = AUDIT.get() else {
+        return;
+    };
+    let mut file = audit.file.lock().unwrap_or_else(|e| e.into_inner());
+    if let Err(e) = writeln!(file, "{}", entry) {
+        warnings::warn(format!("Failed to write audit log entry: {}", e));
+    }
+}
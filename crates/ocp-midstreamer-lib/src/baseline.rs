@@ -0,0 +1,202 @@
+//! Baseline runs for comparisons, picked from the published dashboard manifest.
+//!
+//! `--baseline latest-green` (or `latest`, `label:<label>`, `meta:<key>=<value>`,
+//! `id:<run id>`) selects the most recent matching run in gh-pages'
+//! `runs/manifest.json` and downloads its run file into an output-directory
+//! layout (`results/results.json`, and the resource profiles it carries), so a
+//! regression comparison does not need the old run's output directory.
+
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::publish;
+
+/// Cache subdirectory holding downloaded baselines, one directory per run id.
+const BASELINE_CACHE_DIR: &str = "baselines";
+
+/// Which published run to compare with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Baseline {
+    /// The most recent run
+    Latest,
+    /// The most recent run with tests and no failures
+    LatestGreen,
+    /// The most recent run with this label
+    Label(String),
+    /// The most recent run with this `--meta` value (manifest keys only)
+    Meta(String, String),
+    /// A specific run
+    Id(String),
+}
+
+/// Parse a `--baseline` selector. Used by clap's value_parser.
+pub fn parse_baseline(s: &str) -> std::result::Result<Baseline, String> {
+    match s {
+        "latest" => return Ok(Baseline::Latest),
+        "latest-green" => return Ok(Baseline::LatestGreen),
+        _ => {}
+    }
+    let invalid = || format!("Invalid baseline '{}': use latest, latest-green, label:<label>, meta:<key>=<value> or id:<run id>", s);
+    let (kind, value) = s.split_once(':').ok_or_else(invalid)?;
+    if value.is_empty() {
+        return Err(invalid());
+    }
+    match kind {
+        "label" => Ok(Baseline::Label(value.to_string())),
+        "id" => Ok(Baseline::Id(value.to_string())),
+        "meta" => match value.split_once('=') {
+            Some((key, v)) if !key.is_empty() => Ok(Baseline::Meta(key.to_string(), v.to_string())),
+            _ => Err(invalid()),
+        },
+        _ => Err(invalid()),
+    }
+}
+
+impl Baseline {
+    fn matches(&self, entry: &serde_json::Value) -> bool {
+        match self {
+            Baseline::Latest => true,
+            Baseline::LatestGreen => {
+                entry["total"].as_u64().unwrap_or(0) > 0 && entry["failed"].as_u64().unwrap_or(0) == 0
+            }
+            Baseline::Label(label) => entry["label"].as_str() == Some(label.as_str()),
+            Baseline::Meta(key, value) => entry["meta"][key].as_str() == Some(value.as_str()),
+            Baseline::Id(id) => entry["id"].as_str() == Some(id.as_str()),
+        }
+    }
+}
+
+impl std::fmt::Display for Baseline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Baseline::Latest => write!(f, "latest"),
+            Baseline::LatestGreen => write!(f, "latest-green"),
+            Baseline::Label(label) => write!(f, "label:{}", label),
+            Baseline::Meta(key, value) => write!(f, "meta:{}={}", key, value),
+            Baseline::Id(id) => write!(f, "id:{}", id),
+        }
+    }
+}
+
+/// The most recent manifest entry matching `baseline`.
+pub fn select<'a>(manifest: &'a serde_json::Value, baseline: &Baseline) -> Option<&'a serde_json::Value> {
+    let timestamp = |e: &serde_json::Value| e["timestamp"].as_str().or(e["date"].as_str()).unwrap_or("").to_string();
+    manifest["runs"]
+        .as_array()?
+        .iter()
+        .filter(|e| baseline.matches(e))
+        .max_by_key(|e| timestamp(e))
+}
+
+/// A baseline run downloaded from gh-pages.
+#[derive(Debug, Clone)]
+pub struct ResolvedBaseline {
+    pub run_id: String,
+    pub label: String,
+    pub timestamp: String,
+    /// Output-directory layout holding the run
+    pub dir: PathBuf,
+}
+
+/// Resolve `baseline` against the dashboard manifest of `remote` (default: as for
+/// `publish --api`) and download the run into `dir` (default: the cache).
+pub fn resolve(baseline: &Baseline, remote: Option<&str>, dir: Option<&Path>) -> Result<ResolvedBaseline> {
+    let manifest = publish::fetch_published_file("runs/manifest.json", remote)?
+        .context("gh-pages has no runs/manifest.json (nothing published yet?)")?;
+    let manifest: serde_json::Value = serde_json::from_str(&manifest).context("runs/manifest.json is not valid JSON")?;
+    let Some(entry) = select(&manifest, baseline) else {
+        bail!("No published run matches baseline {}", baseline);
+    };
+    let run_id = entry["id"].as_str().context("Manifest entry has no id")?.to_string();
+    let dir = match dir {
+        Some(d) => d.to_path_buf(),
+        None => crate::platform::cache_dir()
+            .context("No cache directory for baselines; give --baseline-dir")?
+            .join(BASELINE_CACHE_DIR)
+            .join(&run_id),
+    };
+
+    let results_path = dir.join("results/results.json");
+    if results_path.exists() {
+        eprintln!("Baseline {} ({}): using {}", baseline, run_id, dir.display());
+    } else {
+        let file = publish::entry_file(entry, &run_id);
+        eprintln!("Baseline {} resolved to {}, downloading {}...", baseline, run_id, file);
+        let content = publish::fetch_published_file(&file, remote)?
+            .with_context(|| format!("{} is in the manifest but not on gh-pages", file))?;
+        write_run_dir(&dir, &content)?;
+    }
+
+    Ok(ResolvedBaseline {
+        run_id,
+        label: entry["label"].as_str().unwrap_or("").to_string(),
+        timestamp: entry["timestamp"].as_str().unwrap_or("").to_string(),
+        dir,
+    })
+}
+
+/// Lay a published run file out as an output directory: the run data as
+/// `results/results.json` and the resource profiles publish merged into it.
+fn write_run_dir(dir: &Path, content: &str) -> Result<()> {
+    let run: serde_json::Value = serde_json::from_str(content).context("Run file is not valid JSON")?;
+    for (key, path) in [
+        ("resource_profile", "results/resource-profile.json"),
+        ("performance_resources", "perf/resource-profile.json"),
+    ] {
+        if let Some(profile) = run.get(key).filter(|p| p.is_object()) {
+            write_file(&dir.join(path), &serde_json::to_string_pretty(profile)?)?;
+        }
+    }
+    // Last, so an interrupted download is not taken for a cached one
+    write_file(&dir.join("results/results.json"), content)
+}
+
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_baseline() {
+        assert_eq!(parse_baseline("latest-green").unwrap(), Baseline::LatestGreen);
+        assert_eq!(parse_baseline("label:weekly").unwrap(), Baseline::Label("weekly".into()));
+        assert_eq!(parse_baseline("meta:ocp=4.17").unwrap(), Baseline::Meta("ocp".into(), "4.17".into()));
+        assert_eq!(parse_baseline("id:2026-01-01T00-00-00").unwrap(), Baseline::Id("2026-01-01T00-00-00".into()));
+        assert!(parse_baseline("green").is_err());
+        assert!(parse_baseline("label:").is_err());
+        assert!(parse_baseline("meta:ocp").is_err());
+    }
+
+    #[test]
+    fn test_select() {
+        let manifest = serde_json::json!({"runs": [
+            {"id": "r3", "timestamp": "2026-01-03T00:00:00Z", "label": "nightly", "total": 10, "failed": 2},
+            {"id": "r1", "timestamp": "2026-01-01T00:00:00Z", "label": "weekly", "total": 10, "failed": 0, "meta": {"ocp": "4.17"}},
+            {"id": "r2", "timestamp": "2026-01-02T00:00:00Z", "label": "nightly", "total": 10, "failed": 0},
+            {"id": "r4", "timestamp": "2026-01-04T00:00:00Z", "label": "nightly", "total": 0, "failed": 0},
+        ]});
+        let id = |b: &Baseline| select(&manifest, b).map(|e| e["id"].as_str().unwrap());
+        assert_eq!(id(&Baseline::Latest), Some("r4"));
+        assert_eq!(id(&Baseline::LatestGreen), Some("r2"));
+        assert_eq!(id(&Baseline::Label("weekly".into())), Some("r1"));
+        assert_eq!(id(&Baseline::Meta("ocp".into(), "4.17".into())), Some("r1"));
+        assert_eq!(id(&Baseline::Label("monthly".into())), None);
+    }
+
+    #[test]
+    fn test_write_run_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let run = r#"{"id": "r1", "total": 1, "resource_profile": {"specs": []}}"#;
+        write_run_dir(dir.path(), run).unwrap();
+        assert!(dir.path().join("results/results.json").is_file());
+        assert!(dir.path().join("results/resource-profile.json").is_file());
+        assert!(!dir.path().join("perf/resource-profile.json").exists());
+    }
+}
@@ -0,0 +1,393 @@
+//! Batch execution module for date range iteration.
+//!
+//! Provides date range parsing, validation, and progress tracking for
+//! running historical builds across multiple dates.
+
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::results;
+
+/// Env var carrying the batch id from `run --date-range` to each date's run and
+/// on into its in-cluster Job; recorded in the run metadata so published runs
+/// of one batch can be grouped on the dashboard.
+pub const BATCH_ID_ENV: &str = "STREAMSTRESS_BATCH_ID";
+
+/// A date range for batch historical runs.
+#[derive(Debug, Clone)]
+pub struct DateRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+/// Parse a date range string in START:END format (YYYY-MM-DD:YYYY-MM-DD).
+///
+/// # Examples
+/// ```
+/// let range = ocp_midstreamer_lib::batch::parse_date_range("2025-01-01:2025-01-03").unwrap();
+/// assert_eq!(range.end.to_string(), "2025-01-03");
+/// ```
+pub fn parse_date_range(s: &str) -> Result<DateRange, String> {
+    let parts: Vec<&str> = s.split(':').collect();
+    if parts.len() != 2 {
+        return Err(
+            "Date range must be in START:END format (e.g., 2025-01-01:2025-02-05)".to_string(),
+        );
+    }
+
+    let start = NaiveDate::parse_from_str(parts[0], "%Y-%m-%d")
+        .map_err(|_| format!("Invalid start date '{}'. Use YYYY-MM-DD format.", parts[0]))?;
+    let end = NaiveDate::parse_from_str(parts[1], "%Y-%m-%d")
+        .map_err(|_| format!("Invalid end date '{}'. Use YYYY-MM-DD format.", parts[1]))?;
+
+    if end < start {
+        return Err(format!(
+            "End date ({}) must be on or after start date ({})",
+            end, start
+        ));
+    }
+
+    Ok(DateRange { start, end })
+}
+
+/// Generate all dates in a range (inclusive), in chronological order.
+pub fn generate_dates(range: &DateRange) -> Vec<NaiveDate> {
+    let mut dates = Vec::new();
+    let mut current = range.start;
+    while current <= range.end {
+        dates.push(current);
+        current += Duration::days(1);
+    }
+    dates
+}
+
+/// Id shared by every run of one batch, e.g. `batch-20250101-20250131-20250205T140307Z`.
+pub fn batch_id(range: &DateRange) -> String {
+    let prefix = format!("batch-{}-{}", range.start.format("%Y%m%d"), range.end.format("%Y%m%d"));
+    crate::timestamp::id(&prefix, &crate::timestamp::now_rfc3339())
+}
+
+/// Batch id of this process when it runs one date of a batch.
+pub fn current_batch_id() -> Option<String> {
+    std::env::var(BATCH_ID_ENV).ok().filter(|id| !id.is_empty())
+}
+
+/// Progress tracker for batch runs.
+#[derive(Debug)]
+pub struct BatchProgress {
+    pub current: usize,
+    pub total: usize,
+    pub current_date: String,
+    pub passed: usize,
+    pub failed: usize,
+    pub errors: usize,
+    /// (date, exit code) of each finished run, in order
+    pub outcomes: Vec<(String, i32)>,
+}
+
+impl BatchProgress {
+    pub fn new(total: usize) -> Self {
+        Self {
+            current: 0,
+            total,
+            current_date: String::new(),
+            passed: 0,
+            failed: 0,
+            errors: 0,
+            outcomes: Vec::new(),
+        }
+    }
+
+    pub fn advance(&mut self, date: &str) {
+        self.current += 1;
+        self.current_date = date.to_string();
+    }
+
+    pub fn record_result(&mut self, exit_code: i32) {
+        self.outcomes.push((self.current_date.clone(), exit_code));
+        match exit_code {
+            0 => self.passed += 1,
+            1 => self.failed += 1,
+            _ => self.errors += 1,
+        }
+    }
+
+    pub fn print_progress(&self) {
+        println!(
+            "\n[{}/{}] Processing {} ...",
+            self.current, self.total, self.current_date
+        );
+    }
+
+    pub fn print_summary(&self) {
+        println!("\n========================================");
+        println!("BATCH HISTORICAL RUN SUMMARY");
+        println!("========================================");
+        println!("Total runs:  {}", self.total);
+        println!("Passed:      {} (all tests passed)", self.passed);
+        println!("Failed:      {} (some tests failed)", self.failed);
+        println!("Errors:      {} (build/deploy errors)", self.errors);
+        println!("========================================\n");
+    }
+}
+
+/// One date of a batch in `batch-summary.json`.
+#[derive(Debug, Serialize)]
+pub struct DateSummary {
+    pub date: String,
+    pub exit_code: i32,
+    /// passed, failed, error, or submitted (deploy+test handed to an in-cluster Job)
+    pub status: &'static str,
+    pub total: Option<usize>,
+    pub passed: Option<usize>,
+    pub failed: Option<usize>,
+    /// The run's output directory, relative to the batch output directory
+    pub output_dir: String,
+    /// The run's results.json, relative to the batch output directory, when present
+    pub results: Option<String>,
+}
+
+/// The first date a test failed in the batch.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct FirstFailure {
+    /// "spec::scenario", as in the dashboard's test history
+    pub test: String,
+    pub date: String,
+    /// Latest earlier date the test passed, if it ran before failing
+    pub last_passed: Option<String>,
+}
+
+/// Aggregated report written to `batch-summary.json` when a batch completes.
+#[derive(Debug, Serialize)]
+pub struct BatchSummary {
+    pub batch_id: String,
+    pub start: String,
+    pub end: String,
+    pub dates: Vec<DateSummary>,
+    /// Tests that failed on some date, ordered by the date they first failed
+    pub first_failures: Vec<FirstFailure>,
+}
+
+/// Collect each date's results from `output_dir/<date>/` into a summary.
+pub fn build_summary(batch_id: &str, range: &DateRange, outcomes: &[(String, i32)], output_dir: &Path) -> BatchSummary {
+    let mut dates = Vec::new();
+    let mut runs = Vec::new();
+    for (date, exit_code) in outcomes {
+        let results_rel = format!("{}/results/results.json", date);
+        let result = results::read_results_json(&output_dir.join(&results_rel)).ok();
+        let status = match (exit_code, &result) {
+            (0, None) => "submitted",
+            (0, Some(_)) => "passed",
+            (1, _) => "failed",
+            _ => "error",
+        };
+        dates.push(DateSummary {
+            date: date.clone(),
+            exit_code: *exit_code,
+            status,
+            total: result.as_ref().map(|r| r.total),
+            passed: result.as_ref().map(|r| r.passed),
+            failed: result.as_ref().map(|r| r.failed),
+            output_dir: date.clone(),
+            results: result.as_ref().map(|_| results_rel),
+        });
+        if let Some(r) = result {
+            runs.push((date.as_str(), r));
+        }
+    }
+
+    BatchSummary {
+        batch_id: batch_id.to_string(),
+        start: range.start.format("%Y-%m-%d").to_string(),
+        end: range.end.format("%Y-%m-%d").to_string(),
+        first_failures: first_failures(&runs),
+        dates,
+    }
+}
+
+/// First failing date per test across `runs` (in chronological order).
+fn first_failures(runs: &[(&str, results::TestRunResult)]) -> Vec<FirstFailure> {
+    let mut last_passed: BTreeMap<String, String> = BTreeMap::new();
+    let mut failures: Vec<FirstFailure> = Vec::new();
+    for (date, result) in runs {
+        for test in &result.tests {
+            let key = format!("{}::{}", test.spec, test.scenario);
+            if failures.iter().any(|f| f.test == key) {
+                continue;
+            }
+            if test.passed {
+                last_passed.insert(key, date.to_string());
+            } else {
+                failures.push(FirstFailure {
+                    last_passed: last_passed.get(&key).cloned(),
+                    test: key,
+                    date: date.to_string(),
+                });
+            }
+        }
+    }
+    failures.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.test.cmp(&b.test)));
+    failures
+}
+
+/// Markdown table of per-date pass counts linking each run's results, then the
+/// first failing date per test.
+pub fn render_summary(summary: &BatchSummary) -> String {
+    let count = |v: Option<usize>| v.map_or("-".to_string(), |n| n.to_string());
+    let mut out = format!(
+        "# Batch {} ({} to {})\n\n| Date | Status | Passed | Failed | Total | Run |\n|---|---|---:|---:|---:|---|\n",
+        summary.batch_id, summary.start, summary.end
+    );
+    for d in &summary.dates {
+        let run = match &d.results {
+            Some(path) => format!("[results]({})", path),
+            None => format!("[{}/]({}/)", d.output_dir, d.output_dir),
+        };
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} |\n",
+            d.date,
+            d.status,
+            count(d.passed),
+            count(d.failed),
+            count(d.total),
+            run
+        ));
+    }
+    if !summary.first_failures.is_empty() {
+        out.push_str("\n## First failing date per test\n\n| Test | First failed | Last passed |\n|---|---|---|\n");
+        for f in &summary.first_failures {
+            out.push_str(&format!(
+                "| {} | {} | {} |\n",
+                f.test,
+                f.date,
+                f.last_passed.as_deref().unwrap_or("-")
+            ));
+        }
+    }
+    out
+}
+
+/// Write `batch-summary.json` and `batch-summary.md` to `output_dir`. Returns the JSON path.
+pub fn write_summary(output_dir: &Path, summary: &BatchSummary) -> Result<PathBuf> {
+    let json_path = output_dir.join("batch-summary.json");
+    std::fs::write(&json_path, serde_json::to_string_pretty(summary)?)
+        .with_context(|| format!("Failed to write {}", json_path.display()))?;
+    let md_path = output_dir.join("batch-summary.md");
+    std::fs::write(&md_path, render_summary(summary))
+        .with_context(|| format!("Failed to write {}", md_path.display()))?;
+    Ok(json_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_valid_date_range() {
+        let range = parse_date_range("2025-01-01:2025-01-03").unwrap();
+        assert_eq!(range.start.to_string(), "2025-01-01");
+        assert_eq!(range.end.to_string(), "2025-01-03");
+    }
+
+    #[test]
+    fn test_parse_invalid_format() {
+        let result = parse_date_range("invalid");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("START:END"));
+    }
+
+    #[test]
+    fn test_parse_invalid_start_date() {
+        let result = parse_date_range("not-a-date:2025-01-03");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Invalid start date"));
+    }
+
+    #[test]
+    fn test_parse_invalid_end_date() {
+        let result = parse_date_range("2025-01-01:not-a-date");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Invalid end date"));
+    }
+
+    #[test]
+    fn test_parse_end_before_start() {
+        let result = parse_date_range("2025-02-01:2025-01-01");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("must be on or after"));
+    }
+
+    #[test]
+    fn test_generate_dates() {
+        let range = parse_date_range("2025-01-01:2025-01-03").unwrap();
+        let dates = generate_dates(&range);
+        assert_eq!(dates.len(), 3);
+        assert_eq!(dates[0].to_string(), "2025-01-01");
+        assert_eq!(dates[1].to_string(), "2025-01-02");
+        assert_eq!(dates[2].to_string(), "2025-01-03");
+    }
+
+    #[test]
+    fn test_batch_progress() {
+        let mut progress = BatchProgress::new(3);
+        assert_eq!(progress.current, 0);
+        assert_eq!(progress.total, 3);
+
+        progress.advance("2025-01-01");
+        assert_eq!(progress.current, 1);
+        assert_eq!(progress.current_date, "2025-01-01");
+
+        progress.record_result(0);
+        assert_eq!(progress.passed, 1);
+
+        progress.record_result(1);
+        assert_eq!(progress.failed, 1);
+
+        progress.record_result(2);
+        assert_eq!(progress.errors, 1);
+    }
+
+    fn result(tests: &[(&str, bool)]) -> results::TestRunResult {
+        results::TestRunResult {
+            total: tests.len(),
+            passed: tests.iter().filter(|(_, p)| *p).count(),
+            failed: tests.iter().filter(|(_, p)| !*p).count(),
+            errors: 0,
+            duration_secs: 0.0,
+            source: None,
+            tests: tests
+                .iter()
+                .map(|(name, passed)| results::TestCaseResult {
+                    spec: "pipelines".into(),
+                    scenario: name.to_string(),
+                    passed: *passed,
+                    duration_secs: 0.0,
+                    error_message: None,
+                    components: Vec::new(),
+                    log_excerpts: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_first_failures() {
+        let runs = vec![
+            ("2025-01-01", result(&[("a", true), ("b", true)])),
+            ("2025-01-02", result(&[("a", true), ("b", false), ("c", false)])),
+            ("2025-01-03", result(&[("a", false), ("b", false)])),
+        ];
+        let failures = first_failures(&runs);
+        assert_eq!(
+            failures,
+            vec![
+                FirstFailure { test: "pipelines::b".into(), date: "2025-01-02".into(), last_passed: Some("2025-01-01".into()) },
+                FirstFailure { test: "pipelines::c".into(), date: "2025-01-02".into(), last_passed: None },
+                FirstFailure { test: "pipelines::a".into(), date: "2025-01-03".into(), last_passed: Some("2025-01-02".into()) },
+            ]
+        );
+    }
+}
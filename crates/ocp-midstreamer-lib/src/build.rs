@@ -0,0 +1,775 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::audit;
+use crate::component::ComponentSpec;
+use crate::config::{self, ComponentConfig, KoConfig};
+use crate::exec;
+use crate::gitops;
+use crate::gotoolchain::{self, GoOptions};
+use crate::ko;
+use crate::patch::{self, ComponentPatch};
+use crate::progress;
+use crate::registry;
+use crate::warnings;
+use crate::workspace;
+
+/// Where `run` builds component images (`--build-where`).
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq)]
+pub enum BuildWhere {
+    /// Clone and build here, pushing through the registry route
+    #[default]
+    Local,
+    /// Clone and build in the in-cluster Job (ko and Go from the CLI image),
+    /// pushing to the internal registry directly
+    Cluster,
+}
+
+/// Why `specs` cannot be built in the cluster: components built with docker
+/// (the Job has ko only) and patches from local files (the Job cannot read them).
+pub fn cluster_build_blockers(
+    specs: &[ComponentSpec],
+    configs: &HashMap<String, ComponentConfig>,
+    patches: &[ComponentPatch],
+) -> Vec<String> {
+    let mut blockers: Vec<String> = specs
+        .iter()
+        .filter(|s| configs.get(&s.name).is_some_and(|c| c.build_system.as_deref() == Some("docker")))
+        .map(|s| format!("{} is built with docker", s.name))
+        .collect();
+    blockers.extend(
+        patches
+            .iter()
+            .filter(|p| matches!(p.source, patch::PatchSource::File(_)))
+            .map(|p| format!("patch {} is a local file", p.to_arg())),
+    );
+    blockers
+}
+
+/// Memory one ko/go (or docker) build is assumed to need.
+const BUILD_MEMORY_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// CPUs one build is assumed to keep busy.
+const BUILD_CPUS: usize = 2;
+
+static BUILD_PARALLEL: OnceLock<Option<usize>> = OnceLock::new();
+
+/// Set the number of components built at once (--build-parallel); None sizes it
+/// from the host's CPUs and memory. Call once at startup.
+pub fn init(parallel: Option<usize>) {
+    let _ = BUILD_PARALLEL.set(parallel);
+}
+
+/// CLI args recreating this setting (for self-invocation).
+pub fn to_args() -> Vec<String> {
+    match BUILD_PARALLEL.get().copied().flatten() {
+        Some(n) => vec!["--build-parallel".to_string(), n.to_string()],
+        None => Vec::new(),
+    }
+}
+
+/// How many of `count` builds run at once: --build-parallel, else as many as
+/// the available CPUs and memory hold.
+pub fn build_slots(count: usize) -> usize {
+    let slots = match BUILD_PARALLEL.get().copied().flatten() {
+        Some(n) => n,
+        None => {
+            let cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+            slots_for(cpus, available_memory())
+        }
+    };
+    slots.clamp(1, count.max(1))
+}
+
+/// Builds that fit `cpus` and `memory` bytes (unknown memory only limits by CPU).
+fn slots_for(cpus: usize, memory: Option<u64>) -> usize {
+    let by_cpu = (cpus / BUILD_CPUS).max(1);
+    match memory {
+        Some(bytes) => by_cpu.min(((bytes / BUILD_MEMORY_BYTES) as usize).max(1)),
+        None => by_cpu,
+    }
+}
+
+/// Memory available to builds: MemAvailable, capped by the cgroup limit (in a
+/// container or in-cluster Job); `hw.memsize` on macOS.
+fn available_memory() -> Option<u64> {
+    if let Ok(meminfo) = fs::read_to_string("/proc/meminfo") {
+        let available = parse_meminfo(&meminfo)?;
+        let limit = fs::read_to_string("/sys/fs/cgroup/memory.max").ok().and_then(|max| {
+            let max: u64 = max.trim().parse().ok()?;
+            let current: u64 = fs::read_to_string("/sys/fs/cgroup/memory.current").ok()?.trim().parse().ok()?;
+            Some(max.saturating_sub(current))
+        });
+        return Some(limit.map_or(available, |l| l.min(available)));
+    }
+    let out = exec::run_cmd_unchecked("sysctl", &["-n", "hw.memsize"]).ok()?;
+    out.stdout.trim().parse().ok()
+}
+
+/// MemAvailable from /proc/meminfo, in bytes.
+fn parse_meminfo(meminfo: &str) -> Option<u64> {
+    let line = meminfo.lines().find(|l| l.starts_with("MemAvailable:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Build a component and return a HashMap of IMAGE_ env var -> SHA-pinned pullspec.
+///
+/// This function:
+/// 1. Loads the component config
+/// 2. Clones the repo with the specified git ref
+/// 3. Builds with ko to internal registry (capturing SHA refs), using the Go toolchain go.mod requires
+/// 4. Pushes to external registry using skopeo
+/// 5. Returns HashMap where key is IMAGE_ env var name, value is SHA pullspec
+pub fn run_build_with_refs(
+    component: &str,
+    external_registry: Option<&str>,
+    git_ref: &Option<String>,
+    go: &GoOptions,
+) -> Result<HashMap<String, String>> {
+    let config_path = config::default_config_path();
+    let config = config::load_config(&config_path)
+        .with_context(|| format!("Failed to load config from {}", config_path.display()))?;
+
+    let comp_cfg = config
+        .components
+        .get(component)
+        .ok_or_else(|| anyhow::anyhow!("Component '{}' not found in config", component))?;
+
+    // Create temp directory for clone
+    let temp_dir = workspace::scoped(&format!("{}-clone", component))
+        .with_context(|| "Failed to create temp directory")?;
+
+    // Clone with git ref
+    eprintln!("  Cloning {} (ref: {})...", comp_cfg.repo, git_ref.as_deref().unwrap_or("HEAD"));
+    gitops::clone(&comp_cfg.repo, temp_dir.path(), git_ref.as_deref(), None)?;
+
+    // Get internal registry for building (ko pushes here)
+    let internal_registry = registry::get_registry_route()
+        .with_context(|| "Failed to get internal registry route")?;
+    let internal_registry = format!("{}/tekton-upstream", internal_registry);
+
+    // Login to internal registry
+    let registry_host = internal_registry.split('/').next().unwrap_or(&internal_registry);
+    registry::registry_login(registry_host)?;
+
+    let image_refs: Vec<(String, String)> = match comp_cfg.build_system.as_deref() {
+        Some("docker") => {
+            // Docker build to internal registry
+            let built = docker_build(temp_dir.path(), &internal_registry, &comp_cfg.images, None)?;
+            // Get digests
+            let mut refs = Vec::new();
+            for image_name in built {
+                let tag = format!("{}/{}", internal_registry, image_name);
+                let inspect = exec::run_cmd(
+                    "skopeo",
+                    &["inspect", "--format", "{{.Digest}}", &format!("docker://{}", tag), "--tls-verify=false"],
+                );
+                let pullspec = match inspect {
+                    Ok(r) if r.exit_code == 0 => {
+                        format!("{}@{}", tag.split(':').next().unwrap_or(&tag), r.stdout.trim())
+                    }
+                    _ => tag,
+                };
+                refs.push((image_name, pullspec));
+            }
+            refs
+        }
+        _ => {
+            // ko build with --image-refs to internal registry
+            let image_refs_file = temp_dir.path().join(".ko-image-refs");
+            let image_refs_path_str = image_refs_file.to_string_lossy().to_string();
+
+            let ko_cfg = comp_cfg.ko.for_build(component, git_ref.as_deref());
+            ko::write_ko_yaml(temp_dir.path(), &ko_cfg, &comp_cfg.import_paths)?;
+            let args = ko::build_args(&ko_cfg, &image_refs_path_str, &comp_cfg.import_paths);
+
+            let go_env = gotoolchain::prepare(temp_dir.path(), &comp_cfg.import_paths, go)?;
+
+            eprintln!("  Building to internal registry: {}", internal_registry);
+            let status = audit::status(
+                Command::new("ko")
+                    .args(&args)
+                    .env("KO_DOCKER_REPO", &internal_registry)
+                    .envs(go_env)
+                    .current_dir(temp_dir.path())
+                    .stdout(Stdio::inherit())
+                    .stderr(Stdio::inherit()),
+            )
+            .with_context(|| "failed to execute ko")?;
+
+            if !status.success() {
+                anyhow::bail!("ko build failed for {}", component);
+            }
+
+            // Collect refs from ko output
+            if image_refs_file.exists() {
+                registry::collect_image_refs(&image_refs_file)?
+            } else {
+                anyhow::bail!("ko did not produce image refs file");
+            }
+        }
+    };
+
+    // Push to external registry if specified
+    let final_refs: Vec<(String, String)> = if let Some(ext_reg) = external_registry {
+        eprintln!("  Pushing {} images to external registry: {}", image_refs.len(), ext_reg);
+        let mut pushed = Vec::new();
+        let mut stats = registry::PushStats::default();
+        for (short_name, sha_ref) in image_refs {
+            let push = registry::push_to_external(&sha_ref, ext_reg)?;
+            stats.add(push.stats);
+            pushed.push((short_name, push.pinned));
+        }
+        eprintln!("  Push summary for {}: {}", component, stats.summary());
+        pushed
+    } else {
+        image_refs
+    };
+
+    // Map short names to IMAGE_ env var names
+    let mut result: HashMap<String, String> = HashMap::new();
+    for (short_name, pullspec) in final_refs {
+        // Find the IMAGE_ env var for this short name
+        if let Some(env_var) = comp_cfg.images.get(&short_name) {
+            result.insert(env_var.clone(), pullspec);
+        } else {
+            warnings::warn(format!("No IMAGE_ mapping for {}", short_name));
+        }
+    }
+
+    Ok(result)
+}
+
+/// stdout/stderr for a build tool: appended to `log` when given, otherwise the console.
+fn build_stdio(log: Option<&Path>) -> Result<(Stdio, Stdio)> {
+    match log {
+        Some(path) => {
+            let file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("Failed to open build log {}", path.display()))?;
+            let err = file.try_clone().context("Failed to duplicate build log handle")?;
+            Ok((Stdio::from(file), Stdio::from(err)))
+        }
+        None => Ok((Stdio::inherit(), Stdio::inherit())),
+    }
+}
+
+/// Build images using ko, streaming output to the console or to `log`.
+///
+/// Sets `KO_DOCKER_REPO`, `GOFLAGS` (`-mod=vendor` for vendored trees, otherwise
+/// module mode, added to the GOFLAGS already set), and `GOTOOLCHAIN` when go.mod
+/// needs a newer Go than the host has (or `go.version` pins one).
+/// Uses `--base-import-paths` so image names match the last path segment.
+/// Runs ko from `source_dir` with `current_dir`.
+/// Returns the list of expected image names derived from import paths.
+pub fn ko_build(
+    source_dir: &Path,
+    registry: &str,
+    import_paths: &[String],
+    ko: &KoConfig,
+    go: &GoOptions,
+    log: Option<&Path>,
+) -> Result<Vec<String>> {
+    ko_build_with_external(source_dir, registry, import_paths, None, ko, go, log)
+}
+
+/// Build images using ko, optionally pushing to an external registry.
+///
+/// When `external_registry` is Some, images are copied from the build registry
+/// to the external registry using skopeo, and SHA-pinned external pullspecs are returned.
+/// When None, behaves like the original ko_build (returns image names only).
+pub fn ko_build_with_external(
+    source_dir: &Path,
+    registry: &str,
+    import_paths: &[String],
+    external_registry: Option<&str>,
+    ko_cfg: &KoConfig,
+    go: &GoOptions,
+    log: Option<&Path>,
+) -> Result<Vec<String>> {
+    // Create a temp file for --image-refs output
+    let image_refs_file = source_dir.join(".ko-image-refs");
+
+    let image_refs_path_str = image_refs_file.to_string_lossy().to_string();
+    ko::write_ko_yaml(source_dir, ko_cfg, import_paths)?;
+    let args = ko::build_args(ko_cfg, &image_refs_path_str, import_paths);
+
+    let docker_config = std::env::var("DOCKER_CONFIG")
+        .unwrap_or_else(|_| String::new());
+    let mut envs: Vec<(&str, String)> = vec![("KO_DOCKER_REPO", registry.to_string())];
+    if !docker_config.is_empty() {
+        envs.push(("DOCKER_CONFIG", docker_config));
+    }
+    envs.extend(gotoolchain::prepare(source_dir, import_paths, go)?);
+
+    let (stdout, stderr) = build_stdio(log)?;
+    let status = audit::status(
+        Command::new("ko")
+            .args(&args)
+            .envs(envs.iter().cloned())
+            .current_dir(source_dir)
+            .stdout(stdout)
+            .stderr(stderr),
+    )
+    .with_context(|| "failed to execute ko")?;
+
+    let code = status.code().unwrap_or(-1);
+    if code != 0 {
+        match log {
+            Some(path) => anyhow::bail!("ko build failed with exit code {} (see {})", code, path.display()),
+            None => anyhow::bail!("ko build failed with exit code {}", code),
+        }
+    }
+
+    // Collect SHA-pinned image refs from ko output
+    let image_refs = if image_refs_file.exists() {
+        registry::collect_image_refs(&image_refs_file)?
+    } else {
+        // Fallback: derive names from import paths (no SHA info)
+        import_paths
+            .iter()
+            .filter_map(|p| p.rsplit('/').next())
+            .map(|s| (s.to_string(), format!("{}/{}", registry, s)))
+            .collect()
+    };
+
+    // If external registry is specified, push each image there
+    if let Some(ext_registry) = external_registry {
+        eprintln!("Pushing {} images to external registry: {}", image_refs.len(), ext_registry);
+        let mut external_pullspecs = Vec::new();
+        let mut stats = registry::PushStats::default();
+        for (_short_name, sha_ref) in &image_refs {
+            let push = registry::push_to_external(sha_ref, ext_registry)?;
+            stats.add(push.stats);
+            external_pullspecs.push(push.pinned);
+        }
+        eprintln!("Push summary: {}", stats.summary());
+        return Ok(external_pullspecs);
+    }
+
+    // Return image names (original behavior)
+    let image_names: Vec<String> = image_refs
+        .iter()
+        .map(|(short_name, _)| short_name.clone())
+        .collect();
+
+    Ok(image_names)
+}
+
+/// Build images using docker/podman for non-ko components (e.g. console-plugin).
+///
+/// Tries podman first, falls back to docker.
+/// Builds and pushes each image defined in the config images map, with output
+/// going to `log` when given.
+pub fn docker_build(
+    source_dir: &Path,
+    registry: &str,
+    images: &HashMap<String, String>,
+    log: Option<&Path>,
+) -> Result<Vec<String>> {
+    let mut built = Vec::new();
+    for image_name in images.keys() {
+        let tag = format!("{}/{}", registry, image_name);
+        // Try podman first, fall back to docker
+        let builder = if audit::output(Command::new("podman").arg("--version")).is_ok() {
+            "podman"
+        } else {
+            "docker"
+        };
+        let (stdout, stderr) = build_stdio(log)?;
+        let status = audit::status(
+            Command::new(builder)
+                .args(["build", "-t", &tag, "."])
+                .current_dir(source_dir)
+                .stdout(stdout)
+                .stderr(stderr),
+        )
+        .with_context(|| format!("failed to execute {builder} build"))?;
+        if !status.success() {
+            anyhow::bail!("{builder} build failed for {image_name}");
+        }
+        // Push
+        let (stdout, stderr) = build_stdio(log)?;
+        let push_status = audit::status(
+            Command::new(builder)
+                .args(["push", &tag, "--tls-verify=false"])
+                .stdout(stdout)
+                .stderr(stderr),
+        )
+        .with_context(|| format!("failed to push {image_name}"))?;
+        if !push_status.success() {
+            anyhow::bail!("{builder} push failed for {image_name}");
+        }
+        built.push(image_name.clone());
+    }
+    Ok(built)
+}
+
+/// Time spent in one build stage (clone, patch, ko/docker).
+#[derive(Debug, Clone, Serialize)]
+pub struct StageTiming {
+    pub stage: String,
+    pub seconds: f64,
+}
+
+/// Outcome of one component's parallel build.
+#[derive(Debug)]
+pub struct ComponentBuild {
+    pub component: String,
+    pub git_ref: Option<String>,
+    pub result: Result<Vec<String>>,
+    pub stages: Vec<StageTiming>,
+    /// Time spent waiting for a build slot (not part of the stages)
+    pub queued_seconds: f64,
+    /// Captured ko/docker output (`output-dir/build-logs/<component>.log`)
+    pub log: PathBuf,
+}
+
+impl ComponentBuild {
+    pub fn total_seconds(&self) -> f64 {
+        self.stages.iter().map(|s| s.seconds).sum()
+    }
+
+    fn stage_seconds(&self, stage: &str) -> Option<f64> {
+        self.stages.iter().find(|s| s.stage == stage).map(|s| s.seconds)
+    }
+}
+
+/// One component's entry in build-manifest.json.
+#[derive(Debug, Serialize)]
+struct ManifestEntry<'a> {
+    component: &'a str,
+    git_ref: Option<&'a str>,
+    success: bool,
+    images: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    log: String,
+    stages: &'a [StageTiming],
+    queued_seconds: f64,
+    total_seconds: f64,
+}
+
+/// Write `output-dir/build-manifest.json` with images, logs and stage timings per component.
+pub fn write_build_manifest(output_dir: &Path, builds: &[ComponentBuild]) -> Result<PathBuf> {
+    let entries: Vec<ManifestEntry> = builds
+        .iter()
+        .map(|b| ManifestEntry {
+            component: &b.component,
+            git_ref: b.git_ref.as_deref(),
+            success: b.result.is_ok(),
+            images: b.result.as_deref().unwrap_or(&[]),
+            error: b.result.as_ref().err().map(|e| format!("{e:#}")),
+            log: b.log.display().to_string(),
+            stages: &b.stages,
+            queued_seconds: b.queued_seconds,
+            total_seconds: b.total_seconds(),
+        })
+        .collect();
+    let path = output_dir.join("build-manifest.json");
+    fs::create_dir_all(output_dir)
+        .with_context(|| format!("Failed to create {}", output_dir.display()))?;
+    let json = serde_json::to_string_pretty(&serde_json::json!({ "components": entries }))?;
+    fs::write(&path, json).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Print per-component stage times, slowest build first.
+pub fn print_build_summary(builds: &[ComponentBuild]) {
+    let mut sorted: Vec<&ComponentBuild> = builds.iter().collect();
+    sorted.sort_by(|a, b| b.total_seconds().total_cmp(&a.total_seconds()));
+
+    let cell = |secs: Option<f64>| secs.map(|s| format!("{:.1}s", s)).unwrap_or_else(|| "-".to_string());
+    eprintln!("\nBuild time summary:");
+    eprintln!(
+        "  {:<16} {:>9} {:>9} {:>9} {:>9} {:>9}  STATUS",
+        "COMPONENT", "QUEUED", "CLONE", "PATCH", "BUILD", "TOTAL"
+    );
+    for b in sorted {
+        let build = b.stage_seconds("ko").or_else(|| b.stage_seconds("docker"));
+        eprintln!(
+            "  {:<16} {:>9} {:>9} {:>9} {:>9} {:>9}  {}",
+            b.component,
+            cell(Some(b.queued_seconds)),
+            cell(b.stage_seconds("clone")),
+            cell(b.stage_seconds("patch")),
+            cell(build),
+            cell(Some(b.total_seconds())),
+            if b.result.is_ok() { "ok" } else { "FAILED" }
+        );
+    }
+}
+
+/// Everything a spawned build task needs, owned so it can move into the task.
+struct BuildJob {
+    component: String,
+    repo_url: String,
+    git_ref: Option<String>,
+    import_paths: Vec<String>,
+    build_system: Option<String>,
+    ko: KoConfig,
+    images: HashMap<String, String>,
+    registry: String,
+    go: GoOptions,
+    patches: Vec<ComponentPatch>,
+    log: PathBuf,
+}
+
+/// Run a blocking step on the blocking pool, recording how long it took.
+async fn timed_stage<T: Send + 'static>(
+    stages: &mut Vec<StageTiming>,
+    stage: &str,
+    f: impl FnOnce() -> Result<T> + Send + 'static,
+) -> Result<T> {
+    let start = Instant::now();
+    let result = tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| anyhow::anyhow!("join error: {e}"));
+    stages.push(StageTiming {
+        stage: stage.to_string(),
+        seconds: start.elapsed().as_secs_f64(),
+    });
+    result?
+}
+
+/// Clone, patch and build one component, logging build tool output to `job.log`.
+async fn run_build_job(job: BuildJob, pb: &progress::Stage, stages: &mut Vec<StageTiming>) -> Result<Vec<String>> {
+    let comp_name = job.component.clone();
+    let temp_dir = workspace::scoped(&format!("{comp_name}-clone")).context("temp dir")?;
+
+    pb.set_message(format!("{comp_name}: cloning..."));
+    let clone_dest = temp_dir.path().to_path_buf();
+    let clone_repo = job.repo_url.clone();
+    let clone_ref = job.git_ref.clone();
+    timed_stage(stages, "clone", move || {
+        gitops::clone(&clone_repo, &clone_dest, clone_ref.as_deref(), None)
+    })
+    .await?;
+
+    if !job.patches.is_empty() {
+        pb.set_message(format!("{comp_name}: applying {} patch(es)...", job.patches.len()));
+        let patch_dir = temp_dir.path().to_path_buf();
+        let patch_repo = job.repo_url.clone();
+        let patches = job.patches;
+        timed_stage(stages, "patch", move || {
+            let refs: Vec<&ComponentPatch> = patches.iter().collect();
+            patch::apply_patches(&patch_repo, &patch_dir, &refs)
+        })
+        .await?;
+    }
+
+    pb.set_message(format!("{comp_name}: building..."));
+    let build_dir = temp_dir.path().to_path_buf();
+    let stage = match job.build_system.as_deref() {
+        Some("docker") => "docker",
+        _ => "ko",
+    };
+    timed_stage(stages, stage, move || match job.build_system.as_deref() {
+        Some("docker") => docker_build(&build_dir, &job.registry, &job.images, Some(&job.log)),
+        _ => ko_build(&build_dir, &job.registry, &job.import_paths, &job.ko, &job.go, Some(&job.log)),
+    })
+    .await
+}
+
+/// Build multiple components in parallel using tokio JoinSet.
+///
+/// At most `build_slots` builds run at once; the others wait for a slot, their
+/// wait recorded apart from the build stages. Each component gets its own spinner
+/// via MultiProgress, and its ko/docker output goes to
+/// `output_dir/build-logs/<component>.log` instead of the console.
+/// Failed builds do not block other builds.
+pub async fn build_components_parallel(
+    specs: &[ComponentSpec],
+    configs: &HashMap<String, ComponentConfig>,
+    registry: &str,
+    patches: &[ComponentPatch],
+    go: &GoOptions,
+    output_dir: &Path,
+) -> Vec<ComponentBuild> {
+    let mp = progress::multi_progress();
+    let mut set = JoinSet::new();
+    let slots = build_slots(specs.len());
+    if slots < specs.len() {
+        eprintln!("  Building {} components at a time (--build-parallel to change)", slots);
+    }
+    let semaphore = Arc::new(Semaphore::new(slots));
+    let log_dir = output_dir.join("build-logs");
+    if let Err(e) = fs::create_dir_all(&log_dir) {
+        warnings::warn(format!("Failed to create {}: {e}", log_dir.display()));
+    }
+
+    for spec in specs {
+        let comp_name = spec.name.clone();
+        let git_ref = spec.git_ref.clone();
+        let log = log_dir.join(format!("{comp_name}.log"));
+        let pb = progress::component_spinner(&mp, &comp_name);
+
+        let comp_cfg = match configs.get(&comp_name) {
+            Some(c) => c,
+            None => {
+                pb.finish_with_message(format!("{comp_name}: FAILED - not in config"));
+                set.spawn(async move {
+                    ComponentBuild {
+                        component: comp_name,
+                        git_ref,
+                        result: Err(anyhow::anyhow!("component not in config")),
+                        stages: Vec::new(),
+                        queued_seconds: 0.0,
+                        log,
+                    }
+                });
+                continue;
+            }
+        };
+
+        let job = BuildJob {
+            component: comp_name.clone(),
+            repo_url: comp_cfg.repo.clone(),
+            git_ref: git_ref.clone(),
+            import_paths: comp_cfg.import_paths.clone(),
+            build_system: comp_cfg.build_system.clone(),
+            ko: comp_cfg.ko.for_build(&comp_name, git_ref.as_deref()),
+            images: comp_cfg.images.clone(),
+            registry: registry.to_string(),
+            go: go.clone(),
+            patches: patch::for_component(patches, &comp_name).into_iter().cloned().collect(),
+            log: log.clone(),
+        };
+
+        let semaphore = Arc::clone(&semaphore);
+        set.spawn(async move {
+            pb.set_message(format!("{comp_name}: queued"));
+            let queued = Instant::now();
+            let _permit = semaphore.acquire_owned().await;
+            let queued_seconds = queued.elapsed().as_secs_f64();
+            let mut stages = Vec::new();
+            let result = run_build_job(job, &pb, &mut stages).await;
+            match &result {
+                Ok(images) => pb.finish_with_message(format!("{comp_name}: done ({} images)", images.len())),
+                Err(e) => pb.finish_with_message(format!("{comp_name}: FAILED - {e}")),
+            }
+            ComponentBuild {
+                component: comp_name,
+                git_ref,
+                result,
+                stages,
+                queued_seconds,
+                log,
+            }
+        });
+    }
+
+    let mut results = Vec::new();
+    while let Some(res) = set.join_next().await {
+        match res {
+            Ok(build) => results.push(build),
+            Err(e) => results.push(ComponentBuild {
+                component: "unknown".to_string(),
+                git_ref: None,
+                result: Err(anyhow::anyhow!("task panic: {e}")),
+                stages: Vec::new(),
+                queued_seconds: 0.0,
+                log: PathBuf::new(),
+            }),
+        }
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_build_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let builds = vec![
+            ComponentBuild {
+                component: "pipeline".to_string(),
+                git_ref: Some("v0.65.0".to_string()),
+                result: Ok(vec!["controller".to_string()]),
+                stages: vec![
+                    StageTiming { stage: "clone".to_string(), seconds: 2.0 },
+                    StageTiming { stage: "ko".to_string(), seconds: 40.5 },
+                ],
+                queued_seconds: 12.0,
+                log: dir.path().join("build-logs/pipeline.log"),
+            },
+            ComponentBuild {
+                component: "triggers".to_string(),
+                git_ref: None,
+                result: Err(anyhow::anyhow!("ko build failed with exit code 1")),
+                stages: vec![StageTiming { stage: "clone".to_string(), seconds: 1.0 }],
+                queued_seconds: 0.0,
+                log: dir.path().join("build-logs/triggers.log"),
+            },
+        ];
+
+        let path = write_build_manifest(dir.path(), &builds).unwrap();
+        let v: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        let comps = v["components"].as_array().unwrap();
+        assert_eq!(comps[0]["success"], true);
+        assert_eq!(comps[0]["images"][0], "controller");
+        assert_eq!(comps[0]["total_seconds"], 42.5);
+        assert_eq!(comps[0]["queued_seconds"], 12.0);
+        assert_eq!(comps[0]["stages"][1]["stage"], "ko");
+        assert_eq!(comps[1]["success"], false);
+        assert_eq!(comps[1]["error"], "ko build failed with exit code 1");
+    }
+
+    #[test]
+    fn test_slots_for() {
+        const GIB: u64 = 1024 * 1024 * 1024;
+        assert_eq!(slots_for(16, Some(64 * GIB)), 8);
+        assert_eq!(slots_for(16, Some(7 * GIB)), 3);
+        assert_eq!(slots_for(8, None), 4);
+        assert_eq!(slots_for(1, Some(GIB)), 1);
+    }
+
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = "MemTotal:       16303412 kB\nMemFree:         1234567 kB\nMemAvailable:    8388608 kB\n";
+        assert_eq!(parse_meminfo(meminfo), Some(8 * 1024 * 1024 * 1024));
+        assert_eq!(parse_meminfo("MemTotal: 1 kB\n"), None);
+    }
+
+    #[test]
+    fn test_cluster_build_blockers() {
+        let configs: HashMap<String, ComponentConfig> = toml::from_str(
+            r#"
+            [pipeline]
+            repo = "p"
+            images = {}
+
+            [console-plugin]
+            repo = "cp"
+            images = {}
+            build_system = "docker"
+            "#,
+        )
+        .unwrap();
+        let specs = |names: &[&str]| -> Vec<ComponentSpec> {
+            names.iter().map(|n| ComponentSpec { name: n.to_string(), git_ref: None, as_of_date: None }).collect()
+        };
+        let pr = patch::parse_patch_spec("pipeline=pr/123").unwrap();
+        assert!(cluster_build_blockers(&specs(&["pipeline"]), &configs, std::slice::from_ref(&pr)).is_empty());
+
+        let file = ComponentPatch { component: "pipeline".to_string(), source: patch::PatchSource::File(PathBuf::from("fix.diff")) };
+        assert_eq!(
+            cluster_build_blockers(&specs(&["pipeline", "console-plugin"]), &configs, &[pr, file]),
+            vec!["console-plugin is built with docker".to_string(), "patch pipeline=fix.diff is a local file".to_string()]
+        );
+    }
+}
@@ -0,0 +1,433 @@
+use anyhow::{Context, Result, bail};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::exec;
+use crate::gitops;
+use crate::warnings;
+use crate::workspace::{self, ScopedDir};
+
+pub const OPERATOR_REPO: &str = "https://github.com/openshift-pipelines/operator.git";
+
+/// OLM package of the operator, and the channel and CSV the FBC index serves it with.
+pub const OPERATOR_PACKAGE: &str = "openshift-pipelines-operator-rh";
+pub const INDEX_CHANNEL: &str = "upstream-testing";
+pub const INDEX_CSV: &str = "openshift-pipelines-operator-rh.v99.0.0-upstream";
+
+/// Tool that builds and pushes the bundle and FBC index images.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq)]
+pub enum BundleBuilder {
+    /// buildah if it is on PATH, else an OpenShift binary build
+    #[default]
+    Auto,
+    /// Local buildah (Linux; rootless or privileged)
+    Buildah,
+    /// In-cluster BuildConfig (docker strategy) pushing to the registry; needs only oc
+    Openshift,
+}
+
+impl BundleBuilder {
+    /// Resolve `Auto` using `available` to test whether a tool is on PATH.
+    pub fn resolve(self, available: impl Fn(&str) -> bool) -> BundleBuilder {
+        match self {
+            BundleBuilder::Auto if available("buildah") => BundleBuilder::Buildah,
+            BundleBuilder::Auto => BundleBuilder::Openshift,
+            other => other,
+        }
+    }
+
+    /// CLI value of this builder (for self-invocation).
+    pub fn as_arg(self) -> &'static str {
+        match self {
+            BundleBuilder::Auto => "auto",
+            BundleBuilder::Buildah => "buildah",
+            BundleBuilder::Openshift => "openshift",
+        }
+    }
+}
+
+/// Namespace of the bundle BuildConfig and its push Secret.
+const BUILD_NAMESPACE: &str = "openshift-pipelines";
+
+/// BuildConfig used for `--bundle-builder openshift` (bundle and index builds).
+pub const BUNDLE_BUILD_CONFIG: &str = "streamstress-bundle";
+
+/// dockerconfigjson Secret the bundle BuildConfig pushes to the registry with.
+pub const BUNDLE_PUSH_SECRET: &str = "streamstress-bundle-push";
+
+/// Clone the openshift-pipelines/operator repo to a workspace directory.
+pub fn clone_operator_repo(branch: &str) -> Result<ScopedDir> {
+    let temp_dir = workspace::scoped("osp-operator")?;
+
+    eprintln!("Cloning operator repo (branch: {})...", branch);
+    gitops::clone(OPERATOR_REPO, temp_dir.path(), Some(branch), None).context("Failed to clone operator repo")?;
+
+    Ok(temp_dir)
+}
+
+/// Patch the CSV file with upstream image references.
+/// image_map: key = IMAGE_ env var name (e.g., "IMAGE_PIPELINES_CONTROLLER"), value = SHA-pinned pullspec
+pub fn patch_csv(operator_dir: &Path, image_map: &HashMap<String, String>) -> Result<()> {
+    let csv_path = operator_dir
+        .join(".konflux/olm-catalog/bundle/manifests/openshift-pipelines-operator-rh.clusterserviceversion.yaml");
+
+    if !csv_path.exists() {
+        bail!("CSV file not found at {}", csv_path.display());
+    }
+
+    eprintln!("Patching CSV with {} upstream images...", image_map.len());
+
+    let content = fs::read_to_string(&csv_path)
+        .context("Failed to read CSV file")?;
+
+    let mut doc: serde_yaml::Value = serde_yaml::from_str(&content)
+        .context("Failed to parse CSV YAML")?;
+
+    // Navigate to spec.install.spec.deployments[*].spec.template.spec.containers[*].env
+    patch_env_vars_recursive(&mut doc, image_map);
+
+    let patched = serde_yaml::to_string(&doc)?;
+    fs::write(&csv_path, patched)?;
+
+    eprintln!("  Patched CSV at {}", csv_path.display());
+    Ok(())
+}
+
+fn patch_env_vars_recursive(value: &mut serde_yaml::Value, image_map: &HashMap<String, String>) {
+    match value {
+        serde_yaml::Value::Mapping(map) => {
+            // Check if this is an env var entry with name/value
+            let name_key = serde_yaml::Value::String("name".to_string());
+            let value_key = serde_yaml::Value::String("value".to_string());
+
+            // First, check if we need to patch this entry
+            let should_patch = if let Some(serde_yaml::Value::String(name)) = map.get(&name_key) {
+                if name.starts_with("IMAGE_") {
+                    image_map.get(name).cloned()
+                } else {
+                    None
+                }
+            } else {
+                None
+            };
+
+            // Apply the patch if needed
+            if let Some(new_val) = should_patch && let Some(val) = map.get_mut(&value_key) {
+                *val = serde_yaml::Value::String(new_val);
+            }
+
+            // Recurse into all values
+            for (_, v) in map.iter_mut() {
+                patch_env_vars_recursive(v, image_map);
+            }
+        }
+        serde_yaml::Value::Sequence(seq) => {
+            for item in seq.iter_mut() {
+                patch_env_vars_recursive(item, image_map);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Build the operator bundle image and push to registry.
+/// Returns the SHA-pinned pullspec.
+pub fn build_bundle_image(operator_dir: &Path, registry: &str, tag: &str, builder: BundleBuilder) -> Result<String> {
+    let bundle_dir = operator_dir.join(".konflux/olm-catalog/bundle");
+    let dockerfile = bundle_dir.join("bundle.Dockerfile");
+
+    if !dockerfile.exists() {
+        bail!("Bundle Dockerfile not found at {}", dockerfile.display());
+    }
+
+    let image_ref = format!("{}/osp-upstream-bundle:{}", registry, tag);
+    eprintln!("Building bundle image: {}", image_ref);
+
+    if builder.resolve(|tool| which::which(tool).is_ok()) == BundleBuilder::Openshift {
+        let sha_ref = openshift_build(&bundle_dir, "bundle.Dockerfile", registry, &image_ref)?;
+        validate_bundle(&sha_ref);
+        eprintln!("  Bundle pushed: {}", sha_ref);
+        return Ok(sha_ref);
+    }
+
+    // Build with buildah
+    let result = exec::run_cmd(
+        "buildah",
+        &[
+            "build",
+            "-f", dockerfile.to_str().unwrap(),
+            "-t", &image_ref,
+            bundle_dir.to_str().unwrap(),
+        ],
+    )?;
+
+    if result.exit_code != 0 {
+        bail!("Failed to build bundle image: {}", result.stderr);
+    }
+
+    validate_bundle(&image_ref);
+
+    // Push
+    eprintln!("Pushing bundle image...");
+    let push_result = exec::run_cmd(
+        "buildah",
+        &["push", &image_ref],
+    )?;
+
+    if push_result.exit_code != 0 {
+        bail!("Failed to push bundle image: {}", push_result.stderr);
+    }
+
+    // Get digest
+    let digest = get_image_digest(&image_ref)?;
+    let sha_ref = format!("{}@{}", image_ref.split(':').next().unwrap(), digest);
+
+    eprintln!("  Bundle pushed: {}", sha_ref);
+    Ok(sha_ref)
+}
+
+/// Check that opm can render the bundle image; a failure only warns.
+fn validate_bundle(image_ref: &str) {
+    eprintln!("Validating bundle...");
+    let validate_result = exec::run_cmd(
+        "opm",
+        &["render", image_ref],
+    );
+
+    if let Ok(r) = validate_result && r.exit_code != 0 {
+        warnings::warn("Bundle validation with opm render failed, continuing anyway");
+    }
+}
+
+/// Build the FBC index image containing the bundle.
+/// Returns the SHA-pinned pullspec.
+pub fn build_index_image(bundle_pullspec: &str, registry: &str, tag: &str, builder: BundleBuilder) -> Result<String> {
+    let workdir = workspace::scoped("fbc-index")?;
+    let temp_dir = workdir.path();
+
+    let catalog_dir = temp_dir.join("catalog");
+    fs::create_dir_all(&catalog_dir)?;
+
+    eprintln!("Rendering bundle to FBC catalog...");
+
+    // Render bundle to catalog.yaml
+    let render_result = exec::run_cmd(
+        "opm",
+        &["render", bundle_pullspec, "-o", "yaml"],
+    )?;
+
+    if render_result.exit_code != 0 {
+        bail!("Failed to render bundle: {}", render_result.stderr);
+    }
+
+    // Write catalog.yaml with rendered content + channel entry
+    let mut catalog_content = render_result.stdout.clone();
+
+    // Add channel entry
+    catalog_content.push_str("\n---\n");
+    catalog_content.push_str("schema: olm.channel\n");
+    catalog_content.push_str(&format!("package: {}\n", OPERATOR_PACKAGE));
+    catalog_content.push_str(&format!("name: {}\n", INDEX_CHANNEL));
+    catalog_content.push_str("entries:\n");
+    catalog_content.push_str(&format!("  - name: {}\n", INDEX_CSV));
+
+    fs::write(catalog_dir.join("catalog.yaml"), &catalog_content)?;
+
+    // Validate catalog
+    eprintln!("Validating FBC catalog...");
+    let validate_result = exec::run_cmd(
+        "opm",
+        &["validate", catalog_dir.to_str().unwrap()],
+    )?;
+
+    if validate_result.exit_code != 0 {
+        bail!("FBC catalog validation failed: {}", validate_result.stderr);
+    }
+
+    // Create Dockerfile
+    let dockerfile_content = r#"FROM registry.redhat.io/openshift4/ose-operator-registry:v4.17
+COPY catalog /configs
+LABEL operators.operatorframework.io.index.configs.v1=/configs
+"#;
+    fs::write(temp_dir.join("Dockerfile"), dockerfile_content)?;
+
+    let image_ref = format!("{}/osp-upstream-index:{}", registry, tag);
+    eprintln!("Building FBC index image: {}", image_ref);
+
+    if builder.resolve(|tool| which::which(tool).is_ok()) == BundleBuilder::Openshift {
+        let sha_ref = openshift_build(temp_dir, "Dockerfile", registry, &image_ref)?;
+        eprintln!("  Index pushed: {}", sha_ref);
+        return Ok(sha_ref);
+    }
+
+    // Build with buildah
+    let result = exec::run_cmd(
+        "buildah",
+        &[
+            "build",
+            "-f", temp_dir.join("Dockerfile").to_str().unwrap(),
+            "-t", &image_ref,
+            temp_dir.to_str().unwrap(),
+        ],
+    )?;
+
+    if result.exit_code != 0 {
+        bail!("Failed to build index image: {}", result.stderr);
+    }
+
+    // Push
+    eprintln!("Pushing FBC index image...");
+    let push_result = exec::run_cmd(
+        "buildah",
+        &["push", &image_ref],
+    )?;
+
+    if push_result.exit_code != 0 {
+        bail!("Failed to push index image: {}", push_result.stderr);
+    }
+
+    // Get digest
+    let digest = get_image_digest(&image_ref)?;
+    let sha_ref = format!("{}@{}", image_ref.split(':').next().unwrap(), digest);
+
+    eprintln!("  Index pushed: {}", sha_ref);
+    Ok(sha_ref)
+}
+
+/// Build `context_dir` with the bundle BuildConfig and push it to `image_ref`
+/// (a tag under `registry`) with the local credentials for the registry.
+/// Returns the SHA-pinned pullspec, from the digest the build reports.
+fn openshift_build(context_dir: &Path, dockerfile: &str, registry: &str, image_ref: &str) -> Result<String> {
+    let ns = BUILD_NAMESPACE;
+    let exists = exec::run_cmd_unchecked_timeout(
+        "oc",
+        &["get", "buildconfig", BUNDLE_BUILD_CONFIG, "-n", ns],
+        exec::API_TIMEOUT,
+    )?;
+    if exists.exit_code != 0 {
+        eprintln!("Creating BuildConfig {}/{}...", ns, BUNDLE_BUILD_CONFIG);
+        exec::run_cmd(
+            "oc",
+            &["new-build", "--binary", "--strategy=docker", "--name", BUNDLE_BUILD_CONFIG, "-n", ns],
+        )
+        .context("Failed to create bundle BuildConfig")?;
+    }
+
+    let docker_config = crate::deploy::pullsecret::resolve_docker_config(registry, None)?;
+    if let Some(ref config) = docker_config {
+        apply_push_secret(config)?;
+    } else {
+        warnings::warn(format!("No local credentials for {}; the in-cluster build pushes without a push secret", registry));
+    }
+
+    let mut output = serde_json::json!({"to": {"kind": "DockerImage", "name": image_ref}});
+    if docker_config.is_some() {
+        output["pushSecret"] = serde_json::json!({"name": BUNDLE_PUSH_SECRET});
+    }
+    let patch = serde_json::json!({
+        "spec": {
+            "strategy": {"dockerStrategy": {"dockerfilePath": dockerfile}},
+            "output": output
+        }
+    })
+    .to_string();
+    exec::run_cmd(
+        "oc",
+        &["patch", "buildconfig", BUNDLE_BUILD_CONFIG, "-n", ns, "--type=merge", "-p", &patch],
+    )
+    .context("Failed to point bundle BuildConfig at the image")?;
+
+    let from_dir = format!("--from-dir={}", context_dir.display());
+    exec::run_cmd_streaming(
+        "oc",
+        &["start-build", BUNDLE_BUILD_CONFIG, "-n", ns, &from_dir, "--follow", "--wait"],
+        &[],
+        exec::DEFAULT_TIMEOUT,
+    )
+    .with_context(|| format!("In-cluster build of {} failed", image_ref))?;
+
+    let version = exec::run_cmd_timeout(
+        "oc",
+        &["get", "buildconfig", BUNDLE_BUILD_CONFIG, "-n", ns, "-o", "jsonpath={.status.lastVersion}"],
+        exec::API_TIMEOUT,
+    )?;
+    let build = format!("{}-{}", BUNDLE_BUILD_CONFIG, version.stdout.trim());
+    let digest = exec::run_cmd_timeout(
+        "oc",
+        &["get", "build", &build, "-n", ns, "-o", "jsonpath={.status.output.to.imageDigest}"],
+        exec::API_TIMEOUT,
+    )?;
+    let digest = digest.stdout.trim();
+    if digest.is_empty() {
+        bail!("Build {}/{} did not report the pushed digest of {}", ns, build, image_ref);
+    }
+    Ok(pinned_ref(image_ref, digest))
+}
+
+/// Create or update the dockerconfigjson Secret the bundle BuildConfig pushes with.
+fn apply_push_secret(docker_config: &str) -> Result<()> {
+    let secret = serde_json::json!({
+        "apiVersion": "v1",
+        "kind": "Secret",
+        "metadata": {
+            "name": BUNDLE_PUSH_SECRET,
+            "namespace": BUILD_NAMESPACE,
+            "labels": {
+                "app": "streamstress"
+            }
+        },
+        "type": "kubernetes.io/dockerconfigjson",
+        "stringData": {
+            ".dockerconfigjson": docker_config
+        }
+    });
+    // Through a file so the credentials stay out of the command line
+    let file = tempfile::NamedTempFile::new().context("Failed to create push Secret manifest")?;
+    fs::write(file.path(), secret.to_string())?;
+    let path = file.path().to_string_lossy().into_owned();
+    exec::run_cmd("oc", &["apply", "-f", &path]).context("Failed to apply bundle push Secret")?;
+    Ok(())
+}
+
+/// `image_ref` (repo:tag) pinned to `digest` (repo@digest).
+fn pinned_ref(image_ref: &str, digest: &str) -> String {
+    let repo = match image_ref.rsplit_once(':') {
+        // A colon after the last slash separates the tag, not a registry port
+        Some((repo, tag)) if !tag.contains('/') => repo,
+        _ => image_ref,
+    };
+    format!("{}@{}", repo, digest)
+}
+
+fn get_image_digest(image_ref: &str) -> Result<String> {
+    let result = exec::run_cmd(
+        "skopeo",
+        &["inspect", "--format", "{{.Digest}}", &format!("docker://{}", image_ref)],
+    )?;
+
+    if result.exit_code != 0 {
+        bail!("Failed to get image digest: {}", result.stderr);
+    }
+
+    Ok(result.stdout.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_builder_resolve() {
+        assert_eq!(BundleBuilder::Auto.resolve(|t| t == "buildah"), BundleBuilder::Buildah);
+        assert_eq!(BundleBuilder::Auto.resolve(|_| false), BundleBuilder::Openshift);
+        assert_eq!(BundleBuilder::Buildah.resolve(|_| false), BundleBuilder::Buildah);
+    }
+
+    #[test]
+    fn test_pinned_ref() {
+        assert_eq!(pinned_ref("quay.io/me/osp-upstream-index:upstream-1", "sha256:ab"), "quay.io/me/osp-upstream-index@sha256:ab");
+        assert_eq!(pinned_ref("registry:5000/me/bundle:t", "sha256:ab"), "registry:5000/me/bundle@sha256:ab");
+        assert_eq!(pinned_ref("registry:5000/me/bundle", "sha256:ab"), "registry:5000/me/bundle@sha256:ab");
+    }
+}
@@ -0,0 +1,288 @@
+//! Auto-publish of `run` results to the gh-pages dashboard from inside a Job.
+//!
+//! Publishing needs GITHUB_TOKEN and GITHUB_REPOSITORY (set on the Job from the
+//! publish Secret); without them it is skipped with a message instead of failing
+//! the run. The outcome is written to the container termination message and the
+//! Job's info ConfigMap so CI can confirm the dashboard was actually updated:
+//!
+//!   oc get pod -l job-name=<job> -o jsonpath='{..terminated.message}'
+//!   streamstress status -o json
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::incluster::{self, PublishEnv};
+use crate::publish;
+use crate::timestamp;
+use crate::warnings;
+
+/// Publish attempts before giving up on transient git failures.
+const PUBLISH_MAX_ATTEMPTS: u32 = 3;
+
+/// Where Kubernetes reads the container termination message from.
+const TERMINATION_LOG: &str = "/dev/termination-log";
+
+/// Kubernetes truncates termination messages beyond this size.
+const TERMINATION_MESSAGE_MAX: usize = 4096;
+
+/// Outcome of an auto-publish attempt.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum PublishStatus {
+    Published {
+        repository: String,
+        run_id: String,
+        dashboard: String,
+    },
+    Skipped {
+        reason: String,
+    },
+    Failed {
+        repository: String,
+        attempts: u32,
+        error: String,
+    },
+}
+
+impl PublishStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PublishStatus::Published { .. } => "published",
+            PublishStatus::Skipped { .. } => "skipped",
+            PublishStatus::Failed { .. } => "failed",
+        }
+    }
+}
+
+/// Publish `output_dir` to gh-pages if credentials are configured, and record
+/// the outcome for CI. Never fails the run: problems end up in the status.
+pub async fn maybe_publish_results(output_dir: &str) -> PublishStatus {
+    let env = PublishEnv::from_env();
+    let output_dir = output_dir.to_string();
+    let status = tokio::task::spawn_blocking(move || publish_results(&output_dir, &env))
+        .await
+        .unwrap_or_else(|e| PublishStatus::Failed {
+            repository: std::env::var("GITHUB_REPOSITORY").unwrap_or_default(),
+            attempts: 0,
+            error: format!("publish task panicked: {e}"),
+        });
+
+    match &status {
+        PublishStatus::Published { run_id, dashboard, .. } => {
+            eprintln!("Publish: published {} ({})", run_id, dashboard)
+        }
+        PublishStatus::Skipped { reason } => eprintln!("Publish skipped: {}", reason),
+        PublishStatus::Failed { attempts, error, .. } => {
+            warnings::warn(format!("Publish failed after {} attempt(s): {}", attempts, error))
+        }
+    }
+    record_status(&status).await;
+    status
+}
+
+fn publish_results(output_dir: &str, env: &PublishEnv) -> PublishStatus {
+    let (Some(token), Some(repository)) = (&env.github_token, &env.github_repository) else {
+        return PublishStatus::Skipped {
+            reason: "GITHUB_TOKEN or GITHUB_REPOSITORY not set (create the Job with both to publish)".to_string(),
+        };
+    };
+    let results = Path::new(output_dir).join("results/results.json");
+    if !results.exists() {
+        return PublishStatus::Skipped {
+            reason: format!("no results at {}", results.display()),
+        };
+    }
+
+    let remote = format!("https://x-access-token:{}@github.com/{}.git", token, repository);
+    let label = run_label(output_dir, env.label.as_deref());
+    // One id for every attempt, so a push that landed before a failure is
+    // recognized on the retry instead of being published twice.
+    let run_id = publish::new_run_id(&timestamp::now_rfc3339());
+    let published = |run_id: String| PublishStatus::Published {
+        repository: repository.clone(),
+        run_id,
+        dashboard: dashboard_url(repository),
+    };
+    let mut attempt = 1;
+    loop {
+        let signer = env.signing_key.clone().map(|key| crate::signing::Signer { key: Some(key) });
+        match publish::publish(output_dir, Some(&remote), Some(&label), Some(&run_id), None, true, signer.as_ref()) {
+            Ok(run_id) => return published(run_id),
+            Err(e) if attempt > 1 && format!("{e:#}").contains(&format!("Run id {} is already published", run_id)) => {
+                return published(run_id);
+            }
+            Err(e) if attempt < PUBLISH_MAX_ATTEMPTS && is_transient(&e) => {
+                let delay = 10 * attempt as u64;
+                warnings::warn(format!(
+                    "Publish attempt {}/{} failed: {e:#}; retrying in {}s",
+                    attempt, PUBLISH_MAX_ATTEMPTS, delay
+                ));
+                std::thread::sleep(Duration::from_secs(delay));
+                attempt += 1;
+            }
+            Err(e) => {
+                return PublishStatus::Failed {
+                    repository: repository.clone(),
+                    attempts: attempt,
+                    error: format!("{e:#}"),
+                };
+            }
+        }
+    }
+}
+
+/// Errors worth retrying: clone, fetch and push failures, which are usually
+/// network blips, GitHub hiccups or a push rejected as non-fast-forward by a
+/// concurrent publish. Anything else (local results, dashboard assets, signing,
+/// scrubbing) won't go away, and neither will a rejected token.
+fn is_transient(err: &anyhow::Error) -> bool {
+    const TRANSIENT: &[&str] = &[
+        "Failed to clone gh-pages",
+        "git fetch origin gh-pages failed",
+        "Failed to push to gh-pages",
+    ];
+    const AUTH: &[&str] = &["HTTP 401", "HTTP 403", "Authentication failed"];
+    let message = format!("{err:#}");
+    TRANSIENT.iter().any(|t| message.contains(t)) && !AUTH.iter().any(|a| message.contains(a))
+}
+
+/// RUN_LABEL (default "CI run"), with the date appended for a batch run's
+/// per-date output directory.
+fn run_label(output_dir: &str, label: Option<&str>) -> String {
+    let label = label.unwrap_or("CI run");
+    let dir_name = Path::new(output_dir).file_name().and_then(|n| n.to_str()).unwrap_or("");
+    match chrono::NaiveDate::parse_from_str(dir_name, "%Y-%m-%d") {
+        Ok(_) => format!("{} ({})", label, dir_name),
+        Err(_) => label.to_string(),
+    }
+}
+
+/// GitHub Pages URL for `owner/repo`.
+fn dashboard_url(repository: &str) -> String {
+    match repository.split_once('/') {
+        Some((owner, repo)) => format!("https://{}.github.io/{}/", owner, repo),
+        None => repository.to_string(),
+    }
+}
+
+/// Append this publish outcome to the termination message (a JSON array, so
+/// each date of a batch run is kept), dropping the oldest entries to fit.
+fn termination_message(existing: &str, status: &PublishStatus) -> String {
+    let mut entries: Vec<serde_json::Value> = serde_json::from_str(existing).unwrap_or_default();
+    entries.push(serde_json::to_value(status).unwrap_or_default());
+    loop {
+        let message = serde_json::to_string(&entries).unwrap_or_default();
+        if message.len() <= TERMINATION_MESSAGE_MAX || entries.len() == 1 {
+            return message;
+        }
+        entries.remove(0);
+    }
+}
+
+/// Run ids already recorded plus `run_id`, comma-separated.
+fn merge_run_ids(existing: Option<&str>, run_id: &str) -> String {
+    let mut ids: Vec<&str> = existing.unwrap_or("").split(',').filter(|id| !id.is_empty()).collect();
+    if !ids.contains(&run_id) {
+        ids.push(run_id);
+    }
+    ids.join(",")
+}
+
+/// Write the outcome to the termination message and the Job's info ConfigMap
+/// (in-cluster only).
+async fn record_status(status: &PublishStatus) {
+    if Path::new(TERMINATION_LOG).exists() {
+        let existing = std::fs::read_to_string(TERMINATION_LOG).unwrap_or_default();
+        if let Err(e) = std::fs::write(TERMINATION_LOG, termination_message(&existing, status)) {
+            warnings::warn(format!("Could not write termination message: {e}"));
+        }
+    }
+
+    let Ok(job_name) = std::env::var("JOB_NAME") else {
+        return;
+    };
+    if !incluster::is_incluster() {
+        return;
+    }
+    let mut data = incluster::own_job_data(&job_name).await;
+    let mut update = BTreeMap::from([(incluster::PUBLISH_STATUS_KEY.to_string(), status.name().to_string())]);
+    let existing_ids = data.remove(incluster::PUBLISHED_RUN_IDS_KEY);
+    if let PublishStatus::Published { run_id, .. } = status {
+        update.insert(
+            incluster::PUBLISHED_RUN_IDS_KEY.to_string(),
+            merge_run_ids(existing_ids.as_deref(), run_id),
+        );
+    } else if let Some(ids) = existing_ids {
+        update.insert(incluster::PUBLISHED_RUN_IDS_KEY.to_string(), ids);
+    }
+    if let Err(e) = incluster::record_job_data(&job_name, "streamstress-publish", &update).await {
+        warnings::warn(format!("Could not record publish status on Job {}: {e:#}", job_name));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(token: Option<&str>, repo: Option<&str>) -> PublishEnv {
+        PublishEnv {
+            github_token: token.map(str::to_string),
+            github_repository: repo.map(str::to_string),
+            label: None,
+            output_dir: None,
+            batch_id: None,
+            signing_key: None,
+        }
+    }
+
+    #[test]
+    fn test_publish_skips_without_credentials_or_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().to_str().unwrap();
+        let status = publish_results(out, &env(None, Some("org/repo")));
+        assert_eq!(status.name(), "skipped");
+        assert!(matches!(status, PublishStatus::Skipped { ref reason } if reason.contains("GITHUB_TOKEN")));
+
+        let status = publish_results(out, &env(Some("t"), Some("org/repo")));
+        assert!(matches!(status, PublishStatus::Skipped { ref reason } if reason.contains("no results")));
+    }
+
+    #[test]
+    fn test_status_serialization_and_termination_message() {
+        let published = PublishStatus::Published {
+            repository: "org/repo".to_string(),
+            run_id: "run-1".to_string(),
+            dashboard: dashboard_url("org/repo"),
+        };
+        let json = serde_json::to_value(&published).unwrap();
+        assert_eq!(json["status"], "published");
+        assert_eq!(json["dashboard"], "https://org.github.io/repo/");
+
+        let first = termination_message("", &published);
+        let second = termination_message(&first, &PublishStatus::Skipped { reason: "x".to_string() });
+        let entries: Vec<serde_json::Value> = serde_json::from_str(&second).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let big = PublishStatus::Skipped { reason: "r".repeat(3000) };
+        let capped = termination_message(&termination_message("", &big), &big);
+        assert_eq!(serde_json::from_str::<Vec<serde_json::Value>>(&capped).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_run_label_ids_and_transient() {
+        assert_eq!(run_label("/test-output/2025-03-01", Some("nightly")), "nightly (2025-03-01)");
+        assert_eq!(run_label("/test-output", None), "CI run");
+        assert_eq!(merge_run_ids(Some("a,b"), "c"), "a,b,c");
+        assert_eq!(merge_run_ids(Some("a"), "a"), "a");
+        assert_eq!(merge_run_ids(None, "a"), "a");
+        assert!(is_transient(&anyhow::anyhow!("Failed to clone gh-pages branch")));
+        assert!(is_transient(&anyhow::anyhow!("Failed to push to gh-pages after 3 attempts")));
+        assert!(is_transient(&anyhow::anyhow!("git fetch origin gh-pages failed")));
+        assert!(!is_transient(&anyhow::anyhow!("Results not found at /x")));
+        assert!(!is_transient(&anyhow::anyhow!("Failed to sign run file: cosign exited 1")));
+        assert!(!is_transient(&anyhow::anyhow!("Failed to scrub results")));
+        assert!(!is_transient(&anyhow::anyhow!("Failed to clone gh-pages: HTTP 403")));
+    }
+}
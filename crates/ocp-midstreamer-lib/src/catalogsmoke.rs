@@ -0,0 +1,277 @@
+//! Installability smoke test of a freshly built FBC index (`konflux`).
+//!
+//! A broken catalog otherwise only shows up an hour into the Konflux pipeline.
+//! Right after the index is pushed, a throwaway namespace gets a CatalogSource
+//! serving it, which must become READY and list the bundle as the head of its
+//! channel. A Subscription with manual approval must then resolve to an
+//! InstallPlan. The plan is never approved, so nothing gets installed, and the
+//! namespace is deleted with everything in it afterwards.
+
+use anyhow::{Context, Result, bail};
+use k8s_openapi::api::core::v1::Namespace;
+use kube::api::{Api, ApiResource, DeleteParams, DynamicObject, ListParams, PostParams};
+use kube::Client;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+
+use crate::bundle::{INDEX_CHANNEL, INDEX_CSV, OPERATOR_PACKAGE};
+use crate::{audit, k8s, timestamp, warnings};
+
+/// Name of the CatalogSource, OperatorGroup and Subscription in the smoke namespace.
+const NAME: &str = "streamstress-smoke";
+
+/// How long the catalog pod may take to pull the index and serve it.
+const CATALOG_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How long OLM may take to list the package and resolve the Subscription.
+const RESOLVE_TIMEOUT: Duration = Duration::from_secs(180);
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Outcome of a passed smoke test, for `konflux --output json|yaml`.
+#[derive(Debug, Serialize)]
+pub struct SmokeResult {
+    pub channel: String,
+    pub head_csv: String,
+    pub install_plan: String,
+    pub duration_secs: f64,
+}
+
+/// What OLM made of the Subscription so far.
+#[derive(Debug, PartialEq)]
+enum Resolution {
+    Pending,
+    Resolved(String),
+    Failed(String),
+}
+
+/// Check that `index_pullspec` serves an installable operator, in a throwaway
+/// namespace deleted afterwards.
+pub fn smoke_test(index_pullspec: &str) -> Result<SmokeResult> {
+    let started = Instant::now();
+    let (rt, client) = k8s::create_kube_client()?;
+    let namespace = format!("streamstress-catalog-smoke-{}", timestamp::unix_now());
+    create_namespace(&rt, &client, &namespace)?;
+
+    let result = run_checks(&rt, &client, &namespace, index_pullspec);
+
+    let api: Api<Namespace> = Api::all(client.clone());
+    let dp = DeleteParams::default();
+    let deleted = k8s::block_on_retry(&rt, "delete Namespace", || api.delete(&namespace, &dp));
+    audit::record_api("delete", "Namespace", None, &namespace, deleted.is_ok());
+    if let Err(e) = deleted {
+        warnings::warn(format!("Failed to delete catalog smoke test namespace {namespace}: {e}"));
+    }
+
+    let (head_csv, install_plan) = result?;
+    Ok(SmokeResult {
+        channel: INDEX_CHANNEL.to_string(),
+        head_csv,
+        install_plan,
+        duration_secs: started.elapsed().as_secs_f64(),
+    })
+}
+
+/// Returns the channel head and the InstallPlan the Subscription resolved to.
+fn run_checks(rt: &Runtime, client: &Client, namespace: &str, index_pullspec: &str) -> Result<(String, String)> {
+    let pp = PostParams::default();
+    let create = |ar: &ApiResource, manifest: Value| -> Result<()> {
+        let api: Api<DynamicObject> = Api::namespaced_with(client.clone(), namespace, ar);
+        let obj: DynamicObject = serde_json::from_value(manifest)?;
+        let what = format!("create {}", ar.kind);
+        let result = k8s::block_on_retry(rt, &what, || api.create(&pp, &obj));
+        audit::record_api("create", &ar.kind, Some(namespace), NAME, result.is_ok());
+        result.with_context(|| format!("Failed to create {} {}/{}", ar.kind, namespace, NAME))?;
+        Ok(())
+    };
+
+    create(&olm_resource("v1alpha1", "CatalogSource", "catalogsources"), json!({
+        "apiVersion": "operators.coreos.com/v1alpha1",
+        "kind": "CatalogSource",
+        "metadata": {"name": NAME, "labels": {"app": "streamstress"}},
+        "spec": {"sourceType": "grpc", "image": index_pullspec, "displayName": "streamstress smoke test"}
+    }))?;
+    eprintln!("  Created CatalogSource {}/{}; waiting for it to serve the index", namespace, NAME);
+    wait_for_catalog(rt, client, namespace)?;
+
+    let head_csv = wait_for_package(rt, client, namespace)?;
+    eprintln!("  Package {} resolves: channel {} -> {}", OPERATOR_PACKAGE, INDEX_CHANNEL, head_csv);
+
+    create(&olm_resource("v1", "OperatorGroup", "operatorgroups"), json!({
+        "apiVersion": "operators.coreos.com/v1",
+        "kind": "OperatorGroup",
+        "metadata": {"name": NAME, "labels": {"app": "streamstress"}},
+        "spec": {}
+    }))?;
+    create(&olm_resource("v1alpha1", "Subscription", "subscriptions"), json!({
+        "apiVersion": "operators.coreos.com/v1alpha1",
+        "kind": "Subscription",
+        "metadata": {"name": NAME, "labels": {"app": "streamstress"}},
+        "spec": {
+            "channel": INDEX_CHANNEL,
+            "name": OPERATOR_PACKAGE,
+            "source": NAME,
+            "sourceNamespace": namespace,
+            "startingCSV": INDEX_CSV,
+            "installPlanApproval": "Manual"
+        }
+    }))?;
+    let install_plan = wait_for_resolution(rt, client, namespace)?;
+    eprintln!("  Subscription resolved to InstallPlan {} (not approved, nothing installed)", install_plan);
+    Ok((head_csv, install_plan))
+}
+
+fn create_namespace(rt: &Runtime, client: &Client, namespace: &str) -> Result<()> {
+    let api: Api<Namespace> = Api::all(client.clone());
+    let ns: Namespace = serde_json::from_value(json!({
+        "metadata": {"name": namespace, "labels": {"app": "streamstress"}}
+    }))?;
+    let pp = PostParams::default();
+    let result = k8s::block_on_retry(rt, "create Namespace", || api.create(&pp, &ns));
+    audit::record_api("create", "Namespace", None, namespace, result.is_ok());
+    result.with_context(|| format!("Failed to create namespace {namespace}"))?;
+    Ok(())
+}
+
+fn olm_resource(version: &str, kind: &str, plural: &str) -> ApiResource {
+    ApiResource {
+        group: "operators.coreos.com".into(),
+        version: version.into(),
+        api_version: format!("operators.coreos.com/{version}"),
+        kind: kind.into(),
+        plural: plural.into(),
+    }
+}
+
+fn packagemanifest_resource() -> ApiResource {
+    ApiResource {
+        group: "packages.operators.coreos.com".into(),
+        version: "v1".into(),
+        api_version: "packages.operators.coreos.com/v1".into(),
+        kind: "PackageManifest".into(),
+        plural: "packagemanifests".into(),
+    }
+}
+
+/// Poll `check` until it returns a value, failing with `what` and the last
+/// observed state when `timeout` passes.
+fn poll<T>(what: &str, timeout: Duration, mut check: impl FnMut(&mut String) -> Result<Option<T>>) -> Result<T> {
+    let started = Instant::now();
+    let mut last = String::from("unknown");
+    loop {
+        if let Some(value) = check(&mut last)? {
+            return Ok(value);
+        }
+        if started.elapsed() > timeout {
+            bail!("Timed out after {}s waiting for {} (last state: {})", timeout.as_secs(), what, last);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn wait_for_catalog(rt: &Runtime, client: &Client, namespace: &str) -> Result<()> {
+    let api: Api<DynamicObject> =
+        Api::namespaced_with(client.clone(), namespace, &olm_resource("v1alpha1", "CatalogSource", "catalogsources"));
+    poll("the CatalogSource to become READY (can the cluster pull the index image?)", CATALOG_TIMEOUT, |last| {
+        let catalog = k8s::block_on_retry(rt, "get CatalogSource", || api.get(NAME)).context("Failed to get the CatalogSource")?;
+        let state = catalog.data["status"]["connectionState"]["lastObservedState"].as_str().unwrap_or("no status yet");
+        *last = state.to_string();
+        Ok((state == "READY").then_some(()))
+    })
+}
+
+/// Wait for the catalog's PackageManifest and return the head of `INDEX_CHANNEL`.
+fn wait_for_package(rt: &Runtime, client: &Client, namespace: &str) -> Result<String> {
+    let api: Api<DynamicObject> = Api::namespaced_with(client.clone(), namespace, &packagemanifest_resource());
+    let lp = ListParams::default().labels(&format!("catalog={NAME}"));
+    let head = poll(&format!("package {OPERATOR_PACKAGE} to be listed"), RESOLVE_TIMEOUT, |last| {
+        let list = k8s::block_on_retry(rt, "list PackageManifests", || api.list(&lp)).context("Failed to list PackageManifests")?;
+        let packages: Vec<&str> = list.items.iter().filter_map(|p| p.metadata.name.as_deref()).collect();
+        *last = format!("packages [{}]", packages.join(", "));
+        Ok(list.items.into_iter().find(|p| p.metadata.name.as_deref() == Some(OPERATOR_PACKAGE)))
+    })?;
+    match channel_head(&head.data, INDEX_CHANNEL) {
+        Some(csv) if csv == INDEX_CSV => Ok(csv),
+        Some(csv) => bail!("Channel {} of {} has head {}, expected {}", INDEX_CHANNEL, OPERATOR_PACKAGE, csv, INDEX_CSV),
+        None => bail!(
+            "Package {} has no channel {} (channels: {})",
+            OPERATOR_PACKAGE,
+            INDEX_CHANNEL,
+            channels(&head.data).join(", ")
+        ),
+    }
+}
+
+fn channels(package: &Value) -> Vec<String> {
+    package["status"]["channels"]
+        .as_array()
+        .map(|cs| cs.iter().filter_map(|c| c["name"].as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+/// `currentCSV` of `channel` in a PackageManifest.
+fn channel_head(package: &Value, channel: &str) -> Option<String> {
+    package["status"]["channels"]
+        .as_array()?
+        .iter()
+        .find(|c| c["name"] == channel)
+        .and_then(|c| c["currentCSV"].as_str())
+        .map(str::to_string)
+}
+
+fn wait_for_resolution(rt: &Runtime, client: &Client, namespace: &str) -> Result<String> {
+    let api: Api<DynamicObject> =
+        Api::namespaced_with(client.clone(), namespace, &olm_resource("v1alpha1", "Subscription", "subscriptions"));
+    poll("the Subscription to resolve to an InstallPlan", RESOLVE_TIMEOUT, |last| {
+        let sub = k8s::block_on_retry(rt, "get Subscription", || api.get(NAME)).context("Failed to get the Subscription")?;
+        *last = sub.data["status"]["state"].as_str().unwrap_or("no status yet").to_string();
+        match resolution(&sub.data) {
+            Resolution::Pending => Ok(None),
+            Resolution::Resolved(plan) => Ok(Some(plan)),
+            Resolution::Failed(message) => bail!("Subscription resolution failed: {}", message),
+        }
+    })
+}
+
+fn resolution(sub: &Value) -> Resolution {
+    let status = &sub["status"];
+    let failed = status["conditions"].as_array().and_then(|conditions| {
+        conditions.iter().find(|c| c["type"] == "ResolutionFailed" && c["status"] == "True")
+    });
+    if let Some(condition) = failed {
+        return Resolution::Failed(condition["message"].as_str().unwrap_or("no message").to_string());
+    }
+    match status["installPlanRef"]["name"].as_str().or(status["installplan"]["name"].as_str()) {
+        Some(plan) => Resolution::Resolved(plan.to_string()),
+        None => Resolution::Pending,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_and_resolution() {
+        let package = json!({"status": {"channels": [
+            {"name": "latest", "currentCSV": "openshift-pipelines-operator-rh.v1.15.0"},
+            {"name": INDEX_CHANNEL, "currentCSV": INDEX_CSV}
+        ]}});
+        assert_eq!(channel_head(&package, INDEX_CHANNEL).as_deref(), Some(INDEX_CSV));
+        assert_eq!(channel_head(&package, "pipelines-1.14"), None);
+        assert_eq!(channels(&package), vec!["latest", INDEX_CHANNEL]);
+
+        assert_eq!(resolution(&json!({"spec": {}})), Resolution::Pending);
+        assert_eq!(
+            resolution(&json!({"status": {"state": "UpgradePending", "installPlanRef": {"name": "install-abcde"}}})),
+            Resolution::Resolved("install-abcde".to_string())
+        );
+        let failed = json!({"status": {"conditions": [
+            {"type": "CatalogSourcesUnhealthy", "status": "False"},
+            {"type": "ResolutionFailed", "status": "True", "message": "constraints not satisfiable"}
+        ]}});
+        assert_eq!(resolution(&failed), Resolution::Failed("constraints not satisfiable".to_string()));
+    }
+}
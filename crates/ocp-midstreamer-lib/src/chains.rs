@@ -0,0 +1,252 @@
+//! Chains signing verification.
+//!
+//! Gauge only partially covers Tekton Chains, so after Chains is deployed a run
+//! can check signing end to end: it creates a sample TaskRun, waits for Chains
+//! to sign it, and verifies the signature (or in-toto attestation) stored on
+//! the TaskRun with cosign against the cluster's signing key. The outcome is
+//! reported as a synthetic test in the run's results.
+
+use anyhow::{Context, Result, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use k8s_openapi::api::core::v1::Secret;
+use kube::api::{Api, ApiResource, DeleteParams, DynamicObject, PostParams};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+use crate::{audit, exec, k8s, results, workspace};
+
+/// Spec name of the synthetic test in results.
+pub const SPEC: &str = "Chains signing verification";
+
+/// Namespace the sample TaskRun runs in.
+const VERIFY_NAMESPACE: &str = "streamstress-chains-verify";
+
+/// Secret holding the Chains signing key pair (cosign.key, cosign.pub, cosign.password).
+const SIGNING_SECRET: &str = "signing-secrets";
+
+const OPERATOR_NAMESPACE: &str = "openshift-pipelines";
+
+/// How long the TaskRun may take to run and be signed.
+const SIGNING_TIMEOUT: Duration = Duration::from_secs(300);
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A TaskRun signature as Chains stores it in annotations.
+#[derive(Debug, PartialEq)]
+enum SignedPayload {
+    /// "in-toto" format: a DSSE envelope whose payload is an in-toto statement
+    Attestation { envelope: Vec<u8>, statement: Value },
+    /// "tekton" format: a simple signature (base64) over the payload
+    Signature { signature: String, payload: Vec<u8> },
+}
+
+/// Run the verification and report it as a synthetic test.
+pub fn verify_signing() -> results::TestCaseResult {
+    let started = Instant::now();
+    let outcome = verify();
+    match &outcome {
+        Ok(detail) => eprintln!("  Chains signing verified: {}", detail),
+        Err(e) => eprintln!("  Chains signing verification FAILED: {e:#}"),
+    }
+    results::TestCaseResult {
+        spec: SPEC.to_string(),
+        scenario: "Sample TaskRun is signed and verifies with cosign".to_string(),
+        passed: outcome.is_ok(),
+        duration_secs: started.elapsed().as_secs_f64(),
+        error_message: outcome.err().map(|e| format!("{e:#}")),
+        components: vec!["chains".to_string()],
+        log_excerpts: Vec::new(),
+    }
+}
+
+fn verify() -> Result<String> {
+    which::which("cosign").context("cosign not found on PATH (needed to verify Chains signatures)")?;
+    let (rt, client) = k8s::create_kube_client()?;
+    let public_key = ensure_signing_key(&rt, &client)?;
+    k8s::ensure_namespace(&rt, &client, VERIFY_NAMESPACE)?;
+
+    let api: Api<DynamicObject> = Api::namespaced_with(client.clone(), VERIFY_NAMESPACE, &taskrun_resource());
+    let taskrun: DynamicObject = serde_json::from_value(serde_json::json!({
+        "apiVersion": "tekton.dev/v1",
+        "kind": "TaskRun",
+        "metadata": {"generateName": "streamstress-chains-", "labels": {"app": "streamstress"}},
+        "spec": {"taskSpec": {"steps": [{
+            "name": "hello",
+            "image": "registry.access.redhat.com/ubi9/ubi-minimal",
+            "script": "echo signed by chains"
+        }]}}
+    }))?;
+    let pp = PostParams::default();
+    let result = k8s::block_on_retry(&rt, "create TaskRun", || api.create(&pp, &taskrun));
+    let name = result.as_ref().ok().and_then(|t| t.metadata.name.clone()).unwrap_or_default();
+    audit::record_api("create", "TaskRun", Some(VERIFY_NAMESPACE), &name, result.is_ok());
+    let created = result.context("Failed to create the sample TaskRun")?;
+    eprintln!("  Created TaskRun {}/{}; waiting for Chains to sign it", VERIFY_NAMESPACE, name);
+
+    let verified = wait_for_signature(&rt, &api, &name).and_then(|signed| {
+        let uid = created.metadata.uid.as_deref().unwrap_or_default();
+        let payload = signed_payload(&signed, uid)?;
+        verify_with_cosign(&payload, &public_key)
+    });
+
+    let dp = DeleteParams::default();
+    let deleted = k8s::block_on_retry(&rt, "delete TaskRun", || api.delete(&name, &dp));
+    audit::record_api("delete", "TaskRun", Some(VERIFY_NAMESPACE), &name, deleted.is_ok());
+    verified
+}
+
+fn taskrun_resource() -> ApiResource {
+    ApiResource {
+        group: "tekton.dev".into(),
+        version: "v1".into(),
+        api_version: "tekton.dev/v1".into(),
+        kind: "TaskRun".into(),
+        plural: "taskruns".into(),
+    }
+}
+
+/// Public key Chains signs with; generates the key pair with cosign if the
+/// cluster has none (Chains picks up the new secret within a minute or so).
+fn ensure_signing_key(rt: &tokio::runtime::Runtime, client: &kube::Client) -> Result<Vec<u8>> {
+    let api: Api<Secret> = Api::namespaced(client.clone(), OPERATOR_NAMESPACE);
+    let read = || -> Result<Option<Vec<u8>>> {
+        let secret = k8s::block_on_retry(rt, "get Chains signing secret", || api.get_opt(SIGNING_SECRET))?;
+        Ok(secret.and_then(|s| s.data).and_then(|d| d.get("cosign.pub").map(|k| k.0.clone())))
+    };
+    if let Some(key) = read()? {
+        return Ok(key);
+    }
+
+    eprintln!("  No Chains signing key in {}/{}; generating one with cosign", OPERATOR_NAMESPACE, SIGNING_SECRET);
+    // An existing secret without a key blocks cosign from writing its own
+    let dp = DeleteParams::default();
+    let result = k8s::block_on_retry(rt, "delete Chains signing secret", || api.delete(SIGNING_SECRET, &dp));
+    if !matches!(result, Err(kube::Error::Api(ref resp)) if resp.code == 404) {
+        audit::record_api("delete", "Secret", Some(OPERATOR_NAMESPACE), SIGNING_SECRET, result.is_ok());
+    }
+    let target = format!("k8s://{}/{}", OPERATOR_NAMESPACE, SIGNING_SECRET);
+    exec::run_cmd("env", &["COSIGN_PASSWORD=", "cosign", "generate-key-pair", &target])
+        .context("Failed to generate the Chains signing key")?;
+    read()?.with_context(|| format!("{}/{} has no cosign.pub after generating the key", OPERATOR_NAMESPACE, SIGNING_SECRET))
+}
+
+/// Poll until Chains marks the TaskRun signed; fails if the TaskRun or signing fails.
+fn wait_for_signature(rt: &tokio::runtime::Runtime, api: &Api<DynamicObject>, name: &str) -> Result<DynamicObject> {
+    let started = Instant::now();
+    loop {
+        let taskrun = k8s::block_on_retry(rt, "get TaskRun", || api.get(name))
+            .with_context(|| format!("Failed to get TaskRun {}", name))?;
+        let annotation = |key: &str| taskrun.metadata.annotations.as_ref().and_then(|a| a.get(key)).map(String::as_str);
+        match annotation("chains.tekton.dev/signed") {
+            Some("true") => return Ok(taskrun),
+            Some("failed") => bail!("Chains failed to sign TaskRun {} (see the tekton-chains-controller logs)", name),
+            _ => {}
+        }
+        let condition = taskrun
+            .data
+            .pointer("/status/conditions")
+            .and_then(|c| c.as_array())
+            .and_then(|c| c.iter().find(|c| c["type"] == "Succeeded"));
+        if let Some(c) = condition.filter(|c| c["status"] == "False") {
+            bail!("Sample TaskRun {} failed: {}", name, c["message"].as_str().unwrap_or("unknown reason"));
+        }
+        if started.elapsed() > SIGNING_TIMEOUT {
+            let state = if condition.is_some_and(|c| c["status"] == "True") { "completed but was not signed" } else { "did not complete" };
+            bail!(
+                "TaskRun {} {} within {}s; is Chains running and configured with a signing key?",
+                name,
+                state,
+                SIGNING_TIMEOUT.as_secs()
+            );
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Signature Chains stored on the TaskRun with `uid` (tekton storage backend).
+fn signed_payload(taskrun: &DynamicObject, uid: &str) -> Result<SignedPayload> {
+    let annotations = taskrun.metadata.annotations.clone().unwrap_or_default();
+    let signature = annotations.get(&format!("chains.tekton.dev/signature-taskrun-{}", uid)).with_context(|| {
+        "TaskRun is signed but carries no signature annotation; Chains must store TaskRun \
+         artifacts in the 'tekton' backend (artifacts.taskrun.storage)"
+    })?;
+    let payload = annotations.get(&format!("chains.tekton.dev/payload-taskrun-{}", uid));
+    parse_signed_payload(signature, payload.map(String::as_str))
+}
+
+fn parse_signed_payload(signature: &str, payload: Option<&str>) -> Result<SignedPayload> {
+    let decoded = STANDARD.decode(signature.trim()).context("Signature annotation is not base64")?;
+    if let Ok(envelope) = serde_json::from_slice::<Value>(&decoded) && envelope.get("payloadType").is_some() {
+        let statement = envelope["payload"]
+            .as_str()
+            .and_then(|p| STANDARD.decode(p).ok())
+            .and_then(|p| serde_json::from_slice::<Value>(&p).ok())
+            .context("DSSE envelope has no valid in-toto statement payload")?;
+        if statement["predicateType"].as_str().is_none_or(str::is_empty) {
+            bail!("In-toto statement has no predicateType");
+        }
+        return Ok(SignedPayload::Attestation { envelope: decoded, statement });
+    }
+    let payload = payload.context("Simple signature without a payload annotation")?;
+    let payload = STANDARD.decode(payload.trim()).context("Payload annotation is not base64")?;
+    Ok(SignedPayload::Signature { signature: signature.trim().to_string(), payload })
+}
+
+/// Verify `signed` against `public_key` with cosign. The signatures are not
+/// uploaded to a transparency log, so the log check is skipped.
+fn verify_with_cosign(signed: &SignedPayload, public_key: &[u8]) -> Result<String> {
+    let dir = workspace::scoped("chains-verify")?;
+    let key = dir.path().join("cosign.pub");
+    std::fs::write(&key, public_key)?;
+    let key = key.display().to_string();
+    let sig = dir.path().join("signature");
+
+    match signed {
+        SignedPayload::Attestation { envelope, statement } => {
+            std::fs::write(&sig, envelope)?;
+            let sig = sig.display().to_string();
+            exec::run_cmd(
+                "cosign",
+                &["verify-blob-attestation", "--insecure-ignore-tlog", "--key", &key, "--signature", &sig, "--check-claims=false", "/dev/null"],
+            )
+            .context("cosign could not verify the TaskRun attestation")?;
+            Ok(format!("attestation {}", statement["predicateType"].as_str().unwrap_or_default()))
+        }
+        SignedPayload::Signature { signature, payload } => {
+            std::fs::write(&sig, signature)?;
+            let blob = dir.path().join("payload");
+            std::fs::write(&blob, payload)?;
+            let (sig, blob) = (sig.display().to_string(), blob.display().to_string());
+            exec::run_cmd("cosign", &["verify-blob", "--insecure-ignore-tlog", "--key", &key, "--signature", &sig, &blob])
+                .context("cosign could not verify the TaskRun signature")?;
+            Ok("simple signature".to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_signed_payload() {
+        let statement = serde_json::json!({"_type": "https://in-toto.io/Statement/v0.1", "predicateType": "https://slsa.dev/provenance/v0.2"});
+        let envelope = serde_json::json!({
+            "payloadType": "application/vnd.in-toto+json",
+            "payload": STANDARD.encode(statement.to_string()),
+            "signatures": [{"sig": "MEUC"}]
+        })
+        .to_string();
+        match parse_signed_payload(&STANDARD.encode(&envelope), None).unwrap() {
+            SignedPayload::Attestation { statement: s, .. } => assert_eq!(s, statement),
+            other => panic!("expected an attestation, got {:?}", other),
+        }
+
+        let signed = parse_signed_payload("MEUCIQ==", Some(&STANDARD.encode("{\"critical\":{}}"))).unwrap();
+        assert_eq!(signed, SignedPayload::Signature { signature: "MEUCIQ==".to_string(), payload: b"{\"critical\":{}}".to_vec() });
+
+        assert!(parse_signed_payload("MEUCIQ==", None).is_err());
+        assert!(parse_signed_payload("not base64!", None).is_err());
+    }
+}
@@ -1,8 +1,11 @@
 use anyhow::Result;
 use console::Style;
 
-use crate::exec::run_cmd_unchecked;
+use crate::exec::{run_cmd_unchecked_timeout, API_TIMEOUT};
+use crate::netcheck;
+use crate::output::{self, OutputFormat};
 use crate::progress::{finish_spinner, stage_spinner};
+use crate::registry;
 use crate::types::CheckResult;
 
 struct ToolSpec {
@@ -39,14 +42,14 @@ const TOOLS: &[ToolSpec] = &[
     },
 ];
 
-pub fn run_check(_verbose: bool) -> Result<bool> {
+pub fn run_check(format: OutputFormat) -> Result<bool> {
     let mut results: Vec<CheckResult> = Vec::new();
 
     for tool in TOOLS {
         let pb = stage_spinner(&format!("Checking {}...", tool.name));
 
         let result = if which::which(tool.name).is_ok() {
-            match run_cmd_unchecked(tool.name, tool.version_args) {
+            match run_cmd_unchecked_timeout(tool.name, tool.version_args, API_TIMEOUT) {
                 Ok(exec) => {
                     let detail = exec.stdout.lines().next().unwrap_or("").trim().to_string();
                     CheckResult {
@@ -56,12 +59,15 @@ pub fn run_check(_verbose: bool) -> Result<bool> {
                         fix_hint: None,
                     }
                 }
-                Err(_) => CheckResult {
-                    name: tool.name.to_string(),
-                    passed: false,
-                    detail: "Found on PATH but failed to get version".to_string(),
-                    fix_hint: Some(tool.fix_hint.to_string()),
-                },
+                Err(e) => {
+                    tracing::info!("{} {}: {:#}", tool.name, tool.version_args.join(" "), e);
+                    CheckResult {
+                        name: tool.name.to_string(),
+                        passed: false,
+                        detail: "Found on PATH but failed to get version".to_string(),
+                        fix_hint: Some(tool.fix_hint.to_string()),
+                    }
+                }
             }
         } else {
             CheckResult {
@@ -80,7 +86,7 @@ pub fn run_check(_verbose: bool) -> Result<bool> {
     let cluster_connected;
     {
         let pb = stage_spinner("Checking cluster auth...");
-        let result = match run_cmd_unchecked("oc", &["whoami"]) {
+        let result = match run_cmd_unchecked_timeout("oc", &["whoami"], API_TIMEOUT) {
             Ok(exec) if exec.exit_code == 0 => {
                 cluster_connected = true;
                 CheckResult {
@@ -107,7 +113,7 @@ pub fn run_check(_verbose: bool) -> Result<bool> {
     // Operator check (only if connected to cluster)
     if cluster_connected {
         let pb = stage_spinner("Checking OpenShift Pipelines operator...");
-        let result = match run_cmd_unchecked("oc", &["get", "tektonconfigs.operator.tekton.dev", "config"]) {
+        let result = match run_cmd_unchecked_timeout("oc", &["get", "tektonconfigs.operator.tekton.dev", "config"], API_TIMEOUT) {
             Ok(exec) if exec.exit_code == 0 => CheckResult {
                 name: "pipelines operator".to_string(),
                 passed: true,
@@ -136,13 +142,14 @@ pub fn run_check(_verbose: bool) -> Result<bool> {
                 fix_hint: None,
             }
         } else {
-            match run_cmd_unchecked(
+            match run_cmd_unchecked_timeout(
                 "oc",
                 &[
                     "get", "route", "default-route",
                     "-n", "openshift-image-registry",
                     "-o", "jsonpath={.spec.host}",
                 ],
+                API_TIMEOUT,
             ) {
                 Ok(exec) if exec.exit_code == 0 && !exec.stdout.trim().is_empty() => {
                     CheckResult {
@@ -167,13 +174,62 @@ pub fn run_check(_verbose: bool) -> Result<bool> {
         results.push(result);
     }
 
+    // Registry storage check (only if connected to cluster)
+    if cluster_connected {
+        let pb = stage_spinner("Checking registry storage...");
+        let result = match registry::storage_health() {
+            Ok(health) if health.is_empty_dir() => {
+                let disk = match (&health.node, health.available_bytes) {
+                    (Some(node), Some(avail)) => format!("node {} has {} free", node, registry::format_bytes(avail)),
+                    _ => "node disk usage unknown".to_string(),
+                };
+                CheckResult {
+                    name: "registry storage".to_string(),
+                    passed: !health.low_disk(),
+                    detail: format!("emptyDir, images are lost on registry pod restart ({disk})"),
+                    fix_hint: Some("Switch to PVC storage: streamstress check --fix --registry-storage pvc".to_string()),
+                }
+            }
+            Ok(health) => CheckResult {
+                name: "registry storage".to_string(),
+                passed: health.backend.is_some(),
+                detail: health.backend.unwrap_or_else(|| "No storage configured".to_string()),
+                fix_hint: None,
+            },
+            Err(e) => CheckResult {
+                name: "registry storage".to_string(),
+                passed: false,
+                detail: format!("{e:#}"),
+                fix_hint: None,
+            },
+        };
+        finish_spinner(&pb, result.passed);
+        results.push(result);
+    }
+
+    // Network reachability: GitHub and quay.io from here, and with the registry from a pod
+    {
+        let pb = stage_spinner("Checking network reachability...");
+        let mut network = netcheck::host_checks();
+        if cluster_connected {
+            network.extend(netcheck::in_cluster_checks());
+        }
+        finish_spinner(&pb, network.iter().all(|r| r.passed));
+        results.extend(network);
+    }
+
+    if format.is_structured() {
+        output::print(format, &results)?;
+        return Ok(results.iter().all(|r| r.passed));
+    }
+
     // Print summary
     println!();
     let green = Style::new().green().bold();
     let red = Style::new().red().bold();
 
     for r in &results {
-        let auto_fixable = matches!(r.name.as_str(), "registry route" | "pipelines operator");
+        let auto_fixable = matches!(r.name.as_str(), "registry route" | "pipelines operator" | "registry storage");
         if r.passed {
             println!("  {} {}: {}", green.apply_to("PASS"), r.name, r.detail);
         } else {
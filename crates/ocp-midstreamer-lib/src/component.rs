@@ -0,0 +1,238 @@
+use anyhow::Result;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+
+/// Known component names that can be selected via --components.
+pub const KNOWN_COMPONENTS: &[&str] = &[
+    "pipeline", "triggers", "chains", "results",
+    "manual-approval-gate", "console-plugin", "pipelines-as-code",
+];
+
+/// Keywords in a release-tests spec/scenario name that tie it to a component.
+/// `pipeline` also covers the generic ecosystem/pruning specs, which exercise
+/// PipelineRuns and TaskRuns.
+const TEST_KEYWORDS: &[(&str, &[&str])] = &[
+    ("pipeline", &["pipeline", "taskrun", "ecosystem", "auto-prune", "resolver"]),
+    ("triggers", &["trigger", "eventlistener", "interceptor"]),
+    ("chains", &["chains"]),
+    ("results", &["tekton results", "results api", "results watcher"]),
+    ("manual-approval-gate", &["manualapprovalgate", "manual approval", "approval gate", "approvaltask"]),
+    ("console-plugin", &["console plugin", "console-plugin", "dynamic plugin"]),
+    ("pipelines-as-code", &["pipelines as code", "pipelines-as-code", "pipelinesascode"]),
+];
+
+/// Test case ids such as `PIPELINES-27-TC01`, which name the product rather
+/// than the component under test.
+static TEST_CASE_ID: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"pipelines-\d+-tc\d+").expect("Invalid regex"));
+
+/// Components a test exercises, derived from its spec and scenario names.
+/// Empty when nothing matches.
+pub fn components_for_test(spec: &str, scenario: &str) -> Vec<String> {
+    let text = format!("{} {}", spec, scenario).to_lowercase();
+    let text = TEST_CASE_ID.replace_all(&text, "");
+    TEST_KEYWORDS
+        .iter()
+        .filter(|(_, keywords)| keywords.iter().any(|k| text.contains(k)))
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+/// A component to build/deploy/test, with an optional git ref or as-of date override.
+#[derive(Debug, Clone)]
+pub struct ComponentSpec {
+    pub name: String,
+    pub git_ref: Option<String>,
+    /// Date in YYYY-MM-DD format for historical builds. Populated from --as-of flag.
+    pub as_of_date: Option<String>,
+}
+
+/// Parse a comma-separated component spec string.
+///
+/// Format: `name[:ref],name[:ref],...`, where a name may also be a group from
+/// `groups` or `all` (every known component), expanded in place. A component
+/// listed more than once is kept once; an explicit ref wins over a group entry.
+/// Examples:
+///   - `pipeline,triggers` (default refs)
+///   - `pipeline:pr/123,triggers:v0.28.0` (custom refs)
+///   - `core,results` (group plus a component)
+pub fn parse_component_specs(input: &str, groups: &BTreeMap<String, Vec<String>>) -> Result<Vec<ComponentSpec>, String> {
+    let mut specs: Vec<ComponentSpec> = Vec::new();
+    let mut add = |name: &str, git_ref: Option<String>| {
+        match specs.iter_mut().find(|s| s.name == name) {
+            Some(existing) => {
+                if git_ref.is_some() {
+                    existing.git_ref = git_ref;
+                }
+            }
+            None => specs.push(ComponentSpec {
+                name: name.to_string(),
+                git_ref,
+                as_of_date: None, // Populated later from --as-of flag
+            }),
+        }
+    };
+    for part in input.split(',') {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+        let (name, git_ref) = match part.split_once(':') {
+            Some((n, r)) => (n.trim(), Some(r.trim().to_string())),
+            None => (part, None),
+        };
+        let members: Vec<&str> = if name == "all" {
+            KNOWN_COMPONENTS.to_vec()
+        } else if let Some(members) = groups.get(name) {
+            members.iter().map(String::as_str).collect()
+        } else if KNOWN_COMPONENTS.contains(&name) {
+            add(name, git_ref);
+            continue;
+        } else {
+            return Err(unknown_component_error(name, groups));
+        };
+        if git_ref.is_some() {
+            return Err(format!(
+                "Group '{}' cannot take a ref; give refs per component (e.g. {},pipeline:v0.62.0)",
+                name, name
+            ));
+        }
+        for member in members {
+            if !KNOWN_COMPONENTS.contains(&member) {
+                return Err(format!("Group '{}' names unknown component '{}'", name, member));
+            }
+            add(member, None);
+        }
+    }
+    if specs.is_empty() {
+        return Err("No components specified".to_string());
+    }
+    Ok(specs)
+}
+
+/// "Unknown component" with the closest component or group name as a suggestion.
+fn unknown_component_error(name: &str, groups: &BTreeMap<String, Vec<String>>) -> String {
+    let mut candidates: Vec<&str> = KNOWN_COMPONENTS.to_vec();
+    candidates.push("all");
+    candidates.extend(groups.keys().map(String::as_str));
+    let suggestion = candidates
+        .iter()
+        .map(|c| (edit_distance(name, c), *c))
+        .filter(|(d, c)| *d <= 2.max(c.len() / 3))
+        .min()
+        .map(|(_, c)| format!(" (did you mean '{}'?)", c))
+        .unwrap_or_default();
+    let mut known = KNOWN_COMPONENTS.join(", ");
+    if !groups.is_empty() {
+        known.push_str(&format!("; groups: all, {}", groups.keys().cloned().collect::<Vec<_>>().join(", ")));
+    } else {
+        known.push_str("; groups: all");
+    }
+    format!("Unknown component '{}'{}. Known: {}", name, suggestion, known)
+}
+
+/// Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev[j] + usize::from(ca != *cb);
+            row.push(substitute.min(prev[j + 1] + 1).min(row[j] + 1));
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+/// Return specs for all known components with default refs.
+pub fn default_specs() -> Vec<ComponentSpec> {
+    KNOWN_COMPONENTS
+        .iter()
+        .map(|name| ComponentSpec {
+            name: name.to_string(),
+            git_ref: None,
+            as_of_date: None,
+        })
+        .collect()
+}
+
+/// Apply an as-of date to all component specs that don't have an explicit git_ref.
+///
+/// Components with explicit refs (e.g., "pipeline:v0.50.0") are not modified since
+/// the user specified an explicit version to use.
+pub fn apply_as_of_date(specs: &mut [ComponentSpec], as_of: &str) {
+    for spec in specs {
+        if spec.git_ref.is_none() {
+            spec.as_of_date = Some(as_of.to_string());
+        }
+    }
+}
+
+/// Narrow a gauge tag expression so specs carrying any of `excluded` are skipped,
+/// e.g. `e2e` with `["chains"]` becomes `(e2e) & !chains`.
+pub fn exclude_tags(tags: &str, excluded: &[String]) -> String {
+    if excluded.is_empty() {
+        return tags.to_string();
+    }
+    let negated: Vec<String> = excluded.iter().map(|t| format!("!{}", t)).collect();
+    format!("({}) & {}", tags, negated.join(" & "))
+}
+
+/// Validate date format is YYYY-MM-DD.
+///
+/// Used by clap's value_parser for the --as-of flag.
+pub fn validate_date_format(s: &str) -> std::result::Result<String, String> {
+    match crate::timestamp::parse_date(s) {
+        Ok(d) if d.format("%Y-%m-%d").to_string() == s => Ok(s.to_string()),
+        _ => Err("Date must be a valid YYYY-MM-DD date (e.g., 2024-01-15)".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_components_for_test() {
+        assert_eq!(components_for_test("Tekton Chains tests", "Sign images: PIPELINES-27-TC01"), vec!["chains"]);
+        assert_eq!(
+            components_for_test("ManualApprovalGate Pipelines operator specs", "approve: PIPELINES-28-TC01"),
+            vec!["pipeline", "manual-approval-gate"]
+        );
+        assert_eq!(components_for_test("Verify Ecosystem Tasks E2E spec", "buildah: PIPELINES-29-TC01"), vec!["pipeline"]);
+        assert_eq!(components_for_test("Verify Triggers E2E spec", "github push"), vec!["triggers"]);
+        assert!(components_for_test("Verify openshift monitoring", "PIPELINES-30-TC01").is_empty());
+    }
+
+    #[test]
+    fn test_exclude_tags() {
+        assert_eq!(exclude_tags("e2e", &[]), "e2e");
+        assert_eq!(
+            exclude_tags("e2e | sanity", &["chains".to_string(), "pac".to_string()]),
+            "(e2e | sanity) & !chains & !pac"
+        );
+    }
+
+    #[test]
+    fn test_parse_component_specs_groups() {
+        let groups = BTreeMap::from([("core".to_string(), vec!["pipeline".to_string(), "triggers".to_string(), "chains".to_string()])]);
+        let specs = parse_component_specs("triggers:v0.28.0,core,results", &groups).unwrap();
+        let names: Vec<&str> = specs.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["triggers", "pipeline", "chains", "results"]);
+        assert_eq!(specs[0].git_ref.as_deref(), Some("v0.28.0"));
+
+        let all = parse_component_specs("all,pipeline:pr/123", &groups).unwrap();
+        assert_eq!(all.len(), KNOWN_COMPONENTS.len());
+        assert_eq!(all[0].git_ref.as_deref(), Some("pr/123"));
+
+        assert!(parse_component_specs("core:main", &groups).unwrap_err().contains("cannot take a ref"));
+        let err = parse_component_specs("pipeline,trigers", &groups).unwrap_err();
+        assert!(err.contains("did you mean 'triggers'?"), "{err}");
+        assert!(parse_component_specs("cor", &groups).unwrap_err().contains("did you mean 'core'?"));
+        assert!(!parse_component_specs("nonsense", &groups).unwrap_err().contains("did you mean"));
+    }
+}
@@ -0,0 +1,835 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+
+/// Configuration for a single Tekton component (e.g., pipeline, triggers).
+#[derive(Debug, Deserialize)]
+pub struct ComponentConfig {
+    pub repo: String,
+    /// Import paths for ko build (e.g. ["./cmd/controller", "./cmd/webhook"]).
+    #[serde(default)]
+    pub import_paths: Vec<String>,
+    /// Maps short image name (e.g. "controller") to IMAGE_ env var name.
+    pub images: HashMap<String, String>,
+    /// Build system: "ko" (default) or "docker". If None, defaults to ko.
+    #[serde(default)]
+    pub build_system: Option<String>,
+    /// Override prefix for InstallerSet matching. If None, uses component name.
+    #[serde(default)]
+    pub installer_set_prefix: Option<String>,
+    /// Components that must be deployed and reconciled before this one.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// ko build options (base image, ldflags, tags, platforms). Ignored for docker builds.
+    #[serde(default)]
+    pub ko: KoConfig,
+    /// Published nightly releases, for `run --source nightly`. None if upstream has none.
+    #[serde(default)]
+    pub nightly: Option<ReleaseManifests>,
+    /// Published upstream releases, for `run --source release`.
+    #[serde(default)]
+    pub release: Option<ReleaseManifests>,
+    /// Gauge tags of the release-tests specs that need this component. Excluded
+    /// from the run when its build fails under `--continue-on-build-failure`.
+    #[serde(default)]
+    pub test_tags: Vec<String>,
+    /// Gauge tag expression selecting this component's specs for `run --tags auto`
+    /// (e.g. "triggers & e2e"). Defaults to its `test_tags` and "e2e".
+    #[serde(default)]
+    pub auto_tags: Option<String>,
+}
+
+/// Where a component's published release manifests live, from the
+/// `[<component>.nightly]` and `[<component>.release]` tables.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseManifests {
+    /// Nightly: directory holding `latest/` and `previous/<version>/`
+    /// (e.g. "https://storage.googleapis.com/tekton-releases-nightly/pipeline").
+    /// Release: directory of one release, with `{version}` substituted
+    /// (e.g. "https://storage.googleapis.com/tekton-releases/pipeline/previous/{version}").
+    pub url: String,
+    /// Manifests in each release directory that reference the images.
+    #[serde(default = "default_release_files")]
+    pub files: Vec<String>,
+}
+
+fn default_release_files() -> Vec<String> {
+    vec!["release.yaml".to_string()]
+}
+
+/// Per-component ko options, from the `[<component>.ko]` table.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KoConfig {
+    /// Base image for all images (`defaultBaseImage` in .ko.yaml).
+    #[serde(default)]
+    pub base_image: Option<String>,
+    /// Base image per import path (e.g. "./cmd/nop" = "gcr.io/distroless/static").
+    #[serde(default)]
+    pub base_image_overrides: HashMap<String, String>,
+    /// Go build flags for every import path (e.g. ["-trimpath"]).
+    #[serde(default)]
+    pub flags: Vec<String>,
+    /// Linker flags for every import path (e.g. ["-s", "-w"]).
+    #[serde(default)]
+    pub ldflags: Vec<String>,
+    /// Build environment for every import path (e.g. ["CGO_ENABLED=0"]).
+    #[serde(default)]
+    pub env: Vec<String>,
+    /// Image tags; `{component}`, `{ref}` and `{date}` (YYYYMMDD, UTC) are substituted.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Target platforms (e.g. ["linux/amd64", "linux/arm64"]).
+    #[serde(default)]
+    pub platforms: Vec<String>,
+    /// Extra arguments appended to `ko build` as-is.
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+/// Top-level config: keys are component names, values are ComponentConfig,
+/// plus the optional `[groups]` table.
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    /// Named component groups for --components (e.g. `core = ["pipeline", "triggers", "chains"]`).
+    #[serde(default)]
+    pub groups: BTreeMap<String, Vec<String>>,
+    #[serde(flatten)]
+    pub components: HashMap<String, ComponentConfig>,
+}
+
+/// Load component configuration from a TOML file.
+pub fn load_config(path: &Path) -> anyhow::Result<Config> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    let config: Config =
+        toml::from_str(&content).with_context(|| format!("Failed to parse config: {}", path.display()))?;
+    let mut names: Vec<String> = config.components.keys().cloned().collect();
+    names.sort();
+    deploy_groups(&config, &names).with_context(|| format!("Invalid config: {}", path.display()))?;
+    validate_groups(&config).with_context(|| format!("Invalid config: {}", path.display()))?;
+    Ok(config)
+}
+
+/// Component groups from the default config, or none if it cannot be loaded
+/// (the error surfaces where the components themselves are loaded).
+pub fn load_groups() -> BTreeMap<String, Vec<String>> {
+    load_config(&default_config_path()).map(|c| c.groups).unwrap_or_default()
+}
+
+/// Groups must name configured components and must not shadow a component or `all`.
+fn validate_groups(config: &Config) -> anyhow::Result<()> {
+    for (group, members) in &config.groups {
+        if group == "all" || config.components.contains_key(group) {
+            bail!("Group '{}' shadows a component name or the built-in 'all' group", group);
+        }
+        if members.is_empty() {
+            bail!("Group '{}' is empty", group);
+        }
+        if let Some(unknown) = members.iter().find(|m| !config.components.contains_key(*m)) {
+            bail!("Group '{}' names unknown component '{}'", group, unknown);
+        }
+    }
+    Ok(())
+}
+
+/// Gauge tag expression for `run --tags auto`: the union of the `selected`
+/// components' `auto_tags`. A component with neither `auto_tags` nor `test_tags`
+/// (e.g. pipeline, which nearly every spec exercises) selects the whole e2e suite.
+pub fn auto_tags(config: &Config, selected: &[String]) -> String {
+    let mut exprs: Vec<String> = Vec::new();
+    for name in selected {
+        let comp = config.components.get(name);
+        let expr = match (comp.and_then(|c| c.auto_tags.clone()), comp.map(|c| &c.test_tags)) {
+            (Some(expr), _) => expr,
+            (None, Some(tags)) if tags.len() == 1 => format!("{} & e2e", tags[0]),
+            (None, Some(tags)) if !tags.is_empty() => format!("({}) & e2e", tags.join(" | ")),
+            _ => return "e2e".to_string(),
+        };
+        if !exprs.contains(&expr) {
+            exprs.push(expr);
+        }
+    }
+    match exprs.len() {
+        0 => "e2e".to_string(),
+        1 => exprs.remove(0),
+        _ => exprs.iter().map(|e| format!("({})", e)).collect::<Vec<_>>().join(" | "),
+    }
+}
+
+/// Order `selected` components into deploy groups by `depends_on`.
+///
+/// Every component in a group depends only on components in earlier groups, so
+/// groups run in sequence and the components within a group can run in parallel.
+/// Dependencies on components that are not selected are skipped (the operator's
+/// own images stay in place for them). Within a group, the order of `selected` is kept.
+pub fn deploy_groups(config: &Config, selected: &[String]) -> anyhow::Result<Vec<Vec<String>>> {
+    for name in selected {
+        let Some(comp) = config.components.get(name) else {
+            continue;
+        };
+        for dep in &comp.depends_on {
+            if !config.components.contains_key(dep) {
+                bail!("Component '{}' depends on unknown component '{}'", name, dep);
+            }
+        }
+    }
+
+    let selected_set: HashSet<&str> = selected.iter().map(|s| s.as_str()).collect();
+    let mut done: HashSet<&str> = HashSet::new();
+    let mut remaining: Vec<&str> = selected.iter().map(|s| s.as_str()).collect();
+    let mut groups = Vec::new();
+
+    while !remaining.is_empty() {
+        let ready: Vec<&str> = remaining
+            .iter()
+            .copied()
+            .filter(|name| {
+                config.components.get(*name).is_none_or(|c| {
+                    c.depends_on
+                        .iter()
+                        .all(|d| !selected_set.contains(d.as_str()) || done.contains(d.as_str()))
+                })
+            })
+            .collect();
+
+        if ready.is_empty() {
+            bail!("Dependency cycle between components: {}", remaining.join(", "));
+        }
+
+        done.extend(ready.iter().copied());
+        remaining.retain(|name| !ready.contains(name));
+        groups.push(ready.into_iter().map(String::from).collect());
+    }
+
+    Ok(groups)
+}
+
+/// Gauge versions known to work with release-tests, from `config/gauge.toml`.
+#[derive(Debug, Default, Deserialize)]
+pub struct GaugeConfig {
+    /// Gauge core version; a different one is reported but not replaced.
+    #[serde(default)]
+    pub gauge: Option<String>,
+    /// Plugin name to pinned version.
+    #[serde(default)]
+    pub plugins: BTreeMap<String, String>,
+    /// release-tests repository to clone instead of openshift-pipelines/release-tests
+    #[serde(default)]
+    pub release_tests_repo: Option<String>,
+}
+
+/// Load the gauge pins, or no pins if the file does not exist.
+pub fn load_gauge_config(path: &Path) -> anyhow::Result<GaugeConfig> {
+    if !path.exists() {
+        return Ok(GaugeConfig::default());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse config: {}", path.display()))
+}
+
+/// Returns the default path to `config/gauge.toml` (in-cluster: /etc/streamstress/gauge.toml).
+pub fn default_gauge_config_path() -> PathBuf {
+    default_config_path().with_file_name("gauge.toml")
+}
+
+/// Pinned tool versions and checksums, from `config/tools.toml`.
+#[derive(Debug, Default, Deserialize)]
+pub struct ToolPinsConfig {
+    /// Tool name (ko, gauge, oc, opm, skopeo, buildah) to its pin.
+    #[serde(default)]
+    pub tools: BTreeMap<String, ToolPin>,
+}
+
+/// What a tool must be; unset fields are not checked.
+#[derive(Debug, Default, Deserialize)]
+pub struct ToolPin {
+    /// Version as printed by the tool, with or without a leading `v`
+    #[serde(default)]
+    pub version: Option<String>,
+    /// sha256 of the binary on PATH
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+/// Load the tool pins, or no pins if the file does not exist.
+pub fn load_tool_pins(path: &Path) -> anyhow::Result<ToolPinsConfig> {
+    if !path.exists() {
+        return Ok(ToolPinsConfig::default());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse config: {}", path.display()))
+}
+
+/// Returns the default path to `config/tools.toml` (in-cluster: /etc/streamstress/tools.toml).
+pub fn default_tool_pins_path() -> PathBuf {
+    default_config_path().with_file_name("tools.toml")
+}
+
+/// Clone and fetch settings, from `config/git.toml`.
+#[derive(Debug, Default, Deserialize)]
+pub struct GitConfig {
+    /// Retries for a clone or fetch that fails with a network error (default 3)
+    #[serde(default)]
+    pub retries: Option<u32>,
+    /// URL prefix to the prefix of the mirror it is fetched from first.
+    #[serde(default)]
+    pub mirrors: BTreeMap<String, String>,
+}
+
+/// Load the git settings, or the defaults if the file does not exist.
+pub fn load_git_config(path: &Path) -> anyhow::Result<GitConfig> {
+    if !path.exists() {
+        return Ok(GitConfig::default());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse config: {}", path.display()))
+}
+
+/// Returns the default path to `config/git.toml` (in-cluster: /etc/streamstress/git.toml).
+pub fn default_git_config_path() -> PathBuf {
+    default_config_path().with_file_name("git.toml")
+}
+
+/// What makes a cluster look like production, from `config/safety.toml`.
+#[derive(Debug, Deserialize)]
+pub struct SafetyConfig {
+    /// Ingress domains (or parent domains) that are safe to modify regardless of the checks below.
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    /// Regexes matched against the ingress domain (e.g. "apps.prod-east.example.com").
+    #[serde(default = "default_production_domain_patterns")]
+    pub production_domain_patterns: Vec<String>,
+    /// "key=value" labels on the ClusterVersion that mark a production cluster.
+    #[serde(default = "default_production_labels")]
+    pub production_labels: Vec<String>,
+}
+
+impl Default for SafetyConfig {
+    fn default() -> Self {
+        SafetyConfig {
+            allowed_domains: Vec::new(),
+            production_domain_patterns: default_production_domain_patterns(),
+            production_labels: default_production_labels(),
+        }
+    }
+}
+
+fn default_production_domain_patterns() -> Vec<String> {
+    vec![r"(^|[.-])prod(uction)?([.-]|$)".to_string()]
+}
+
+fn default_production_labels() -> Vec<String> {
+    ["env=prod", "env=production", "environment=prod", "environment=production"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Load the production-cluster checks, or the built-in ones if the file does not exist.
+pub fn load_safety_config(path: &Path) -> anyhow::Result<SafetyConfig> {
+    if !path.exists() {
+        return Ok(SafetyConfig::default());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    let config: SafetyConfig =
+        toml::from_str(&content).with_context(|| format!("Failed to parse config: {}", path.display()))?;
+    for pattern in &config.production_domain_patterns {
+        regex::Regex::new(pattern)
+            .with_context(|| format!("Invalid production_domain_patterns entry '{}' in {}", pattern, path.display()))?;
+    }
+    if let Some(label) = config.production_labels.iter().find(|l| !l.contains('=')) {
+        bail!("Invalid production_labels entry '{}' in {} (expected key=value)", label, path.display());
+    }
+    Ok(config)
+}
+
+/// Returns the default path to `config/safety.toml`.
+pub fn default_safety_config_path() -> PathBuf {
+    default_config_path().with_file_name("safety.toml")
+}
+
+/// Cluster prep the release-tests specs depend on, from `config/test-hooks.toml`.
+#[derive(Debug, Default, Deserialize)]
+pub struct TestHooksConfig {
+    /// Hooks in the order they are set up; torn down in reverse.
+    #[serde(default)]
+    pub hooks: Vec<TestHook>,
+}
+
+/// One piece of test data: a manifest applied before the tests and deleted
+/// after, or a setup script with an optional teardown script.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TestHook {
+    pub name: String,
+    /// Manifest (file or directory) for `oc apply -f`, relative to the config file.
+    #[serde(default)]
+    pub manifest: Option<String>,
+    /// Script run with `sh` before the tests, relative to the config file.
+    #[serde(default)]
+    pub setup: Option<String>,
+    /// Script run with `sh` after the tests, relative to the config file.
+    #[serde(default)]
+    pub teardown: Option<String>,
+    /// Whether a failed setup stops the tests; optional hooks only warn.
+    #[serde(default = "default_true")]
+    pub required: bool,
+    /// Seconds each step of the hook may take.
+    #[serde(default = "default_hook_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_hook_timeout_secs() -> u64 {
+    600
+}
+
+/// Load the test hooks, or none if the file does not exist.
+pub fn load_test_hooks_config(path: &Path) -> anyhow::Result<TestHooksConfig> {
+    if !path.exists() {
+        return Ok(TestHooksConfig::default());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    let config: TestHooksConfig =
+        toml::from_str(&content).with_context(|| format!("Failed to parse config: {}", path.display()))?;
+    validate_test_hooks(&config).with_context(|| format!("Invalid test hooks in {}", path.display()))?;
+    Ok(config)
+}
+
+fn validate_test_hooks(config: &TestHooksConfig) -> anyhow::Result<()> {
+    let mut names = HashSet::new();
+    for hook in &config.hooks {
+        if hook.name.is_empty() || !names.insert(hook.name.as_str()) {
+            bail!("hook names must be non-empty and unique (got '{}')", hook.name);
+        }
+        match (&hook.manifest, &hook.setup, &hook.teardown) {
+            (Some(_), None, None) | (None, Some(_), _) => {}
+            (Some(_), _, _) => bail!("hook '{}' has both a manifest and scripts", hook.name),
+            (None, None, _) => bail!("hook '{}' needs a manifest or a setup script", hook.name),
+        }
+    }
+    Ok(())
+}
+
+/// Returns the default path to `config/test-hooks.toml` (in-cluster: /etc/streamstress/test-hooks.toml).
+pub fn default_test_hooks_config_path() -> PathBuf {
+    default_config_path().with_file_name("test-hooks.toml")
+}
+
+/// Upstream source paths mapped to the gauge tags of the specs they affect,
+/// from `config/impact.toml`.
+#[derive(Debug, Default, Deserialize)]
+pub struct ImpactConfig {
+    #[serde(default)]
+    pub rules: Vec<ImpactRule>,
+}
+
+/// Specs to run when a Go package under one of `paths` of `component` changes.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImpactRule {
+    pub component: String,
+    /// Package directories, matched with everything below them (e.g. "pkg/reconciler").
+    pub paths: Vec<String>,
+    /// Gauge tag expression of the affected specs (e.g. "triggers & e2e").
+    pub tags: String,
+}
+
+/// Load the impact mapping, or an empty one (every change is unmapped) if the file does not exist.
+pub fn load_impact_config(path: &Path) -> anyhow::Result<ImpactConfig> {
+    if !path.exists() {
+        return Ok(ImpactConfig::default());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse config: {}", path.display()))
+}
+
+/// Returns the default path to `config/impact.toml` (in-cluster: /etc/streamstress/impact.toml).
+pub fn default_impact_config_path() -> PathBuf {
+    default_config_path().with_file_name("impact.toml")
+}
+
+/// What `publish` redacts from run data, from `config/scrub.toml`.
+#[derive(Debug, Deserialize)]
+pub struct ScrubConfig {
+    /// Secrets and identities; each match becomes `[REDACTED:<name>]`.
+    #[serde(default = "default_redact_patterns")]
+    pub redact: Vec<RedactPattern>,
+    /// Replace cluster hostnames with stable placeholders (host-1.redacted, ...).
+    #[serde(default = "default_true")]
+    pub anonymize_hostnames: bool,
+    /// Regexes matching the hostnames to anonymize.
+    #[serde(default = "default_hostname_patterns")]
+    pub hostname_patterns: Vec<String>,
+    /// Domains (and their subdomains) never anonymized, e.g. public registries.
+    #[serde(default = "default_keep_domains")]
+    pub keep_domains: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RedactPattern {
+    pub name: String,
+    pub regex: String,
+}
+
+impl Default for ScrubConfig {
+    fn default() -> Self {
+        ScrubConfig {
+            redact: default_redact_patterns(),
+            anonymize_hostnames: true,
+            hostname_patterns: default_hostname_patterns(),
+            keep_domains: default_keep_domains(),
+        }
+    }
+}
+
+fn default_redact_patterns() -> Vec<RedactPattern> {
+    [
+        ("github-token", r"\b(gh[pousr]_[A-Za-z0-9]{30,}|github_pat_[A-Za-z0-9_]{40,})"),
+        ("openshift-token", r"sha256~[A-Za-z0-9_-]{20,}"),
+        ("bearer-token", r"(?i)bearer\s+[A-Za-z0-9._~+/=-]{20,}"),
+        ("jwt", r"eyJ[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,}"),
+        ("url-credentials", r"[a-z][a-z0-9+.-]*://[^/\s:@]+:[^/\s@]+@"),
+        ("email", r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}"),
+        ("user", r#"\b[Uu]ser "[^"]+""#),
+    ]
+    .iter()
+    .map(|(name, regex)| RedactPattern { name: name.to_string(), regex: regex.to_string() })
+    .collect()
+}
+
+fn default_hostname_patterns() -> Vec<String> {
+    vec![
+        r"\b[a-z0-9-]+(\.[a-z0-9-]+)*\.apps\.[a-z0-9-]+(\.[a-z0-9-]+)+".to_string(),
+        r"\bapi\.[a-z0-9-]+(\.[a-z0-9-]+)+".to_string(),
+    ]
+}
+
+fn default_keep_domains() -> Vec<String> {
+    ["github.com", "githubusercontent.com", "quay.io", "ghcr.io", "gcr.io", "docker.io", "redhat.com", "redhat.io", "k8s.io"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Load the scrub rules, or the built-in ones if the file does not exist.
+pub fn load_scrub_config(path: &Path) -> anyhow::Result<ScrubConfig> {
+    if !path.exists() {
+        return Ok(ScrubConfig::default());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse config: {}", path.display()))
+}
+
+/// Returns the default path to `config/scrub.toml` (in-cluster: /etc/streamstress/scrub.toml).
+pub fn default_scrub_config_path() -> PathBuf {
+    default_config_path().with_file_name("scrub.toml")
+}
+
+/// OLM Subscription auto-setup creates for the OpenShift Pipelines operator,
+/// from `config/operator.toml`; `--operator-*` flags override it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct OperatorConfig {
+    /// Subscription channel (e.g. "latest", "pipelines-1.15")
+    pub channel: String,
+    /// CatalogSource providing the operator
+    pub source: String,
+    /// Namespace of the CatalogSource
+    pub source_namespace: String,
+    /// CSV to install first (e.g. "openshift-pipelines-operator-rh.v1.15.2"); OLM
+    /// still upgrades to the channel head afterwards
+    pub starting_csv: Option<String>,
+    /// Approve the operator's InstallPlans that wait for manual approval
+    /// (installPlanApproval: Manual) instead of stopping at them
+    pub approve_install_plans: bool,
+}
+
+impl Default for OperatorConfig {
+    fn default() -> Self {
+        OperatorConfig {
+            channel: "latest".to_string(),
+            source: "redhat-operators".to_string(),
+            source_namespace: "openshift-marketplace".to_string(),
+            starting_csv: None,
+            approve_install_plans: false,
+        }
+    }
+}
+
+/// Load the operator Subscription settings, or the defaults if the file does not exist.
+pub fn load_operator_config(path: &Path) -> anyhow::Result<OperatorConfig> {
+    if !path.exists() {
+        return Ok(OperatorConfig::default());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse config: {}", path.display()))
+}
+
+/// Returns the default path to `config/operator.toml` (in-cluster: /etc/streamstress/operator.toml).
+pub fn default_operator_config_path() -> PathBuf {
+    default_config_path().with_file_name("operator.toml")
+}
+
+/// Which tasks of the Konflux release-test-pipeline hold gauge output, from
+/// `config/konflux.toml`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct KonfluxConfig {
+    /// Pipeline tasks whose step container runs gauge
+    pub e2e_tasks: Vec<String>,
+    /// Step container running gauge in those tasks
+    pub e2e_step_container: String,
+    /// Also scan the logs of the PipelineRun's other tasks for gauge output
+    pub discover_tasks: bool,
+}
+
+impl Default for KonfluxConfig {
+    fn default() -> Self {
+        KonfluxConfig {
+            e2e_tasks: ["e2e-test-pipelines", "e2e-test-metrics", "e2e-test-manualapprovalgate"]
+                .map(String::from)
+                .to_vec(),
+            e2e_step_container: "step-run-e2e-test".to_string(),
+            discover_tasks: true,
+        }
+    }
+}
+
+/// Load the Konflux log collection settings, defaulting when the file doesn't exist.
+pub fn load_konflux_config(path: &Path) -> anyhow::Result<KonfluxConfig> {
+    if !path.exists() {
+        return Ok(KonfluxConfig::default());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse config: {}", path.display()))
+}
+
+/// Returns the default path to `config/konflux.toml` (in-cluster: /etc/streamstress/konflux.toml).
+pub fn default_konflux_config_path() -> PathBuf {
+    default_config_path().with_file_name("konflux.toml")
+}
+
+/// When email reports are sent, from `config/email.toml`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EmailMode {
+    /// After every run
+    #[default]
+    Always,
+    /// Only after runs with failures
+    OnFailure,
+    /// Queue each run's summary; `streamstress email digest` sends them as one email
+    Digest,
+}
+
+/// SMTP email reports of run summaries, from `config/email.toml`. Credentials come
+/// from SMTP_USERNAME / SMTP_PASSWORD.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailConfig {
+    pub enabled: bool,
+    /// SMTP server URL: smtps://host:465, or smtp://host:587 (upgraded with STARTTLS)
+    pub smtp_url: String,
+    /// Fail instead of sending in clear text when the server offers no TLS
+    pub require_tls: bool,
+    pub from: String,
+    pub to: Vec<String>,
+    pub mode: EmailMode,
+    pub subject_prefix: String,
+    /// Queue of summaries for `mode = "digest"`; must outlive the runs (e.g. a mounted volume)
+    pub digest_file: String,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        EmailConfig {
+            enabled: false,
+            smtp_url: String::new(),
+            require_tls: true,
+            from: String::new(),
+            to: Vec::new(),
+            mode: EmailMode::Always,
+            subject_prefix: "[streamstress]".to_string(),
+            digest_file: "email-digest.jsonl".to_string(),
+        }
+    }
+}
+
+/// Load email settings, defaulting (disabled) when the file doesn't exist.
+pub fn load_email_config(path: &Path) -> anyhow::Result<EmailConfig> {
+    if !path.exists() {
+        return Ok(EmailConfig::default());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    let cfg: EmailConfig = toml::from_str(&content).with_context(|| format!("Failed to parse config: {}", path.display()))?;
+    if cfg.enabled && (cfg.smtp_url.is_empty() || cfg.from.is_empty() || cfg.to.is_empty()) {
+        bail!("{}: enabled email reports need smtp_url, from and to", path.display());
+    }
+    Ok(cfg)
+}
+
+/// Returns the default path to `config/email.toml` (in-cluster: /etc/streamstress/email.toml).
+pub fn default_email_config_path() -> PathBuf {
+    default_config_path().with_file_name("email.toml")
+}
+
+/// Returns the default path to `config/components.toml`.
+/// When running in-cluster (STREAMSTRESS_INCLUSTER=1), uses /etc/streamstress/components.toml.
+/// Otherwise, uses config/components.toml relative to the current directory.
+pub fn default_config_path() -> PathBuf {
+    if std::env::var("STREAMSTRESS_INCLUSTER").is_ok() {
+        PathBuf::from("/etc/streamstress/components.toml")
+    } else {
+        PathBuf::from("config/components.toml")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(toml_str: &str) -> Config {
+        toml::from_str(toml_str).unwrap()
+    }
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    const CHAIN: &str = r#"
+        [pipeline]
+        repo = "p"
+        images = {}
+
+        [triggers]
+        repo = "t"
+        images = {}
+        depends_on = ["pipeline"]
+
+        [chains]
+        repo = "c"
+        images = {}
+        depends_on = ["pipeline"]
+
+        [console-plugin]
+        repo = "cp"
+        images = {}
+        depends_on = ["triggers"]
+    "#;
+
+    #[test]
+    fn test_deploy_groups_orders_by_dependency() {
+        let cfg = config(CHAIN);
+        let groups = deploy_groups(&cfg, &names(&["console-plugin", "chains", "triggers", "pipeline"])).unwrap();
+        assert_eq!(
+            groups,
+            vec![names(&["pipeline"]), names(&["chains", "triggers"]), names(&["console-plugin"])]
+        );
+    }
+
+    #[test]
+    fn test_deploy_groups_skips_unselected_dependencies() {
+        let cfg = config(CHAIN);
+        let groups = deploy_groups(&cfg, &names(&["console-plugin", "chains"])).unwrap();
+        assert_eq!(groups, vec![names(&["console-plugin", "chains"])]);
+    }
+
+    #[test]
+    fn test_deploy_groups_detects_cycle() {
+        let cfg = config(
+            r#"
+            [a]
+            repo = "a"
+            images = {}
+            depends_on = ["b"]
+
+            [b]
+            repo = "b"
+            images = {}
+            depends_on = ["a"]
+        "#,
+        );
+        let err = deploy_groups(&cfg, &names(&["a", "b"])).unwrap_err();
+        assert!(err.to_string().contains("Dependency cycle"));
+    }
+
+    #[test]
+    fn test_deploy_groups_rejects_unknown_dependency() {
+        let cfg = config(
+            r#"
+            [a]
+            repo = "a"
+            images = {}
+            depends_on = ["missing"]
+        "#,
+        );
+        let err = deploy_groups(&cfg, &names(&["a"])).unwrap_err();
+        assert!(err.to_string().contains("unknown component 'missing'"));
+    }
+
+    #[test]
+    fn test_auto_tags() {
+        let cfg = config(
+            r#"
+            [pipeline]
+            repo = "p"
+            images = {}
+
+            [triggers]
+            repo = "t"
+            images = {}
+            test_tags = ["triggers"]
+
+            [chains]
+            repo = "c"
+            images = {}
+            auto_tags = "chains & sanity"
+
+            [results]
+            repo = "r"
+            images = {}
+            test_tags = ["results", "results-api"]
+        "#,
+        );
+        assert_eq!(auto_tags(&cfg, &names(&["triggers"])), "triggers & e2e");
+        assert_eq!(
+            auto_tags(&cfg, &names(&["triggers", "chains", "results"])),
+            "(triggers & e2e) | (chains & sanity) | ((results | results-api) & e2e)"
+        );
+        assert_eq!(auto_tags(&cfg, &names(&["pipeline", "triggers"])), "e2e");
+    }
+
+    #[test]
+    fn test_validate_test_hooks() {
+        let hooks = |toml_str: &str| validate_test_hooks(&toml::from_str(toml_str).unwrap());
+        assert!(hooks(
+            r#"
+            [[hooks]]
+            name = "ns"
+            manifest = "hooks/ns.yaml"
+            [[hooks]]
+            name = "secret"
+            setup = "hooks/secret.sh"
+            teardown = "hooks/secret-cleanup.sh"
+        "#
+        )
+        .is_ok());
+        assert!(hooks("[[hooks]]\nname = \"a\"\nmanifest = \"a.yaml\"\nsetup = \"a.sh\"").is_err());
+        assert!(hooks("[[hooks]]\nname = \"a\"\nteardown = \"a.sh\"").is_err());
+        assert!(hooks("[[hooks]]\nname = \"a\"\nsetup = \"a.sh\"\n[[hooks]]\nname = \"a\"\nsetup = \"b.sh\"").is_err());
+    }
+}
@@ -0,0 +1,81 @@
+//! Confirmation prompts before destructive cluster changes (`--yes`).
+//!
+//! Patching the operator Deployment, deleting InstallerSets and binding
+//! cluster roles first list exactly what will change and ask for a yes on the
+//! terminal. `--yes` (or `--non-interactive`) answers for the user; runs
+//! without a terminal on stdin and stderr (CI, in-cluster Jobs, pipes) are not
+//! asked either, so automation behaves as before.
+
+use anyhow::{bail, Context, Result};
+use std::io::{BufRead, IsTerminal, Write};
+use std::sync::{Mutex, OnceLock};
+
+static ASSUME_YES: OnceLock<bool> = OnceLock::new();
+
+/// Parallel deploys must not ask at the same time.
+static PROMPT: Mutex<()> = Mutex::new(());
+
+/// Set whether prompts are answered with yes. Call once at startup.
+pub fn init(assume_yes: bool) {
+    let _ = ASSUME_YES.set(assume_yes);
+}
+
+/// CLI args recreating this setting (for self-invocation).
+pub fn to_args() -> Vec<String> {
+    if ASSUME_YES.get().copied().unwrap_or_default() {
+        vec!["--yes".to_string()]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Whether `confirm` will ask; callers skip gathering the change list otherwise.
+/// A `--replay` changes nothing, so it is not asked about.
+pub fn required() -> bool {
+    !ASSUME_YES.get().copied().unwrap_or_default()
+        && !crate::cassette::replaying()
+        && std::io::stdin().is_terminal()
+        && std::io::stderr().is_terminal()
+}
+
+/// Ask before `action`, listing `changes` (one line each). Fails unless the
+/// user answers yes; returns at once when no prompt is `required`.
+pub fn confirm(action: &str, changes: &[String]) -> Result<()> {
+    if !required() {
+        return Ok(());
+    }
+    let _guard = PROMPT.lock().unwrap_or_else(|e| e.into_inner());
+    let mut stderr = std::io::stderr().lock();
+    writeln!(stderr, "\nAbout to {}:", action)?;
+    for change in changes {
+        writeln!(stderr, "  - {}", change)?;
+    }
+    write!(stderr, "Proceed? [y/N] ")?;
+    stderr.flush()?;
+    drop(stderr);
+
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer).context("Failed to read the answer")?;
+    if !is_yes(&answer) {
+        bail!("Not confirmed: did not {} (pass --yes to skip this prompt)", action);
+    }
+    Ok(())
+}
+
+fn is_yes(answer: &str) -> bool {
+    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_yes() {
+        assert!(is_yes("y\n"));
+        assert!(is_yes(" YES \n"));
+        assert!(!is_yes("\n"));
+        assert!(!is_yes("n\n"));
+        assert!(!is_yes("yep\n"));
+    }
+}
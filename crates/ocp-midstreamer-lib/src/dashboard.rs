@@ -0,0 +1,167 @@
+//! Local preview server for a gh-pages dashboard tree.
+//!
+//! Serves the output of `publish --dry-run` over plain HTTP on localhost so a run's
+//! dashboard rendering can be checked before it is made public.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::thread;
+
+use crate::warnings;
+
+/// Serve `dir` on `bind:port` until interrupted.
+pub fn serve(dir: &Path, bind: &str, port: u16) -> Result<()> {
+    let root = dir
+        .canonicalize()
+        .with_context(|| format!("Dashboard directory {} not found", dir.display()))?;
+    if !root.join("index.html").exists() {
+        warnings::warn(format!("{} has no index.html; is this a gh-pages tree?", root.display()));
+    }
+
+    let listener = TcpListener::bind((bind, port))
+        .with_context(|| format!("Failed to bind {}:{}", bind, port))?;
+    eprintln!("Serving {} at http://{}:{}/ (Ctrl+C to stop)", root.display(), bind, port);
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let root = root.clone();
+                thread::spawn(move || {
+                    if let Err(e) = handle_connection(stream, &root) {
+                        warnings::warn(format!("request failed: {e:#}"));
+                    }
+                });
+            }
+            Err(e) => warnings::warn(format!("connection failed: {}", e)),
+        }
+    }
+    Ok(())
+}
+
+fn handle_connection(mut stream: TcpStream, root: &Path) -> Result<()> {
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let target = parts.next().unwrap_or("/");
+
+    if method != "GET" && method != "HEAD" {
+        return respond(&mut stream, "405 Method Not Allowed", "text/plain", b"Method not allowed\n", false);
+    }
+
+    let Some(path) = resolve_path(root, target) else {
+        return respond(&mut stream, "404 Not Found", "text/plain", b"Not found\n", method == "HEAD");
+    };
+    match fs::read(&path) {
+        Ok(body) => {
+            eprintln!("  {} {}", method, target);
+            respond(&mut stream, "200 OK", content_type(&path), &body, method == "HEAD")
+        }
+        Err(_) => respond(&mut stream, "404 Not Found", "text/plain", b"Not found\n", method == "HEAD"),
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &[u8], head_only: bool) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    if !head_only {
+        stream.write_all(body)?;
+    }
+    stream.flush()?;
+    Ok(())
+}
+
+/// Map a request target to a file under `root`. Rejects anything that would escape
+/// the root, and serves `index.html` for directories.
+fn resolve_path(root: &Path, target: &str) -> Option<PathBuf> {
+    let path = target.split(['?', '#']).next().unwrap_or("/");
+    let path = percent_decode(path);
+
+    let mut resolved = root.to_path_buf();
+    for component in Path::new(path.trim_start_matches('/')).components() {
+        match component {
+            Component::Normal(c) => resolved.push(c),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    if resolved.is_dir() {
+        resolved.push("index.html");
+    }
+    resolved.is_file().then_some(resolved)
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 2 < bytes.len()
+            && let Some(b) = std::str::from_utf8(&bytes[i + 1..i + 3])
+                .ok()
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+        {
+            out.push(b);
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "html" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" => "application/javascript; charset=utf-8",
+        "json" => "application/json",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "ico" => "image/x-icon",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_path_rejects_traversal() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("index.html"), "ok").unwrap();
+        assert!(resolve_path(dir.path(), "/../etc/passwd").is_none());
+        assert!(resolve_path(dir.path(), "/%2e%2e/etc/passwd").is_none());
+    }
+
+    #[test]
+    fn test_resolve_path_serves_index_for_root() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("index.html"), "ok").unwrap();
+        fs::create_dir(dir.path().join("runs")).unwrap();
+        fs::write(dir.path().join("runs/manifest.json"), "{}").unwrap();
+        assert_eq!(resolve_path(dir.path(), "/"), Some(dir.path().join("index.html")));
+        assert_eq!(
+            resolve_path(dir.path(), "/runs/manifest.json?t=1"),
+            Some(dir.path().join("runs/manifest.json"))
+        );
+    }
+
+    #[test]
+    fn test_content_type() {
+        assert_eq!(content_type(Path::new("a/app.js")), "application/javascript; charset=utf-8");
+        assert_eq!(content_type(Path::new("runs/manifest.json")), "application/json");
+    }
+}
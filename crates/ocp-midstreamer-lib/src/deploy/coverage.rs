@@ -0,0 +1,180 @@
+//! Operator payload coverage: which IMAGE_ env vars of the installed CSV a run
+//! deployed upstream images for, and which still point at released images.
+//!
+//! A component's image missing from `images` in components.toml (or a component
+//! left out of the run) is deployed silently as the released image, and the
+//! tests then exercise it instead of upstream. This check makes that visible.
+
+use anyhow::{Context, Result};
+use kube::Client;
+use kube::api::{Api, ApiResource, DynamicObject, ListParams};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use tokio::runtime::Runtime;
+
+use crate::config::Config;
+use crate::k8s;
+
+use super::DeployReport;
+
+/// Prefix of the operator's CSV names (e.g. openshift-pipelines-operator-rh.v1.17.0).
+const CSV_PREFIX: &str = "openshift-pipelines-operator";
+
+/// A released image the run did not override.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct UncoveredImage {
+    pub env_var: String,
+    /// Component whose `images` map the env var; None if none does
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component: Option<String>,
+}
+
+/// IMAGE_ env vars of the installed CSV against those the run deployed.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ImageCoverage {
+    pub csv: String,
+    pub overridden: Vec<String>,
+    pub uncovered: Vec<UncoveredImage>,
+    /// Overridden env vars the CSV does not have (renamed or removed in this release)
+    pub unknown: Vec<String>,
+}
+
+impl ImageCoverage {
+    /// Uncovered images of components the run deployed, or owned by no component:
+    /// the ones a tester would assume to be upstream.
+    pub fn gaps<'a>(&'a self, deployed: &'a [String]) -> impl Iterator<Item = &'a UncoveredImage> {
+        self.uncovered
+            .iter()
+            .filter(move |u| u.component.as_ref().is_none_or(|c| deployed.contains(c)))
+    }
+}
+
+/// Compare the CSV's IMAGE_ env vars with those the run overrode.
+pub fn coverage(csv: &str, csv_env: &BTreeSet<String>, overridden: &BTreeSet<String>, config: &Config) -> ImageCoverage {
+    let owners: BTreeMap<&str, &str> = config
+        .components
+        .iter()
+        .flat_map(|(name, comp)| comp.images.values().map(move |env_var| (env_var.as_str(), name.as_str())))
+        .collect();
+    ImageCoverage {
+        csv: csv.to_string(),
+        overridden: overridden.iter().cloned().collect(),
+        uncovered: csv_env
+            .difference(overridden)
+            .map(|env_var| UncoveredImage {
+                env_var: env_var.clone(),
+                component: owners.get(env_var.as_str()).map(|c| c.to_string()),
+            })
+            .collect(),
+        unknown: overridden.difference(csv_env).cloned().collect(),
+    }
+}
+
+/// The env vars the deploys in `reports` set.
+pub fn overridden_env(reports: &[DeployReport]) -> BTreeSet<String> {
+    reports.iter().flat_map(|r| r.mappings.iter().map(|m| m.env_var.clone())).collect()
+}
+
+/// Name and IMAGE_ env vars (all deployments and containers) of the operator CSV in `namespace`.
+pub fn read_csv_image_env(rt: &Runtime, client: &Client, namespace: &str) -> Result<(String, BTreeSet<String>)> {
+    let ar = ApiResource {
+        group: "operators.coreos.com".into(),
+        version: "v1alpha1".into(),
+        api_version: "operators.coreos.com/v1alpha1".into(),
+        kind: "ClusterServiceVersion".into(),
+        plural: "clusterserviceversions".into(),
+    };
+    let api: Api<DynamicObject> = Api::namespaced_with(client.clone(), namespace, &ar);
+    let lp = ListParams::default();
+    let csvs = k8s::block_on_retry(rt, "list ClusterServiceVersions", || api.list(&lp))
+        .context("Failed to list ClusterServiceVersions")?;
+    let csv = csvs
+        .items
+        .iter()
+        .find(|c| c.metadata.name.as_deref().is_some_and(|n| n.starts_with(CSV_PREFIX)))
+        .with_context(|| format!("No {} CSV in {}", CSV_PREFIX, namespace))?;
+    Ok((csv.metadata.name.clone().unwrap_or_default(), csv_image_env(&csv.data)))
+}
+
+/// IMAGE_ env var names in a CSV's install strategy.
+fn csv_image_env(csv: &serde_json::Value) -> BTreeSet<String> {
+    let empty = Vec::new();
+    csv["spec"]["install"]["spec"]["deployments"]
+        .as_array()
+        .unwrap_or(&empty)
+        .iter()
+        .flat_map(|d| d["spec"]["template"]["spec"]["containers"].as_array().unwrap_or(&empty))
+        .flat_map(|c| c["env"].as_array().unwrap_or(&empty))
+        .filter_map(|e| e["name"].as_str())
+        .filter(|name| name.starts_with("IMAGE_"))
+        .map(String::from)
+        .collect()
+}
+
+/// Print the coverage gaps: one warning per released image a deployed component
+/// (or no component) leaves in place, a summary line for the rest.
+pub fn print_coverage(coverage: &ImageCoverage, deployed: &[String]) {
+    let gaps: Vec<&UncoveredImage> = coverage.gaps(deployed).collect();
+    eprintln!(
+        "\nOperator image coverage ({}): {} of {} IMAGE_ env vars overridden",
+        coverage.csv,
+        coverage.overridden.len() - coverage.unknown.len(),
+        coverage.overridden.len() - coverage.unknown.len() + coverage.uncovered.len(),
+    );
+    for gap in &gaps {
+        let owner = gap.component.as_deref().map(|c| format!(" ({} does not build it)", c)).unwrap_or_default();
+        crate::warnings::warn(format!(
+            "we are not overriding {}{} — tests will exercise the released image",
+            gap.env_var, owner
+        ));
+    }
+    let others = coverage.uncovered.len() - gaps.len();
+    if others > 0 {
+        eprintln!("  {} more released image(s) belong to components not in this run", others);
+    }
+    for env_var in &coverage.unknown {
+        crate::warnings::warn(format!("{} is not in {}: the override has no effect on this release", env_var, coverage.csv));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coverage() {
+        let config: Config = toml::from_str(
+            r#"
+            [pipeline]
+            repo = "tektoncd/pipeline"
+            images = { controller = "IMAGE_PIPELINES_CONTROLLER", webhook = "IMAGE_PIPELINES_WEBHOOK" }
+
+            [pac]
+            repo = "openshift-pipelines/pipelines-as-code"
+            images = { watcher = "IMAGE_PAC_WATCHER" }
+            "#,
+        )
+        .unwrap();
+        let csv = serde_json::json!({"spec": {"install": {"spec": {"deployments": [
+            {"spec": {"template": {"spec": {"containers": [{"env": [
+                {"name": "IMAGE_PIPELINES_CONTROLLER", "value": "a"},
+                {"name": "IMAGE_PIPELINES_WEBHOOK", "value": "b"},
+                {"name": "IMAGE_PAC_WATCHER", "value": "c"},
+                {"name": "IMAGE_ADDONS_TKN", "value": "d"},
+                {"name": "VERSION", "value": "1.17.0"},
+            ]}]}}}},
+        ]}}}});
+        let csv_env = csv_image_env(&csv);
+        assert_eq!(csv_env.len(), 4);
+
+        let overridden: BTreeSet<String> =
+            ["IMAGE_PIPELINES_CONTROLLER", "IMAGE_PIPELINES_RESOLVERS"].into_iter().map(String::from).collect();
+        let cov = coverage("openshift-pipelines-operator-rh.v1.17.0", &csv_env, &overridden, &config);
+        assert_eq!(cov.unknown, vec!["IMAGE_PIPELINES_RESOLVERS"]);
+        assert_eq!(cov.uncovered.len(), 3);
+
+        let deployed = vec!["pipeline".to_string()];
+        let gaps: Vec<&str> = cov.gaps(&deployed).map(|g| g.env_var.as_str()).collect();
+        assert_eq!(gaps, vec!["IMAGE_ADDONS_TKN", "IMAGE_PIPELINES_WEBHOOK"]);
+    }
+}
@@ -0,0 +1,269 @@
+//! Drift of the operator-rendered manifests across a deploy.
+//!
+//! The manifests in a component's TektonInstallerSets are snapshotted before
+//! they are deleted and again after the operator has re-created them. The diff
+//! shows which objects now reference the deployed images and anything else the
+//! operator rendered differently, to confirm the override took effect and
+//! nothing unexpected changed.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use kube::api::DynamicObject;
+
+/// Server-populated metadata that differs between renderings of the same object.
+const VOLATILE_METADATA: &[&str] = &[
+    "creationTimestamp",
+    "generation",
+    "managedFields",
+    "ownerReferences",
+    "resourceVersion",
+    "uid",
+];
+
+/// Longest value shown in the drift summary before it is cut short.
+const MAX_VALUE_DISPLAY: usize = 120;
+
+/// Rendered objects keyed by "Kind namespace/name", flattened to field path -> value.
+pub type ManifestSnapshot = BTreeMap<String, BTreeMap<String, String>>;
+
+/// One field that differs between the snapshots.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub path: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// Field changes of one object, split into image overrides and everything else.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ObjectDrift {
+    pub object: String,
+    pub image_changes: Vec<FieldChange>,
+    pub other_changes: Vec<FieldChange>,
+}
+
+/// Difference between the manifests rendered before and after a deploy.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ManifestDrift {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<ObjectDrift>,
+}
+
+impl ManifestDrift {
+    /// Objects whose only changes are image overrides.
+    pub fn image_only(&self) -> usize {
+        self.changed.iter().filter(|c| c.other_changes.is_empty()).count()
+    }
+}
+
+/// Snapshot the manifests rendered into `installer_sets`.
+pub fn snapshot(installer_sets: &[DynamicObject]) -> ManifestSnapshot {
+    let mut snap = ManifestSnapshot::new();
+    for set in installer_sets {
+        let manifests = set
+            .data
+            .get("spec")
+            .and_then(|s| s.get("manifests"))
+            .and_then(|m| m.as_array());
+        for manifest in manifests.into_iter().flatten() {
+            let mut fields = BTreeMap::new();
+            flatten(&strip_volatile(manifest), "", &mut fields);
+            snap.insert(object_key(manifest), fields);
+        }
+    }
+    snap
+}
+
+fn object_key(manifest: &Value) -> String {
+    let kind = manifest.get("kind").and_then(|v| v.as_str()).unwrap_or("?");
+    let meta = manifest.get("metadata");
+    let name = meta.and_then(|m| m.get("name")).and_then(|v| v.as_str()).unwrap_or("?");
+    match meta.and_then(|m| m.get("namespace")).and_then(|v| v.as_str()) {
+        Some(ns) => format!("{} {}/{}", kind, ns, name),
+        None => format!("{} {}", kind, name),
+    }
+}
+
+fn strip_volatile(manifest: &Value) -> Value {
+    let mut manifest = manifest.clone();
+    if let Some(meta) = manifest.get_mut("metadata").and_then(|m| m.as_object_mut()) {
+        for key in VOLATILE_METADATA {
+            meta.remove(*key);
+        }
+    }
+    if let Some(obj) = manifest.as_object_mut() {
+        obj.remove("status");
+    }
+    manifest
+}
+
+/// Flatten JSON to leaf paths like `spec.template.spec.containers[0].image`.
+fn flatten(value: &Value, path: &str, out: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                let child = if path.is_empty() { k.clone() } else { format!("{}.{}", path, k) };
+                flatten(v, &child, out);
+            }
+        }
+        Value::Array(items) => {
+            for (i, v) in items.iter().enumerate() {
+                flatten(v, &format!("{}[{}]", path, i), out);
+            }
+        }
+        Value::String(s) => {
+            out.insert(path.to_string(), s.clone());
+        }
+        other => {
+            out.insert(path.to_string(), other.to_string());
+        }
+    }
+}
+
+/// Diff two snapshots. A changed field counts as an image override when its new
+/// value references one of `images`.
+pub fn diff(before: &ManifestSnapshot, after: &ManifestSnapshot, images: &[String]) -> ManifestDrift {
+    let mut drift = ManifestDrift::default();
+    for key in after.keys().filter(|k| !before.contains_key(*k)) {
+        drift.added.push(key.clone());
+    }
+    for key in before.keys().filter(|k| !after.contains_key(*k)) {
+        drift.removed.push(key.clone());
+    }
+    for (key, old) in before {
+        let Some(new) = after.get(key) else {
+            continue;
+        };
+        let mut object = ObjectDrift {
+            object: key.clone(),
+            image_changes: Vec::new(),
+            other_changes: Vec::new(),
+        };
+        let paths: std::collections::BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+        for path in paths {
+            let (b, a) = (old.get(path), new.get(path));
+            if b == a {
+                continue;
+            }
+            let change = FieldChange {
+                path: path.clone(),
+                before: b.cloned(),
+                after: a.cloned(),
+            };
+            let is_image = a.is_some_and(|v| images.iter().any(|img| v.contains(img.as_str())));
+            if is_image {
+                object.image_changes.push(change);
+            } else {
+                object.other_changes.push(change);
+            }
+        }
+        if !object.image_changes.is_empty() || !object.other_changes.is_empty() {
+            drift.changed.push(object);
+        }
+    }
+    drift
+}
+
+/// Print the drift: image overrides per object, then anything else that changed.
+pub fn print_drift(component: &str, drift: &ManifestDrift, verbose: bool) {
+    eprintln!(
+        "\nManifest drift for {}: {} object(s) changed ({} image overrides only), {} added, {} removed",
+        component,
+        drift.changed.len(),
+        drift.image_only(),
+        drift.added.len(),
+        drift.removed.len()
+    );
+    for object in &drift.changed {
+        eprintln!("  ~ {}", object.object);
+        for c in &object.image_changes {
+            eprintln!("      image {}: {}", c.path, shorten(c.after.as_deref()));
+        }
+        if !object.other_changes.is_empty() {
+            if verbose {
+                for c in &object.other_changes {
+                    eprintln!(
+                        "      other {}: {} -> {}",
+                        c.path,
+                        shorten(c.before.as_deref()),
+                        shorten(c.after.as_deref())
+                    );
+                }
+            } else {
+                let paths: Vec<&str> = object.other_changes.iter().map(|c| c.path.as_str()).collect();
+                eprintln!("      other: {}", paths.join(", "));
+            }
+        }
+    }
+    for key in &drift.added {
+        eprintln!("  + {}", key);
+    }
+    for key in &drift.removed {
+        eprintln!("  - {}", key);
+    }
+}
+
+fn shorten(value: Option<&str>) -> String {
+    match value {
+        None => "(unset)".to_string(),
+        Some(v) if v.chars().count() > MAX_VALUE_DISPLAY => {
+            format!("{}...", v.chars().take(MAX_VALUE_DISPLAY).collect::<String>())
+        }
+        Some(v) => v.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn installer_set(manifests: Value) -> DynamicObject {
+        let mut obj = DynamicObject::new(
+            "pipeline-main-deployment-abc",
+            &kube::api::ApiResource {
+                group: "operator.tekton.dev".into(),
+                version: "v1alpha1".into(),
+                api_version: "operator.tekton.dev/v1alpha1".into(),
+                kind: "TektonInstallerSet".into(),
+                plural: "tektoninstallersets".into(),
+            },
+        );
+        obj.data = json!({"spec": {"manifests": manifests}});
+        obj
+    }
+
+    #[test]
+    fn test_diff_separates_image_overrides() {
+        let deployment = |image: &str, replicas: u32, uid: &str| {
+            json!({
+                "kind": "Deployment",
+                "metadata": {"name": "tekton-pipelines-controller", "namespace": "openshift-pipelines", "uid": uid},
+                "spec": {"replicas": replicas, "template": {"spec": {"containers": [{"image": image}]}}}
+            })
+        };
+        let config_map = json!({"kind": "ConfigMap", "metadata": {"name": "feature-flags", "namespace": "openshift-pipelines"}});
+        let before = snapshot(&[installer_set(json!([deployment("registry.redhat.io/controller@sha256:1", 1, "a"), config_map]))]);
+        let upstream = "image-registry.openshift-image-registry.svc:5000/tekton-upstream/controller";
+        let after = snapshot(&[installer_set(json!([
+            deployment(upstream, 2, "b"),
+            {"kind": "ClusterRole", "metadata": {"name": "tekton-new-role"}}
+        ]))]);
+
+        let drift = diff(&before, &after, &[upstream.to_string()]);
+        assert_eq!(drift.added, ["ClusterRole tekton-new-role"]);
+        assert_eq!(drift.removed, ["ConfigMap openshift-pipelines/feature-flags"]);
+        assert_eq!(drift.changed.len(), 1);
+        let object = &drift.changed[0];
+        assert_eq!(object.object, "Deployment openshift-pipelines/tekton-pipelines-controller");
+        assert_eq!(object.image_changes.len(), 1);
+        assert_eq!(object.image_changes[0].path, "spec.template.spec.containers[0].image");
+        // uid is volatile; replicas is a real re-rendering change
+        assert_eq!(object.other_changes.len(), 1);
+        assert_eq!(object.other_changes[0].path, "spec.replicas");
+        assert_eq!(drift.image_only(), 0);
+    }
+}
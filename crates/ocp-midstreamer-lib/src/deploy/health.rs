@@ -0,0 +1,156 @@
+//! Health of the operator's components for `streamstress status`.
+//!
+//! The TektonConfig, TektonPipeline and TektonTrigger CRs carry the operator's
+//! view of each component: a Ready condition, the version it reconciled, and
+//! the message of the condition that failed last. Summarizing them answers
+//! "is this cluster fit for testing" without several `oc get` calls.
+
+use anyhow::Result;
+use kube::api::{Api, ApiResource, DynamicObject, ListParams};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::k8s;
+
+/// Operator CRs summarized, as (kind, plural).
+const KINDS: &[(&str, &str)] = &[
+    ("TektonConfig", "tektonconfigs"),
+    ("TektonPipeline", "tektonpipelines"),
+    ("TektonTrigger", "tektontriggers"),
+];
+
+/// One operator CR.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComponentHealth {
+    pub kind: String,
+    pub name: String,
+    /// Ready condition; None when the operator has not reported one yet
+    pub ready: Option<bool>,
+    /// Version in the CR status
+    pub version: Option<String>,
+    /// Message of the Ready condition, or of another condition that is not True,
+    /// when the CR is not ready: the last reconcile error
+    pub error: Option<String>,
+}
+
+impl ComponentHealth {
+    /// READY column text.
+    pub fn ready_label(&self) -> &'static str {
+        match self.ready {
+            Some(true) => "True",
+            Some(false) => "False",
+            None => "Unknown",
+        }
+    }
+}
+
+/// Summarize the status of one operator CR.
+pub fn summarize(kind: &str, obj: &DynamicObject) -> ComponentHealth {
+    let status = obj.data.get("status");
+    let conditions: &[Value] = status
+        .and_then(|s| s.get("conditions"))
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let field = |cond: &Value, key: &str| cond.get(key).and_then(Value::as_str).map(str::to_string);
+
+    let ready_cond = conditions.iter().find(|c| c.get("type").and_then(Value::as_str) == Some("Ready"));
+    let ready = ready_cond.and_then(|c| field(c, "status")).map(|s| s == "True");
+    let error = if ready == Some(true) {
+        None
+    } else {
+        ready_cond
+            .and_then(|c| field(c, "message"))
+            .filter(|m| !m.is_empty())
+            .or_else(|| {
+                conditions
+                    .iter()
+                    .filter(|c| field(c, "status").as_deref() != Some("True"))
+                    .find_map(|c| field(c, "message").filter(|m| !m.is_empty()))
+            })
+    };
+    ComponentHealth {
+        kind: kind.to_string(),
+        name: obj.metadata.name.clone().unwrap_or_default(),
+        ready,
+        version: status.and_then(|s| s.get("version")).and_then(Value::as_str).map(str::to_string),
+        error,
+    }
+}
+
+/// Health of every TektonConfig, TektonPipeline and TektonTrigger on the cluster.
+/// Kinds whose CRD is missing (operator not installed) are left out.
+pub async fn collect(client: &kube::Client) -> Result<Vec<ComponentHealth>> {
+    let mut out = Vec::new();
+    for (kind, plural) in KINDS {
+        let ar = ApiResource {
+            group: "operator.tekton.dev".into(),
+            version: "v1alpha1".into(),
+            api_version: "operator.tekton.dev/v1alpha1".into(),
+            kind: kind.to_string(),
+            plural: plural.to_string(),
+        };
+        let api: Api<DynamicObject> = Api::all_with(client.clone(), &ar);
+        let lp = ListParams::default();
+        let list = match k8s::retry(&format!("list {}", kind), || api.list(&lp)).await {
+            Ok(list) => list,
+            Err(kube::Error::Api(resp)) if resp.code == 404 => continue,
+            Err(e) => return Err(e.into()),
+        };
+        out.extend(list.items.iter().map(|obj| summarize(kind, obj)));
+    }
+    Ok(out)
+}
+
+/// Whether every component is Ready (and there is at least one).
+pub fn all_ready(health: &[ComponentHealth]) -> bool {
+    !health.is_empty() && health.iter().all(|h| h.ready == Some(true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn obj(name: &str, status: Value) -> DynamicObject {
+        serde_json::from_value(serde_json::json!({
+            "apiVersion": "operator.tekton.dev/v1alpha1",
+            "kind": "TektonPipeline",
+            "metadata": { "name": name },
+            "status": status
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_summarize() {
+        let ready = summarize(
+            "TektonPipeline",
+            &obj("pipeline", serde_json::json!({
+                "version": "v0.65.1",
+                "conditions": [{ "type": "Ready", "status": "True" }]
+            })),
+        );
+        assert_eq!(ready.ready, Some(true));
+        assert_eq!(ready.version.as_deref(), Some("v0.65.1"));
+        assert_eq!(ready.error, None);
+
+        let failing = summarize(
+            "TektonPipeline",
+            &obj("pipeline", serde_json::json!({
+                "conditions": [
+                    { "type": "DependenciesInstalled", "status": "True" },
+                    { "type": "InstallerSetReady", "status": "False", "message": "webhook deployment not ready" },
+                    { "type": "Ready", "status": "False", "message": "" }
+                ]
+            })),
+        );
+        assert_eq!(failing.ready, Some(false));
+        assert_eq!(failing.error.as_deref(), Some("webhook deployment not ready"));
+        assert!(!all_ready(&[ready.clone(), failing]));
+        assert!(all_ready(&[ready]));
+
+        let fresh = summarize("TektonTrigger", &obj("trigger", serde_json::json!({})));
+        assert_eq!((fresh.ready, fresh.ready_label(), fresh.name.as_str()), (None, "Unknown", "trigger"));
+        assert!(!all_ready(&[]));
+    }
+}
@@ -0,0 +1,187 @@
+use anyhow::bail;
+
+use crate::config::{ComponentConfig, Config};
+
+/// Build image mappings from built image names to IMAGE_ env var keys.
+///
+/// For each built image, looks up the corresponding IMAGE_ env var from config.
+/// Returns vec of (env_var_key, full_image_ref) pairs.
+pub fn build_image_mappings(
+    config: &Config,
+    component: &str,
+    registry: &str,
+    built_images: &[String],
+) -> anyhow::Result<Vec<(String, String)>> {
+    let comp = config
+        .components
+        .get(component)
+        .ok_or_else(|| anyhow::anyhow!("Component '{component}' not found in config"))?;
+
+    let mut mappings = Vec::new();
+    for image_name in built_images {
+        let env_var = comp
+            .images
+            .get(image_name.as_str())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No IMAGE_ mapping found for built image '{image_name}' in component '{component}'"
+                )
+            })?;
+
+        let full_ref = format!("{registry}/{image_name}");
+        mappings.push((env_var.clone(), full_ref));
+    }
+
+    if mappings.is_empty() {
+        bail!("No image mappings produced for component '{component}'");
+    }
+
+    Ok(mappings)
+}
+
+/// Display a formatted table of IMAGE_ env var mappings to stderr.
+pub fn display_mapping_table(mappings: &[(String, String)]) {
+    let max_key_len = mappings.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+    eprintln!();
+    eprintln!("  {:<width$}  IMAGE", "ENV VAR", width = max_key_len);
+    eprintln!("  {:<width$}  -----", "-------", width = max_key_len);
+    for (key, value) in mappings {
+        eprintln!("  {:<width$}  {}", key, value, width = max_key_len);
+    }
+    eprintln!();
+}
+
+/// An `--image-override ENV_VAR=PULLSPEC` value: a pullspec set on the operator
+/// as-is, in place of the image streamstress would build for that env var.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageOverride {
+    pub env_var: String,
+    pub image: String,
+}
+
+impl ImageOverride {
+    /// The `--image-override` value this override was parsed from.
+    pub fn to_arg(&self) -> String {
+        format!("{}={}", self.env_var, self.image)
+    }
+}
+
+/// Parse `IMAGE_<NAME>=<pullspec>`. Used by clap's value_parser for --image-override.
+pub fn parse_image_override(s: &str) -> std::result::Result<ImageOverride, String> {
+    let Some((env_var, image)) = s.split_once('=') else {
+        return Err(format!("Expected ENV_VAR=PULLSPEC, got '{}'", s));
+    };
+    let (env_var, image) = (env_var.trim(), image.trim());
+    if !env_var.starts_with("IMAGE_") {
+        return Err(format!("'{}' is not an IMAGE_ env var of the operator", env_var));
+    }
+    if image.is_empty() || image.contains(char::is_whitespace) {
+        return Err(format!("Invalid pullspec '{}' for {}", image, env_var));
+    }
+    Ok(ImageOverride { env_var: env_var.to_string(), image: image.to_string() })
+}
+
+/// `--image-override` arguments reproducing `overrides`, for the in-cluster Job.
+pub fn to_args(overrides: &[ImageOverride]) -> Vec<String> {
+    overrides
+        .iter()
+        .flat_map(|o| ["--image-override".to_string(), o.to_arg()])
+        .collect()
+}
+
+/// The overrides to apply when deploying `component`: those of its own IMAGE_ env
+/// vars, plus (with `include_unowned`) those no component in `deployed` maps, so
+/// each override is applied by exactly one deploy.
+pub fn overrides_for(
+    config: &Config,
+    component: &str,
+    deployed: &[String],
+    overrides: &[ImageOverride],
+    include_unowned: bool,
+) -> Vec<ImageOverride> {
+    let owner = |env_var: &str| {
+        deployed
+            .iter()
+            .find(|name| config.components.get(name.as_str()).is_some_and(|c| c.images.values().any(|v| v == env_var)))
+            .map(|name| name.as_str())
+    };
+    overrides
+        .iter()
+        .filter(|o| match owner(&o.env_var) {
+            Some(name) => name == component,
+            None => include_unowned,
+        })
+        .cloned()
+        .collect()
+}
+
+/// Drop the images replaced by `overrides` from `comp`'s build (their `images`
+/// entries and ko import paths). Returns whether any image is left to build.
+pub fn skip_overridden_builds(comp: &mut ComponentConfig, overrides: &[ImageOverride]) -> bool {
+    let skipped: Vec<String> = comp
+        .images
+        .iter()
+        .filter(|(_, env_var)| overrides.iter().any(|o| &o.env_var == *env_var))
+        .map(|(name, _)| name.clone())
+        .collect();
+    comp.images.retain(|name, _| !skipped.contains(name));
+    comp.import_paths
+        .retain(|p| !skipped.iter().any(|name| p.rsplit('/').next() == Some(name.as_str())));
+    !comp.images.is_empty()
+}
+
+/// Set each override in `mappings`, replacing the mapping of the same env var or
+/// adding one.
+pub fn apply_overrides(mappings: &mut Vec<(String, String)>, overrides: &[ImageOverride]) {
+    for o in overrides {
+        match mappings.iter_mut().find(|(env_var, _)| *env_var == o.env_var) {
+            Some(m) => m.1 = o.image.clone(),
+            None => mappings.push((o.env_var.clone(), o.image.clone())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_overrides() {
+        let o = parse_image_override("IMAGE_PIPELINES_WEBHOOK=quay.io/me/webhook@sha256:abc").unwrap();
+        assert_eq!(o.to_arg(), "IMAGE_PIPELINES_WEBHOOK=quay.io/me/webhook@sha256:abc");
+        assert!(parse_image_override("PIPELINES_WEBHOOK=quay.io/me/webhook").is_err());
+        assert!(parse_image_override("IMAGE_PIPELINES_WEBHOOK=").is_err());
+
+        let extra = parse_image_override("IMAGE_ADDON_EXTRA=quay.io/me/extra:v1").unwrap();
+        let mut config: Config = toml::from_str(
+            r#"
+            [pipeline]
+            repo = "https://github.com/tektoncd/pipeline.git"
+            import_paths = ["./cmd/controller", "./cmd/webhook"]
+            images = { controller = "IMAGE_PIPELINES_CONTROLLER", webhook = "IMAGE_PIPELINES_WEBHOOK" }
+
+            [triggers]
+            repo = "https://github.com/tektoncd/triggers.git"
+            images = {}
+            "#,
+        )
+        .unwrap();
+
+        let overrides = vec![o.clone(), extra.clone()];
+        let deployed = vec!["pipeline".to_string(), "triggers".to_string()];
+        assert_eq!(overrides_for(&config, "pipeline", &deployed, &overrides, false), vec![o.clone()]);
+        assert_eq!(overrides_for(&config, "triggers", &deployed, &overrides, true), vec![extra.clone()]);
+        // Not deploying pipeline: its override goes with the first deploy
+        assert_eq!(overrides_for(&config, "triggers", &deployed[1..], &overrides, true).len(), 2);
+
+        let comp = config.components.get_mut("pipeline").unwrap();
+        assert!(skip_overridden_builds(comp, &overrides));
+        assert_eq!(comp.import_paths, vec!["./cmd/controller".to_string()]);
+        assert!(!skip_overridden_builds(comp, &[parse_image_override("IMAGE_PIPELINES_CONTROLLER=x/c").unwrap()]));
+
+        let mut mappings = vec![("IMAGE_PIPELINES_WEBHOOK".to_string(), "reg/webhook".to_string())];
+        apply_overrides(&mut mappings, &overrides);
+        assert_eq!(mappings[0].1, "quay.io/me/webhook@sha256:abc");
+        assert_eq!(mappings[1].0, "IMAGE_ADDON_EXTRA");
+    }
+}
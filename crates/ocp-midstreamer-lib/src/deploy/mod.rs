@@ -0,0 +1,293 @@
+pub mod coverage;
+pub mod drift;
+pub mod health;
+pub mod mapping;
+pub mod operator;
+pub mod pullsecret;
+pub mod state;
+pub mod wait;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{config, confirm, imagesource, k8s, progress, warnings};
+
+const INTERNAL_REGISTRY: &str = "image-registry.openshift-image-registry.svc:5000";
+
+/// Convert an external registry route to the internal cluster service URL.
+/// e.g. "default-route-openshift-image-registry.apps.example.com/tekton-upstream"
+///   -> "image-registry.openshift-image-registry.svc:5000/tekton-upstream"
+fn to_internal_registry(registry: &str) -> String {
+    // If it already uses the internal service, return as-is
+    if registry.starts_with(INTERNAL_REGISTRY) {
+        // Ensure namespace is present even if internal registry was passed bare
+        if !registry.contains("/tekton-upstream") {
+            return format!("{}/tekton-upstream", INTERNAL_REGISTRY);
+        }
+        return registry.to_string();
+    }
+    // Extract namespace path after the hostname (e.g. "/tekton-upstream")
+    if let Some(slash_pos) = registry.find('/') {
+        let namespace_path = &registry[slash_pos..];
+        format!("{}{}", INTERNAL_REGISTRY, namespace_path)
+    } else {
+        // No namespace in the path — default to tekton-upstream
+        format!("{}/tekton-upstream", INTERNAL_REGISTRY)
+    }
+}
+
+/// One IMAGE_ env var set on the operator Deployment.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImageMapping {
+    pub env_var: String,
+    pub image: String,
+}
+
+/// Outcome of a deploy, for `--output json|yaml`.
+#[derive(Debug, Serialize)]
+pub struct DeployReport {
+    pub component: String,
+    pub namespace: String,
+    pub deployment: String,
+    pub mappings: Vec<ImageMapping>,
+    pub installer_sets_deleted: u32,
+    pub reconciled: bool,
+    /// Published release deployed instead of a source build (`run --source
+    /// nightly|release`), e.g. "nightly v20250301-abc1234" or "v0.62.0".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published_release: Option<String>,
+    /// How the operator-rendered manifests changed (None if they could not be read).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drift: Option<drift::ManifestDrift>,
+}
+
+/// Run the deploy flow: verify operator, map images, patch operator deployment.
+///
+/// Images from an external registry are deployed as-is, with a pull secret
+/// linked in the Tekton namespaces (see `pullsecret`); `pull_secret_file` is an
+/// optional docker config holding the credentials. `overrides` (see
+/// `mapping::overrides_for`) are deployed as given instead of the built images
+/// of their env vars.
+pub fn run_deploy(
+    component: &str,
+    registry: &str,
+    built_images: &[String],
+    overrides: &[mapping::ImageOverride],
+    pull_secret_file: Option<&str>,
+    verbose: bool,
+) -> anyhow::Result<DeployReport> {
+    let (rt, client, config) = connect()?;
+
+    // Step 4: Build image mappings. For the cluster registry, pods pull from the
+    // internal service, not the external route; external registries are used as given
+    let external = pullsecret::is_external_registry(registry);
+    let pull_registry = if external {
+        registry.trim_end_matches('/').to_string()
+    } else {
+        to_internal_registry(registry)
+    };
+    let pb = progress::stage_spinner("Building image mappings");
+    let overridden = |name: &&String| {
+        config.components.get(component)
+            .and_then(|c| c.images.get(name.as_str()))
+            .is_some_and(|env_var| overrides.iter().any(|o| &o.env_var == env_var))
+    };
+    let built_images: Vec<String> = built_images.iter().filter(|n| !overridden(n)).cloned().collect();
+    let mut mappings = if built_images.is_empty() && !overrides.is_empty() {
+        Vec::new()
+    } else {
+        mapping::build_image_mappings(&config, component, &pull_registry, &built_images)?
+    };
+    mapping::apply_overrides(&mut mappings, overrides);
+    progress::finish_spinner(&pb, true);
+
+    // Step 5: Display mapping table
+    mapping::display_mapping_table(&mappings);
+
+    // Step 6: Ensure pods can pull: image-pull RBAC for the internal registry's
+    // upstream namespace, or a linked pull secret for an external registry
+    let mut pull_secret_namespaces = Vec::new();
+    if external {
+        match pullsecret::resolve_docker_config(registry, pull_secret_file)? {
+            Some(docker_config) => {
+                let pb = progress::stage_spinner("Linking image pull secret");
+                pull_secret_namespaces = pullsecret::ensure_pull_secret(&rt, &client, &docker_config)?;
+                progress::finish_spinner(&pb, true);
+            }
+            None => {
+                warnings::warn(format!("No credentials found for {}; pods will fail with ImagePullBackOff if it is private.", registry));
+                eprintln!("  Log in with podman/docker, pass --pull-secret, or set REGISTRY_USERNAME and REGISTRY_PASSWORD.");
+            }
+        }
+    } else {
+        let image_namespace = pull_registry
+            .rsplit('/')
+            .next()
+            .unwrap_or("tekton-upstream");
+        let pb = progress::stage_spinner("Ensuring image-pull RBAC");
+        operator::ensure_image_pull_rbac(&rt, &client, image_namespace)?;
+        progress::finish_spinner(&pb, true);
+    }
+
+    apply_mappings(&rt, &client, &config, component, mappings, &pull_secret_namespaces, verbose)
+}
+
+/// Deploy the published images of `component` from `source` (no build): the
+/// nightly (of `as_of` when given), or the upstream release named by `git_ref`.
+/// The images are public, so no pull access needs setting up. `overrides` replace
+/// the published images of their env vars.
+pub fn run_deploy_published(
+    component: &str,
+    source: imagesource::ImageSource,
+    git_ref: Option<&str>,
+    as_of: Option<&str>,
+    overrides: &[mapping::ImageOverride],
+    verbose: bool,
+) -> anyhow::Result<DeployReport> {
+    let (rt, client, config) = connect()?;
+
+    let comp = config
+        .components
+        .get(component)
+        .ok_or_else(|| anyhow::anyhow!("Component '{component}' not found in config"))?;
+    let pb = progress::stage_spinner(&format!("Resolving {} release", source.as_str()));
+    let release = imagesource::resolve_published(component, comp, source, git_ref, as_of)?;
+    let mut mappings = imagesource::image_mappings(component, comp, &release)?;
+    mapping::apply_overrides(&mut mappings, overrides);
+    progress::finish_spinner(&pb, true);
+    let published = match source {
+        imagesource::ImageSource::Nightly => format!("nightly {}", release.version),
+        _ => release.version,
+    };
+    eprintln!("  Using {} for {}", published, component);
+
+    mapping::display_mapping_table(&mappings);
+
+    let mut report = apply_mappings(&rt, &client, &config, component, mappings, &[], verbose)?;
+    report.published_release = Some(published);
+    Ok(report)
+}
+
+/// Steps 1-3: connect to the cluster, verify the operator, load component config.
+fn connect() -> anyhow::Result<(tokio::runtime::Runtime, kube::Client, config::Config)> {
+    // Step 1: Connect to cluster
+    let pb = progress::stage_spinner("Connecting to cluster");
+    let (rt, client) = k8s::create_kube_client()?;
+    progress::finish_spinner(&pb, true);
+
+    // Step 2: Verify operator is installed
+    let pb = progress::stage_spinner("Verifying OpenShift Pipelines operator");
+    operator::verify_operator(&rt, &client)?;
+    progress::finish_spinner(&pb, true);
+
+    // Step 3: Load component config
+    let pb = progress::stage_spinner("Loading component config");
+    let config_path = config::default_config_path();
+    let config = config::load_config(&config_path)?;
+    progress::finish_spinner(&pb, true);
+
+    Ok((rt, client, config))
+}
+
+/// Patch the operator with `mappings`, force re-reconciliation and wait for it.
+fn apply_mappings(
+    rt: &tokio::runtime::Runtime,
+    client: &kube::Client,
+    config: &config::Config,
+    component: &str,
+    mappings: Vec<(String, String)>,
+    pull_secret_namespaces: &[String],
+    verbose: bool,
+) -> anyhow::Result<DeployReport> {
+    // Snapshot the rendered manifests before the operator sees the new images
+    let prefix = config.components.get(component)
+        .and_then(|c| c.installer_set_prefix.as_deref());
+    let before = snapshot_manifests(rt, client, component, prefix);
+
+    // Step 7: Find operator deployment
+    let pb = progress::stage_spinner("Finding operator controller deployment");
+    let (namespace, deployment_name) = operator::find_operator_deployment(rt, client)?;
+    progress::finish_spinner(&pb, true);
+
+    // On a terminal, list the patch and the InstallerSets it will delete and ask first
+    if confirm::required() {
+        let mut changes: Vec<String> = mappings
+            .iter()
+            .map(|(env_var, image)| format!("set {}={} on Deployment {}/{}", env_var, image, namespace, deployment_name))
+            .collect();
+        let sets = operator::list_installer_sets(rt, client, component, prefix)?;
+        changes.extend(
+            sets.iter()
+                .filter_map(|set| set.metadata.name.as_ref())
+                .map(|name| format!("delete TektonInstallerSet {}", name)),
+        );
+        confirm::confirm(&format!("deploy {} over the operator", component), &changes)?;
+    }
+
+    // Step 8: Patch Deployment directly (OLM does NOT revert deployment patches per issue #1853)
+    let pb = progress::stage_spinner("Patching operator Deployment with IMAGE_ env vars");
+    operator::patch_operator_deployment_env(rt, client, &namespace, &deployment_name, &mappings)?;
+    progress::finish_spinner(&pb, true);
+    eprintln!("  Patched {}/{} with {} IMAGE_ env vars", namespace, deployment_name, mappings.len());
+
+    // Step 9: Delete InstallerSets to force operator re-reconciliation with new images
+    let pb = progress::stage_spinner("Deleting InstallerSets to trigger re-reconciliation");
+    let deleted = operator::delete_installer_sets(rt, client, component, prefix)?;
+    progress::finish_spinner(&pb, true);
+    eprintln!("  Deleted {} InstallerSets — operator will recreate with upstream images", deleted);
+
+    // Step 10: Wait for reconciliation (failure is a warning, not fatal)
+    eprintln!();
+    let reconciled = match wait::wait_for_reconciliation(rt, client, &mappings, pull_secret_namespaces, verbose) {
+        Ok(()) => {
+            eprintln!(
+                "Deploy complete. All Tekton components running with upstream images."
+            );
+            true
+        }
+        Err(e) => {
+            warnings::warn("Reconciliation wait failed:");
+            eprintln!("  {}", e);
+            eprintln!("  Continuing — deployment failure does not block the pipeline.");
+            false
+        }
+    };
+
+    // Step 11: Compare the re-rendered manifests with the earlier snapshot
+    let drift = before.zip(snapshot_manifests(rt, client, component, prefix)).map(|(before, after)| {
+        let images: Vec<String> = mappings.iter().map(|(_, image)| image.clone()).collect();
+        let drift = drift::diff(&before, &after, &images);
+        drift::print_drift(component, &drift, verbose);
+        drift
+    });
+
+    Ok(DeployReport {
+        component: component.to_string(),
+        namespace,
+        deployment: deployment_name,
+        mappings: mappings
+            .into_iter()
+            .map(|(env_var, image)| ImageMapping { env_var, image })
+            .collect(),
+        installer_sets_deleted: deleted,
+        reconciled,
+        published_release: None,
+        drift,
+    })
+}
+
+/// Snapshot of the manifests in `component`'s InstallerSets; None (with a
+/// warning) if they could not be listed.
+fn snapshot_manifests(
+    rt: &tokio::runtime::Runtime,
+    client: &kube::Client,
+    component: &str,
+    prefix: Option<&str>,
+) -> Option<drift::ManifestSnapshot> {
+    match operator::list_installer_sets(rt, client, component, prefix) {
+        Ok(sets) => Some(drift::snapshot(&sets)),
+        Err(e) => {
+            warnings::warn(format!("Could not snapshot rendered manifests: {e:#}"));
+            None
+        }
+    }
+}
@@ -0,0 +1,469 @@
+use anyhow::{bail, Context};
+use k8s_openapi::api::apps::v1::Deployment;
+use kube::api::{Api, ApiResource, DynamicObject, ListParams, Patch, PatchParams};
+use kube::Client;
+use serde_json::json;
+use std::sync::Mutex;
+use tokio::runtime::Runtime;
+
+use crate::{audit, k8s, warnings};
+
+/// Verify that the OpenShift Pipelines operator is installed by checking for the TektonConfig CR.
+pub fn verify_operator(rt: &Runtime, client: &Client) -> anyhow::Result<DynamicObject> {
+    let ar = ApiResource {
+        group: "operator.tekton.dev".into(),
+        version: "v1alpha1".into(),
+        api_version: "operator.tekton.dev/v1alpha1".into(),
+        kind: "TektonConfig".into(),
+        plural: "tektonconfigs".into(),
+    };
+
+    let api: Api<DynamicObject> = Api::all_with(client.clone(), &ar);
+
+    let result = k8s::block_on_retry(rt, "get TektonConfig", || api.get("config"));
+
+    match result {
+        Ok(tc) => Ok(tc),
+        Err(kube::Error::Api(ref resp)) if resp.code == 404 => {
+            bail!("OpenShift Pipelines operator not found. TektonConfig CR 'config' does not exist.\nIs the operator installed?")
+        }
+        Err(kube::Error::Api(ref resp)) if resp.code == 403 => {
+            bail!("Insufficient permissions to read TektonConfig CR.\nEnsure you have cluster-admin or appropriate RBAC.")
+        }
+        Err(e) => Err(e).context("Failed to verify operator installation"),
+    }
+}
+
+/// Known namespaces where the operator controller deployment may live.
+const OPERATOR_NAMESPACES: &[&str] = &[
+    "openshift-pipelines",
+    "openshift-operators",
+    "tekton-pipelines",
+];
+
+/// Known deployment names for the operator controller.
+const OPERATOR_DEPLOYMENT_NAMES: &[&str] = &[
+    "openshift-pipelines-operator",
+    "tekton-operator",
+];
+
+/// Known label selectors for the operator controller deployment.
+const OPERATOR_LABEL_SELECTORS: &[&str] = &[
+    "app.kubernetes.io/name=openshift-pipelines-operator",
+    "app=tekton-operator",
+];
+
+/// Find the operator controller deployment by checking known namespaces, names, and label selectors.
+/// Returns (namespace, deployment_name).
+///
+/// This is the primary function used for deploying upstream images: the returned deployment
+/// is patched directly via `patch_operator_deployment_env()` (direct Deployment patching,
+/// not CSV patching -- OLM does NOT revert direct deployment modifications per issue #1853).
+pub fn find_operator_deployment(
+    rt: &Runtime,
+    client: &Client,
+) -> anyhow::Result<(String, String)> {
+    // First try known deployment names directly (most reliable for OLM-managed operators)
+    for ns in OPERATOR_NAMESPACES {
+        let api: Api<Deployment> = Api::namespaced(client.clone(), ns);
+        for name in OPERATOR_DEPLOYMENT_NAMES {
+            if let Ok(dep) = k8s::block_on_retry(rt, "get operator Deployment", || api.get(name))
+                && let Some(n) = &dep.metadata.name
+            {
+                return Ok((ns.to_string(), n.clone()));
+            }
+        }
+    }
+
+    // Fall back to label selectors
+    for ns in OPERATOR_NAMESPACES {
+        let api: Api<Deployment> = Api::namespaced(client.clone(), ns);
+        for selector in OPERATOR_LABEL_SELECTORS {
+            let lp = kube::api::ListParams::default().labels(selector);
+            let list = k8s::block_on_retry(rt, "list operator Deployments", || api.list(&lp))
+                .with_context(|| format!("Failed to list deployments in namespace {ns}"))?;
+            if let Some(dep) = list.items.first() && let Some(name) = &dep.metadata.name {
+                return Ok((ns.to_string(), name.clone()));
+            }
+        }
+    }
+
+    bail!(
+        "Operator controller deployment not found.\nChecked namespaces: {}\nChecked names: {}\nChecked labels: {}",
+        OPERATOR_NAMESPACES.join(", "),
+        OPERATOR_DEPLOYMENT_NAMES.join(", "),
+        OPERATOR_LABEL_SELECTORS.join(", ")
+    )
+}
+
+/// Container in the operator Deployment that carries the IMAGE_ env vars.
+pub const LIFECYCLE_CONTAINER: &str = "openshift-pipelines-operator-lifecycle";
+
+/// IMAGE_ env vars set by value on the operator Deployment, as (name, image).
+pub fn read_operator_image_env(
+    rt: &Runtime,
+    client: &Client,
+    namespace: &str,
+    deployment_name: &str,
+) -> anyhow::Result<Vec<(String, String)>> {
+    let api: Api<Deployment> = Api::namespaced(client.clone(), namespace);
+    let dep = k8s::block_on_retry(rt, "get operator Deployment", || api.get(deployment_name))
+        .with_context(|| format!("Failed to get Deployment {}/{}", namespace, deployment_name))?;
+    let container = dep
+        .spec
+        .and_then(|s| s.template.spec)
+        .and_then(|s| s.containers.into_iter().find(|c| c.name == LIFECYCLE_CONTAINER))
+        .with_context(|| format!("Container '{}' not found in Deployment", LIFECYCLE_CONTAINER))?;
+    Ok(container
+        .env
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|e| e.name.starts_with("IMAGE_"))
+        .filter_map(|e| Some((e.name, e.value?)))
+        .collect())
+}
+
+/// Field manager of the IMAGE_ env vars streamstress applies to the operator
+/// Deployment. `metadata.managedFields` tells its fields apart from the
+/// operator's and OLM's, and `release_image_env` drops exactly those.
+pub const FIELD_MANAGER: &str = "streamstress-images";
+
+/// An apply replaces everything its field manager set before, so deploys of one
+/// process (components of a deploy group run in parallel) apply one at a time.
+static APPLY_LOCK: Mutex<()> = Mutex::new(());
+
+/// Set the IMAGE_ env vars of `mappings` on the operator Deployment with
+/// Server-Side Apply, as `FIELD_MANAGER`.
+///
+/// This bypasses CSV patching entirely. OLM does NOT revert direct deployment
+/// modifications per OLM issue #1853, so patching the Deployment directly is
+/// both safe and more reliable than patching the CSV (which was broken with
+/// OpenShift Pipelines v1.21.0 -- CSV env var changes were not propagated to
+/// the Deployment).
+///
+/// The env vars set by earlier deploys are applied again, or the apply would
+/// remove them. Vars another manager (OLM, from the CSV) owns make the apply
+/// conflict; the conflicts are printed and the apply forced, which moves those
+/// fields to `FIELD_MANAGER`.
+pub fn patch_operator_deployment_env(
+    rt: &Runtime,
+    client: &Client,
+    namespace: &str,
+    deployment_name: &str,
+    mappings: &[(String, String)],
+) -> anyhow::Result<()> {
+    let api: Api<Deployment> = Api::namespaced(client.clone(), namespace);
+    let _guard = APPLY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let dep = k8s::block_on_retry(rt, "get operator Deployment", || api.get(deployment_name))
+        .with_context(|| format!("Failed to get Deployment {}/{}", namespace, deployment_name))?;
+    let current = dep
+        .spec
+        .as_ref()
+        .and_then(|s| s.template.spec.as_ref())
+        .and_then(|s| s.containers.iter().find(|c| c.name == LIFECYCLE_CONTAINER))
+        .with_context(|| format!("Container '{}' not found in Deployment", LIFECYCLE_CONTAINER))?
+        .env
+        .clone()
+        .unwrap_or_default();
+
+    // Our earlier vars first, then the new values (which win)
+    let owned = owned_env(&dep, FIELD_MANAGER);
+    let mut env: Vec<(String, String)> = current
+        .iter()
+        .filter(|e| owned.contains(&e.name) && !mappings.iter().any(|(k, _)| k == &e.name))
+        .filter_map(|e| Some((e.name.clone(), e.value.clone()?)))
+        .collect();
+    env.extend(mappings.iter().cloned());
+
+    // A var set from a ConfigMap or Secret cannot also have a value: drop its valueFrom first
+    let from_refs: Vec<&str> = current
+        .iter()
+        .filter(|e| e.value_from.is_some() && mappings.iter().any(|(k, _)| k == &e.name))
+        .map(|e| e.name.as_str())
+        .collect();
+    if !from_refs.is_empty() {
+        let cleared: Vec<serde_json::Value> = from_refs.iter().map(|name| json!({"name": name, "valueFrom": null})).collect();
+        let patch = Patch::Strategic(json!({
+            "spec": {"template": {"spec": {"containers": [{"name": LIFECYCLE_CONTAINER, "env": cleared}]}}}
+        }));
+        let pp = PatchParams::default();
+        let result = k8s::block_on_retry(rt, "patch operator Deployment", || api.patch(deployment_name, &pp, &patch));
+        audit::record_api("patch", "Deployment", Some(namespace), deployment_name, result.is_ok());
+        result.with_context(|| format!("Failed to clear valueFrom of {} in Deployment {}/{}", from_refs.join(", "), namespace, deployment_name))?;
+    }
+
+    let patch = Patch::Apply(env_apply_config(namespace, deployment_name, &env));
+    let pp = PatchParams::apply(FIELD_MANAGER);
+    let result = match k8s::block_on_retry(rt, "apply operator Deployment env", || api.patch(deployment_name, &pp, &patch)) {
+        Err(kube::Error::Api(ae)) if ae.code == 409 => {
+            let (vars, managers) = (conflicting_env(&ae.message), conflicting_managers(&ae.message));
+            eprintln!(
+                "  Taking over {} from {}",
+                if vars.is_empty() { "conflicting fields".to_string() } else { vars.join(", ") },
+                if managers.is_empty() { "other field managers".to_string() } else { managers.join(", ") }
+            );
+            tracing::info!(target: crate::logging::API_TARGET, "apply conflicts on Deployment {}/{}: {}", namespace, deployment_name, ae.message);
+            let pp = PatchParams::apply(FIELD_MANAGER).force();
+            k8s::block_on_retry(rt, "apply operator Deployment env", || api.patch(deployment_name, &pp, &patch))
+        }
+        result => result,
+    };
+    audit::record_api("apply", "Deployment", Some(namespace), deployment_name, result.is_ok());
+    result.with_context(|| format!("Failed to update Deployment {}/{} with IMAGE_ env vars", namespace, deployment_name))?;
+    Ok(())
+}
+
+/// Remove the env vars `FIELD_MANAGER` applied to the operator Deployment by
+/// applying an empty configuration. Vars taken over from another manager are
+/// left without a value; the caller restores them. Returns the vars released.
+pub fn release_image_env(
+    rt: &Runtime,
+    client: &Client,
+    namespace: &str,
+    deployment_name: &str,
+) -> anyhow::Result<Vec<String>> {
+    let api: Api<Deployment> = Api::namespaced(client.clone(), namespace);
+    let dep = k8s::block_on_retry(rt, "get operator Deployment", || api.get(deployment_name))
+        .with_context(|| format!("Failed to get Deployment {}/{}", namespace, deployment_name))?;
+    let owned = owned_env(&dep, FIELD_MANAGER);
+    if owned.is_empty() {
+        return Ok(owned);
+    }
+    let patch = Patch::Apply(json!({
+        "apiVersion": "apps/v1",
+        "kind": "Deployment",
+        "metadata": {"name": deployment_name, "namespace": namespace},
+    }));
+    let pp = PatchParams::apply(FIELD_MANAGER);
+    let result = k8s::block_on_retry(rt, "apply operator Deployment env", || api.patch(deployment_name, &pp, &patch));
+    audit::record_api("apply", "Deployment", Some(namespace), deployment_name, result.is_ok());
+    result.with_context(|| format!("Failed to release the IMAGE_ env vars of Deployment {}/{}", namespace, deployment_name))?;
+    Ok(owned)
+}
+
+/// Apply configuration holding only `env` in the lifecycle container.
+fn env_apply_config(namespace: &str, deployment_name: &str, env: &[(String, String)]) -> serde_json::Value {
+    let env: Vec<serde_json::Value> = env.iter().map(|(name, value)| json!({"name": name, "value": value})).collect();
+    json!({
+        "apiVersion": "apps/v1",
+        "kind": "Deployment",
+        "metadata": {"name": deployment_name, "namespace": namespace},
+        "spec": {"template": {"spec": {"containers": [{"name": LIFECYCLE_CONTAINER, "env": env}]}}}
+    })
+}
+
+/// Env vars of the lifecycle container whose value `manager` owns, from
+/// `metadata.managedFields`.
+pub fn owned_env(dep: &Deployment, manager: &str) -> Vec<String> {
+    let container_key = format!("k:{}", json!({"name": LIFECYCLE_CONTAINER}));
+    dep.metadata
+        .managed_fields
+        .iter()
+        .flatten()
+        .filter(|m| m.manager.as_deref() == Some(manager))
+        .filter_map(|m| m.fields_v1.as_ref())
+        .filter_map(|f| f.0["f:spec"]["f:template"]["f:spec"]["f:containers"][&container_key]["f:env"].as_object())
+        .flatten()
+        .filter(|(_, fields)| fields.get("f:value").is_some())
+        .filter_map(|(key, _)| {
+            let item: serde_json::Value = serde_json::from_str(key.strip_prefix("k:")?).ok()?;
+            item["name"].as_str().map(str::to_string)
+        })
+        .collect()
+}
+
+/// Env vars named in an apply conflict message, e.g.
+/// `.spec.template.spec.containers[name="..."].env[name="IMAGE_X"].value`.
+fn conflicting_env(message: &str) -> Vec<String> {
+    message
+        .split("env[name=\"")
+        .skip(1)
+        .filter_map(|rest| rest.split('"').next())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Field managers named in an apply conflict message (`conflict(s) with "olm"`).
+fn conflicting_managers(message: &str) -> Vec<String> {
+    let mut managers: Vec<String> = Vec::new();
+    for manager in message.split(" with \"").skip(1).filter_map(|rest| rest.split('"').next()) {
+        if !managers.iter().any(|m| m == manager) {
+            managers.push(manager.to_string());
+        }
+    }
+    managers
+}
+
+/// TektonInstallerSets belonging to `component` (by name prefix, e.g.
+/// "pipeline-main-deployment-*"). `prefix_override` replaces the component
+/// name in the prefix (e.g. "manualapprovalgate" for manual-approval-gate).
+pub fn list_installer_sets(
+    rt: &Runtime,
+    client: &Client,
+    component: &str,
+    prefix_override: Option<&str>,
+) -> anyhow::Result<Vec<DynamicObject>> {
+    let api = installer_set_api(client);
+    let lp = ListParams::default();
+    let sets = k8s::block_on_retry(rt, "list TektonInstallerSets", || api.list(&lp))
+        .context("Failed to list TektonInstallerSets")?;
+
+    let prefix = prefix_override.unwrap_or(component);
+    let prefixes: Vec<String> = vec![
+        format!("{}-main-deployment-", prefix),
+        format!("{}-main-static-", prefix),
+        format!("{}-post-", prefix),
+        format!("{}-pre-", prefix),
+    ];
+
+    Ok(sets
+        .items
+        .into_iter()
+        .filter(|set| {
+            set.metadata
+                .name
+                .as_deref()
+                .is_some_and(|name| prefixes.iter().any(|p| name.starts_with(p)))
+        })
+        .collect())
+}
+
+pub fn installer_set_api(client: &Client) -> Api<DynamicObject> {
+    let ar = ApiResource {
+        group: "operator.tekton.dev".into(),
+        version: "v1alpha1".into(),
+        api_version: "operator.tekton.dev/v1alpha1".into(),
+        kind: "TektonInstallerSet".into(),
+        plural: "tektoninstallersets".into(),
+    };
+    Api::all_with(client.clone(), &ar)
+}
+
+/// Delete TektonInstallerSets matching a component to force the operator to re-reconcile.
+/// The operator uses IMAGE_ env vars when creating InstallerSets, so deleting them
+/// causes recreation with the new (upstream) images.
+pub fn delete_installer_sets(
+    rt: &Runtime,
+    client: &Client,
+    component: &str,
+    prefix_override: Option<&str>,
+) -> anyhow::Result<u32> {
+    let api = installer_set_api(client);
+    let sets = list_installer_sets(rt, client, component, prefix_override)?;
+
+    let mut deleted = 0u32;
+    for set in &sets {
+        if let Some(name) = &set.metadata.name {
+            let dp = kube::api::DeleteParams::default();
+            let result = k8s::block_on_retry(rt, "delete TektonInstallerSet", || api.delete(name, &dp));
+            audit::record_api("delete", "TektonInstallerSet", None, name, result.is_ok());
+            match result {
+                Ok(_) => {
+                    eprintln!("  Deleted InstallerSet: {}", name);
+                    deleted += 1;
+                }
+                Err(e) => {
+                    warnings::warn(format!("Failed to delete InstallerSet {}: {}", name, e));
+                }
+            }
+        }
+    }
+
+    Ok(deleted)
+}
+
+/// Ensure all authenticated users can pull images from the upstream namespace.
+/// Tekton uses entrypoint/nop/workingdirinit as init containers in arbitrary user
+/// namespaces, so we need cluster-wide pull access — not just specific namespaces.
+pub fn ensure_image_pull_rbac(
+    rt: &Runtime,
+    client: &Client,
+    image_namespace: &str,
+) -> anyhow::Result<()> {
+    use k8s_openapi::api::rbac::v1::RoleBinding;
+
+    let api: Api<RoleBinding> = Api::namespaced(client.clone(), image_namespace);
+
+    let binding_name = "image-puller-all-authenticated";
+
+    // Check if it already exists
+    if k8s::block_on_retry(rt, "get RoleBinding", || api.get(binding_name)).is_ok() {
+        return Ok(());
+    }
+
+    let rb: RoleBinding = serde_json::from_value(json!({
+        "apiVersion": "rbac.authorization.k8s.io/v1",
+        "kind": "RoleBinding",
+        "metadata": {
+            "name": binding_name,
+            "namespace": image_namespace,
+        },
+        "roleRef": {
+            "apiGroup": "rbac.authorization.k8s.io",
+            "kind": "ClusterRole",
+            "name": "system:image-puller",
+        },
+        "subjects": [{
+            "apiGroup": "rbac.authorization.k8s.io",
+            "kind": "Group",
+            "name": "system:authenticated",
+        }]
+    }))?;
+
+    let pp = kube::api::PostParams::default();
+    let result = k8s::block_on_retry(rt, "create RoleBinding", || api.create(&pp, &rb));
+    audit::record_api("create", "RoleBinding", Some(image_namespace), binding_name, result.is_ok());
+    result.with_context(|| {
+        format!(
+            "Failed to create image-puller RoleBinding in {}",
+            image_namespace
+        )
+    })?;
+    eprintln!("  Granted image-puller to all authenticated users in {}", image_namespace);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owned_env() {
+        let dep: Deployment = serde_json::from_value(json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": {
+                "name": "openshift-pipelines-operator",
+                "managedFields": [
+                    {"manager": "olm", "operation": "Update", "fieldsType": "FieldsV1", "fieldsV1": {"f:spec": {"f:template": {"f:spec": {"f:containers": {
+                        "k:{\"name\":\"openshift-pipelines-operator-lifecycle\"}": {"f:env": {
+                            "k:{\"name\":\"IMAGE_PIPELINES_CONTROLLER\"}": {".": {}, "f:name": {}},
+                            "k:{\"name\":\"IMAGE_TRIGGERS_WEBHOOK\"}": {".": {}, "f:name": {}, "f:value": {}}
+                        }}
+                    }}}}}},
+                    {"manager": FIELD_MANAGER, "operation": "Apply", "fieldsType": "FieldsV1", "fieldsV1": {"f:spec": {"f:template": {"f:spec": {"f:containers": {
+                        "k:{\"name\":\"openshift-pipelines-operator-lifecycle\"}": {".": {}, "f:name": {}, "f:env": {
+                            "k:{\"name\":\"IMAGE_PIPELINES_CONTROLLER\"}": {".": {}, "f:name": {}, "f:value": {}}
+                        }}
+                    }}}}}}
+                ]
+            }
+        }))
+        .unwrap();
+        assert_eq!(owned_env(&dep, FIELD_MANAGER), vec!["IMAGE_PIPELINES_CONTROLLER"]);
+        assert_eq!(owned_env(&dep, "olm"), vec!["IMAGE_TRIGGERS_WEBHOOK"]);
+        assert!(owned_env(&Deployment::default(), FIELD_MANAGER).is_empty());
+    }
+
+    #[test]
+    fn test_apply_conflicts() {
+        let message = "Apply failed with 2 conflicts: conflicts with \"olm\" using apps/v1:\n\
+            - .spec.template.spec.containers[name=\"openshift-pipelines-operator-lifecycle\"].env[name=\"IMAGE_A\"].value\n\
+            - .spec.template.spec.containers[name=\"openshift-pipelines-operator-lifecycle\"].env[name=\"IMAGE_B\"].value";
+        assert_eq!(conflicting_env(message), vec!["IMAGE_A", "IMAGE_B"]);
+        assert_eq!(conflicting_managers(message), vec!["olm"]);
+        assert!(conflicting_env("injected failure").is_empty());
+    }
+}
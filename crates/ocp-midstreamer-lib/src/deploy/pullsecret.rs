@@ -0,0 +1,268 @@
+//! Pull secret for images deployed from a private external registry (e.g. a
+//! private quay organization).
+//!
+//! The internal registry is covered by the image-puller RoleBinding; anything
+//! else needs credentials on the ServiceAccounts that run the Tekton pods, or
+//! the pods sit in ImagePullBackOff. Credentials come from, in order:
+//!   1. REGISTRY_USERNAME / REGISTRY_PASSWORD
+//!   2. a docker config file given with `deploy --pull-secret`
+//!   3. the local docker config or containers auth file (e.g. after `podman login quay.io`)
+
+use anyhow::{bail, Context};
+use k8s_openapi::api::core::v1::{Namespace, Pod, Secret, ServiceAccount};
+use kube::api::{Api, DeleteParams, ListParams, Patch, PatchParams};
+use kube::Client;
+use serde_json::json;
+use tokio::runtime::Runtime;
+
+use crate::{audit, exec, k8s, platform, warnings};
+
+/// Name of the dockerconfigjson Secret created in each target namespace.
+pub const PULL_SECRET: &str = "streamstress-pull-secret";
+
+/// Namespaces whose ServiceAccounts get the pull secret linked.
+const TARGET_NAMESPACES: &[&str] = &["openshift-pipelines", "tekton-pipelines"];
+
+/// Container waiting reasons that mean the image could not be pulled.
+const PULL_FAILURE_REASONS: &[&str] = &["ErrImagePull", "ImagePullBackOff"];
+
+/// Whether `registry` points outside the cluster's internal registry.
+pub fn is_external_registry(registry: &str) -> bool {
+    !registry_host(registry).contains("openshift-image-registry")
+}
+
+/// Host part of a registry path ("quay.io/org" -> "quay.io").
+fn registry_host(registry: &str) -> &str {
+    let registry = registry
+        .trim_start_matches("https://")
+        .trim_start_matches("http://");
+    registry.split('/').next().unwrap_or(registry)
+}
+
+/// Build the `.dockerconfigjson` payload for `registry`, or None when no
+/// credentials for it are available.
+pub fn resolve_docker_config(registry: &str, pull_secret_file: Option<&str>) -> anyhow::Result<Option<String>> {
+    let host = registry_host(registry);
+
+    if let (Ok(user), Ok(password)) = (std::env::var("REGISTRY_USERNAME"), std::env::var("REGISTRY_PASSWORD")) {
+        exec::register_secret(&password);
+        let config = json!({"auths": {host: {"username": user, "password": password}}});
+        return Ok(Some(config.to_string()));
+    }
+
+    if let Some(file) = pull_secret_file {
+        let content = std::fs::read_to_string(file)
+            .with_context(|| format!("Failed to read pull secret {}", file))?;
+        let config: serde_json::Value = serde_json::from_str(&content)
+            .with_context(|| format!("Pull secret {} is not a docker config JSON file", file))?;
+        if config.get("auths").and_then(|a| a.as_object()).is_none_or(|a| a.is_empty()) {
+            bail!("Pull secret {} has no \"auths\" entries", file);
+        }
+        return Ok(Some(config.to_string()));
+    }
+
+    let local_files = [
+        platform::docker_config_dir().map(|d| d.join("config.json")),
+        platform::containers_auth_file(),
+    ];
+    for path in local_files.into_iter().flatten() {
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Some(config) = auths_for_registry(&content, registry) {
+            return Ok(Some(config.to_string()));
+        }
+    }
+    Ok(None)
+}
+
+/// The entries of a docker config that apply to `registry` (keys may be a bare
+/// host, a URL, or a host with a repository path), keeping only inline auths.
+fn auths_for_registry(content: &str, registry: &str) -> Option<serde_json::Value> {
+    let config: serde_json::Value = serde_json::from_str(content).ok()?;
+    let auths = config.get("auths")?.as_object()?;
+    let host = registry_host(registry);
+    let matching: serde_json::Map<String, serde_json::Value> = auths
+        .iter()
+        .filter(|(key, entry)| {
+            let key = key.trim_start_matches("https://").trim_start_matches("http://");
+            let key = key.trim_end_matches('/').trim_end_matches("/v1").trim_end_matches("/v2");
+            let applies = key == host || (key.starts_with(host) && registry.starts_with(key));
+            let inline = entry.get("auth").is_some() || entry.get("password").is_some();
+            applies && inline
+        })
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    if matching.is_empty() {
+        None
+    } else {
+        Some(json!({"auths": matching}))
+    }
+}
+
+/// Create or update the pull secret in each existing target namespace and link
+/// it to every ServiceAccount there. Returns the namespaces it was applied to.
+pub fn ensure_pull_secret(rt: &Runtime, client: &Client, docker_config: &str) -> anyhow::Result<Vec<String>> {
+    let ns_api: Api<Namespace> = Api::all(client.clone());
+    let mut applied = Vec::new();
+    for ns in TARGET_NAMESPACES {
+        if k8s::block_on_retry(rt, "get Namespace", || ns_api.get_opt(ns))?.is_none() {
+            continue;
+        }
+        apply_secret(rt, client, ns, docker_config)?;
+        let linked = link_service_accounts(rt, client, ns)?;
+        eprintln!("  Linked {} to {} ServiceAccount(s) in {}", PULL_SECRET, linked, ns);
+        applied.push(ns.to_string());
+    }
+    if applied.is_empty() {
+        bail!("None of the target namespaces exist: {}", TARGET_NAMESPACES.join(", "));
+    }
+    Ok(applied)
+}
+
+fn apply_secret(rt: &Runtime, client: &Client, namespace: &str, docker_config: &str) -> anyhow::Result<()> {
+    let secret: Secret = serde_json::from_value(json!({
+        "apiVersion": "v1",
+        "kind": "Secret",
+        "metadata": {
+            "name": PULL_SECRET,
+            "namespace": namespace,
+            "labels": {
+                "app": "streamstress"
+            }
+        },
+        "type": "kubernetes.io/dockerconfigjson",
+        "stringData": {
+            ".dockerconfigjson": docker_config
+        }
+    }))?;
+
+    let api: Api<Secret> = Api::namespaced(client.clone(), namespace);
+    let pp = PatchParams::apply("streamstress").force();
+    let patch = Patch::Apply(&secret);
+    let result = k8s::block_on_retry(rt, "apply pull Secret", || api.patch(PULL_SECRET, &pp, &patch));
+    audit::record_api("apply", "Secret", Some(namespace), PULL_SECRET, result.is_ok());
+    result.with_context(|| format!("Failed to apply pull secret in {}", namespace))?;
+    Ok(())
+}
+
+/// Add the pull secret to the imagePullSecrets of every ServiceAccount in
+/// `namespace` that lacks it. Returns how many ServiceAccounts have it.
+fn link_service_accounts(rt: &Runtime, client: &Client, namespace: &str) -> anyhow::Result<usize> {
+    let api: Api<ServiceAccount> = Api::namespaced(client.clone(), namespace);
+    let lp = ListParams::default();
+    let sas = k8s::block_on_retry(rt, "list ServiceAccounts", || api.list(&lp))
+        .with_context(|| format!("Failed to list ServiceAccounts in {}", namespace))?;
+
+    let mut linked = 0;
+    for sa in &sas.items {
+        let Some(name) = sa.metadata.name.as_deref() else {
+            continue;
+        };
+        let mut secrets: Vec<String> = sa
+            .image_pull_secrets
+            .iter()
+            .flatten()
+            .map(|s| s.name.clone())
+            .collect();
+        if !secrets.iter().any(|s| s == PULL_SECRET) {
+            secrets.push(PULL_SECRET.to_string());
+            // Merge patch replaces the list, so send the existing entries along
+            let patch = json!({
+                "imagePullSecrets": secrets.iter().map(|s| json!({"name": s})).collect::<Vec<_>>()
+            });
+            let (pp, patch) = (PatchParams::default(), Patch::Merge(&patch));
+            let result = k8s::block_on_retry(rt, "patch ServiceAccount", || api.patch(name, &pp, &patch));
+            audit::record_api("patch", "ServiceAccount", Some(namespace), name, result.is_ok());
+            if let Err(e) = result {
+                warnings::warn(format!("Failed to link pull secret to ServiceAccount {}/{}: {}", namespace, name, e));
+                continue;
+            }
+        }
+        linked += 1;
+    }
+    Ok(linked)
+}
+
+/// Re-link ServiceAccounts recreated by the operator since the secret was
+/// linked, and delete pods stuck on image pulls that were admitted without it
+/// so their controllers recreate them with the secret. Returns pods deleted.
+pub fn repair_pull_failures(rt: &Runtime, client: &Client, namespaces: &[String]) -> anyhow::Result<u32> {
+    let mut deleted = 0;
+    for ns in namespaces {
+        link_service_accounts(rt, client, ns)?;
+
+        let api: Api<Pod> = Api::namespaced(client.clone(), ns);
+        let lp = ListParams::default();
+        let pods = k8s::block_on_retry(rt, "list Pods", || api.list(&lp))
+            .with_context(|| format!("Failed to list pods in {}", ns))?;
+        for pod in &pods.items {
+            let Some(name) = pod.metadata.name.as_deref() else {
+                continue;
+            };
+            let has_secret = pod
+                .spec
+                .as_ref()
+                .and_then(|s| s.image_pull_secrets.as_ref())
+                .is_some_and(|s| s.iter().any(|r| r.name == PULL_SECRET));
+            if has_secret || !is_pull_failure(pod) {
+                continue;
+            }
+            let dp = DeleteParams::default();
+            let result = k8s::block_on_retry(rt, "delete Pod", || api.delete(name, &dp));
+            audit::record_api("delete", "Pod", Some(ns), name, result.is_ok());
+            if result.is_ok() {
+                eprintln!("  Restarted {}/{} (image pull failed before the pull secret was linked)", ns, name);
+                deleted += 1;
+            }
+        }
+    }
+    Ok(deleted)
+}
+
+fn is_pull_failure(pod: &Pod) -> bool {
+    let Some(status) = pod.status.as_ref() else {
+        return false;
+    };
+    status
+        .init_container_statuses
+        .iter()
+        .flatten()
+        .chain(status.container_statuses.iter().flatten())
+        .filter_map(|c| c.state.as_ref()?.waiting.as_ref()?.reason.as_deref())
+        .any(|reason| PULL_FAILURE_REASONS.contains(&reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_external_registry_detection() {
+        assert!(is_external_registry("quay.io/my-org"));
+        assert!(!is_external_registry("default-route-openshift-image-registry.apps.example.com/tekton-upstream"));
+        assert!(!is_external_registry("image-registry.openshift-image-registry.svc:5000/tekton-upstream"));
+        assert_eq!(registry_host("https://quay.io/my-org"), "quay.io");
+    }
+
+    #[test]
+    fn test_auths_for_registry() {
+        let config = r#"{"auths": {
+            "quay.io": {"auth": "dTpw"},
+            "https://quay.io/other-org": {"auth": "eDp5"},
+            "ghcr.io": {"auth": "ejp6"},
+            "registry.redhat.io": {}
+        }}"#;
+        let matched = auths_for_registry(config, "quay.io/my-org").unwrap();
+        let auths = matched["auths"].as_object().unwrap();
+        assert_eq!(auths.len(), 1);
+        assert!(auths.contains_key("quay.io"));
+
+        let matched = auths_for_registry(config, "quay.io/other-org").unwrap();
+        assert_eq!(matched["auths"].as_object().unwrap().len(), 2);
+
+        // Credential-helper entries carry no inline auth
+        assert!(auths_for_registry(config, "registry.redhat.io/ns").is_none());
+        assert!(auths_for_registry(config, "docker.io/library").is_none());
+    }
+}
@@ -0,0 +1,304 @@
+//! Export and import of the deployed image state.
+//!
+//! `deploy export-state` records the IMAGE_ env vars on the operator Deployment
+//! and the images rendered into each component's TektonInstallerSets.
+//! `deploy import-state` re-applies the env vars on another cluster, forces the
+//! operator to re-render, and checks the rendered images against the export, so
+//! a reported failing environment can be reproduced.
+
+use anyhow::{Context, bail};
+use kube::api::DynamicObject;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use super::{ImageMapping, INTERNAL_REGISTRY, connect, mapping, operator, wait};
+use crate::{config, progress, timestamp, warnings};
+
+/// Bumped when the file layout changes incompatibly.
+const STATE_VERSION: u32 = 1;
+
+/// Deployed image state of a cluster, as written by `deploy export-state`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeployState {
+    pub version: u32,
+    pub captured_at: String,
+    pub namespace: String,
+    pub deployment: String,
+    /// IMAGE_ env vars set on the operator Deployment
+    pub images: Vec<ImageMapping>,
+    pub installer_sets: Vec<InstallerSetImages>,
+}
+
+/// Images rendered into one TektonInstallerSet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstallerSetImages {
+    pub component: String,
+    pub name: String,
+    pub images: BTreeSet<String>,
+}
+
+/// Outcome of `deploy import-state`, for `--output json|yaml`.
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub namespace: String,
+    pub deployment: String,
+    pub mappings: Vec<ImageMapping>,
+    pub installer_sets_deleted: u32,
+    pub reconciled: bool,
+    /// Exported images the re-rendered InstallerSets no longer reference, per component
+    pub missing_images: Vec<InstallerSetImages>,
+}
+
+/// Capture the deployed image state and write it to `path`.
+pub fn export_state(path: &Path) -> anyhow::Result<DeployState> {
+    let (rt, client, config) = connect()?;
+
+    let pb = progress::stage_spinner("Reading operator IMAGE_ env vars");
+    let (namespace, deployment) = operator::find_operator_deployment(&rt, &client)?;
+    let images = operator::read_operator_image_env(&rt, &client, &namespace, &deployment)?;
+    progress::finish_spinner(&pb, true);
+
+    let pb = progress::stage_spinner("Reading InstallerSet images");
+    let mut components: Vec<&String> = config.components.keys().collect();
+    components.sort();
+    let installer_sets = read_installer_sets(&rt, &client, &config, &components)?;
+    progress::finish_spinner(&pb, true);
+
+    let state = DeployState {
+        version: STATE_VERSION,
+        captured_at: timestamp::now_rfc3339(),
+        namespace,
+        deployment,
+        images: images.into_iter().map(|(env_var, image)| ImageMapping { env_var, image }).collect(),
+        installer_sets,
+    };
+    let json = serde_json::to_string_pretty(&state)?;
+    std::fs::write(path, json).with_context(|| format!("Failed to write {}", path.display()))?;
+    eprintln!(
+        "Exported {} IMAGE_ env vars and {} InstallerSets to {}",
+        state.images.len(),
+        state.installer_sets.len(),
+        path.display()
+    );
+    Ok(state)
+}
+
+/// Read a state file written by `export_state`.
+pub fn read_state(path: &Path) -> anyhow::Result<DeployState> {
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let state: DeployState =
+        serde_json::from_str(&content).with_context(|| format!("Invalid deploy state file: {}", path.display()))?;
+    if state.version != STATE_VERSION {
+        bail!(
+            "Deploy state file {} has version {}, expected {}",
+            path.display(),
+            state.version,
+            STATE_VERSION
+        );
+    }
+    Ok(state)
+}
+
+/// Re-apply the IMAGE_ env vars from `path` and re-render the InstallerSets of
+/// the components they belong to.
+pub fn import_state(path: &Path, verbose: bool) -> anyhow::Result<ImportReport> {
+    let state = read_state(path)?;
+    if state.images.is_empty() {
+        bail!("Deploy state file {} has no IMAGE_ env vars", path.display());
+    }
+    let internal: Vec<&str> = state
+        .images
+        .iter()
+        .filter(|m| m.image.starts_with(INTERNAL_REGISTRY))
+        .map(|m| m.env_var.as_str())
+        .collect();
+    if !internal.is_empty() {
+        warnings::warn(format!(
+            "{} image(s) are in the exporting cluster's internal registry ({}); they only pull if pushed to this cluster's registry too",
+            internal.len(),
+            internal.join(", ")
+        ));
+    }
+
+    let (rt, client, config) = connect()?;
+    let mappings: Vec<(String, String)> = state.images.iter().map(|m| (m.env_var.clone(), m.image.clone())).collect();
+    mapping::display_mapping_table(&mappings);
+
+    let pb = progress::stage_spinner("Finding operator controller deployment");
+    let (namespace, deployment) = operator::find_operator_deployment(&rt, &client)?;
+    progress::finish_spinner(&pb, true);
+
+    let pb = progress::stage_spinner("Patching operator Deployment with IMAGE_ env vars");
+    operator::patch_operator_deployment_env(&rt, &client, &namespace, &deployment, &mappings)?;
+    progress::finish_spinner(&pb, true);
+    eprintln!("  Patched {}/{} with {} IMAGE_ env vars", namespace, deployment, mappings.len());
+
+    // Re-render every component one of the env vars belongs to
+    let mut components: Vec<&String> = config
+        .components
+        .iter()
+        .filter(|(_, c)| c.images.values().any(|env| mappings.iter().any(|(k, _)| k == env)))
+        .map(|(name, _)| name)
+        .collect();
+    components.sort();
+    let pb = progress::stage_spinner("Deleting InstallerSets to trigger re-reconciliation");
+    let mut deleted = 0;
+    for component in &components {
+        let prefix = config.components[*component].installer_set_prefix.as_deref();
+        deleted += operator::delete_installer_sets(&rt, &client, component, prefix)?;
+    }
+    progress::finish_spinner(&pb, true);
+    eprintln!("  Deleted {} InstallerSets of {}", deleted, components.iter().map(|c| c.as_str()).collect::<Vec<_>>().join(", "));
+
+    eprintln!();
+    let reconciled = match wait::wait_for_reconciliation(&rt, &client, &mappings, &[], verbose) {
+        Ok(()) => true,
+        Err(e) => {
+            warnings::warn("Reconciliation wait failed:");
+            eprintln!("  {}", e);
+            false
+        }
+    };
+
+    let rendered = read_installer_sets(&rt, &client, &config, &components)?;
+    let missing_images = missing_images(&state.installer_sets, &rendered);
+    if missing_images.is_empty() {
+        eprintln!("Imported deploy state matches the exported InstallerSet images.");
+    } else {
+        warnings::warn("Re-rendered InstallerSets do not reference some exported images:");
+        for m in &missing_images {
+            for image in &m.images {
+                eprintln!("  {}: {}", m.component, image);
+            }
+        }
+    }
+
+    Ok(ImportReport {
+        namespace,
+        deployment,
+        mappings: state.images,
+        installer_sets_deleted: deleted,
+        reconciled,
+        missing_images,
+    })
+}
+
+/// Images rendered into the InstallerSets of `components`.
+fn read_installer_sets(
+    rt: &tokio::runtime::Runtime,
+    client: &kube::Client,
+    config: &config::Config,
+    components: &[&String],
+) -> anyhow::Result<Vec<InstallerSetImages>> {
+    let mut sets = Vec::new();
+    for component in components {
+        let prefix = config.components[*component].installer_set_prefix.as_deref();
+        for set in operator::list_installer_sets(rt, client, component, prefix)? {
+            sets.push(InstallerSetImages {
+                component: component.to_string(),
+                name: set.metadata.name.clone().unwrap_or_default(),
+                images: installer_set_images(&set),
+            });
+        }
+    }
+    Ok(sets)
+}
+
+/// Image references in the manifests of an InstallerSet: `image` fields and
+/// any other value pinned by digest (e.g. images passed to controllers as args or env).
+fn installer_set_images(set: &DynamicObject) -> BTreeSet<String> {
+    let mut images = BTreeSet::new();
+    if let Some(manifests) = set.data.get("spec").and_then(|s| s.get("manifests")) {
+        collect_images(manifests, None, &mut images);
+    }
+    images
+}
+
+fn collect_images(value: &Value, key: Option<&str>, out: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                collect_images(v, Some(k), out);
+            }
+        }
+        Value::Array(items) => {
+            for v in items {
+                collect_images(v, key, out);
+            }
+        }
+        Value::String(s) if key == Some("image") || s.contains("@sha256:") => {
+            out.insert(s.clone());
+        }
+        _ => {}
+    }
+}
+
+/// Per component, the exported images no re-rendered InstallerSet references.
+/// InstallerSet names carry a random suffix, so images are compared per component.
+fn missing_images(exported: &[InstallerSetImages], rendered: &[InstallerSetImages]) -> Vec<InstallerSetImages> {
+    let mut components: Vec<&str> = rendered.iter().map(|s| s.component.as_str()).collect();
+    components.dedup();
+    components
+        .into_iter()
+        .filter_map(|component| {
+            let now: BTreeSet<&String> = rendered
+                .iter()
+                .filter(|s| s.component == component)
+                .flat_map(|s| &s.images)
+                .collect();
+            let images: BTreeSet<String> = exported
+                .iter()
+                .filter(|s| s.component == component)
+                .flat_map(|s| &s.images)
+                .filter(|i| !now.contains(i))
+                .cloned()
+                .collect();
+            (!images.is_empty()).then(|| InstallerSetImages {
+                component: component.to_string(),
+                name: String::new(),
+                images,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn set(component: &str, images: &[&str]) -> InstallerSetImages {
+        InstallerSetImages {
+            component: component.to_string(),
+            name: format!("{}-main-deployment-abc", component),
+            images: images.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_installer_set_images_and_missing() {
+        let mut obj = DynamicObject::new(
+            "pipeline-main-deployment-abc",
+            &kube::api::ApiResource::erase::<k8s_openapi::api::apps::v1::Deployment>(&()),
+        );
+        obj.data = json!({"spec": {"manifests": [{
+            "kind": "Deployment",
+            "spec": {"template": {"spec": {"containers": [{
+                "image": "quay.io/tekton/controller:v1",
+                "args": ["-entrypoint-image", "quay.io/tekton/entrypoint@sha256:abc", "-v"]
+            }]}}}
+        }]}});
+        let images: Vec<String> = installer_set_images(&obj).into_iter().collect();
+        assert_eq!(images, ["quay.io/tekton/controller:v1", "quay.io/tekton/entrypoint@sha256:abc"]);
+
+        let exported = [set("pipeline", &["a", "b"]), set("triggers", &["t"])];
+        let rendered = [set("pipeline", &["a"]), set("triggers", &["t", "u"])];
+        assert_eq!(missing_images(&exported, &rendered), [InstallerSetImages {
+            component: "pipeline".to_string(),
+            name: String::new(),
+            images: BTreeSet::from(["b".to_string()]),
+        }]);
+    }
+}
@@ -4,7 +4,8 @@ use kube::api::{Api, ApiResource, DynamicObject, ListParams};
 use kube::Client;
 use tokio::runtime::Runtime;
 
-use crate::progress;
+use super::pullsecret;
+use crate::{k8s, progress, warnings};
 
 /// Known namespaces where Tekton pods may run.
 const TEKTON_NAMESPACES: &[&str] = &["openshift-pipelines", "tekton-pipelines"];
@@ -12,10 +13,13 @@ const TEKTON_NAMESPACES: &[&str] = &["openshift-pipelines", "tekton-pipelines"];
 /// Wait for operator reconciliation: TektonConfig Ready=True and pod images matching expected.
 ///
 /// Uses exponential backoff: start 5s, double each time, cap at 60s, max 10 retries.
+/// While images don't match, pods in `pull_secret_namespaces` stuck on image pulls
+/// are restarted once their ServiceAccounts have the pull secret.
 pub fn wait_for_reconciliation(
     rt: &Runtime,
     client: &Client,
     expected_images: &[(String, String)],
+    pull_secret_namespaces: &[String],
     verbose: bool,
 ) -> anyhow::Result<()> {
     let max_retries: u32 = 20;
@@ -29,10 +33,6 @@ pub fn wait_for_reconciliation(
             "Retry {}/{}: checking reconciliation status...",
             attempt, max_retries
         ));
-        eprintln!(
-            "  Retry {}/{}: checking reconciliation status...",
-            attempt, max_retries
-        );
 
         let config_ready = check_tektonconfig_ready(rt, client, verbose)?;
         let images_ok = if config_ready {
@@ -50,6 +50,14 @@ pub fn wait_for_reconciliation(
             return Ok(());
         }
 
+        // The operator recreates ServiceAccounts along with the InstallerSets,
+        // so pods may have been admitted before the pull secret was re-linked
+        if !pull_secret_namespaces.is_empty()
+            && let Err(e) = pullsecret::repair_pull_failures(rt, client, pull_secret_namespaces)
+        {
+            warnings::warn(format!("Could not check for image pull failures: {e:#}"));
+        }
+
         if attempt < max_retries {
             std::thread::sleep(std::time::Duration::from_secs(delay_secs));
             delay_secs = (delay_secs * 2).min(cap_secs);
@@ -86,24 +94,22 @@ fn check_tektonconfig_ready(
     };
 
     let api: Api<DynamicObject> = Api::all_with(client.clone(), &ar);
-    let tc = rt
-        .block_on(api.get("config"))
+    let tc = k8s::block_on_retry(rt, "get TektonConfig", || api.get("config"))
         .context("Failed to get TektonConfig 'config'")?;
 
     // Parse status.conditions from the dynamic object
-    if let Some(status) = tc.data.get("status") {
-        if let Some(conditions) = status.get("conditions") {
-            if let Some(conditions_arr) = conditions.as_array() {
-                for cond in conditions_arr {
-                    let ctype = cond.get("type").and_then(|v| v.as_str()).unwrap_or("");
-                    let cstatus = cond.get("status").and_then(|v| v.as_str()).unwrap_or("");
-                    if verbose {
-                        eprintln!("    TektonConfig condition: {}={}", ctype, cstatus);
-                    }
-                    if ctype == "Ready" && cstatus == "True" {
-                        return Ok(true);
-                    }
-                }
+    if let Some(status) = tc.data.get("status")
+        && let Some(conditions) = status.get("conditions")
+        && let Some(conditions_arr) = conditions.as_array()
+    {
+        for cond in conditions_arr {
+            let ctype = cond.get("type").and_then(|v| v.as_str()).unwrap_or("");
+            let cstatus = cond.get("status").and_then(|v| v.as_str()).unwrap_or("");
+            if verbose {
+                eprintln!("    TektonConfig condition: {}={}", ctype, cstatus);
+            }
+            if ctype == "Ready" && cstatus == "True" {
+                return Ok(true);
             }
         }
     }
@@ -127,8 +133,7 @@ fn verify_pod_images(
     for ns in TEKTON_NAMESPACES {
         let api: Api<Pod> = Api::namespaced(client.clone(), ns);
         let lp = ListParams::default().labels("app.kubernetes.io/part-of=tekton-pipelines");
-        let pods = rt
-            .block_on(api.list(&lp))
+        let pods = k8s::block_on_retry(rt, "list Pods", || api.list(&lp))
             .unwrap_or_else(|_| kube::api::ObjectList {
                 metadata: Default::default(),
                 items: vec![],
@@ -0,0 +1,363 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::exec;
+use crate::component::ComponentSpec;
+use crate::config::ComponentConfig;
+use crate::github;
+use crate::gitops;
+use crate::warnings;
+use crate::workspace;
+
+/// Resolved component info for dry-run display.
+#[derive(Debug, Serialize)]
+pub struct ResolvedComponent {
+    pub name: String,
+    pub repo_url: String,
+    pub git_ref: String,
+    pub resolved_sha: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit_message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub as_of_date: Option<String>,
+    pub import_paths: Vec<String>,
+    pub image_names: Vec<String>,
+}
+
+/// Resolve all component specs to ResolvedComponent with SHA lookup and optional as-of date resolution.
+///
+/// When `as_of` is provided, components without explicit git refs will resolve to the commit
+/// that was HEAD at end-of-day UTC on that date. Components with explicit refs ignore as_of.
+pub fn resolve_components_with_date(
+    specs: &[ComponentSpec],
+    configs: &HashMap<String, ComponentConfig>,
+    as_of: Option<&str>,
+) -> Vec<ResolvedComponent> {
+    resolve_components_for_dates(specs, configs, &[as_of]).pop().unwrap_or_default()
+}
+
+/// Resolve `specs` once per entry of `dates` (as with `resolve_components_with_date`).
+/// Lookups are batched per repository: one `git ls-remote` for all refs, and one
+/// shared shallow clone for as-of dates the cache cannot answer. Repositories are
+/// resolved in parallel.
+pub fn resolve_components_for_dates(
+    specs: &[ComponentSpec],
+    configs: &HashMap<String, ComponentConfig>,
+    dates: &[Option<&str>],
+) -> Vec<Vec<ResolvedComponent>> {
+    let mut per_repo: BTreeMap<&str, BTreeSet<Lookup>> = BTreeMap::new();
+    for date in dates {
+        for spec in specs {
+            if let Some(cfg) = configs.get(&spec.name) {
+                per_repo.entry(cfg.repo.as_str()).or_default().insert(Lookup::for_spec(spec, *date));
+            }
+        }
+    }
+
+    let resolved: HashMap<(String, Lookup), Resolution> = std::thread::scope(|scope| {
+        let handles: Vec<_> = per_repo
+            .iter()
+            .map(|(repo, lookups)| scope.spawn(move || resolve_repo(repo, lookups)))
+            .collect();
+        handles.into_iter().flat_map(|h| h.join().unwrap_or_default()).collect()
+    });
+
+    dates
+        .iter()
+        .map(|date| {
+            specs
+                .iter()
+                .filter_map(|spec| {
+                    let cfg = configs.get(&spec.name)?;
+                    let lookup = Lookup::for_spec(spec, *date);
+                    let r = resolved.get(&(cfg.repo.clone(), lookup))?.clone();
+
+                    let image_names: Vec<String> = cfg
+                        .import_paths
+                        .iter()
+                        .filter_map(|p| p.rsplit('/').next())
+                        .map(|s| s.to_string())
+                        .collect();
+
+                    Some(ResolvedComponent {
+                        name: spec.name.clone(),
+                        repo_url: cfg.repo.clone(),
+                        git_ref: r.git_ref,
+                        resolved_sha: r.sha,
+                        commit_date: r.commit_date,
+                        commit_message: r.commit_message,
+                        as_of_date: r.as_of_date,
+                        import_paths: cfg.import_paths.clone(),
+                        image_names,
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// What a component resolves: explicit ref > as-of date > HEAD.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Lookup {
+    Ref(String),
+    AsOf(String),
+    Head,
+}
+
+impl Lookup {
+    fn for_spec(spec: &ComponentSpec, as_of: Option<&str>) -> Self {
+        if let Some(ref r) = spec.git_ref {
+            Lookup::Ref(r.clone())
+        } else if let Some(date) = as_of.or(spec.as_of_date.as_deref()) {
+            Lookup::AsOf(date.to_string())
+        } else {
+            Lookup::Head
+        }
+    }
+}
+
+/// Outcome of one lookup, as shown in the plan.
+#[derive(Debug, Clone)]
+struct Resolution {
+    git_ref: String,
+    sha: String,
+    commit_date: Option<String>,
+    commit_message: Option<String>,
+    as_of_date: Option<String>,
+}
+
+/// How far before the earliest as-of date the shared clone reaches, so a date
+/// still finds its last commit after a quiet stretch.
+const SHALLOW_SINCE_MARGIN_DAYS: i64 = 30;
+
+/// Resolve every lookup for one repository.
+fn resolve_repo(repo_url: &str, lookups: &BTreeSet<Lookup>) -> Vec<((String, Lookup), Resolution)> {
+    let wanted: Vec<String> = lookups
+        .iter()
+        .filter_map(|l| match l {
+            Lookup::Ref(r) if !is_commit_sha(r) => Some(gitops::resolve_ref(r)),
+            _ => None,
+        })
+        .collect();
+    let refs = ls_remote(repo_url, &wanted).unwrap_or_default();
+    let head = || Resolution {
+        git_ref: "HEAD".to_string(),
+        sha: match_ref(&refs, "HEAD").unwrap_or_else(|| "N/A".to_string()),
+        commit_date: None,
+        commit_message: None,
+        as_of_date: None,
+    };
+
+    // As-of dates: the cache first, then one shallow clone for the rest, then the API
+    let dates: Vec<&str> = lookups
+        .iter()
+        .filter_map(|l| match l {
+            Lookup::AsOf(d) => Some(d.as_str()),
+            _ => None,
+        })
+        .collect();
+    let mut commits: HashMap<&str, github::CommitInfo> = dates
+        .iter()
+        .filter_map(|d| github::cached_commit_before_date(repo_url, d).map(|c| (*d, c)))
+        .collect();
+    let missing: Vec<&str> = dates.iter().copied().filter(|d| !commits.contains_key(d)).collect();
+    if !missing.is_empty() && !github::is_offline()
+        && let Some(clone) = shallow_clone(repo_url, missing.iter().min().copied().unwrap_or_default())
+    {
+        for date in &missing {
+            if let Some(info) = commit_before_date(clone.path(), date) {
+                github::store_commit_before_date(repo_url, date, &info);
+                commits.insert(date, info);
+            }
+        }
+    }
+
+    lookups
+        .iter()
+        .map(|lookup| {
+            let resolution = match lookup {
+                Lookup::Head => head(),
+                Lookup::Ref(r) => {
+                    let sha = if is_commit_sha(r) {
+                        short_sha(r)
+                    } else {
+                        match_ref(&refs, &gitops::resolve_ref(r)).unwrap_or_else(|| "N/A".to_string())
+                    };
+                    Resolution { git_ref: r.clone(), sha, commit_date: None, commit_message: None, as_of_date: None }
+                }
+                Lookup::AsOf(date) => {
+                    let info = match commits.get(date.as_str()) {
+                        Some(info) => Ok(info.clone()),
+                        None => github::resolve_commit_before_date(repo_url, date),
+                    };
+                    match info {
+                        Ok(info) => Resolution {
+                            git_ref: format!("as-of:{}", date),
+                            sha: short_sha(&info.sha),
+                            commit_date: Some(info.date),
+                            commit_message: Some(info.message),
+                            as_of_date: Some(date.clone()),
+                        },
+                        Err(e) => {
+                            warnings::warn(format!("Could not resolve as-of date {} for {}: {}", date, repo_url, e));
+                            head()
+                        }
+                    }
+                }
+            };
+            ((repo_url.to_string(), lookup.clone()), resolution)
+        })
+        .collect()
+}
+
+pub(crate) fn is_commit_sha(r: &str) -> bool {
+    (7..=40).contains(&r.len()) && r.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn short_sha(sha: &str) -> String {
+    sha[..std::cmp::min(sha.len(), 12)].to_string()
+}
+
+/// One `git ls-remote` for HEAD plus `patterns`: (sha, refname) pairs.
+pub(crate) fn ls_remote(repo_url: &str, patterns: &[String]) -> anyhow::Result<Vec<(String, String)>> {
+    Ok(parse_ls_remote(&gitops::ls_remote(repo_url, patterns)?))
+}
+
+fn parse_ls_remote(stdout: &str) -> Vec<(String, String)> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let (sha, name) = line.split_once('\t')?;
+            Some((sha.trim().to_string(), name.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Short SHA `wanted` points at: an exact ref name, else a tag (peeled to its
+/// commit), else a branch.
+pub(crate) fn match_ref(refs: &[(String, String)], wanted: &str) -> Option<String> {
+    let candidates = [
+        wanted.to_string(),
+        format!("refs/tags/{}^{{}}", wanted),
+        format!("refs/tags/{}", wanted),
+        format!("refs/heads/{}", wanted),
+    ];
+    candidates
+        .iter()
+        .find_map(|c| refs.iter().find(|(_, name)| name == c))
+        .map(|(sha, _)| short_sha(sha))
+}
+
+/// Commit-only shallow clone of the default branch reaching back before `earliest_date`.
+fn shallow_clone(repo_url: &str, earliest_date: &str) -> Option<workspace::ScopedDir> {
+    let since = crate::timestamp::parse_date(earliest_date).ok()? - chrono::Duration::days(SHALLOW_SINCE_MARGIN_DAYS);
+    let dir = workspace::scoped("dry-run-clone").ok()?;
+    match gitops::bare_clone_since(repo_url, dir.path(), &since.format("%Y-%m-%d").to_string()) {
+        Ok(()) => Some(dir),
+        Err(e) => {
+            warnings::warn(format!("Shallow clone of {} failed, resolving as-of dates via the GitHub API: {e:#}", repo_url));
+            None
+        }
+    }
+}
+
+/// Last commit on the cloned branch at end-of-day UTC on `date`.
+fn commit_before_date(clone: &Path, date: &str) -> Option<github::CommitInfo> {
+    let until = crate::timestamp::to_rfc3339(crate::timestamp::end_of_day_utc(date).ok()?);
+    let dir = clone.to_string_lossy();
+    let before = format!("--before={}", until);
+    let r = exec::run_cmd_unchecked(
+        "git",
+        &["-C", &dir, "log", "-1", &before, "--format=%H%x1f%aI%x1f%s", "HEAD"],
+    )
+    .ok()?;
+    let line = r.stdout.trim();
+    let mut fields = line.splitn(3, '\x1f');
+    let sha = fields.next().filter(|s| !s.is_empty())?;
+    Some(github::CommitInfo {
+        sha: sha.to_string(),
+        date: fields.next().unwrap_or("").to_string(),
+        message: fields.next().unwrap_or("").to_string(),
+    })
+}
+
+/// Print a human-readable table of resolved components.
+pub fn print_table(resolved: &[ResolvedComponent]) {
+    // Check if any component has as-of resolution (to decide column format)
+    let has_as_of = resolved.iter().any(|r| r.as_of_date.is_some());
+
+    if has_as_of {
+        println!(
+            "{:<12} {:<50} {:<14} {:<14} {:<12} MESSAGE",
+            "COMPONENT", "REPO", "REF", "COMMIT SHA", "DATE"
+        );
+        println!(
+            "{:<12} {:<50} {:<14} {:<14} {:<12} -------",
+            "---------", "----", "---", "----------", "----"
+        );
+        for rc in resolved {
+            let date_display = rc
+                .commit_date
+                .as_ref()
+                .map(|d| &d[..10.min(d.len())]) // Just YYYY-MM-DD part
+                .unwrap_or("-");
+            let msg_display = rc.commit_message.as_deref().unwrap_or("-");
+            // Truncate message to 40 chars
+            let msg_truncated = if msg_display.len() > 40 {
+                format!("{}...", &msg_display[..37])
+            } else {
+                msg_display.to_string()
+            };
+            println!(
+                "{:<12} {:<50} {:<14} {:<14} {:<12} {}",
+                rc.name, rc.repo_url, rc.git_ref, rc.resolved_sha, date_display, msg_truncated
+            );
+        }
+    } else {
+        // Original format without date/message columns
+        println!(
+            "{:<12} {:<50} {:<12} {:<14} IMAGES",
+            "COMPONENT", "REPO", "REF", "COMMIT SHA"
+        );
+        println!(
+            "{:<12} {:<50} {:<12} {:<14} ------",
+            "---------", "----", "---", "----------"
+        );
+        for rc in resolved {
+            println!(
+                "{:<12} {:<50} {:<12} {:<14} {}",
+                rc.name,
+                rc.repo_url,
+                rc.git_ref,
+                rc.resolved_sha,
+                rc.image_names.join(", ")
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_ref_prefers_peeled_tags() {
+        let refs = parse_ls_remote(
+            "1111111111111111\tHEAD\n\
+             2222222222222222\trefs/heads/main\n\
+             3333333333333333\trefs/tags/v0.62.0\n\
+             4444444444444444\trefs/tags/v0.62.0^{}\n\
+             5555555555555555\trefs/pull/123/head\n",
+        );
+        assert_eq!(match_ref(&refs, "HEAD").as_deref(), Some("111111111111"));
+        assert_eq!(match_ref(&refs, "main").as_deref(), Some("222222222222"));
+        assert_eq!(match_ref(&refs, "v0.62.0").as_deref(), Some("444444444444"));
+        assert_eq!(match_ref(&refs, "refs/pull/123/head").as_deref(), Some("555555555555"));
+        assert_eq!(match_ref(&refs, "release-v1"), None);
+        assert!(is_commit_sha("abc1234") && !is_commit_sha("main"));
+    }
+}
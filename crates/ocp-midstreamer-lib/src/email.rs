@@ -0,0 +1,420 @@
+//! Email reports of run summaries (`config/email.toml`).
+//!
+//! A run's `results/summary.md` is sent as a multipart message, the markdown as
+//! the plain-text part next to a rendered HTML part, through curl's SMTP support.
+//! `mode` sends after every run, only after runs with failures, or queues each
+//! summary for `streamstress email digest` to send as one email.
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+
+use crate::config::{self, EmailConfig, EmailMode};
+use crate::{exec, results, timestamp, warnings};
+
+/// Environment variable carrying the email settings (JSON) into in-cluster Jobs.
+pub const EMAIL_CONFIG_ENV: &str = "STREAMSTRESS_EMAIL_CONFIG";
+
+/// A run summary to send or queue in the digest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    pub subject: String,
+    pub markdown: String,
+    pub failed: bool,
+    pub timestamp: String,
+}
+
+/// The email settings: from `EMAIL_CONFIG_ENV` in Jobs, else `config/email.toml`.
+pub fn load_config() -> Result<EmailConfig> {
+    match std::env::var(EMAIL_CONFIG_ENV) {
+        Ok(json) => serde_json::from_str(&json).with_context(|| format!("Invalid email settings in {EMAIL_CONFIG_ENV}")),
+        Err(_) => config::load_email_config(&config::default_email_config_path()),
+    }
+}
+
+/// The enabled email settings as JSON for `EMAIL_CONFIG_ENV` of an in-cluster Job.
+pub fn job_config() -> Option<String> {
+    match load_config() {
+        Ok(cfg) if cfg.enabled => serde_json::to_string(&cfg).ok(),
+        Ok(_) => None,
+        Err(e) => {
+            warnings::warn(format!("Email reports disabled for the Job: {e:#}"));
+            None
+        }
+    }
+}
+
+/// SMTP_USERNAME / SMTP_PASSWORD, when both are set.
+pub fn smtp_credentials() -> Option<(String, String)> {
+    let username = std::env::var("SMTP_USERNAME").ok().filter(|u| !u.is_empty())?;
+    let password = std::env::var("SMTP_PASSWORD").ok().filter(|p| !p.is_empty())?;
+    Some((username, password))
+}
+
+/// Build the report of the run in `output_dir` from its `results/`.
+pub fn run_report(cfg: &EmailConfig, output_dir: &Path, label: Option<&str>) -> Result<Report> {
+    let results_dir = output_dir.join("results");
+    let result = results::read_results_json(&results_dir.join("results.json"))?;
+    let markdown = std::fs::read_to_string(results_dir.join("summary.md"))
+        .unwrap_or_else(|_| results::markdown_summary(&results::categorize_results(&result)));
+
+    let failed = result.failed > 0;
+    let mut subject = format!(
+        "{} {}: {}/{} passed",
+        cfg.subject_prefix,
+        if failed { "FAILED" } else { "PASSED" },
+        result.passed,
+        result.total
+    );
+    if let Some(label) = label {
+        subject.push_str(&format!(" ({label})"));
+    }
+    Ok(Report { subject, markdown, failed, timestamp: timestamp::now_rfc3339() })
+}
+
+/// Email the report of the run in `output_dir` according to `mode`. Returns what
+/// was done, or None when email reports are disabled.
+pub fn report_run(output_dir: &Path, label: Option<&str>) -> Result<Option<String>> {
+    let cfg = load_config()?;
+    if !cfg.enabled {
+        return Ok(None);
+    }
+    let report = run_report(&cfg, output_dir, label)?;
+    let outcome = match cfg.mode {
+        EmailMode::OnFailure if !report.failed => "not sent, no failures (mode = on-failure)".to_string(),
+        EmailMode::Always | EmailMode::OnFailure => {
+            send(&cfg, &render_message(&cfg, &report.subject, &report.markdown))?;
+            format!("sent to {}", cfg.to.join(", "))
+        }
+        EmailMode::Digest => {
+            queue(&cfg, &report)?;
+            format!("queued in {}", cfg.digest_file)
+        }
+    };
+    Ok(Some(outcome))
+}
+
+/// Email the report of the run in `output_dir` now, whatever the mode (and even
+/// when reports are disabled). With `dry_run`, print the message instead.
+pub fn send_run(output_dir: &Path, label: Option<&str>, dry_run: bool) -> Result<()> {
+    let cfg = load_config()?;
+    let report = run_report(&cfg, output_dir, label)?;
+    let message = render_message(&cfg, &report.subject, &report.markdown);
+    if dry_run {
+        println!("{}", message);
+        return Ok(());
+    }
+    send(&cfg, &message)?;
+    eprintln!("Sent \"{}\" to {}", report.subject, cfg.to.join(", "));
+    Ok(())
+}
+
+/// `report_run` after `run`, with the label from RUN_LABEL. Never fails the run.
+pub async fn maybe_report(output_dir: &str) {
+    let output_dir = output_dir.to_string();
+    let label = std::env::var("RUN_LABEL").ok();
+    let outcome = tokio::task::spawn_blocking(move || report_run(Path::new(&output_dir), label.as_deref())).await;
+    match outcome {
+        Ok(Ok(Some(outcome))) => eprintln!("Email report: {}", outcome),
+        Ok(Ok(None)) => {}
+        Ok(Err(e)) => warnings::warn(format!("Email report failed: {e:#}")),
+        Err(e) => warnings::warn(format!("Email report task panicked: {e}")),
+    }
+}
+
+/// Append `report` to the digest queue.
+fn queue(cfg: &EmailConfig, report: &Report) -> Result<()> {
+    let path = Path::new(&cfg.digest_file);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(report)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// The reports queued in the digest file, oldest first.
+fn queued(cfg: &EmailConfig) -> Result<Vec<Report>> {
+    let path = Path::new(&cfg.digest_file);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut reports = Vec::new();
+    for (i, line) in content.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(report) => reports.push(report),
+            Err(e) => warnings::warn(format!("Skipping {} line {}: {}", path.display(), i + 1, e)),
+        }
+    }
+    Ok(reports)
+}
+
+/// Subject and markdown of one email covering all `reports`.
+fn digest(cfg: &EmailConfig, reports: &[Report]) -> (String, String) {
+    let failed = reports.iter().filter(|r| r.failed).count();
+    let subject = format!("{} Digest: {} run(s), {} with failures", cfg.subject_prefix, reports.len(), failed);
+    let markdown = reports
+        .iter()
+        .map(|r| format!("# {}\n\n_{}_\n\n{}", r.subject, r.timestamp, r.markdown.trim_end()))
+        .collect::<Vec<_>>()
+        .join("\n\n---\n\n");
+    (subject, markdown)
+}
+
+/// Send the queued reports as one email and empty the queue. With `dry_run`,
+/// print the message instead and keep the queue. Returns the number of reports.
+pub fn send_digest(cfg: &EmailConfig, dry_run: bool) -> Result<usize> {
+    let reports = queued(cfg)?;
+    if reports.is_empty() {
+        return Ok(0);
+    }
+    let (subject, markdown) = digest(cfg, &reports);
+    let message = render_message(cfg, &subject, &markdown);
+    if dry_run {
+        println!("{}", message);
+        return Ok(reports.len());
+    }
+    send(cfg, &message)?;
+    std::fs::write(&cfg.digest_file, "").with_context(|| format!("Failed to empty {}", cfg.digest_file))?;
+    Ok(reports.len())
+}
+
+/// Check that `cfg` names a server, a sender and recipients.
+pub fn validate(cfg: &EmailConfig) -> Result<()> {
+    if cfg.smtp_url.is_empty() || cfg.from.is_empty() || cfg.to.is_empty() {
+        bail!("Email reports need smtp_url, from and to in {}", config::default_email_config_path().display());
+    }
+    Ok(())
+}
+
+/// Send an RFC 5322 `message` with curl, authenticating with SMTP_USERNAME /
+/// SMTP_PASSWORD when set. The credentials go through a curl config file so they
+/// never appear on a command line.
+pub fn send(cfg: &EmailConfig, message: &str) -> Result<()> {
+    validate(cfg)?;
+    let mut message_file = tempfile::NamedTempFile::new()?;
+    message_file.write_all(message.as_bytes())?;
+    let message_path = message_file.path().to_string_lossy().to_string();
+
+    let mut args: Vec<&str> = vec!["--silent", "--show-error", "--url", &cfg.smtp_url, "--mail-from", &cfg.from];
+    for rcpt in &cfg.to {
+        args.extend(["--mail-rcpt", rcpt.as_str()]);
+    }
+    args.extend(["--upload-file", &message_path]);
+    if cfg.require_tls {
+        args.push("--ssl-reqd");
+    }
+
+    let mut credentials_file = tempfile::NamedTempFile::new()?;
+    let credentials_path = credentials_file.path().to_string_lossy().to_string();
+    if let Some((username, password)) = smtp_credentials() {
+        exec::register_secret(&password);
+        let user = format!("{username}:{password}").replace('\\', "\\\\").replace('"', "\\\"");
+        writeln!(credentials_file, "user = \"{}\"", user)?;
+        args.extend(["--config", &credentials_path]);
+    }
+
+    exec::run_cmd_timeout("curl", &args, exec::API_TIMEOUT)
+        .with_context(|| format!("Failed to send email via {}", cfg.smtp_url))?;
+    Ok(())
+}
+
+/// An RFC 5322 multipart/alternative message: the markdown as text/plain and
+/// its HTML rendering as text/html, both base64-encoded.
+pub fn render_message(cfg: &EmailConfig, subject: &str, markdown: &str) -> String {
+    let boundary = format!("streamstress-{}", timestamp::unix_now());
+    let html = format!(
+        "<html><body style=\"font-family: sans-serif\">\n{}</body></html>\n",
+        markdown_to_html(markdown)
+    );
+    let mut message = String::new();
+    message.push_str(&format!("From: {}\r\n", cfg.from));
+    message.push_str(&format!("To: {}\r\n", cfg.to.join(", ")));
+    message.push_str(&format!("Subject: {}\r\n", encode_header(subject)));
+    message.push_str(&format!("Date: {}\r\n", chrono::Utc::now().to_rfc2822()));
+    message.push_str("MIME-Version: 1.0\r\n");
+    message.push_str(&format!("Content-Type: multipart/alternative; boundary=\"{}\"\r\n\r\n", boundary));
+    for (content_type, body) in [("text/plain", markdown), ("text/html", html.as_str())] {
+        message.push_str(&format!("--{}\r\n", boundary));
+        message.push_str(&format!("Content-Type: {}; charset=utf-8\r\n", content_type));
+        message.push_str("Content-Transfer-Encoding: base64\r\n\r\n");
+        message.push_str(&base64_lines(body));
+    }
+    message.push_str(&format!("--{}--\r\n", boundary));
+    message
+}
+
+/// A header value, RFC 2047-encoded when it isn't plain ASCII.
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        value.to_string()
+    } else {
+        format!("=?UTF-8?B?{}?=", base64::engine::general_purpose::STANDARD.encode(value))
+    }
+}
+
+/// Base64 of `body` in 76-character lines.
+fn base64_lines(body: &str) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(body);
+    let mut out = String::new();
+    for chunk in encoded.as_bytes().chunks(76) {
+        out.push_str(std::str::from_utf8(chunk).unwrap_or_default());
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Render the markdown of run summaries as HTML: headings, tables, lists, bold,
+/// italics and the `<details>` blocks of `results::markdown_summary`. Anything
+/// else becomes a paragraph.
+pub fn markdown_to_html(markdown: &str) -> String {
+    let mut html = String::new();
+    let mut in_list = false;
+    let mut in_table = false;
+    let mut header_row = false;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+        if in_list && !trimmed.starts_with("- ") {
+            html.push_str("</ul>\n");
+            in_list = false;
+        }
+        if in_table && !trimmed.starts_with('|') {
+            html.push_str("</table>\n");
+            in_table = false;
+        }
+
+        if trimmed.is_empty() {
+            continue;
+        } else if let Some(summary) = trimmed.strip_prefix("<details><summary>").and_then(|s| s.strip_suffix("</summary>")) {
+            html.push_str(&format!("<h4>{}</h4>\n", inline(summary)));
+        } else if trimmed == "</details>" {
+            continue;
+        } else if trimmed == "---" {
+            html.push_str("<hr>\n");
+        } else if let Some((level, text)) = heading(trimmed) {
+            html.push_str(&format!("<h{level}>{}</h{level}>\n", inline(text)));
+        } else if let Some(item) = trimmed.strip_prefix("- ") {
+            if !in_list {
+                html.push_str("<ul>\n");
+                in_list = true;
+            }
+            html.push_str(&format!("<li>{}</li>\n", inline(item)));
+        } else if trimmed.starts_with('|') {
+            let cells: Vec<&str> = trimmed.trim_matches('|').split('|').map(str::trim).collect();
+            if cells.iter().all(|c| !c.is_empty() && c.chars().all(|ch| ch == '-' || ch == ':')) {
+                continue;
+            }
+            if !in_table {
+                html.push_str("<table border=\"1\" cellpadding=\"4\" cellspacing=\"0\">\n");
+                in_table = true;
+                header_row = true;
+            }
+            let tag = if header_row { "th" } else { "td" };
+            header_row = false;
+            html.push_str("<tr>");
+            for cell in cells {
+                html.push_str(&format!("<{tag}>{}</{tag}>", inline(cell)));
+            }
+            html.push_str("</tr>\n");
+        } else {
+            html.push_str(&format!("<p>{}</p>\n", inline(trimmed)));
+        }
+    }
+    if in_list {
+        html.push_str("</ul>\n");
+    }
+    if in_table {
+        html.push_str("</table>\n");
+    }
+    html
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let text = line[level..].strip_prefix(' ')?;
+    (1..=6).contains(&level).then_some((level, text))
+}
+
+/// Escape `text` and render `**bold**`, `_italics_` and the status emoji codes.
+fn inline(text: &str) -> String {
+    let escaped = text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace(":white_check_mark:", "\u{2705}")
+        .replace(":x:", "\u{274c}");
+    let bold = wrap_pairs(&escaped, "**", "b");
+    if bold.len() > 2 && bold.starts_with('_') && bold.ends_with('_') {
+        format!("<i>{}</i>", &bold[1..bold.len() - 1])
+    } else {
+        bold
+    }
+}
+
+/// Replace pairs of `marker` with `<tag>`...`</tag>`, leaving an unpaired one as is.
+fn wrap_pairs(text: &str, marker: &str, tag: &str) -> String {
+    let parts: Vec<&str> = text.split(marker).collect();
+    if parts.len() < 3 {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            let unpaired = i == parts.len() - 1 && parts.len().is_multiple_of(2);
+            out.push_str(&if unpaired { marker.to_string() } else if i % 2 == 1 { format!("<{tag}>") } else { format!("</{tag}>") });
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg() -> EmailConfig {
+        EmailConfig {
+            enabled: true,
+            smtp_url: "smtps://smtp.example.com:465".to_string(),
+            from: "streamstress@example.com".to_string(),
+            to: vec!["qe@example.com".to_string(), "dev@example.com".to_string()],
+            ..EmailConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_render_message() {
+        let md = "## Test Results\n\n**9/10 passed**, 1 failed (12.0s)\n\n### By Component\n\n\
+                  | Component | Status |\n|---|---|\n| pipeline | :x: |\n\n\
+                  <details><summary>timeout (1)</summary>\n\n- a <b> test\n\n</details>\n";
+        let html = markdown_to_html(md);
+        assert!(html.contains("<h2>Test Results</h2>"));
+        assert!(html.contains("<p><b>9/10 passed</b>, 1 failed (12.0s)</p>"));
+        assert!(html.contains("<tr><th>Component</th><th>Status</th></tr>\n<tr><td>pipeline</td><td>\u{274c}</td></tr>\n</table>"));
+        assert!(html.contains("<h4>timeout (1)</h4>\n<ul>\n<li>a &lt;b&gt; test</li>\n</ul>"));
+        assert!(!html.contains("---"));
+
+        let message = render_message(&cfg(), "[streamstress] FAILED: 9/10 passed", md);
+        assert!(message.contains("To: qe@example.com, dev@example.com\r\n"));
+        assert!(message.contains("Subject: [streamstress] FAILED: 9/10 passed\r\n"));
+        assert_eq!(message.matches("Content-Transfer-Encoding: base64").count(), 2);
+        assert!(message.lines().all(|l| l.len() <= 998));
+        assert_eq!(encode_header("Résultats"), "=?UTF-8?B?UsOpc3VsdGF0cw==?=");
+
+        let reports = [
+            Report { subject: "a".into(), markdown: "one\n".into(), failed: true, timestamp: "t1".into() },
+            Report { subject: "b".into(), markdown: "two".into(), failed: false, timestamp: "t2".into() },
+        ];
+        let (subject, markdown) = digest(&cfg(), &reports);
+        assert_eq!(subject, "[streamstress] Digest: 2 run(s), 1 with failures");
+        assert_eq!(markdown, "# a\n\n_t1_\n\none\n\n---\n\n# b\n\n_t2_\n\ntwo");
+    }
+}
@@ -0,0 +1,259 @@
+//! Kubernetes Warning Events timeline for the test window.
+//!
+//! While gauge runs, Warning Events in the pipelines namespaces and the test
+//! namespaces are polled and recorded with the spec that was running when they
+//! happened (spec boundaries as detected for the profiler). The timeline is
+//! exported as `results/events.jsonl` with a rendered `results/events-summary.md`,
+//! so OOMKills, FailedScheduling and webhook denials show up next to the results.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use k8s_openapi::api::core::v1::Event;
+use kube::Client;
+use kube::api::{Api, ListParams};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::profile::SpecEvent;
+use crate::{k8s, warnings};
+
+/// How often Events are listed.
+const POLL_INTERVAL_SECS: u64 = 15;
+
+/// Namespaces of the operator and the components; other `openshift-*` and
+/// `kube-*` namespaces belong to the platform and are ignored.
+const PIPELINES_NAMESPACES: &[&str] = &["openshift-pipelines", "openshift-operators"];
+
+/// One occurrence of a Warning Event.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EventRecord {
+    pub time: String,
+    pub namespace: String,
+    /// `Kind/name` of the object the event is about
+    pub object: String,
+    pub reason: String,
+    pub message: String,
+    pub count: i32,
+    /// Spec running when the event happened
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spec: Option<String>,
+}
+
+/// Background poller of Warning Events.
+pub struct EventCollector {
+    stop_tx: watch::Sender<bool>,
+    poll_handle: JoinHandle<()>,
+    records: Arc<Mutex<Vec<EventRecord>>>,
+    /// Spec starts (and ends, as `None`) in the order gauge reported them
+    boundaries: Mutex<Vec<(DateTime<Utc>, Option<String>)>>,
+}
+
+impl EventCollector {
+    /// Start polling; only events from now on are recorded.
+    pub fn start(client: Client) -> Self {
+        let (stop_tx, mut stop_rx) = watch::channel(false);
+        let records: Arc<Mutex<Vec<EventRecord>>> = Arc::new(Mutex::new(Vec::new()));
+        let started = Utc::now();
+
+        let r = records.clone();
+        let poll_handle = tokio::spawn(async move {
+            let api: Api<Event> = Api::all(client);
+            let lp = ListParams::default().fields("type=Warning");
+            // Last seen count per event, so repeats of a series are recorded once each
+            let mut seen: HashMap<String, i32> = HashMap::new();
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(POLL_INTERVAL_SECS));
+            loop {
+                let stopping = tokio::select! {
+                    _ = interval.tick() => false,
+                    _ = stop_rx.changed() => true,
+                };
+                match k8s::retry("list Events", || api.list(&lp)).await {
+                    Ok(list) => {
+                        let new = list.items.iter().filter_map(|e| new_occurrence(e, started, &mut seen));
+                        r.lock().expect("events lock").extend(new);
+                    }
+                    Err(e) => warnings::warn(format!("Event poll failed (will retry): {e}")),
+                }
+                if stopping {
+                    break;
+                }
+            }
+        });
+
+        EventCollector { stop_tx, poll_handle, records, boundaries: Mutex::new(Vec::new()) }
+    }
+
+    /// Notify the collector of a spec boundary event.
+    pub fn notify_spec_event(&self, event: &SpecEvent) {
+        let spec = match event {
+            SpecEvent::SpecStart(name) => Some(name.clone()),
+            SpecEvent::SpecEnd => None,
+        };
+        self.boundaries.lock().expect("boundaries lock").push((Utc::now(), spec));
+    }
+
+    /// Poll a last time, stop, and return the events in time order, tagged with their spec.
+    pub async fn stop(self) -> Vec<EventRecord> {
+        let _ = self.stop_tx.send(true);
+        let _ = self.poll_handle.await;
+        let boundaries = self.boundaries.into_inner().expect("boundaries lock");
+        let mut records = std::mem::take(&mut *self.records.lock().expect("events lock"));
+        for record in &mut records {
+            record.spec = spec_at(&boundaries, &record.time);
+        }
+        records.sort_by(|a, b| a.time.cmp(&b.time));
+        records
+    }
+}
+
+/// Record for `event` if it is a new occurrence in a watched namespace since `started`.
+fn new_occurrence(event: &Event, started: DateTime<Utc>, seen: &mut HashMap<String, i32>) -> Option<EventRecord> {
+    let namespace = event.metadata.namespace.clone().unwrap_or_default();
+    if !is_watched_namespace(&namespace) {
+        return None;
+    }
+    let time = event
+        .last_timestamp
+        .as_ref()
+        .map(|t| t.0.to_string())
+        .or_else(|| event.event_time.as_ref().map(|t| t.0.to_string()))
+        .or_else(|| event.metadata.creation_timestamp.as_ref().map(|t| t.0.to_string()))?;
+    if parse_time(&time).is_none_or(|t| t < started) {
+        return None;
+    }
+    let count = event.count.or_else(|| event.series.as_ref().and_then(|s| s.count)).unwrap_or(1);
+    let uid = event.metadata.uid.clone().unwrap_or_default();
+    if seen.get(&uid).is_some_and(|&c| c >= count) {
+        return None;
+    }
+    seen.insert(uid, count);
+    let object = &event.involved_object;
+    Some(EventRecord {
+        time,
+        namespace,
+        object: format!("{}/{}", object.kind.as_deref().unwrap_or("?"), object.name.as_deref().unwrap_or("?")),
+        reason: event.reason.clone().unwrap_or_default(),
+        message: event.message.clone().unwrap_or_default().trim().to_string(),
+        count,
+        spec: None,
+    })
+}
+
+fn is_watched_namespace(namespace: &str) -> bool {
+    PIPELINES_NAMESPACES.contains(&namespace)
+        || !(namespace.is_empty() || namespace.starts_with("openshift") || namespace.starts_with("kube-") || namespace == "default")
+}
+
+fn parse_time(time: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(time).ok().map(|t| t.with_timezone(&Utc))
+}
+
+/// Spec whose window holds `time`: the last boundary at or before it.
+fn spec_at(boundaries: &[(DateTime<Utc>, Option<String>)], time: &str) -> Option<String> {
+    let time = parse_time(time)?;
+    boundaries.iter().take_while(|(t, _)| *t <= time).last().and_then(|(_, spec)| spec.clone())
+}
+
+/// Markdown summary: counts by reason, by spec, and the latest events.
+fn render_summary(records: &[EventRecord]) -> String {
+    let mut md = String::from("## Warning events during the tests\n\n");
+    if records.is_empty() {
+        md.push_str("No Warning events.\n");
+        return md;
+    }
+    let mut by_reason: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut by_spec: BTreeMap<&str, BTreeMap<&str, usize>> = BTreeMap::new();
+    for r in records {
+        *by_reason.entry(&r.reason).or_default() += 1;
+        *by_spec.entry(r.spec.as_deref().unwrap_or("(between specs)")).or_default().entry(&r.reason).or_default() += 1;
+    }
+    let mut reasons: Vec<(&str, usize)> = by_reason.into_iter().collect();
+    reasons.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+
+    md.push_str("| Reason | Events |\n|--------|--------|\n");
+    for (reason, n) in &reasons {
+        let _ = writeln!(md, "| {} | {} |", reason, n);
+    }
+    md.push_str("\n| Spec | Events | Reasons |\n|------|--------|---------|\n");
+    for (spec, reasons) in &by_spec {
+        let total: usize = reasons.values().sum();
+        let list: Vec<String> = reasons.iter().map(|(r, n)| format!("{} ×{}", r, n)).collect();
+        let _ = writeln!(md, "| {} | {} | {} |", spec, total, list.join(", "));
+    }
+    md.push_str("\n| Time | Namespace | Object | Reason | Message |\n|------|-----------|--------|--------|---------|\n");
+    for r in records.iter().rev().take(20) {
+        let message: String = r.message.replace('|', "\\|").replace('\n', " ").chars().take(200).collect();
+        let _ = writeln!(md, "| {} | {} | {} | {} | {} |", r.time, r.namespace, r.object, r.reason, message);
+    }
+    md
+}
+
+/// Write `results/events.jsonl` and `results/events-summary.md`, and print the counts by reason.
+pub fn write_timeline(output_dir: &Path, records: &[EventRecord]) -> Result<()> {
+    let results_dir = output_dir.join("results");
+    fs::create_dir_all(&results_dir).context("Failed to create results directory")?;
+    let mut jsonl = String::new();
+    for r in records {
+        jsonl.push_str(&serde_json::to_string(r)?);
+        jsonl.push('\n');
+    }
+    fs::write(results_dir.join("events.jsonl"), jsonl).context("Failed to write events.jsonl")?;
+    fs::write(results_dir.join("events-summary.md"), render_summary(records)).context("Failed to write events-summary.md")?;
+
+    let mut by_reason: BTreeMap<&str, usize> = BTreeMap::new();
+    for r in records {
+        *by_reason.entry(&r.reason).or_default() += 1;
+    }
+    let counts: Vec<String> = by_reason.iter().map(|(r, n)| format!("{} {}", r, n)).collect();
+    if counts.is_empty() {
+        println!("Warning events during the tests: none");
+    } else {
+        println!("Warning events during the tests: {} ({})", records.len(), counts.join(", "));
+    }
+    println!("Events timeline written to {}", results_dir.join("events.jsonl").display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_alignment_and_summary() {
+        let at = |t: &str| parse_time(t).unwrap();
+        let boundaries = vec![
+            (at("2026-01-01T10:00:00Z"), Some("specs/pipelines.spec".to_string())),
+            (at("2026-01-01T10:10:00Z"), None),
+            (at("2026-01-01T10:11:00Z"), Some("specs/triggers.spec".to_string())),
+        ];
+        assert_eq!(spec_at(&boundaries, "2026-01-01T09:59:00Z"), None);
+        assert_eq!(spec_at(&boundaries, "2026-01-01T10:05:00Z").as_deref(), Some("specs/pipelines.spec"));
+        assert_eq!(spec_at(&boundaries, "2026-01-01T10:10:30Z"), None);
+        assert_eq!(spec_at(&boundaries, "2026-01-01T10:12:00Z").as_deref(), Some("specs/triggers.spec"));
+
+        assert!(is_watched_namespace("openshift-pipelines"));
+        assert!(is_watched_namespace("releasetest-abcd"));
+        assert!(!is_watched_namespace("openshift-monitoring"));
+
+        let record = |reason: &str, spec: Option<&str>| EventRecord {
+            time: "2026-01-01T10:05:00Z".into(),
+            namespace: "releasetest-abcd".into(),
+            object: "Pod/build-pod".into(),
+            reason: reason.into(),
+            message: "Container step-build was OOMKilled".into(),
+            count: 1,
+            spec: spec.map(String::from),
+        };
+        let md = render_summary(&[record("OOMKilling", Some("specs/pipelines.spec")), record("FailedScheduling", None)]);
+        assert!(md.contains("| OOMKilling | 1 |"));
+        assert!(md.contains("| specs/pipelines.spec | 1 | OOMKilling ×1 |"));
+        assert!(md.contains("(between specs)"));
+        assert!(render_summary(&[]).contains("No Warning events"));
+    }
+}
@@ -0,0 +1,210 @@
+//! Gauge machine-readable execution events.
+//!
+//! `gauge run --machine-readable` prints one JSON event per line (suite, spec
+//! and scenario start/end, plus captured output) instead of its console
+//! report. The tracker here turns the stream into authoritative spec
+//! boundaries for the profiler and event collector, and into scenario spans
+//! (start, end, gauge's own duration) for log correlation and results. It also
+//! renders a console-style text that is printed and saved as
+//! `logs/test-stdout.log`, in the format `results::parse_gauge_stdout` reads.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::profile::SpecEvent;
+use crate::results::TestRunResult;
+use crate::timestamp;
+
+/// When a scenario ran, from its scenarioStart and scenarioEnd events.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ScenarioSpan {
+    pub spec: String,
+    pub scenario: String,
+    pub started_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<String>,
+    /// Duration gauge measured for the scenario
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passed: Option<bool>,
+}
+
+/// Path of the scenario spans a gauge run writes.
+pub fn timeline_path(output_dir: &Path) -> PathBuf {
+    output_dir.join("logs").join("scenario-timeline.json")
+}
+
+/// Scenario spans of the last gauge run in `output_dir` (empty if none were written).
+pub fn read_timeline(output_dir: &Path) -> Vec<ScenarioSpan> {
+    std::fs::read_to_string(timeline_path(output_dir))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// What one line of gauge's output amounts to.
+#[derive(Debug, Default, PartialEq)]
+pub struct Handled {
+    /// Console-style lines to print and log
+    pub text: Vec<String>,
+    /// Spec boundary, for the profiler and event collector
+    pub boundary: Option<SpecEvent>,
+}
+
+/// Follows the event stream; scenarios of parallel streams are told apart by id.
+#[derive(Debug, Default)]
+pub struct Tracker {
+    /// Spec heading by spec id (its file name)
+    specs: HashMap<String, String>,
+    /// Index in `spans` of each running scenario, by scenario id
+    running: HashMap<String, usize>,
+    pub spans: Vec<ScenarioSpan>,
+}
+
+impl Tracker {
+    /// Handle one line of `gauge run --machine-readable` output. Lines that are
+    /// not events (e.g. printed before gauge starts reporting) pass through as text.
+    pub fn handle(&mut self, line: &str) -> Handled {
+        let event: Value = match serde_json::from_str(line) {
+            Ok(v @ Value::Object(_)) if v.get("type").is_some() => v,
+            _ => return Handled { text: vec![line.to_string()], boundary: None },
+        };
+        let str_of = |key: &str| event.get(key).and_then(|v| v.as_str()).unwrap_or("").to_string();
+        let id = str_of("id");
+        let name = str_of("name");
+        let result = event.get("result");
+        let mut handled = Handled::default();
+
+        match event["type"].as_str().unwrap_or("") {
+            "specStart" => {
+                self.specs.insert(id, name.clone());
+                handled.text = vec![String::new(), format!("# {}", name)];
+                handled.boundary = Some(SpecEvent::SpecStart(name));
+            }
+            "specEnd" => handled.boundary = Some(SpecEvent::SpecEnd),
+            "scenarioStart" => {
+                let spec = self.specs.get(&str_of("parentId")).cloned().unwrap_or_default();
+                self.running.insert(id, self.spans.len());
+                self.spans.push(ScenarioSpan {
+                    spec,
+                    scenario: name.clone(),
+                    started_at: timestamp::now_rfc3339(),
+                    ended_at: None,
+                    duration_secs: None,
+                    passed: None,
+                });
+                handled.text = vec![format!("  ## {}", name)];
+            }
+            "scenarioEnd" => {
+                let status = result.and_then(|r| r["status"].as_str()).unwrap_or("");
+                let millis = result.and_then(|r| r["time"].as_f64());
+                if let Some(span) = self.running.remove(&id).and_then(|i| self.spans.get_mut(i)) {
+                    span.ended_at = Some(timestamp::now_rfc3339());
+                    span.duration_secs = millis.map(|ms| ms / 1000.0);
+                    span.passed = Some(status != "fail");
+                }
+                let took = millis.map(|ms| format!(" ({:.1}s)", ms / 1000.0)).unwrap_or_default();
+                match status {
+                    "fail" => {
+                        handled.text.push(format!("    ...[FAIL]{}", took));
+                        for message in result.map(error_messages).unwrap_or_default() {
+                            handled.text.push(format!("    Error Message: {}", message));
+                        }
+                        handled.text.push(String::new());
+                    }
+                    "skip" => handled.text.push(format!("    ...[SKIP]{}", took)),
+                    _ => handled.text.push(format!("    ...[PASS]{}", took)),
+                }
+            }
+            _ => {}
+        }
+        // Captured step output, on any event type
+        for key in ["out", "message"] {
+            if let Some(out) = event.get(key).and_then(|v| v.as_str()).filter(|o| !o.trim().is_empty()) {
+                handled.text.extend(out.trim_end().lines().map(String::from));
+            }
+        }
+        handled
+    }
+}
+
+/// Error messages of a failed result, including hook failures.
+fn error_messages(result: &Value) -> Vec<String> {
+    let mut errors: Vec<&Value> = result["errors"].as_array().map(|e| e.iter().collect()).unwrap_or_default();
+    errors.extend(["beforeHookFailure", "afterHookFailure"].iter().filter_map(|k| result.get(*k)).filter(|v| v.is_object()));
+    errors
+        .iter()
+        .filter_map(|e| e["message"].as_str())
+        .map(|m| m.lines().next().unwrap_or("").trim().to_string())
+        .filter(|m| !m.is_empty())
+        .collect()
+}
+
+/// Replace each test's duration with the one gauge reported for its scenario,
+/// matching on spec and scenario, or on the scenario alone. A run without a
+/// total duration gets the sum.
+pub fn apply_durations(result: &mut TestRunResult, spans: &[ScenarioSpan]) {
+    for test in result.tests.iter_mut() {
+        let span = spans
+            .iter()
+            .find(|s| s.scenario == test.scenario && s.spec == test.spec)
+            .or_else(|| spans.iter().find(|s| s.scenario == test.scenario));
+        if let Some(duration) = span.and_then(|s| s.duration_secs) {
+            test.duration_secs = duration;
+        }
+    }
+    if result.duration_secs == 0.0 {
+        result.duration_secs = result.tests.iter().map(|t| t.duration_secs).sum();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_events() {
+        let mut tracker = Tracker::default();
+        let start = tracker.handle(r#"{"type":"specStart","id":"specs/pipelines/run.spec","name":"Pipeline runs","filename":"/src/specs/pipelines/run.spec","line":1}"#);
+        assert_eq!(start.boundary, Some(SpecEvent::SpecStart("Pipeline runs".to_string())));
+        assert_eq!(start.text, vec!["".to_string(), "# Pipeline runs".to_string()]);
+
+        // Interleaved scenarios from parallel streams
+        tracker.handle(r#"{"type":"scenarioStart","id":"specs/pipelines/run.spec:5","parentId":"specs/pipelines/run.spec","name":"Run a pipeline"}"#);
+        tracker.handle(r#"{"type":"scenarioStart","id":"specs/pipelines/run.spec:20","parentId":"specs/pipelines/run.spec","name":"Cancel a pipeline"}"#);
+        let failed = tracker.handle(r#"{"type":"scenarioEnd","id":"specs/pipelines/run.spec:20","parentId":"specs/pipelines/run.spec","name":"Cancel a pipeline","result":{"status":"fail","time":4200,"errors":[{"text":"Verify status","message":"expected Cancelled, got Running\nstack..."}]}}"#);
+        assert_eq!(failed.text[0], "    ...[FAIL] (4.2s)");
+        assert_eq!(failed.text[1], "    Error Message: expected Cancelled, got Running");
+        tracker.handle(r#"{"type":"scenarioEnd","id":"specs/pipelines/run.spec:5","name":"Run a pipeline","result":{"status":"pass","time":61000}}"#);
+        assert_eq!(tracker.handle(r#"{"type":"specEnd","id":"specs/pipelines/run.spec"}"#).boundary, Some(SpecEvent::SpecEnd));
+        assert_eq!(tracker.handle("not an event").text, vec!["not an event".to_string()]);
+        assert_eq!(tracker.handle(r#"{"type":"out","out":"step output\n"}"#).text, vec!["step output".to_string()]);
+
+        let spans = &tracker.spans;
+        assert_eq!((spans[0].scenario.as_str(), spans[0].duration_secs, spans[0].passed), ("Run a pipeline", Some(61.0), Some(true)));
+        assert_eq!((spans[1].spec.as_str(), spans[1].duration_secs, spans[1].passed), ("Pipeline runs", Some(4.2), Some(false)));
+
+        // The rendered text parses like gauge's console report
+        let mut tracker = Tracker::default();
+        let rendered: Vec<String> = [
+            r#"{"type":"specStart","id":"a.spec","name":"Spec A"}"#,
+            r#"{"type":"scenarioStart","id":"a.spec:3","parentId":"a.spec","name":"Scenario 1"}"#,
+            r#"{"type":"scenarioEnd","id":"a.spec:3","result":{"status":"fail","time":1500,"errors":[{"message":"boom"}]}}"#,
+            r#"{"type":"scenarioStart","id":"a.spec:9","parentId":"a.spec","name":"Scenario 2"}"#,
+            r#"{"type":"scenarioEnd","id":"a.spec:9","result":{"status":"pass","time":2500}}"#,
+        ]
+        .iter()
+        .flat_map(|l| tracker.handle(l).text)
+        .collect();
+        let mut parsed = crate::results::parse_gauge_stdout_str(&rendered.join("\n")).unwrap();
+        assert_eq!((parsed.tests.len(), parsed.failed), (2, 1));
+        assert_eq!(parsed.tests[0].error_message.as_deref(), Some("boom"));
+
+        apply_durations(&mut parsed, &tracker.spans);
+        assert_eq!((parsed.tests[0].duration_secs, parsed.tests[1].duration_secs), (1.5, 2.5));
+        assert_eq!(parsed.duration_secs, 4.0);
+    }
+}
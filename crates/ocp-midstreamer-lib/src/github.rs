@@ -0,0 +1,662 @@
+//! GitHub API client, over the gh CLI (or curl when gh is not installed).
+//!
+//! This module provides functionality to resolve a date to the commit SHA that was
+//! HEAD at end-of-day UTC for that date. This is the foundation for historical builds.
+//! Resolutions of past dates never change, so they are cached on disk per repo and
+//! date; `--offline` answers from that cache alone. It also exposes the generic
+//! request helper used by API-based publishing, patches, ref checks and
+//! self-update: token rotation, rate limit backoff and ETag revalidation live there.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{LazyLock, Mutex, OnceLock};
+use std::time::Instant;
+
+use crate::warnings;
+
+/// File under the cache directory holding as-of resolutions, keyed `owner/repo@date`.
+const AS_OF_CACHE_FILE: &str = "as-of-commits.json";
+
+static OFFLINE: OnceLock<bool> = OnceLock::new();
+
+/// Set offline mode (`--offline`): as-of dates resolve from the cache only. Call once at startup.
+pub fn init(offline: bool) {
+    let _ = OFFLINE.set(offline);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.get().copied().unwrap_or(false)
+}
+
+/// Information about a commit returned from the GitHub API.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CommitInfo {
+    pub sha: String,
+    pub date: String,
+    pub message: String,
+}
+
+/// Parse owner and repo from a GitHub URL.
+///
+/// Handles `https://github.com/owner/repo.git`, `https://github.com/owner/repo`,
+/// and SSH remotes like `git@github.com:owner/repo.git`
+///
+/// # Examples
+/// ```ignore
+/// let (owner, repo) = parse_github_url("https://github.com/tektoncd/pipeline")?;
+/// assert_eq!(owner, "tektoncd");
+/// assert_eq!(repo, "pipeline");
+/// ```
+pub fn parse_github_url(url: &str) -> Result<(String, String)> {
+    let url = url.trim_end_matches(".git");
+    let url = url.replacen("git@github.com:", "github.com/", 1);
+    let parts: Vec<&str> = url.rsplitn(3, '/').collect();
+    if parts.len() >= 2 {
+        Ok((parts[1].to_string(), parts[0].to_string()))
+    } else {
+        anyhow::bail!(
+            "Invalid GitHub URL: {}. Expected format: https://github.com/owner/repo",
+            url
+        )
+    }
+}
+
+/// Resolve the latest commit before a given date using the GitHub API (see `api_request`).
+///
+/// This function queries the GitHub commits API for commits up to end-of-day UTC
+/// on the given date, returning the most recent commit. Past dates are answered
+/// from the on-disk cache when possible; in offline mode only the cache is used.
+///
+/// # Arguments
+/// * `repo_url` - GitHub repository URL (e.g., `https://github.com/tektoncd/pipeline`)
+/// * `date` - Date in YYYY-MM-DD format
+///
+/// # Returns
+/// `CommitInfo` with sha, date, and first line of commit message
+///
+/// # Errors
+/// - Returns error if the URL is not a valid GitHub URL
+/// - Returns error if neither gh nor curl is installed
+/// - Returns error if the rate limit stays exceeded for every token (suggests GITHUB_TOKEN or `gh auth login`)
+/// - Returns error in offline mode when the date is not cached
+/// - Returns error if repository is not found
+/// - Returns error if no commits exist before the given date
+///
+/// # Example
+/// ```ignore
+/// let commit = resolve_commit_before_date(
+///     "https://github.com/tektoncd/pipeline",
+///     "2024-01-15"
+/// )?;
+/// println!("Commit {} from {}: {}", commit.sha, commit.date, commit.message);
+/// ```
+pub fn resolve_commit_before_date(repo_url: &str, date: &str) -> Result<CommitInfo> {
+    let (owner, repo) = parse_github_url(repo_url)?;
+
+    // End-of-day UTC for consistent behavior regardless of local timezone
+    let until_time = crate::timestamp::end_of_day_utc(date)?;
+    let key = format!("{}/{}@{}", owner, repo, date);
+
+    if let Some(info) = load_as_of_cache().remove(&key) {
+        return Ok(info);
+    }
+    if is_offline() {
+        anyhow::bail!(
+            "Offline: no cached commit for {}/{} on {}. Run once without --offline to cache it.",
+            owner,
+            repo,
+            date
+        );
+    }
+
+    let endpoint = format!(
+        "repos/{}/{}/commits?per_page=1&until={}",
+        owner,
+        repo,
+        crate::timestamp::to_rfc3339(until_time)
+    );
+    let response = api_request("GET", &endpoint, None, &[])?;
+    match response.status {
+        200 => {}
+        404 => anyhow::bail!("Repository not found: {}/{}. Check the URL is correct.", owner, repo),
+        other => anyhow::bail!("GitHub API returned HTTP {}: {}", other, crate::exec::redact(response.body.trim())),
+    }
+
+    let commits: serde_json::Value = serde_json::from_str(&response.body)
+        .with_context(|| format!("Failed to parse commit info from GitHub API response: {}", response.body.trim()))?;
+    let Some(info) = commit_from_api(&commits) else {
+        anyhow::bail!(
+            "No commits found before {} in {}/{}. The repository may not have existed yet on that date.",
+            date,
+            owner,
+            repo
+        );
+    };
+
+    store_commit_before_date(repo_url, date, &info);
+    Ok(info)
+}
+
+/// The cached resolution of `date` for `repo_url`, without calling the API.
+pub fn cached_commit_before_date(repo_url: &str, date: &str) -> Option<CommitInfo> {
+    let (owner, repo) = parse_github_url(repo_url).ok()?;
+    load_as_of_cache().remove(&format!("{}/{}@{}", owner, repo, date))
+}
+
+/// Cache a resolution of `date` found by other means (e.g. a local clone).
+/// Today's HEAD can still move, so only completed days are stored.
+pub fn store_commit_before_date(repo_url: &str, date: &str, info: &CommitInfo) {
+    let (Ok((owner, repo)), Ok(until)) = (parse_github_url(repo_url), crate::timestamp::end_of_day_utc(date)) else {
+        return;
+    };
+    if until >= chrono::Utc::now() {
+        return;
+    }
+    let mut cache = load_as_of_cache();
+    cache.insert(format!("{}/{}@{}", owner, repo, date), info.clone());
+    save_as_of_cache(&cache);
+}
+
+/// First commit of a `commits` API response, with the message cut to its first line.
+fn commit_from_api(commits: &serde_json::Value) -> Option<CommitInfo> {
+    let commit = commits.get(0)?;
+    Some(CommitInfo {
+        sha: commit["sha"].as_str()?.to_string(),
+        date: commit["commit"]["author"]["date"].as_str().unwrap_or("").to_string(),
+        message: commit["commit"]["message"].as_str().unwrap_or("").lines().next().unwrap_or("").to_string(),
+    })
+}
+
+fn as_of_cache_path() -> Option<PathBuf> {
+    crate::platform::cache_dir().map(|d| d.join(AS_OF_CACHE_FILE))
+}
+
+fn load_as_of_cache() -> BTreeMap<String, CommitInfo> {
+    as_of_cache_path()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Best effort: a cache that cannot be written only costs another API call next time.
+fn save_as_of_cache(cache: &BTreeMap<String, CommitInfo>) {
+    let Some(path) = as_of_cache_path() else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(cache) && let Err(e) = std::fs::write(&path, json) {
+        warnings::warn(format!("Could not write as-of cache {}: {e}", path.display()));
+    }
+}
+
+/// Response from a GitHub API call.
+#[derive(Debug)]
+pub struct ApiResponse {
+    /// HTTP status code (a 304 answered from the ETag cache is reported as 200)
+    pub status: u16,
+    pub body: String,
+}
+
+impl ApiResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Parse the response body as JSON.
+    pub fn json(&self) -> Result<serde_json::Value> {
+        serde_json::from_str(&self.body)
+            .with_context(|| format!("Failed to parse GitHub API response: {}", self.body.trim()))
+    }
+}
+
+/// Environment variable with extra tokens (comma- or whitespace-separated) to
+/// rotate through when one runs out of rate limit.
+pub const TOKEN_POOL_ENV: &str = "GITHUB_TOKENS";
+
+/// Attempts per request when throttled before giving up.
+const RATE_LIMIT_ATTEMPTS: u32 = 5;
+
+/// Longest wait for a primary rate limit to reset when every token is exhausted.
+const MAX_RESET_WAIT_SECS: i64 = 300;
+
+/// Longest single backoff for a secondary (abuse) rate limit.
+const MAX_SECONDARY_WAIT_SECS: u64 = 120;
+
+/// Backoff for a secondary rate limit that names no Retry-After.
+const DEFAULT_SECONDARY_WAIT_SECS: u64 = 60;
+
+/// Directory under the cache directory holding ETag-validated GET responses.
+const ETAG_CACHE_DIR: &str = "github-api";
+
+/// Responses larger than this are not kept for conditional requests.
+const ETAG_CACHE_MAX_BYTES: usize = 1024 * 1024;
+
+/// Rate limit state of one token (`None`: the gh login, or anonymous with curl).
+#[derive(Debug, Clone, PartialEq)]
+struct PooledToken {
+    token: Option<String>,
+    remaining: Option<u64>,
+    /// Unix time the primary limit resets at
+    reset: Option<i64>,
+}
+
+/// Tokens requests rotate through, each with its last reported rate limit.
+#[derive(Debug)]
+struct TokenPool {
+    tokens: Vec<PooledToken>,
+}
+
+impl TokenPool {
+    /// Tokens from GITHUB_TOKENS, GITHUB_TOKEN and GH_TOKEN (deduplicated);
+    /// without any, the gh login or anonymous access.
+    fn from_env() -> Self {
+        let mut values: Vec<String> = std::env::var(TOKEN_POOL_ENV)
+            .unwrap_or_default()
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .map(str::to_string)
+            .collect();
+        values.extend(["GITHUB_TOKEN", "GH_TOKEN"].iter().filter_map(|v| std::env::var(v).ok()));
+        Self::new(values)
+    }
+
+    fn new(values: Vec<String>) -> Self {
+        let mut tokens: Vec<PooledToken> = Vec::new();
+        for value in values.into_iter().map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+            if tokens.iter().any(|t| t.token.as_deref() == Some(value.as_str())) {
+                continue;
+            }
+            crate::exec::register_secret(&value);
+            tokens.push(PooledToken { token: Some(value), remaining: None, reset: None });
+        }
+        if tokens.is_empty() {
+            tokens.push(PooledToken { token: None, remaining: None, reset: None });
+        }
+        TokenPool { tokens }
+    }
+
+    /// Index of the token with the most requests left at `now` (unknown counts as
+    /// plenty), or Err with the earliest reset when all are exhausted.
+    fn pick(&self, now: i64) -> std::result::Result<usize, i64> {
+        let available = |t: &PooledToken| t.remaining != Some(0) || t.reset.is_some_and(|r| r <= now);
+        self.tokens
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| available(t))
+            .max_by_key(|(i, t)| {
+                let left = if t.reset.is_some_and(|r| r <= now) { u64::MAX } else { t.remaining.unwrap_or(u64::MAX) };
+                // Earlier tokens win ties, so the first one is used until it runs low
+                (left, std::cmp::Reverse(*i))
+            })
+            .map(|(i, _)| i)
+            .ok_or_else(|| self.tokens.iter().filter_map(|t| t.reset).min().unwrap_or(now + DEFAULT_SECONDARY_WAIT_SECS as i64))
+    }
+
+    /// Update token `index` from the rate limit headers of a response.
+    fn record(&mut self, index: usize, headers: &HashMap<String, String>) {
+        let token = &mut self.tokens[index];
+        if let Some(remaining) = headers.get("x-ratelimit-remaining").and_then(|v| v.parse().ok()) {
+            token.remaining = Some(remaining);
+        }
+        if let Some(reset) = headers.get("x-ratelimit-reset").and_then(|v| v.parse().ok()) {
+            token.reset = Some(reset);
+        }
+    }
+
+    /// Mark token `index` as out of requests until `reset`.
+    fn exhaust(&mut self, index: usize, reset: Option<i64>, now: i64) {
+        let token = &mut self.tokens[index];
+        token.remaining = Some(0);
+        token.reset = Some(reset.or(token.reset).filter(|r| *r > now).unwrap_or(now + DEFAULT_SECONDARY_WAIT_SECS as i64));
+    }
+}
+
+static TOKEN_POOL: LazyLock<Mutex<TokenPool>> = LazyLock::new(|| Mutex::new(TokenPool::from_env()));
+
+/// How a response was throttled.
+#[derive(Debug, PartialEq)]
+enum Throttle {
+    None,
+    /// The token's hourly quota is used up until `reset` (unix time)
+    Primary { reset: Option<i64> },
+    /// Too many requests too fast; retry after this many seconds
+    Secondary { retry_after: u64 },
+}
+
+fn classify(status: u16, headers: &HashMap<String, String>, body: &str) -> Throttle {
+    if status != 403 && status != 429 {
+        return Throttle::None;
+    }
+    let reset = headers.get("x-ratelimit-reset").and_then(|v| v.parse().ok());
+    let retry_after = headers.get("retry-after").and_then(|v| v.parse().ok());
+    let body = body.to_lowercase();
+    if headers.get("x-ratelimit-remaining").map(String::as_str) == Some("0") {
+        Throttle::Primary { reset }
+    } else if let Some(retry_after) = retry_after {
+        Throttle::Secondary { retry_after }
+    } else if body.contains("secondary rate limit") || body.contains("abuse") {
+        Throttle::Secondary { retry_after: DEFAULT_SECONDARY_WAIT_SECS }
+    } else if body.contains("rate limit") {
+        Throttle::Primary { reset }
+    } else {
+        Throttle::None
+    }
+}
+
+/// A GET response kept to revalidate with If-None-Match.
+#[derive(Debug, Deserialize, Serialize)]
+struct CachedResponse {
+    etag: String,
+    body: String,
+}
+
+fn etag_cache_path(key: &str) -> Option<PathBuf> {
+    let digest = Sha256::digest(key.as_bytes());
+    let name: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    crate::platform::cache_dir().map(|d| d.join(ETAG_CACHE_DIR).join(format!("{}.json", name)))
+}
+
+fn load_etag(key: &str) -> Option<CachedResponse> {
+    let content = std::fs::read_to_string(etag_cache_path(key)?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Best effort, like the as-of cache.
+fn store_etag(key: &str, cached: &CachedResponse) {
+    if cached.body.len() > ETAG_CACHE_MAX_BYTES {
+        return;
+    }
+    let Some(path) = etag_cache_path(key) else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_string(cached) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+/// Status, lowercased headers and body of a response printed with headers
+/// (`gh api --include`, `curl -i`). Interim and redirect header blocks are skipped.
+fn parse_included(output: &str) -> Option<(u16, HashMap<String, String>, String)> {
+    let mut rest = output;
+    let mut last = None;
+    while rest.starts_with("HTTP/") {
+        let (head, body) = rest
+            .split_once("\r\n\r\n")
+            .or_else(|| rest.split_once("\n\n"))
+            .unwrap_or((rest, ""));
+        let mut lines = head.lines();
+        let status: u16 = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+        let headers = lines
+            .filter_map(|l| l.split_once(':'))
+            .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_string()))
+            .collect();
+        last = Some((status, headers));
+        rest = body;
+    }
+    last.map(|(status, headers)| (status, headers, rest.to_string()))
+}
+
+static HTTP_STATUS_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\(HTTP (\d{3})\)").expect("Invalid regex"));
+
+/// One request with `token` through gh (when installed) or curl.
+fn send(
+    method: &str,
+    endpoint: &str,
+    body: Option<&serde_json::Value>,
+    headers: &[&str],
+    token: Option<&str>,
+) -> Result<(u16, HashMap<String, String>, String)> {
+    let use_gh = which::which("gh").is_ok();
+    let mut cmd = if use_gh {
+        let mut cmd = Command::new("gh");
+        cmd.args(["api", "--include", "-X", method, endpoint]).args(headers);
+        if let Some(t) = token {
+            cmd.env("GH_TOKEN", t);
+        }
+        if body.is_some() {
+            cmd.args(["--input", "-"]);
+        }
+        cmd
+    } else {
+        let mut cmd = Command::new("curl");
+        cmd.args(["-sS", "-i", "-X", method, "-H", "Accept: application/vnd.github+json"]).args(headers);
+        if let Some(t) = token {
+            let authorization = format!("Authorization: Bearer {}", t);
+            cmd.args(["-H", authorization.as_str()]);
+        }
+        if body.is_some() {
+            cmd.args(["-H", "Content-Type: application/json", "--data-binary", "@-"]);
+        }
+        cmd.arg(format!("https://api.github.com/{}", endpoint.trim_start_matches('/')));
+        cmd
+    };
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    if body.is_some() {
+        cmd.stdin(Stdio::piped());
+    }
+
+    let output = if crate::cassette::replaying() {
+        let recorded = crate::cassette::replay_process(&cmd)?;
+        std::process::Output {
+            status: crate::cassette::exit_status(recorded.exit_code),
+            stdout: recorded.stdout.into_bytes(),
+            stderr: recorded.stderr.into_bytes(),
+        }
+    } else {
+        let start = Instant::now();
+        let mut child = cmd
+            .spawn()
+            .context("Failed to query the GitHub API (install gh or curl)")?;
+        if let Some(b) = body {
+            let mut stdin = child.stdin.take().expect("stdin was piped");
+            stdin
+                .write_all(serde_json::to_string(b)?.as_bytes())
+                .context("Failed to write GitHub API request body")?;
+        }
+        let output = child.wait_with_output().context("Failed to wait for the GitHub API request")?;
+        crate::audit::record_process(&cmd, output.status.code(), start.elapsed());
+        crate::cassette::record_process(&cmd, output.status.code(), &output.stdout, &output.stderr);
+        output
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if let Some(parsed) = parse_included(&stdout) {
+        return Ok(parsed);
+    }
+    // No response printed: gh only reports the status on stderr
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let status = if output.status.success() && use_gh {
+        200
+    } else {
+        HTTP_STATUS_RE
+            .captures(&stderr)
+            .and_then(|c| c[1].parse().ok())
+            .unwrap_or(0)
+    };
+    if status == 0 {
+        anyhow::bail!("GitHub API {} {} failed: {}", method, endpoint, crate::exec::redact(stderr.trim()));
+    }
+    Ok((status, HashMap::new(), stdout.into_owned()))
+}
+
+/// Call the GitHub REST API via gh (authenticated by the token pool or the gh
+/// login) or curl. Non-2xx responses are returned rather than treated as errors
+/// so callers can react to conflicts and missing resources.
+///
+/// Requests rotate through the tokens in GITHUB_TOKENS/GITHUB_TOKEN/GH_TOKEN by
+/// remaining quota, back off on secondary rate limits, and wait (briefly) for a
+/// reset when every token is exhausted. GETs are revalidated with the ETag of a
+/// cached response, which does not count against the rate limit.
+pub fn api_request(
+    method: &str,
+    endpoint: &str,
+    body: Option<&serde_json::Value>,
+    extra_args: &[&str],
+) -> Result<ApiResponse> {
+    let cache_key = (method == "GET" && body.is_none()).then(|| format!("{} {}", endpoint, extra_args.join(" ")));
+    let cached = cache_key.as_deref().and_then(load_etag);
+    let if_none_match = cached.as_ref().map(|c| format!("If-None-Match: {}", c.etag));
+    let mut headers: Vec<&str> = extra_args.to_vec();
+    if let Some(ref h) = if_none_match {
+        headers.extend(["-H", h.as_str()]);
+    }
+
+    for attempt in 1..=RATE_LIMIT_ATTEMPTS {
+        let now = chrono::Utc::now().timestamp();
+        let picked = TOKEN_POOL.lock().unwrap_or_else(|e| e.into_inner()).pick(now);
+        let index = match picked {
+            Ok(i) => i,
+            Err(reset) if reset - now <= MAX_RESET_WAIT_SECS => {
+                let wait = (reset - now).max(1) as u64;
+                warnings::warn(format!("GitHub API rate limit exhausted for every token; waiting {}s for the reset", wait));
+                std::thread::sleep(std::time::Duration::from_secs(wait));
+                continue;
+            }
+            Err(reset) => anyhow::bail!(
+                "GitHub API rate limit exceeded until {}. Set GITHUB_TOKEN (or several in {}) or run `gh auth login` for higher limits, or use --offline with cached dates.",
+                chrono::DateTime::from_timestamp(reset, 0).map(crate::timestamp::to_rfc3339).unwrap_or_default(),
+                TOKEN_POOL_ENV
+            ),
+        };
+        let token = TOKEN_POOL.lock().unwrap_or_else(|e| e.into_inner()).tokens[index].token.clone();
+
+        let (status, response_headers, response_body) = send(method, endpoint, body, &headers, token.as_deref())?;
+        TOKEN_POOL.lock().unwrap_or_else(|e| e.into_inner()).record(index, &response_headers);
+
+        match classify(status, &response_headers, &response_body) {
+            Throttle::Primary { reset } => {
+                TOKEN_POOL.lock().unwrap_or_else(|e| e.into_inner()).exhaust(index, reset, now);
+                continue;
+            }
+            Throttle::Secondary { retry_after } if attempt < RATE_LIMIT_ATTEMPTS => {
+                let wait = retry_after.clamp(1, MAX_SECONDARY_WAIT_SECS);
+                warnings::warn(format!("GitHub API secondary rate limit on {}; retrying in {}s", endpoint, wait));
+                std::thread::sleep(std::time::Duration::from_secs(wait));
+                continue;
+            }
+            Throttle::Secondary { .. } => break,
+            Throttle::None => {}
+        }
+
+        if status == 304 && let Some(cached) = cached {
+            return Ok(ApiResponse { status: 200, body: cached.body });
+        }
+        if let (Some(key), Some(etag)) = (cache_key.as_deref(), response_headers.get("etag"))
+            && status == 200
+        {
+            store_etag(key, &CachedResponse { etag: etag.clone(), body: response_body.clone() });
+        }
+        return Ok(ApiResponse { status, body: response_body });
+    }
+    anyhow::bail!(
+        "GitHub API rate limit exceeded for {}. Set GITHUB_TOKEN (or several in {}) or run `gh auth login` for higher limits, or use --offline with cached dates.",
+        endpoint,
+        TOKEN_POOL_ENV
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_from_api() {
+        let commits = serde_json::json!([{
+            "sha": "abc123",
+            "commit": {"author": {"date": "2025-01-15T10:00:00Z"}, "message": "Fix thing\n\nLonger body"}
+        }]);
+        let info = commit_from_api(&commits).unwrap();
+        assert_eq!(info.sha, "abc123");
+        assert_eq!(info.date, "2025-01-15T10:00:00Z");
+        assert_eq!(info.message, "Fix thing");
+        assert!(commit_from_api(&serde_json::json!([])).is_none());
+    }
+
+    #[test]
+    fn test_parse_included() {
+        let out = "HTTP/1.1 302 Found\r\nLocation: x\r\n\r\nHTTP/2.0 200 OK\r\nETag: \"abc\"\r\nX-RateLimit-Remaining: 41\r\n\r\n[{\"sha\":\"1\"}]";
+        let (status, headers, body) = parse_included(out).unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(headers["etag"], "\"abc\"");
+        assert_eq!(headers["x-ratelimit-remaining"], "41");
+        assert_eq!(body, "[{\"sha\":\"1\"}]");
+        assert!(parse_included("{}").is_none());
+    }
+
+    #[test]
+    fn test_classify_throttle() {
+        let headers = |pairs: &[(&str, &str)]| pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>();
+        assert_eq!(
+            classify(403, &headers(&[("x-ratelimit-remaining", "0"), ("x-ratelimit-reset", "1700000000")]), ""),
+            Throttle::Primary { reset: Some(1700000000) }
+        );
+        assert_eq!(classify(429, &headers(&[("retry-after", "30")]), ""), Throttle::Secondary { retry_after: 30 });
+        assert_eq!(
+            classify(403, &headers(&[]), "You have exceeded a secondary rate limit"),
+            Throttle::Secondary { retry_after: DEFAULT_SECONDARY_WAIT_SECS }
+        );
+        assert_eq!(classify(403, &headers(&[]), "Resource not accessible by integration"), Throttle::None);
+        assert_eq!(classify(404, &headers(&[("x-ratelimit-remaining", "0")]), ""), Throttle::None);
+    }
+
+    #[test]
+    fn test_token_pool_rotation() {
+        let mut pool = TokenPool::new(vec!["a".into(), " b ".into(), "a".into(), "".into()]);
+        assert_eq!(pool.tokens.len(), 2);
+        let now = 1_000;
+        assert_eq!(pool.pick(now), Ok(0));
+
+        pool.record(0, &HashMap::from([("x-ratelimit-remaining".to_string(), "10".to_string())]));
+        assert_eq!(pool.pick(now), Ok(1), "unknown quota beats a low one");
+        pool.record(1, &HashMap::from([("x-ratelimit-remaining".to_string(), "500".to_string())]));
+        assert_eq!(pool.pick(now), Ok(1));
+
+        pool.exhaust(1, Some(now + 60), now);
+        assert_eq!(pool.pick(now), Ok(0));
+        pool.exhaust(0, Some(now + 30), now);
+        assert_eq!(pool.pick(now), Err(now + 30));
+        assert_eq!(pool.pick(now + 30), Ok(0), "reset tokens are usable again");
+
+        assert_eq!(TokenPool::new(Vec::new()).tokens[0].token, None);
+    }
+
+    #[test]
+    fn test_parse_github_url_standard() {
+        let (owner, repo) = parse_github_url("https://github.com/tektoncd/pipeline").unwrap();
+        assert_eq!(owner, "tektoncd");
+        assert_eq!(repo, "pipeline");
+    }
+
+    #[test]
+    fn test_parse_github_url_with_git_suffix() {
+        let (owner, repo) =
+            parse_github_url("https://github.com/tektoncd/pipeline.git").unwrap();
+        assert_eq!(owner, "tektoncd");
+        assert_eq!(repo, "pipeline");
+    }
+
+    #[test]
+    fn test_parse_github_url_ssh() {
+        let (owner, repo) =
+            parse_github_url("git@github.com:openshift-pipelines/ocp-midstreamer.git").unwrap();
+        assert_eq!(owner, "openshift-pipelines");
+        assert_eq!(repo, "ocp-midstreamer");
+    }
+
+    #[test]
+    fn test_parse_github_url_invalid() {
+        let result = parse_github_url("not-a-url");
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,346 @@
+//! Shared git fetch and clone layer.
+//!
+//! Component builds, release-tests, the performance repo, the operator repo and
+//! the as-of resolution of batch and dry runs all clone from GitHub. Every clone and fetch goes through here so they
+//! behave the same way: a ref is fetched shallow when the server allows it,
+//! `pr/N` refs resolve to the pull request head, network errors are retried
+//! with exponential backoff, and repositories can be fetched from a mirror.
+//!
+//! Mirrors map a URL prefix to another prefix, e.g.
+//! `https://github.com/=https://git-mirror.example.com/github/`, from
+//! `--git-mirror` or `[mirrors]` in `config/git.toml`. The mirror is tried
+//! first; when it fails (down, or lagging behind and missing the ref) the
+//! original URL is used.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::dryrun::is_commit_sha;
+use crate::{config, exec, progress, warnings};
+
+/// Retries after the first attempt when `retries` is not set in config/git.toml.
+const DEFAULT_RETRIES: u32 = 3;
+
+/// Delay before the first retry; doubled for each one after.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Budget for one clone or fetch; full clones of large repositories are slow.
+const CLONE_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// git errors that a retry can fix: DNS, connection, TLS and server hiccups.
+const TRANSIENT_ERRORS: &[&str] = &[
+    "could not resolve host",
+    "failed to connect",
+    "connection timed out",
+    "connection reset",
+    "connection refused",
+    "operation timed out",
+    "early eof",
+    "unexpected disconnect",
+    "rpc failed",
+    "remote end hung up",
+    "gnutls recv error",
+    "ssl_error_syscall",
+    "the requested url returned error: 5",
+    "http 5",
+    "too many requests",
+    "returned error: 429",
+];
+
+struct Settings {
+    /// (prefix, mirror prefix), longest prefix first
+    mirrors: Vec<(String, String)>,
+    retries: u32,
+}
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+/// Set the mirrors (`--git-mirror PREFIX=MIRROR`, over `[mirrors]` in
+/// config/git.toml) and the retry count. Call once at startup.
+pub fn init(flags: &[String]) -> Result<()> {
+    let cfg = config::load_git_config(&config::default_git_config_path())?;
+    let mut mirrors: Vec<(String, String)> = cfg.mirrors.into_iter().collect();
+    for flag in flags {
+        let (prefix, mirror) = parse_mirror(flag)?;
+        mirrors.retain(|(p, _)| *p != prefix);
+        mirrors.push((prefix, mirror));
+    }
+    mirrors.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+    let _ = SETTINGS.set(Settings { mirrors, retries: cfg.retries.unwrap_or(DEFAULT_RETRIES) });
+    Ok(())
+}
+
+fn settings() -> &'static Settings {
+    SETTINGS.get_or_init(|| Settings { mirrors: Vec::new(), retries: DEFAULT_RETRIES })
+}
+
+/// Arguments that reproduce the mirrors in a child process.
+pub fn to_args() -> Vec<String> {
+    settings()
+        .mirrors
+        .iter()
+        .flat_map(|(prefix, mirror)| ["--git-mirror".to_string(), format!("{}={}", prefix, mirror)])
+        .collect()
+}
+
+/// Parse a `PREFIX=MIRROR` mirror mapping.
+pub fn parse_mirror(s: &str) -> Result<(String, String)> {
+    match s.split_once('=') {
+        Some((prefix, mirror)) if !prefix.is_empty() && !mirror.is_empty() => {
+            Ok((prefix.to_string(), mirror.to_string()))
+        }
+        _ => anyhow::bail!("Invalid git mirror '{}': expected PREFIX=MIRROR", s),
+    }
+}
+
+/// Resolve a user-provided git ref to a fetchable refspec.
+///
+/// Maps `pr/NNN` to `refs/pull/NNN/head`; passes through everything else.
+pub fn resolve_ref(user_ref: &str) -> String {
+    if let Some(pr_num) = user_ref.strip_prefix("pr/") {
+        format!("refs/pull/{}/head", pr_num)
+    } else {
+        user_ref.to_string()
+    }
+}
+
+/// `url` with the longest matching mirror prefix substituted, if any.
+fn mirrored(mirrors: &[(String, String)], url: &str) -> Option<String> {
+    mirrors
+        .iter()
+        .find(|(prefix, _)| url.starts_with(prefix.as_str()))
+        .map(|(prefix, mirror)| format!("{}{}", mirror, &url[prefix.len()..]))
+}
+
+/// URLs to fetch `url` from, in order: its mirror, then itself.
+fn sources(url: &str) -> Vec<String> {
+    let mut out: Vec<String> = mirrored(&settings().mirrors, url).into_iter().collect();
+    out.push(url.to_string());
+    out
+}
+
+/// Clone `url` into `dest` (absent or empty) at `git_ref`, or its default branch.
+///
+/// Branches and tags are cloned with `--depth 1 --branch`; `pr/N` refs, commit
+/// SHAs and refs the server has no branch for are fetched shallow into a fresh
+/// repository and checked out. Servers that refuse a shallow fetch of a commit
+/// fall back to a full clone and a checkout. Retries are shown on `stage`.
+pub fn clone(url: &str, dest: &Path, git_ref: Option<&str>, stage: Option<&progress::Stage>) -> Result<()> {
+    from_sources(url, dest, |source| {
+        tracing::info!("Cloning {} (ref: {}) into {}", source, git_ref.unwrap_or("HEAD"), dest.display());
+        clone_from(source, dest, git_ref, stage)
+    })
+}
+
+/// Run `fetch` with each source of `url` in turn, emptying `dest` between them.
+fn from_sources(url: &str, dest: &Path, mut fetch: impl FnMut(&str) -> Result<()>) -> Result<()> {
+    let mut last_err = None;
+    for (i, source) in sources(url).iter().enumerate() {
+        if i > 0 {
+            warnings::warn(format!("Fetching {} from its mirror failed, using the original URL", url));
+            reset_dir(dest)?;
+        }
+        match fetch(source.as_str()) {
+            Ok(()) => return Ok(()),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| anyhow::anyhow!("No source to clone {} from", url)))
+}
+
+fn clone_from(url: &str, dest: &Path, git_ref: Option<&str>, stage: Option<&progress::Stage>) -> Result<()> {
+    let dest_str = dest.to_str().unwrap_or_default();
+    let run = |args: &[&str]| git(args, stage);
+    let Some(r) = git_ref else {
+        return run(&["clone", "--depth", "1", url, dest_str]).map_err(GitFailure::into_error);
+    };
+
+    if !r.starts_with("pr/") && !is_commit_sha(r) {
+        match run(&["clone", "--depth", "1", "--branch", r, url, dest_str]) {
+            Ok(()) => return Ok(()),
+            Err(f) if f.transient => return Err(f.into_error()),
+            Err(_) => reset_dir(dest)?,
+        }
+    }
+
+    let resolved = resolve_ref(r);
+    let fetched = run(&["init", "--quiet", dest_str])
+        .and_then(|()| run(&["-C", dest_str, "fetch", "--depth", "1", url, &resolved]))
+        .and_then(|()| run(&["-C", dest_str, "checkout", "--quiet", "FETCH_HEAD"]));
+    match fetched {
+        Ok(()) => return Ok(()),
+        Err(f) if f.transient || r.starts_with("pr/") => {
+            return Err(f.into_error().context(format!("Failed to fetch ref '{}' (resolved: '{}')", r, resolved)));
+        }
+        Err(_) => reset_dir(dest)?,
+    }
+
+    run(&["clone", url, dest_str]).map_err(GitFailure::into_error)?;
+    run(&["-C", dest_str, "checkout", "--quiet", r])
+        .map_err(GitFailure::into_error)
+        .with_context(|| format!("Failed to checkout ref '{}'", r))
+}
+
+/// Fetch all remotes of the existing clone in `dir`.
+pub fn fetch_all(dir: &Path) -> Result<()> {
+    git(&["-C", dir.to_str().unwrap_or_default(), "fetch", "--all"], None).map_err(GitFailure::into_error)
+}
+
+/// Check out `git_ref` in the existing clone in `dir`.
+pub fn checkout(dir: &Path, git_ref: &str) -> Result<()> {
+    git(&["-C", dir.to_str().unwrap_or_default(), "checkout", git_ref], None)
+        .map_err(GitFailure::into_error)
+        .with_context(|| format!("Failed to checkout ref '{}'", git_ref))
+}
+
+/// Commit-only bare clone of `url`'s default branch into `dest` (absent or
+/// empty), reaching back to `since` (YYYY-MM-DD); for walking history by date.
+pub fn bare_clone_since(url: &str, dest: &Path, since: &str) -> Result<()> {
+    let dest_str = dest.to_str().unwrap_or_default();
+    let since_arg = format!("--shallow-since={}", since);
+    from_sources(url, dest, |source| {
+        git(&["clone", "--quiet", "--bare", "--single-branch", "--filter=tree:0", &since_arg, source, dest_str], None)
+            .map_err(GitFailure::into_error)
+    })
+}
+
+/// `git ls-remote url HEAD <patterns>` output, from the mirror when it answers.
+pub fn ls_remote(url: &str, patterns: &[String]) -> Result<String> {
+    let mut last_err = None;
+    for source in sources(url) {
+        let mut args = vec!["ls-remote", source.as_str(), "HEAD"];
+        args.extend(patterns.iter().map(String::as_str));
+        match git_output(&args, None) {
+            Ok(stdout) => return Ok(stdout),
+            Err(f) => last_err = Some(f.into_error()),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| anyhow::anyhow!("No source to list {} from", url)))
+}
+
+/// Whether `url` (or its mirror) has branch `branch`.
+pub fn branch_exists(url: &str, branch: &str) -> bool {
+    sources(url).iter().any(|source| {
+        exec::run_cmd_timeout("git", &["ls-remote", "--heads", source.as_str(), branch], exec::API_TIMEOUT)
+            .is_ok_and(|r| !r.stdout.trim().is_empty())
+    })
+}
+
+/// A git command that failed after its retries.
+struct GitFailure {
+    /// Failed with a network error (retries exhausted), not a missing ref or repository
+    transient: bool,
+    message: String,
+}
+
+impl GitFailure {
+    fn into_error(self) -> anyhow::Error {
+        anyhow::anyhow!(self.message)
+    }
+}
+
+/// Run git, retrying network errors with exponential backoff. Retries are
+/// warned about and shown on `stage`, whose message is restored afterwards.
+fn git(args: &[&str], stage: Option<&progress::Stage>) -> std::result::Result<(), GitFailure> {
+    git_output(args, stage).map(drop)
+}
+
+/// `git`, returning stdout.
+fn git_output(args: &[&str], stage: Option<&progress::Stage>) -> std::result::Result<String, GitFailure> {
+    let retries = settings().retries;
+    let message = stage.map(|s| s.message());
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 0;
+    let result = loop {
+        let failure = match exec::run_cmd_unchecked_timeout("git", args, CLONE_TIMEOUT) {
+            Ok(r) if r.timed_out => GitFailure {
+                transient: true,
+                message: format!("{} timed out after {}s", exec::display_cmd("git", args), CLONE_TIMEOUT.as_secs()),
+            },
+            Ok(r) if r.exit_code == 0 => break Ok(r.stdout),
+            Ok(r) => GitFailure {
+                transient: is_transient(&r.stderr),
+                message: format!("{} failed: {}", exec::display_cmd("git", args), exec::redact(r.stderr.trim())),
+            },
+            Err(e) => break Err(GitFailure { transient: false, message: format!("{e:#}") }),
+        };
+        if !failure.transient || attempt >= retries {
+            break Err(failure);
+        }
+        attempt += 1;
+        warnings::warn(format!("{} (retry {}/{} in {}s)", failure.message, attempt, retries, backoff.as_secs()));
+        if let (Some(stage), Some(message)) = (stage, &message) {
+            stage.set_message(format!("{} (retry {}/{})", message, attempt, retries));
+        }
+        std::thread::sleep(backoff);
+        backoff *= 2;
+    };
+    if let (Some(stage), Some(message)) = (stage, message) && attempt > 0 {
+        stage.set_message(message);
+    }
+    result
+}
+
+/// Whether git's stderr reports an error a retry can fix.
+fn is_transient(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+    TRANSIENT_ERRORS.iter().any(|e| stderr.contains(e))
+}
+
+/// Empty `dir` (or create it) for the next attempt; a failed clone can leave files behind.
+fn reset_dir(dir: &Path) -> Result<()> {
+    if dir.exists() {
+        std::fs::remove_dir_all(dir).with_context(|| format!("Failed to clear {}", dir.display()))?;
+    }
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mirror() {
+        let (prefix, mirror) = parse_mirror("https://github.com/=https://mirror.example/gh/").unwrap();
+        assert_eq!(prefix, "https://github.com/");
+        assert_eq!(mirror, "https://mirror.example/gh/");
+        assert!(parse_mirror("https://github.com/").is_err());
+        assert!(parse_mirror("=https://mirror.example/").is_err());
+    }
+
+    #[test]
+    fn test_mirrored() {
+        let mirrors = vec![
+            ("https://github.com/openshift-pipelines/".to_string(), "https://osp-mirror.example/".to_string()),
+            ("https://github.com/".to_string(), "https://mirror.example/gh/".to_string()),
+        ];
+        assert_eq!(
+            mirrored(&mirrors, "https://github.com/openshift-pipelines/release-tests.git").as_deref(),
+            Some("https://osp-mirror.example/release-tests.git")
+        );
+        assert_eq!(
+            mirrored(&mirrors, "https://github.com/tektoncd/pipeline").as_deref(),
+            Some("https://mirror.example/gh/tektoncd/pipeline")
+        );
+        assert_eq!(mirrored(&mirrors, "https://gitlab.com/x/y"), None);
+    }
+
+    #[test]
+    fn test_resolve_ref() {
+        assert_eq!(resolve_ref("pr/123"), "refs/pull/123/head");
+        assert_eq!(resolve_ref("release-v1.15"), "release-v1.15");
+        assert_eq!(resolve_ref("abc1234"), "abc1234");
+    }
+
+    #[test]
+    fn test_is_transient() {
+        assert!(is_transient("fatal: unable to access 'https://github.com/x/': Could not resolve host: github.com"));
+        assert!(is_transient("error: RPC failed; curl 56 GnuTLS recv error (-9)\nfatal: early EOF"));
+        assert!(is_transient("fatal: unable to access 'https://github.com/x/': The requested URL returned error: 502"));
+        assert!(!is_transient("fatal: Remote branch nope not found in upstream origin"));
+        assert!(!is_transient("fatal: couldn't find remote ref refs/pull/1/head"));
+        assert!(!is_transient("remote: Repository not found.\nfatal: repository 'https://github.com/x/y/' not found"));
+    }
+}
@@ -0,0 +1,328 @@
+//! Go toolchain selection for ko builds.
+//!
+//! ko compiles with whatever `go` is on PATH. Upstream components bump their Go
+//! requirement over time, so the required version is read from the component's
+//! go.mod and, when the host Go is older, a matching toolchain is selected via
+//! `GOTOOLCHAIN` (Go 1.21+ downloads it on demand). Builds can also be
+//! compile-checked against a matrix of Go versions to catch toolchain-sensitive breakage.
+//!
+//! Dependency resolution follows the checkout: vendored trees build with
+//! `-mod=vendor`; trees without vendor/ download modules (into the shared module
+//! cache) and build with `-mod=mod`; go.work workspaces use `-mod=readonly`.
+
+use anyhow::{Context, Result};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::audit;
+
+/// Oldest Go release that understands `GOTOOLCHAIN` and can download other toolchains.
+const MIN_SWITCHABLE: GoVersion = GoVersion { major: 1, minor: 21, patch: 0 };
+
+/// A Go release version (e.g. 1.22.5). A missing patch component means `.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GoVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl GoVersion {
+    /// Parse `1.22`, `1.22.5`, or `go1.22.5`. Pre-release suffixes (`rc1`) are ignored.
+    pub fn parse(s: &str) -> Option<GoVersion> {
+        let s = s.trim();
+        let s = s.strip_prefix("go").unwrap_or(s);
+        let mut parts = s.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor_raw = parts.next()?;
+        let minor_digits: String = minor_raw.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let minor = minor_digits.parse().ok()?;
+        let patch = match parts.next() {
+            Some(p) => {
+                let digits: String = p.chars().take_while(|c| c.is_ascii_digit()).collect();
+                digits.parse().ok()?
+            }
+            None => 0,
+        };
+        Some(GoVersion { major, minor, patch })
+    }
+
+    /// Toolchain name for `GOTOOLCHAIN` (e.g. `go1.22.0`).
+    pub fn toolchain_name(&self) -> String {
+        format!("go{}", self)
+    }
+}
+
+impl fmt::Display for GoVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Parse a Go version for clap's value_parser (`--go-version`, `--go-matrix`).
+pub fn parse_go_version_arg(s: &str) -> std::result::Result<GoVersion, String> {
+    let v = GoVersion::parse(s).ok_or_else(|| format!("Invalid Go version '{}'. Use e.g. 1.22 or 1.22.5", s))?;
+    if v < MIN_SWITCHABLE {
+        return Err(format!("Go {} is too old: toolchain selection requires Go 1.21 or newer", v));
+    }
+    Ok(v)
+}
+
+/// Go toolchain options for a build.
+#[derive(Debug, Clone, Default)]
+pub struct GoOptions {
+    /// Build with exactly this version instead of the one go.mod requires
+    pub version: Option<GoVersion>,
+    /// Extra versions to compile-check the import paths against before building
+    pub matrix: Vec<GoVersion>,
+}
+
+/// Version required by go.mod content: the `toolchain` directive if present, else `go`.
+pub fn required_version_from_go_mod(content: &str) -> Option<GoVersion> {
+    let mut go = None;
+    let mut toolchain = None;
+    for line in content.lines() {
+        let line = line.split("//").next().unwrap_or("").trim();
+        if let Some(v) = line.strip_prefix("toolchain ") {
+            toolchain = GoVersion::parse(v);
+        } else if let Some(v) = line.strip_prefix("go ") {
+            go = GoVersion::parse(v);
+        }
+    }
+    match (go, toolchain) {
+        (Some(g), Some(t)) => Some(g.max(t)),
+        (g, t) => t.or(g),
+    }
+}
+
+/// Version required by `source_dir/go.mod`, or None if there is no go.mod.
+pub fn required_version(source_dir: &Path) -> Result<Option<GoVersion>> {
+    let go_mod = source_dir.join("go.mod");
+    if !go_mod.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&go_mod).with_context(|| format!("Failed to read {}", go_mod.display()))?;
+    Ok(required_version_from_go_mod(&content))
+}
+
+/// Version of the `go` on PATH, ignoring any GOTOOLCHAIN switching.
+pub fn host_version() -> Option<GoVersion> {
+    let output = audit::output(Command::new("go").args(["env", "GOVERSION"]).env("GOTOOLCHAIN", "local")).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    GoVersion::parse(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Decide the `GOTOOLCHAIN` value for building `source_dir`, or None to use the host Go.
+pub fn select_toolchain(source_dir: &Path, pinned: Option<GoVersion>) -> Result<Option<String>> {
+    let host = host_version();
+    let wanted = match pinned {
+        Some(v) => v,
+        None => match required_version(source_dir)? {
+            Some(required) if host.is_none_or(|h| h < required) => required,
+            _ => return Ok(None),
+        },
+    };
+    if let Some(h) = host && h < MIN_SWITCHABLE && h != wanted {
+        anyhow::bail!(
+            "Build needs Go {} but host Go is {}, which cannot switch toolchains. Install Go {} or newer.",
+            wanted,
+            h,
+            wanted.max(MIN_SWITCHABLE)
+        );
+    }
+    eprintln!(
+        "  Using Go toolchain {} (host: {})",
+        wanted.toolchain_name(),
+        host.map(|h| h.to_string()).unwrap_or_else(|| "unknown".to_string())
+    );
+    Ok(Some(wanted.toolchain_name()))
+}
+
+/// How Go resolves dependencies for a checkout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModMode {
+    /// vendor/modules.txt is present
+    Vendor,
+    /// go.work workspace without vendoring (-mod=mod is not allowed there)
+    Workspace,
+    /// Plain module: download dependencies into the module cache
+    Mod,
+}
+
+impl ModMode {
+    pub fn goflags(self) -> &'static str {
+        match self {
+            ModMode::Vendor => "-mod=vendor",
+            ModMode::Workspace => "-mod=readonly",
+            ModMode::Mod => "-mod=mod",
+        }
+    }
+}
+
+/// GOFLAGS for building in `mode`: the GOFLAGS of the environment (e.g.
+/// `-modcacherw` in in-cluster Jobs with a module cache PVC) with its `-mod`
+/// flag, if any, replaced.
+pub fn goflags(mode: ModMode) -> String {
+    merge_goflags(std::env::var("GOFLAGS").ok().as_deref(), mode)
+}
+
+fn merge_goflags(existing: Option<&str>, mode: ModMode) -> String {
+    let mut flags: Vec<&str> = existing
+        .unwrap_or_default()
+        .split_whitespace()
+        .filter(|f| !f.starts_with("-mod=") && !f.starts_with("--mod="))
+        .collect();
+    flags.push(mode.goflags());
+    flags.join(" ")
+}
+
+/// Pick the dependency mode from what the checkout contains.
+pub fn detect_mod_mode(source_dir: &Path) -> Result<ModMode> {
+    if source_dir.join("vendor").join("modules.txt").exists() {
+        Ok(ModMode::Vendor)
+    } else if source_dir.join("go.work").exists() {
+        Ok(ModMode::Workspace)
+    } else if source_dir.join("go.mod").exists() {
+        Ok(ModMode::Mod)
+    } else {
+        anyhow::bail!(
+            "{} has no go.mod, go.work or vendor/modules.txt; cannot build it with ko",
+            source_dir.display()
+        )
+    }
+}
+
+/// Download module dependencies so a failure is reported before ko starts.
+/// Modules land in the shared module cache (GOMODCACHE) and are reused by later builds.
+fn download_modules(source_dir: &Path, mode: ModMode, toolchain: Option<&str>) -> Result<()> {
+    let mut cmd = Command::new("go");
+    cmd.args(["mod", "download"])
+        .env("GOFLAGS", goflags(mode))
+        .current_dir(source_dir);
+    if let Some(t) = toolchain {
+        cmd.env("GOTOOLCHAIN", t);
+    }
+    let output = audit::output(&mut cmd).context("Failed to execute go mod download")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} has no vendor/ directory and downloading modules failed (check network/GOPROXY access):\n{}",
+            source_dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Compile the import paths with each matrix version. Returns the versions that failed.
+pub fn run_matrix(
+    source_dir: &Path,
+    import_paths: &[String],
+    matrix: &[GoVersion],
+    mode: ModMode,
+) -> Vec<(GoVersion, String)> {
+    let mut failures = Vec::new();
+    for version in matrix {
+        eprintln!("  Go matrix: compiling with {}...", version.toolchain_name());
+        let status = audit::status(
+            Command::new("go")
+                .args(["build", "-o", null_device()])
+                .args(import_paths)
+                .env("GOTOOLCHAIN", version.toolchain_name())
+                .env("GOFLAGS", goflags(mode))
+                .current_dir(source_dir)
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit()),
+        );
+        match status {
+            Ok(s) if s.success() => eprintln!("  Go matrix: {} ok", version.toolchain_name()),
+            Ok(s) => failures.push((*version, format!("exit code {}", s.code().unwrap_or(-1)))),
+            Err(e) => failures.push((*version, e.to_string())),
+        }
+    }
+    failures
+}
+
+fn null_device() -> &'static str {
+    if cfg!(windows) { "NUL" } else { "/dev/null" }
+}
+
+/// Pick the toolchain and dependency mode for the real build, fetching modules
+/// if needed, and run the matrix (if any). Returns the env vars to build with.
+pub fn prepare(source_dir: &Path, import_paths: &[String], go: &GoOptions) -> Result<Vec<(&'static str, String)>> {
+    let toolchain = select_toolchain(source_dir, go.version)?;
+    let mode = detect_mod_mode(source_dir)?;
+    if mode != ModMode::Vendor {
+        eprintln!("  No vendor/ directory; building with {}", mode.goflags());
+        download_modules(source_dir, mode, toolchain.as_deref())?;
+    }
+
+    if !go.matrix.is_empty() {
+        let failures = run_matrix(source_dir, import_paths, &go.matrix, mode);
+        if !failures.is_empty() {
+            let summary: Vec<String> = failures.iter().map(|(v, e)| format!("{} ({})", v.toolchain_name(), e)).collect();
+            anyhow::bail!("Go matrix build failed for: {}", summary.join(", "));
+        }
+    }
+
+    let mut envs = vec![("GOFLAGS", goflags(mode))];
+    if let Some(t) = toolchain {
+        envs.push(("GOTOOLCHAIN", t));
+    }
+    Ok(envs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_go_version() {
+        assert_eq!(GoVersion::parse("1.22"), Some(GoVersion { major: 1, minor: 22, patch: 0 }));
+        assert_eq!(GoVersion::parse("go1.22.5"), Some(GoVersion { major: 1, minor: 22, patch: 5 }));
+        assert_eq!(GoVersion::parse("go1.23rc1"), Some(GoVersion { major: 1, minor: 23, patch: 0 }));
+        assert_eq!(GoVersion::parse("devel +abc"), None);
+        assert_eq!(GoVersion::parse("1.22.5").unwrap().toolchain_name(), "go1.22.5");
+    }
+
+    #[test]
+    fn test_required_version_prefers_toolchain_directive() {
+        let go_mod = "module github.com/tektoncd/pipeline\n\ngo 1.22.0 // minimum\n\ntoolchain go1.22.5\n\nrequire (\n)\n";
+        assert_eq!(required_version_from_go_mod(go_mod), GoVersion::parse("1.22.5"));
+        assert_eq!(required_version_from_go_mod("module x\n\ngo 1.21\n"), GoVersion::parse("1.21.0"));
+        assert_eq!(required_version_from_go_mod("module x\n"), None);
+    }
+
+    #[test]
+    fn test_detect_mod_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(detect_mod_mode(dir.path()).is_err());
+
+        fs::write(dir.path().join("go.mod"), "module x\n\ngo 1.22\n").unwrap();
+        assert_eq!(detect_mod_mode(dir.path()).unwrap(), ModMode::Mod);
+
+        fs::write(dir.path().join("go.work"), "go 1.22\n\nuse .\n").unwrap();
+        assert_eq!(detect_mod_mode(dir.path()).unwrap(), ModMode::Workspace);
+
+        fs::create_dir(dir.path().join("vendor")).unwrap();
+        fs::write(dir.path().join("vendor/modules.txt"), "").unwrap();
+        assert_eq!(detect_mod_mode(dir.path()).unwrap(), ModMode::Vendor);
+    }
+
+    #[test]
+    fn test_merge_goflags_keeps_existing_flags() {
+        assert_eq!(merge_goflags(None, ModMode::Vendor), "-mod=vendor");
+        assert_eq!(merge_goflags(Some("-modcacherw"), ModMode::Mod), "-modcacherw -mod=mod");
+        assert_eq!(merge_goflags(Some("-mod=vendor  -trimpath"), ModMode::Workspace), "-trimpath -mod=readonly");
+    }
+
+    #[test]
+    fn test_parse_go_version_arg_rejects_old_versions() {
+        assert!(parse_go_version_arg("1.20").is_err());
+        assert!(parse_go_version_arg("latest").is_err());
+        assert!(parse_go_version_arg("1.23").is_ok());
+    }
+}
@@ -0,0 +1,326 @@
+//! Deploy Tekton's published images instead of building from source.
+//!
+//! `--source nightly` (or `--source pipeline=nightly`) skips the ko build for a
+//! component; the deploy phase maps its IMAGE_ env vars to the digests in the
+//! nightly release manifests, from the `[<component>.nightly]` config table.
+//! With `--as-of`, the last nightly published on that date (or up to a week
+//! before) is used.
+//!
+//! `--source release` does the same with the manifests of the upstream release
+//! named by the component's ref (e.g. `pipeline:v0.62.0`), from the
+//! `[<component>.release]` table, to test a released upstream version against
+//! the installed operator.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+use crate::component::KNOWN_COMPONENTS;
+use crate::config::{ComponentConfig, ReleaseManifests};
+use crate::exec;
+use crate::warnings;
+
+/// Days before `--as-of` searched for a nightly when that date has none.
+const AS_OF_LOOKBACK_DAYS: i64 = 7;
+
+/// Where a component's images come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageSource {
+    /// Clone and build with ko (the default).
+    Source,
+    /// Tekton's published nightly release images.
+    Nightly,
+    /// The published images of the upstream release named by the component's ref.
+    Release,
+}
+
+impl ImageSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImageSource::Source => "source",
+            ImageSource::Nightly => "nightly",
+            ImageSource::Release => "release",
+        }
+    }
+}
+
+/// A `--source` value: for one component, or all when `component` is None.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceSpec {
+    pub component: Option<String>,
+    pub source: ImageSource,
+}
+
+impl SourceSpec {
+    /// The `--source` value this spec was parsed from (e.g. `pipeline=nightly`).
+    pub fn to_arg(&self) -> String {
+        match &self.component {
+            Some(c) => format!("{}={}", c, self.source.as_str()),
+            None => self.source.as_str().to_string(),
+        }
+    }
+}
+
+/// Parse `source`, `nightly`, `release`, or `component=<one of those>`. Used by clap's
+/// value_parser for --source.
+pub fn parse_source_spec(s: &str) -> std::result::Result<SourceSpec, String> {
+    let (component, source) = match s.split_once('=') {
+        Some((c, src)) => (Some(c.trim()), src.trim()),
+        None => (None, s.trim()),
+    };
+    if let Some(c) = component && !KNOWN_COMPONENTS.contains(&c) {
+        return Err(format!("Unknown component '{}'. Known: {}", c, KNOWN_COMPONENTS.join(", ")));
+    }
+    let source = match source {
+        "source" => ImageSource::Source,
+        "nightly" => ImageSource::Nightly,
+        "release" => ImageSource::Release,
+        other => return Err(format!("Unknown source '{}'. Use source, nightly or release", other)),
+    };
+    Ok(SourceSpec {
+        component: component.map(str::to_string),
+        source,
+    })
+}
+
+/// `--source` arguments passing `sources` on to a child `run` (in-cluster Job
+/// or batch date run); empty when none were given.
+pub fn to_args(sources: &[SourceSpec]) -> Vec<String> {
+    if sources.is_empty() {
+        return Vec::new();
+    }
+    let value = sources.iter().map(SourceSpec::to_arg).collect::<Vec<_>>().join(",");
+    vec!["--source".to_string(), value]
+}
+
+/// Image source for `component`: a component-specific spec wins over a global one.
+pub fn source_for(sources: &[SourceSpec], component: &str) -> ImageSource {
+    sources
+        .iter()
+        .rev()
+        .find(|s| s.component.as_deref() == Some(component))
+        .or_else(|| sources.iter().rev().find(|s| s.component.is_none()))
+        .map(|s| s.source)
+        .unwrap_or(ImageSource::Source)
+}
+
+/// A resolved published release for one component.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublishedRelease {
+    /// Release version (e.g. "v0.62.0" or nightly "v20250301-1a2b3c4d5e").
+    pub version: String,
+    /// Short image name (e.g. "controller") to digest-pinned reference.
+    pub images: BTreeMap<String, String>,
+}
+
+/// ko-published image refs in a release manifest, named either by import path
+/// (`<repo>/cmd/<name>:<tag>@sha256:<digest>`) or ko's default
+/// `<repo>/<name>-<md5 of import path>:<tag>@sha256:<digest>`.
+static PUBLISHED_IMAGE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"[\w.\-]+(?:/[\w.\-]+)*/([\w.\-]+?)(?:-[0-9a-f]{32})?(?::([\w.\-]+))?@sha256:[0-9a-f]{64}").unwrap()
+});
+
+/// Resolve the published release to deploy for `component` from `source`:
+/// the nightly (of `as_of` when given), or the release named by `git_ref`.
+pub fn resolve_published(
+    component: &str,
+    comp: &ComponentConfig,
+    source: ImageSource,
+    git_ref: Option<&str>,
+    as_of: Option<&str>,
+) -> Result<PublishedRelease> {
+    match source {
+        ImageSource::Nightly => {
+            let nightly = comp
+                .nightly
+                .as_ref()
+                .with_context(|| format!("Component '{}' has no nightly release configured", component))?;
+            resolve_nightly(nightly, as_of)
+        }
+        ImageSource::Release => {
+            let release = comp
+                .release
+                .as_ref()
+                .with_context(|| format!("Component '{}' has no upstream releases configured", component))?;
+            let version = git_ref
+                .with_context(|| format!("--source release needs a version for {0} (e.g. {0}:v1.2.3)", component))?;
+            let dir = release.url.trim_end_matches('/').replace("{version}", version);
+            let mut published = fetch_release(&dir, &release.files)?;
+            published.version = version.to_string();
+            Ok(published)
+        }
+        ImageSource::Source => anyhow::bail!("Component '{}' is built from source", component),
+    }
+}
+
+/// Resolve the nightly release to deploy: the latest, or the last one published
+/// on `as_of` (falling back to the latest, with a warning, when none is found).
+fn resolve_nightly(nightly: &ReleaseManifests, as_of: Option<&str>) -> Result<PublishedRelease> {
+    let base = nightly.url.trim_end_matches('/');
+    let dir = match as_of {
+        Some(date) => match find_version_for_date(base, date)? {
+            Some(version) => format!("{}/previous/{}", base, version),
+            None => {
+                warnings::warn(format!(
+                    "No nightly found for {} within {} days before {}; using the latest",
+                    base, AS_OF_LOOKBACK_DAYS, date
+                ));
+                format!("{}/latest", base)
+            }
+        },
+        None => format!("{}/latest", base),
+    };
+    fetch_release(&dir, &nightly.files)
+}
+
+/// Fetch `files` from the release directory `dir` and collect their images.
+fn fetch_release(dir: &str, files: &[String]) -> Result<PublishedRelease> {
+    let mut manifests = String::new();
+    for file in files {
+        let url = format!("{}/{}", dir, file);
+        let result = exec::run_cmd_timeout("curl", &["-fsSL", &url], exec::API_TIMEOUT)
+            .with_context(|| format!("Failed to fetch release manifest {}", url))?;
+        manifests.push_str(&result.stdout);
+        manifests.push('\n');
+    }
+    let release = parse_release(&manifests);
+    if release.images.is_empty() {
+        anyhow::bail!("No published images found in {}", dir);
+    }
+    Ok(release)
+}
+
+/// Collect the image refs from release manifests.
+fn parse_release(manifests: &str) -> PublishedRelease {
+    let mut images = BTreeMap::new();
+    let mut version = String::new();
+    for caps in PUBLISHED_IMAGE.captures_iter(manifests) {
+        if version.is_empty() && let Some(tag) = caps.get(2) {
+            version = tag.as_str().to_string();
+        }
+        images.entry(caps[1].to_string()).or_insert_with(|| caps[0].to_string());
+    }
+    PublishedRelease { version, images }
+}
+
+/// The last nightly version directory published on `date`, looking back up to
+/// a week. Nightly versions are named `vYYYYMMDD-<commit>` under `previous/`.
+fn find_version_for_date(base: &str, date: &str) -> Result<Option<String>> {
+    let (bucket, prefix) = gcs_bucket_prefix(base)?;
+    let date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .with_context(|| format!("Invalid date: {}", date))?;
+    for days_back in 0..=AS_OF_LOOKBACK_DAYS {
+        let day = date - chrono::Duration::days(days_back);
+        let list_prefix = format!("{}/previous/v{}", prefix, day.format("%Y%m%d"));
+        let url = format!(
+            "https://storage.googleapis.com/storage/v1/b/{}/o?delimiter=/&prefix={}",
+            bucket, list_prefix
+        );
+        let result = exec::run_cmd_timeout("curl", &["-fsSL", &url], exec::API_TIMEOUT)
+            .with_context(|| format!("Failed to list nightly releases in {}", bucket))?;
+        if let Some(version) = latest_listed_version(&result.stdout) {
+            return Ok(Some(version));
+        }
+    }
+    Ok(None)
+}
+
+/// Split `https://storage.googleapis.com/<bucket>/<prefix>` into bucket and prefix.
+fn gcs_bucket_prefix(base: &str) -> Result<(String, String)> {
+    let path = base
+        .strip_prefix("https://storage.googleapis.com/")
+        .with_context(|| format!("--as-of needs a storage.googleapis.com nightly url, got {}", base))?;
+    let (bucket, prefix) = path
+        .split_once('/')
+        .with_context(|| format!("Nightly url {} has no path within the bucket", base))?;
+    Ok((bucket.to_string(), prefix.trim_end_matches('/').to_string()))
+}
+
+/// The highest version directory in a GCS object listing (`prefixes` are
+/// `<prefix>/previous/<version>/`).
+fn latest_listed_version(listing: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(listing).ok()?;
+    json.get("prefixes")?
+        .as_array()?
+        .iter()
+        .filter_map(|p| p.as_str())
+        .filter_map(|p| p.trim_end_matches('/').rsplit('/').next())
+        .max()
+        .map(str::to_string)
+}
+
+/// IMAGE_ env var mappings for `component` from a published release. Images
+/// the release does not publish are left as they are, with a warning.
+pub fn image_mappings(component: &str, comp: &ComponentConfig, release: &PublishedRelease) -> Result<Vec<(String, String)>> {
+    let mut names: Vec<&String> = comp.images.keys().collect();
+    names.sort();
+    let mut mappings = Vec::new();
+    for name in names {
+        match release.images.get(name.as_str()) {
+            Some(image) => mappings.push((comp.images[name].clone(), image.clone())),
+            None => warnings::warn(format!(
+                "Release {} of {} has no '{}' image; {} left unchanged",
+                release.version, component, name, comp.images[name]
+            )),
+        }
+    }
+    if mappings.is_empty() {
+        anyhow::bail!("No published images for component '{}'", component);
+    }
+    Ok(mappings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_source_specs() {
+        let all = parse_source_spec("nightly").unwrap();
+        assert_eq!(all.component, None);
+        assert_eq!(all.to_arg(), "nightly");
+        let pipeline = parse_source_spec("pipeline=source").unwrap();
+        assert_eq!(pipeline.to_arg(), "pipeline=source");
+        assert!(parse_source_spec("bogus=nightly").is_err());
+        assert!(parse_source_spec("pipeline=weekly").is_err());
+        assert_eq!(parse_source_spec("triggers=release").unwrap().source, ImageSource::Release);
+
+        let sources = vec![all, pipeline];
+        assert_eq!(source_for(&sources, "pipeline"), ImageSource::Source);
+        assert_eq!(source_for(&sources, "triggers"), ImageSource::Nightly);
+        assert_eq!(source_for(&[], "triggers"), ImageSource::Source);
+    }
+
+    #[test]
+    fn test_parse_release_and_listing() {
+        let digest = "0".repeat(64);
+        let manifest = format!(
+            "image: ghcr.io/tektoncd/pipeline/cmd/controller:v20250301-abc1234@sha256:{d}\n\
+             args: [\"-entrypoint-image\", \"ghcr.io/tektoncd/pipeline/cmd/entrypoint:v20250301-abc1234@sha256:{d}\"]\n\
+             image: ghcr.io/tektoncd/pipeline/cmd/controller:v20250301-abc1234@sha256:{d}\n",
+            d = digest
+        );
+        let release = parse_release(&manifest);
+        assert_eq!(release.version, "v20250301-abc1234");
+        assert_eq!(release.images.len(), 2);
+        assert!(release.images["entrypoint"].ends_with(&digest));
+
+        // ko's default naming: <name>-<md5 of the import path>
+        let manifest = format!(
+            "image: ghcr.io/tektoncd/pipeline/webhook-d4749e605405422fd87700164e31b2d1:v0.62.0@sha256:{d}\n\
+             image: gcr.io/tekton-releases/github.com/tektoncd/pipeline/cmd/nop:v0.62.0@sha256:{d}\n",
+            d = digest
+        );
+        let release = parse_release(&manifest);
+        assert_eq!(release.images.keys().collect::<Vec<_>>(), ["nop", "webhook"]);
+        assert_eq!(release.version, "v0.62.0");
+
+        let listing = r#"{"prefixes": ["pipeline/previous/v20250301-aaa/", "pipeline/previous/v20250301-bbb/"]}"#;
+        assert_eq!(latest_listed_version(listing).as_deref(), Some("v20250301-bbb"));
+        assert_eq!(latest_listed_version("{}"), None);
+
+        let (bucket, prefix) = gcs_bucket_prefix("https://storage.googleapis.com/tekton-releases-nightly/pipeline").unwrap();
+        assert_eq!((bucket.as_str(), prefix.as_str()), ("tekton-releases-nightly", "pipeline"));
+    }
+}
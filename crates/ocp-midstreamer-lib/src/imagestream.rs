@@ -0,0 +1,234 @@
+//! Housekeeping for the imagestreams that builds push into the registry namespace.
+//!
+//! Every build pushes new revisions, so a long batch run can fill the registry's
+//! storage or the namespace quota and start failing pushes partway through.
+//! Before building, `run` warns about quota usage and registry disk pressure, and
+//! with `--prune-keep N` trims each imagestream to its N most recent tags/revisions.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+use crate::exec;
+use crate::profile;
+use crate::registry;
+use crate::warnings;
+
+/// Quota usage (fraction of hard limit) at which a warning is printed.
+const QUOTA_WARN_RATIO: f64 = 0.9;
+
+/// What pruning will remove from a namespace.
+#[derive(Debug, Default, PartialEq)]
+pub struct PrunePlan {
+    /// `imagestream:tag` names to delete (beyond the newest N tags per imagestream)
+    pub tags_to_delete: Vec<String>,
+    /// History revisions beyond the newest N in the tags that are kept
+    pub stale_revisions: usize,
+}
+
+impl PrunePlan {
+    pub fn is_empty(&self) -> bool {
+        self.tags_to_delete.is_empty() && self.stale_revisions == 0
+    }
+}
+
+/// Plan pruning from `oc get imagestreams -o json` output, keeping the `keep`
+/// most recently updated tags per imagestream and `keep` revisions per tag.
+pub fn plan_prune(imagestreams: &Value, keep: usize) -> PrunePlan {
+    let mut plan = PrunePlan::default();
+    let streams = imagestreams["items"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+    for stream in streams {
+        let Some(name) = stream["metadata"]["name"].as_str() else {
+            continue;
+        };
+        let mut tags: Vec<(&str, &str, usize)> = stream["status"]["tags"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+            .iter()
+            .filter_map(|t| {
+                let tag = t["tag"].as_str()?;
+                let items = t["items"].as_array()?;
+                // Items are newest first; RFC 3339 timestamps sort lexically
+                let newest = items.first().and_then(|i| i["created"].as_str()).unwrap_or("");
+                Some((tag, newest, items.len()))
+            })
+            .collect();
+        tags.sort_by(|a, b| b.1.cmp(a.1));
+
+        for (i, (tag, _, revisions)) in tags.iter().enumerate() {
+            if i < keep {
+                plan.stale_revisions += revisions.saturating_sub(keep);
+            } else {
+                plan.tags_to_delete.push(format!("{}:{}", name, tag));
+            }
+        }
+    }
+    plan
+}
+
+/// Prune imagestreams in `namespace` down to `keep` tags and revisions each.
+///
+/// Extra tags are deleted directly. Extra revisions are trimmed with a
+/// namespace-scoped `oc adm prune images`, which only drops imagestream history
+/// (Image objects and blobs are left to the cluster's image pruner) and needs
+/// the system:image-pruner role.
+pub fn prune(namespace: &str, keep: usize) -> Result<PrunePlan> {
+    let result = exec::run_cmd_timeout(
+        "oc",
+        &["get", "imagestreams", "-n", namespace, "-o", "json"],
+        exec::API_TIMEOUT,
+    )
+    .with_context(|| format!("Failed to list imagestreams in {}", namespace))?;
+    let streams: Value = serde_json::from_str(&result.stdout).context("Failed to parse imagestream list")?;
+    let plan = plan_prune(&streams, keep);
+
+    for tag in &plan.tags_to_delete {
+        let delete = exec::run_cmd_unchecked_timeout(
+            "oc",
+            &["delete", "imagestreamtag", tag, "-n", namespace],
+            exec::API_TIMEOUT,
+        )?;
+        if delete.exit_code != 0 {
+            warnings::warn(format!("Failed to delete imagestreamtag {}: {}", tag, delete.stderr.trim()));
+        }
+    }
+
+    if plan.stale_revisions > 0 {
+        let keep_arg = format!("--keep-tag-revisions={}", keep);
+        let trim = exec::run_cmd_unchecked(
+            "oc",
+            &[
+                "adm", "prune", "images",
+                "--namespace", namespace,
+                &keep_arg,
+                "--keep-younger-than=0s",
+                "--confirm",
+            ],
+        )?;
+        if trim.exit_code != 0 {
+            warnings::warn(format!(
+                "Failed to trim imagestream history in {} (needs system:image-pruner): {}",
+                namespace,
+                trim.stderr.trim()
+            ));
+        }
+    }
+
+    Ok(plan)
+}
+
+/// ResourceQuota entries in `oc get resourcequota -o json` output that are at
+/// or above `ratio` of their hard limit, as (resource, used, hard).
+pub fn quota_pressure(quotas: &Value, ratio: f64) -> Vec<(String, String, String)> {
+    let mut pressured = Vec::new();
+    for quota in quotas["items"].as_array().map(Vec::as_slice).unwrap_or(&[]) {
+        let Some(hard) = quota["status"]["hard"].as_object() else {
+            continue;
+        };
+        for (resource, limit) in hard {
+            let limit = limit.as_str().unwrap_or("");
+            let used = quota["status"]["used"][resource].as_str().unwrap_or("0");
+            let (Some(l), Some(u)) = (profile::parse_memory_bytes(limit), profile::parse_memory_bytes(used)) else {
+                continue;
+            };
+            if l > 0 && u as f64 >= l as f64 * ratio {
+                pressured.push((resource.clone(), used.to_string(), limit.to_string()));
+            }
+        }
+    }
+    pressured
+}
+
+/// Warn about namespace quota and registry disk pressure before a run pushes
+/// images, pruning first when `prune_keep` is set.
+pub fn preflight(namespace: &str, prune_keep: Option<usize>) {
+    if let Some(keep) = prune_keep {
+        match prune(namespace, keep) {
+            Ok(plan) if plan.is_empty() => eprintln!("  Imagestreams in {} already within {} per image.", namespace, keep),
+            Ok(plan) => eprintln!(
+                "  Pruned {} tag(s) and {} old revision(s) in {} (keeping {} per image).",
+                plan.tags_to_delete.len(),
+                plan.stale_revisions,
+                namespace,
+                keep
+            ),
+            Err(e) => warnings::warn(format!("Imagestream pruning failed: {e:#}")),
+        }
+    }
+
+    if let Ok(result) = exec::run_cmd_unchecked_timeout(
+        "oc",
+        &["get", "resourcequota", "-n", namespace, "-o", "json"],
+        exec::API_TIMEOUT,
+    ) && let Ok(quotas) = serde_json::from_str::<Value>(&result.stdout)
+    {
+        for (resource, used, hard) in quota_pressure(&quotas, QUOTA_WARN_RATIO) {
+            warnings::warn(format!(
+                "Quota {} in {} is at {}/{}; pushes may start failing (try --prune-keep)",
+                resource, namespace, used, hard
+            ));
+        }
+    }
+
+    match registry::storage_health() {
+        Ok(health) if health.disk_pressure => warnings::warn(format!(
+            "Registry node {} reports DiskPressure; pushes may fail mid-run (try --prune-keep)",
+            health.node.as_deref().unwrap_or("?")
+        )),
+        Ok(health) if health.low_disk() => warnings::warn(format!(
+            "Registry node {} is low on disk ({} free); pushes may fail mid-run (try --prune-keep)",
+            health.node.as_deref().unwrap_or("?"),
+            registry::format_bytes(health.available_bytes.unwrap_or(0))
+        )),
+        Ok(_) => {}
+        Err(e) => warnings::warn(format!("Could not check registry storage: {e:#}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tag(name: &str, created: &[&str]) -> Value {
+        json!({
+            "tag": name,
+            "items": created.iter().map(|c| json!({"created": c, "image": "sha256:x"})).collect::<Vec<_>>()
+        })
+    }
+
+    #[test]
+    fn test_plan_prune() {
+        let streams = json!({"items": [
+            {
+                "metadata": {"name": "controller"},
+                "status": {"tags": [
+                    tag("old", &["2025-01-01T00:00:00Z"]),
+                    tag("latest", &["2025-03-01T00:00:00Z", "2025-02-01T00:00:00Z", "2025-01-15T00:00:00Z"]),
+                    tag("mid", &["2025-02-10T00:00:00Z"]),
+                ]}
+            },
+            {
+                "metadata": {"name": "webhook"},
+                "status": {"tags": [tag("latest", &["2025-03-01T00:00:00Z"])]}
+            }
+        ]});
+
+        let plan = plan_prune(&streams, 2);
+        assert_eq!(plan.tags_to_delete, vec!["controller:old"]);
+        assert_eq!(plan.stale_revisions, 1);
+        assert!(plan_prune(&streams, 3).is_empty());
+    }
+
+    #[test]
+    fn test_quota_pressure() {
+        let quotas = json!({"items": [{
+            "status": {
+                "hard": {"openshift.io/imagestreams": "20", "requests.storage": "10Gi", "pods": "10"},
+                "used": {"openshift.io/imagestreams": "19", "requests.storage": "2Gi"}
+            }
+        }]});
+        let pressured = quota_pressure(&quotas, 0.9);
+        assert_eq!(pressured, vec![("openshift.io/imagestreams".to_string(), "19".to_string(), "20".to_string())]);
+    }
+}
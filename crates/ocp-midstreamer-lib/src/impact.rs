@@ -0,0 +1,262 @@
+//! Test impact analysis for `run --tags impacted`.
+//!
+//! For each component, the commit about to be tested is compared with the one
+//! the previous run tested (recorded in the cache directory; for `--as-of`
+//! runs without a record, the previous day's commit) through the GitHub
+//! compare API. Changed Go packages are mapped to gauge tags by the rules in
+//! `config/impact.toml`, and the run selects the union. Anything that cannot
+//! be mapped — no base commit, an unmapped package, a truncated diff, the API
+//! failing — falls back to the full suite.
+
+use anyhow::{Context, Result, bail};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use crate::component::ComponentSpec;
+use crate::config::{ComponentConfig, ImpactRule};
+use crate::{github, warnings};
+
+/// Tags of the full suite, run when impact cannot be determined.
+pub const FULL_SUITE_TAGS: &str = "e2e";
+
+/// File under the cache directory holding the commit each repo was last tested at, keyed `owner/repo`.
+const LAST_RUN_CACHE_FILE: &str = "impact-last-run.json";
+
+/// The compare API lists at most this many files; a longer diff is incomplete.
+const COMPARE_FILE_LIMIT: usize = 300;
+
+/// What a component's changes select.
+#[derive(Debug, PartialEq)]
+enum Impact {
+    /// Tag expressions of the affected specs (empty: nothing to run for it)
+    Tags(Vec<String>),
+    /// Run the full suite, for this reason
+    Full(String),
+}
+
+/// Directories of the Go packages changed by `files`, ignoring tests and vendored code.
+fn changed_packages(files: &[String]) -> BTreeSet<String> {
+    files
+        .iter()
+        .filter(|f| f.ends_with(".go") && !f.ends_with("_test.go") && !f.starts_with("vendor/"))
+        .map(|f| f.rsplit_once('/').map(|(dir, _)| dir).unwrap_or(".").to_string())
+        .collect()
+}
+
+/// Tags of the rules for `component` covering `packages`; `Full` if one is not covered.
+fn map_packages(component: &str, packages: &BTreeSet<String>, rules: &[ImpactRule]) -> Impact {
+    let mut tags: Vec<String> = Vec::new();
+    for package in packages {
+        let matching: Vec<&ImpactRule> = rules
+            .iter()
+            .filter(|r| r.component == component)
+            .filter(|r| {
+                r.paths.iter().any(|p| {
+                    let p = p.trim_end_matches('/');
+                    package == p || package.starts_with(&format!("{}/", p))
+                })
+            })
+            .collect();
+        if matching.is_empty() {
+            return Impact::Full(format!("{} changed {}, which no rule in impact.toml covers", component, package));
+        }
+        for rule in matching {
+            if !tags.contains(&rule.tags) {
+                tags.push(rule.tags.clone());
+            }
+        }
+    }
+    Impact::Tags(tags)
+}
+
+/// Union of tag expressions, e.g. `(triggers & e2e) | (chains & e2e)`.
+fn union(exprs: &[String]) -> String {
+    match exprs {
+        [single] => single.clone(),
+        _ => exprs.iter().map(|e| format!("({})", e)).collect::<Vec<_>>().join(" | "),
+    }
+}
+
+fn last_run_cache_path() -> Option<PathBuf> {
+    crate::platform::cache_dir().map(|d| d.join(LAST_RUN_CACHE_FILE))
+}
+
+fn load_last_run() -> BTreeMap<String, String> {
+    last_run_cache_path()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Best effort: without the record the next run compares by date or runs the full suite.
+fn save_last_run(commits: &BTreeMap<String, String>) {
+    let Some(path) = last_run_cache_path() else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(commits) && let Err(e) = std::fs::write(&path, json) {
+        warnings::warn(format!("Could not write impact cache {}: {e}", path.display()));
+    }
+}
+
+/// Commit `spec` will be built from: its as-of commit, or the head of its ref.
+fn head_commit(spec: &ComponentSpec, repo_url: &str, owner: &str, repo: &str) -> Result<String> {
+    if let Some(date) = &spec.as_of_date {
+        return Ok(github::resolve_commit_before_date(repo_url, date)?.sha);
+    }
+    let git_ref = match spec.git_ref.as_deref() {
+        Some(r) => r.strip_prefix("pr/").map(|n| format!("pull/{}/head", n)).unwrap_or_else(|| r.to_string()),
+        None => "HEAD".to_string(),
+    };
+    let response = github::api_request("GET", &format!("repos/{}/{}/commits/{}", owner, repo, git_ref), None, &[])?;
+    if !response.is_success() {
+        bail!("could not resolve {} in {}/{} (HTTP {})", git_ref, owner, repo, response.status);
+    }
+    response.json()?["sha"].as_str().map(String::from).context("commit has no sha")
+}
+
+/// Files changed between `base` and `head`.
+fn compare(owner: &str, repo: &str, base: &str, head: &str) -> Result<Vec<String>> {
+    let endpoint = format!("repos/{}/{}/compare/{}...{}", owner, repo, base, head);
+    let response = github::api_request("GET", &endpoint, None, &[])?;
+    if !response.is_success() {
+        bail!("compare {}...{} failed (HTTP {})", &base[..base.len().min(12)], &head[..head.len().min(12)], response.status);
+    }
+    let json = response.json()?;
+    let files: Vec<String> = json["files"]
+        .as_array()
+        .map(|f| f.iter().filter_map(|f| f["filename"].as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    if files.len() >= COMPARE_FILE_LIMIT {
+        bail!("the diff touches {}+ files, more than the compare API lists", COMPARE_FILE_LIMIT);
+    }
+    Ok(files)
+}
+
+/// Impact of one component, recording the commit it resolved to in `last_run`.
+fn component_impact(
+    spec: &ComponentSpec,
+    comp: &ComponentConfig,
+    rules: &[ImpactRule],
+    last_run: &mut BTreeMap<String, String>,
+) -> Result<Impact> {
+    let (owner, repo) = github::parse_github_url(&comp.repo)?;
+    let key = format!("{}/{}", owner, repo);
+    let head = head_commit(spec, &comp.repo, &owner, &repo)?;
+    let base = match last_run.insert(key, head.clone()) {
+        Some(sha) => sha,
+        None => match &spec.as_of_date {
+            Some(date) => {
+                let previous = crate::timestamp::parse_date(date)? - chrono::Duration::days(1);
+                github::resolve_commit_before_date(&comp.repo, &previous.format("%Y-%m-%d").to_string())?.sha
+            }
+            None => return Ok(Impact::Full(format!("no previous run of {} to compare with", spec.name))),
+        },
+    };
+    if base == head {
+        return Ok(Impact::Tags(Vec::new()));
+    }
+    let files = compare(&owner, &repo, &base, &head)?;
+    let packages = changed_packages(&files);
+    eprintln!(
+        "  {}: {} file(s) changed since {}, {} Go package(s)",
+        spec.name,
+        files.len(),
+        &base[..base.len().min(12)],
+        packages.len()
+    );
+    Ok(map_packages(&spec.name, &packages, rules))
+}
+
+/// Tag expression selecting the specs impacted by the changes to `specs` since the
+/// previous run, or the full suite when the impact cannot be determined.
+pub fn impacted_tags(specs: &[ComponentSpec], components: &std::collections::HashMap<String, ComponentConfig>, rules: &[ImpactRule]) -> String {
+    if github::is_offline() {
+        eprintln!("--tags impacted: offline, running the full suite");
+        return FULL_SUITE_TAGS.to_string();
+    }
+    eprintln!("Analyzing test impact of upstream changes:");
+    let mut last_run = load_last_run();
+    let mut tags: Vec<String> = Vec::new();
+    let mut full: Option<String> = None;
+    for spec in specs {
+        let Some(comp) = components.get(&spec.name) else {
+            continue;
+        };
+        match component_impact(spec, comp, rules, &mut last_run) {
+            Ok(Impact::Tags(t)) => {
+                for t in t {
+                    if !tags.contains(&t) {
+                        tags.push(t);
+                    }
+                }
+            }
+            Ok(Impact::Full(reason)) => {
+                full.get_or_insert(reason);
+            }
+            Err(e) => {
+                full.get_or_insert(format!("impact of {} unknown: {e:#}", spec.name));
+            }
+        }
+    }
+    save_last_run(&last_run);
+
+    let resolved = match full {
+        Some(reason) => {
+            eprintln!("  {}; running the full suite", reason);
+            FULL_SUITE_TAGS.to_string()
+        }
+        None if tags.is_empty() => {
+            eprintln!("  No Go package changed; running the full suite");
+            FULL_SUITE_TAGS.to_string()
+        }
+        None => union(&tags),
+    };
+    eprintln!("--tags impacted: {}", resolved);
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packages_to_tags() {
+        let files: Vec<String> = [
+            "pkg/resolution/resolver/git/resolver.go",
+            "pkg/resolution/resolver/git/resolver_test.go",
+            "cmd/resolvers/main.go",
+            "vendor/k8s.io/api/core/v1/types.go",
+            "docs/resolvers.md",
+            "main.go",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let packages = changed_packages(&files);
+        assert_eq!(packages.into_iter().collect::<Vec<_>>(), vec![".", "cmd/resolvers", "pkg/resolution/resolver/git"]);
+
+        let rule = |component: &str, paths: &[&str], tags: &str| ImpactRule {
+            component: component.into(),
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+            tags: tags.into(),
+        };
+        let rules = vec![
+            rule("pipeline", &["pkg/resolution/", "cmd/resolvers"], "resolvers & e2e"),
+            rule("triggers", &["pkg"], "triggers & e2e"),
+        ];
+        let set = |p: &[&str]| p.iter().map(|s| s.to_string()).collect::<BTreeSet<_>>();
+        assert_eq!(
+            map_packages("pipeline", &set(&["pkg/resolution/resolver/git", "cmd/resolvers"]), &rules),
+            Impact::Tags(vec!["resolvers & e2e".to_string()])
+        );
+        // pkg/resolutionx is not below pkg/resolution
+        assert!(matches!(map_packages("pipeline", &set(&["pkg/resolutionx"]), &rules), Impact::Full(_)));
+        assert!(matches!(map_packages("triggers", &set(&["."]), &rules), Impact::Full(_)));
+
+        assert_eq!(union(&["triggers & e2e".to_string()]), "triggers & e2e");
+        assert_eq!(union(&["a".to_string(), "b | c".to_string()]), "(a) | (b | c)");
+    }
+}
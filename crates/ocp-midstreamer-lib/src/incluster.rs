@@ -0,0 +1,1207 @@
+use anyhow::{Context, Result};
+use k8s_openapi::api::batch::v1::Job;
+use k8s_openapi::api::core::v1::{ConfigMap, PersistentVolumeClaim, Pod, Secret, ServiceAccount};
+use k8s_openapi::api::rbac::v1::{ClusterRole, ClusterRoleBinding};
+use kube::api::{Api, DeleteParams, ListParams, LogParams, Patch, PatchParams, PostParams};
+use futures::{AsyncBufReadExt, TryStreamExt};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::{audit, confirm, k8s, warnings};
+use crate::output::{self, OutputFormat};
+use crate::rbac::{self, RbacProfile};
+use crate::timestamp;
+use crate::workspace;
+
+/// Returns the CLI version tag for image caching.
+pub fn cli_image_tag() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+/// Job annotation recording the version of the CLI that created the Job.
+pub const CLIENT_VERSION_ANNOTATION: &str = "streamstress.openshift-pipelines.org/client-version";
+
+/// Returns the full image reference for the CLI container, tagged with
+/// `tag` (default: this CLI's version).
+pub fn cli_image_ref(registry: &str, tag: Option<&str>) -> String {
+    format!(
+        "{}/openshift-pipelines/streamstress-cli:{}",
+        registry,
+        tag.map(str::to_string).unwrap_or_else(cli_image_tag)
+    )
+}
+
+/// Tag of the CLI image with release-tests' Go modules for `release_tests_ref`
+/// baked in (`run --go-mod-cache image`).
+pub fn mod_cache_image_tag(release_tests_ref: &str) -> String {
+    let sanitized: String = release_tests_ref
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-' { c } else { '-' })
+        .collect();
+    let tag = format!("{}-gomod-{}", cli_image_tag(), sanitized);
+    // Image tags are limited to 128 characters
+    tag.chars().take(128).collect()
+}
+
+/// Check if the cached CLI image already exists in the registry.
+pub fn image_exists(registry: &str, tag: Option<&str>) -> Result<bool> {
+    let image_ref = cli_image_ref(registry, tag);
+    let result = crate::exec::run_cmd_unchecked("oc", &["image", "info", &image_ref]);
+    match result {
+        Ok(r) => Ok(r.exit_code == 0),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Tool used to build Dockerfile.cli into the CLI image.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq)]
+pub enum ImageBuilder {
+    /// First of podman, buildah, docker found on PATH; else an OpenShift binary build
+    #[default]
+    Auto,
+    Podman,
+    Buildah,
+    Docker,
+    /// In-cluster BuildConfig (docker strategy) fed from the local tree; needs only oc
+    Openshift,
+}
+
+impl ImageBuilder {
+    /// Resolve `Auto` using `available` to test whether a tool is on PATH.
+    pub fn resolve(self, available: impl Fn(&str) -> bool) -> ImageBuilder {
+        if self != ImageBuilder::Auto {
+            return self;
+        }
+        [
+            (ImageBuilder::Podman, "podman"),
+            (ImageBuilder::Buildah, "buildah"),
+            (ImageBuilder::Docker, "docker"),
+        ]
+        .into_iter()
+        .find(|(_, tool)| available(tool))
+        .map(|(b, _)| b)
+        .unwrap_or(ImageBuilder::Openshift)
+    }
+}
+
+/// BuildConfig (and ImageStream) used for `--image-builder openshift`.
+const CLI_BUILD_CONFIG: &str = "streamstress-cli";
+
+/// Files Dockerfile.cli copies from the build context.
+const CLI_BUILD_CONTEXT: &[&str] = &["Dockerfile.cli", "Cargo.toml", "Cargo.lock", "src", "crates", "config", "scripts", "dashboard"];
+
+/// Build and push the CLI container image, using version-based caching.
+///
+/// A pinned `tag` other than this CLI's version is never built from the local
+/// tree (it would not match the tag); it must already exist in the registry.
+/// With `prewarm_ref`, the image also carries release-tests' Go modules for that
+/// ref and is tagged with `mod_cache_image_tag`.
+pub fn build_and_push_cli_image(registry: &str, tag: Option<&str>, builder: ImageBuilder, prewarm_ref: Option<&str>) -> Result<()> {
+    let image_ref = cli_image_ref(registry, tag);
+    let own_tag = prewarm_ref.map(mod_cache_image_tag).unwrap_or_else(cli_image_tag);
+
+    if image_exists(registry, tag).unwrap_or(false) {
+        eprintln!("Using cached CLI image {}", image_ref);
+        return Ok(());
+    }
+    if tag.is_some_and(|t| t != own_tag) {
+        anyhow::bail!(
+            "CLI image {} not found. Push it first, or drop --image-tag to build {} from this tree",
+            image_ref,
+            own_tag
+        );
+    }
+    let build_args: Vec<String> = prewarm_ref
+        .map(|r| format!("RELEASE_TESTS_REF={}", r))
+        .into_iter()
+        .collect();
+
+    let builder = builder.resolve(|tool| which::which(tool).is_ok());
+    let tool = match builder {
+        ImageBuilder::Openshift => return openshift_binary_build(&own_tag, &build_args),
+        ImageBuilder::Buildah => "buildah",
+        ImageBuilder::Docker => "docker",
+        ImageBuilder::Podman | ImageBuilder::Auto => "podman",
+    };
+
+    eprintln!("Building CLI image {} with {}...", image_ref, tool);
+    let build_verb = if builder == ImageBuilder::Buildah { "bud" } else { "build" };
+    let mut args = vec![build_verb, "-f", "Dockerfile.cli", "-t", &image_ref];
+    for arg in &build_args {
+        args.extend(["--build-arg", arg.as_str()]);
+    }
+    args.push(".");
+    crate::exec::run_cmd_streaming(
+        tool,
+        &args,
+        &[],
+        crate::exec::DEFAULT_TIMEOUT,
+    )
+    .context("Failed to build CLI container image")?;
+
+    eprintln!("Pushing CLI image {}...", image_ref);
+    crate::exec::run_cmd(tool, &["push", &image_ref])
+        .context("Failed to push CLI container image")?;
+
+    eprintln!("CLI image pushed successfully.");
+    Ok(())
+}
+
+/// Build the CLI image in-cluster: a binary BuildConfig in openshift-pipelines
+/// receives the Dockerfile.cli build context and pushes to the
+/// streamstress-cli:<tag> ImageStreamTag that Jobs pull from.
+fn openshift_binary_build(tag: &str, build_args: &[String]) -> Result<()> {
+    let ns = "openshift-pipelines";
+    let exists = crate::exec::run_cmd_unchecked_timeout(
+        "oc",
+        &["get", "buildconfig", CLI_BUILD_CONFIG, "-n", ns],
+        crate::exec::API_TIMEOUT,
+    )?;
+    if exists.exit_code != 0 {
+        eprintln!("Creating BuildConfig {}/{}...", ns, CLI_BUILD_CONFIG);
+        crate::exec::run_cmd(
+            "oc",
+            &["new-build", "--binary", "--strategy=docker", "--name", CLI_BUILD_CONFIG, "-n", ns],
+        )
+        .context("Failed to create CLI BuildConfig")?;
+    }
+
+    let patch = serde_json::json!({
+        "spec": {
+            "strategy": {"dockerStrategy": {"dockerfilePath": "Dockerfile.cli"}},
+            "output": {"to": {"kind": "ImageStreamTag", "name": format!("{}:{}", CLI_BUILD_CONFIG, tag)}}
+        }
+    })
+    .to_string();
+    crate::exec::run_cmd(
+        "oc",
+        &["patch", "buildconfig", CLI_BUILD_CONFIG, "-n", ns, "--type=merge", "-p", &patch],
+    )
+    .context("Failed to point CLI BuildConfig at this version")?;
+
+    // Upload only what Dockerfile.cli needs (not target/ or test output)
+    let context_dir = workspace::scoped("cli-build-context").context("Failed to create build context directory")?;
+    for entry in CLI_BUILD_CONTEXT {
+        let src = std::path::Path::new(entry);
+        let dest = context_dir.path().join(entry);
+        if src.is_dir() {
+            crate::platform::copy_dir_recursive(src, &dest)?;
+        } else if src.exists() {
+            std::fs::copy(src, &dest).with_context(|| format!("Failed to copy {}", entry))?;
+        }
+    }
+
+    eprintln!("Building CLI image {}:{} in-cluster...", CLI_BUILD_CONFIG, tag);
+    let from_dir = format!("--from-dir={}", context_dir.path().display());
+    let mut args = vec!["start-build", CLI_BUILD_CONFIG, "-n", ns, &from_dir, "--follow", "--wait"];
+    for arg in build_args {
+        args.extend(["--build-arg", arg.as_str()]);
+    }
+    crate::exec::run_cmd_streaming(
+        "oc",
+        &args,
+        &[],
+        crate::exec::DEFAULT_TIMEOUT,
+    )
+    .context("In-cluster CLI image build failed")?;
+
+    eprintln!("CLI image built successfully.");
+    Ok(())
+}
+
+/// Detect if already running inside a Kubernetes pod.
+pub fn is_incluster() -> bool {
+    std::path::Path::new("/var/run/secrets/kubernetes.io/serviceaccount/token").exists()
+}
+
+/// Ensure the ServiceAccount, ClusterRole (minimal profile), and ClusterRoleBinding
+/// exist for in-cluster execution.
+pub async fn ensure_service_account(client: &kube::Client, namespace: &str, profile: RbacProfile) -> Result<()> {
+    let sa_api: Api<ServiceAccount> = Api::namespaced(client.clone(), namespace);
+    let sa = serde_json::from_value(rbac::service_account_manifest(namespace))?;
+
+    let pp = PostParams::default();
+    let result = k8s::retry("create ServiceAccount", || sa_api.create(&pp, &sa)).await;
+    audit::record_api("create", "ServiceAccount", Some(namespace), rbac::SERVICE_ACCOUNT, result.is_ok());
+    match result {
+        Ok(_) => eprintln!("Created ServiceAccount {}", rbac::SERVICE_ACCOUNT),
+        Err(kube::Error::Api(ae)) if ae.code == 409 => {
+            // Already exists
+        }
+        Err(e) => return Err(e).context("Failed to create ServiceAccount"),
+    }
+
+    if profile == RbacProfile::Minimal {
+        // Server-side apply so rule changes in newer CLI versions are picked up
+        let cr_api: Api<ClusterRole> = Api::all(client.clone());
+        let cr: ClusterRole = serde_json::from_value(rbac::minimal_cluster_role_manifest())?;
+        let pp = PatchParams::apply("streamstress").force();
+        let patch = Patch::Apply(&cr);
+        let result = k8s::retry("apply ClusterRole", || cr_api.patch(rbac::MINIMAL_CLUSTER_ROLE, &pp, &patch)).await;
+        audit::record_api("apply", "ClusterRole", None, rbac::MINIMAL_CLUSTER_ROLE, result.is_ok());
+        result.context("Failed to apply minimal ClusterRole")?;
+    }
+
+    let crb_api: Api<ClusterRoleBinding> = Api::all(client.clone());
+    let crb: ClusterRoleBinding = serde_json::from_value(rbac::cluster_role_binding_manifest(profile, namespace))?;
+
+    let create = format!(
+        "create ClusterRoleBinding {} -> {} for {}/{}",
+        rbac::CLUSTER_ROLE_BINDING, profile.cluster_role(), namespace, rbac::SERVICE_ACCOUNT
+    );
+
+    // roleRef is immutable, so a binding left over from another profile must be recreated
+    if let Ok(existing) = k8s::retry("get ClusterRoleBinding", || crb_api.get(rbac::CLUSTER_ROLE_BINDING)).await {
+        if existing.role_ref.name == profile.cluster_role() {
+            return Ok(());
+        }
+        confirm::confirm(
+            "rebind the streamstress ServiceAccount",
+            &[format!("delete ClusterRoleBinding {} -> {}", rbac::CLUSTER_ROLE_BINDING, existing.role_ref.name), create],
+        )?;
+        eprintln!(
+            "Rebinding {} from {} to {}",
+            rbac::CLUSTER_ROLE_BINDING, existing.role_ref.name, profile.cluster_role()
+        );
+        let dp = DeleteParams::default();
+        let result = k8s::retry("delete ClusterRoleBinding", || crb_api.delete(rbac::CLUSTER_ROLE_BINDING, &dp)).await;
+        audit::record_api("delete", "ClusterRoleBinding", None, rbac::CLUSTER_ROLE_BINDING, result.is_ok());
+        result.context("Failed to delete existing ClusterRoleBinding")?;
+    } else {
+        confirm::confirm("bind a cluster role to the streamstress ServiceAccount", &[create])?;
+    }
+
+    let pp = PostParams::default();
+    let result = k8s::retry("create ClusterRoleBinding", || crb_api.create(&pp, &crb)).await;
+    audit::record_api("create", "ClusterRoleBinding", None, rbac::CLUSTER_ROLE_BINDING, result.is_ok());
+    match result {
+        Ok(_) => eprintln!("Created ClusterRoleBinding {} -> {}", rbac::CLUSTER_ROLE_BINDING, profile.cluster_role()),
+        Err(kube::Error::Api(ae)) if ae.code == 409 => {
+            // Already exists
+        }
+        Err(e) => return Err(e).context("Failed to create ClusterRoleBinding"),
+    }
+
+    Ok(())
+}
+
+/// Publish configuration passed to the Job for direct gh-pages push.
+#[derive(Debug, Clone, Default)]
+pub struct PublishEnv {
+    pub github_token: Option<String>,
+    pub github_repository: Option<String>,
+    pub label: Option<String>,
+    pub output_dir: Option<String>,
+    /// Batch this run belongs to (`run --date-range`), for dashboard grouping
+    pub batch_id: Option<String>,
+    /// cosign key reference the Job signs the published run with
+    pub signing_key: Option<String>,
+}
+
+impl PublishEnv {
+    /// Load publish config from current environment (for passing to Job).
+    pub fn from_env() -> Self {
+        Self {
+            github_token: std::env::var("GITHUB_TOKEN").ok(),
+            github_repository: std::env::var("GITHUB_REPOSITORY").ok(),
+            label: std::env::var("RUN_LABEL").ok(),
+            output_dir: std::env::var("OUTPUT_DIR").ok(),
+            batch_id: crate::batch::current_batch_id(),
+            signing_key: crate::signing::Signer::from_env().and_then(|s| s.key),
+        }
+    }
+
+    /// Check if publish is configured.
+    pub fn is_configured(&self) -> bool {
+        self.github_token.is_some() && self.github_repository.is_some()
+    }
+}
+
+/// Secret holding publish credentials for in-cluster Jobs.
+pub const PUBLISH_SECRET: &str = "streamstress-publish";
+
+/// Key within `PUBLISH_SECRET` holding the GitHub token.
+const PUBLISH_SECRET_TOKEN_KEY: &str = "github-token";
+
+/// Create or rotate the publish Secret so the token is referenced by the Job
+/// rather than embedded in its spec (readable by anyone who can get Jobs).
+pub async fn ensure_publish_secret(client: &kube::Client, namespace: &str, github_token: &str) -> Result<()> {
+    let secret: Secret = serde_json::from_value(serde_json::json!({
+        "apiVersion": "v1",
+        "kind": "Secret",
+        "metadata": {
+            "name": PUBLISH_SECRET,
+            "namespace": namespace,
+            "labels": {
+                "app": "streamstress"
+            }
+        },
+        "type": "Opaque",
+        "stringData": {
+            PUBLISH_SECRET_TOKEN_KEY: github_token
+        }
+    }))?;
+
+    // Server-side apply creates the Secret or replaces the token in place on rotation
+    let secrets_api: Api<Secret> = Api::namespaced(client.clone(), namespace);
+    let pp = PatchParams::apply("streamstress").force();
+    let patch = Patch::Apply(&secret);
+    let result = k8s::retry("apply publish Secret", || secrets_api.patch(PUBLISH_SECRET, &pp, &patch)).await;
+    audit::record_api("apply", "Secret", Some(namespace), PUBLISH_SECRET, result.is_ok());
+    result.context("Failed to apply publish Secret")?;
+
+    Ok(())
+}
+
+/// Secret holding SMTP credentials for email reports from in-cluster Jobs.
+pub const SMTP_SECRET: &str = "streamstress-smtp";
+
+/// Create or update `SMTP_SECRET` with the credentials for email reports.
+pub async fn ensure_smtp_secret(client: &kube::Client, namespace: &str, username: &str, password: &str) -> Result<()> {
+    let secret: Secret = serde_json::from_value(serde_json::json!({
+        "apiVersion": "v1",
+        "kind": "Secret",
+        "metadata": {
+            "name": SMTP_SECRET,
+            "namespace": namespace,
+            "labels": {
+                "app": "streamstress"
+            }
+        },
+        "type": "Opaque",
+        "stringData": {
+            "username": username,
+            "password": password
+        }
+    }))?;
+
+    let secrets_api: Api<Secret> = Api::namespaced(client.clone(), namespace);
+    let pp = PatchParams::apply("streamstress").force();
+    let patch = Patch::Apply(&secret);
+    let result = k8s::retry("apply SMTP Secret", || secrets_api.patch(SMTP_SECRET, &pp, &patch)).await;
+    audit::record_api("apply", "Secret", Some(namespace), SMTP_SECRET, result.is_ok());
+    result.context("Failed to apply SMTP Secret")?;
+
+    Ok(())
+}
+
+/// Where in-cluster Jobs keep release-tests' Go modules between runs.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq)]
+pub enum GoModCache {
+    /// Download the whole module graph in every Job
+    #[default]
+    None,
+    /// Bake the modules for --release-tests-ref into a CLI image layer
+    Image,
+    /// Keep module and build caches on a PersistentVolumeClaim reused by every Job
+    Pvc,
+}
+
+/// Go settings for the gauge Go runner in in-cluster Jobs.
+#[derive(Debug, Clone, Default)]
+pub struct JobGoEnv {
+    pub mod_cache: GoModCache,
+    /// GOPROXY for module downloads (e.g. an internal mirror)
+    pub goproxy: Option<String>,
+}
+
+impl JobGoEnv {
+    /// CLI args recreating these settings (for self-invocation).
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.mod_cache != GoModCache::None {
+            args.push("--go-mod-cache".to_string());
+            args.push(clap::ValueEnum::to_possible_value(&self.mod_cache).map(|v| v.get_name().to_string()).unwrap_or_default());
+        }
+        if let Some(ref proxy) = self.goproxy {
+            args.push("--goproxy".to_string());
+            args.push(proxy.clone());
+        }
+        args
+    }
+
+    /// Container env vars: GOPROXY if set; with a PVC cache, module and build
+    /// caches on the volume, written group-writable so Jobs running under
+    /// different UIDs can share them.
+    fn env_vars(&self) -> Vec<serde_json::Value> {
+        let mut env = Vec::new();
+        if let Some(ref proxy) = self.goproxy {
+            env.push(serde_json::json!({"name": "GOPROXY", "value": proxy}));
+        }
+        if self.mod_cache == GoModCache::Pvc {
+            env.push(serde_json::json!({"name": "GOMODCACHE", "value": format!("{}/mod", GO_CACHE_MOUNT)}));
+            env.push(serde_json::json!({"name": "GOCACHE", "value": format!("{}/build", GO_CACHE_MOUNT)}));
+            env.push(serde_json::json!({"name": "GOFLAGS", "value": "-modcacherw"}));
+        }
+        env
+    }
+}
+
+/// PersistentVolumeClaim holding the Go caches for `--go-mod-cache pvc`.
+pub const GO_CACHE_PVC: &str = "streamstress-go-cache";
+
+/// Where the Go cache volume is mounted in the Job container.
+const GO_CACHE_MOUNT: &str = "/var/cache/streamstress-go";
+
+/// Size requested for the Go cache volume (module graph plus build cache).
+const GO_CACHE_SIZE: &str = "10Gi";
+
+/// Create the Go cache PVC if missing. An existing claim is left as is, since
+/// its spec is immutable once bound.
+pub async fn ensure_go_cache_pvc(client: &kube::Client, namespace: &str) -> Result<()> {
+    let pvcs: Api<PersistentVolumeClaim> = Api::namespaced(client.clone(), namespace);
+    if k8s::retry("get Go cache PVC", || pvcs.get_opt(GO_CACHE_PVC)).await.context("Failed to look up Go cache PVC")?.is_some() {
+        return Ok(());
+    }
+    let pvc: PersistentVolumeClaim = serde_json::from_value(serde_json::json!({
+        "apiVersion": "v1",
+        "kind": "PersistentVolumeClaim",
+        "metadata": {
+            "name": GO_CACHE_PVC,
+            "namespace": namespace,
+            "labels": {
+                "app": "streamstress"
+            }
+        },
+        "spec": {
+            "accessModes": ["ReadWriteOnce"],
+            "resources": {"requests": {"storage": GO_CACHE_SIZE}}
+        }
+    }))?;
+    let pp = PostParams::default();
+    let result = k8s::retry("create Go cache PVC", || pvcs.create(&pp, &pvc)).await;
+    audit::record_api("create", "PersistentVolumeClaim", Some(namespace), GO_CACHE_PVC, result.is_ok());
+    result.context("Failed to create Go cache PVC")?;
+    eprintln!("Created PVC {}/{} ({}) for the Go module cache", namespace, GO_CACHE_PVC, GO_CACHE_SIZE);
+    Ok(())
+}
+
+/// Secret holding registry credentials for Jobs that push images (`konflux --in-cluster`).
+pub const REGISTRY_AUTH_SECRET: &str = "streamstress-registry-auth";
+
+/// Where `REGISTRY_AUTH_SECRET` is mounted; entrypoint.sh copies it into the
+/// podman and Docker config locations, which must stay writable for `oc registry login`.
+const REGISTRY_AUTH_MOUNT: &str = "/var/run/streamstress/registry-auth";
+
+/// Create or update `REGISTRY_AUTH_SECRET` from a local containers/Docker auth file.
+pub async fn ensure_registry_auth_secret(client: &kube::Client, namespace: &str, auth_json: &str) -> Result<()> {
+    let secret: Secret = serde_json::from_value(serde_json::json!({
+        "apiVersion": "v1",
+        "kind": "Secret",
+        "metadata": {
+            "name": REGISTRY_AUTH_SECRET,
+            "namespace": namespace,
+            "labels": {
+                "app": "streamstress"
+            }
+        },
+        "type": "Opaque",
+        "stringData": {
+            "config.json": auth_json
+        }
+    }))?;
+
+    let secrets_api: Api<Secret> = Api::namespaced(client.clone(), namespace);
+    let pp = PatchParams::apply("streamstress").force();
+    let patch = Patch::Apply(&secret);
+    let result = k8s::retry("apply registry auth Secret", || secrets_api.patch(REGISTRY_AUTH_SECRET, &pp, &patch)).await;
+    audit::record_api("apply", "Secret", Some(namespace), REGISTRY_AUTH_SECRET, result.is_ok());
+    result.context("Failed to apply registry auth Secret")?;
+
+    Ok(())
+}
+
+/// Deadline of a `run` Job (deploy and tests).
+const RUN_JOB_DEADLINE_SECS: u64 = 10800;
+
+/// Extra time a `run` Job gets for building the components (`--build-where cluster`).
+const BUILD_DEADLINE_SECS: u64 = 3600;
+
+/// Time a `konflux` Job gets for the builds, bundle, index and catalog smoke
+/// test, on top of the pipeline timeout.
+pub const KONFLUX_BUILD_DEADLINE_SECS: u64 = 10800;
+
+/// What a Job's pod is allowed to do beyond deploying and testing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JobProfile {
+    /// activeDeadlineSeconds of the Job
+    pub deadline_secs: u64,
+    /// Build and push images in the pod: buildah needs a privileged container, and
+    /// pushes use the credentials in `REGISTRY_AUTH_SECRET` (when `registry_auth`)
+    pub builds_images: bool,
+    /// Mount `REGISTRY_AUTH_SECRET` into the pod
+    pub registry_auth: bool,
+}
+
+impl JobProfile {
+    /// A `run` Job: deploy and test images built before the Job was created.
+    pub const RUN: JobProfile = JobProfile { deadline_secs: RUN_JOB_DEADLINE_SECS, builds_images: false, registry_auth: false };
+
+    /// A `run` Job that first builds the components with ko, which needs no
+    /// privileges, pushing to the internal registry with the Job's token.
+    pub const RUN_WITH_BUILDS: JobProfile = JobProfile {
+        deadline_secs: RUN_JOB_DEADLINE_SECS + BUILD_DEADLINE_SECS,
+        builds_images: false,
+        registry_auth: false,
+    };
+
+    /// A `konflux` Job that builds and pushes images, waiting up to
+    /// `pipeline_timeout` seconds for the triggered pipeline.
+    pub fn konflux(pipeline_timeout: u64, registry_auth: bool) -> Self {
+        JobProfile {
+            deadline_secs: KONFLUX_BUILD_DEADLINE_SECS + pipeline_timeout,
+            builds_images: true,
+            registry_auth,
+        }
+    }
+
+    /// Volumes, volume mounts and container securityContext for this profile.
+    fn pod_settings(&self) -> (Vec<serde_json::Value>, Vec<serde_json::Value>, serde_json::Value) {
+        let mut volumes = Vec::new();
+        let mut mounts = Vec::new();
+        if self.registry_auth {
+            volumes.push(serde_json::json!({"name": "registry-auth", "secret": {"secretName": REGISTRY_AUTH_SECRET}}));
+            mounts.push(serde_json::json!({"name": "registry-auth", "mountPath": REGISTRY_AUTH_MOUNT, "readOnly": true}));
+        }
+        let security_context = if self.builds_images {
+            serde_json::json!({"privileged": true})
+        } else {
+            serde_json::json!({})
+        };
+        (volumes, mounts, security_context)
+    }
+}
+
+/// Create a detached Kubernetes Job for in-cluster execution. Returns the Job name.
+pub async fn create_job(
+    client: &kube::Client,
+    namespace: &str,
+    image_ref: &str,
+    cli_args: &[String],
+    publish_env: &PublishEnv,
+    go_env: &JobGoEnv,
+    profile: &JobProfile,
+) -> Result<String> {
+    let job_name = format!("streamstress-{}", timestamp::unix_now());
+
+    let args_json: Vec<serde_json::Value> = cli_args.iter().map(|a| serde_json::json!(a)).collect();
+
+    // Build env vars for direct gh-pages publish (if configured)
+    let mut env_vars: Vec<serde_json::Value> = vec![
+        serde_json::json!({"name": "JOB_NAME", "value": &job_name}),
+    ];
+
+    if publish_env.github_token.is_some() {
+        env_vars.push(serde_json::json!({
+            "name": "GITHUB_TOKEN",
+            "valueFrom": {
+                "secretKeyRef": {"name": PUBLISH_SECRET, "key": PUBLISH_SECRET_TOKEN_KEY}
+            }
+        }));
+    }
+    if let Some(ref repo) = publish_env.github_repository {
+        env_vars.push(serde_json::json!({"name": "GITHUB_REPOSITORY", "value": repo}));
+    }
+    if let Some(ref label) = publish_env.label {
+        env_vars.push(serde_json::json!({"name": "RUN_LABEL", "value": label}));
+    }
+    if let Some(ref output_dir) = publish_env.output_dir {
+        env_vars.push(serde_json::json!({"name": "OUTPUT_DIR", "value": output_dir}));
+    }
+    if let Some(ref batch_id) = publish_env.batch_id {
+        env_vars.push(serde_json::json!({"name": crate::batch::BATCH_ID_ENV, "value": batch_id}));
+    }
+    if let Some(ref key) = publish_env.signing_key {
+        env_vars.push(serde_json::json!({"name": crate::signing::SIGNING_KEY_ENV, "value": key}));
+    }
+    // Email reports: settings inline, credentials from the Secret made by run_incluster
+    if let Some(email_config) = crate::email::job_config() {
+        env_vars.push(serde_json::json!({"name": crate::email::EMAIL_CONFIG_ENV, "value": email_config}));
+        if crate::email::smtp_credentials().is_some() {
+            for (name, key) in [("SMTP_USERNAME", "username"), ("SMTP_PASSWORD", "password")] {
+                env_vars.push(serde_json::json!({
+                    "name": name,
+                    "valueFrom": {"secretKeyRef": {"name": SMTP_SECRET, "key": key}}
+                }));
+            }
+        }
+    }
+    env_vars.extend(go_env.env_vars());
+
+    let (mut volumes, mut volume_mounts, security_context) = profile.pod_settings();
+    if go_env.mod_cache == GoModCache::Pvc {
+        volumes.push(serde_json::json!({"name": "go-cache", "persistentVolumeClaim": {"claimName": GO_CACHE_PVC}}));
+        volume_mounts.push(serde_json::json!({"name": "go-cache", "mountPath": GO_CACHE_MOUNT}));
+    }
+
+    let job: Job = serde_json::from_value(serde_json::json!({
+        "apiVersion": "batch/v1",
+        "kind": "Job",
+        "metadata": {
+            "name": &job_name,
+            "namespace": namespace,
+            "labels": {
+                "app": "streamstress"
+            },
+            "annotations": {
+                CLIENT_VERSION_ANNOTATION: cli_image_tag()
+            }
+        },
+        "spec": {
+            "backoffLimit": 0,
+            "activeDeadlineSeconds": profile.deadline_secs,
+            "template": {
+                "metadata": {
+                    "labels": {
+                        "app": "streamstress",
+                        "job-name": &job_name
+                    }
+                },
+                "spec": {
+                    "serviceAccountName": rbac::SERVICE_ACCOUNT,
+                    "restartPolicy": "Never",
+                    "containers": [{
+                        "name": "streamstress",
+                        "image": image_ref,
+                        "imagePullPolicy": "Always",
+                        "args": args_json,
+                        "env": env_vars,
+                        "securityContext": security_context,
+                        "volumeMounts": volume_mounts
+                    }],
+                    "volumes": volumes
+                }
+            }
+        }
+    }))?;
+
+    let jobs_api: Api<Job> = Api::namespaced(client.clone(), namespace);
+    let pp = PostParams::default();
+    let result = k8s::retry("create Job", || jobs_api.create(&pp, &job)).await;
+    audit::record_api("create", "Job", Some(namespace), &job_name, result.is_ok());
+    result.context("Failed to create Job")?;
+
+    Ok(job_name)
+}
+
+/// Internal service address for the OCP image registry.
+/// Pods pull from (and `--build-where cluster` Jobs push to) this address.
+pub const INTERNAL_REGISTRY: &str = "image-registry.openshift-image-registry.svc:5000";
+
+/// How `run_incluster` gets the CLI image and what the Job does.
+#[derive(Debug, Clone, Copy)]
+pub struct JobOptions<'a> {
+    /// Pre-built CLI image used instead of building one (`--image`)
+    pub image_override: Option<&'a str>,
+    pub image_tag: Option<&'a str>,
+    pub image_builder: ImageBuilder,
+    pub rbac_profile: RbacProfile,
+    pub go_env: &'a JobGoEnv,
+    pub release_tests_ref: &'a str,
+    /// The Job builds the components itself instead of deploying images
+    /// built before it was created
+    pub builds: bool,
+}
+
+/// Main entry point for in-cluster execution. Builds image, creates Job, returns immediately.
+pub fn run_incluster(registry: &str, namespace: &str, cli_args: &[String], opts: &JobOptions) -> Result<()> {
+    let JobOptions { image_override, image_tag, image_builder, rbac_profile, go_env, release_tests_ref, builds } = *opts;
+    if builds && rbac_profile != RbacProfile::ClusterAdmin {
+        anyhow::bail!("--build-where cluster pushes images from the Job, which --rbac-profile minimal does not allow");
+    }
+    let image_ref = if let Some(img) = image_override {
+        eprintln!("Using pre-built image: {}", img);
+        if go_env.mod_cache == GoModCache::Image {
+            warnings::warn("--go-mod-cache image has no effect with --image; the Job downloads Go modules itself");
+        }
+        img.to_string()
+    } else if go_env.mod_cache == GoModCache::Image {
+        if image_tag.is_some() {
+            anyhow::bail!("--go-mod-cache image builds its own tagged CLI image; drop --image-tag");
+        }
+        let tag = mod_cache_image_tag(release_tests_ref);
+        build_and_push_cli_image(registry, Some(&tag), image_builder, Some(release_tests_ref))?;
+        cli_image_ref(INTERNAL_REGISTRY, Some(&tag))
+    } else {
+        // Push to external route, but Job pulls via internal service address
+        build_and_push_cli_image(registry, image_tag, image_builder, None)?;
+        cli_image_ref(INTERNAL_REGISTRY, image_tag)
+    };
+
+    // Append --skip-build so the in-cluster copy skips clone/build, unless it builds
+    let mut job_args = cli_args.to_vec();
+    if !builds && !job_args.contains(&"--skip-build".to_string()) {
+        job_args.push("--skip-build".to_string());
+    }
+    let profile = if builds { JobProfile::RUN_WITH_BUILDS } else { JobProfile::RUN };
+
+    // Load publish env from current environment (CI passes these)
+    let publish_env = PublishEnv::from_env();
+    if publish_env.is_configured() {
+        eprintln!("Auto-publish configured: results will be pushed to gh-pages when Job completes");
+    }
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to create tokio runtime")?;
+
+    let client = rt
+        .block_on(k8s::client())
+        .context("Failed to connect to cluster")?;
+
+    rt.block_on(ensure_service_account(&client, namespace, rbac_profile))?;
+    if let Some(ref token) = publish_env.github_token {
+        rt.block_on(ensure_publish_secret(&client, namespace, token))?;
+    }
+    if let Some((username, password)) = crate::email::smtp_credentials()
+        && crate::email::job_config().is_some()
+    {
+        rt.block_on(ensure_smtp_secret(&client, namespace, &username, &password))?;
+    }
+    if go_env.mod_cache == GoModCache::Pvc {
+        rt.block_on(ensure_go_cache_pvc(&client, namespace))?;
+    }
+    let job_name = rt.block_on(create_job(&client, namespace, &image_ref, &job_args, &publish_env, go_env, &profile))?;
+
+    eprintln!("Job {} created in namespace {}", job_name, namespace);
+    eprintln!("  View status:  streamstress status");
+    eprintln!("  Stream logs:  streamstress logs");
+
+    Ok(())
+}
+
+/// Local registry credentials for a `konflux` Job: the containers auth file,
+/// else the Docker config.
+fn local_registry_auth() -> Option<String> {
+    [
+        crate::platform::containers_auth_file(),
+        crate::platform::docker_config_dir().map(|d| d.join("config.json")),
+    ]
+    .into_iter()
+    .flatten()
+    .find_map(|path| std::fs::read_to_string(path).ok())
+}
+
+/// Run `konflux` in a Job: builds the CLI image, then creates a Job that builds
+/// the component, bundle and index images (buildah and opm from the CLI image),
+/// generates the SNAPSHOT and optionally triggers and waits for the pipeline.
+/// `cli_args` are the Job's `konflux` arguments; returns once the Job is created.
+pub fn run_konflux_incluster(
+    registry: &str,
+    namespace: &str,
+    cli_args: &[String],
+    image_builder: ImageBuilder,
+    pipeline_timeout: u64,
+) -> Result<()> {
+    build_and_push_cli_image(registry, None, image_builder, None)?;
+    let image_ref = cli_image_ref(INTERNAL_REGISTRY, None);
+
+    let registry_auth = local_registry_auth();
+    if registry_auth.is_none() {
+        warnings::warn("No local registry credentials found; pushes from the Job to --registry may fail");
+    }
+
+    let publish_env = PublishEnv::from_env();
+    if publish_env.is_configured() {
+        eprintln!("Auto-publish configured: pipeline results will be pushed to gh-pages when the Job completes");
+    }
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to create tokio runtime")?;
+
+    let client = rt
+        .block_on(k8s::client())
+        .context("Failed to connect to cluster")?;
+
+    // Building images needs a privileged pod, which only cluster-admin may create
+    rt.block_on(ensure_service_account(&client, namespace, RbacProfile::ClusterAdmin))?;
+    if let Some(ref token) = publish_env.github_token {
+        rt.block_on(ensure_publish_secret(&client, namespace, token))?;
+    }
+    if let Some(ref auth) = registry_auth {
+        rt.block_on(ensure_registry_auth_secret(&client, namespace, auth))?;
+    }
+    let profile = JobProfile::konflux(pipeline_timeout, registry_auth.is_some());
+    let job_name = rt.block_on(create_job(&client, namespace, &image_ref, cli_args, &publish_env, &JobGoEnv::default(), &profile))?;
+
+    eprintln!("Job {} created in namespace {}", job_name, namespace);
+    eprintln!("  View status:  streamstress status");
+    eprintln!("  Stream logs:  streamstress logs");
+
+    Ok(())
+}
+
+/// Name of the ConfigMap a Job records its CLI version and publish outcome in.
+fn job_configmap_name(job_name: &str) -> String {
+    format!("{}-info", job_name)
+}
+
+/// Namespace of the pod we are running in (in-cluster only).
+fn pod_namespace() -> Option<String> {
+    std::fs::read_to_string("/var/run/secrets/kubernetes.io/serviceaccount/namespace")
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Called inside a Job: server-side apply `data` into the Job's info ConfigMap,
+/// owned by the Job so it is garbage-collected with it. Each writer uses its own
+/// `field_manager` so its keys don't remove another writer's.
+pub async fn record_job_data(job_name: &str, field_manager: &str, data: &BTreeMap<String, String>) -> Result<()> {
+    let namespace = pod_namespace().unwrap_or_else(|| "openshift-pipelines".to_string());
+    let client = k8s::client().await.context("Failed to connect to cluster")?;
+
+    let jobs_api: Api<Job> = Api::namespaced(client.clone(), &namespace);
+    let job = k8s::retry("get Job", || jobs_api.get(job_name)).await.with_context(|| format!("Failed to get Job {}", job_name))?;
+    let uid = job.metadata.uid.unwrap_or_default();
+
+    let name = job_configmap_name(job_name);
+    let cm: ConfigMap = serde_json::from_value(serde_json::json!({
+        "apiVersion": "v1",
+        "kind": "ConfigMap",
+        "metadata": {
+            "name": &name,
+            "namespace": &namespace,
+            "labels": {"app": "streamstress", "job-name": job_name},
+            "ownerReferences": [{
+                "apiVersion": "batch/v1",
+                "kind": "Job",
+                "name": job_name,
+                "uid": uid
+            }]
+        },
+        "data": data
+    }))?;
+    let cm_api: Api<ConfigMap> = Api::namespaced(client, &namespace);
+    let pp = PatchParams::apply(field_manager).force();
+    let patch = Patch::Apply(&cm);
+    let result = k8s::retry("apply Job ConfigMap", || cm_api.patch(&name, &pp, &patch)).await;
+    audit::record_api("apply", "ConfigMap", Some(&namespace), &name, result.is_ok());
+    result.context("Failed to write Job info ConfigMap")?;
+    Ok(())
+}
+
+/// Called at startup inside a Job: record this binary's version so `status`
+/// and `logs` can compare it with the local CLI.
+pub async fn record_job_version(job_name: &str) -> Result<()> {
+    let data = BTreeMap::from([("version".to_string(), cli_image_tag())]);
+    record_job_data(job_name, "streamstress", &data).await
+}
+
+/// Data a Job has recorded in its info ConfigMap (empty until the pod starts).
+async fn job_data(client: &kube::Client, namespace: &str, job_name: &str) -> BTreeMap<String, String> {
+    let cm_api: Api<ConfigMap> = Api::namespaced(client.clone(), namespace);
+    let cm_name = job_configmap_name(job_name);
+    match k8s::retry("get Job ConfigMap", || cm_api.get_opt(&cm_name)).await {
+        Ok(Some(cm)) => cm.data.unwrap_or_default(),
+        _ => BTreeMap::new(),
+    }
+}
+
+/// Info ConfigMap key holding the auto-publish outcome (published/skipped/failed).
+pub const PUBLISH_STATUS_KEY: &str = "publish-status";
+
+/// Info ConfigMap key holding the comma-separated run ids published by the Job.
+pub const PUBLISHED_RUN_IDS_KEY: &str = "published-run-ids";
+
+/// Called inside a Job: data this Job has already recorded.
+pub async fn own_job_data(job_name: &str) -> BTreeMap<String, String> {
+    let namespace = pod_namespace().unwrap_or_else(|| "openshift-pipelines".to_string());
+    match k8s::client().await {
+        Ok(client) => job_data(&client, &namespace, job_name).await,
+        Err(_) => BTreeMap::new(),
+    }
+}
+
+/// State of a streamstress Job and its pod, as reported by `status`.
+#[derive(Debug, Serialize)]
+pub struct JobStatus {
+    pub name: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_seconds: Option<i64>,
+    pub pod_phase: String,
+    /// CLI version running in the Job's image (None until the pod reports it)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_version: Option<String>,
+    /// Auto-publish outcome (None until the run finishes or if it predates reporting)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publish: Option<String>,
+    /// Dashboard run ids the Job published
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub published_run_ids: Vec<String>,
+}
+
+/// Collect the state of all streamstress Jobs in the namespace.
+pub async fn job_statuses(client: &kube::Client, namespace: &str) -> Result<Vec<JobStatus>> {
+    let jobs_api: Api<Job> = Api::namespaced(client.clone(), namespace);
+    let lp = ListParams::default().labels("app=streamstress");
+    let job_list = k8s::retry("list Jobs", || jobs_api.list(&lp)).await.context("Failed to list Jobs")?;
+
+    let pods_api: Api<Pod> = Api::namespaced(client.clone(), namespace);
+    let mut statuses = Vec::new();
+
+    for job in &job_list.items {
+        let name = job.metadata.name.as_deref().unwrap_or("unknown");
+
+        let status = if let Some(ref s) = job.status {
+            if s.succeeded.unwrap_or(0) > 0 {
+                "Succeeded"
+            } else if s.failed.unwrap_or(0) > 0 {
+                "Failed"
+            } else if s.active.unwrap_or(0) > 0 {
+                "Active"
+            } else {
+                "Pending"
+            }
+        } else {
+            "Unknown"
+        };
+
+        let created = job.metadata.creation_timestamp.as_ref().map(|ct| ct.0.as_second());
+
+        // Look up pod for this job
+        let pod_lp = ListParams::default().labels(&format!("job-name={}", name));
+        let pod_phase = match k8s::retry("list Job Pods", || pods_api.list(&pod_lp)).await {
+            Ok(pods) => {
+                if let Some(pod) = pods.items.first() {
+                    pod.status
+                        .as_ref()
+                        .and_then(|s| s.phase.clone())
+                        .unwrap_or_else(|| "Unknown".to_string())
+                } else {
+                    "NoPod".to_string()
+                }
+            }
+            Err(_) => "Error".to_string(),
+        };
+
+        let data = job_data(client, namespace, name).await;
+        statuses.push(JobStatus {
+            name: name.to_string(),
+            status: status.to_string(),
+            created: created
+                .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+                .map(timestamp::to_rfc3339),
+            age_seconds: created.map(|secs| timestamp::unix_now() - secs),
+            pod_phase,
+            image_version: data.get("version").cloned(),
+            publish: data.get(PUBLISH_STATUS_KEY).cloned(),
+            published_run_ids: data
+                .get(PUBLISHED_RUN_IDS_KEY)
+                .map(|ids| ids.split(',').filter(|id| !id.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default(),
+        });
+    }
+
+    Ok(statuses)
+}
+
+/// Show status of streamstress Jobs in the namespace.
+pub async fn show_status(client: &kube::Client, namespace: &str, format: OutputFormat) -> Result<()> {
+    let statuses = job_statuses(client, namespace).await?;
+    let lock_line = match crate::lock::current_holder(client, namespace).await {
+        Ok(Some(h)) if h.expired => format!("Cluster lock: expired (last held by {})", h.identity),
+        Ok(Some(h)) => format!(
+            "Cluster lock: held by {}{}",
+            h.identity,
+            h.acquired.map(|a| format!(" since {}", a)).unwrap_or_default()
+        ),
+        Ok(None) => "Cluster lock: free".to_string(),
+        Err(e) => format!("Cluster lock: unknown ({e:#})"),
+    };
+    let operator_lines = match crate::deploy::health::collect(client).await {
+        Ok(health) => render_operator_health(&health),
+        Err(e) => format!("Operator: unknown ({e:#})\n"),
+    };
+
+    if format.is_structured() {
+        eprintln!("{}", lock_line);
+        eprint!("{}", operator_lines);
+        return output::print(format, &statuses);
+    }
+
+    println!("{}\n", lock_line);
+    println!("{}", operator_lines);
+    if statuses.is_empty() {
+        println!("No streamstress Jobs found in namespace {}", namespace);
+        return Ok(());
+    }
+
+    println!("{:<40} {:<12} {:<12} {:<12} {:<10} {:<10}", "NAME", "STATUS", "AGE", "POD PHASE", "VERSION", "PUBLISH");
+    println!("{}", "-".repeat(98));
+
+    for job in &statuses {
+        let age = job.age_seconds.map(format_age).unwrap_or_else(|| "N/A".to_string());
+        let version = job.image_version.as_deref().unwrap_or("-");
+        let publish = job.publish.as_deref().unwrap_or("-");
+        println!("{:<40} {:<12} {:<12} {:<12} {:<10} {:<10}", job.name, job.status, age, job.pod_phase, version, publish);
+    }
+
+    Ok(())
+}
+
+/// Operator CR table for `status`: readiness, version and the last reconcile
+/// error of each TektonConfig, TektonPipeline and TektonTrigger.
+fn render_operator_health(health: &[crate::deploy::health::ComponentHealth]) -> String {
+    if health.is_empty() {
+        return "Operator: not installed (no TektonConfig found)\n".to_string();
+    }
+    let verdict = if crate::deploy::health::all_ready(health) { "ready for testing" } else { "NOT ready" };
+    let mut out = format!("Operator: {}\n", verdict);
+    out.push_str(&format!("  {:<16} {:<12} {:<8} {:<12} {}\n", "KIND", "NAME", "READY", "VERSION", "ERROR"));
+    for h in health {
+        out.push_str(&format!(
+            "  {:<16} {:<12} {:<8} {:<12} {}\n",
+            h.kind,
+            h.name,
+            h.ready_label(),
+            h.version.as_deref().unwrap_or("-"),
+            h.error.as_deref().unwrap_or("-")
+        ));
+    }
+    out
+}
+
+/// Stream logs from the most recent (or specified) streamstress Job pod.
+pub async fn stream_job_logs(
+    client: &kube::Client,
+    namespace: &str,
+    job_name: Option<&str>,
+) -> Result<()> {
+    let jobs_api: Api<Job> = Api::namespaced(client.clone(), namespace);
+
+    let target_job_name = if let Some(name) = job_name {
+        name.to_string()
+    } else {
+        // Find the most recent Job
+        let lp = ListParams::default().labels("app=streamstress");
+        let job_list = k8s::retry("list Jobs", || jobs_api.list(&lp)).await.context("Failed to list Jobs")?;
+        let most_recent = job_list
+            .items
+            .iter()
+            .max_by_key(|j| {
+                j.metadata
+                    .creation_timestamp
+                    .as_ref()
+                    .map(|t| t.0.as_second())
+                    .unwrap_or(0)
+            })
+            .ok_or_else(|| anyhow::anyhow!("No streamstress Jobs found"))?;
+        most_recent
+            .metadata
+            .name
+            .clone()
+            .unwrap_or_else(|| "unknown".to_string())
+    };
+
+    eprintln!("Streaming logs for Job {}...", target_job_name);
+
+    let pods_api: Api<Pod> = Api::namespaced(client.clone(), namespace);
+    let pod_lp = ListParams::default().labels(&format!("job-name={}", target_job_name));
+
+    // Wait for pod to appear (up to 60s)
+    let mut pod_name = None;
+    for _ in 0..30 {
+        let pods = k8s::retry("list Job Pods", || pods_api.list(&pod_lp)).await?;
+        if let Some(pod) = pods.items.first() {
+            pod_name = pod.metadata.name.clone();
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    }
+
+    let pod_name = pod_name.ok_or_else(|| anyhow::anyhow!("No pod found for Job {}", target_job_name))?;
+
+    // Check pod phase to decide follow mode
+    let pod = k8s::retry("get Pod", || pods_api.get(&pod_name)).await?;
+    let phase = pod
+        .status
+        .as_ref()
+        .and_then(|s| s.phase.as_deref())
+        .unwrap_or("Unknown");
+
+    let follow = matches!(phase, "Running" | "Pending");
+
+    match job_data(client, namespace, &target_job_name).await.remove("version") {
+        Some(v) if v == cli_image_tag() => eprintln!("Job image version: {}", v),
+        Some(v) => warnings::warn(format!(
+            "Job image version {} differs from local CLI {}; results may not match this CLI's behavior",
+            v,
+            cli_image_tag()
+        )),
+        None => eprintln!("Job image version: not reported (image predates version handshake or pod not started)"),
+    }
+
+    let log_params = LogParams {
+        follow,
+        ..Default::default()
+    };
+
+    let log_stream = pods_api.log_stream(&pod_name, &log_params).await?;
+    let mut lines = log_stream.lines();
+    while let Some(line) = lines.try_next().await? {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+/// Format a duration in seconds to human-readable age.
+fn format_age(seconds: i64) -> String {
+    if seconds < 60 {
+        format!("{}s", seconds)
+    } else if seconds < 3600 {
+        format!("{}m", seconds / 60)
+    } else if seconds < 86400 {
+        format!("{}h", seconds / 3600)
+    } else {
+        format!("{}d", seconds / 86400)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_builder_resolve() {
+        assert_eq!(ImageBuilder::Auto.resolve(|t| t == "docker" || t == "buildah"), ImageBuilder::Buildah);
+        assert_eq!(ImageBuilder::Auto.resolve(|_| false), ImageBuilder::Openshift);
+        assert_eq!(ImageBuilder::Docker.resolve(|_| false), ImageBuilder::Docker);
+    }
+
+    #[test]
+    fn test_job_go_env() {
+        assert_eq!(mod_cache_image_tag("release-v1.16"), format!("{}-gomod-release-v1.16", cli_image_tag()));
+        assert_eq!(mod_cache_image_tag("feature/x"), format!("{}-gomod-feature-x", cli_image_tag()));
+
+        let env = JobGoEnv { mod_cache: GoModCache::Pvc, goproxy: Some("https://proxy.corp".into()) };
+        assert_eq!(env.to_args(), ["--go-mod-cache", "pvc", "--goproxy", "https://proxy.corp"]);
+        let vars = env.env_vars();
+        let names: Vec<&str> = vars.iter().filter_map(|e| e["name"].as_str()).collect();
+        assert_eq!(names, ["GOPROXY", "GOMODCACHE", "GOCACHE", "GOFLAGS"]);
+        assert!(JobGoEnv::default().to_args().is_empty());
+        assert!(JobGoEnv { mod_cache: GoModCache::Image, goproxy: None }.env_vars().is_empty());
+    }
+
+    #[test]
+    fn test_job_profile_pod_settings() {
+        let (volumes, mounts, security) = JobProfile::RUN.pod_settings();
+        assert!(volumes.is_empty() && mounts.is_empty());
+        assert_eq!(security, serde_json::json!({}));
+
+        let konflux = JobProfile::konflux(3600, true);
+        assert_eq!(konflux.deadline_secs, KONFLUX_BUILD_DEADLINE_SECS + 3600);
+        let (volumes, mounts, security) = konflux.pod_settings();
+        assert_eq!(volumes[0]["secret"]["secretName"], REGISTRY_AUTH_SECRET);
+        assert_eq!(mounts[0]["mountPath"], REGISTRY_AUTH_MOUNT);
+        assert_eq!(security["privileged"], true);
+        assert!(JobProfile::konflux(3600, false).pod_settings().0.is_empty());
+    }
+}
@@ -0,0 +1,213 @@
+use anyhow::Context;
+use k8s_openapi::api::core::v1::Namespace;
+use kube::Resource;
+use kube::api::{Api, PostParams};
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::{audit, warnings};
+
+/// Default for --api-retries.
+pub const DEFAULT_API_RETRIES: u32 = 4;
+
+/// First retry delay; doubled on each further retry.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest wait between two attempts.
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+static API_RETRIES: OnceLock<u32> = OnceLock::new();
+
+/// Set how often transient Kubernetes API failures are retried (--api-retries).
+pub fn init(retries: u32) {
+    let _ = API_RETRIES.set(retries);
+}
+
+/// Configured --api-retries.
+pub fn api_retries() -> u32 {
+    API_RETRIES.get().copied().unwrap_or(DEFAULT_API_RETRIES)
+}
+
+/// Client for the default kubeconfig/in-cluster config. Under `--record` it
+/// records its exchanges; under `--replay` it is served from the cassette.
+pub async fn client() -> kube::Result<kube::Client> {
+    crate::cassette::client().await
+}
+
+/// Creates a kube client using the default kubeconfig/in-cluster config.
+/// Returns both the tokio Runtime (needed for subsequent async calls) and the Client.
+pub fn create_kube_client() -> anyhow::Result<(tokio::runtime::Runtime, kube::Client)> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to create tokio runtime")?;
+
+    let client = rt
+        .block_on(client())
+        .context("Failed to connect to cluster. Are you logged in? Try: oc login")?;
+
+    Ok((rt, client))
+}
+
+/// Run the API call made by `call`, retrying transient failures (throttling,
+/// 5xx during etcd leader elections, dropped connections) with exponential
+/// backoff and jitter. Other errors are returned at once.
+pub async fn retry<T, F, Fut>(what: &str, call: F) -> kube::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = kube::Result<T>>,
+{
+    retry_with(what, api_retries(), call).await
+}
+
+async fn retry_with<T, F, Fut>(what: &str, retries: u32, mut call: F) -> kube::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = kube::Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match call().await {
+            Err(e) if attempt < retries && is_transient(&e) => {
+                let delay = retry_delay(attempt, retry_after(&e), jitter_seed());
+                attempt += 1;
+                // Same message for every attempt, so repeated failures are deduplicated
+                warnings::warn(format!("{} failed ({}); retrying up to {} times", what, short_error(&e), retries));
+                tokio::time::sleep(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// `retry` for the blocking modules that drive their own runtime.
+pub fn block_on_retry<T, F, Fut>(rt: &tokio::runtime::Runtime, what: &str, call: F) -> kube::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = kube::Result<T>>,
+{
+    rt.block_on(retry(what, call))
+}
+
+/// Create `object` through `api` unless one of that name already exists. The
+/// creation is recorded in the audit log.
+pub fn create_if_missing<K>(rt: &tokio::runtime::Runtime, api: Api<K>, object: &K) -> anyhow::Result<()>
+where
+    K: Resource<DynamicType = ()> + Clone + DeserializeOwned + serde::Serialize + std::fmt::Debug,
+{
+    let kind = K::kind(&());
+    let name = object.meta().name.clone().unwrap_or_default();
+    let namespace = api.namespace().map(str::to_string);
+    if block_on_retry(rt, &format!("get {}", kind), || api.get_opt(&name))?.is_some() {
+        return Ok(());
+    }
+    let pp = PostParams::default();
+    let result = block_on_retry(rt, &format!("create {}", kind), || api.create(&pp, object));
+    audit::record_api("create", &kind, namespace.as_deref(), &name, result.is_ok());
+    result.with_context(|| format!("Failed to create {} {}", kind, name))?;
+    Ok(())
+}
+
+/// Create the namespace `name` of a probe or smoke test, labelled
+/// `app=streamstress`, unless it exists.
+pub fn ensure_namespace(rt: &tokio::runtime::Runtime, client: &kube::Client, name: &str) -> anyhow::Result<()> {
+    let ns: Namespace = serde_json::from_value(serde_json::json!({
+        "metadata": {"name": name, "labels": {"app": "streamstress"}}
+    }))?;
+    create_if_missing(rt, Api::all(client.clone()), &ns)
+}
+
+/// Whether retrying `err` may succeed: throttling, server-side 5xx, or a
+/// failure to reach the API server at all.
+pub fn is_transient(err: &kube::Error) -> bool {
+    match err {
+        kube::Error::Api(status) => matches!(status.code, 429 | 500 | 502 | 503 | 504),
+        kube::Error::HyperError(_) | kube::Error::Service(_) => true,
+        _ => false,
+    }
+}
+
+/// Delay the server asked for (Retry-After on 429s), if any.
+fn retry_after(err: &kube::Error) -> Option<Duration> {
+    match err {
+        kube::Error::Api(status) => status
+            .details
+            .as_ref()
+            .map(|d| d.retry_after_seconds)
+            .filter(|s| *s > 0)
+            .map(|s| Duration::from_secs(s as u64)),
+        _ => None,
+    }
+}
+
+/// Exponential delay for retry `attempt` (0-based), capped, with "equal jitter":
+/// between half and all of the step, picked by `seed`. A server-requested
+/// delay is used as the floor.
+fn retry_delay(attempt: u32, server: Option<Duration>, seed: u64) -> Duration {
+    let step = RETRY_BASE_DELAY.saturating_mul(1 << attempt.min(16)).min(RETRY_MAX_DELAY);
+    let half = step / 2;
+    let jitter = Duration::from_millis(seed % (half.as_millis() as u64 + 1));
+    (half + jitter).max(server.unwrap_or_default())
+}
+
+fn jitter_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64 ^ std::process::id() as u64)
+        .unwrap_or_default()
+}
+
+fn short_error(err: &kube::Error) -> String {
+    match err {
+        kube::Error::Api(status) => format!("HTTP {} {}", status.code, status.reason),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_error(code: u16) -> kube::Error {
+        kube::Error::Api(Box::new(kube::core::Status::failure("boom", "Reason").with_code(code)))
+    }
+
+    #[test]
+    fn test_is_transient() {
+        assert!(is_transient(&api_error(429)));
+        assert!(is_transient(&api_error(503)));
+        assert!(!is_transient(&api_error(404)));
+        assert!(!is_transient(&api_error(409)));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(0, None, 0), Duration::from_millis(250));
+        assert_eq!(retry_delay(0, None, 250), Duration::from_millis(500));
+        assert_eq!(retry_delay(3, None, 0), Duration::from_secs(2));
+        assert_eq!(retry_delay(20, None, 0), RETRY_MAX_DELAY / 2);
+        assert_eq!(retry_delay(0, Some(Duration::from_secs(5)), 0), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_retry_gives_up_after_configured_retries() {
+        let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let mut calls = 0;
+        let result: kube::Result<()> = rt.block_on(retry_with("get thing", 1, || {
+            calls += 1;
+            async { Err(api_error(503)) }
+        }));
+        assert!(result.is_err());
+        assert_eq!(calls, 2);
+
+        let mut calls = 0;
+        let result: kube::Result<()> = rt.block_on(retry_with("get thing", 1, || {
+            calls += 1;
+            async { Err(api_error(404)) }
+        }));
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+}
@@ -0,0 +1,252 @@
+//! ko invocation: command-line flags and .ko.yaml generation from the
+//! per-component `[<component>.ko]` config table.
+
+use anyhow::{Context, Result};
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::path::Path;
+
+use crate::config::KoConfig;
+
+/// Flags every build gets, matching the image names the deploy phase expects.
+const BASE_ARGS: &[&str] = &["build", "--base-import-paths", "--sbom=none"];
+
+/// Substitute `{component}`, `{ref}` and `{date}` in tag templates. Characters that
+/// are not valid in an image tag (e.g. `/` in `pr/123`) become `-`.
+pub fn render_tags(tags: &[String], component: &str, git_ref: Option<&str>, date: &str) -> Vec<String> {
+    let git_ref = git_ref.unwrap_or("HEAD");
+    tags.iter()
+        .map(|t| {
+            t.replace("{component}", component)
+                .replace("{ref}", git_ref)
+                .replace("{date}", date)
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-') { c } else { '-' })
+                .collect()
+        })
+        .collect()
+}
+
+impl KoConfig {
+    /// Copy of this config with tag templates rendered for one build.
+    pub fn for_build(&self, component: &str, git_ref: Option<&str>) -> KoConfig {
+        let date = chrono::Utc::now().format("%Y%m%d").to_string();
+        KoConfig {
+            tags: render_tags(&self.tags, component, git_ref, &date),
+            ..self.clone()
+        }
+    }
+
+    fn needs_ko_yaml(&self) -> bool {
+        self.base_image.is_some()
+            || !self.base_image_overrides.is_empty()
+            || !self.flags.is_empty()
+            || !self.ldflags.is_empty()
+            || !self.env.is_empty()
+    }
+}
+
+/// Arguments for `ko build`: the fixed flags, configured tags, platforms and
+/// passthrough args, then the import paths.
+pub fn build_args(ko: &KoConfig, image_refs_path: &str, import_paths: &[String]) -> Vec<String> {
+    let mut args: Vec<String> = BASE_ARGS.iter().map(|s| s.to_string()).collect();
+    args.push("--image-refs".to_string());
+    args.push(image_refs_path.to_string());
+    if !ko.tags.is_empty() {
+        args.push(format!("--tags={}", ko.tags.join(",")));
+    }
+    if !ko.platforms.is_empty() {
+        args.push(format!("--platform={}", ko.platforms.join(",")));
+    }
+    args.extend(ko.extra_args.iter().cloned());
+    args.extend(import_paths.iter().cloned());
+    args
+}
+
+/// Write base image and build settings into `source_dir/.ko.yaml`, merging with
+/// the upstream file if there is one. Does nothing when no such options are set.
+pub fn write_ko_yaml(source_dir: &Path, ko: &KoConfig, import_paths: &[String]) -> Result<()> {
+    if !ko.needs_ko_yaml() {
+        return Ok(());
+    }
+    let path = source_dir.join(".ko.yaml");
+    let existing = if path.exists() {
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?
+    } else {
+        String::new()
+    };
+    let module = module_path(source_dir);
+    let merged = merge_ko_yaml(&existing, ko, import_paths, module.as_deref())?;
+    fs::write(&path, merged).with_context(|| format!("Failed to write {}", path.display()))?;
+    eprintln!("  Wrote {} from component ko config", path.display());
+    Ok(())
+}
+
+/// Go module path from `source_dir/go.mod`.
+fn module_path(source_dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(source_dir.join("go.mod")).ok()?;
+    content
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("module "))
+        .map(|m| m.trim().trim_matches('"').to_string())
+}
+
+/// Full import path for a `./cmd/x` style path, as .ko.yaml's baseImageOverrides expects.
+fn full_import_path(path: &str, module: Option<&str>) -> String {
+    match (path.strip_prefix("./"), module) {
+        (Some(rel), Some(m)) => format!("{}/{}", m, rel),
+        _ => path.to_string(),
+    }
+}
+
+fn merge_ko_yaml(existing: &str, ko: &KoConfig, import_paths: &[String], module: Option<&str>) -> Result<String> {
+    let mut root = if existing.trim().is_empty() {
+        Mapping::new()
+    } else {
+        match serde_yaml::from_str::<Value>(existing).context("Failed to parse upstream .ko.yaml")? {
+            Value::Mapping(m) => m,
+            _ => Mapping::new(),
+        }
+    };
+
+    if let Some(ref base) = ko.base_image {
+        root.insert("defaultBaseImage".into(), base.clone().into());
+    }
+
+    if !ko.base_image_overrides.is_empty() {
+        let overrides = root
+            .entry("baseImageOverrides".into())
+            .or_insert_with(|| Value::Mapping(Mapping::new()));
+        if let Value::Mapping(map) = overrides {
+            let mut keys: Vec<&String> = ko.base_image_overrides.keys().collect();
+            keys.sort();
+            for key in keys {
+                map.insert(full_import_path(key, module).into(), ko.base_image_overrides[key].clone().into());
+            }
+        }
+    }
+
+    if !ko.flags.is_empty() || !ko.ldflags.is_empty() || !ko.env.is_empty() {
+        let builds = root
+            .entry("builds".into())
+            .or_insert_with(|| Value::Sequence(Vec::new()));
+        if let Value::Sequence(list) = builds {
+            for path in import_paths {
+                let idx = list
+                    .iter()
+                    .position(|b| b.get("main").and_then(|m| m.as_str()) == Some(path.as_str()));
+                let idx = match idx {
+                    Some(i) => i,
+                    None => {
+                        let mut entry = Mapping::new();
+                        let id = path.rsplit('/').next().unwrap_or(path);
+                        entry.insert("id".into(), id.into());
+                        entry.insert("main".into(), path.as_str().into());
+                        list.push(Value::Mapping(entry));
+                        list.len() - 1
+                    }
+                };
+                if let Value::Mapping(entry) = &mut list[idx] {
+                    append_list(entry, "flags", &ko.flags);
+                    append_list(entry, "ldflags", &ko.ldflags);
+                    append_list(entry, "env", &ko.env);
+                }
+            }
+        }
+    }
+
+    serde_yaml::to_string(&Value::Mapping(root)).context("Failed to serialize .ko.yaml")
+}
+
+/// Append `values` to the list at `key`, keeping upstream entries first.
+fn append_list(entry: &mut Mapping, key: &str, values: &[String]) {
+    if values.is_empty() {
+        return;
+    }
+    let list = entry
+        .entry(key.into())
+        .or_insert_with(|| Value::Sequence(Vec::new()));
+    if let Value::Sequence(items) = list {
+        items.extend(values.iter().map(|v| Value::from(v.as_str())));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_render_tags() {
+        let tags = paths(&["{component}-{date}", "{ref}"]);
+        assert_eq!(
+            render_tags(&tags, "pipeline", Some("pr/123"), "20250205"),
+            vec!["pipeline-20250205", "pr-123"]
+        );
+        assert_eq!(render_tags(&paths(&["{ref}"]), "pipeline", None, "20250205"), vec!["HEAD"]);
+    }
+
+    #[test]
+    fn test_build_args() {
+        let ko = KoConfig {
+            tags: paths(&["latest", "nightly"]),
+            platforms: paths(&["linux/amd64", "linux/arm64"]),
+            extra_args: paths(&["--sbom=spdx"]),
+            ..Default::default()
+        };
+        let args = build_args(&ko, "/tmp/refs", &paths(&["./cmd/controller"]));
+        assert_eq!(
+            args,
+            paths(&[
+                "build",
+                "--base-import-paths",
+                "--sbom=none",
+                "--image-refs",
+                "/tmp/refs",
+                "--tags=latest,nightly",
+                "--platform=linux/amd64,linux/arm64",
+                "--sbom=spdx",
+                "./cmd/controller",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_merge_ko_yaml_with_upstream() {
+        let upstream = "defaultBaseImage: cgr.dev/chainguard/static\nbuilds:\n- id: controller\n  main: ./cmd/controller\n  ldflags:\n  - -X main.version=devel\n";
+        let ko = KoConfig {
+            base_image: Some("registry.access.redhat.com/ubi9/ubi-minimal".to_string()),
+            base_image_overrides: [("./cmd/nop".to_string(), "scratch".to_string())].into(),
+            ldflags: paths(&["-s", "-w"]),
+            ..Default::default()
+        };
+        let merged = merge_ko_yaml(
+            upstream,
+            &ko,
+            &paths(&["./cmd/controller", "./cmd/webhook"]),
+            Some("github.com/tektoncd/pipeline"),
+        )
+        .unwrap();
+        let v: Value = serde_yaml::from_str(&merged).unwrap();
+
+        assert_eq!(v["defaultBaseImage"], "registry.access.redhat.com/ubi9/ubi-minimal");
+        assert_eq!(v["baseImageOverrides"]["github.com/tektoncd/pipeline/cmd/nop"], "scratch");
+        let builds = v["builds"].as_sequence().unwrap();
+        assert_eq!(builds.len(), 2);
+        let ldflags: Vec<&str> = builds[0]["ldflags"].as_sequence().unwrap().iter().filter_map(|f| f.as_str()).collect();
+        assert_eq!(ldflags, vec!["-X main.version=devel", "-s", "-w"]);
+        assert_eq!(builds[1]["id"], "webhook");
+        assert_eq!(builds[1]["main"], "./cmd/webhook");
+    }
+
+    #[test]
+    fn test_write_ko_yaml_noop_without_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let ko = KoConfig { tags: paths(&["latest"]), ..Default::default() };
+        write_ko_yaml(dir.path(), &ko, &paths(&["./cmd/controller"])).unwrap();
+        assert!(!dir.path().join(".ko.yaml").exists());
+    }
+}
@@ -0,0 +1,974 @@
+use anyhow::{Context, Result};
+use serde::{Serialize, Serializer};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::config::{self, KonfluxConfig};
+use crate::exec;
+use crate::results;
+use crate::warnings;
+
+/// Result of a completed PipelineRun.
+#[derive(Debug, Serialize)]
+pub struct PipelineRunResult {
+    pub name: String,
+    pub status: PipelineRunStatus,
+    #[serde(rename = "duration_secs", serialize_with = "serialize_secs")]
+    pub duration: Duration,
+    pub reason: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub enum PipelineRunStatus {
+    Succeeded,
+    Failed,
+    Timeout,
+}
+
+fn serialize_secs<S: Serializer>(d: &Duration, s: S) -> std::result::Result<S::Ok, S::Error> {
+    s.serialize_u64(d.as_secs())
+}
+
+/// Per-suite test counts from a PipelineRun.
+#[derive(Debug, Serialize)]
+pub struct SuiteSummary {
+    pub suite: String,
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+}
+
+/// Status and timing of one TaskRun of a PipelineRun.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaskTiming {
+    /// Pipeline task name (e.g. "e2e-test-pipelines")
+    pub task: String,
+    pub task_run: String,
+    /// Succeeded, Failed, Running or Pending
+    pub status: String,
+    pub reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+}
+
+/// Outcome of the `konflux` command, for `--output json|yaml`.
+#[derive(Debug, Serialize)]
+pub struct KonfluxReport {
+    pub snapshot: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub catalog_smoke: Option<crate::catalogsmoke::SmokeResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pipeline_run: Option<PipelineRunResult>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub task_timings: Vec<TaskTiming>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suites: Vec<SuiteSummary>,
+}
+
+/// EaaS-related task names that should be removed for standalone execution.
+const EAAS_TASKS: &[&str] = &[
+    "provision-eaas-space",
+    "provision-cluster",
+    "eaas-provision-space",
+    "eaas-provision-cluster",
+];
+
+/// Read the release-test-pipeline.yaml from the cloned operator repo.
+pub fn fetch_pipeline_yaml(operator_dir: &Path) -> Result<String> {
+    let pipeline_path = operator_dir.join(".konflux/tekton/release-test-pipeline.yaml");
+    std::fs::read_to_string(&pipeline_path).with_context(|| {
+        format!(
+            "Failed to read pipeline YAML at {}",
+            pipeline_path.display()
+        )
+    })
+}
+
+/// The standalone pipeline and a line per change made to the original, for
+/// `konflux render`.
+#[derive(Debug, Serialize)]
+pub struct StandalonePipeline {
+    pub changes: Vec<String>,
+    pub pipeline: serde_json::Value,
+}
+
+/// The standalone variant of the operator checkout's pipeline, or of `file`
+/// (`konflux render`).
+pub fn render(operator_dir: &Path, file: Option<&str>) -> Result<StandalonePipeline> {
+    let yaml = match file {
+        Some(file) => std::fs::read_to_string(file).with_context(|| format!("Failed to read {file}"))?,
+        None => fetch_pipeline_yaml(operator_dir)?,
+    };
+    transform_pipeline(&yaml)
+}
+
+/// Modify the pipeline YAML to create a standalone variant.
+///
+/// Removes EaaS provisioning tasks and adjusts references so the pipeline
+/// runs directly on the user's current cluster. Adds an INDEX_IMAGE pipeline
+/// param that deploy-operator reads instead of EaaS task results.
+pub fn create_standalone_pipeline(pipeline_yaml: &str) -> Result<String> {
+    let standalone = transform_pipeline(pipeline_yaml)?;
+    serde_yaml::to_string(&standalone.pipeline).context("Failed to serialize standalone pipeline YAML")
+}
+
+/// Apply the standalone transformations to the parsed pipeline, in order.
+pub fn transform_pipeline(pipeline_yaml: &str) -> Result<StandalonePipeline> {
+    let mut doc: serde_json::Value =
+        serde_yaml::from_str(pipeline_yaml).context("Failed to parse pipeline YAML")?;
+    if doc["kind"] != "Pipeline" {
+        anyhow::bail!("Expected a Tekton Pipeline, got kind {}", doc["kind"]);
+    }
+
+    let mut changes = Vec::new();
+    rename_pipeline(&mut doc, &mut changes);
+    add_index_image_param(&mut doc, &mut changes);
+    remove_eaas_tasks(&mut doc, &mut changes);
+    remove_eaas_run_after(&mut doc, &mut changes);
+    replace_eaas_references(&mut doc, &mut changes);
+    Ok(StandalonePipeline { changes, pipeline: doc })
+}
+
+/// Call `f` with each pipeline task in `spec.tasks` and `spec.finally`.
+fn for_each_task(doc: &mut serde_json::Value, mut f: impl FnMut(&str, &mut Vec<serde_json::Value>)) {
+    for section in ["tasks", "finally"] {
+        if let Some(tasks) = doc.pointer_mut(&format!("/spec/{section}")).and_then(|t| t.as_array_mut()) {
+            f(section, tasks);
+        }
+    }
+}
+
+fn is_eaas_task(name: &str) -> bool {
+    EAAS_TASKS.iter().any(|eaas| name.contains(eaas))
+}
+
+/// Rename the pipeline to avoid conflicts with the original.
+fn rename_pipeline(doc: &mut serde_json::Value, changes: &mut Vec<String>) {
+    let orig = doc["metadata"]["name"].as_str().unwrap_or("release-test-pipeline").to_string();
+    let renamed = format!("{}-standalone", orig);
+    changes.push(format!("Renamed the pipeline to {}", renamed));
+    doc["metadata"]["name"] = serde_json::json!(renamed);
+}
+
+fn add_index_image_param(doc: &mut serde_json::Value, changes: &mut Vec<String>) {
+    let Some(spec) = doc.get_mut("spec").and_then(|s| s.as_object_mut()) else { return };
+    let Some(params) = spec.entry("params").or_insert_with(|| serde_json::json!([])).as_array_mut() else { return };
+    if params.iter().any(|p| p["name"] == "INDEX_IMAGE") {
+        return;
+    }
+    params.push(serde_json::json!({
+        "name": "INDEX_IMAGE",
+        "type": "string",
+        "description": "FBC index image containing the operator bundle"
+    }));
+    changes.push("Added pipeline param INDEX_IMAGE".to_string());
+}
+
+/// Remove the EaaS tasks from `spec.tasks` and `spec.finally`.
+fn remove_eaas_tasks(doc: &mut serde_json::Value, changes: &mut Vec<String>) {
+    for_each_task(doc, |section, tasks| {
+        tasks.retain(|task| {
+            let name = task["name"].as_str().unwrap_or("");
+            if is_eaas_task(name) {
+                changes.push(format!("Removed EaaS task {} from spec.{}", name, section));
+                return false;
+            }
+            true
+        });
+    });
+}
+
+/// Remove runAfter references to EaaS tasks from all pipeline tasks.
+fn remove_eaas_run_after(doc: &mut serde_json::Value, changes: &mut Vec<String>) {
+    for_each_task(doc, |_, tasks| {
+        for task in tasks.iter_mut() {
+            let name = task["name"].as_str().unwrap_or("").to_string();
+            let Some(run_after) = task.get_mut("runAfter").and_then(|r| r.as_array_mut()) else { continue };
+            run_after.retain(|v| {
+                let after = v.as_str().unwrap_or("");
+                if is_eaas_task(after) {
+                    changes.push(format!("{}: dropped runAfter {}", name, after));
+                    return false;
+                }
+                true
+            });
+            if run_after.is_empty() {
+                task.as_object_mut().map(|t| t.remove("runAfter"));
+            }
+        }
+    });
+}
+
+/// Replace references to EaaS task results with values for the current cluster:
+/// `clusterName` becomes "standalone-cluster", anything else (e.g. the kubeconfig
+/// `secretRef`) the empty string.
+fn replace_eaas_references(doc: &mut serde_json::Value, changes: &mut Vec<String>) {
+    let alternatives = EAAS_TASKS.iter().map(|t| regex::escape(t)).collect::<Vec<_>>().join("|");
+    let re = regex::Regex::new(&format!(r"\$\(tasks\.({alternatives})\.results\.([^)\s]+)\)"))
+        .expect("valid EaaS reference regex");
+    for_each_task(doc, |_, tasks| {
+        for task in tasks.iter_mut() {
+            let name = task["name"].as_str().unwrap_or("").to_string();
+            replace_in_strings(task, &mut |value| {
+                if !re.is_match(value) {
+                    return None;
+                }
+                let replaced = re
+                    .replace_all(value, |caps: &regex::Captures| {
+                        if &caps[2] == "clusterName" { "standalone-cluster" } else { "" }
+                    })
+                    .to_string();
+                changes.push(format!("{}: {:?} -> {:?}", name, value, replaced));
+                Some(replaced)
+            });
+        }
+    });
+}
+
+/// Apply `replace` to every string in `value`, keys excluded.
+fn replace_in_strings(value: &mut serde_json::Value, replace: &mut impl FnMut(&str) -> Option<String>) {
+    match value {
+        serde_json::Value::String(s) => {
+            if let Some(new) = replace(s) {
+                *s = new;
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(|v| replace_in_strings(v, replace)),
+        serde_json::Value::Object(map) => map.values_mut().for_each(|v| replace_in_strings(v, replace)),
+        _ => {}
+    }
+}
+
+/// Orchestrate the full pipeline trigger flow.
+///
+/// 1. Read SNAPSHOT JSON, extract the FBC index containerImage.
+/// 2. Fetch and create standalone pipeline YAML.
+/// 3. Apply the Pipeline to the cluster namespace.
+/// 4. Create and apply a PipelineRun with the SNAPSHOT and INDEX_IMAGE params.
+/// 5. Return the PipelineRun name.
+pub fn trigger_pipeline(
+    snapshot_path: &Path,
+    operator_dir: &Path,
+    namespace: &str,
+) -> Result<String> {
+    // 1. Read SNAPSHOT and extract index image
+    let snapshot_json = std::fs::read_to_string(snapshot_path)
+        .with_context(|| format!("Failed to read snapshot at {}", snapshot_path.display()))?;
+    let snapshot: serde_json::Value =
+        serde_json::from_str(&snapshot_json).context("Failed to parse SNAPSHOT JSON")?;
+
+    let index_image = extract_index_image(&snapshot)?;
+    eprintln!("Index image from SNAPSHOT: {}", index_image);
+
+    // 2. Fetch and create standalone pipeline
+    let raw_yaml = fetch_pipeline_yaml(operator_dir)?;
+    let standalone_yaml = create_standalone_pipeline(&raw_yaml)?;
+
+    // 3. Apply Pipeline to cluster
+    let pipeline_file = tempfile::NamedTempFile::new().context("Failed to create temp file")?;
+    std::fs::write(pipeline_file.path(), &standalone_yaml)?;
+
+    eprintln!("Applying standalone pipeline to namespace {}...", namespace);
+    exec::run_cmd(
+        "oc",
+        &[
+            "apply",
+            "-f",
+            pipeline_file.path().to_str().unwrap(),
+            "-n",
+            namespace,
+        ],
+    )?;
+
+    // 4. Create PipelineRun
+    let pipelinerun_name = format!(
+        "streamstress-test-{}",
+        crate::timestamp::unix_now()
+    );
+
+    let pipelinerun_yaml = create_pipelinerun_yaml(
+        &pipelinerun_name,
+        &snapshot_json,
+        &index_image,
+        namespace,
+    );
+
+    let pr_file = tempfile::NamedTempFile::new().context("Failed to create temp file")?;
+    std::fs::write(pr_file.path(), &pipelinerun_yaml)?;
+
+    eprintln!("Creating PipelineRun {}...", pipelinerun_name);
+    exec::run_cmd(
+        "oc",
+        &[
+            "apply",
+            "-f",
+            pr_file.path().to_str().unwrap(),
+            "-n",
+            namespace,
+        ],
+    )?;
+
+    Ok(pipelinerun_name)
+}
+
+/// Extract the FBC index image from the SNAPSHOT JSON.
+///
+/// The SNAPSHOT has a `components` array; we look for one whose name contains
+/// "fbc" or "index" and return its `containerImage`.
+fn extract_index_image(snapshot: &serde_json::Value) -> Result<String> {
+    let components = snapshot
+        .get("components")
+        .and_then(|c| c.as_array())
+        .ok_or_else(|| anyhow::anyhow!("SNAPSHOT JSON missing 'components' array"))?;
+
+    // Look for the FBC index component
+    for comp in components {
+        let name = comp.get("name").and_then(|n| n.as_str()).unwrap_or("");
+        if (name.contains("fbc") || name.contains("index"))
+            && let Some(image) = comp.get("containerImage").and_then(|i| i.as_str())
+        {
+            return Ok(image.to_string());
+        }
+    }
+
+    // Fallback: use the last component's image (index is typically built last)
+    if let Some(last) = components.last()
+        && let Some(image) = last.get("containerImage").and_then(|i| i.as_str())
+    {
+        return Ok(image.to_string());
+    }
+
+    anyhow::bail!("No FBC index image found in SNAPSHOT components")
+}
+
+/// Generate PipelineRun YAML for the standalone pipeline.
+fn create_pipelinerun_yaml(
+    name: &str,
+    snapshot_json: &str,
+    index_image: &str,
+    namespace: &str,
+) -> String {
+    let pr = serde_json::json!({
+        "apiVersion": "tekton.dev/v1",
+        "kind": "PipelineRun",
+        "metadata": {
+            "name": name,
+            "namespace": namespace,
+        },
+        "spec": {
+            "pipelineRef": {
+                "name": "release-test-pipeline-standalone",
+            },
+            "params": [
+                {
+                    "name": "SNAPSHOT",
+                    "value": snapshot_json,
+                },
+                {
+                    "name": "INDEX_IMAGE",
+                    "value": index_image,
+                },
+            ],
+            "timeouts": {
+                "pipeline": "1h30m0s",
+            },
+        },
+    });
+
+    serde_yaml::to_string(&pr).unwrap_or_default()
+}
+
+/// Poll a PipelineRun until completion or timeout.
+///
+/// Checks `oc get pipelinerun` status every 30 seconds. Default timeout is 60 minutes
+/// (gauge tests typically take ~45 min).
+pub fn wait_for_pipeline(
+    pipelinerun_name: &str,
+    namespace: &str,
+    timeout_secs: u64,
+) -> Result<PipelineRunResult> {
+    let start = Instant::now();
+    let timeout = Duration::from_secs(timeout_secs);
+    let poll_interval = Duration::from_secs(30);
+
+    eprintln!(
+        "Waiting for PipelineRun {} (timeout: {}m)...",
+        pipelinerun_name,
+        timeout_secs / 60
+    );
+
+    loop {
+        if start.elapsed() > timeout {
+            return Ok(PipelineRunResult {
+                name: pipelinerun_name.to_string(),
+                status: PipelineRunStatus::Timeout,
+                duration: start.elapsed(),
+                reason: "Timeout".to_string(),
+            });
+        }
+
+        let result = exec::run_cmd_unchecked(
+            "oc",
+            &[
+                "get",
+                "pipelinerun",
+                pipelinerun_name,
+                "-n",
+                namespace,
+                "-o",
+                "jsonpath={.status.conditions[0].reason}",
+            ],
+        )?;
+
+        let reason = result.stdout.trim().to_string();
+
+        match reason.as_str() {
+            "Succeeded" => {
+                eprintln!("PipelineRun {} succeeded!", pipelinerun_name);
+                return Ok(PipelineRunResult {
+                    name: pipelinerun_name.to_string(),
+                    status: PipelineRunStatus::Succeeded,
+                    duration: start.elapsed(),
+                    reason,
+                });
+            }
+            "Failed" | "PipelineRunTimeout" | "CouldntGetPipeline" => {
+                eprintln!("PipelineRun {} failed: {}", pipelinerun_name, reason);
+                return Ok(PipelineRunResult {
+                    name: pipelinerun_name.to_string(),
+                    status: PipelineRunStatus::Failed,
+                    duration: start.elapsed(),
+                    reason,
+                });
+            }
+            _ => {
+                // Still running (Running, Started, etc.)
+                let elapsed_min = start.elapsed().as_secs() / 60;
+                eprintln!(
+                    "  [{}m] PipelineRun status: {}",
+                    elapsed_min,
+                    if reason.is_empty() { "Pending" } else { &reason }
+                );
+            }
+        }
+
+        std::thread::sleep(poll_interval);
+    }
+}
+
+/// Collect test results from PipelineRun task logs.
+///
+/// For each e2e test task in `cfg`, fetches the step container's logs and parses
+/// gauge stdout using the existing `results::parse_gauge_stdout` parser. With
+/// `discover_tasks`, the PipelineRun's other tasks are scanned for gauge output
+/// too, so a renamed task is collected (with a warning) instead of skipped.
+pub fn collect_results(
+    pipelinerun_name: &str,
+    namespace: &str,
+    output_dir: &Path,
+    cfg: &KonfluxConfig,
+) -> Result<Vec<results::TestRunResult>> {
+    let logs_dir = output_dir.join("logs");
+    std::fs::create_dir_all(&logs_dir)?;
+
+    let task_pods = match task_pods(pipelinerun_name, namespace) {
+        Ok(pods) => pods,
+        Err(e) => {
+            warnings::warn(format!("Could not list the tasks of PipelineRun {}: {e:#}", pipelinerun_name));
+            Vec::new()
+        }
+    };
+
+    let mut all_results = Vec::new();
+
+    for task_name in &cfg.e2e_tasks {
+        if !task_pods.is_empty() && !task_pods.iter().any(|(task, _)| task == task_name) {
+            warnings::warn(format!(
+                "E2E task {} is not in PipelineRun {}; was it renamed? (e2e_tasks in {})",
+                task_name,
+                pipelinerun_name,
+                config::default_konflux_config_path().display()
+            ));
+            continue;
+        }
+        let pod_name = format!("{}-{}-pod", pipelinerun_name, task_name);
+
+        eprintln!("Collecting results from task: {} (pod: {})", task_name, pod_name);
+
+        // Fetch task logs from the step container
+        let log_result = exec::run_cmd_unchecked(
+            "oc",
+            &[
+                "logs",
+                "-n", namespace,
+                &pod_name,
+                "-c", &cfg.e2e_step_container,
+            ],
+        );
+
+        let log_output = match log_result {
+            Ok(ref r) if r.exit_code == 0 && !r.stdout.is_empty() => r.stdout.clone(),
+            Ok(ref r) => {
+                // Try alternative pod naming: pipelinerun-taskname-random-pod
+                eprintln!(
+                    "  Could not get logs for {} (exit={}), trying label selector...",
+                    pod_name, r.exit_code
+                );
+                match get_task_logs_by_label(pipelinerun_name, task_name, namespace, &cfg.e2e_step_container) {
+                    Ok(logs) => logs,
+                    Err(e) => {
+                        warnings::warn(format!("Skipping {}: {}", task_name, e));
+                        continue;
+                    }
+                }
+            }
+            Err(e) => {
+                warnings::warn(format!("Skipping {}: {}", task_name, e));
+                continue;
+            }
+        };
+
+        if let Some(run_result) = parse_task_log(task_name, &log_output, &logs_dir)? {
+            all_results.push(run_result);
+        }
+    }
+
+    if cfg.discover_tasks {
+        for (task_name, pod_name) in task_pods.iter().filter(|(task, _)| !cfg.e2e_tasks.contains(task)) {
+            let log_result = exec::run_cmd_unchecked("oc", &["logs", "-n", namespace, pod_name, "--all-containers"]);
+            let Ok(r) = log_result else { continue };
+            if r.exit_code != 0 || !looks_like_gauge(&r.stdout) {
+                continue;
+            }
+            warnings::warn(format!(
+                "Task {} has gauge output but is not in e2e_tasks of {}; collected it anyway",
+                task_name,
+                config::default_konflux_config_path().display()
+            ));
+            if let Some(run_result) = parse_task_log(task_name, &r.stdout, &logs_dir)? {
+                all_results.push(run_result);
+            }
+        }
+    }
+
+    if all_results.is_empty() {
+        warnings::warn(format!(
+            "No test results collected from PipelineRun {}: none of its tasks had gauge output in the expected place. \
+             Check e2e_tasks and e2e_step_container in {} against the pipeline.",
+            pipelinerun_name,
+            config::default_konflux_config_path().display()
+        ));
+    }
+
+    Ok(all_results)
+}
+
+/// Save a task's log and parse its gauge output; None (with a warning) if it has none.
+fn parse_task_log(task_name: &str, log_output: &str, logs_dir: &Path) -> Result<Option<results::TestRunResult>> {
+    // Save raw log for reference
+    let log_file = logs_dir.join(format!("{}.log", task_name));
+    std::fs::write(&log_file, log_output)?;
+
+    // Parse gauge stdout using existing parser
+    match results::parse_gauge_stdout_str(log_output) {
+        Ok(mut run_result) => {
+            run_result.source = Some(format!("konflux-pipeline:{}", task_name));
+            eprintln!(
+                "  {} -- {}/{} passed, {} failed",
+                task_name, run_result.passed, run_result.total, run_result.failed
+            );
+            Ok(Some(run_result))
+        }
+        Err(e) => {
+            warnings::warn(format!("Failed to parse results from {}: {}", task_name, e));
+            Ok(None)
+        }
+    }
+}
+
+/// Whether a log has gauge's end-of-run summary ("Specifications: ..." and
+/// "Scenarios: ..." lines).
+fn looks_like_gauge(log: &str) -> bool {
+    let lines: Vec<String> = log.lines().map(|l| results::strip_ansi(l).trim().to_string()).collect();
+    ["Specifications:", "Scenarios:"]
+        .iter()
+        .all(|marker| lines.iter().any(|l| l.starts_with(marker) && l.contains("executed")))
+}
+
+/// The PipelineRun's TaskRuns as JSON.
+fn list_taskruns(pipelinerun_name: &str, namespace: &str) -> Result<serde_json::Value> {
+    let label = format!("tekton.dev/pipelineRun={}", pipelinerun_name);
+    let result = exec::run_cmd("oc", &["get", "taskruns", "-n", namespace, "-l", &label, "-o", "json"])?;
+    serde_json::from_str(&result.stdout).context("Failed to parse the PipelineRun's TaskRuns")
+}
+
+/// Pipeline task name and pod of each TaskRun of the PipelineRun that has a pod.
+fn task_pods(pipelinerun_name: &str, namespace: &str) -> Result<Vec<(String, String)>> {
+    let list = list_taskruns(pipelinerun_name, namespace)?;
+    Ok(list["items"]
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|tr| {
+                    let task = tr["metadata"]["labels"]["tekton.dev/pipelineTask"].as_str()?;
+                    let pod = tr["status"]["podName"].as_str()?;
+                    Some((task.to_string(), pod.to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Status and duration of every TaskRun of the PipelineRun, in start order.
+pub fn collect_task_timings(pipelinerun_name: &str, namespace: &str) -> Result<Vec<TaskTiming>> {
+    Ok(task_timings(&list_taskruns(pipelinerun_name, namespace)?))
+}
+
+fn task_timings(list: &serde_json::Value) -> Vec<TaskTiming> {
+    let mut timings: Vec<TaskTiming> = list["items"]
+        .as_array()
+        .map(|items| items.iter().map(task_timing).collect())
+        .unwrap_or_default();
+    // RFC 3339 timestamps sort chronologically; unstarted tasks go last
+    timings.sort_by(|a, b| (a.start_time.is_none(), &a.start_time, &a.task).cmp(&(b.start_time.is_none(), &b.start_time, &b.task)));
+    timings
+}
+
+fn task_timing(taskrun: &serde_json::Value) -> TaskTiming {
+    let metadata = &taskrun["metadata"];
+    let status = &taskrun["status"];
+    let condition = status["conditions"]
+        .as_array()
+        .and_then(|cs| cs.iter().find(|c| c["type"] == "Succeeded"));
+    let state = match condition.and_then(|c| c["status"].as_str()) {
+        Some("True") => "Succeeded",
+        Some("False") => "Failed",
+        Some(_) => "Running",
+        None => "Pending",
+    };
+    let time = |key: &str| status[key].as_str().map(str::to_string);
+    let (start_time, completion_time) = (time("startTime"), time("completionTime"));
+    let parse = |t: &Option<String>| t.as_deref().and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok());
+    let duration_secs = match (parse(&start_time), parse(&completion_time)) {
+        (Some(start), Some(end)) => Some((end - start).num_milliseconds() as f64 / 1000.0),
+        _ => None,
+    };
+    let name = metadata["name"].as_str().unwrap_or_default().to_string();
+    TaskTiming {
+        task: metadata["labels"]["tekton.dev/pipelineTask"].as_str().map(str::to_string).unwrap_or_else(|| name.clone()),
+        task_run: name,
+        status: state.to_string(),
+        reason: condition.and_then(|c| c["reason"].as_str()).unwrap_or_default().to_string(),
+        start_time,
+        completion_time,
+        duration_secs,
+    }
+}
+
+/// Write the task timings to output_dir/results/konflux-task-timings.json,
+/// which `publish` includes in the run. Returns the path.
+pub fn save_task_timings(timings: &[TaskTiming], output_dir: &Path) -> Result<PathBuf> {
+    let results_dir = output_dir.join("results");
+    std::fs::create_dir_all(&results_dir)?;
+    let path = results_dir.join("konflux-task-timings.json");
+    std::fs::write(&path, serde_json::to_string_pretty(timings)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Print the task timings, in start order, to stdout.
+pub fn print_task_timings(timings: &[TaskTiming]) {
+    println!();
+    println!("{:<40} {:<10} {:>10}", "Task", "Status", "Duration");
+    println!("{}", "-".repeat(62));
+    for t in timings {
+        let duration = t
+            .duration_secs
+            .map(|d| format!("{}m {:02}s", d as u64 / 60, d as u64 % 60))
+            .unwrap_or_else(|| "-".to_string());
+        println!("{:<40} {:<10} {:>10}", t.task, t.status, duration);
+    }
+}
+
+/// Get task logs using label selector when pod name doesn't match convention.
+fn get_task_logs_by_label(
+    pipelinerun_name: &str,
+    task_name: &str,
+    namespace: &str,
+    step_container: &str,
+) -> Result<String> {
+    let label = format!(
+        "tekton.dev/pipelineRun={},tekton.dev/pipelineTask={}",
+        pipelinerun_name, task_name
+    );
+
+    // Get pod name via label
+    let pod_result = exec::run_cmd(
+        "oc",
+        &[
+            "get", "pods",
+            "-n", namespace,
+            "-l", &label,
+            "-o", "jsonpath={.items[0].metadata.name}",
+        ],
+    )?;
+
+    let actual_pod = pod_result.stdout.trim().to_string();
+    if actual_pod.is_empty() {
+        anyhow::bail!("No pod found for task {}", task_name);
+    }
+
+    let log_result = exec::run_cmd(
+        "oc",
+        &["logs", "-n", namespace, &actual_pod, "-c", step_container],
+    )?;
+
+    Ok(log_result.stdout)
+}
+
+/// Save collected pipeline results to output_dir/results/results.json.
+///
+/// Merges results from all e2e test tasks into a single CategorizedTestRunResult,
+/// and copies the SNAPSHOT JSON to output_dir for QE reference.
+pub fn save_konflux_results(
+    task_results: &[results::TestRunResult],
+    snapshot_path: &Path,
+    output_dir: &Path,
+) -> Result<()> {
+    let results_dir = output_dir.join("results");
+    std::fs::create_dir_all(&results_dir)?;
+
+    // Merge all task results into one combined TestRunResult
+    let mut all_tests = Vec::new();
+    let mut total_duration = 0.0;
+
+    for tr in task_results {
+        all_tests.extend(tr.tests.clone());
+        total_duration += tr.duration_secs;
+    }
+
+    let passed = all_tests.iter().filter(|t| t.passed).count();
+    let failed = all_tests.iter().filter(|t| !t.passed).count();
+
+    let combined = results::TestRunResult {
+        total: all_tests.len(),
+        passed,
+        failed,
+        errors: 0,
+        duration_secs: total_duration,
+        source: Some("konflux-pipeline".to_string()),
+        tests: all_tests,
+    };
+
+    // Categorize and write
+    let categorized = results::categorize_results(&combined);
+    results::write_results(&categorized, &results_dir)?;
+
+    // Copy SNAPSHOT JSON to output_dir for QE reference
+    if snapshot_path.exists() {
+        let dest = output_dir.join("snapshot.json");
+        std::fs::copy(snapshot_path, &dest)
+            .with_context(|| format!("Failed to copy snapshot to {}", dest.display()))?;
+        eprintln!("SNAPSHOT copied to {}", dest.display());
+    }
+
+    Ok(())
+}
+
+/// Suite name from a result's `konflux-pipeline:<task>` source.
+fn suite_name(tr: &results::TestRunResult) -> &str {
+    tr.source
+        .as_deref()
+        .unwrap_or("unknown")
+        .strip_prefix("konflux-pipeline:")
+        .unwrap_or("unknown")
+}
+
+/// Per-suite counts for structured output.
+pub fn suite_summaries(task_results: &[results::TestRunResult]) -> Vec<SuiteSummary> {
+    task_results
+        .iter()
+        .map(|tr| SuiteSummary {
+            suite: suite_name(tr).to_string(),
+            total: tr.total,
+            passed: tr.passed,
+            failed: tr.failed,
+        })
+        .collect()
+}
+
+/// Print a summary table of results per test suite to stdout.
+pub fn print_pipeline_summary(task_results: &[results::TestRunResult]) {
+    use console::Style;
+
+    let green = Style::new().green().bold();
+    let red = Style::new().red().bold();
+    let bold = Style::new().bold();
+
+    println!();
+    println!("{}", bold.apply_to("Pipeline Test Results Summary"));
+    println!("{}", "=".repeat(60));
+    println!(
+        "{:<30} {:>8} {:>8} {:>8}",
+        "Suite", "Total", "Passed", "Failed"
+    );
+    println!("{}", "-".repeat(60));
+
+    let mut grand_total = 0;
+    let mut grand_passed = 0;
+    let mut grand_failed = 0;
+
+    for tr in task_results {
+        let suite_name = suite_name(tr);
+
+        let passed_str = format!("{}", tr.passed);
+        let failed_str = format!("{}", tr.failed);
+
+        println!(
+            "{:<30} {:>8} {:>8} {:>8}",
+            suite_name,
+            tr.total,
+            green.apply_to(&passed_str),
+            if tr.failed > 0 {
+                red.apply_to(&failed_str).to_string()
+            } else {
+                failed_str
+            }
+        );
+
+        grand_total += tr.total;
+        grand_passed += tr.passed;
+        grand_failed += tr.failed;
+    }
+
+    println!("{}", "-".repeat(60));
+    println!(
+        "{:<30} {:>8} {:>8} {:>8}",
+        bold.apply_to("TOTAL"),
+        grand_total,
+        green.apply_to(format!("{}", grand_passed)),
+        if grand_failed > 0 {
+            red.apply_to(format!("{}", grand_failed)).to_string()
+        } else {
+            format!("{}", grand_failed)
+        }
+    );
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_timings() {
+        let list = serde_json::json!({"items": [
+            {
+                "metadata": {"name": "pr-e2e-test-pipelines", "labels": {"tekton.dev/pipelineTask": "e2e-test-pipelines"}},
+                "status": {
+                    "startTime": "2025-03-01T12:10:00Z",
+                    "conditions": [{"type": "Succeeded", "status": "Unknown", "reason": "Running"}]
+                }
+            },
+            {
+                "metadata": {"name": "pr-deploy-operator", "labels": {"tekton.dev/pipelineTask": "deploy-operator"}},
+                "status": {
+                    "startTime": "2025-03-01T12:00:00Z",
+                    "completionTime": "2025-03-01T12:07:30Z",
+                    "conditions": [{"type": "Succeeded", "status": "False", "reason": "Failed"}]
+                }
+            },
+            {"metadata": {"name": "pr-cleanup"}}
+        ]});
+        let timings = task_timings(&list);
+        let tasks: Vec<&str> = timings.iter().map(|t| t.task.as_str()).collect();
+        assert_eq!(tasks, vec!["deploy-operator", "e2e-test-pipelines", "pr-cleanup"]);
+        assert_eq!(timings[0].status, "Failed");
+        assert_eq!(timings[0].reason, "Failed");
+        assert_eq!(timings[0].duration_secs, Some(450.0));
+        assert_eq!(timings[1].status, "Running");
+        assert_eq!(timings[1].duration_secs, None);
+        assert_eq!(timings[2].status, "Pending");
+    }
+
+    /// Golden files: each `testdata/konflux/<branch>/release-test-pipeline.yaml` must
+    /// transform into the `standalone.yaml` next to it. After a deliberate change,
+    /// or to add a branch's pipeline, regenerate them with
+    /// `UPDATE_GOLDEN=1 cargo test test_standalone_pipeline_golden`, which fails
+    /// after rewriting them; review the diff (see testdata/konflux/README.md).
+    #[test]
+    fn test_standalone_pipeline_golden() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/konflux");
+        let mut branches: Vec<PathBuf> =
+            std::fs::read_dir(&root).unwrap().map(|e| e.unwrap().path()).filter(|p| p.is_dir()).collect();
+        branches.sort();
+        assert!(branches.len() >= 3);
+
+        let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+        let mut rewritten = Vec::new();
+        for branch in branches {
+            let input = std::fs::read_to_string(branch.join("release-test-pipeline.yaml")).unwrap();
+            let golden_path = branch.join("standalone.yaml");
+            let standalone = create_standalone_pipeline(&input).unwrap();
+            if update {
+                if std::fs::read_to_string(&golden_path).ok().as_deref() != Some(standalone.as_str()) {
+                    std::fs::write(&golden_path, &standalone).unwrap();
+                    rewritten.push(golden_path.display().to_string());
+                }
+                continue;
+            }
+            let golden = std::fs::read_to_string(&golden_path).unwrap();
+            assert_eq!(standalone, golden, "{} differs from its golden file", branch.display());
+
+            for eaas in EAAS_TASKS {
+                assert!(!standalone.contains(&format!("$(tasks.{eaas}.")), "{}: reference to {eaas} left", branch.display());
+                assert!(!standalone.contains(&format!("- {eaas}\n")), "{}: runAfter {eaas} left", branch.display());
+                assert!(!standalone.contains(&format!("name: {eaas}\n")), "{}: task {eaas} left", branch.display());
+            }
+            let doc: serde_json::Value = serde_yaml::from_str(&standalone).unwrap();
+            assert_eq!(doc["metadata"]["name"], "release-test-pipeline-standalone");
+            let params = doc["spec"]["params"].as_array().unwrap();
+            assert_eq!(params.iter().filter(|p| p["name"] == "INDEX_IMAGE").count(), 1);
+        }
+        assert!(!update, "UPDATE_GOLDEN: rewrote {:?}; review the diff and rerun without UPDATE_GOLDEN", rewritten);
+    }
+
+    #[test]
+    fn test_transform_pipeline_changes() {
+        let input = std::fs::read_to_string(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/konflux/release-v1.15/release-test-pipeline.yaml"),
+        )
+        .unwrap();
+        let standalone = transform_pipeline(&input).unwrap();
+        assert_eq!(standalone.changes[..4], [
+            "Renamed the pipeline to release-test-pipeline-standalone",
+            "Added pipeline param INDEX_IMAGE",
+            "Removed EaaS task provision-eaas-space from spec.tasks",
+            "Removed EaaS task provision-cluster from spec.tasks",
+        ]);
+        assert!(standalone.changes.contains(&"deploy-operator: dropped runAfter provision-cluster".to_string()));
+        let script = standalone.pipeline["spec"]["tasks"][0]["taskSpec"]["steps"][0]["script"].as_str().unwrap();
+        assert!(script.contains("Deploying to cluster standalone-cluster\""));
+        assert!(script.contains("KUBECONFIG=/credentials//kubeconfig"));
+        assert!(standalone.pipeline["spec"].get("finally").is_none());
+        assert!(standalone.pipeline["spec"]["tasks"][0].get("runAfter").is_none());
+
+        assert!(transform_pipeline("apiVersion: v1\nkind: ConfigMap\n").is_err());
+    }
+
+    #[test]
+    fn test_looks_like_gauge() {
+        let gauge = "# Pipelines\n## Run a pipeline\n\u{1b}[32mSpecifications:\t1 executed\t1 passed\t0 failed\t0 skipped\u{1b}[0m\n\
+                     Scenarios:\t3 executed\t3 passed\t0 failed\t0 skipped\n";
+        assert!(looks_like_gauge(gauge));
+        assert!(!looks_like_gauge("Specifications: see README\nDeploying operator...\n"));
+        assert!(!looks_like_gauge(""));
+    }
+}
@@ -0,0 +1,194 @@
+//! Post-run leak check (`run --check-leaks`).
+//!
+//! Before the test phase, the namespaces, PipelineRuns, TaskRuns and PVCs on the
+//! cluster are snapshotted; after it, whatever is new and not being deleted is
+//! reported as leaked, so tests that don't clean up are caught before they fill
+//! a shared cluster. Objects inside a leaked namespace are reported with it.
+
+use anyhow::{Context, Result};
+use k8s_openapi::api::core::v1::{Namespace, PersistentVolumeClaim};
+use kube::api::{Api, ApiResource, DynamicObject, ListParams, ObjectMeta};
+use kube::Client;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use tokio::runtime::Runtime;
+
+use crate::{k8s, warnings};
+
+/// Cluster objects a run may leave behind. Namespaced ones are keyed `ns/name`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ClusterState {
+    pub namespaces: BTreeSet<String>,
+    pub pipeline_runs: BTreeSet<String>,
+    pub task_runs: BTreeSet<String>,
+    pub pvcs: BTreeSet<String>,
+}
+
+/// Objects present after the run but not before, written to `results/cluster-leaks.json`.
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct LeakReport {
+    pub namespaces: Vec<String>,
+    pub pipeline_runs: Vec<String>,
+    pub task_runs: Vec<String>,
+    pub pvcs: Vec<String>,
+}
+
+impl LeakReport {
+    pub fn is_empty(&self) -> bool {
+        self.count() == 0
+    }
+
+    pub fn count(&self) -> usize {
+        self.namespaces.len() + self.pipeline_runs.len() + self.task_runs.len() + self.pvcs.len()
+    }
+
+    /// One line per leaked kind, e.g. "2 namespace(s): e2e-abc, e2e-def".
+    fn lines(&self) -> Vec<String> {
+        [
+            ("namespace(s)", &self.namespaces),
+            ("PipelineRun(s)", &self.pipeline_runs),
+            ("TaskRun(s)", &self.task_runs),
+            ("PVC(s)", &self.pvcs),
+        ]
+        .into_iter()
+        .filter(|(_, names)| !names.is_empty())
+        .map(|(kind, names)| format!("{} {}: {}", names.len(), kind, names.join(", ")))
+        .collect()
+    }
+}
+
+/// Snapshot the cluster state to compare against with `check`.
+pub fn capture() -> Result<ClusterState> {
+    let (rt, client) = k8s::create_kube_client()?;
+    capture_with(&rt, &client)
+}
+
+fn capture_with(rt: &Runtime, client: &Client) -> Result<ClusterState> {
+    let lp = ListParams::default();
+
+    let ns_api: Api<Namespace> = Api::all(client.clone());
+    let namespaces = k8s::block_on_retry(rt, "list Namespaces", || ns_api.list(&lp))
+        .context("Failed to list namespaces")?;
+    let pvc_api: Api<PersistentVolumeClaim> = Api::all(client.clone());
+    let pvcs = k8s::block_on_retry(rt, "list PersistentVolumeClaims", || pvc_api.list(&lp))
+        .context("Failed to list PVCs")?;
+
+    Ok(ClusterState {
+        namespaces: live_keys(namespaces.items.iter().map(|n| &n.metadata)),
+        pipeline_runs: tekton_runs(rt, client, "PipelineRun", "pipelineruns")?,
+        task_runs: tekton_runs(rt, client, "TaskRun", "taskruns")?,
+        pvcs: live_keys(pvcs.items.iter().map(|p| &p.metadata)),
+    })
+}
+
+/// Keys of the objects of a Tekton run kind, empty when its CRD is not installed.
+fn tekton_runs(rt: &Runtime, client: &Client, kind: &str, plural: &str) -> Result<BTreeSet<String>> {
+    let ar = ApiResource {
+        group: "tekton.dev".into(),
+        version: "v1".into(),
+        api_version: "tekton.dev/v1".into(),
+        kind: kind.into(),
+        plural: plural.into(),
+    };
+    let api: Api<DynamicObject> = Api::all_with(client.clone(), &ar);
+    let lp = ListParams::default();
+    match k8s::block_on_retry(rt, &format!("list {kind}s"), || api.list(&lp)) {
+        Ok(list) => Ok(live_keys(list.items.iter().map(|o| &o.metadata))),
+        Err(kube::Error::Api(resp)) if resp.code == 404 => Ok(BTreeSet::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to list {kind}s")),
+    }
+}
+
+/// `ns/name` (or `name` when cluster-scoped) of each object not being deleted.
+fn live_keys<'a>(metas: impl Iterator<Item = &'a ObjectMeta>) -> BTreeSet<String> {
+    metas
+        .filter(|m| m.deletion_timestamp.is_none())
+        .filter_map(|m| {
+            let name = m.name.as_deref()?;
+            Some(match m.namespace.as_deref() {
+                Some(ns) => format!("{ns}/{name}"),
+                None => name.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// What `after` has that `before` did not, folding objects of leaked namespaces
+/// into their namespace.
+pub fn leaks(before: &ClusterState, after: &ClusterState) -> LeakReport {
+    let namespaces: Vec<String> = after.namespaces.difference(&before.namespaces).cloned().collect();
+    let new = |before: &BTreeSet<String>, after: &BTreeSet<String>| -> Vec<String> {
+        after
+            .difference(before)
+            .filter(|key| !key.split_once('/').is_some_and(|(ns, _)| namespaces.iter().any(|n| n == ns)))
+            .cloned()
+            .collect()
+    };
+    LeakReport {
+        pipeline_runs: new(&before.pipeline_runs, &after.pipeline_runs),
+        task_runs: new(&before.task_runs, &after.task_runs),
+        pvcs: new(&before.pvcs, &after.pvcs),
+        namespaces,
+    }
+}
+
+pub fn report_path(output_dir: &Path) -> PathBuf {
+    output_dir.join("results").join("cluster-leaks.json")
+}
+
+/// Snapshot the cluster again, report what the run left behind since `before`
+/// (a warning per kind) and write the report. Returns the report.
+pub fn check(before: &ClusterState, output_dir: &Path) -> Result<LeakReport> {
+    let report = leaks(before, &capture()?);
+    let path = report_path(output_dir);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(&report)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    if report.is_empty() {
+        eprintln!("Cluster left clean: no new namespaces, PipelineRuns, TaskRuns or PVCs");
+    } else {
+        for line in report.lines() {
+            warnings::warn(format!("Run left behind {}", line));
+        }
+        eprintln!("Leak report: {}", path.display());
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(items: &[&str]) -> BTreeSet<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_leaks() {
+        let before = ClusterState {
+            namespaces: set(&["default", "openshift-pipelines"]),
+            pipeline_runs: set(&["default/old-run"]),
+            task_runs: BTreeSet::new(),
+            pvcs: set(&["openshift-pipelines/go-cache"]),
+        };
+        let after = ClusterState {
+            namespaces: set(&["default", "openshift-pipelines", "e2e-abc"]),
+            pipeline_runs: set(&["default/old-run", "default/left-run", "e2e-abc/run-1"]),
+            task_runs: set(&["e2e-abc/run-1-task"]),
+            pvcs: set(&["openshift-pipelines/go-cache", "default/pvc-1"]),
+        };
+        let report = leaks(&before, &after);
+        assert_eq!(report.namespaces, vec!["e2e-abc"]);
+        assert_eq!(report.pipeline_runs, vec!["default/left-run"]);
+        assert!(report.task_runs.is_empty());
+        assert_eq!(report.pvcs, vec!["default/pvc-1"]);
+        assert_eq!(report.count(), 3);
+        assert_eq!(report.lines()[0], "1 namespace(s): e2e-abc");
+
+        assert!(leaks(&after, &before).is_empty());
+    }
+}
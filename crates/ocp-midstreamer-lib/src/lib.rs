@@ -0,0 +1,91 @@
+//! Core of streamstress: building upstream components, deploying them over the
+//! operator, running the release tests, and collecting, profiling and
+//! publishing the results, plus the Konflux and snapshot flows.
+//!
+//! The `streamstress` binary is a thin CLI over this crate; other tools can
+//! embed the same phases. Library functions report failures as `anyhow` errors
+//! and never exit the process. Process-wide settings (progress output, GitHub
+//! tokens, API retries, run metadata, the scratch workspace) are set once with
+//! each module's `init`, as the CLI does at startup. Child processes spawned by
+//! a run (in-cluster Jobs, batch date runs) rebuild them from the `to_args`
+//! helpers of those modules.
+//!
+//! The main entry points:
+//! - [`build`], [`ko`]: building component images
+//! - [`deploy`]: patching the operator CSV and waiting for rollouts
+//! - [`test`], [`results`]: running the release tests and parsing their reports
+//! - [`profile`], [`profilediff`]: resource profiles and their comparison
+//! - [`konflux`], [`snapshot`]: Konflux bundle runs and release SNAPSHOTs
+
+pub mod access;
+pub mod admission;
+pub mod aggregate;
+pub mod audit;
+pub mod baseline;
+pub mod batch;
+pub mod build;
+pub mod bundle;
+pub mod callback;
+pub mod cassette;
+pub mod catalogsmoke;
+pub mod chains;
+pub mod check;
+pub mod component;
+pub mod config;
+pub mod confirm;
+pub mod dashboard;
+pub mod deploy;
+pub mod dryrun;
+pub mod email;
+pub mod events;
+pub mod exec;
+pub mod gaugeevents;
+pub mod github;
+pub mod gitops;
+pub mod gotoolchain;
+pub mod imagesource;
+pub mod imagestream;
+pub mod impact;
+pub mod incluster;
+pub mod k8s;
+pub mod ko;
+pub mod konflux;
+pub mod leaks;
+pub mod lock;
+pub mod logging;
+pub mod logscan;
+pub mod netcheck;
+pub mod matrix;
+pub mod output;
+pub mod pac;
+pub mod patch;
+pub mod perf;
+pub mod platform;
+pub mod profile;
+pub mod profilediff;
+pub mod profileexport;
+pub mod progress;
+pub mod publish;
+pub mod rbac;
+pub mod refcheck;
+pub mod safety;
+pub mod scopeduser;
+pub mod scrub;
+pub mod registry;
+pub mod results;
+pub mod runbundle;
+pub mod runmeta;
+pub mod selfupdate;
+pub mod setup;
+pub mod signing;
+pub mod snapshot;
+pub mod test;
+pub mod testref;
+pub mod timestamp;
+pub mod tools;
+pub mod top;
+pub mod triggers;
+pub mod types;
+pub mod uninstall;
+pub mod warnings;
+pub mod workspace;
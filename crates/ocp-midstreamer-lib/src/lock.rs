@@ -0,0 +1,243 @@
+//! Cluster-scoped run lock.
+//!
+//! Deploys patch the one operator Deployment and delete InstallerSets, so two
+//! runs against the same cluster overwrite each other's images. A run holds a
+//! Lease in the operator namespace from deploy start to run end, renewing it
+//! while it works; a holder that dies stops renewing and its lock expires.
+
+use anyhow::{Context, Result, bail};
+use k8s_openapi::api::coordination::v1::Lease;
+use kube::api::{Api, DeleteParams, PostParams};
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::{audit, k8s, timestamp, warnings};
+
+/// Name of the Lease guarding the cluster.
+pub const LOCK_NAME: &str = "streamstress-lock";
+
+/// Seconds a lock stays valid without renewal.
+const LEASE_DURATION_SECS: i32 = 300;
+
+/// How often the holder renews the lock.
+const RENEW_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long an in-cluster Job waits for the lock, e.g. behind the other Jobs of a batch.
+pub const JOB_WAIT_TIMEOUT: Duration = Duration::from_secs(6 * 3600);
+
+/// How often a waiting run checks whether the lock is free.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Who holds the lock, as shown by `status`.
+#[derive(Debug, Clone, Serialize)]
+pub struct LockHolder {
+    pub identity: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acquired: Option<String>,
+    /// Seconds since the holder last renewed the lock
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renewed_seconds_ago: Option<i64>,
+    /// Not renewed within the lease duration: the next run takes it over
+    pub expired: bool,
+}
+
+impl LockHolder {
+    fn describe(&self) -> String {
+        let since = self.acquired.as_deref().map(|a| format!(" since {}", a)).unwrap_or_default();
+        format!("{}{}", self.identity, since)
+    }
+}
+
+/// A held lock; released with `release`, or left to expire if the process dies.
+pub struct ClusterLock {
+    api: Api<Lease>,
+    identity: String,
+    renewer: tokio::task::JoinHandle<()>,
+}
+
+/// Identity recorded as the lock holder: the Job when in-cluster, else user@host and pid.
+pub fn own_identity() -> String {
+    if let Ok(job) = std::env::var("JOB_NAME") {
+        return format!("job/{}", job);
+    }
+    let user = std::env::var("USER").or_else(|_| std::env::var("USERNAME")).unwrap_or_else(|_| "unknown".to_string());
+    let host = std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    format!("{}@{} (pid {})", user, host, std::process::id())
+}
+
+/// Current holder of the lock, or None if nobody holds it.
+pub async fn current_holder(client: &kube::Client, namespace: &str) -> Result<Option<LockHolder>> {
+    let api: Api<Lease> = Api::namespaced(client.clone(), namespace);
+    let lease = api.get_opt(LOCK_NAME).await.context("Failed to read the streamstress lock Lease")?;
+    Ok(lease.as_ref().and_then(holder_of))
+}
+
+fn holder_of(lease: &Lease) -> Option<LockHolder> {
+    let spec = lease.spec.as_ref()?;
+    let identity = spec.holder_identity.clone().filter(|h| !h.is_empty())?;
+    let renewed = spec.renew_time.as_ref().or(spec.acquire_time.as_ref()).map(|t| t.0.as_second());
+    let renewed_seconds_ago = renewed.map(|r| timestamp::unix_now() - r);
+    let duration = spec.lease_duration_seconds.unwrap_or(LEASE_DURATION_SECS) as i64;
+    Some(LockHolder {
+        identity,
+        acquired: spec
+            .acquire_time
+            .as_ref()
+            .and_then(|t| chrono::DateTime::from_timestamp(t.0.as_second(), 0))
+            .map(timestamp::to_rfc3339),
+        renewed_seconds_ago,
+        expired: renewed_seconds_ago.is_none_or(|ago| ago > duration),
+    })
+}
+
+/// Acquire the lock. A live lock held by someone else fails the call, unless
+/// `force` takes it over or `wait` is given, which polls for up to that long.
+pub async fn acquire(client: &kube::Client, namespace: &str, force: bool, wait: Option<Duration>) -> Result<ClusterLock> {
+    let api: Api<Lease> = Api::namespaced(client.clone(), namespace);
+    let identity = own_identity();
+    let started = std::time::Instant::now();
+    let mut announced = false;
+    loop {
+        let existing = api.get_opt(LOCK_NAME).await.context("Failed to read the streamstress lock Lease")?;
+        let holder = existing.as_ref().and_then(holder_of).filter(|h| h.identity != identity);
+        match holder {
+            Some(h) if !h.expired && !force => {
+                let Some(wait) = wait.filter(|w| started.elapsed() < *w) else {
+                    bail!(
+                        "Cluster is locked by {} (another streamstress run is deploying or testing).\n\
+                         Wait for it to finish, or pass --force-unlock if that run is gone.",
+                        h.describe()
+                    );
+                };
+                if !announced {
+                    eprintln!("Waiting for the cluster lock held by {} (up to {} min)...", h.describe(), wait.as_secs() / 60);
+                    announced = true;
+                }
+                tokio::time::sleep(WAIT_POLL_INTERVAL).await;
+                continue;
+            }
+            Some(h) if h.expired => eprintln!("Taking over expired cluster lock of {}", h.describe()),
+            Some(h) => warnings::warn(format!("Forcibly taking the cluster lock from {}", h.describe())),
+            None => {}
+        }
+
+        let now = timestamp::now_rfc3339();
+        let mut lease: Lease = serde_json::from_value(serde_json::json!({
+            "apiVersion": "coordination.k8s.io/v1",
+            "kind": "Lease",
+            "metadata": {"name": LOCK_NAME, "namespace": namespace, "labels": {"app": "streamstress"}},
+            "spec": {
+                "holderIdentity": &identity,
+                "leaseDurationSeconds": LEASE_DURATION_SECS,
+                "acquireTime": &now,
+                "renewTime": &now,
+            }
+        }))?;
+        let pp = PostParams::default();
+        let result = match existing {
+            Some(e) => {
+                lease.metadata.resource_version = e.metadata.resource_version;
+                k8s::retry("replace lock Lease", || api.replace(LOCK_NAME, &pp, &lease)).await
+            }
+            None => k8s::retry("create lock Lease", || api.create(&pp, &lease)).await,
+        };
+        audit::record_api("apply", "Lease", Some(namespace), LOCK_NAME, result.is_ok());
+        match result {
+            Ok(_) => break,
+            // Another run got there between our read and write: look again
+            Err(kube::Error::Api(ae)) if ae.code == 409 => continue,
+            Err(e) => return Err(e).context("Failed to acquire the streamstress lock Lease"),
+        }
+    }
+    eprintln!("Acquired cluster lock ({}/{}) as {}", namespace, LOCK_NAME, identity);
+
+    let renewer = tokio::spawn(renew_loop(api.clone(), identity.clone()));
+    Ok(ClusterLock { api, identity, renewer })
+}
+
+/// Keep the lock alive until aborted; stops if another run took it over.
+async fn renew_loop(api: Api<Lease>, identity: String) {
+    loop {
+        tokio::time::sleep(RENEW_INTERVAL).await;
+        let Ok(Some(mut lease)) = api.get_opt(LOCK_NAME).await else {
+            continue;
+        };
+        let Some(spec) = lease.spec.as_mut() else {
+            continue;
+        };
+        if spec.holder_identity.as_deref() != Some(identity.as_str()) {
+            warnings::warn(format!(
+                "The cluster lock was taken over by {}; this run no longer holds it",
+                spec.holder_identity.as_deref().unwrap_or("unknown")
+            ));
+            return;
+        }
+        spec.renew_time = serde_json::from_value(serde_json::json!(timestamp::now_rfc3339())).ok();
+        let pp = PostParams::default();
+        let result = k8s::retry("renew lock Lease", || api.replace(LOCK_NAME, &pp, &lease)).await;
+        audit::record_api("replace", "Lease", api.namespace(), LOCK_NAME, result.is_ok());
+        if let Err(e) = result {
+            warnings::warn(format!("Could not renew the cluster lock: {e}"));
+        }
+    }
+}
+
+impl ClusterLock {
+    /// Stop renewing and delete the lock if this run still holds it.
+    pub async fn release(self) {
+        self.renewer.abort();
+        match self.api.get_opt(LOCK_NAME).await {
+            Ok(Some(lease)) if lease.spec.as_ref().and_then(|s| s.holder_identity.as_deref()) == Some(self.identity.as_str()) => {
+                let dp = DeleteParams {
+                    preconditions: lease.metadata.resource_version.map(|rv| kube::api::Preconditions {
+                        resource_version: Some(rv),
+                        uid: None,
+                    }),
+                    ..DeleteParams::default()
+                };
+                let result = self.api.delete(LOCK_NAME, &dp).await;
+                audit::record_api("delete", "Lease", self.api.namespace(), LOCK_NAME, result.is_ok());
+                match result {
+                    Ok(_) => eprintln!("Released cluster lock"),
+                    Err(e) => warnings::warn(format!("Could not release the cluster lock (it expires in {}s): {e}", LEASE_DURATION_SECS)),
+                }
+            }
+            Ok(_) => {}
+            Err(e) => warnings::warn(format!("Could not release the cluster lock (it expires in {}s): {e}", LEASE_DURATION_SECS)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lease(holder: &str, renewed_ago: i64) -> Lease {
+        let renewed = chrono::DateTime::from_timestamp(timestamp::unix_now() - renewed_ago, 0).unwrap();
+        serde_json::from_value(serde_json::json!({
+            "metadata": {"name": LOCK_NAME},
+            "spec": {
+                "holderIdentity": holder,
+                "leaseDurationSeconds": LEASE_DURATION_SECS,
+                "acquireTime": timestamp::to_rfc3339(renewed),
+                "renewTime": timestamp::to_rfc3339(renewed),
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_holder_of() {
+        let live = holder_of(&lease("job/streamstress-abc", 30)).unwrap();
+        assert_eq!(live.identity, "job/streamstress-abc");
+        assert!(!live.expired);
+        assert!(holder_of(&lease("alice@laptop (pid 1)", 600)).unwrap().expired);
+        assert!(holder_of(&lease("", 0)).is_none());
+    }
+}
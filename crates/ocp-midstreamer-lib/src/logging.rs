@@ -0,0 +1,125 @@
+//! Diagnostic logging (`-v`, `-vv`, `-vvv`, `--log`).
+//!
+//! Modules emit `tracing` events, written to stderr by the subscriber `init`
+//! installs. Each `-v` lowers the level for streamstress' own modules: info
+//! shows every cluster change, debug adds every external command line with its
+//! exit code, and trace adds command output and full Kubernetes API payloads.
+//! `--log` adds per-module filters on top, e.g. `deploy=debug,kube=warn`.
+
+use anyhow::{Context, Result};
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+use tracing_subscriber::EnvFilter;
+
+/// Crates whose events follow the `-v` level.
+const OWN_CRATES: &[&str] = &["ocp_midstreamer_lib", "streamstress"];
+
+/// Dependencies `--log` may name directly; any other bare name is a
+/// streamstress module.
+const DEPENDENCY_TARGETS: &[&str] = &["kube", "kube_client", "kube_core", "hyper", "hyper_util", "h2", "tower", "rustls"];
+
+/// Target of Kubernetes API events: changes at info, payloads at trace (`--log k8s=trace`).
+pub const API_TARGET: &str = "ocp_midstreamer_lib::k8s";
+
+/// Target of external command events: command lines at debug, output at trace.
+pub const COMMAND_TARGET: &str = "ocp_midstreamer_lib::exec";
+
+struct Settings {
+    verbosity: u8,
+    filters: Option<String>,
+}
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+/// Install the stderr subscriber for `verbosity` (the number of `-v`) and the
+/// `--log` filters. Call once at startup, before anything logs.
+pub fn init(verbosity: u8, filters: Option<&str>) -> Result<()> {
+    let filter = EnvFilter::try_new(directives(verbosity, filters))
+        .with_context(|| format!("Invalid --log filter '{}'", filters.unwrap_or_default()))?;
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::stderr().is_terminal())
+        .try_init();
+    let _ = SETTINGS.set(Settings {
+        verbosity,
+        filters: filters.map(str::to_string),
+    });
+    Ok(())
+}
+
+/// Number of `-v` given.
+pub fn verbosity() -> u8 {
+    SETTINGS.get().map(|s| s.verbosity).unwrap_or_default()
+}
+
+/// CLI args recreating these settings (for self-invocation).
+pub fn to_args() -> Vec<String> {
+    let Some(settings) = SETTINGS.get() else {
+        return Vec::new();
+    };
+    let mut args = Vec::new();
+    if settings.verbosity > 0 {
+        args.push(format!("-{}", "v".repeat(settings.verbosity as usize)));
+    }
+    if let Some(ref filters) = settings.filters {
+        args.push("--log".to_string());
+        args.push(filters.clone());
+    }
+    args
+}
+
+/// `EnvFilter` directives: the `-v` level for our crates, then the `--log` filters,
+/// which win over it. At `-vvv` dependencies log at info.
+fn directives(verbosity: u8, filters: Option<&str>) -> String {
+    let level = match verbosity {
+        0 => "warn",
+        1 => "info",
+        2 => "debug",
+        _ => "trace",
+    };
+    let mut directives = vec![if verbosity >= 3 { "info" } else { "warn" }.to_string()];
+    directives.extend(OWN_CRATES.iter().map(|c| format!("{}={}", c, level)));
+    if let Some(filters) = filters {
+        directives.extend(filters.split(',').map(str::trim).filter(|d| !d.is_empty()).map(qualify));
+    }
+    directives.join(",")
+}
+
+/// `deploy=debug` -> `ocp_midstreamer_lib::deploy=debug`. Our crates, the
+/// dependencies in `DEPENDENCY_TARGETS` and bare levels are kept as given.
+fn qualify(directive: &str) -> String {
+    let Some((target, level)) = directive.split_once('=') else {
+        return directive.to_string();
+    };
+    let root = target.split("::").next().unwrap_or(target);
+    if OWN_CRATES.contains(&root) || DEPENDENCY_TARGETS.contains(&root) {
+        directive.to_string()
+    } else {
+        format!("{}::{}={}", OWN_CRATES[0], target, level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directives() {
+        assert_eq!(directives(0, None), "warn,ocp_midstreamer_lib=warn,streamstress=warn");
+        assert_eq!(directives(2, None), "warn,ocp_midstreamer_lib=debug,streamstress=debug");
+        assert_eq!(directives(5, None), "info,ocp_midstreamer_lib=trace,streamstress=trace");
+        assert_eq!(
+            directives(1, Some("deploy=debug, kube=warn,deploy::wait=trace,ocp_midstreamer_lib::k8s=trace")),
+            "warn,ocp_midstreamer_lib=info,streamstress=info,ocp_midstreamer_lib::deploy=debug,kube=warn,\
+             ocp_midstreamer_lib::deploy::wait=trace,ocp_midstreamer_lib::k8s=trace"
+        );
+        assert_eq!(directives(0, Some("debug")), "warn,ocp_midstreamer_lib=warn,streamstress=warn,debug");
+    }
+
+    #[test]
+    fn test_filters_parse() {
+        assert!(EnvFilter::try_new(directives(3, Some("deploy=debug,kube=warn"))).is_ok());
+        assert!(EnvFilter::try_new(directives(0, Some("deploy=loud"))).is_err());
+    }
+}
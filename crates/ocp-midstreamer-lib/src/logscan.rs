@@ -0,0 +1,185 @@
+//! Controller log scanning.
+//!
+//! After the test suite, the logs of the operator and of the component
+//! controllers in openshift-pipelines are collected for the test window and
+//! scanned for error and panic signatures. Each failed scenario gets the error
+//! lines logged while it ran (from `logs/scenario-timeline.json`, recorded
+//! from gauge's execution events) attached as `log_excerpts` in results.json.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::{Api, ListParams, LogParams};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use crate::gaugeevents::ScenarioSpan;
+use crate::{k8s, results, warnings};
+
+/// Namespace of the component controllers and webhooks.
+const COMPONENT_NAMESPACE: &str = "openshift-pipelines";
+
+/// Namespace and pod name prefix of the OLM-installed operator.
+const OPERATOR_NAMESPACE: &str = "openshift-operators";
+const OPERATOR_POD_PREFIX: &str = "openshift-pipelines-operator";
+
+/// Log lines logged this long after a scenario's end still count towards it:
+/// controllers reconcile asynchronously.
+const CORRELATION_SLACK_SECS: i64 = 30;
+
+/// Excerpts attached to one failed scenario.
+const MAX_EXCERPTS_PER_TEST: usize = 5;
+
+/// Longest excerpt line kept; JSON-formatted controller logs can be very long.
+const MAX_LINE_LEN: usize = 500;
+
+/// Error and panic signatures: zap/logrus JSON and logfmt levels, klog error
+/// and fatal prefixes, and Go panics.
+static ERROR_SIGNATURE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)"level":\s*"(error|fatal|panic|dpanic)"|\blevel=(error|fatal|panic)\b|^[EF]\d{4} \d{2}:\d{2}:\d{2}|^panic:|goroutine \d+ \[running\]"#)
+        .expect("Invalid regex")
+});
+
+/// An error line from a controller log.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LogExcerpt {
+    /// `namespace/pod/container`
+    pub source: String,
+    pub timestamp: String,
+    pub line: String,
+}
+
+/// Collect the operator and controller logs since `since`, save them under
+/// `logs/controllers/`, and return their error lines.
+pub fn scan(since: DateTime<Utc>, output_dir: &Path) -> Result<Vec<LogExcerpt>> {
+    let (rt, client) = k8s::create_kube_client()?;
+    let logs_dir = output_dir.join("logs").join("controllers");
+    fs::create_dir_all(&logs_dir).context("Failed to create controller logs directory")?;
+    let since_seconds = (Utc::now() - since).num_seconds().max(1) + CORRELATION_SLACK_SECS;
+
+    let lp = ListParams::default();
+    let mut excerpts = Vec::new();
+    let mut containers = 0;
+    for namespace in [COMPONENT_NAMESPACE, OPERATOR_NAMESPACE] {
+        let api: Api<Pod> = Api::namespaced(client.clone(), namespace);
+        let pods = k8s::block_on_retry(&rt, "list Pods", || api.list(&lp))
+            .with_context(|| format!("Failed to list pods in {}", namespace))?;
+        for pod in pods.items {
+            let pod_name = pod.metadata.name.clone().unwrap_or_default();
+            if namespace == OPERATOR_NAMESPACE && !pod_name.starts_with(OPERATOR_POD_PREFIX) {
+                continue;
+            }
+            let names: Vec<String> = pod.spec.iter().flat_map(|s| s.containers.iter().map(|c| c.name.clone())).collect();
+            for container in names {
+                let log_params = LogParams {
+                    container: Some(container.clone()),
+                    since_seconds: Some(since_seconds),
+                    timestamps: true,
+                    ..LogParams::default()
+                };
+                let log = match k8s::block_on_retry(&rt, "get Pod logs", || api.logs(&pod_name, &log_params)) {
+                    Ok(log) => log,
+                    Err(e) => {
+                        warnings::warn(format!("Could not read logs of {}/{}/{}: {}", namespace, pod_name, container, e));
+                        continue;
+                    }
+                };
+                containers += 1;
+                let source = format!("{}/{}/{}", namespace, pod_name, container);
+                let _ = fs::write(logs_dir.join(format!("{}_{}_{}.log", namespace, pod_name, container)), &log);
+                excerpts.extend(error_lines(&source, &log));
+            }
+        }
+    }
+    eprintln!("Scanned {} controller log(s): {} error line(s)", containers, excerpts.len());
+
+    let json = serde_json::to_string_pretty(&excerpts)?;
+    fs::write(output_dir.join("results").join("log-errors.json"), json).context("Failed to write log-errors.json")?;
+    Ok(excerpts)
+}
+
+/// Error lines of a log fetched with timestamps (`<RFC 3339> <message>` per line).
+fn error_lines(source: &str, log: &str) -> Vec<LogExcerpt> {
+    log.lines()
+        .filter_map(|l| l.split_once(' '))
+        .filter(|(_, message)| ERROR_SIGNATURE.is_match(message))
+        .map(|(timestamp, message)| LogExcerpt {
+            source: source.to_string(),
+            timestamp: timestamp.to_string(),
+            line: message.chars().take(MAX_LINE_LEN).collect(),
+        })
+        .collect()
+}
+
+/// Attach to each failed test the error lines logged while its scenario ran
+/// (plus slack). A scenario without an end runs until the next one starts, or
+/// until `ended` if it was the last.
+pub fn attach(tests: &mut [results::TestCaseResult], timeline: &[ScenarioSpan], errors: &[LogExcerpt], ended: DateTime<Utc>) {
+    let parse = |t: &str| DateTime::parse_from_rfc3339(t).ok().map(|t| t.with_timezone(&Utc));
+    for test in tests.iter_mut().filter(|t| !t.passed) {
+        let Some(i) = timeline.iter().position(|s| s.scenario == test.scenario && s.spec == test.spec)
+            .or_else(|| timeline.iter().position(|s| s.scenario == test.scenario))
+        else {
+            continue;
+        };
+        let Some(start) = parse(&timeline[i].started_at) else { continue };
+        let end = timeline[i].ended_at.as_deref().and_then(parse)
+            .or_else(|| timeline.get(i + 1).and_then(|s| parse(&s.started_at)))
+            .unwrap_or(ended)
+            + chrono::Duration::seconds(CORRELATION_SLACK_SECS);
+        test.log_excerpts = errors
+            .iter()
+            .filter(|e| parse(&e.timestamp).is_some_and(|t| t >= start && t <= end))
+            .take(MAX_EXCERPTS_PER_TEST)
+            .cloned()
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_lines_and_attach() {
+        let log = "2026-01-01T10:00:05.000000000Z {\"level\":\"info\",\"msg\":\"reconciled\"}\n\
+                   2026-01-01T10:00:10.000000000Z {\"level\":\"error\",\"msg\":\"failed to reconcile\"}\n\
+                   2026-01-01T10:05:00.000000000Z E0101 10:05:00.000000       1 controller.go:42] sync failed\n\
+                   2026-01-01T10:09:00.000000000Z panic: runtime error: invalid memory address\n";
+        let errors = error_lines("openshift-pipelines/controller-0/controller", log);
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].line.contains("failed to reconcile"));
+
+        let span = |scenario: &str, started_at: &str, ended_at: Option<&str>| ScenarioSpan {
+            spec: "s".into(),
+            scenario: scenario.into(),
+            started_at: started_at.into(),
+            ended_at: ended_at.map(String::from),
+            duration_secs: None,
+            passed: Some(false),
+        };
+        let starts = vec![
+            span("a", "2026-01-01T10:00:00Z", None),
+            span("b", "2026-01-01T10:04:00Z", Some("2026-01-01T10:04:50Z")),
+        ];
+        let test = |scenario: &str, passed: bool| results::TestCaseResult {
+            spec: "s".into(),
+            scenario: scenario.into(),
+            passed,
+            duration_secs: 0.0,
+            error_message: None,
+            components: Vec::new(),
+            log_excerpts: Vec::new(),
+        };
+        let mut tests = vec![test("a", false), test("b", false), test("c", false)];
+        let ended = DateTime::parse_from_rfc3339("2026-01-01T10:06:00Z").unwrap().with_timezone(&Utc);
+        attach(&mut tests, &starts, &errors, ended);
+        assert_eq!(tests[0].log_excerpts.len(), 1);
+        assert_eq!(tests[1].log_excerpts.len(), 1);
+        assert!(tests[1].log_excerpts[0].line.contains("sync failed"));
+        assert!(tests[2].log_excerpts.is_empty());
+    }
+}
@@ -0,0 +1,316 @@
+//! `streamstress matrix`: the same component set across OCP versions and
+//! operator release branches.
+//!
+//! Each OCP version is a cluster reached through a kube context
+//! (`--context 4.17=<context>`). Every (OCP version, operator branch) cell is a
+//! `run` of its own, against the release-tests branch of the operator branch and,
+//! when auto-setup installs the operator, its `pipelines-X.Y` channel. Clusters
+//! run in parallel and the branches of one cluster one after another. The cells
+//! share the matrix id as their batch id and carry `ocp` and `operator_branch`
+//! metadata, so the published runs are grouped and filtered by their matrix
+//! coordinates on the dashboard. `matrix-summary.json` collects every cell's
+//! results. With `--publish` every cell with results is published once the
+//! matrix finishes, and the summary, with each cell's run id, goes to
+//! `matrix/<matrix id>.json` on gh-pages.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::{exec, results, timestamp};
+
+/// Run metadata key of a cell's OCP version.
+pub const OCP_META_KEY: &str = "ocp";
+
+/// Run metadata key of a cell's operator branch.
+pub const BRANCH_META_KEY: &str = "operator_branch";
+
+/// One `--context OCP=CONTEXT` flag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextSpec {
+    pub ocp: String,
+    pub context: String,
+}
+
+/// Parse `OCP=CONTEXT`. Used by clap's value_parser for --context.
+pub fn parse_context(s: &str) -> std::result::Result<ContextSpec, String> {
+    match s.split_once('=') {
+        Some((ocp, context)) if !ocp.trim().is_empty() && !context.trim().is_empty() => Ok(ContextSpec {
+            ocp: ocp.trim().to_string(),
+            context: context.trim().to_string(),
+        }),
+        _ => Err(format!("Expected OCP=CONTEXT (e.g. 4.17=ci-417), got '{}'", s)),
+    }
+}
+
+/// One run of the matrix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    pub ocp: String,
+    pub operator_branch: String,
+    /// Kube context of the OCP version's cluster
+    pub context: String,
+}
+
+impl Cell {
+    /// Output directory of the cell under the matrix output directory, e.g.
+    /// `ocp-4.17_release-v1.17`.
+    pub fn dir_name(&self) -> String {
+        format!("ocp-{}_{}", self.ocp, self.operator_branch.replace('/', "-"))
+    }
+}
+
+/// Every (OCP version, operator branch) cell, by OCP version then branch. Each
+/// OCP version needs a context; a context for a version not in `ocp` is an error
+/// too, as it is most likely a typo.
+pub fn cells(ocp: &[String], branches: &[String], contexts: &[ContextSpec]) -> Result<Vec<Cell>> {
+    if ocp.is_empty() || branches.is_empty() {
+        bail!("A matrix needs at least one --ocp version and one --operator-branch");
+    }
+    let by_ocp: BTreeMap<&str, &str> = contexts.iter().map(|c| (c.ocp.as_str(), c.context.as_str())).collect();
+    if let Some(extra) = by_ocp.keys().find(|v| !ocp.iter().any(|o| o == *v)) {
+        bail!("--context given for OCP {} which is not in --ocp {}", extra, ocp.join(","));
+    }
+    let mut cells = Vec::new();
+    for version in ocp {
+        let context = by_ocp
+            .get(version.as_str())
+            .with_context(|| format!("No kube context for OCP {}: pass --context {}=<context>", version, version))?;
+        for branch in branches {
+            cells.push(Cell {
+                ocp: version.clone(),
+                operator_branch: branch.clone(),
+                context: context.to_string(),
+            });
+        }
+    }
+    Ok(cells)
+}
+
+/// Id shared by the runs of one matrix, e.g. `matrix-20250205T140307Z`.
+pub fn matrix_id() -> String {
+    timestamp::id("matrix", &timestamp::now_rfc3339())
+}
+
+/// OLM channel of an operator release branch: `release-v1.17` -> `pipelines-1.17`.
+/// None for branches without a version (e.g. `main`).
+pub fn operator_channel(branch: &str) -> Option<String> {
+    let version = branch.strip_prefix("release-v")?;
+    let mut parts = version.split('.');
+    let (major, minor) = (parts.next()?, parts.next()?);
+    if major.parse::<u32>().is_err() || minor.parse::<u32>().is_err() {
+        return None;
+    }
+    Some(format!("pipelines-{}.{}", major, minor))
+}
+
+/// Write a kubeconfig holding only `context` (with its credentials inlined) to
+/// `dir`, for a cell's run to use as KUBECONFIG. Returns its path.
+pub fn write_kubeconfig(context: &str, dir: &Path) -> Result<PathBuf> {
+    let out = exec::run_cmd("oc", &["config", "view", "--minify", "--flatten", "--context", context])
+        .with_context(|| format!("Failed to read kube context '{}'", context))?;
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let path = dir.join("kubeconfig");
+    std::fs::write(&path, out.stdout).with_context(|| format!("Failed to write {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(path)
+}
+
+/// Where `--publish` sends a matrix's cells and summary.
+#[derive(Debug, Clone, Default)]
+pub struct PublishOptions {
+    /// Git remote with the gh-pages branch (default: origin of the current repo)
+    pub remote: Option<String>,
+    /// Label of every published cell
+    pub label: Option<String>,
+}
+
+/// Outcome of one cell in `matrix-summary.json`.
+#[derive(Debug, Serialize)]
+pub struct CellSummary {
+    pub ocp: String,
+    pub operator_branch: String,
+    pub context: String,
+    pub exit_code: i32,
+    /// "passed", "failed", "error", or "submitted" (tests still running in-cluster)
+    pub status: &'static str,
+    pub total: Option<usize>,
+    pub passed: Option<usize>,
+    pub failed: Option<usize>,
+    /// Cell output directory, relative to the matrix output directory
+    pub output_dir: String,
+    /// Dashboard run id of the cell, with `--publish`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+}
+
+/// `matrix-summary.json`: every cell of one matrix.
+#[derive(Debug, Serialize)]
+pub struct MatrixSummary {
+    pub matrix_id: String,
+    pub components: Option<String>,
+    pub ocp: Vec<String>,
+    pub operator_branches: Vec<String>,
+    pub cells: Vec<CellSummary>,
+}
+
+/// Collect each cell's results from `output_dir/<cell dir>/` into a summary.
+pub fn build_summary(
+    matrix_id: &str,
+    components: Option<&str>,
+    outcomes: &[(Cell, i32)],
+    output_dir: &Path,
+) -> MatrixSummary {
+    let mut ocp: Vec<String> = Vec::new();
+    let mut branches: Vec<String> = Vec::new();
+    let mut cells = Vec::new();
+    for (cell, exit_code) in outcomes {
+        if !ocp.contains(&cell.ocp) {
+            ocp.push(cell.ocp.clone());
+        }
+        if !branches.contains(&cell.operator_branch) {
+            branches.push(cell.operator_branch.clone());
+        }
+        let dir = cell.dir_name();
+        let result = results::read_results_json(&output_dir.join(&dir).join("results/results.json")).ok();
+        let status = match (exit_code, &result) {
+            (0, None) => "submitted",
+            (0, Some(_)) => "passed",
+            (1, _) => "failed",
+            _ => "error",
+        };
+        cells.push(CellSummary {
+            ocp: cell.ocp.clone(),
+            operator_branch: cell.operator_branch.clone(),
+            context: cell.context.clone(),
+            exit_code: *exit_code,
+            status,
+            total: result.as_ref().map(|r| r.total),
+            passed: result.as_ref().map(|r| r.passed),
+            failed: result.as_ref().map(|r| r.failed),
+            output_dir: dir,
+            run_id: None,
+        });
+    }
+    MatrixSummary {
+        matrix_id: matrix_id.to_string(),
+        components: components.map(str::to_string),
+        ocp,
+        operator_branches: branches,
+        cells,
+    }
+}
+
+/// Pass counts as an OCP version x operator branch table.
+pub fn render_summary(summary: &MatrixSummary) -> String {
+    let mut out = format!("# Matrix {}\n\n| OCP |", summary.matrix_id);
+    for branch in &summary.operator_branches {
+        out.push_str(&format!(" {} |", branch));
+    }
+    out.push_str(&format!("\n|---|{}\n", "---|".repeat(summary.operator_branches.len())));
+    for ocp in &summary.ocp {
+        out.push_str(&format!("| {} |", ocp));
+        for branch in &summary.operator_branches {
+            let cell = summary.cells.iter().find(|c| &c.ocp == ocp && &c.operator_branch == branch);
+            let text = match cell {
+                Some(c) => match (c.passed, c.total) {
+                    (Some(passed), Some(total)) => format!("{} {}/{}", c.status, passed, total),
+                    _ => c.status.to_string(),
+                },
+                None => "-".to_string(),
+            };
+            out.push_str(&format!(" {} |", text));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Path of a matrix's `matrix-summary.json` on gh-pages, with `--publish`.
+pub fn published_summary_path(matrix_id: &str) -> String {
+    format!("matrix/{}.json", matrix_id)
+}
+
+/// Write `matrix-summary.json` to `output_dir`. Returns its path.
+pub fn write_summary(output_dir: &Path, summary: &MatrixSummary) -> Result<PathBuf> {
+    let path = output_dir.join("matrix-summary.json");
+    std::fs::write(&path, serde_json::to_string_pretty(summary)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contexts(pairs: &[(&str, &str)]) -> Vec<ContextSpec> {
+        pairs.iter().map(|(o, c)| ContextSpec { ocp: o.to_string(), context: c.to_string() }).collect()
+    }
+
+    #[test]
+    fn test_parse_context() {
+        assert_eq!(parse_context("4.17=ci-417").unwrap(), ContextSpec { ocp: "4.17".into(), context: "ci-417".into() });
+        assert!(parse_context("4.17").is_err());
+        assert!(parse_context("=ci").is_err());
+    }
+
+    #[test]
+    fn test_cells() {
+        let ocp = vec!["4.16".to_string(), "4.17".to_string()];
+        let branches = vec!["release-v1.17".to_string(), "release-v1.18".to_string()];
+        let cells = cells(&ocp, &branches, &contexts(&[("4.16", "a"), ("4.17", "b")])).unwrap();
+        let names: Vec<(String, &str)> = cells.iter().map(|c| (c.dir_name(), c.context.as_str())).collect();
+        assert_eq!(
+            names,
+            vec![
+                ("ocp-4.16_release-v1.17".to_string(), "a"),
+                ("ocp-4.16_release-v1.18".to_string(), "a"),
+                ("ocp-4.17_release-v1.17".to_string(), "b"),
+                ("ocp-4.17_release-v1.18".to_string(), "b"),
+            ]
+        );
+        let err = super::cells(&ocp, &branches, &contexts(&[("4.16", "a")])).unwrap_err();
+        assert!(err.to_string().contains("--context 4.17="), "{err:#}");
+        assert!(super::cells(&ocp, &branches, &contexts(&[("4.16", "a"), ("4.17", "b"), ("4.18", "c")])).is_err());
+    }
+
+    #[test]
+    fn test_operator_channel() {
+        assert_eq!(operator_channel("release-v1.17").as_deref(), Some("pipelines-1.17"));
+        assert_eq!(operator_channel("release-v1.17.x").as_deref(), Some("pipelines-1.17"));
+        assert_eq!(operator_channel("main"), None);
+        assert_eq!(operator_channel("release-vnext"), None);
+    }
+
+    #[test]
+    fn test_render_summary() {
+        let cell = |ocp: &str, branch: &str, status, passed| CellSummary {
+            ocp: ocp.into(),
+            operator_branch: branch.into(),
+            context: String::new(),
+            exit_code: 0,
+            status,
+            total: passed.map(|_| 10),
+            passed,
+            failed: passed.map(|p| 10 - p),
+            output_dir: String::new(),
+            run_id: None,
+        };
+        let summary = MatrixSummary {
+            matrix_id: "matrix-1".into(),
+            components: None,
+            ocp: vec!["4.16".into(), "4.17".into()],
+            operator_branches: vec!["release-v1.17".into()],
+            cells: vec![cell("4.16", "release-v1.17", "passed", Some(10)), cell("4.17", "release-v1.17", "submitted", None)],
+        };
+        assert_eq!(
+            render_summary(&summary),
+            "# Matrix matrix-1\n\n| OCP | release-v1.17 |\n|---|---|\n| 4.16 | passed 10/10 |\n| 4.17 | submitted |\n"
+        );
+    }
+}
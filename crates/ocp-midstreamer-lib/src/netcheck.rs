@@ -0,0 +1,176 @@
+//! Network reachability checks for `streamstress check`.
+//!
+//! Builds clone from GitHub and pull base images from quay.io, and deploys pull
+//! the built images from the internal registry. When one of those is not
+//! reachable, the run fails late with a build or test error that looks like a
+//! product bug. These checks curl GitHub and quay.io from this host, and the
+//! internal registry, GitHub and quay.io from a short-lived pod, so network,
+//! DNS and egress problems show up in `check` instead.
+
+use std::time::Duration;
+
+use crate::exec::{self, API_TIMEOUT};
+use crate::timestamp;
+use crate::types::CheckResult;
+
+/// Targets reached from this host, as (label, URL).
+const HOST_TARGETS: &[(&str, &str)] = &[("GitHub", "https://github.com"), ("quay.io", "https://quay.io/v2/")];
+
+/// Targets reached from inside the cluster, as (label, URL).
+const CLUSTER_TARGETS: &[(&str, &str)] = &[
+    ("internal registry", "https://image-registry.openshift-image-registry.svc:5000/healthz"),
+    ("GitHub", "https://github.com"),
+    ("quay.io", "https://quay.io/v2/"),
+];
+
+/// Image of the probe pod; ubi-minimal ships curl.
+const PROBE_IMAGE: &str = "registry.access.redhat.com/ubi9/ubi-minimal";
+
+const PROBE_NAMESPACE: &str = "default";
+
+/// Seconds each curl may take.
+const CURL_TIMEOUT_SECS: u32 = 10;
+
+/// Budget for the probe pod, image pull included.
+const POD_TIMEOUT: Duration = Duration::from_secs(180);
+
+const EGRESS_HINT: &str = "Check DNS and egress from the cluster: oc get proxy/cluster -o yaml, and the cluster's firewall";
+
+/// Reach GitHub and quay.io from this host.
+pub fn host_checks() -> Vec<CheckResult> {
+    HOST_TARGETS
+        .iter()
+        .map(|(label, url)| {
+            let timeout = CURL_TIMEOUT_SECS.to_string();
+            let args = ["-sS", "-o", "/dev/null", "-w", "%{http_code}", "--max-time", timeout.as_str(), *url];
+            let (code, error) = match exec::run_cmd_unchecked_timeout("curl", &args, API_TIMEOUT) {
+                Ok(r) => (r.stdout.trim().to_string(), r.stderr.trim().to_string()),
+                Err(e) => ("000".to_string(), format!("{e:#}")),
+            };
+            result(label, "host", url, &code, &error, "Check this host's DNS, proxy (HTTPS_PROXY) and firewall")
+        })
+        .collect()
+}
+
+/// Reach the internal registry, GitHub and quay.io from a pod in the cluster.
+/// A pod that cannot be started fails every check with the reason.
+pub fn in_cluster_checks() -> Vec<CheckResult> {
+    let name = format!("streamstress-netcheck-{}", timestamp::unix_now());
+    let script = probe_script(CLUSTER_TARGETS);
+    let pod_timeout = format!("--pod-running-timeout={}s", POD_TIMEOUT.as_secs());
+    let args = [
+        "run", name.as_str(), "-n", PROBE_NAMESPACE, "--image", PROBE_IMAGE, "--restart=Never", "--rm", "-i", "--quiet",
+        pod_timeout.as_str(), "--labels", "app=streamstress", "--command", "--", "sh", "-c", script.as_str(),
+    ];
+    let lines = match exec::run_cmd_unchecked_timeout("oc", &args, POD_TIMEOUT + API_TIMEOUT) {
+        Ok(r) if r.timed_out => Err(format!("probe pod timed out after {}s", (POD_TIMEOUT + API_TIMEOUT).as_secs())),
+        Ok(r) if r.exit_code == 0 => Ok(parse_probe_output(&r.stdout)),
+        Ok(r) => Err(format!("probe pod failed: {}", r.stderr.trim())),
+        Err(e) => Err(format!("{e:#}")),
+    };
+    CLUSTER_TARGETS
+        .iter()
+        .map(|(label, url)| match &lines {
+            Ok(lines) => {
+                let (code, error) = lines
+                    .iter()
+                    .find(|(u, _, _)| u.as_str() == *url)
+                    .map(|(_, code, error)| (code.as_str(), error.as_str()))
+                    .unwrap_or(("000", "no output from the probe pod"));
+                result(label, "in-cluster", url, code, error, EGRESS_HINT)
+            }
+            Err(e) => CheckResult {
+                name: check_name(label, "in-cluster"),
+                passed: false,
+                detail: e.clone(),
+                fix_hint: Some(format!(
+                    "Check that pods can start in {} and pull {}",
+                    PROBE_NAMESPACE, PROBE_IMAGE
+                )),
+            },
+        })
+        .collect()
+}
+
+fn check_name(label: &str, from: &str) -> String {
+    format!("network: {} ({})", label, from)
+}
+
+/// Any HTTP response means the target is reachable; curl reports `000` when
+/// it got none (DNS, connect, TLS or timeout failure).
+fn result(label: &str, from: &str, url: &str, code: &str, error: &str, hint: &str) -> CheckResult {
+    let reachable = !code.is_empty() && code != "000";
+    CheckResult {
+        name: check_name(label, from),
+        passed: reachable,
+        detail: if reachable {
+            format!("{} answered HTTP {}", url, code)
+        } else if error.is_empty() {
+            format!("{} did not answer", url)
+        } else {
+            format!("{}: {}", url, error)
+        },
+        fix_hint: (!reachable).then(|| hint.to_string()),
+    }
+}
+
+/// Shell script curling each target and printing `<url> <code> <error>` per line.
+fn probe_script(targets: &[(&str, &str)]) -> String {
+    let urls: Vec<&str> = targets.iter().map(|(_, url)| *url).collect();
+    format!(
+        "for u in {}; do c=$(curl -skS -o /dev/null -w '%{{http_code}}' --max-time {} \"$u\" 2>/tmp/err); \
+         echo \"$u $c $(head -c 200 /tmp/err | tr '\\n' ' ')\"; done",
+        urls.join(" "),
+        CURL_TIMEOUT_SECS
+    )
+}
+
+/// Parse the probe script's output into (url, code, error) lines.
+fn parse_probe_output(out: &str) -> Vec<(String, String, String)> {
+    out.lines()
+        .filter_map(|line| {
+            let mut parts = line.trim().splitn(3, ' ');
+            let url = parts.next()?.to_string();
+            let code = parts.next()?.to_string();
+            let error = parts.next().unwrap_or_default().trim().to_string();
+            url.starts_with("https://").then_some((url, code, error))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_probe_output() {
+        let out = "https://github.com 200 \n\
+                   https://quay.io/v2/ 000 curl: (6) Could not resolve host: quay.io \n\
+                   pod default/x terminated\n";
+        let lines = parse_probe_output(out);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], ("https://github.com".to_string(), "200".to_string(), String::new()));
+        assert_eq!(lines[1].1, "000");
+        assert_eq!(lines[1].2, "curl: (6) Could not resolve host: quay.io");
+    }
+
+    #[test]
+    fn test_result() {
+        let ok = result("quay.io", "host", "https://quay.io/v2/", "401", "", "hint");
+        assert!(ok.passed);
+        assert_eq!(ok.name, "network: quay.io (host)");
+        assert!(ok.fix_hint.is_none());
+
+        let down = result("GitHub", "in-cluster", "https://github.com", "000", "curl: (28) timed out", "hint");
+        assert!(!down.passed);
+        assert_eq!(down.detail, "https://github.com: curl: (28) timed out");
+        assert_eq!(down.fix_hint.as_deref(), Some("hint"));
+    }
+
+    #[test]
+    fn test_probe_script() {
+        let script = probe_script(&[("a", "https://a.example"), ("b", "https://b.example/v2/")]);
+        assert!(script.starts_with("for u in https://a.example https://b.example/v2/; do"));
+        assert!(script.contains("-w '%{http_code}' --max-time 10"));
+    }
+}
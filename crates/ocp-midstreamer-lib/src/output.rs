@@ -0,0 +1,72 @@
+//! Machine-readable output for `--output json|yaml`.
+//!
+//! Structured documents go to stdout; progress and diagnostics stay on stderr so
+//! the output can be piped straight into `jq` or `yq`.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+/// Output format selected with the global `--output` flag.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq)]
+pub enum OutputFormat {
+    /// Human-readable tables and summaries
+    #[default]
+    Text,
+    Json,
+    Yaml,
+}
+
+impl OutputFormat {
+    /// True for formats meant for scripts rather than people.
+    pub fn is_structured(self) -> bool {
+        self != OutputFormat::Text
+    }
+}
+
+/// Render `value` in a structured format. Text has no generic rendering;
+/// callers print their own tables for it.
+pub fn render<T: Serialize + ?Sized>(format: OutputFormat, value: &T) -> Result<String> {
+    match format {
+        OutputFormat::Json => {
+            let mut s = serde_json::to_string_pretty(value).context("Failed to serialize JSON")?;
+            s.push('\n');
+            Ok(s)
+        }
+        OutputFormat::Yaml => serde_yaml::to_string(value).context("Failed to serialize YAML"),
+        OutputFormat::Text => anyhow::bail!("text output has no structured rendering"),
+    }
+}
+
+/// Print `value` to stdout in a structured format.
+pub fn print<T: Serialize + ?Sized>(format: OutputFormat, value: &T) -> Result<()> {
+    print!("{}", render(format, value)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Sample {
+        name: &'static str,
+        passed: bool,
+    }
+
+    #[test]
+    fn test_render_json_and_yaml() {
+        let sample = Sample { name: "oc", passed: true };
+        let json = render(OutputFormat::Json, &sample).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["name"], "oc");
+
+        let yaml = render(OutputFormat::Yaml, &sample).unwrap();
+        assert!(yaml.contains("name: oc"));
+        assert!(yaml.contains("passed: true"));
+    }
+
+    #[test]
+    fn test_render_text_is_error() {
+        assert!(render(OutputFormat::Text, &[1, 2]).is_err());
+    }
+}
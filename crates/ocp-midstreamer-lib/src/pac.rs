@@ -0,0 +1,344 @@
+//! Pipelines-as-Code smoke test.
+//!
+//! Gauge coverage of Pipelines-as-Code is thin in midstream runs, so a run can
+//! exercise it end to end: point a GitHub test repository's webhook at the PaC
+//! controller, install a Repository CR for it, push a commit carrying a
+//! `.tekton/` PipelineRun, and check that PaC triggers that PipelineRun and it
+//! succeeds. The outcome is reported as a synthetic test in the run's results.
+
+use anyhow::{Context, Result, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use k8s_openapi::api::core::v1::Secret;
+use kube::api::{Api, ApiResource, DeleteParams, DynamicObject, ListParams, Patch, PatchParams};
+use serde_json::{Value, json};
+use std::time::{Duration, Instant};
+
+use crate::{audit, exec, github, k8s, results, timestamp, warnings};
+
+/// Spec name of the synthetic test in results.
+pub const SPEC: &str = "Pipelines-as-Code smoke";
+
+/// Namespace the Repository CR and the triggered PipelineRuns live in.
+const SMOKE_NAMESPACE: &str = "streamstress-pac-smoke";
+
+/// Name of the Repository CR and of the Secret with its token and webhook secret.
+const SMOKE_NAME: &str = "streamstress-pac-smoke";
+
+/// File in the test repository holding the PipelineRun PaC runs on push.
+const PIPELINERUN_PATH: &str = ".tekton/streamstress-smoke.yaml";
+
+const OPERATOR_NAMESPACE: &str = "openshift-pipelines";
+
+/// Route the PaC controller receives webhooks on.
+const CONTROLLER_ROUTE: &str = "pipelines-as-code-controller";
+
+/// How long PaC may take to create the PipelineRun and the PipelineRun to finish.
+const SMOKE_TIMEOUT: Duration = Duration::from_secs(600);
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Run the smoke test against the GitHub repository `repo` (owner/name or URL)
+/// and report it as a synthetic test.
+pub fn smoke_test(repo: &str) -> results::TestCaseResult {
+    let started = Instant::now();
+    let outcome = run_smoke(repo);
+    match &outcome {
+        Ok(pipelinerun) => eprintln!("  PipelineRun {} triggered by PaC succeeded", pipelinerun),
+        Err(e) => eprintln!("  Pipelines-as-Code smoke test FAILED: {e:#}"),
+    }
+    results::TestCaseResult {
+        spec: SPEC.to_string(),
+        scenario: "Push to the test repository triggers a successful PipelineRun".to_string(),
+        passed: outcome.is_ok(),
+        duration_secs: started.elapsed().as_secs_f64(),
+        error_message: outcome.err().map(|e| format!("{e:#}")),
+        components: vec!["pipelines-as-code".to_string()],
+        log_excerpts: Vec::new(),
+    }
+}
+
+fn run_smoke(repo: &str) -> Result<String> {
+    let (owner, name) = github::parse_github_url(repo)?;
+    let full_name = format!("{}/{}", owner, name);
+    let token = github_token()?;
+    exec::register_secret(&token);
+    let webhook_secret = random_hex();
+
+    let (rt, client) = k8s::create_kube_client()?;
+    let controller_url = controller_url(&rt, &client)?;
+    k8s::ensure_namespace(&rt, &client, SMOKE_NAMESPACE)?;
+    apply_repository(&rt, &client, &full_name, &token, &webhook_secret)?;
+    eprintln!("  Installed Repository {}/{} for https://github.com/{}", SMOKE_NAMESPACE, SMOKE_NAME, full_name);
+
+    let hook_id = match create_webhook(&full_name, &controller_url, &webhook_secret) {
+        Ok(id) => id,
+        Err(e) => {
+            delete_repository(&rt, &client);
+            return Err(e);
+        }
+    };
+    eprintln!("  Created webhook {} on {} -> {}", hook_id, full_name, controller_url);
+
+    let outcome = push_commit(&full_name).and_then(|sha| {
+        eprintln!("  Pushed {} ({}); waiting for PaC to trigger a PipelineRun", PIPELINERUN_PATH, &sha[..sha.len().min(12)]);
+        wait_for_pipelinerun(&rt, &client, &sha)
+            .with_context(|| webhook_diagnosis(&full_name, hook_id))
+    });
+
+    let deleted = github::api_request("DELETE", &format!("repos/{}/hooks/{}", full_name, hook_id), None, &[]);
+    if !deleted.is_ok_and(|r| r.is_success()) {
+        warnings::warn(format!("Could not delete webhook {} on {}; remove it by hand", hook_id, full_name));
+    }
+    delete_repository(&rt, &client);
+    outcome
+}
+
+/// Token for the GitHub API and for PaC to read the repository and report status.
+fn github_token() -> Result<String> {
+    if let Some(token) = ["GITHUB_TOKEN", "GH_TOKEN"].iter().filter_map(|k| std::env::var(k).ok()).find(|t| !t.is_empty()) {
+        return Ok(token);
+    }
+    let out = exec::run_cmd("gh", &["auth", "token"])
+        .context("No GitHub token: set GITHUB_TOKEN or log in with `gh auth login`")?;
+    Ok(out.stdout.trim().to_string())
+}
+
+/// Webhook secret shared by GitHub and the Repository CR.
+fn random_hex() -> String {
+    use std::hash::{BuildHasher, Hasher};
+    (0..2)
+        .map(|_| {
+            let mut h = std::collections::hash_map::RandomState::new().build_hasher();
+            h.write_u128(timestamp::unix_now() as u128 ^ std::process::id() as u128);
+            format!("{:016x}", h.finish())
+        })
+        .collect()
+}
+
+/// Public URL of the PaC controller's webhook endpoint.
+fn controller_url(rt: &tokio::runtime::Runtime, client: &kube::Client) -> Result<String> {
+    let ar = ApiResource {
+        group: "route.openshift.io".into(),
+        version: "v1".into(),
+        api_version: "route.openshift.io/v1".into(),
+        kind: "Route".into(),
+        plural: "routes".into(),
+    };
+    let api: Api<DynamicObject> = Api::namespaced_with(client.clone(), OPERATOR_NAMESPACE, &ar);
+    let route = k8s::block_on_retry(rt, "get PaC controller Route", || api.get_opt(CONTROLLER_ROUTE))?
+        .with_context(|| format!("Route {}/{} not found; is Pipelines-as-Code enabled in the TektonConfig?", OPERATOR_NAMESPACE, CONTROLLER_ROUTE))?;
+    let host = route.data.pointer("/spec/host").and_then(|h| h.as_str()).context("PaC controller Route has no host")?;
+    Ok(format!("https://{}", host))
+}
+
+/// Apply the Secret and the Repository CR that tie the test repository to the namespace.
+fn apply_repository(rt: &tokio::runtime::Runtime, client: &kube::Client, full_name: &str, token: &str, webhook_secret: &str) -> Result<()> {
+    let pp = PatchParams::apply("streamstress").force();
+
+    let secrets: Api<Secret> = Api::namespaced(client.clone(), SMOKE_NAMESPACE);
+    let secret: Secret = serde_json::from_value(json!({
+        "apiVersion": "v1",
+        "kind": "Secret",
+        "metadata": {"name": SMOKE_NAME, "labels": {"app": "streamstress"}},
+        "stringData": {"token": token, "webhook-secret": webhook_secret}
+    }))?;
+    let patch = Patch::Apply(&secret);
+    let result = k8s::block_on_retry(rt, "apply PaC smoke Secret", || secrets.patch(SMOKE_NAME, &pp, &patch));
+    audit::record_api("apply", "Secret", Some(SMOKE_NAMESPACE), SMOKE_NAME, result.is_ok());
+    result.context("Failed to apply the PaC smoke Secret")?;
+
+    let repos: Api<DynamicObject> = Api::namespaced_with(client.clone(), SMOKE_NAMESPACE, &repository_resource());
+    let repository: DynamicObject = serde_json::from_value(json!({
+        "apiVersion": "pipelinesascode.tekton.dev/v1alpha1",
+        "kind": "Repository",
+        "metadata": {"name": SMOKE_NAME, "labels": {"app": "streamstress"}},
+        "spec": {
+            "url": format!("https://github.com/{}", full_name),
+            "git_provider": {
+                "secret": {"name": SMOKE_NAME, "key": "token"},
+                "webhook_secret": {"name": SMOKE_NAME, "key": "webhook-secret"}
+            }
+        }
+    }))?;
+    let patch = Patch::Apply(&repository);
+    let result = k8s::block_on_retry(rt, "apply Repository", || repos.patch(SMOKE_NAME, &pp, &patch));
+    audit::record_api("apply", "Repository", Some(SMOKE_NAMESPACE), SMOKE_NAME, result.is_ok());
+    result.context("Failed to apply the Repository CR (is Pipelines-as-Code installed?)")?;
+    Ok(())
+}
+
+fn repository_resource() -> ApiResource {
+    ApiResource {
+        group: "pipelinesascode.tekton.dev".into(),
+        version: "v1alpha1".into(),
+        api_version: "pipelinesascode.tekton.dev/v1alpha1".into(),
+        kind: "Repository".into(),
+        plural: "repositories".into(),
+    }
+}
+
+/// Remove the Repository CR and the Secret holding the token. The namespace and its
+/// PipelineRuns are kept for inspection.
+fn delete_repository(rt: &tokio::runtime::Runtime, client: &kube::Client) {
+    let dp = DeleteParams::default();
+    let repos: Api<DynamicObject> = Api::namespaced_with(client.clone(), SMOKE_NAMESPACE, &repository_resource());
+    let result = k8s::block_on_retry(rt, "delete Repository", || repos.delete(SMOKE_NAME, &dp));
+    audit::record_api("delete", "Repository", Some(SMOKE_NAMESPACE), SMOKE_NAME, result.is_ok());
+    let secrets: Api<Secret> = Api::namespaced(client.clone(), SMOKE_NAMESPACE);
+    let result = k8s::block_on_retry(rt, "delete Secret", || secrets.delete(SMOKE_NAME, &dp));
+    audit::record_api("delete", "Secret", Some(SMOKE_NAMESPACE), SMOKE_NAME, result.is_ok());
+    if result.is_err() {
+        warnings::warn(format!("Could not delete Secret {}/{}; it holds a GitHub token", SMOKE_NAMESPACE, SMOKE_NAME));
+    }
+}
+
+/// Create a push webhook to the PaC controller; returns its id.
+fn create_webhook(full_name: &str, controller_url: &str, secret: &str) -> Result<u64> {
+    let body = json!({
+        "name": "web",
+        "active": true,
+        "events": ["push", "pull_request"],
+        "config": {
+            "url": controller_url,
+            "content_type": "json",
+            "secret": secret,
+            // Test clusters usually serve routes with self-signed certificates
+            "insecure_ssl": "1"
+        }
+    });
+    let resp = github::api_request("POST", &format!("repos/{}/hooks", full_name), Some(&body), &[])?;
+    if !resp.is_success() {
+        bail!(
+            "Could not create a webhook on {} (HTTP {}); the token needs admin:repo_hook on the test repository",
+            full_name,
+            resp.status
+        );
+    }
+    resp.json()?["id"].as_u64().context("GitHub returned a webhook without an id")
+}
+
+/// The `.tekton/` PipelineRun PaC runs on pushes to `branch`; `run_id` makes every push a change.
+fn pipelinerun_yaml(branch: &str, run_id: &str) -> String {
+    format!(
+        r#"# Written by streamstress' Pipelines-as-Code smoke test ({run_id})
+apiVersion: tekton.dev/v1
+kind: PipelineRun
+metadata:
+  generateName: streamstress-pac-smoke-
+  annotations:
+    pipelinesascode.tekton.dev/on-event: "[push]"
+    pipelinesascode.tekton.dev/on-target-branch: "[{branch}]"
+spec:
+  pipelineSpec:
+    tasks:
+      - name: smoke
+        taskSpec:
+          steps:
+            - name: echo
+              image: registry.access.redhat.com/ubi9/ubi-minimal
+              script: echo "streamstress smoke {run_id}"
+"#
+    )
+}
+
+/// Commit a new version of the PipelineRun to the default branch; returns the commit sha.
+fn push_commit(full_name: &str) -> Result<String> {
+    let repo = github::api_request("GET", &format!("repos/{}", full_name), None, &[])?;
+    if !repo.is_success() {
+        bail!("Test repository {} not found or not accessible (HTTP {})", full_name, repo.status);
+    }
+    let branch = repo.json()?["default_branch"].as_str().unwrap_or("main").to_string();
+
+    let contents = format!("repos/{}/contents/{}", full_name, PIPELINERUN_PATH);
+    let existing = github::api_request("GET", &format!("{}?ref={}", contents, branch), None, &[])?;
+    let existing_sha = existing.is_success().then(|| existing.json().ok()).flatten().and_then(|j| j["sha"].as_str().map(String::from));
+
+    let run_id = timestamp::now_rfc3339();
+    let mut body = json!({
+        "message": format!("streamstress PaC smoke test {}", run_id),
+        "content": STANDARD.encode(pipelinerun_yaml(&branch, &run_id)),
+        "branch": branch,
+    });
+    if let Some(sha) = existing_sha {
+        body["sha"] = json!(sha);
+    }
+    let resp = github::api_request("PUT", &contents, Some(&body), &[])?;
+    if !resp.is_success() {
+        bail!("Could not push {} to {} (HTTP {}): {}", PIPELINERUN_PATH, full_name, resp.status, resp.body.trim());
+    }
+    resp.json()?["commit"]["sha"].as_str().map(String::from).context("GitHub returned no commit sha")
+}
+
+/// Wait for the PipelineRun PaC creates for commit `sha` to finish; returns its name.
+fn wait_for_pipelinerun(rt: &tokio::runtime::Runtime, client: &kube::Client, sha: &str) -> Result<String> {
+    let ar = ApiResource {
+        group: "tekton.dev".into(),
+        version: "v1".into(),
+        api_version: "tekton.dev/v1".into(),
+        kind: "PipelineRun".into(),
+        plural: "pipelineruns".into(),
+    };
+    let api: Api<DynamicObject> = Api::namespaced_with(client.clone(), SMOKE_NAMESPACE, &ar);
+    let lp = ListParams::default().labels(&format!("pipelinesascode.tekton.dev/sha={}", sha));
+    let started = Instant::now();
+    let mut announced = false;
+    loop {
+        let runs = k8s::block_on_retry(rt, "list PipelineRuns", || api.list(&lp))?;
+        if let Some(run) = runs.items.first() {
+            let name = run.metadata.name.clone().unwrap_or_default();
+            if !announced {
+                eprintln!("  PaC created PipelineRun {}", name);
+                announced = true;
+            }
+            let succeeded = run
+                .data
+                .pointer("/status/conditions")
+                .and_then(|c| c.as_array())
+                .and_then(|c| c.iter().find(|c| c["type"] == "Succeeded"));
+            match succeeded.map(|c| (c["status"].as_str(), c)) {
+                Some((Some("True"), _)) => return Ok(name),
+                Some((Some("False"), c)) => {
+                    bail!("PipelineRun {} failed: {}", name, c["message"].as_str().unwrap_or("unknown reason"))
+                }
+                _ => {}
+            }
+        }
+        if started.elapsed() > SMOKE_TIMEOUT {
+            if announced {
+                bail!("PipelineRun for commit {} did not finish within {}s", sha, SMOKE_TIMEOUT.as_secs());
+            }
+            bail!("PaC created no PipelineRun for commit {} within {}s", sha, SMOKE_TIMEOUT.as_secs());
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Status of the webhook's latest delivery, to tell GitHub-side from cluster-side failures.
+fn webhook_diagnosis(full_name: &str, hook_id: u64) -> String {
+    let deliveries = github::api_request("GET", &format!("repos/{}/hooks/{}/deliveries?per_page=1", full_name, hook_id), None, &[]);
+    let latest: Option<Value> = deliveries.ok().filter(|r| r.is_success()).and_then(|r| r.json().ok()).and_then(|j| j.get(0).cloned());
+    match latest {
+        Some(d) => format!(
+            "latest webhook delivery: {} (HTTP {}); see the pipelines-as-code-controller logs",
+            d["status"].as_str().unwrap_or("unknown"),
+            d["status_code"]
+        ),
+        None => "GitHub reports no webhook delivery; check that the push event reached the controller route".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipelinerun_yaml() {
+        let yaml: serde_yaml::Value = serde_yaml::from_str(&pipelinerun_yaml("main", "2026-01-01T00:00:00Z")).unwrap();
+        let annotations = &yaml["metadata"]["annotations"];
+        assert_eq!(annotations["pipelinesascode.tekton.dev/on-event"], "[push]");
+        assert_eq!(annotations["pipelinesascode.tekton.dev/on-target-branch"], "[main]");
+        assert_eq!(yaml["kind"], "PipelineRun");
+        assert_eq!(random_hex().len(), 32);
+    }
+}
@@ -0,0 +1,186 @@
+//! Apply local patch files or open-PR diffs to cloned upstream source before building.
+//!
+//! `--patches pipeline=fix.diff` applies a local diff; `--patches pipeline=pr/123`
+//! applies the diff of PR #123 from the component's upstream repo. This lets a
+//! candidate fix be tested on top of any ref without being merged upstream.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::component::KNOWN_COMPONENTS;
+use crate::exec;
+use crate::github;
+
+/// Where a patch comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchSource {
+    File(PathBuf),
+    PullRequest(u64),
+}
+
+/// A patch to apply to one component's source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentPatch {
+    pub component: String,
+    pub source: PatchSource,
+}
+
+impl ComponentPatch {
+    /// The `--patches` value this patch was parsed from (e.g. `pipeline=pr/123`).
+    pub fn to_arg(&self) -> String {
+        format!("{}={}", self.component, self.source_display())
+    }
+
+    pub fn source_display(&self) -> String {
+        match &self.source {
+            PatchSource::File(p) => p.display().to_string(),
+            PatchSource::PullRequest(n) => format!("pr/{}", n),
+        }
+    }
+}
+
+/// A patch as recorded in run metadata.
+#[derive(Debug, Clone, Serialize)]
+pub struct PatchRecord {
+    pub component: String,
+    pub source: String,
+}
+
+impl From<&ComponentPatch> for PatchRecord {
+    fn from(p: &ComponentPatch) -> Self {
+        PatchRecord {
+            component: p.component.clone(),
+            source: p.source_display(),
+        }
+    }
+}
+
+/// Parse a `component=source` patch spec. Used by clap's value_parser for --patches.
+///
+/// The file is not checked here: in-cluster Jobs receive the same specs for
+/// metadata only, where the local path does not exist.
+pub fn parse_patch_spec(s: &str) -> std::result::Result<ComponentPatch, String> {
+    let (component, source) = s
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid patch '{}'. Use component=file.diff or component=pr/NNN", s))?;
+    let component = component.trim();
+    let source = source.trim();
+    if !KNOWN_COMPONENTS.contains(&component) {
+        return Err(format!(
+            "Unknown component '{}'. Known: {}",
+            component,
+            KNOWN_COMPONENTS.join(", ")
+        ));
+    }
+    if source.is_empty() {
+        return Err(format!("Missing patch source for '{}'", component));
+    }
+    let source = match source.strip_prefix("pr/") {
+        Some(num) => PatchSource::PullRequest(
+            num.parse()
+                .map_err(|_| format!("Invalid PR number '{}' in patch for '{}'", num, component))?,
+        ),
+        None => PatchSource::File(PathBuf::from(source)),
+    };
+    Ok(ComponentPatch {
+        component: component.to_string(),
+        source,
+    })
+}
+
+/// Patches for `component`, in the order given on the command line.
+pub fn for_component<'a>(patches: &'a [ComponentPatch], component: &str) -> Vec<&'a ComponentPatch> {
+    patches.iter().filter(|p| p.component == component).collect()
+}
+
+/// Apply patches in order to the clone at `source_dir`. Fails on the first patch
+/// that does not apply cleanly.
+pub fn apply_patches(repo_url: &str, source_dir: &Path, patches: &[&ComponentPatch]) -> Result<()> {
+    for patch in patches {
+        let diff = fetch_diff(repo_url, patch)?;
+        let diff_file = tempfile::NamedTempFile::new().context("Failed to create patch temp file")?;
+        fs::write(diff_file.path(), diff).context("Failed to write patch temp file")?;
+
+        let dir = source_dir.to_string_lossy().to_string();
+        let diff_path = diff_file.path().to_string_lossy().to_string();
+        let result = exec::run_cmd_unchecked("git", &["-C", &dir, "apply", "--whitespace=nowarn", &diff_path])?;
+        if result.exit_code != 0 {
+            anyhow::bail!(
+                "Patch {} does not apply to {}: {}",
+                patch.to_arg(),
+                repo_url,
+                result.stderr.trim()
+            );
+        }
+        eprintln!("  Applied patch {}", patch.to_arg());
+    }
+    Ok(())
+}
+
+fn fetch_diff(repo_url: &str, patch: &ComponentPatch) -> Result<String> {
+    match &patch.source {
+        PatchSource::File(path) => fs::read_to_string(path)
+            .with_context(|| format!("Failed to read patch file {}", path.display())),
+        PatchSource::PullRequest(num) => {
+            let (owner, repo) = github::parse_github_url(repo_url)?;
+            let endpoint = format!("repos/{}/{}/pulls/{}", owner, repo, num);
+            let resp = github::api_request("GET", &endpoint, None, &["-H", "Accept: application/vnd.github.diff"])?;
+            if !resp.is_success() {
+                anyhow::bail!("Failed to fetch diff for {}/{}#{} (HTTP {})", owner, repo, num, resp.status);
+            }
+            Ok(resp.body)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_patch_spec() {
+        let p = parse_patch_spec("pipeline=fixes/retry.diff").unwrap();
+        assert_eq!(p.component, "pipeline");
+        assert_eq!(p.source, PatchSource::File(PathBuf::from("fixes/retry.diff")));
+
+        let p = parse_patch_spec("triggers=pr/1234").unwrap();
+        assert_eq!(p.source, PatchSource::PullRequest(1234));
+        assert_eq!(p.to_arg(), "triggers=pr/1234");
+    }
+
+    #[test]
+    fn test_parse_patch_spec_rejects_invalid() {
+        assert!(parse_patch_spec("pipeline").is_err());
+        assert!(parse_patch_spec("unknown=fix.diff").is_err());
+        assert!(parse_patch_spec("pipeline=").is_err());
+        assert!(parse_patch_spec("pipeline=pr/abc").is_err());
+    }
+
+    #[test]
+    fn test_apply_patches_local_file() {
+        let repo = tempfile::tempdir().unwrap();
+        let dir = repo.path().to_string_lossy().to_string();
+        exec::run_cmd("git", &["init", "-q", &dir]).unwrap();
+        fs::write(repo.path().join("main.go"), "package main\n").unwrap();
+
+        let patch_dir = tempfile::tempdir().unwrap();
+        let patch_file = patch_dir.path().join("fix.diff");
+        fs::write(
+            &patch_file,
+            "--- a/main.go\n+++ b/main.go\n@@ -1 +1,2 @@\n package main\n+// patched\n",
+        )
+        .unwrap();
+
+        let patch = ComponentPatch {
+            component: "pipeline".to_string(),
+            source: PatchSource::File(patch_file),
+        };
+        apply_patches("https://github.com/tektoncd/pipeline.git", repo.path(), &[&patch]).unwrap();
+        assert_eq!(
+            fs::read_to_string(repo.path().join("main.go")).unwrap(),
+            "package main\n// patched\n"
+        );
+    }
+}
@@ -3,6 +3,11 @@ use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use crate::audit;
+use crate::gitops;
+use crate::platform;
+use crate::warnings;
+
 /// Supported performance test scenarios from openshift-pipelines/performance.
 #[derive(Debug, Clone, PartialEq)]
 pub enum PerfScenario {
@@ -71,45 +76,26 @@ pub struct PerfMetrics {
     pub throughput_per_minute: Option<f64>,
 }
 
-const PERF_REPO: &str = "https://github.com/openshift-pipelines/performance.git";
+pub const PERF_REPO: &str = "https://github.com/openshift-pipelines/performance.git";
 const PERF_DEFAULT_BRANCH: &str = "main";
 
-/// Clone the performance test repository.
+/// Clone the performance test repository at `git_ref` (default: main), or
+/// update and check out the ref in an existing clone.
 pub fn clone_perf_repo(target_dir: &Path, git_ref: Option<&str>) -> Result<PathBuf> {
     let perf_dir = target_dir.join("performance");
+    let checkout_ref = git_ref.unwrap_or(PERF_DEFAULT_BRANCH);
 
     if perf_dir.exists() {
         println!("  Performance repo already cloned, updating...");
-        let status = Command::new("git")
-            .args(["fetch", "--all"])
-            .current_dir(&perf_dir)
-            .status()
-            .context("Failed to fetch performance repo updates")?;
-        if !status.success() {
-            eprintln!("WARNING: git fetch failed, continuing with existing state");
+        if let Err(e) = gitops::fetch_all(&perf_dir) {
+            warnings::warn(format!("git fetch failed, continuing with existing state: {e:#}"));
         }
+        gitops::checkout(&perf_dir, checkout_ref).context("Failed to checkout performance repo ref")?;
     } else {
         println!("  Cloning performance repo...");
         std::fs::create_dir_all(target_dir)
             .context("Failed to create target directory for performance repo")?;
-        let status = Command::new("git")
-            .args(["clone", "--depth=1", PERF_REPO, perf_dir.to_str().unwrap()])
-            .status()
-            .context("Failed to clone performance repo")?;
-        if !status.success() {
-            anyhow::bail!("git clone failed for performance repo");
-        }
-    }
-
-    // Checkout specific ref if provided
-    let checkout_ref = git_ref.unwrap_or(PERF_DEFAULT_BRANCH);
-    let status = Command::new("git")
-        .args(["checkout", checkout_ref])
-        .current_dir(&perf_dir)
-        .status()
-        .context("Failed to checkout performance repo ref")?;
-    if !status.success() {
-        anyhow::bail!("git checkout {} failed", checkout_ref);
+        gitops::clone(PERF_REPO, &perf_dir, Some(checkout_ref), None).context("Failed to clone performance repo")?;
     }
 
     Ok(perf_dir)
@@ -126,6 +112,9 @@ pub fn run_perf_tests(
 
     println!("  Running performance scenario: {}", scenario.as_env_value());
 
+    // The performance repo's ci-scripts are bash-only
+    platform::require_tool("bash", "Performance tests")?;
+
     // Ensure output directory exists
     std::fs::create_dir_all(output_dir)
         .context("Failed to create perf output directory")?;
@@ -134,16 +123,15 @@ pub fn run_perf_tests(
     let setup_script = perf_repo_dir.join("ci-scripts/setup-cluster.sh");
     if setup_script.exists() {
         println!("    Running cluster setup...");
-        let status = Command::new("bash")
-            .arg(&setup_script)
-            .env("TEST_SCENARIO", scenario.as_env_value())
-            .current_dir(perf_repo_dir)
-            .status();
-
-        if let Ok(s) = status {
-            if !s.success() {
-                eprintln!("WARNING: setup-cluster.sh returned non-zero, continuing anyway");
-            }
+        let status = audit::status(
+            Command::new("bash")
+                .arg(&setup_script)
+                .env("TEST_SCENARIO", scenario.as_env_value())
+                .current_dir(perf_repo_dir),
+        );
+
+        if let Ok(s) = status && !s.success() {
+            warnings::warn("setup-cluster.sh returned non-zero, continuing anyway");
         }
     }
 
@@ -154,13 +142,14 @@ pub fn run_perf_tests(
     }
 
     println!("    Executing load test...");
-    let output = Command::new("bash")
-        .arg(&load_script)
-        .env("TEST_SCENARIO", scenario.as_env_value())
-        .env("OUTPUT_DIR", output_dir.to_str().unwrap_or("."))
-        .current_dir(perf_repo_dir)
-        .output()
-        .context("Failed to execute load-test.sh")?;
+    let output = audit::output(
+        Command::new("bash")
+            .arg(&load_script)
+            .env("TEST_SCENARIO", scenario.as_env_value())
+            .env("OUTPUT_DIR", output_dir.to_str().unwrap_or("."))
+            .current_dir(perf_repo_dir),
+    )
+    .context("Failed to execute load-test.sh")?;
 
     let passed = output.status.success();
     let duration = start.elapsed().as_secs_f64();
@@ -176,12 +165,13 @@ pub fn run_perf_tests(
 
     if collect_script.exists() {
         println!("    Collecting results...");
-        let result_output = Command::new("bash")
-            .arg(&collect_script)
-            .env("TEST_SCENARIO", scenario.as_env_value())
-            .env("OUTPUT_DIR", output_dir.to_str().unwrap_or("."))
-            .current_dir(perf_repo_dir)
-            .output();
+        let result_output = audit::output(
+            Command::new("bash")
+                .arg(&collect_script)
+                .env("TEST_SCENARIO", scenario.as_env_value())
+                .env("OUTPUT_DIR", output_dir.to_str().unwrap_or("."))
+                .current_dir(perf_repo_dir),
+        );
 
         if let Ok(ro) = result_output {
             // Try to parse metrics from output
@@ -192,16 +182,15 @@ pub fn run_perf_tests(
 
     // Also check for results.json in output_dir
     let results_file = output_dir.join("results.json");
-    if results_file.exists() {
-        if let Ok(content) = std::fs::read_to_string(&results_file) {
-            if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&content) {
-                // Extract metrics from JSON if available
-                if let Some(m) = parsed.get("metrics") {
-                    if let Ok(parsed_metrics) = serde_json::from_value::<PerfMetrics>(m.clone()) {
-                        metrics = parsed_metrics;
-                    }
-                }
-            }
+    if results_file.exists()
+        && let Ok(content) = std::fs::read_to_string(&results_file)
+        && let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&content)
+    {
+        // Extract metrics from JSON if available
+        if let Some(m) = parsed.get("metrics")
+            && let Ok(parsed_metrics) = serde_json::from_value::<PerfMetrics>(m.clone())
+        {
+            metrics = parsed_metrics;
         }
     }
 
@@ -225,45 +214,35 @@ fn parse_perf_metrics(output: &str) -> PerfMetrics {
     // Look for common metric patterns in output
     for line in output.lines() {
         let line_lower = line.to_lowercase();
-        if line_lower.contains("total runs:") {
-            if let Some(num) = extract_number(line) {
-                metrics.total_runs = Some(num as u64);
-            }
+        if line_lower.contains("total runs:") && let Some(num) = extract_number(line) {
+            metrics.total_runs = Some(num as u64);
         }
-        if line_lower.contains("successful:") {
-            if let Some(num) = extract_number(line) {
-                metrics.successful_runs = Some(num as u64);
-            }
+        if line_lower.contains("successful:") && let Some(num) = extract_number(line) {
+            metrics.successful_runs = Some(num as u64);
         }
-        if line_lower.contains("failed:") {
-            if let Some(num) = extract_number(line) {
-                metrics.failed_runs = Some(num as u64);
-            }
+        if line_lower.contains("failed:") && let Some(num) = extract_number(line) {
+            metrics.failed_runs = Some(num as u64);
         }
-        if line_lower.contains("avg duration:") || line_lower.contains("average:") {
-            if let Some(num) = extract_float(line) {
-                metrics.avg_duration_seconds = Some(num);
-            }
+        if (line_lower.contains("avg duration:") || line_lower.contains("average:"))
+            && let Some(num) = extract_float(line)
+        {
+            metrics.avg_duration_seconds = Some(num);
         }
-        if line_lower.contains("p50:") || line_lower.contains("median:") {
-            if let Some(num) = extract_float(line) {
-                metrics.p50_latency_seconds = Some(num);
-            }
+        if (line_lower.contains("p50:") || line_lower.contains("median:"))
+            && let Some(num) = extract_float(line)
+        {
+            metrics.p50_latency_seconds = Some(num);
         }
-        if line_lower.contains("p95:") {
-            if let Some(num) = extract_float(line) {
-                metrics.p95_latency_seconds = Some(num);
-            }
+        if line_lower.contains("p95:") && let Some(num) = extract_float(line) {
+            metrics.p95_latency_seconds = Some(num);
         }
-        if line_lower.contains("p99:") {
-            if let Some(num) = extract_float(line) {
-                metrics.p99_latency_seconds = Some(num);
-            }
+        if line_lower.contains("p99:") && let Some(num) = extract_float(line) {
+            metrics.p99_latency_seconds = Some(num);
         }
-        if line_lower.contains("throughput:") || line_lower.contains("runs/min:") {
-            if let Some(num) = extract_float(line) {
-                metrics.throughput_per_minute = Some(num);
-            }
+        if (line_lower.contains("throughput:") || line_lower.contains("runs/min:"))
+            && let Some(num) = extract_float(line)
+        {
+            metrics.throughput_per_minute = Some(num);
         }
     }
 
@@ -0,0 +1,122 @@
+//! Cross-platform helpers for the local phases (build, dry-run, results, publish).
+//!
+//! Replaces Unix shell utilities and HOME-based path assumptions so those phases
+//! work from macOS and Windows workstations. Features that genuinely need a Unix
+//! toolchain are gated with `require_tool`.
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// User home directory: HOME on Unix (and Git Bash), USERPROFILE on Windows.
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|h| !h.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Per-user cache directory for streamstress: $XDG_CACHE_HOME/streamstress, else
+/// %LOCALAPPDATA%\streamstress on Windows, else ~/.cache/streamstress.
+pub fn cache_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CACHE_HOME")
+        .or_else(|| if cfg!(windows) { std::env::var_os("LOCALAPPDATA") } else { None })
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|h| h.join(".cache")))
+        .map(|d| d.join("streamstress"))
+}
+
+/// Docker config directory: $DOCKER_CONFIG, else ~/.docker.
+pub fn docker_config_dir() -> Option<PathBuf> {
+    std::env::var_os("DOCKER_CONFIG")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|h| h.join(".docker")))
+}
+
+/// Podman/containers auth file: $REGISTRY_AUTH_FILE, else ~/.config/containers/auth.json.
+pub fn containers_auth_file() -> Option<PathBuf> {
+    std::env::var_os("REGISTRY_AUTH_FILE")
+        .filter(|f| !f.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|h| h.join(".config").join("containers").join("auth.json")))
+}
+
+/// Recursively copy `src` into `dest`, creating `dest` if needed (std::fs equivalent of `cp -r`).
+pub fn copy_dir_recursive(src: &Path, dest: &Path) -> Result<()> {
+    fs::create_dir_all(dest).with_context(|| format!("Failed to create {}", dest.display()))?;
+    for entry in fs::read_dir(src).with_context(|| format!("Failed to read {}", src.display()))? {
+        let entry = entry?;
+        let src_path = entry.path();
+        let dest_path = dest.join(entry.file_name());
+
+        if src_path.is_dir() {
+            copy_dir_recursive(&src_path, &dest_path)?;
+        } else {
+            fs::copy(&src_path, &dest_path)
+                .with_context(|| format!("Failed to copy {}", src_path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Lowercase hex SHA-256 of the file at `path`, streamed rather than read whole.
+pub(crate) fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Fail with a clear message if `tool` is not on PATH, naming the feature that needs it.
+pub fn require_tool(tool: &str, feature: &str) -> Result<()> {
+    if which::which(tool).is_err() {
+        let hint = if cfg!(windows) {
+            " On Windows, run from WSL or Git Bash, or run this phase in-cluster."
+        } else {
+            ""
+        };
+        anyhow::bail!("{} requires `{}`, which was not found on PATH.{}", feature, tool, hint);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_dir_recursive() {
+        let src = tempfile::tempdir().unwrap();
+        fs::create_dir_all(src.path().join("a/b")).unwrap();
+        fs::write(src.path().join("top.txt"), "1").unwrap();
+        fs::write(src.path().join("a/b/nested.txt"), "2").unwrap();
+
+        let dest = tempfile::tempdir().unwrap();
+        let target = dest.path().join("copy");
+        copy_dir_recursive(src.path(), &target).unwrap();
+
+        assert_eq!(fs::read_to_string(target.join("top.txt")).unwrap(), "1");
+        assert_eq!(fs::read_to_string(target.join("a/b/nested.txt")).unwrap(), "2");
+    }
+
+    #[test]
+    fn test_sha256_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("abc.txt");
+        fs::write(&path, "abc").unwrap();
+        assert_eq!(
+            sha256_file(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(sha256_file(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_require_tool_missing() {
+        let err = require_tool("definitely-not-a-real-tool-xyz", "Widget").unwrap_err();
+        assert!(err.to_string().contains("Widget requires `definitely-not-a-real-tool-xyz`"));
+    }
+}
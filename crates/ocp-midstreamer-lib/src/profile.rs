@@ -10,9 +10,13 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::{watch, Mutex};
 use tokio::task::JoinHandle;
 
+use crate::k8s;
+use crate::warnings;
+
 /// Overall resource profile for a test run.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceProfile {
+    /// RFC3339 UTC time the profile was generated
     pub run_timestamp: String,
     pub cluster: ClusterCapacity,
     pub baseline: ResourceSnapshot,
@@ -167,14 +171,15 @@ fn pod_metrics_api(client: &Client) -> Api<DynamicObject> {
 /// Check whether the metrics-server PodMetrics API is available on the cluster.
 pub async fn check_metrics_available(client: &Client) -> Result<bool> {
     let api = pod_metrics_api(client);
-    match api.list(&ListParams::default().limit(1)).await {
+    let lp = ListParams::default().limit(1);
+    match k8s::retry("list PodMetrics", || api.list(&lp)).await {
         Ok(_) => Ok(true),
         Err(kube::Error::Api(resp)) if resp.code == 404 => {
-            eprintln!("Warning: metrics.k8s.io API not available (metrics-server not installed?)");
+            warnings::warn("metrics.k8s.io API not available (metrics-server not installed?)");
             Ok(false)
         }
         Err(e) => {
-            eprintln!("Warning: metrics API check failed: {e}");
+            warnings::warn(format!("metrics API check failed: {e}"));
             Ok(false)
         }
     }
@@ -183,7 +188,8 @@ pub async fn check_metrics_available(client: &Client) -> Result<bool> {
 /// Collect cluster capacity by summing allocatable resources across all nodes.
 pub async fn collect_cluster_capacity(client: &Client) -> Result<ClusterCapacity> {
     let nodes: Api<Node> = Api::all(client.clone());
-    let list = nodes.list(&ListParams::default()).await.context("Failed to list nodes")?;
+    let lp = ListParams::default();
+    let list = k8s::retry("list Nodes", || nodes.list(&lp)).await.context("Failed to list nodes")?;
 
     let mut total_cpu: u64 = 0;
     let mut total_mem: u64 = 0;
@@ -223,7 +229,8 @@ pub async fn collect_cluster_capacity(client: &Client) -> Result<ClusterCapacity
 /// Collect a baseline resource snapshot by summing current PodMetrics across all namespaces.
 pub async fn collect_baseline(client: &Client) -> Result<ResourceSnapshot> {
     let api = pod_metrics_api(client);
-    let list = api.list(&ListParams::default()).await.context("Failed to list PodMetrics for baseline")?;
+    let lp = ListParams::default();
+    let list = k8s::retry("list PodMetrics", || api.list(&lp)).await.context("Failed to list PodMetrics for baseline")?;
 
     let mut cpu_total: u64 = 0;
     let mut mem_total: u64 = 0;
@@ -252,60 +259,17 @@ pub async fn collect_baseline(client: &Client) -> Result<ResourceSnapshot> {
 }
 
 // ---------------------------------------------------------------------------
-// Spec boundary detection
+// Spec boundaries
 // ---------------------------------------------------------------------------
 
-/// Events signaling spec execution boundaries in Gauge stdout.
+/// Events signaling spec execution boundaries, from gauge's machine-readable
+/// events (see `gaugeevents`).
 #[derive(Debug, Clone, PartialEq)]
 pub enum SpecEvent {
     SpecStart(String),
     SpecEnd,
 }
 
-/// Detect spec boundary events from a Gauge stdout line.
-pub fn detect_spec_boundary(line: &str) -> Option<SpecEvent> {
-    let trimmed = line.trim();
-
-    // Start patterns
-    // Pattern 1: "# Executing specification <path>"
-    if let Some(rest) = trimmed.strip_prefix("# Executing specification") {
-        let spec = rest.trim().trim_matches('"');
-        if !spec.is_empty() {
-            return Some(SpecEvent::SpecStart(spec.to_string()));
-        }
-    }
-    // Pattern 2: "## <spec-name>" (Gauge heading for spec)
-    if let Some(rest) = trimmed.strip_prefix("## ") {
-        let spec = rest.trim();
-        if !spec.is_empty() && !spec.contains("Scenario") {
-            return Some(SpecEvent::SpecStart(spec.to_string()));
-        }
-    }
-    // Pattern 3: "Executing Spec: <path>"
-    if let Some(rest) = trimmed.strip_prefix("Executing Spec:") {
-        let spec = rest.trim();
-        if !spec.is_empty() {
-            return Some(SpecEvent::SpecStart(spec.to_string()));
-        }
-    }
-
-    // End patterns
-    // Pattern 1: "Successfully generated html-report"
-    if trimmed.contains("Successfully generated") {
-        return Some(SpecEvent::SpecEnd);
-    }
-    // Pattern 2: "Specifications:" summary line
-    if trimmed.starts_with("Specifications:") {
-        return Some(SpecEvent::SpecEnd);
-    }
-    // Pattern 3: "Scenarios:" summary line (end of spec run)
-    if trimmed.starts_with("Scenarios:") {
-        return Some(SpecEvent::SpecEnd);
-    }
-
-    None
-}
-
 // ---------------------------------------------------------------------------
 // MetricsCollector - background poller
 // ---------------------------------------------------------------------------
@@ -356,7 +320,7 @@ impl MetricsCollector {
                                 });
                             }
                             Err(e) => {
-                                eprintln!("Warning: metrics poll failed (will retry): {e}");
+                                warnings::warn(format!("metrics poll failed (will retry): {e}"));
                             }
                         }
                     }
@@ -379,8 +343,9 @@ impl MetricsCollector {
         }
     }
 
-    /// Stop polling and return per-spec profiles built from collected samples.
-    pub async fn stop(self) -> Result<Vec<SpecProfile>> {
+    /// Stop polling and return per-spec profiles built from collected samples,
+    /// along with the samples themselves in poll order.
+    pub async fn stop(self) -> Result<(Vec<SpecProfile>, Vec<MetricSample>)> {
         let _ = self.stop_tx.send(true);
         let _ = self.poll_handle.await;
         let samples = self.samples.lock().await;
@@ -415,14 +380,57 @@ impl MetricsCollector {
             });
         }
 
-        Ok(profiles)
+        Ok((profiles, samples.clone()))
+    }
+}
+
+/// Current usage of one pod, summed over its containers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PodUsage {
+    pub namespace: String,
+    pub pod: String,
+    pub cpu_millicores: u64,
+    pub memory_bytes: u64,
+}
+
+/// Poll PodMetrics in `namespace` once and return per-pod usage.
+pub async fn collect_pod_usage(client: &Client, namespace: &str) -> Result<Vec<PodUsage>> {
+    let gvk = GroupVersionKind::gvk("metrics.k8s.io", "v1beta1", "PodMetrics");
+    let api: Api<DynamicObject> = Api::namespaced_with(client.clone(), namespace, &ApiResource::from_gvk(&gvk));
+    let lp = ListParams::default();
+    let list = k8s::retry("list PodMetrics", || api.list(&lp)).await
+        .with_context(|| format!("Failed to list PodMetrics in {namespace}"))?;
+    let mut usage = Vec::new();
+    for pod in &list.items {
+        let mut cpu: u64 = 0;
+        let mut mem: u64 = 0;
+        if let Some(containers) = pod.data.get("containers").and_then(|v| v.as_array()) {
+            for container in containers {
+                if let Some(u) = container.get("usage") {
+                    if let Some(c) = u.get("cpu").and_then(|v| v.as_str()) {
+                        cpu += parse_cpu_millicores(c).unwrap_or(0);
+                    }
+                    if let Some(m) = u.get("memory").and_then(|v| v.as_str()) {
+                        mem += parse_memory_bytes(m).unwrap_or(0);
+                    }
+                }
+            }
+        }
+        usage.push(PodUsage {
+            namespace: namespace.to_string(),
+            pod: pod.metadata.name.clone().unwrap_or_default(),
+            cpu_millicores: cpu,
+            memory_bytes: mem,
+        });
     }
+    Ok(usage)
 }
 
 /// Internal: poll PodMetrics once and sum usage.
 async fn collect_poll_sample(client: &Client) -> Result<(u64, u64, u32)> {
     let api = pod_metrics_api(client);
-    let list = api.list(&ListParams::default()).await.context("poll PodMetrics")?;
+    let lp = ListParams::default();
+    let list = k8s::retry("list PodMetrics", || api.list(&lp)).await.context("poll PodMetrics")?;
     let mut cpu: u64 = 0;
     let mut mem: u64 = 0;
     let pods = list.items.len() as u32;
@@ -561,58 +569,4 @@ mod tests {
         assert_eq!(deserialized.cluster.node_count, 4);
         assert_eq!(deserialized.recommendation.max_parallel_specs, 3);
     }
-
-    // Spec boundary detection tests
-    #[test]
-    fn test_detect_spec_start_executing() {
-        assert_eq!(
-            detect_spec_boundary("# Executing specification specs/pipeline_test.spec"),
-            Some(SpecEvent::SpecStart("specs/pipeline_test.spec".to_string()))
-        );
-    }
-
-    #[test]
-    fn test_detect_spec_start_heading() {
-        assert_eq!(
-            detect_spec_boundary("## Pipeline E2E Tests"),
-            Some(SpecEvent::SpecStart("Pipeline E2E Tests".to_string()))
-        );
-    }
-
-    #[test]
-    fn test_detect_spec_start_executing_spec() {
-        assert_eq!(
-            detect_spec_boundary("Executing Spec: specs/triggers.spec"),
-            Some(SpecEvent::SpecStart("specs/triggers.spec".to_string()))
-        );
-    }
-
-    #[test]
-    fn test_detect_spec_end_generated() {
-        assert_eq!(
-            detect_spec_boundary("Successfully generated html-report to => /tmp/reports"),
-            Some(SpecEvent::SpecEnd)
-        );
-    }
-
-    #[test]
-    fn test_detect_spec_end_specifications() {
-        assert_eq!(
-            detect_spec_boundary("Specifications: 3 executed, 2 passed, 1 failed"),
-            Some(SpecEvent::SpecEnd)
-        );
-    }
-
-    #[test]
-    fn test_detect_spec_end_scenarios() {
-        assert_eq!(
-            detect_spec_boundary("Scenarios: 10 executed, 8 passed, 2 failed"),
-            Some(SpecEvent::SpecEnd)
-        );
-    }
-
-    #[test]
-    fn test_detect_spec_boundary_none() {
-        assert_eq!(detect_spec_boundary("some random output line"), None);
-    }
 }
@@ -0,0 +1,295 @@
+//! `streamstress profile diff`: compare the resource profiles of two runs.
+//!
+//! Per spec, the CPU and memory p95/avg of run B are compared with run A; a p95
+//! rising by more than the threshold is flagged as a regression. Specs that
+//! entered the top consumers in B, and the drift of the idle-cluster baseline
+//! taken before the tests, are reported alongside, so a resource regression from
+//! an upstream change stands out the way a failing test does.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::profile::{ResourceProfile, SpecProfile};
+use crate::registry::format_bytes;
+
+/// Specs per resource counted as top consumers.
+const TOP_CONSUMERS: usize = 5;
+
+/// Where `resource-profile.json` is looked for under a run's output directory.
+const PROFILE_LOCATIONS: &[&str] = &["results/resource-profile.json", "perf/resource-profile.json", "resource-profile.json"];
+
+/// A value in run A and run B.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct Delta {
+    pub a: u64,
+    pub b: u64,
+    /// Change from A to B in percent; None when A is zero.
+    pub change_percent: Option<f64>,
+}
+
+impl Delta {
+    fn new(a: u64, b: u64) -> Self {
+        let change_percent = (a > 0).then(|| (b as f64 - a as f64) * 100.0 / a as f64);
+        Delta { a, b, change_percent }
+    }
+
+    fn exceeds(&self, threshold_percent: f64) -> bool {
+        self.change_percent.is_some_and(|c| c > threshold_percent)
+    }
+
+    fn percent(&self) -> String {
+        match self.change_percent {
+            Some(c) => format!("{:+.1}%", c),
+            None => "n/a".to_string(),
+        }
+    }
+}
+
+/// Usage of one spec in both runs.
+#[derive(Debug, Serialize)]
+pub struct SpecDelta {
+    pub spec_name: String,
+    pub cpu_avg_millicores: Delta,
+    pub cpu_p95_millicores: Delta,
+    pub memory_avg_bytes: Delta,
+    pub memory_p95_bytes: Delta,
+    pub duration_seconds: Delta,
+    /// CPU or memory p95 grew by more than the threshold.
+    pub regression: bool,
+}
+
+/// Idle-cluster usage measured before the tests of each run.
+#[derive(Debug, Serialize)]
+pub struct BaselineDrift {
+    pub cpu_millicores: Delta,
+    pub memory_bytes: Delta,
+    pub pod_count: Delta,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProfileDiff {
+    pub run_a: String,
+    pub run_b: String,
+    pub threshold_percent: f64,
+    pub baseline: BaselineDrift,
+    pub specs: Vec<SpecDelta>,
+    pub only_in_a: Vec<String>,
+    pub only_in_b: Vec<String>,
+    /// Specs among B's top CPU or memory consumers (by p95) that were not in A's.
+    pub new_top_consumers: Vec<String>,
+}
+
+impl ProfileDiff {
+    pub fn regressions(&self) -> usize {
+        self.specs.iter().filter(|s| s.regression).count()
+    }
+}
+
+/// Find the `resource-profile.json` of a run: the file itself, or the first of
+/// `PROFILE_LOCATIONS` under a run's output directory.
+pub fn find_profile(run: &Path) -> Result<PathBuf> {
+    if run.is_file() {
+        return Ok(run.to_path_buf());
+    }
+    PROFILE_LOCATIONS
+        .iter()
+        .map(|p| run.join(p))
+        .find(|p| p.is_file())
+        .with_context(|| format!("No resource-profile.json under {} (was the run profiled?)", run.display()))
+}
+
+pub fn load_profile(run: &Path) -> Result<ResourceProfile> {
+    let path = find_profile(run)?;
+    let content = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Compare the profiles of `run_a` and `run_b`.
+pub fn diff_runs(run_a: &Path, run_b: &Path, threshold_percent: f64) -> Result<ProfileDiff> {
+    if threshold_percent < 0.0 {
+        bail!("--threshold must not be negative");
+    }
+    let (a, b) = (load_profile(run_a)?, load_profile(run_b)?);
+    Ok(diff(&a, &b, &run_a.display().to_string(), &run_b.display().to_string(), threshold_percent))
+}
+
+pub fn diff(a: &ResourceProfile, b: &ResourceProfile, name_a: &str, name_b: &str, threshold_percent: f64) -> ProfileDiff {
+    let find = |profile: &'_ ResourceProfile, name: &str| profile.specs.iter().find(|s| s.spec_name == name).cloned();
+    let mut specs = Vec::new();
+    let mut only_in_a = Vec::new();
+    for spec_a in &a.specs {
+        let Some(spec_b) = find(b, &spec_a.spec_name) else {
+            only_in_a.push(spec_a.spec_name.clone());
+            continue;
+        };
+        let cpu_p95 = Delta::new(spec_a.cpu.p95, spec_b.cpu.p95);
+        let memory_p95 = Delta::new(spec_a.memory.p95, spec_b.memory.p95);
+        specs.push(SpecDelta {
+            spec_name: spec_a.spec_name.clone(),
+            cpu_avg_millicores: Delta::new(spec_a.cpu.avg, spec_b.cpu.avg),
+            cpu_p95_millicores: cpu_p95,
+            memory_avg_bytes: Delta::new(spec_a.memory.avg, spec_b.memory.avg),
+            memory_p95_bytes: memory_p95,
+            duration_seconds: Delta::new(spec_a.duration_seconds, spec_b.duration_seconds),
+            regression: cpu_p95.exceeds(threshold_percent) || memory_p95.exceeds(threshold_percent),
+        });
+    }
+    let only_in_b = b
+        .specs
+        .iter()
+        .filter(|s| find(a, &s.spec_name).is_none())
+        .map(|s| s.spec_name.clone())
+        .collect();
+
+    let top_a = top_consumers(&a.specs);
+    let new_top_consumers = top_consumers(&b.specs).into_iter().filter(|s| !top_a.contains(s)).collect();
+
+    ProfileDiff {
+        run_a: name_a.to_string(),
+        run_b: name_b.to_string(),
+        threshold_percent,
+        baseline: BaselineDrift {
+            cpu_millicores: Delta::new(a.baseline.cpu_millicores, b.baseline.cpu_millicores),
+            memory_bytes: Delta::new(a.baseline.memory_bytes, b.baseline.memory_bytes),
+            pod_count: Delta::new(a.baseline.pod_count as u64, b.baseline.pod_count as u64),
+        },
+        specs,
+        only_in_a,
+        only_in_b,
+        new_top_consumers,
+    }
+}
+
+/// The top CPU and top memory consumers by p95, CPU first, without duplicates.
+fn top_consumers(specs: &[SpecProfile]) -> Vec<String> {
+    let mut by_cpu: Vec<&SpecProfile> = specs.iter().collect();
+    by_cpu.sort_by_key(|s| std::cmp::Reverse(s.cpu.p95));
+    let mut by_memory: Vec<&SpecProfile> = specs.iter().collect();
+    by_memory.sort_by_key(|s| std::cmp::Reverse(s.memory.p95));
+
+    let mut top: Vec<String> = Vec::new();
+    for s in by_cpu.iter().take(TOP_CONSUMERS).chain(by_memory.iter().take(TOP_CONSUMERS)) {
+        if !top.contains(&s.spec_name) {
+            top.push(s.spec_name.clone());
+        }
+    }
+    top
+}
+
+/// Print the diff as tables, regressions first, then by CPU p95 change.
+pub fn print_table(diff: &ProfileDiff) {
+    println!("Resource profile: {} -> {}", diff.run_a, diff.run_b);
+    println!();
+    let b = &diff.baseline;
+    println!("Baseline drift (idle cluster before the tests):");
+    println!("  CPU     {}m -> {}m ({})", b.cpu_millicores.a, b.cpu_millicores.b, b.cpu_millicores.percent());
+    println!("  Memory  {} -> {} ({})", format_bytes(b.memory_bytes.a), format_bytes(b.memory_bytes.b), b.memory_bytes.percent());
+    println!("  Pods    {} -> {} ({})", b.pod_count.a, b.pod_count.b, b.pod_count.percent());
+    println!();
+
+    let mut specs: Vec<&SpecDelta> = diff.specs.iter().collect();
+    specs.sort_by(|x, y| {
+        y.regression.cmp(&x.regression).then(
+            y.cpu_p95_millicores
+                .change_percent
+                .unwrap_or(0.0)
+                .total_cmp(&x.cpu_p95_millicores.change_percent.unwrap_or(0.0)),
+        )
+    });
+    let width = specs.iter().map(|s| s.spec_name.len()).max().unwrap_or(4).max(4);
+    println!(
+        "{:<width$}  {:>22}  {:>30}  {:>16}",
+        "SPEC", "CPU P95 (m)", "MEMORY P95", "DURATION (s)"
+    );
+    for s in specs {
+        let cpu = format!("{} -> {} {:>7}", s.cpu_p95_millicores.a, s.cpu_p95_millicores.b, s.cpu_p95_millicores.percent());
+        let memory = format!(
+            "{} -> {} {:>7}",
+            format_bytes(s.memory_p95_bytes.a),
+            format_bytes(s.memory_p95_bytes.b),
+            s.memory_p95_bytes.percent()
+        );
+        let duration = format!("{} -> {}", s.duration_seconds.a, s.duration_seconds.b);
+        let flag = if s.regression { "  REGRESSION" } else { "" };
+        println!("{:<width$}  {:>22}  {:>30}  {:>16}{}", s.spec_name, cpu, memory, duration, flag);
+    }
+
+    if !diff.new_top_consumers.is_empty() {
+        println!();
+        println!("New top consumers: {}", diff.new_top_consumers.join(", "));
+    }
+    if !diff.only_in_a.is_empty() {
+        println!("Only in {}: {}", diff.run_a, diff.only_in_a.join(", "));
+    }
+    if !diff.only_in_b.is_empty() {
+        println!("Only in {}: {}", diff.run_b, diff.only_in_b.join(", "));
+    }
+    println!();
+    println!(
+        "{} of {} specs regressed (p95 CPU or memory up more than {}%)",
+        diff.regressions(),
+        diff.specs.len(),
+        diff.threshold_percent
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::{ClusterCapacity, ParallelismRecommendation, ResourceSnapshot, UsageStats};
+
+    fn stats(avg: u64, p95: u64) -> UsageStats {
+        UsageStats { min: 0, max: p95, avg, p95 }
+    }
+
+    fn profile(baseline_cpu: u64, specs: &[(&str, u64, u64)]) -> ResourceProfile {
+        ResourceProfile {
+            run_timestamp: "2026-10-01T00:00:00Z".to_string(),
+            cluster: ClusterCapacity {
+                total_cpu_millicores: 16000,
+                total_memory_bytes: 64 << 30,
+                allocatable_cpu_millicores: 15000,
+                allocatable_memory_bytes: 60 << 30,
+                node_count: 3,
+            },
+            baseline: ResourceSnapshot { cpu_millicores: baseline_cpu, memory_bytes: 8 << 30, pod_count: 100 },
+            specs: specs
+                .iter()
+                .map(|(name, cpu, memory)| SpecProfile {
+                    spec_name: name.to_string(),
+                    duration_seconds: 60,
+                    samples: 6,
+                    cpu: stats(*cpu / 2, *cpu),
+                    memory: stats(*memory / 2, *memory),
+                    peak_pod_count: 110,
+                })
+                .collect(),
+            recommendation: ParallelismRecommendation {
+                max_parallel_specs: 2,
+                limiting_resource: "cpu".to_string(),
+                safety_margin_percent: 20,
+                reasoning: String::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let a = profile(1000, &[("pipelinerun", 400, 1 << 30), ("triggers", 200, 512 << 20), ("old", 100, 1 << 20)]);
+        let b = profile(1200, &[("pipelinerun", 600, 1 << 30), ("triggers", 210, 512 << 20), ("new", 50, 1 << 20)]);
+        let d = diff(&a, &b, "a", "b", 20.0);
+
+        assert_eq!(d.baseline.cpu_millicores.change_percent, Some(20.0));
+        assert_eq!(d.specs.len(), 2);
+        let pipelinerun = d.specs.iter().find(|s| s.spec_name == "pipelinerun").unwrap();
+        assert_eq!(pipelinerun.cpu_p95_millicores.change_percent, Some(50.0));
+        assert!(pipelinerun.regression);
+        assert!(!d.specs.iter().find(|s| s.spec_name == "triggers").unwrap().regression);
+        assert_eq!(d.regressions(), 1);
+        assert_eq!(d.only_in_a, vec!["old"]);
+        assert_eq!(d.only_in_b, vec!["new"]);
+        assert_eq!(d.new_top_consumers, vec!["new"]);
+        assert_eq!(Delta::new(0, 5).change_percent, None);
+    }
+}
@@ -0,0 +1,181 @@
+//! Exporters for the profiler's sample time series.
+//!
+//! `resource-profile.json` only holds per-spec aggregates. These exporters
+//! write every sample to `resource-samples.csv` and render
+//! `resource-profile.svg` — cluster CPU and memory over time with the spec
+//! windows shaded — so a run's performance can be attached to a bug report
+//! without external tooling.
+
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use crate::profile::MetricSample;
+
+const WIDTH: f64 = 960.0;
+const HEIGHT: f64 = 420.0;
+const MARGIN_LEFT: f64 = 70.0;
+const MARGIN_RIGHT: f64 = 80.0;
+const MARGIN_TOP: f64 = 40.0;
+const MARGIN_BOTTOM: f64 = 40.0;
+
+const CPU_COLOR: &str = "#1f77b4";
+const MEMORY_COLOR: &str = "#d62728";
+const SPEC_FILLS: &[&str] = &["#e8eef7", "#f4efe6"];
+
+/// Write `resource-samples.csv` and `resource-profile.svg` into `results_dir`.
+pub fn export(results_dir: &Path, samples: &[MetricSample]) -> Result<()> {
+    let csv_path = results_dir.join("resource-samples.csv");
+    fs::write(&csv_path, samples_csv(samples)).with_context(|| format!("Failed to write {}", csv_path.display()))?;
+    let svg_path = results_dir.join("resource-profile.svg");
+    fs::write(&svg_path, render_svg(samples)).with_context(|| format!("Failed to write {}", svg_path.display()))?;
+    println!("  Samples written to: {}", csv_path.display());
+    println!("  Chart written to: {}", svg_path.display());
+    Ok(())
+}
+
+/// Seconds since the first sample, per sample.
+fn elapsed(samples: &[MetricSample]) -> Vec<f64> {
+    let Some(first) = samples.first() else { return Vec::new() };
+    samples.iter().map(|s| s.timestamp.duration_since(first.timestamp).as_secs_f64()).collect()
+}
+
+/// One row per sample; `spec` is empty between specs.
+fn samples_csv(samples: &[MetricSample]) -> String {
+    let mut csv = String::from("elapsed_secs,spec,cpu_millicores,memory_bytes,pod_count\n");
+    for (t, s) in elapsed(samples).iter().zip(samples) {
+        let spec = s.spec_name.as_deref().unwrap_or("");
+        let spec = if spec.contains([',', '"', '\n']) { format!("\"{}\"", spec.replace('"', "\"\"")) } else { spec.to_string() };
+        let _ = writeln!(csv, "{:.1},{},{},{},{}", t, spec, s.total_cpu_millicores, s.total_memory_bytes, s.pod_count);
+    }
+    csv
+}
+
+/// Consecutive samples of the same spec as (spec, start secs, end secs).
+fn spec_windows(samples: &[MetricSample], times: &[f64]) -> Vec<(String, f64, f64)> {
+    let mut windows: Vec<(String, f64, f64)> = Vec::new();
+    for (i, s) in samples.iter().enumerate() {
+        // A window extends to the next sample, which is when the spec was last seen running
+        let end = times.get(i + 1).copied().unwrap_or(times[i]);
+        match (&s.spec_name, windows.last_mut()) {
+            (Some(spec), Some(last)) if last.0 == *spec && (last.2 - times[i]).abs() < f64::EPSILON => last.2 = end,
+            (Some(spec), _) => windows.push((spec.clone(), times[i], end)),
+            (None, _) => {}
+        }
+    }
+    windows
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Short label for a spec: file name without directory or extension.
+fn spec_label(spec: &str) -> String {
+    let name = spec.rsplit('/').next().unwrap_or(spec).trim_end_matches(".spec");
+    if name.chars().count() > 24 { format!("{}…", name.chars().take(23).collect::<String>()) } else { name.to_string() }
+}
+
+/// SVG line chart of CPU (left axis) and memory (right axis) over time, spec windows shaded.
+fn render_svg(samples: &[MetricSample]) -> String {
+    let times = elapsed(samples);
+    let plot_w = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_h = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let max_t = times.last().copied().unwrap_or(0.0).max(1.0);
+    let max_cpu = samples.iter().map(|s| s.total_cpu_millicores).max().unwrap_or(0).max(1) as f64;
+    let max_mem = samples.iter().map(|s| s.total_memory_bytes).max().unwrap_or(0).max(1) as f64 / (1024.0 * 1024.0);
+    let x = |t: f64| MARGIN_LEFT + t / max_t * plot_w;
+    let y = |v: f64, max: f64| MARGIN_TOP + plot_h - v / max * plot_h;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" font-family="sans-serif" font-size="11">"#
+    );
+    svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n");
+
+    for (i, (spec, start, end)) in spec_windows(samples, &times).iter().enumerate() {
+        let _ = writeln!(
+            svg,
+            r##"<g><title>{}</title><rect x="{:.1}" y="{MARGIN_TOP}" width="{:.1}" height="{plot_h}" fill="{}"/><text x="{:.1}" y="{:.1}" font-size="9" fill="#555">{}</text></g>"##,
+            escape(spec),
+            x(*start),
+            (x(*end) - x(*start)).max(1.0),
+            SPEC_FILLS[i % SPEC_FILLS.len()],
+            x(*start) + 2.0,
+            MARGIN_TOP + 10.0 + (i % 3) as f64 * 11.0,
+            escape(&spec_label(spec))
+        );
+    }
+
+    // Axes with min/mid/max ticks
+    let _ = writeln!(
+        svg,
+        r##"<path d="M{MARGIN_LEFT} {MARGIN_TOP} V{:.1} H{:.1} V{MARGIN_TOP}" fill="none" stroke="#333"/>"##,
+        MARGIN_TOP + plot_h,
+        MARGIN_LEFT + plot_w
+    );
+    for f in [0.0, 0.5, 1.0] {
+        let ty = MARGIN_TOP + plot_h - f * plot_h;
+        let _ = writeln!(svg, r#"<text x="{:.1}" y="{:.1}" text-anchor="end" fill="{CPU_COLOR}">{:.0}m</text>"#, MARGIN_LEFT - 5.0, ty + 4.0, f * max_cpu);
+        let _ = writeln!(svg, r#"<text x="{:.1}" y="{:.1}" fill="{MEMORY_COLOR}">{:.0}Mi</text>"#, MARGIN_LEFT + plot_w + 5.0, ty + 4.0, f * max_mem);
+        let _ = writeln!(svg, r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">{:.0}s</text>"#, x(f * max_t), MARGIN_TOP + plot_h + 16.0, f * max_t);
+    }
+
+    let line = |values: Vec<f64>, max: f64| -> String {
+        times.iter().zip(values).map(|(t, v)| format!("{:.1},{:.1}", x(*t), y(v, max))).collect::<Vec<_>>().join(" ")
+    };
+    let cpu = line(samples.iter().map(|s| s.total_cpu_millicores as f64).collect(), max_cpu);
+    let mem = line(samples.iter().map(|s| s.total_memory_bytes as f64 / (1024.0 * 1024.0)).collect(), max_mem);
+    let _ = writeln!(svg, r#"<polyline points="{}" fill="none" stroke="{CPU_COLOR}" stroke-width="1.5"/>"#, cpu);
+    let _ = writeln!(svg, r#"<polyline points="{}" fill="none" stroke="{MEMORY_COLOR}" stroke-width="1.5"/>"#, mem);
+
+    let _ = writeln!(
+        svg,
+        r#"<text x="{MARGIN_LEFT}" y="20" font-size="13">Cluster resource usage during the tests</text><text x="{:.1}" y="20" fill="{CPU_COLOR}">— CPU (millicores)</text><text x="{:.1}" y="20" fill="{MEMORY_COLOR}">— memory (MiB)</text>"#,
+        WIDTH - 330.0,
+        WIDTH - 200.0
+    );
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_csv_and_svg() {
+        let t0 = Instant::now();
+        let sample = |secs: u64, spec: Option<&str>, cpu: u64| MetricSample {
+            timestamp: t0 + Duration::from_secs(secs),
+            spec_name: spec.map(String::from),
+            total_cpu_millicores: cpu,
+            total_memory_bytes: 512 * 1024 * 1024,
+            pod_count: 12,
+        };
+        let samples = vec![
+            sample(0, None, 100),
+            sample(5, Some("specs/pipelines/run.spec"), 400),
+            sample(10, Some("specs/pipelines/run.spec"), 600),
+            sample(15, Some("specs/a,b.spec"), 300),
+        ];
+
+        let csv = samples_csv(&samples);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[1], "0.0,,100,536870912,12");
+        assert_eq!(lines[2], "5.0,specs/pipelines/run.spec,400,536870912,12");
+        assert_eq!(lines[4], "15.0,\"specs/a,b.spec\",300,536870912,12");
+
+        let windows = spec_windows(&samples, &elapsed(&samples));
+        assert_eq!(windows, vec![("specs/pipelines/run.spec".to_string(), 5.0, 15.0), ("specs/a,b.spec".to_string(), 15.0, 15.0)]);
+
+        let svg = render_svg(&samples);
+        assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<polyline").count(), 2);
+        assert!(svg.contains(">run</text>"));
+        assert!(!render_svg(&[]).is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// How stage progress is rendered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    /// Animated spinners (interactive terminal)
+    Spinner,
+    /// Plain timestamped lines, one per stage transition (CI logs, pipes)
+    Plain,
+    /// No progress output
+    Quiet,
+}
+
+static MODE: OnceLock<Mode> = OnceLock::new();
+
+/// Select the progress mode for this process. Call once at startup, before any stage.
+pub fn init(quiet: bool, no_progress: bool) {
+    let _ = MODE.set(select_mode(quiet, no_progress, std::io::stderr().is_terminal()));
+}
+
+/// Current progress mode. Falls back to TTY detection if `init` was not called.
+pub fn mode() -> Mode {
+    *MODE.get_or_init(|| select_mode(false, false, std::io::stderr().is_terminal()))
+}
+
+fn select_mode(quiet: bool, no_progress: bool, is_tty: bool) -> Mode {
+    if quiet {
+        Mode::Quiet
+    } else if no_progress || !is_tty {
+        Mode::Plain
+    } else {
+        Mode::Spinner
+    }
+}
+
+/// A single progress stage: a spinner on a terminal, timestamped lines otherwise.
+pub struct Stage {
+    pb: ProgressBar,
+    start: Instant,
+}
+
+impl Stage {
+    fn new(pb: ProgressBar, message: String) -> Self {
+        if mode() == Mode::Plain {
+            plain_line("▸", &message, None);
+        }
+        pb.set_message(message);
+        Stage { pb, start: Instant::now() }
+    }
+
+    pub fn message(&self) -> String {
+        self.pb.message()
+    }
+
+    pub fn set_message(&self, message: impl Into<String>) {
+        let message = message.into();
+        if mode() == Mode::Plain && message != self.pb.message() {
+            plain_line("▸", &message, None);
+        }
+        self.pb.set_message(message);
+    }
+
+    pub fn finish_with_message(&self, message: impl Into<String>) {
+        let message = message.into();
+        if mode() == Mode::Plain {
+            plain_line(" ", &message, Some(self.start.elapsed().as_secs_f64()));
+        }
+        self.pb.finish_with_message(message);
+    }
+
+    fn finish_marked(&self, marker: &str) {
+        let message = self.pb.message();
+        if mode() == Mode::Plain {
+            plain_line(marker, &message, Some(self.start.elapsed().as_secs_f64()));
+        }
+        self.pb.finish_with_message(format!("{} {}", marker, message));
+    }
+}
+
+/// A stage dropped without being finished (e.g. an early `?` return) is reported as failed.
+impl Drop for Stage {
+    fn drop(&mut self) {
+        if !self.pb.is_finished() {
+            self.finish_marked("✗");
+        }
+    }
+}
+
+fn plain_line(marker: &str, message: &str, elapsed_secs: Option<f64>) {
+    let ts = crate::timestamp::now_rfc3339();
+    match elapsed_secs {
+        Some(secs) => eprintln!("[{}] {} {} ({:.1}s)", ts, marker, message, secs),
+        None => eprintln!("[{}] {} {}", ts, marker, message),
+    }
+}
+
+fn spinner_style(template: &str) -> ProgressStyle {
+    ProgressStyle::default_spinner()
+        .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
+        .template(template)
+        .expect("invalid spinner template")
+}
+
+pub fn stage_spinner(message: &str) -> Stage {
+    let pb = if mode() == Mode::Spinner {
+        let pb = ProgressBar::new_spinner();
+        pb.set_style(spinner_style("{spinner} {msg}"));
+        pb.enable_steady_tick(std::time::Duration::from_millis(80));
+        pb
+    } else {
+        ProgressBar::hidden()
+    };
+    Stage::new(pb, message.to_string())
+}
+
+pub fn finish_spinner(stage: &Stage, success: bool) {
+    stage.finish_marked(if success { "✓" } else { "✗" });
+}
+
+/// Create a MultiProgress instance for parallel component builds.
+/// Draws nothing unless spinners are enabled.
+pub fn multi_progress() -> MultiProgress {
+    if mode() == Mode::Spinner {
+        MultiProgress::new()
+    } else {
+        MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
+    }
+}
+
+/// Add a spinner to a MultiProgress for a named component.
+pub fn component_spinner(mp: &MultiProgress, component: &str) -> Stage {
+    let pb = if mode() == Mode::Spinner {
+        let pb = mp.add(ProgressBar::new_spinner());
+        pb.set_style(spinner_style("{spinner} [{elapsed}] {msg}"));
+        pb.enable_steady_tick(std::time::Duration::from_millis(80));
+        pb
+    } else {
+        mp.add(ProgressBar::hidden())
+    };
+    Stage::new(pb, format!("{component}: waiting..."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_mode() {
+        assert_eq!(select_mode(false, false, true), Mode::Spinner);
+        assert_eq!(select_mode(false, false, false), Mode::Plain);
+        assert_eq!(select_mode(false, true, true), Mode::Plain);
+        assert_eq!(select_mode(true, false, true), Mode::Quiet);
+        assert_eq!(select_mode(true, true, false), Mode::Quiet);
+    }
+}
@@ -0,0 +1,1123 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::aggregate;
+use crate::audit;
+use crate::exec;
+use crate::github;
+use crate::platform;
+use crate::runmeta;
+use crate::scrub;
+use crate::signing;
+use crate::timestamp;
+use crate::workspace;
+
+/// Maximum push attempts when gh-pages moves underneath a git-based publish.
+const PUSH_MAX_ATTEMPTS: u32 = 5;
+
+/// Maximum attempts to update gh-pages via the API when the branch moves underneath us.
+const API_MAX_ATTEMPTS: u32 = 5;
+
+/// A run file and its manifest entry, ready to publish.
+struct PreparedRun {
+    run_id: String,
+    run_data: serde_json::Value,
+    entry: serde_json::Value,
+    /// Sigstore bundle signing the run file's content, with `--sign`
+    signature: Option<String>,
+}
+
+impl PreparedRun {
+    fn file(&self) -> String {
+        format!("runs/{}.json", self.run_id)
+    }
+
+    fn signature_file(&self) -> String {
+        format!("{}{}", self.file(), signing::BUNDLE_SUFFIX)
+    }
+
+    /// The run file as published (what the signature covers).
+    fn content(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.run_data)?)
+    }
+
+    /// Sign the run file's content with `signer`, if any.
+    fn sign(&mut self, signer: Option<&signing::Signer>) -> Result<()> {
+        if let Some(signer) = signer {
+            eprintln!("Signing run file {}...", self.file());
+            self.signature = Some(signer.sign_blob(self.content()?.as_bytes()).context("Failed to sign run file")?);
+            self.entry["signed"] = serde_json::json!(true);
+        }
+        Ok(())
+    }
+
+    fn commit_message(&self) -> String {
+        format!("publish: {} ({} total, {} passed)", self.run_id,
+            self.run_data.get("total").and_then(|v| v.as_u64()).unwrap_or(0),
+            self.run_data.get("passed").and_then(|v| v.as_u64()).unwrap_or(0),
+        )
+    }
+}
+
+/// Read results from the output directory and build the run file and manifest
+/// entry, under `run_id` if given (see `new_run_id`).
+fn prepare_run(output_dir: &str, label: Option<&str>, run_id: Option<&str>, scrub: bool) -> Result<PreparedRun> {
+    // 1. Read results JSON
+    let results_path = Path::new(output_dir).join("results/results.json");
+    if !results_path.exists() {
+        anyhow::bail!(
+            "Results not found at {}. Run `streamstress results` first.",
+            results_path.display()
+        );
+    }
+    let results_str = fs::read_to_string(&results_path)
+        .with_context(|| format!("Failed to read {}", results_path.display()))?;
+    let mut run_data: serde_json::Value =
+        serde_json::from_str(&results_str).context("Failed to parse results JSON")?;
+    let mut meta_tags = runmeta::RunMeta::new();
+
+    // 1b. Check for metadata.json (as-of date and patch tracking)
+    let metadata_path = Path::new(output_dir).join("results/metadata.json");
+    if metadata_path.exists()
+        && let Ok(meta_str) = fs::read_to_string(&metadata_path)
+        && let Ok(meta) = serde_json::from_str::<serde_json::Value>(&meta_str)
+    {
+        // Merge as_of_date into run data
+        if let Some(as_of) = meta.get("as_of_date").filter(|v| !v.is_null()) {
+            run_data["as_of_date"] = as_of.clone();
+            eprintln!("Including as_of_date: {} in run data", as_of);
+        }
+        // Merge resolved_components into run data
+        if let Some(components) = meta.get("resolved_components") {
+            run_data["component_refs"] = components.clone();
+        }
+        // Merge the batch id so the dashboard can group a batch's runs
+        if let Some(batch_id) = meta.get("batch_id").filter(|v| !v.is_null()) {
+            run_data["batch_id"] = batch_id.clone();
+        }
+        // The run's --meta metadata
+        if let Some(tags) = meta.get("meta").and_then(|m| serde_json::from_value::<runmeta::RunMeta>(m.clone()).ok()) {
+            meta_tags = tags;
+        }
+        // Merge applied patches into run data
+        if let Some(patches) = meta.get("patches").filter(|p| p.as_array().is_some_and(|a| !a.is_empty())) {
+            run_data["patches"] = patches.clone();
+            eprintln!("Including {} applied patch(es) in run data", patches.as_array().map_or(0, |a| a.len()));
+        }
+        // Merge the versions and checksums of the tools the run used
+        if let Some(tools) = meta.get("tools").filter(|t| t.as_array().is_some_and(|a| !a.is_empty())) {
+            run_data["tools"] = tools.clone();
+        }
+        // Merge the warnings summary of the run
+        if let Some(warnings) = meta.get("warnings").filter(|w| w.as_array().is_some_and(|a| !a.is_empty())) {
+            run_data["warnings"] = warnings.clone();
+        }
+    }
+
+    // 1c. Check for performance results (perf/perf-results.json)
+    let perf_results_path = Path::new(output_dir).join("perf/perf-results.json");
+    if perf_results_path.exists()
+        && let Ok(perf_str) = fs::read_to_string(&perf_results_path)
+        && let Ok(perf_data) = serde_json::from_str::<serde_json::Value>(&perf_str)
+    {
+        run_data["performance"] = perf_data;
+        eprintln!("Including performance test results in run data");
+    }
+
+    // 1d. Check for performance resource profile (perf/resource-profile.json)
+    let perf_resource_path = Path::new(output_dir).join("perf/resource-profile.json");
+    if perf_resource_path.exists()
+        && let Ok(resource_str) = fs::read_to_string(&perf_resource_path)
+        && let Ok(resource_data) = serde_json::from_str::<serde_json::Value>(&resource_str)
+    {
+        run_data["performance_resources"] = resource_data;
+        eprintln!("Including performance resource profile in run data");
+    }
+
+    // 1e. Check for the functional tests' resource profile (results/resource-profile.json)
+    let resource_profile_path = Path::new(output_dir).join("results/resource-profile.json");
+    if resource_profile_path.exists()
+        && let Ok(profile_str) = fs::read_to_string(&resource_profile_path)
+        && let Ok(profile) = serde_json::from_str::<serde_json::Value>(&profile_str)
+    {
+        run_data["resource_profile"] = profile;
+        eprintln!("Including resource profile in run data");
+    }
+
+    // 1f. Check for Konflux pipeline task timings (results/konflux-task-timings.json)
+    let task_timings_path = Path::new(output_dir).join("results/konflux-task-timings.json");
+    if task_timings_path.exists()
+        && let Ok(timings_str) = fs::read_to_string(&task_timings_path)
+        && let Ok(timings) = serde_json::from_str::<serde_json::Value>(&timings_str)
+    {
+        run_data["konflux_task_timings"] = timings;
+        eprintln!("Including Konflux pipeline task timings in run data");
+    }
+
+    // 1g. Metadata given to publish wins over the run's
+    meta_tags.extend(runmeta::current());
+    if !meta_tags.is_empty() {
+        run_data["meta"] = serde_json::json!(meta_tags);
+    }
+
+    // 2. Generate run metadata
+    let timestamp = timestamp::now_rfc3339();
+    let run_id = match run_id {
+        Some(id) => id.to_string(),
+        None => new_run_id(&timestamp),
+    };
+
+    run_data["id"] = serde_json::json!(run_id);
+    run_data["timestamp"] = serde_json::json!(timestamp);
+    if let Some(lbl) = label {
+        run_data["label"] = serde_json::json!(lbl);
+    }
+
+    // Truncate error_messages to 500 chars
+    truncate_error_messages(&mut run_data, 500);
+
+    // Redact secrets, identities and cluster hostnames (see config/scrub.toml)
+    if scrub {
+        scrub_run_data(Path::new(output_dir), &mut run_data)?;
+    } else {
+        eprintln!("Publishing without scrubbing (--no-scrub)");
+    }
+
+    let mut entry = serde_json::json!({
+        "id": run_id,
+        "timestamp": timestamp,
+        "label": label.unwrap_or(""),
+        "total": run_data.get("total").and_then(|v| v.as_u64()).unwrap_or(0),
+        "passed": run_data.get("passed").and_then(|v| v.as_u64()).unwrap_or(0),
+        "failed": run_data.get("failed").and_then(|v| v.as_u64()).unwrap_or(0),
+        "file": format!("runs/{}.json", run_id),
+    });
+    if let Some(batch_id) = run_data.get("batch_id") {
+        entry["batch_id"] = batch_id.clone();
+    }
+    // Keys the dashboard filters on, so it need not load every run file
+    let filter_tags = runmeta::select(&meta_tags, &runmeta::manifest_keys());
+    if !filter_tags.is_empty() {
+        entry["meta"] = serde_json::json!(filter_tags);
+    }
+
+    Ok(PreparedRun { run_id, run_data, entry, signature: None })
+}
+
+/// Publish test results to the gh-pages branch for the dashboard. Returns the run id.
+/// With `dry_run_dir`, the would-be gh-pages tree is written there and nothing is pushed.
+/// With `signer`, a sigstore bundle for the run file is published next to it.
+/// A `run_id` already in the manifest is refused.
+pub fn publish(output_dir: &str, remote: Option<&str>, label: Option<&str>, run_id: Option<&str>, dry_run_dir: Option<&Path>, scrub: bool, signer: Option<&signing::Signer>) -> Result<String> {
+    let mut run = prepare_run(output_dir, label, run_id, scrub)?;
+    run.sign(signer)?;
+
+    // 3. Determine remote
+    let remote_url = match remote {
+        Some(r) => r.to_string(),
+        None => detect_remote()?,
+    };
+    eprintln!("Publishing to: {}", exec::redact(&remote_url));
+
+    // 4. Clone gh-pages into tempdir (or the dry-run directory)
+    let tmp = workspace::scoped("gh-pages").context("Failed to create temp dir")?;
+    let work = match dry_run_dir {
+        Some(dir) => {
+            if dir.exists() && fs::read_dir(dir)?.next().is_some() {
+                anyhow::bail!("Dry-run directory {} is not empty", dir.display());
+            }
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+            dir
+        }
+        None => tmp.path(),
+    };
+
+    let branch_exists = gh_pages_exists(&remote_url);
+
+    if branch_exists {
+        clone_gh_pages(&remote_url, work)?;
+    } else {
+        // Bootstrap: init orphan branch
+        eprintln!("gh-pages branch not found, bootstrapping...");
+        if dry_run_dir.is_none() {
+            run_git(work, &["init"])?;
+            run_git(work, &["checkout", "--orphan", "gh-pages"])?;
+            run_git(work, &["remote", "add", "origin", &remote_url])?;
+        }
+
+        // Create runs directory with empty manifest
+        let runs_dir = work.join("runs");
+        fs::create_dir_all(&runs_dir)?;
+        let empty_manifest = serde_json::json!({"runs": []});
+        fs::write(
+            runs_dir.join("manifest.json"),
+            serde_json::to_string_pretty(&empty_manifest)?,
+        )?;
+    }
+
+    if let Some(dir) = dry_run_dir {
+        write_run_files(work, &run)?;
+        // Plain tree for previewing; the clone's history is not needed
+        let _ = fs::remove_dir_all(work.join(".git"));
+        eprintln!("Dry run: gh-pages tree written to {} (nothing pushed)", dir.display());
+        eprintln!("  Preview with: streamstress dashboard serve --dir {}", dir.display());
+        return Ok(run.run_id);
+    }
+
+    // 5-8. Write run file, merge manifest, commit
+    commit_run(work, &run)?;
+
+    // Push; on rejection re-read the remote manifest and re-merge our entry
+    for attempt in 1..=PUSH_MAX_ATTEMPTS {
+        let pushed = audit::status(
+            Command::new("git")
+                .args(["push", "origin", "gh-pages"])
+                .current_dir(work),
+        );
+        if matches!(pushed, Ok(ref s) if s.success()) {
+            eprintln!("Published {} to gh-pages", run.run_id);
+            return Ok(run.run_id);
+        }
+        if attempt == PUSH_MAX_ATTEMPTS {
+            break;
+        }
+        eprintln!("Push rejected (attempt {attempt}/{PUSH_MAX_ATTEMPTS}), merging with remote manifest and retrying...");
+        run_git(work, &["fetch", "origin", "gh-pages"])?;
+        run_git(work, &["reset", "--hard", "FETCH_HEAD"])?;
+        commit_run(work, &run)?;
+    }
+
+    anyhow::bail!("Failed to push to gh-pages after {} attempts", PUSH_MAX_ATTEMPTS)
+}
+
+/// Write the run into `work` and commit. Safe to call again after resetting to a
+/// newer remote head.
+fn commit_run(work: &Path, run: &PreparedRun) -> Result<()> {
+    write_run_files(work, run)?;
+    run_git(work, &["add", "-A"])?;
+    run_git(work, &["commit", "-m", &run.commit_message()])?;
+    Ok(())
+}
+
+/// Copy dashboard assets, write the run file, and merge the run into the manifest in `work`.
+fn write_run_files(work: &Path, run: &PreparedRun) -> Result<()> {
+    // Copy dashboard assets (every publish, so updates propagate)
+    copy_dashboard_assets(work)?;
+
+    // Write run file
+    let runs_dir = work.join("runs");
+    fs::create_dir_all(&runs_dir)?;
+    let run_file = runs_dir.join(format!("{}.json", run.run_id));
+    fs::write(
+        &run_file,
+        run.content()?,
+    )?;
+    eprintln!("Wrote run file: {}", run.run_id);
+    if let Some(ref signature) = run.signature {
+        fs::write(work.join(run.signature_file()), signature)?;
+    }
+
+    // Update manifest: merge new entry by id
+    let manifest_path = runs_dir.join("manifest.json");
+    let mut manifest: serde_json::Value = if manifest_path.exists() {
+        let s = fs::read_to_string(&manifest_path)?;
+        serde_json::from_str(&s).unwrap_or_else(|_| serde_json::json!({"runs": []}))
+    } else {
+        serde_json::json!({"runs": []})
+    };
+    merge_manifest_entry(&mut manifest, &run.entry)?;
+
+    fs::write(
+        &manifest_path,
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+
+    // Fold the run into the pre-computed dashboard indexes
+    let mut indexes = aggregate::Indexes::load(work)?;
+    indexes.add_run(&run.run_id, &run.file(), &run.run_data);
+    indexes.write(work)?;
+    Ok(())
+}
+
+/// Insert a run entry at the head of the manifest, replacing an earlier merge of
+/// the same entry. Idempotent, so re-merging after a conflict never duplicates or
+/// drops runs. Another run with the same id (published at another time) is a
+/// collision and fails instead of being overwritten.
+fn merge_manifest_entry(manifest: &mut serde_json::Value, entry: &serde_json::Value) -> Result<()> {
+    if !manifest.get("runs").is_some_and(|v| v.is_array()) {
+        manifest["runs"] = serde_json::json!([]);
+    }
+    if let Some(runs) = manifest.get_mut("runs").and_then(|v| v.as_array_mut()) {
+        if let Some(other) = runs.iter().find(|r| r.get("id") == entry.get("id") && r.get("timestamp") != entry.get("timestamp")) {
+            anyhow::bail!(
+                "Run id {} is already published (at {}); pass another --run-id",
+                entry["id"].as_str().unwrap_or_default(),
+                other["timestamp"].as_str().unwrap_or("an unknown time")
+            );
+        }
+        runs.retain(|r| r.get("id") != entry.get("id"));
+        runs.insert(0, entry.clone());
+    }
+    Ok(())
+}
+
+/// Run id for a run published at `timestamp`: `run-20250205T140307Z-3f9a1c`. The
+/// random suffix keeps runs published in the same second (e.g. by batch date
+/// runs) apart.
+pub fn new_run_id(timestamp: &str) -> String {
+    use std::hash::{BuildHasher, Hasher};
+    let mut h = std::collections::hash_map::RandomState::new().build_hasher();
+    h.write_u32(std::process::id());
+    format!("{}-{:06x}", timestamp::run_id(timestamp), h.finish() & 0xff_ffff)
+}
+
+/// Validate a `--run-id`: it names the run file, so letters, digits, '.', '_'
+/// and '-' only. Used by clap's value_parser.
+pub fn parse_run_id(s: &str) -> std::result::Result<String, String> {
+    if s.is_empty() || s.starts_with('.') || !s.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')) {
+        return Err(format!("Invalid run id '{}' (letters, digits, '.', '_' and '-' only, not starting with '.')", s));
+    }
+    Ok(s.to_string())
+}
+
+/// Publish test results to gh-pages through the GitHub git data API (via `gh api`)
+/// instead of cloning the branch. The manifest update is applied as a fast-forward
+/// ref update and retried when gh-pages moves concurrently. Returns the run id.
+pub fn publish_via_api(output_dir: &str, remote: Option<&str>, label: Option<&str>, run_id: Option<&str>, scrub: bool, signer: Option<&signing::Signer>) -> Result<String> {
+    let mut run = prepare_run(output_dir, label, run_id, scrub)?;
+    run.sign(signer)?;
+
+    let (owner, repo) = api_repo(remote)?;
+    eprintln!("Publishing to {}/{} via GitHub API", owner, repo);
+
+    let assets = collect_dashboard_assets()?;
+    let run_content = run.content()?;
+
+    for attempt in 1..=API_MAX_ATTEMPTS {
+        if try_publish_via_api(&owner, &repo, &run, &run_content, &assets)? {
+            eprintln!("Published {} to gh-pages", run.run_id);
+            return Ok(run.run_id);
+        }
+        if attempt < API_MAX_ATTEMPTS {
+            eprintln!("gh-pages changed during publish (attempt {attempt}/{API_MAX_ATTEMPTS}), retrying...");
+            std::thread::sleep(std::time::Duration::from_secs(2 * attempt as u64));
+        }
+    }
+    anyhow::bail!("Failed to update gh-pages after {} attempts", API_MAX_ATTEMPTS)
+}
+
+/// GitHub owner and repository publishes go to: `remote`, else $GITHUB_REPOSITORY,
+/// else the origin remote.
+fn api_repo(remote: Option<&str>) -> Result<(String, String)> {
+    match (remote, std::env::var("GITHUB_REPOSITORY")) {
+        (Some(r), _) => github::parse_github_url(r),
+        (None, Ok(slug)) if slug.contains('/') => {
+            let (o, r) = slug.split_once('/').unwrap();
+            Ok((o.to_string(), r.to_string()))
+        }
+        _ => github::parse_github_url(&detect_remote()?),
+    }
+}
+
+/// Content of `path` on the gh-pages branch of the dashboard repository (see
+/// `api_repo`), read through the GitHub API; None if it does not exist.
+pub fn fetch_published_file(path: &str, remote: Option<&str>) -> Result<Option<String>> {
+    let (owner, repo) = api_repo(remote)?;
+    let resp = github::api_request(
+        "GET",
+        &format!("repos/{owner}/{repo}/contents/{path}?ref=gh-pages"),
+        None,
+        &["-H", "Accept: application/vnd.github.raw+json"],
+    )?;
+    match resp.status {
+        404 => Ok(None),
+        _ if resp.is_success() => Ok(Some(resp.body)),
+        s => anyhow::bail!("Failed to read {} from gh-pages of {}/{} (HTTP {}): {}", path, owner, repo, s, resp.body.trim()),
+    }
+}
+
+/// A change to an already published run (`publish delete`, `publish relabel`).
+#[derive(Debug, Clone)]
+pub enum RunEdit {
+    Delete,
+    Relabel(String),
+}
+
+impl RunEdit {
+    fn commit_message(&self, run_id: &str) -> String {
+        match self {
+            RunEdit::Delete => format!("delete: {}", run_id),
+            RunEdit::Relabel(label) => format!("relabel: {} ({})", run_id, label),
+        }
+    }
+}
+
+/// Path under the gh-pages root of the run file of manifest `entry`.
+pub fn entry_file(entry: &serde_json::Value, run_id: &str) -> String {
+    // Script-published manifests list files relative to runs/
+    match entry["file"].as_str() {
+        Some(f) if f.starts_with("runs/") => f.to_string(),
+        Some(f) => format!("runs/{f}"),
+        None => format!("runs/{run_id}.json"),
+    }
+}
+
+/// Apply `edit` to the manifest entry of `run_id`. Returns the run file's path
+/// under the gh-pages root; errors if the manifest does not list the run.
+fn edit_manifest(manifest: &mut serde_json::Value, run_id: &str, edit: &RunEdit) -> Result<String> {
+    let runs = manifest
+        .get_mut("runs")
+        .and_then(|v| v.as_array_mut())
+        .context("Dashboard manifest has no runs")?;
+    let pos = runs
+        .iter()
+        .position(|r| r["id"].as_str() == Some(run_id))
+        .with_context(|| format!("Run {} is not in the dashboard manifest", run_id))?;
+    let file = entry_file(&runs[pos], run_id);
+    match edit {
+        RunEdit::Delete => {
+            runs.remove(pos);
+        }
+        RunEdit::Relabel(label) => runs[pos]["label"] = serde_json::json!(label),
+    }
+    Ok(file)
+}
+
+/// The run file with its label replaced.
+fn relabel_run_data(content: &str, label: &str) -> Result<String> {
+    let mut run: serde_json::Value = serde_json::from_str(content).context("Run file is not valid JSON")?;
+    run["label"] = serde_json::json!(label);
+    Ok(serde_json::to_string_pretty(&run)?)
+}
+
+/// Delete or relabel a published run on gh-pages: the manifest entry, the run
+/// file and the dashboard indexes change in one commit, retried like a publish
+/// when gh-pages moves underneath.
+pub fn edit_published_run(run_id: &str, edit: &RunEdit, remote: Option<&str>) -> Result<()> {
+    let remote_url = match remote {
+        Some(r) => r.to_string(),
+        None => detect_remote()?,
+    };
+    if !gh_pages_exists(&remote_url) {
+        anyhow::bail!("No gh-pages branch at {}", exec::redact(&remote_url));
+    }
+    eprintln!("Updating gh-pages at: {}", exec::redact(&remote_url));
+
+    let tmp = workspace::scoped("gh-pages").context("Failed to create temp dir")?;
+    let work = tmp.path();
+    clone_gh_pages(&remote_url, work)?;
+    commit_edit(work, run_id, edit)?;
+
+    for attempt in 1..=PUSH_MAX_ATTEMPTS {
+        let pushed = audit::status(
+            Command::new("git")
+                .args(["push", "origin", "gh-pages"])
+                .current_dir(work),
+        );
+        if matches!(pushed, Ok(ref s) if s.success()) {
+            eprintln!("Updated {} on gh-pages ({})", run_id, edit.commit_message(run_id));
+            return Ok(());
+        }
+        if attempt == PUSH_MAX_ATTEMPTS {
+            break;
+        }
+        eprintln!("Push rejected (attempt {attempt}/{PUSH_MAX_ATTEMPTS}), re-applying on the remote head and retrying...");
+        run_git(work, &["fetch", "origin", "gh-pages"])?;
+        run_git(work, &["reset", "--hard", "FETCH_HEAD"])?;
+        commit_edit(work, run_id, edit)?;
+    }
+
+    anyhow::bail!("Failed to push to gh-pages after {} attempts", PUSH_MAX_ATTEMPTS)
+}
+
+/// Apply `edit` to the gh-pages tree in `work` and commit.
+fn commit_edit(work: &Path, run_id: &str, edit: &RunEdit) -> Result<()> {
+    let manifest_path = work.join("runs/manifest.json");
+    let content = fs::read_to_string(&manifest_path).context("gh-pages has no runs/manifest.json")?;
+    let mut manifest: serde_json::Value = serde_json::from_str(&content).context("runs/manifest.json is not valid JSON")?;
+    let file = edit_manifest(&mut manifest, run_id, edit)?;
+    fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+
+    let run_path = work.join(&file);
+    // A relabeled run file no longer matches its signature; a deleted one needs none
+    let signature_path = signing::bundle_path(&run_path);
+    if signature_path.exists() {
+        if matches!(edit, RunEdit::Relabel(_)) {
+            crate::warnings::warn(format!("Removing the signature of {}: relabeling changes the signed content", file));
+        }
+        fs::remove_file(&signature_path).with_context(|| format!("Failed to remove the signature of {}", file))?;
+    }
+    match edit {
+        RunEdit::Delete => {
+            if run_path.exists() {
+                fs::remove_file(&run_path).with_context(|| format!("Failed to remove {}", file))?;
+            }
+            // Indexes are aggregates: rebuild them from the remaining runs
+            aggregate::Indexes::backfill(&manifest, |f| Ok(fs::read_to_string(work.join(f)).ok()))?.write(work)?;
+        }
+        RunEdit::Relabel(label) => {
+            if let Ok(content) = fs::read_to_string(&run_path) {
+                fs::write(&run_path, relabel_run_data(&content, label)?)?;
+            }
+            let mut indexes = aggregate::Indexes::load(work)?;
+            indexes.relabel_run(run_id, label);
+            indexes.write(work)?;
+        }
+    }
+
+    run_git(work, &["add", "-A"])?;
+    run_git(work, &["commit", "-m", &edit.commit_message(run_id)])?;
+    Ok(())
+}
+
+/// Write `content` to `path` on gh-pages (e.g. `matrix/<id>.json`) in one commit,
+/// retried like a publish when gh-pages moves underneath.
+pub fn publish_file(path: &str, content: &str, remote: Option<&str>) -> Result<()> {
+    let remote_url = match remote {
+        Some(r) => r.to_string(),
+        None => detect_remote()?,
+    };
+    if !gh_pages_exists(&remote_url) {
+        anyhow::bail!("No gh-pages branch at {}", exec::redact(&remote_url));
+    }
+
+    let tmp = workspace::scoped("gh-pages").context("Failed to create temp dir")?;
+    let work = tmp.path();
+    clone_gh_pages(&remote_url, work)?;
+    commit_file(work, path, content)?;
+
+    for attempt in 1..=PUSH_MAX_ATTEMPTS {
+        let pushed = audit::status(
+            Command::new("git")
+                .args(["push", "origin", "gh-pages"])
+                .current_dir(work),
+        );
+        if matches!(pushed, Ok(ref s) if s.success()) {
+            eprintln!("Published {} to gh-pages", path);
+            return Ok(());
+        }
+        if attempt == PUSH_MAX_ATTEMPTS {
+            break;
+        }
+        eprintln!("Push rejected (attempt {attempt}/{PUSH_MAX_ATTEMPTS}), re-applying on the remote head and retrying...");
+        run_git(work, &["fetch", "origin", "gh-pages"])?;
+        run_git(work, &["reset", "--hard", "FETCH_HEAD"])?;
+        commit_file(work, path, content)?;
+    }
+
+    anyhow::bail!("Failed to push to gh-pages after {} attempts", PUSH_MAX_ATTEMPTS)
+}
+
+/// Write `content` to `path` in the gh-pages tree in `work` and commit.
+fn commit_file(work: &Path, path: &str, content: &str) -> Result<()> {
+    let dest = work.join(path);
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&dest, content).with_context(|| format!("Failed to write {}", path))?;
+    run_git(work, &["add", path])?;
+    run_git(work, &["commit", "--allow-empty", "-m", &format!("Publish {}", path)])?;
+    Ok(())
+}
+
+/// `edit_published_run` through the GitHub git data API instead of a clone.
+pub fn edit_published_run_via_api(run_id: &str, edit: &RunEdit, remote: Option<&str>) -> Result<()> {
+    let (owner, repo) = api_repo(remote)?;
+    eprintln!("Updating gh-pages of {}/{} via GitHub API", owner, repo);
+
+    for attempt in 1..=API_MAX_ATTEMPTS {
+        if try_edit_via_api(&owner, &repo, run_id, edit)? {
+            eprintln!("Updated {} on gh-pages ({})", run_id, edit.commit_message(run_id));
+            return Ok(());
+        }
+        if attempt < API_MAX_ATTEMPTS {
+            eprintln!("gh-pages changed during the update (attempt {attempt}/{API_MAX_ATTEMPTS}), retrying...");
+            std::thread::sleep(std::time::Duration::from_secs(2 * attempt as u64));
+        }
+    }
+    anyhow::bail!("Failed to update gh-pages after {} attempts", API_MAX_ATTEMPTS)
+}
+
+/// One attempt at committing `edit`. Returns Ok(false) if gh-pages moved and it should be retried.
+fn try_edit_via_api(owner: &str, repo: &str, run_id: &str, edit: &RunEdit) -> Result<bool> {
+    let head = ApiHead::read(owner, repo)?;
+    if head.sha.is_none() {
+        anyhow::bail!("No gh-pages branch in {}/{}", owner, repo);
+    }
+    let mut manifest = head.manifest()?;
+    let file = edit_manifest(&mut manifest, run_id, edit)?;
+
+    let mut entries = vec![blob("runs/manifest.json", &serde_json::to_string_pretty(&manifest)?)];
+    // A relabeled run file no longer matches its signature; a deleted one needs none
+    let signature_file = format!("{}{}", file, signing::BUNDLE_SUFFIX);
+    if head.files.contains_key(&signature_file) {
+        if matches!(edit, RunEdit::Relabel(_)) {
+            crate::warnings::warn(format!("Removing the signature of {}: relabeling changes the signed content", file));
+        }
+        entries.push(serde_json::json!({"path": signature_file, "mode": "100644", "type": "blob", "sha": null}));
+    }
+    let indexes = match edit {
+        RunEdit::Delete => {
+            if head.files.contains_key(&file) {
+                // A null sha removes the path from the tree
+                entries.push(serde_json::json!({"path": file, "mode": "100644", "type": "blob", "sha": null}));
+            }
+            aggregate::Indexes::backfill(&manifest, |f| head.fetch(f))?
+        }
+        RunEdit::Relabel(label) => {
+            if let Some(content) = head.fetch(&file)? {
+                entries.push(blob(&file, &relabel_run_data(&content, label)?));
+            }
+            let mut indexes = head.indexes(&manifest)?;
+            indexes.relabel_run(run_id, label);
+            indexes
+        }
+    };
+    for (path, content) in indexes.files()? {
+        entries.push(blob(&path, &content));
+    }
+
+    head.commit(entries, &edit.commit_message(run_id))
+}
+
+/// A dashboard file to upload: path relative to the site root, content, and git blob SHA.
+struct DashboardAsset {
+    path: String,
+    content: String,
+    blob_sha: String,
+}
+
+/// The gh-pages branch as read through the API: head commit, its tree, and the
+/// blob SHA of every file (all empty when the branch does not exist yet).
+struct ApiHead {
+    base: String,
+    sha: Option<String>,
+    tree: Option<String>,
+    files: HashMap<String, String>,
+}
+
+impl ApiHead {
+    fn read(owner: &str, repo: &str) -> Result<Self> {
+        let base = format!("repos/{}/{}", owner, repo);
+        let head = github::api_request("GET", &format!("{base}/git/ref/heads/gh-pages"), None, &[])?;
+        let sha = match head.status {
+            404 => None,
+            _ if head.is_success() => head.json()?["object"]["sha"].as_str().map(String::from),
+            s => anyhow::bail!("Failed to read gh-pages ref (HTTP {}): {}", s, head.body.trim()),
+        };
+
+        // Current tree, so unchanged dashboard assets can be skipped
+        let mut tree = None;
+        let mut files = HashMap::new();
+        if let Some(ref sha) = sha {
+            let commit = api_ok(github::api_request("GET", &format!("{base}/git/commits/{sha}"), None, &[])?)?;
+            let tree_sha = commit["tree"]["sha"].as_str().context("Commit has no tree")?.to_string();
+            let listing = api_ok(github::api_request(
+                "GET",
+                &format!("{base}/git/trees/{tree_sha}?recursive=1"),
+                None,
+                &[],
+            )?)?;
+            if let Some(items) = listing["tree"].as_array() {
+                for item in items {
+                    if let (Some(p), Some(s)) = (item["path"].as_str(), item["sha"].as_str()) {
+                        files.insert(p.to_string(), s.to_string());
+                    }
+                }
+            }
+            tree = Some(tree_sha);
+        }
+        Ok(ApiHead { base, sha, tree, files })
+    }
+
+    /// Raw content of a file in the current gh-pages tree.
+    fn fetch(&self, path: &str) -> Result<Option<String>> {
+        let Some(ref sha) = self.sha else {
+            return Ok(None);
+        };
+        if !self.files.contains_key(path) {
+            return Ok(None);
+        }
+        let resp = github::api_request(
+            "GET",
+            &format!("{}/contents/{path}?ref={sha}", self.base),
+            None,
+            &["-H", "Accept: application/vnd.github.raw+json"],
+        )?;
+        Ok(resp.is_success().then_some(resp.body))
+    }
+
+    fn manifest(&self) -> Result<serde_json::Value> {
+        Ok(self
+            .fetch("runs/manifest.json")?
+            .and_then(|body| serde_json::from_str(&body).ok())
+            .unwrap_or_else(|| serde_json::json!({"runs": []})))
+    }
+
+    /// The dashboard indexes, backfilled from the published runs when gh-pages has none.
+    fn indexes(&self, manifest: &serde_json::Value) -> Result<aggregate::Indexes> {
+        let [history_path, weekly_path, latest_path] = aggregate::index_paths();
+        if self.files.contains_key(&latest_path) {
+            Ok(aggregate::Indexes::from_contents(
+                self.fetch(&history_path)?.as_deref(),
+                self.fetch(&weekly_path)?.as_deref(),
+                self.fetch(&latest_path)?.as_deref(),
+            ))
+        } else {
+            // First publish with indexes: backfill from the runs already published
+            aggregate::Indexes::backfill(manifest, |file| self.fetch(file))
+        }
+    }
+
+    /// Commit `entries` (git tree entries) on top of the head and fast-forward
+    /// gh-pages to it. Returns Ok(false) if the branch moved and the caller should retry.
+    fn commit(&self, entries: Vec<serde_json::Value>, message: &str) -> Result<bool> {
+        let base = &self.base;
+        let mut tree_req = serde_json::json!({"tree": entries});
+        if let Some(ref t) = self.tree {
+            tree_req["base_tree"] = serde_json::json!(t);
+        }
+        let new_tree = api_ok(github::api_request("POST", &format!("{base}/git/trees"), Some(&tree_req), &[])?)?;
+
+        let commit_req = serde_json::json!({
+            "message": message,
+            "tree": new_tree["sha"],
+            "parents": self.sha.iter().collect::<Vec<_>>(),
+        });
+        let new_commit = api_ok(github::api_request("POST", &format!("{base}/git/commits"), Some(&commit_req), &[])?)?;
+        let new_sha = new_commit["sha"].as_str().context("Created commit has no sha")?;
+
+        // Fast-forward only: a conflict means someone else published first
+        let update = if self.sha.is_some() {
+            github::api_request(
+                "PATCH",
+                &format!("{base}/git/refs/heads/gh-pages"),
+                Some(&serde_json::json!({"sha": new_sha, "force": false})),
+                &[],
+            )?
+        } else {
+            eprintln!("gh-pages branch not found, bootstrapping...");
+            github::api_request(
+                "POST",
+                &format!("{base}/git/refs"),
+                Some(&serde_json::json!({"ref": "refs/heads/gh-pages", "sha": new_sha})),
+                &[],
+            )?
+        };
+        match update.status {
+            409 | 422 => Ok(false),
+            _ if update.is_success() => Ok(true),
+            s => anyhow::bail!("Failed to update gh-pages ref (HTTP {}): {}", s, update.body.trim()),
+        }
+    }
+}
+
+/// A git tree entry writing `content` to `path`.
+fn blob(path: &str, content: &str) -> serde_json::Value {
+    serde_json::json!({"path": path, "mode": "100644", "type": "blob", "content": content})
+}
+
+/// One attempt at committing the run to gh-pages. Returns Ok(false) if the ref
+/// update lost a race with another publisher and should be retried.
+fn try_publish_via_api(
+    owner: &str,
+    repo: &str,
+    run: &PreparedRun,
+    run_content: &str,
+    assets: &[DashboardAsset],
+) -> Result<bool> {
+    let head = ApiHead::read(owner, repo)?;
+    let mut manifest = head.manifest()?;
+    let mut indexes = head.indexes(&manifest)?;
+    indexes.add_run(&run.run_id, &run.file(), &run.run_data);
+
+    merge_manifest_entry(&mut manifest, &run.entry)?;
+
+    let mut entries = vec![
+        blob(&run.file(), run_content),
+        blob("runs/manifest.json", &serde_json::to_string_pretty(&manifest)?),
+    ];
+    if let Some(ref signature) = run.signature {
+        entries.push(blob(&run.signature_file(), signature));
+    }
+    for (path, content) in indexes.files()? {
+        entries.push(blob(&path, &content));
+    }
+    let changed: Vec<&DashboardAsset> = assets
+        .iter()
+        .filter(|a| head.files.get(&a.path) != Some(&a.blob_sha))
+        .collect();
+    if changed.is_empty() {
+        eprintln!("Dashboard assets unchanged, skipping upload");
+    } else {
+        eprintln!("Uploading {} changed dashboard asset(s)", changed.len());
+    }
+    for a in changed {
+        entries.push(blob(&a.path, &a.content));
+    }
+
+    head.commit(entries, &run.commit_message())
+}
+
+/// Return the JSON body of a successful API response, or an error with the status.
+fn api_ok(resp: github::ApiResponse) -> Result<serde_json::Value> {
+    if !resp.is_success() {
+        anyhow::bail!("GitHub API request failed (HTTP {}): {}", resp.status, resp.body.trim());
+    }
+    resp.json()
+}
+
+/// Read the dashboard assets and compute their git blob SHAs for change detection.
+fn collect_dashboard_assets() -> Result<Vec<DashboardAsset>> {
+    let dashboard_src = dashboard_dir()?;
+    if !dashboard_src.exists() {
+        anyhow::bail!(
+            "Dashboard assets not found at {}. Ensure dashboard/ exists in repo root.",
+            dashboard_src.display()
+        );
+    }
+
+    let mut files = Vec::new();
+    list_files_recursive(&dashboard_src, &mut files)?;
+    files.sort();
+
+    let paths: Vec<&str> = files.iter().filter_map(|f| f.to_str()).collect();
+    let mut args = vec!["hash-object"];
+    args.extend(paths.iter().copied());
+    let hashes = exec::run_cmd("git", &args)?;
+
+    let mut assets = Vec::new();
+    for (file, sha) in files.iter().zip(hashes.stdout.lines()) {
+        let content = fs::read_to_string(file)
+            .with_context(|| format!("Dashboard asset {} is not valid UTF-8", file.display()))?;
+        let rel = file.strip_prefix(&dashboard_src).unwrap_or(file);
+        assets.push(DashboardAsset {
+            path: rel.to_string_lossy().replace('\\', "/"),
+            content,
+            blob_sha: sha.trim().to_string(),
+        });
+    }
+    Ok(assets)
+}
+
+fn list_files_recursive(dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            list_files_recursive(&path, out)?;
+        } else {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn detect_remote() -> Result<String> {
+    let output = audit::output(
+        Command::new("git")
+            .args(["remote", "get-url", "origin"]),
+    )
+    .context("Failed to run git remote get-url")?;
+    if !output.status.success() {
+        anyhow::bail!("No git remote 'origin' found. Use --remote to specify.");
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn gh_pages_exists(remote_url: &str) -> bool {
+    audit::output(
+        Command::new("git")
+            .args(["ls-remote", "--heads", remote_url, "gh-pages"]),
+    )
+    .map(|o| o.status.success() && !o.stdout.is_empty())
+    .unwrap_or(false)
+}
+
+/// Shallow-clone the gh-pages branch of `remote_url` into `work`.
+fn clone_gh_pages(remote_url: &str, work: &Path) -> Result<()> {
+    let status = audit::status(
+        Command::new("git")
+            .args(["clone", "--branch", "gh-pages", "--single-branch", "--depth", "1", remote_url, "."])
+            .current_dir(work),
+    )
+    .context("Failed to clone gh-pages")?;
+    if !status.success() {
+        anyhow::bail!("Failed to clone gh-pages branch");
+    }
+    Ok(())
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> Result<()> {
+    let status = audit::status(
+        Command::new("git")
+            .args(args)
+            .current_dir(dir),
+    )
+    .with_context(|| format!("Failed to run {}", exec::display_cmd("git", args)))?;
+    if !status.success() {
+        anyhow::bail!("{} failed", exec::display_cmd("git", args));
+    }
+    Ok(())
+}
+
+fn copy_dashboard_assets(dest: &Path) -> Result<()> {
+    let dashboard_src = dashboard_dir()?;
+
+    if !dashboard_src.exists() {
+        anyhow::bail!(
+            "Dashboard assets not found at {}. Ensure dashboard/ exists in repo root.",
+            dashboard_src.display()
+        );
+    }
+
+    platform::copy_dir_recursive(&dashboard_src, dest)?;
+    Ok(())
+}
+
+/// Dashboard assets: `$DASHBOARD_DIR` (set in the CLI image, which has no
+/// checkout) or `dashboard/` in the repo root.
+fn dashboard_dir() -> Result<PathBuf> {
+    match std::env::var_os("DASHBOARD_DIR") {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => Ok(find_repo_root()?.join("dashboard")),
+    }
+}
+
+fn find_repo_root() -> Result<std::path::PathBuf> {
+    let output = audit::output(
+        Command::new("git")
+            .args(["rev-parse", "--show-toplevel"]),
+    )
+    .context("Failed to find git repo root")?;
+    if !output.status.success() {
+        anyhow::bail!("Not in a git repository");
+    }
+    Ok(std::path::PathBuf::from(
+        String::from_utf8_lossy(&output.stdout).trim(),
+    ))
+}
+
+/// Scrub `run_data` in place, printing what was redacted and writing the report
+/// (with the hostname mapping, so it stays in the output directory).
+fn scrub_run_data(output_dir: &Path, run_data: &mut serde_json::Value) -> Result<()> {
+    let cfg = crate::config::load_scrub_config(&crate::config::default_scrub_config_path())?;
+    let mut scrubber = scrub::Scrubber::new(&cfg)?;
+    scrubber.scrub_value(run_data);
+    let report = scrubber.report;
+    if report.is_empty() {
+        eprintln!("Scrubbed run data: nothing to redact");
+        return Ok(());
+    }
+    eprintln!("Scrubbed run data:");
+    for line in report.summary() {
+        eprintln!("  {}", line);
+    }
+    let path = scrub::report_path(output_dir);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(&report)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    eprintln!("  Report: {}", path.display());
+    Ok(())
+}
+
+fn truncate_error_messages(value: &mut serde_json::Value, max_len: usize) {
+    match value {
+        serde_json::Value::Object(map) => {
+            if let Some(msg) = map.get_mut("error_message")
+                && let Some(s) = msg.as_str()
+                && s.len() > max_len
+            {
+                *msg = serde_json::Value::String(format!("{}...", &s[..max_len]));
+            }
+            for v in map.values_mut() {
+                truncate_error_messages(v, max_len);
+            }
+        }
+        serde_json::Value::Array(arr) => {
+            for v in arr {
+                truncate_error_messages(v, max_len);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_manifest_entry_prepends() {
+        let mut manifest = serde_json::json!({"runs": [{"id": "run-1"}]});
+        merge_manifest_entry(&mut manifest, &serde_json::json!({"id": "run-2"})).unwrap();
+        let ids: Vec<&str> = manifest["runs"].as_array().unwrap().iter()
+            .map(|r| r["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, vec!["run-2", "run-1"]);
+    }
+
+    #[test]
+    fn test_merge_manifest_entry_is_idempotent() {
+        let mut manifest = serde_json::json!({"runs": [{"id": "run-3"}, {"id": "run-2", "total": 1}, {"id": "run-1"}]});
+        merge_manifest_entry(&mut manifest, &serde_json::json!({"id": "run-2", "total": 5})).unwrap();
+        let runs = manifest["runs"].as_array().unwrap();
+        assert_eq!(runs.len(), 3);
+        assert_eq!(runs[0]["total"], 5);
+        assert!(runs.iter().any(|r| r["id"] == "run-3"));
+        assert!(runs.iter().any(|r| r["id"] == "run-1"));
+    }
+
+    #[test]
+    fn test_merge_manifest_entry_repairs_missing_runs() {
+        let mut manifest = serde_json::json!({});
+        merge_manifest_entry(&mut manifest, &serde_json::json!({"id": "run-1"})).unwrap();
+        assert_eq!(manifest["runs"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_merge_manifest_entry_refuses_collisions() {
+        let published = serde_json::json!({"id": "nightly-1", "timestamp": "2025-02-05T14:03:07Z"});
+        let mut manifest = serde_json::json!({"runs": [published.clone()]});
+        // Merging the same run again (a retried push) is fine
+        merge_manifest_entry(&mut manifest, &published).unwrap();
+        let other = serde_json::json!({"id": "nightly-1", "timestamp": "2025-02-06T09:00:00Z"});
+        let err = merge_manifest_entry(&mut manifest, &other).unwrap_err();
+        assert!(err.to_string().contains("2025-02-05T14:03:07Z"), "{err:#}");
+        assert_eq!(manifest["runs"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_run_ids() {
+        let id = new_run_id("2025-02-05T14:03:07Z");
+        assert!(id.starts_with("run-20250205T140307Z-"), "{id}");
+        assert_eq!(id.len(), "run-20250205T140307Z-".len() + 6);
+        assert_ne!(id, new_run_id("2025-02-05T14:03:07Z"));
+        assert_eq!(parse_run_id("nightly-4.17_aws").unwrap(), "nightly-4.17_aws");
+        assert!(parse_run_id("").is_err());
+        assert!(parse_run_id("../runs").is_err());
+        assert!(parse_run_id("a b").is_err());
+    }
+
+    #[test]
+    fn test_edit_manifest() {
+        let mut manifest = serde_json::json!({"runs": [
+            {"id": "run-2", "label": "", "file": "runs/run-2.json"},
+            {"id": "run-1", "label": "", "file": "run-1.json"}
+        ]});
+        let file = edit_manifest(&mut manifest, "run-1", &RunEdit::Relabel("nightly".into())).unwrap();
+        assert_eq!(file, "runs/run-1.json");
+        assert_eq!(manifest["runs"][1]["label"], "nightly");
+
+        assert_eq!(edit_manifest(&mut manifest, "run-2", &RunEdit::Delete).unwrap(), "runs/run-2.json");
+        assert_eq!(manifest["runs"].as_array().unwrap().len(), 1);
+        assert!(edit_manifest(&mut manifest, "run-2", &RunEdit::Delete).is_err());
+
+        let run = relabel_run_data(r#"{"id": "run-1", "label": "oops"}"#, "nightly").unwrap();
+        assert!(run.contains("\"label\": \"nightly\""));
+    }
+}
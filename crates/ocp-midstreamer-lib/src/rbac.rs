@@ -0,0 +1,222 @@
+//! RBAC manifests for the in-cluster streamstress ServiceAccount.
+//!
+//! The default profile binds cluster-admin. The minimal profile binds a scoped
+//! ClusterRole covering only what deploy+test needs, for clusters where
+//! cluster-admin cannot be granted.
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+
+/// ServiceAccount the in-cluster Job runs as.
+pub const SERVICE_ACCOUNT: &str = "streamstress-sa";
+
+/// ClusterRoleBinding granting the ServiceAccount its permissions.
+pub const CLUSTER_ROLE_BINDING: &str = "streamstress-crb";
+
+/// Scoped ClusterRole used by the minimal profile.
+pub const MINIMAL_CLUSTER_ROLE: &str = "streamstress-minimal";
+
+/// Which permissions the streamstress ServiceAccount is granted.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq)]
+pub enum RbacProfile {
+    /// Bind the built-in cluster-admin ClusterRole
+    #[default]
+    ClusterAdmin,
+    /// Bind a scoped ClusterRole covering only the resources streamstress touches
+    Minimal,
+}
+
+impl RbacProfile {
+    /// Name of the ClusterRole bound for this profile.
+    pub fn cluster_role(&self) -> &'static str {
+        match self {
+            RbacProfile::ClusterAdmin => "cluster-admin",
+            RbacProfile::Minimal => MINIMAL_CLUSTER_ROLE,
+        }
+    }
+}
+
+pub fn service_account_manifest(namespace: &str) -> Value {
+    json!({
+        "apiVersion": "v1",
+        "kind": "ServiceAccount",
+        "metadata": {
+            "name": SERVICE_ACCOUNT,
+            "namespace": namespace
+        }
+    })
+}
+
+/// ClusterRole covering deploy (operator Deployment env, InstallerSets, image-puller
+/// RoleBinding), release-tests (test namespaces and Tekton resources), and profiling.
+pub fn minimal_cluster_role_manifest() -> Value {
+    let all = json!(["get", "list", "watch", "create", "update", "patch", "delete"]);
+    let read = json!(["get", "list", "watch"]);
+    json!({
+        "apiVersion": "rbac.authorization.k8s.io/v1",
+        "kind": "ClusterRole",
+        "metadata": {
+            "name": MINIMAL_CLUSTER_ROLE,
+            "labels": { "app": "streamstress" }
+        },
+        "rules": [
+            {
+                "apiGroups": [""],
+                "resources": ["namespaces"],
+                "verbs": ["get", "list", "watch", "create", "delete"]
+            },
+            {
+                "apiGroups": [""],
+                "resources": [
+                    "pods", "pods/log", "services", "services/proxy", "endpoints", "configmaps", "secrets",
+                    "serviceaccounts", "serviceaccounts/token", "persistentvolumeclaims", "events"
+                ],
+                "verbs": all
+            },
+            {
+                "apiGroups": ["apps"],
+                "resources": ["deployments", "replicasets", "statefulsets"],
+                "verbs": all
+            },
+            {
+                "apiGroups": ["discovery.k8s.io"],
+                "resources": ["endpointslices"],
+                "verbs": ["get", "list", "watch"]
+            },
+            {
+                "apiGroups": ["operator.tekton.dev"],
+                "resources": ["*"],
+                "verbs": ["get", "list", "watch", "update", "patch", "delete"]
+            },
+            {
+                "apiGroups": ["tekton.dev", "triggers.tekton.dev", "results.tekton.dev", "pipelinesascode.tekton.dev", "openshift-pipelines.org"],
+                "resources": ["*"],
+                "verbs": all
+            },
+            {
+                "apiGroups": ["rbac.authorization.k8s.io"],
+                "resources": ["roles", "rolebindings"],
+                "verbs": ["get", "list", "create", "delete"]
+            },
+            {
+                "apiGroups": ["rbac.authorization.k8s.io"],
+                "resources": ["clusterroles"],
+                "verbs": ["bind"],
+                "resourceNames": [
+                    "system:image-puller",
+                    "tekton-triggers-eventlistener-roles", "tekton-triggers-eventlistener-clusterroles",
+                    "admin", "edit", "view"
+                ]
+            },
+            {
+                "apiGroups": ["rbac.authorization.k8s.io"],
+                "resources": ["clusterrolebindings"],
+                "verbs": ["get", "create"]
+            },
+            {
+                "apiGroups": ["route.openshift.io"],
+                "resources": ["routes"],
+                "verbs": ["get", "list", "watch", "create", "delete"]
+            },
+            {
+                "apiGroups": ["image.openshift.io"],
+                "resources": ["imagestreams", "imagestreams/layers"],
+                "verbs": read
+            },
+            {
+                "apiGroups": ["operators.coreos.com"],
+                "resources": ["clusterserviceversions", "subscriptions", "installplans"],
+                "verbs": read
+            },
+            {
+                "apiGroups": ["batch"],
+                "resources": ["jobs"],
+                "verbs": read
+            },
+            {
+                "apiGroups": ["coordination.k8s.io"],
+                "resources": ["leases"],
+                "resourceNames": ["streamstress-lock"],
+                "verbs": ["get", "update", "delete"]
+            },
+            {
+                "apiGroups": ["coordination.k8s.io"],
+                "resources": ["leases"],
+                "verbs": ["create"]
+            },
+            {
+                "apiGroups": ["metrics.k8s.io"],
+                "resources": ["pods", "nodes"],
+                "verbs": ["get", "list"]
+            }
+        ]
+    })
+}
+
+pub fn cluster_role_binding_manifest(profile: RbacProfile, namespace: &str) -> Value {
+    json!({
+        "apiVersion": "rbac.authorization.k8s.io/v1",
+        "kind": "ClusterRoleBinding",
+        "metadata": {
+            "name": CLUSTER_ROLE_BINDING
+        },
+        "roleRef": {
+            "apiGroup": "rbac.authorization.k8s.io",
+            "kind": "ClusterRole",
+            "name": profile.cluster_role()
+        },
+        "subjects": [{
+            "kind": "ServiceAccount",
+            "name": SERVICE_ACCOUNT,
+            "namespace": namespace
+        }]
+    })
+}
+
+/// All manifests for a profile, in apply order.
+pub fn manifests(profile: RbacProfile, namespace: &str) -> Vec<Value> {
+    let mut docs = vec![service_account_manifest(namespace)];
+    if profile == RbacProfile::Minimal {
+        docs.push(minimal_cluster_role_manifest());
+    }
+    docs.push(cluster_role_binding_manifest(profile, namespace));
+    docs
+}
+
+/// Render the manifests for a profile as a multi-document YAML stream.
+pub fn render_yaml(profile: RbacProfile, namespace: &str) -> Result<String> {
+    let mut out = String::new();
+    for doc in manifests(profile, namespace) {
+        out.push_str("---\n");
+        out.push_str(&serde_yaml::to_string(&doc).context("Failed to render RBAC manifest")?);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimal_profile_binds_scoped_role() {
+        let docs = manifests(RbacProfile::Minimal, "openshift-pipelines");
+        let kinds: Vec<&str> = docs.iter().filter_map(|d| d["kind"].as_str()).collect();
+        assert_eq!(kinds, vec!["ServiceAccount", "ClusterRole", "ClusterRoleBinding"]);
+        assert_eq!(docs[2]["roleRef"]["name"], MINIMAL_CLUSTER_ROLE);
+    }
+
+    #[test]
+    fn test_cluster_admin_profile_has_no_custom_role() {
+        let docs = manifests(RbacProfile::ClusterAdmin, "openshift-pipelines");
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[1]["roleRef"]["name"], "cluster-admin");
+        assert_eq!(docs[1]["subjects"][0]["namespace"], "openshift-pipelines");
+    }
+
+    #[test]
+    fn test_render_yaml_is_multi_document() {
+        let yaml = render_yaml(RbacProfile::Minimal, "ns").unwrap();
+        assert_eq!(yaml.matches("---\n").count(), 3);
+        assert!(yaml.contains("kind: ClusterRole\n"));
+    }
+}
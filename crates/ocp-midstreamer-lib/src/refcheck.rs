@@ -0,0 +1,151 @@
+//! Upfront validation of the git refs a run will check out.
+//!
+//! A mistyped tag otherwise only surfaces when its clone fails, well into the
+//! run. Every ref is checked before any work starts: branches, tags and PR refs
+//! with one `git ls-remote` per repository, commit SHAs via the GitHub API. All
+//! invalid refs are reported together.
+
+use anyhow::{Result, bail};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::component::ComponentSpec;
+use crate::config::ComponentConfig;
+use crate::dryrun;
+use crate::gitops;
+use crate::github;
+use crate::warnings;
+
+/// A ref a run will check out, and what it is for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefCheck {
+    /// Shown in the report (e.g. "pipeline", "--release-tests-ref")
+    pub what: String,
+    pub repo_url: String,
+    pub git_ref: String,
+}
+
+impl RefCheck {
+    pub fn new(what: &str, repo_url: &str, git_ref: &str) -> Self {
+        RefCheck { what: what.to_string(), repo_url: repo_url.to_string(), git_ref: git_ref.to_string() }
+    }
+}
+
+/// Checks for the components given an explicit ref (as-of dates and HEAD always resolve).
+pub fn component_checks(specs: &[ComponentSpec], configs: &HashMap<String, ComponentConfig>) -> Vec<RefCheck> {
+    specs
+        .iter()
+        .filter_map(|spec| {
+            let git_ref = spec.git_ref.as_deref()?;
+            let cfg = configs.get(&spec.name)?;
+            Some(RefCheck::new(&spec.name, &cfg.repo, git_ref))
+        })
+        .collect()
+}
+
+/// Validate every ref in `checks`, failing with all invalid refs at once.
+/// Repositories that cannot be reached are warned about and skipped, and
+/// nothing is checked with --offline.
+pub fn validate_refs(checks: &[RefCheck]) -> Result<()> {
+    if checks.is_empty() {
+        return Ok(());
+    }
+    if github::is_offline() {
+        eprintln!("Skipping git ref validation (--offline)");
+        return Ok(());
+    }
+
+    let mut per_repo: BTreeMap<&str, Vec<&RefCheck>> = BTreeMap::new();
+    for check in checks {
+        per_repo.entry(check.repo_url.as_str()).or_default().push(check);
+    }
+    eprintln!("Validating {} git ref(s) in {} repo(s)...", checks.len(), per_repo.len());
+
+    let invalid: Vec<String> = std::thread::scope(|scope| {
+        let handles: Vec<_> = per_repo
+            .iter()
+            .map(|(repo_url, checks)| scope.spawn(move || invalid_refs(repo_url, checks)))
+            .collect();
+        handles.into_iter().flat_map(|h| h.join().unwrap_or_default()).collect()
+    });
+    if !invalid.is_empty() {
+        bail!("{} git ref(s) not found:\n{}", invalid.len(), invalid.join("\n"));
+    }
+    Ok(())
+}
+
+/// Report lines for the refs of one repository that do not exist.
+fn invalid_refs(repo_url: &str, checks: &[&RefCheck]) -> Vec<String> {
+    let patterns: Vec<String> = checks
+        .iter()
+        .filter(|c| !dryrun::is_commit_sha(&c.git_ref))
+        .map(|c| gitops::resolve_ref(&c.git_ref))
+        .collect();
+    let refs = if patterns.is_empty() {
+        Vec::new()
+    } else {
+        match dryrun::ls_remote(repo_url, &patterns) {
+            Ok(refs) => refs,
+            Err(e) => {
+                warnings::warn(format!("Could not validate refs in {}: {e:#}", repo_url));
+                return Vec::new();
+            }
+        }
+    };
+
+    checks
+        .iter()
+        .filter(|c| {
+            if dryrun::is_commit_sha(&c.git_ref) {
+                commit_exists(repo_url, &c.git_ref) == Some(false)
+            } else {
+                dryrun::match_ref(&refs, &gitops::resolve_ref(&c.git_ref)).is_none()
+            }
+        })
+        .map(|c| format!("  {}: '{}' not found in {}", c.what, c.git_ref, repo_url))
+        .collect()
+}
+
+/// Whether `sha` names a commit in the repository, or None if GitHub cannot tell.
+fn commit_exists(repo_url: &str, sha: &str) -> Option<bool> {
+    let (owner, repo) = github::parse_github_url(repo_url).ok()?;
+    let endpoint = format!("repos/{}/{}/commits/{}", owner, repo, sha);
+    match github::api_request("GET", &endpoint, None, &[]) {
+        Ok(r) if r.is_success() => Some(true),
+        Ok(r) if r.status == 404 || r.status == 422 => Some(false),
+        Ok(r) => {
+            warnings::warn(format!("Could not validate commit {} in {}: HTTP {}", sha, repo_url, r.status));
+            None
+        }
+        Err(e) => {
+            warnings::warn(format!("Could not validate commit {} in {}: {e:#}", sha, repo_url));
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component;
+
+    #[test]
+    fn test_component_checks_only_explicit_refs() {
+        let configs: HashMap<String, ComponentConfig> = toml::from_str(
+            r#"
+            [pipeline]
+            repo = "https://github.com/tektoncd/pipeline.git"
+            images = {}
+            [triggers]
+            repo = "https://github.com/tektoncd/triggers.git"
+            images = {}
+            "#,
+        )
+        .unwrap();
+        let mut specs = component::parse_component_specs("pipeline:v0.62.0,triggers", &BTreeMap::new()).unwrap();
+        component::apply_as_of_date(&mut specs, "2025-01-15");
+        assert_eq!(
+            component_checks(&specs, &configs),
+            [RefCheck::new("pipeline", "https://github.com/tektoncd/pipeline.git", "v0.62.0")]
+        );
+    }
+}
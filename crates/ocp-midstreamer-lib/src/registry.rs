@@ -0,0 +1,552 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+
+use crate::exec;
+use crate::platform;
+
+/// Default namespace for upstream Tekton deployments.
+pub const DEFAULT_NAMESPACE: &str = "tekton-upstream";
+
+/// Get the OCP internal image registry route.
+///
+/// Queries the `default-route` in the `openshift-image-registry` namespace.
+pub fn get_registry_route() -> Result<String> {
+    let result = exec::run_cmd_timeout(
+        "oc",
+        &[
+            "get", "route", "default-route",
+            "-n", "openshift-image-registry",
+            "-o", "jsonpath={.spec.host}",
+        ],
+        exec::API_TIMEOUT,
+    );
+
+    match result {
+        Ok(r) => Ok(r.stdout.trim().to_string()),
+        Err(_) => anyhow::bail!(
+            "Could not get OCP image registry route. Is the registry exposed? \
+             Run: oc patch configs.imageregistry.operator.openshift.io/cluster \
+             --patch '{{\"spec\":{{\"defaultRoute\":true}}}}' --type=merge"
+        ),
+    }
+}
+
+/// Authenticate to the OCP internal registry using the current oc token.
+pub fn registry_login(registry_route: &str) -> Result<()> {
+    let token_result = exec::run_cmd_timeout("oc", &["whoami", "-t"], exec::API_TIMEOUT)?;
+    let token = token_result.stdout.trim();
+    exec::register_secret(token);
+
+    let registry_arg = format!("--registry={}", registry_route);
+    let token_arg = format!("--token={}", token);
+
+    exec::run_cmd(
+        "oc",
+        &["registry", "login", &registry_arg, &token_arg, "--insecure=true"],
+    )?;
+
+    // ko reads Docker credentials from ~/.docker/config.json, but oc registry login
+    // writes to ~/.config/containers/auth.json. Copy credentials so ko can push.
+    sync_docker_config();
+
+    Ok(())
+}
+
+/// Copy container auth credentials to ~/.docker/config.json for ko compatibility.
+///
+/// oc registry login writes to ~/.config/containers/auth.json (podman convention).
+/// ko uses the Docker convention (~/.docker/config.json). This function merges
+/// the container auth into the Docker config so ko can authenticate to the registry.
+/// Honors $REGISTRY_AUTH_FILE and $DOCKER_CONFIG, and USERPROFILE on Windows.
+fn sync_docker_config() {
+    let (Some(containers_auth), Some(docker_dir)) =
+        (platform::containers_auth_file(), platform::docker_config_dir())
+    else {
+        return;
+    };
+    let docker_config = docker_dir.join("config.json");
+
+    if !containers_auth.exists() {
+        return;
+    }
+
+    // Read source credentials
+    let src = match fs::read_to_string(&containers_auth) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+
+    let _ = fs::create_dir_all(&docker_dir);
+
+    if docker_config.exists() {
+        // Merge: parse both, merge auths from containers into docker config
+        let src_json: serde_json::Value = match serde_json::from_str(&src) {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+        let existing = match fs::read_to_string(&docker_config) {
+            Ok(s) => s,
+            Err(_) => return,
+        };
+        let mut dst_json: serde_json::Value = match serde_json::from_str(&existing) {
+            Ok(v) => v,
+            Err(_) => {
+                // Existing file is invalid JSON, overwrite
+                let _ = fs::write(&docker_config, &src);
+                return;
+            }
+        };
+
+        if let (Some(src_auths), Some(dst_auths)) = (
+            src_json.get("auths").and_then(|a| a.as_object()),
+            dst_json.get_mut("auths").and_then(|a| a.as_object_mut()),
+        ) {
+            for (k, v) in src_auths {
+                dst_auths.insert(k.clone(), v.clone());
+            }
+        }
+
+        let _ = fs::write(&docker_config, serde_json::to_string_pretty(&dst_json).unwrap_or_default());
+    } else {
+        // No existing docker config, just copy
+        let _ = fs::write(&docker_config, &src);
+    }
+}
+
+/// What an external push copied versus found already present.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PushStats {
+    pub images_copied: u32,
+    pub images_skipped: u32,
+    pub bytes_transferred: u64,
+    pub bytes_reused: u64,
+}
+
+impl PushStats {
+    pub fn add(&mut self, other: PushStats) {
+        self.images_copied += other.images_copied;
+        self.images_skipped += other.images_skipped;
+        self.bytes_transferred += other.bytes_transferred;
+        self.bytes_reused += other.bytes_reused;
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} copied, {} already present; {} transferred, {} reused",
+            self.images_copied,
+            self.images_skipped,
+            format_bytes(self.bytes_transferred),
+            format_bytes(self.bytes_reused)
+        )
+    }
+}
+
+/// Result of pushing one image to an external registry.
+#[derive(Debug)]
+pub struct PushResult {
+    /// SHA-pinned pullspec in the external registry
+    pub pinned: String,
+    pub stats: PushStats,
+}
+
+/// Push an image to an external registry (e.g. quay.io) using skopeo.
+///
+/// The destination is checked for the source digest first; when it is already
+/// there the copy is skipped. Otherwise skopeo copies the image, reusing any
+/// blobs the destination already has. Returns the SHA-pinned pullspec
+/// (e.g. quay.io/org/image@sha256:abc...) and what was transferred vs reused.
+pub fn push_to_external(image_ref: &str, target_registry: &str) -> Result<PushResult> {
+    // Derive image name from the source ref (last path segment before @/: tag)
+    let image_name = image_ref
+        .rsplit('/')
+        .next()
+        .unwrap_or(image_ref)
+        .split('@')
+        .next()
+        .unwrap_or(image_ref)
+        .split(':')
+        .next()
+        .unwrap_or(image_ref);
+
+    let dest_repo = format!("{}/{}", target_registry, image_name);
+    let dest = format!("docker://{}", dest_repo);
+    let src = format!("docker://{}", image_ref);
+
+    // Find auth file - try Docker config first, then containers auth
+    let auth_file = platform::docker_config_dir()
+        .map(|d| d.join("config.json"))
+        .filter(|p| p.exists())
+        .or_else(|| platform::containers_auth_file().filter(|p| p.exists()))
+        .map(|p| p.to_string_lossy().into_owned());
+
+    let blob_sizes = source_blob_sizes(image_ref, auth_file.as_deref());
+    let total_bytes: u64 = blob_sizes.values().sum();
+
+    // Skip the copy entirely when the destination already has this digest
+    if let Some(digest) = source_digest(image_ref, auth_file.as_deref()) {
+        let pinned_dest = format!("docker://{}@{}", dest_repo, digest);
+        let mut args = vec!["inspect", "--raw"];
+        if let Some(ref auth) = auth_file {
+            args.push("--authfile");
+            args.push(auth);
+        }
+        args.push(&pinned_dest);
+        if exec::run_cmd_unchecked("skopeo", &args).is_ok_and(|r| r.exit_code == 0) {
+            let pinned = format!("{}@{}", dest_repo, digest);
+            eprintln!("  Up to date: {} (already present, copy skipped)", pinned);
+            return Ok(PushResult {
+                pinned,
+                stats: PushStats {
+                    images_skipped: 1,
+                    bytes_reused: total_bytes,
+                    ..Default::default()
+                },
+            });
+        }
+    }
+
+    // Build skopeo command with auth and source TLS skip (for internal registry)
+    let mut args = vec!["copy", "--all", "--src-tls-verify=false"];
+    if let Some(ref auth) = auth_file {
+        args.push("--authfile");
+        args.push(auth);
+    }
+    args.push(&src);
+    args.push(&dest);
+
+    let copy_result = exec::run_cmd("skopeo", &args)?;
+    let skipped = skipped_blobs(&copy_result.stdout);
+    let bytes_reused: u64 = blob_sizes
+        .iter()
+        .filter(|(digest, _)| {
+            let hex = digest.trim_start_matches("sha256:");
+            skipped.iter().any(|s| hex.starts_with(s.as_str()))
+        })
+        .map(|(_, size)| size)
+        .sum();
+
+    // Get the digest of the pushed image via skopeo inspect
+    let mut inspect_args = vec!["inspect", "--format", "{{.Digest}}"];
+    if let Some(ref auth) = auth_file {
+        inspect_args.push("--authfile");
+        inspect_args.push(auth);
+    }
+    inspect_args.push(&dest);
+
+    let inspect_result = exec::run_cmd("skopeo", &inspect_args)?;
+    let digest = inspect_result.stdout.trim().to_string();
+
+    let pinned = format!("{}@{}", dest_repo, digest);
+    let stats = PushStats {
+        images_copied: 1,
+        bytes_transferred: total_bytes - bytes_reused,
+        bytes_reused,
+        ..Default::default()
+    };
+    eprintln!(
+        "  Pushed: {} ({} transferred, {} reused)",
+        pinned,
+        format_bytes(stats.bytes_transferred),
+        format_bytes(stats.bytes_reused)
+    );
+    Ok(PushResult { pinned, stats })
+}
+
+/// Digest of the source image: taken from a `@sha256:` ref, else inspected.
+fn source_digest(image_ref: &str, auth_file: Option<&str>) -> Option<String> {
+    if let Some((_, digest)) = image_ref.split_once('@') {
+        return Some(digest.to_string());
+    }
+    let src = format!("docker://{}", image_ref);
+    let mut args = vec!["inspect", "--tls-verify=false", "--format", "{{.Digest}}"];
+    if let Some(auth) = auth_file {
+        args.push("--authfile");
+        args.push(auth);
+    }
+    args.push(&src);
+    let result = exec::run_cmd_unchecked("skopeo", &args).ok()?;
+    let digest = result.stdout.trim();
+    (result.exit_code == 0 && !digest.is_empty()).then(|| digest.to_string())
+}
+
+/// Sizes of every blob (configs and layers) in the source image, across all
+/// platforms of a manifest list. Best effort: empty if the manifests can't be read.
+fn source_blob_sizes(image_ref: &str, auth_file: Option<&str>) -> HashMap<String, u64> {
+    let repo = image_ref.split('@').next().unwrap_or(image_ref);
+    let raw_manifest = |reference: &str| -> Option<serde_json::Value> {
+        let src = format!("docker://{}", reference);
+        let mut args = vec!["inspect", "--raw", "--tls-verify=false"];
+        if let Some(auth) = auth_file {
+            args.push("--authfile");
+            args.push(auth);
+        }
+        args.push(&src);
+        let result = exec::run_cmd_unchecked("skopeo", &args).ok()?;
+        if result.exit_code != 0 {
+            return None;
+        }
+        serde_json::from_str(&result.stdout).ok()
+    };
+
+    let mut sizes = HashMap::new();
+    let Some(top) = raw_manifest(image_ref) else {
+        return sizes;
+    };
+    match top.get("manifests").and_then(|m| m.as_array()) {
+        Some(children) => {
+            for child in children.iter().filter_map(|c| c.get("digest").and_then(|d| d.as_str())) {
+                if let Some(manifest) = raw_manifest(&format!("{}@{}", repo, child)) {
+                    sizes.extend(manifest_blob_sizes(&manifest));
+                }
+            }
+        }
+        None => sizes.extend(manifest_blob_sizes(&top)),
+    }
+    sizes
+}
+
+/// Config and layer digests with their sizes from an image manifest.
+fn manifest_blob_sizes(manifest: &serde_json::Value) -> Vec<(String, u64)> {
+    let config = manifest.get("config").into_iter();
+    let layers = manifest
+        .get("layers")
+        .and_then(|l| l.as_array())
+        .into_iter()
+        .flatten();
+    config
+        .chain(layers)
+        .filter_map(|b| {
+            let digest = b.get("digest")?.as_str()?;
+            let size = b.get("size")?.as_u64()?;
+            Some((digest.to_string(), size))
+        })
+        .collect()
+}
+
+/// Blob digests (possibly abbreviated, without `sha256:`) that skopeo reported
+/// as already present at the destination.
+fn skipped_blobs(copy_output: &str) -> Vec<String> {
+    copy_output
+        .lines()
+        .filter(|l| l.contains("skipped"))
+        .filter_map(|l| l.trim().strip_prefix("Copying blob "))
+        .filter_map(|rest| rest.split_whitespace().next())
+        .map(|d| d.trim_start_matches("sha256:").to_string())
+        .collect()
+}
+
+/// Human-readable byte count (binary units).
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64;
+    let mut unit = "B";
+    for u in UNITS {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = u;
+    }
+    format!("{:.1} {}", value, unit)
+}
+
+/// Collect image references from ko's --image-refs output file.
+///
+/// Each line in the file is a SHA-pinned image reference produced by ko.
+/// Returns Vec of (short_name, sha_pullspec) pairs where short_name is the
+/// last path segment before the @sha256: digest.
+pub fn collect_image_refs(image_refs_file: &std::path::Path) -> Result<Vec<(String, String)>> {
+    let content = std::fs::read_to_string(image_refs_file)
+        .with_context(|| format!("failed to read image refs file: {}", image_refs_file.display()))?;
+
+    let mut refs = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        // Extract short name: last path segment before @sha256:
+        let short_name = line
+            .rsplit('/')
+            .next()
+            .unwrap_or(line)
+            .split('@')
+            .next()
+            .unwrap_or(line)
+            .to_string();
+        refs.push((short_name, line.to_string()));
+    }
+    Ok(refs)
+}
+
+/// Free space below which emptyDir registry storage is reported as a risk.
+const LOW_DISK_BYTES: u64 = 20 * 1024 * 1024 * 1024;
+
+/// How the internal registry stores images, and the disk left on its node.
+#[derive(Debug, Default)]
+pub struct StorageHealth {
+    /// Storage backend key from the registry config (emptyDir, pvc, s3, ...), if any
+    pub backend: Option<String>,
+    /// Node running the registry pod
+    pub node: Option<String>,
+    pub available_bytes: Option<u64>,
+    pub capacity_bytes: Option<u64>,
+    /// The node's DiskPressure condition is True
+    pub disk_pressure: bool,
+}
+
+impl StorageHealth {
+    pub fn is_empty_dir(&self) -> bool {
+        self.backend.as_deref() == Some("emptyDir")
+    }
+
+    /// True when the registry node has under 20 GiB or 10% of its filesystem free.
+    pub fn low_disk(&self) -> bool {
+        match (self.available_bytes, self.capacity_bytes) {
+            (Some(avail), Some(cap)) => avail < LOW_DISK_BYTES || avail * 10 < cap,
+            (Some(avail), None) => avail < LOW_DISK_BYTES,
+            _ => false,
+        }
+    }
+}
+
+/// Storage backend key from the image registry config's `spec.storage` JSON.
+fn storage_backend(storage_json: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(storage_json).ok()?;
+    value
+        .as_object()?
+        .iter()
+        .find(|(k, v)| !v.is_null() && k.as_str() != "managementState")
+        .map(|(k, _)| k.clone())
+}
+
+/// Inspect the internal registry's storage backend and, for the node running
+/// the registry pod, its DiskPressure condition and filesystem usage from the
+/// kubelet stats summary.
+pub fn storage_health() -> Result<StorageHealth> {
+    let storage = exec::run_cmd_timeout(
+        "oc",
+        &[
+            "get", "configs.imageregistry.operator.openshift.io", "cluster",
+            "-o", "jsonpath={.spec.storage}",
+        ],
+        exec::API_TIMEOUT,
+    )
+    .context("Failed to read image registry config")?;
+    let mut health = StorageHealth {
+        backend: storage_backend(storage.stdout.trim()),
+        ..Default::default()
+    };
+
+    let node = exec::run_cmd_unchecked_timeout(
+        "oc",
+        &[
+            "get", "pods", "-n", "openshift-image-registry",
+            "-l", "docker-registry=default",
+            "-o", "jsonpath={.items[0].spec.nodeName}",
+        ],
+        exec::API_TIMEOUT,
+    )?;
+    let node = node.stdout.trim();
+    if node.is_empty() {
+        return Ok(health);
+    }
+    health.node = Some(node.to_string());
+
+    let pressure = exec::run_cmd_unchecked_timeout(
+        "oc",
+        &[
+            "get", "node", node,
+            "-o", "jsonpath={.status.conditions[?(@.type==\"DiskPressure\")].status}",
+        ],
+        exec::API_TIMEOUT,
+    )?;
+    health.disk_pressure = pressure.exit_code == 0 && pressure.stdout.trim() == "True";
+
+    let summary = exec::run_cmd_unchecked_timeout(
+        "oc",
+        &["get", "--raw", &format!("/api/v1/nodes/{}/proxy/stats/summary", node)],
+        exec::API_TIMEOUT,
+    )?;
+    if summary.exit_code == 0 && let Ok(v) = serde_json::from_str::<serde_json::Value>(&summary.stdout) {
+        health.available_bytes = v["node"]["fs"]["availableBytes"].as_u64();
+        health.capacity_bytes = v["node"]["fs"]["capacityBytes"].as_u64();
+    }
+    Ok(health)
+}
+
+/// Ensure a namespace exists, creating it if necessary.
+pub fn ensure_namespace(namespace: &str) -> Result<()> {
+    let check = exec::run_cmd_unchecked_timeout("oc", &["get", "namespace", namespace], exec::API_TIMEOUT)?;
+    if check.exit_code == 0 {
+        return Ok(());
+    }
+
+    exec::run_cmd("oc", &["create", "namespace", namespace])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skipped_blobs() {
+        let output = "Getting image source signatures\n\
+            Copying blob sha256:1e3d9b7d1452aa skipped: already exists\n\
+            Copying blob 4c87b8c9e5a6 skipped: already exists\n\
+            Copying blob sha256:9f2e00aa11bb\n\
+            Copying config sha256:7d1d00 done\n\
+            Writing manifest to image destination\n";
+        assert_eq!(skipped_blobs(output), vec!["1e3d9b7d1452aa", "4c87b8c9e5a6"]);
+    }
+
+    #[test]
+    fn test_manifest_blob_sizes() {
+        let manifest = serde_json::json!({
+            "schemaVersion": 2,
+            "config": {"digest": "sha256:cfg", "size": 1500},
+            "layers": [
+                {"digest": "sha256:aaa", "size": 1000},
+                {"digest": "sha256:bbb", "size": 2000}
+            ]
+        });
+        let sizes = manifest_blob_sizes(&manifest);
+        assert_eq!(sizes.len(), 3);
+        assert_eq!(sizes.iter().map(|(_, s)| s).sum::<u64>(), 4500);
+    }
+
+    #[test]
+    fn test_storage_health() {
+        assert_eq!(storage_backend(r#"{"emptyDir":{}}"#).as_deref(), Some("emptyDir"));
+        assert_eq!(
+            storage_backend(r#"{"managementState":"Managed","pvc":{"claim":"image-registry-storage"}}"#).as_deref(),
+            Some("pvc")
+        );
+        assert_eq!(storage_backend(""), None);
+
+        let gib = 1024 * 1024 * 1024;
+        let health = StorageHealth {
+            backend: Some("emptyDir".to_string()),
+            node: Some("worker-0".to_string()),
+            available_bytes: Some(50 * gib),
+            capacity_bytes: Some(120 * gib),
+            disk_pressure: false,
+        };
+        assert!(health.is_empty_dir());
+        assert!(!health.low_disk());
+        assert!(StorageHealth { available_bytes: Some(8 * gib), ..health }.low_disk());
+        assert!(StorageHealth { available_bytes: Some(30 * gib), capacity_bytes: Some(500 * gib), ..Default::default() }.low_disk());
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(45 * 1024 * 1024), "45.0 MiB");
+    }
+}
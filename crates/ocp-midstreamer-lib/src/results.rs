@@ -3,7 +3,11 @@ use console::Style;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::component;
+use crate::logscan;
+use crate::warnings;
 
 // --- JUnit XML deserialization structs ---
 
@@ -61,7 +65,7 @@ struct JUnitError {
 
 // --- Output structs ---
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TestRunResult {
     pub total: usize,
     pub passed: usize,
@@ -73,13 +77,19 @@ pub struct TestRunResult {
     pub tests: Vec<TestCaseResult>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TestCaseResult {
     pub spec: String,
     pub scenario: String,
     pub passed: bool,
     pub duration_secs: f64,
     pub error_message: Option<String>,
+    /// Components the test exercises (see `component::components_for_test`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<String>,
+    /// Controller log error lines logged while a failed test ran (see `logscan::attach`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub log_excerpts: Vec<logscan::LogExcerpt>,
 }
 
 // --- Failure categorization ---
@@ -112,11 +122,24 @@ pub struct CategoryGroup {
     pub tests: Vec<String>,
 }
 
+/// Pass/fail counts for the tests attributed to one component.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ComponentSummary {
+    pub component: String,
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub pass_rate: f64,
+}
+
 #[derive(Debug, Serialize)]
 pub struct CategorizedTestRunResult {
     #[serde(flatten)]
     pub result: TestRunResult,
     pub categories: Vec<CategoryGroup>,
+    /// Per-component breakdown, in `KNOWN_COMPONENTS` order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<ComponentSummary>,
 }
 
 /// Categorize a failure based on error message keywords.
@@ -190,17 +213,46 @@ pub fn categorize_results(result: &TestRunResult) -> CategorizedTestRunResult {
         .collect();
 
     // Sort by count descending
-    categories.sort_by(|a, b| b.count.cmp(&a.count));
+    categories.sort_by_key(|c| std::cmp::Reverse(c.count));
+
+    let mut result = result.clone();
+    for test in &mut result.tests {
+        test.components = component::components_for_test(&test.spec, &test.scenario);
+    }
+    let components = summarize_components(&result.tests);
 
     CategorizedTestRunResult {
-        result: result.clone(),
+        result,
         categories,
+        components,
     }
 }
 
+/// Aggregate pass rates per component over tests already tagged with components.
+/// A test exercising several components counts towards each of them.
+pub fn summarize_components(tests: &[TestCaseResult]) -> Vec<ComponentSummary> {
+    component::KNOWN_COMPONENTS
+        .iter()
+        .filter_map(|name| {
+            let tagged: Vec<&TestCaseResult> = tests.iter().filter(|t| t.components.iter().any(|c| c == name)).collect();
+            if tagged.is_empty() {
+                return None;
+            }
+            let passed = tagged.iter().filter(|t| t.passed).count();
+            Some(ComponentSummary {
+                component: name.to_string(),
+                total: tagged.len(),
+                passed,
+                failed: tagged.len() - passed,
+                pass_rate: passed as f64 / tagged.len() as f64 * 100.0,
+            })
+        })
+        .collect()
+}
+
 // --- ANSI stripping ---
 
-fn strip_ansi(text: &str) -> String {
+pub fn strip_ansi(text: &str) -> String {
     let re = Regex::new(r"\x1b\[[0-9;]*m").unwrap();
     re.replace_all(text, "").to_string()
 }
@@ -242,6 +294,8 @@ pub fn parse_gauge_stdout(log_path: &Path) -> Result<TestRunResult> {
                     passed: !scenario_failed,
                     duration_secs: 0.0,
                     error_message: error_msg,
+                    components: Vec::new(),
+                    log_excerpts: Vec::new(),
                 });
                 current_scenario.clear();
                 scenario_failed = false;
@@ -269,6 +323,8 @@ pub fn parse_gauge_stdout(log_path: &Path) -> Result<TestRunResult> {
                     passed: !scenario_failed,
                     duration_secs: 0.0,
                     error_message: error_msg,
+                    components: Vec::new(),
+                    log_excerpts: Vec::new(),
                 });
             }
             current_scenario = rest.trim().to_string();
@@ -308,12 +364,11 @@ pub fn parse_gauge_stdout(log_path: &Path) -> Result<TestRunResult> {
         }
 
         // Duration from final summary line like "FAIL\tcommand-line-arguments\t9487.532s"
-        if (trimmed.starts_with("FAIL\t") || trimmed.starts_with("ok\t")) && trimmed.ends_with('s') {
-            if let Some(duration_str) = trimmed.rsplit('\t').next() {
-                if let Ok(d) = duration_str.trim_end_matches('s').parse::<f64>() {
-                    total_duration = d;
-                }
-            }
+        if (trimmed.starts_with("FAIL\t") || trimmed.starts_with("ok\t")) && trimmed.ends_with('s')
+            && let Some(duration_str) = trimmed.rsplit('\t').next()
+            && let Ok(d) = duration_str.trim_end_matches('s').parse::<f64>()
+        {
+            total_duration = d;
         }
     }
 
@@ -332,6 +387,8 @@ pub fn parse_gauge_stdout(log_path: &Path) -> Result<TestRunResult> {
             passed: !scenario_failed,
             duration_secs: 0.0,
             error_message: error_msg,
+            components: Vec::new(),
+            log_excerpts: Vec::new(),
         });
     }
 
@@ -381,6 +438,8 @@ pub fn parse_junit_xml(xml_path: &Path) -> Result<TestRunResult> {
                 passed,
                 duration_secs: tc.time,
                 error_message,
+                components: Vec::new(),
+                log_excerpts: Vec::new(),
             });
         }
     }
@@ -440,6 +499,17 @@ pub fn print_results(result: &TestRunResult) {
 pub fn print_categorized_results(categorized: &CategorizedTestRunResult) {
     print_results(&categorized.result);
 
+    if !categorized.components.is_empty() {
+        let green = Style::new().green().bold();
+        let red = Style::new().red().bold();
+        println!("By Component:");
+        for c in &categorized.components {
+            let status = if c.failed == 0 { green.apply_to("[PASS]") } else { red.apply_to("[FAIL]") };
+            println!("{} {:<22} {}/{} passed ({:.1}%)", status, c.component, c.passed, c.total, c.pass_rate);
+        }
+        println!();
+    }
+
     if categorized.categories.is_empty() {
         return;
     }
@@ -465,14 +535,6 @@ pub fn print_categorized_results(categorized: &CategorizedTestRunResult) {
     println!();
 }
 
-/// Write test results as pretty-printed JSON to a file.
-pub fn write_json(result: &TestRunResult, output_path: &Path) -> Result<()> {
-    let json = serde_json::to_string_pretty(result).context("Failed to serialize results to JSON")?;
-    fs::write(output_path, json)
-        .with_context(|| format!("Failed to write results to {}", output_path.display()))?;
-    Ok(())
-}
-
 /// Write categorized test results as pretty-printed JSON.
 pub fn write_categorized_json(result: &CategorizedTestRunResult, output_path: &Path) -> Result<()> {
     let json =
@@ -481,3 +543,206 @@ pub fn write_categorized_json(result: &CategorizedTestRunResult, output_path: &P
         .with_context(|| format!("Failed to write results to {}", output_path.display()))?;
     Ok(())
 }
+
+/// Render a run's results as a markdown summary: totals, per-component
+/// breakdown and failures by category.
+pub fn markdown_summary(categorized: &CategorizedTestRunResult) -> String {
+    let result = &categorized.result;
+    let mut md = String::from("## Test Results\n\n");
+    md.push_str(&format!(
+        "**{}/{} passed**, {} failed ({:.1}s)\n",
+        result.passed, result.total, result.failed, result.duration_secs
+    ));
+
+    if !categorized.components.is_empty() {
+        md.push_str("\n### By Component\n\n| Component | Status | Passed | Failed | Pass Rate |\n|---|---|---|---|---|\n");
+        for c in &categorized.components {
+            let status = if c.failed == 0 { ":white_check_mark:" } else { ":x:" };
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {:.1}% |\n",
+                c.component, status, c.passed, c.failed, c.pass_rate
+            ));
+        }
+    }
+
+    if !categorized.categories.is_empty() {
+        md.push_str("\n### Failures by Category\n\n");
+        for group in &categorized.categories {
+            md.push_str(&format!("<details><summary>{} ({})</summary>\n\n", group.category, group.count));
+            for test in &group.tests {
+                md.push_str(&format!("- {}\n", test));
+            }
+            md.push_str("\n</details>\n");
+        }
+    }
+    md
+}
+
+/// Write `results.json` and `summary.md` into `results_dir`, also appending the
+/// summary to `$GITHUB_STEP_SUMMARY` when running under GitHub Actions.
+/// Returns the path of `results.json`.
+pub fn write_results(categorized: &CategorizedTestRunResult, results_dir: &Path) -> Result<PathBuf> {
+    let json_path = results_dir.join("results.json");
+    write_categorized_json(categorized, &json_path)?;
+
+    let summary = markdown_summary(categorized);
+    let summary_path = results_dir.join("summary.md");
+    fs::write(&summary_path, &summary)
+        .with_context(|| format!("Failed to write {}", summary_path.display()))?;
+
+    if let Some(step_summary) = std::env::var_os("GITHUB_STEP_SUMMARY") {
+        use std::io::Write;
+        let appended = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&step_summary)
+            .and_then(|mut f| f.write_all(summary.as_bytes()));
+        if let Err(e) = appended {
+            warnings::warn(format!("Could not append to GITHUB_STEP_SUMMARY: {e}"));
+        }
+    }
+    Ok(json_path)
+}
+
+/// Read a `results.json` written by `write_results`.
+pub fn read_results_json(path: &Path) -> Result<TestRunResult> {
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Scenario names of the failed tests, deduplicated, in result order.
+pub fn failed_scenarios(result: &TestRunResult) -> Vec<String> {
+    let mut scenarios: Vec<String> = Vec::new();
+    for test in result.tests.iter().filter(|t| !t.passed) {
+        if !scenarios.contains(&test.scenario) {
+            scenarios.push(test.scenario.clone());
+        }
+    }
+    scenarios
+}
+
+/// Quote an argument for a POSIX shell.
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=".contains(c)) {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// Shell command line for a gauge invocation, as printed by `results rerun-failed`.
+pub fn gauge_command_line(args: &[String]) -> String {
+    std::iter::once("gauge".to_string())
+        .chain(args.iter().map(|a| shell_quote(a)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Append tests run outside gauge (e.g. the Chains signing check) and update the counts.
+pub fn add_tests(result: &mut TestRunResult, tests: &[TestCaseResult]) {
+    result.tests.extend(tests.iter().cloned());
+    result.total += tests.len();
+    result.passed += tests.iter().filter(|t| t.passed).count();
+    result.failed += tests.iter().filter(|t| !t.passed).count();
+    result.duration_secs += tests.iter().map(|t| t.duration_secs).sum::<f64>();
+}
+
+/// Merge a re-run's results into the original: re-run outcomes replace the
+/// original ones for the same spec/scenario, everything else is kept, and the
+/// totals are recomputed.
+pub fn merge_results(original: &TestRunResult, rerun: &TestRunResult) -> TestRunResult {
+    let mut merged = original.clone();
+    for test in &mut merged.tests {
+        if let Some(r) = rerun.tests.iter().find(|r| r.spec == test.spec && r.scenario == test.scenario) {
+            *test = r.clone();
+        }
+    }
+    merged.passed = merged.tests.iter().filter(|t| t.passed).count();
+    merged.failed = merged.tests.len() - merged.passed;
+    merged.total = merged.tests.len();
+    merged.duration_secs = original.duration_secs + rerun.duration_secs;
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_case(spec: &str, passed: bool) -> TestCaseResult {
+        TestCaseResult {
+            spec: spec.to_string(),
+            scenario: "scenario: PIPELINES-01-TC01".to_string(),
+            passed,
+            duration_secs: 1.0,
+            error_message: (!passed).then(|| "eventlistener not ready".to_string()),
+            components: Vec::new(),
+            log_excerpts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_component_breakdown_and_summary() {
+        let tests = vec![
+            test_case("Verify Triggers E2E spec", false),
+            test_case("Verify Triggers E2E spec", true),
+            test_case("Verify Ecosystem E2E spec", true),
+            test_case("Verify monitoring", true),
+        ];
+        let result = TestRunResult {
+            total: 4,
+            passed: 3,
+            failed: 1,
+            errors: 0,
+            duration_secs: 4.0,
+            source: None,
+            tests,
+        };
+        let categorized = categorize_results(&result);
+        assert_eq!(categorized.result.tests[0].components, vec!["triggers"]);
+        assert!(categorized.result.tests[3].components.is_empty());
+        let names: Vec<(&str, usize, usize)> = categorized
+            .components
+            .iter()
+            .map(|c| (c.component.as_str(), c.passed, c.total))
+            .collect();
+        assert_eq!(names, vec![("pipeline", 1, 1), ("triggers", 1, 2)]);
+
+        let md = markdown_summary(&categorized);
+        assert!(md.contains("| triggers | :x: | 1 | 1 | 50.0% |"));
+        assert!(md.contains("| pipeline | :white_check_mark: | 1 | 0 | 100.0% |"));
+    }
+
+    #[test]
+    fn test_rerun_failed_helpers() {
+        let mut original = TestRunResult {
+            total: 3,
+            passed: 1,
+            failed: 2,
+            errors: 0,
+            duration_secs: 10.0,
+            source: None,
+            tests: vec![
+                test_case("Verify Triggers E2E spec", false),
+                test_case("Verify Ecosystem E2E spec", true),
+                test_case("Tekton Chains tests", false),
+            ],
+        };
+        original.tests[2].scenario = "sign it's image".to_string();
+
+        let json = serde_json::to_string(&categorize_results(&original)).unwrap();
+        let reread: TestRunResult = serde_json::from_str(&json).unwrap();
+        let scenarios = failed_scenarios(&reread);
+        assert_eq!(scenarios, vec!["scenario: PIPELINES-01-TC01", "sign it's image"]);
+
+        let args: Vec<String> = ["run", "--scenario", "sign it's image", "specs/"].map(String::from).to_vec();
+        assert_eq!(gauge_command_line(&args), r"gauge run --scenario 'sign it'\''s image' specs/");
+
+        let mut fixed = original.tests[2].clone();
+        fixed.passed = true;
+        fixed.error_message = None;
+        let rerun = TestRunResult { total: 1, passed: 1, failed: 0, errors: 0, duration_secs: 2.0, source: None, tests: vec![fixed] };
+        let merged = merge_results(&original, &rerun);
+        assert_eq!((merged.total, merged.passed, merged.failed), (3, 2, 1));
+        assert!(merged.tests[2].passed);
+        assert_eq!(merged.duration_secs, 12.0);
+    }
+}
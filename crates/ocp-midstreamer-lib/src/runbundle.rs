@@ -0,0 +1,207 @@
+//! Run bundles: a test output directory packed into one tarball, for moving
+//! runs out of disconnected labs.
+//!
+//! `export-bundle` copies the output directory (results, logs, profiles,
+//! metadata) under `run/` and adds `MANIFEST.json` with the SHA-256 of every
+//! file and the run's provenance (its `results/metadata.json`: components,
+//! commits, as-of date, patches). A `<bundle>.sha256` file next to the tarball
+//! covers the tarball itself. `import-bundle` checks both before unpacking the
+//! run, which can then be re-analyzed or published like a local one.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{exec, platform, timestamp, workspace};
+
+/// Version of the bundle layout; bumped when it changes incompatibly.
+const BUNDLE_FORMAT: u32 = 1;
+
+const MANIFEST_FILE: &str = "MANIFEST.json";
+
+/// Directory in the bundle holding the output directory.
+const RUN_DIR: &str = "run";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format: u32,
+    pub created_at: String,
+    pub streamstress_version: String,
+    /// The run's results/metadata.json, if it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<serde_json::Value>,
+    pub files: Vec<BundleFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct BundleFile {
+    /// Path below `run/`, '/'-separated
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// Files below `dir` with their sizes and checksums, sorted by path.
+fn list_files(dir: &Path) -> Result<Vec<BundleFile>> {
+    fn walk(root: &Path, dir: &Path, out: &mut Vec<BundleFile>) -> Result<()> {
+        for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+            let path = entry?.path();
+            if path.is_dir() {
+                walk(root, &path, out)?;
+            } else {
+                let rel = path.strip_prefix(root)?.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+                out.push(BundleFile { path: rel, size: fs::metadata(&path)?.len(), sha256: platform::sha256_file(&path)? });
+            }
+        }
+        Ok(())
+    }
+    let mut files = Vec::new();
+    walk(dir, dir, &mut files)?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Path of the checksum file written next to a bundle.
+fn checksum_path(bundle: &Path) -> PathBuf {
+    let mut name = bundle.as_os_str().to_os_string();
+    name.push(".sha256");
+    PathBuf::from(name)
+}
+
+/// Pack `output_dir` into `dest` (default: streamstress-bundle-<run id>.tar.gz). Returns the bundle path.
+pub fn export_bundle(output_dir: &Path, dest: Option<&Path>) -> Result<PathBuf> {
+    platform::require_tool("tar", "export-bundle")?;
+    let results = output_dir.join("results/results.json");
+    if !results.exists() {
+        bail!("Results not found at {}. Run `streamstress results` first.", results.display());
+    }
+
+    let created_at = timestamp::now_rfc3339();
+    let dest = match dest {
+        Some(d) => d.to_path_buf(),
+        None => PathBuf::from(format!("streamstress-bundle-{}.tar.gz", timestamp::run_id(&created_at))),
+    };
+
+    let staging = workspace::scoped("bundle-staging").context("Failed to create temp dir")?;
+    let run_dir = staging.path().join(RUN_DIR);
+    platform::copy_dir_recursive(output_dir, &run_dir)?;
+    let files = list_files(&run_dir)?;
+    let provenance = fs::read_to_string(run_dir.join("results/metadata.json"))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok());
+    let manifest = BundleManifest {
+        format: BUNDLE_FORMAT,
+        created_at,
+        streamstress_version: env!("CARGO_PKG_VERSION").to_string(),
+        provenance,
+        files,
+    };
+    fs::write(staging.path().join(MANIFEST_FILE), serde_json::to_string_pretty(&manifest)?)?;
+
+    let dest_str = dest.to_str().context("Bundle path is not valid UTF-8")?;
+    let staging_str = staging.path().to_str().context("Temp dir path is not valid UTF-8")?;
+    exec::run_cmd("tar", &["-czf", dest_str, "-C", staging_str, MANIFEST_FILE, RUN_DIR])?;
+
+    let digest = platform::sha256_file(&dest)?;
+    let file_name = dest.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    fs::write(checksum_path(&dest), format!("{}  {}\n", digest, file_name))?;
+    eprintln!("Bundled {} file(s) from {} into {}", manifest.files.len(), output_dir.display(), dest.display());
+    eprintln!("Checksum: {}", checksum_path(&dest).display());
+    Ok(dest)
+}
+
+/// Verify `bundle` and unpack its run into `dest` (which must be empty or absent).
+pub fn import_bundle(bundle: &Path, dest: &Path) -> Result<BundleManifest> {
+    platform::require_tool("tar", "import-bundle")?;
+    if dest.exists() && fs::read_dir(dest)?.next().is_some() {
+        bail!("{} is not empty", dest.display());
+    }
+
+    let sidecar = checksum_path(bundle);
+    match fs::read_to_string(&sidecar) {
+        Ok(content) => {
+            let expected = content.split_whitespace().next().unwrap_or("");
+            if platform::sha256_file(bundle)? != expected {
+                bail!("{} does not match its checksum in {}", bundle.display(), sidecar.display());
+            }
+            eprintln!("Bundle checksum verified.");
+        }
+        Err(_) => eprintln!("No {} next to the bundle; checking file checksums only.", sidecar.display()),
+    }
+
+    let staging = workspace::scoped("bundle-staging").context("Failed to create temp dir")?;
+    let bundle_str = bundle.to_str().context("Bundle path is not valid UTF-8")?;
+    let staging_str = staging.path().to_str().context("Temp dir path is not valid UTF-8")?;
+    exec::run_cmd("tar", &["-xzf", bundle_str, "-C", staging_str])?;
+
+    let manifest_str = fs::read_to_string(staging.path().join(MANIFEST_FILE))
+        .with_context(|| format!("{} has no {}; not a streamstress bundle", bundle.display(), MANIFEST_FILE))?;
+    let manifest: BundleManifest = serde_json::from_str(&manifest_str).context("Failed to parse bundle manifest")?;
+    if manifest.format != BUNDLE_FORMAT {
+        bail!("Bundle format {} is not supported (expected {}); use a matching streamstress version", manifest.format, BUNDLE_FORMAT);
+    }
+
+    let run_dir = staging.path().join(RUN_DIR);
+    let found = list_files(&run_dir)?;
+    for expected in &manifest.files {
+        match found.iter().find(|f| f.path == expected.path) {
+            None => bail!("Bundle is missing {}", expected.path),
+            Some(f) if f.sha256 != expected.sha256 => bail!("Checksum mismatch for {}", expected.path),
+            Some(_) => {}
+        }
+    }
+    if let Some(extra) = found.iter().find(|f| !manifest.files.iter().any(|m| m.path == f.path)) {
+        bail!("Bundle contains {}, which its manifest does not list", extra.path);
+    }
+
+    platform::copy_dir_recursive(&run_dir, dest)?;
+    eprintln!(
+        "Imported {} verified file(s) (bundled {} by streamstress {}) into {}",
+        manifest.files.len(),
+        manifest.created_at,
+        manifest.streamstress_version,
+        dest.display()
+    );
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("test-output");
+        fs::create_dir_all(output.join("results")).unwrap();
+        fs::create_dir_all(output.join("logs")).unwrap();
+        fs::write(output.join("results/results.json"), r#"{"total":1,"passed":1}"#).unwrap();
+        fs::write(output.join("results/metadata.json"), r#"{"as_of_date":"2026-01-05"}"#).unwrap();
+        fs::write(output.join("logs/test-stdout.log"), "# Spec\n  ## Scenario\n    ...[PASS]\n").unwrap();
+
+        let bundle = export_bundle(&output, Some(&dir.path().join("run.tar.gz"))).unwrap();
+        let imported = dir.path().join("imported");
+        let manifest = import_bundle(&bundle, &imported).unwrap();
+        let paths: Vec<&str> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["logs/test-stdout.log", "results/metadata.json", "results/results.json"]);
+        assert_eq!(manifest.provenance.unwrap()["as_of_date"], "2026-01-05");
+        assert_eq!(fs::read_to_string(imported.join("results/results.json")).unwrap(), r#"{"total":1,"passed":1}"#);
+
+        // A changed tarball fails its checksum; without the checksum file, a changed member fails the manifest
+        assert!(import_bundle(&bundle, &imported).is_err(), "destination not empty");
+        fs::write(checksum_path(&bundle), "0000  run.tar.gz\n").unwrap();
+        assert!(import_bundle(&bundle, &dir.path().join("a")).is_err());
+        fs::remove_file(checksum_path(&bundle)).unwrap();
+        exec::run_cmd("tar", &["-xzf", bundle.to_str().unwrap(), "-C", dir.path().to_str().unwrap()]).unwrap();
+        fs::write(dir.path().join("run/results/results.json"), r#"{"total":1,"passed":0}"#).unwrap();
+        let tampered = dir.path().join("tampered.tar.gz");
+        exec::run_cmd(
+            "tar",
+            &["-czf", tampered.to_str().unwrap(), "-C", dir.path().to_str().unwrap(), MANIFEST_FILE, RUN_DIR],
+        )
+        .unwrap();
+        let err = import_bundle(&tampered, &dir.path().join("b")).unwrap_err();
+        assert!(err.to_string().contains("results/results.json"), "{err}");
+    }
+}
@@ -0,0 +1,124 @@
+//! Arbitrary `key=value` metadata attached to a run with `--meta` (e.g. `ocp=4.17`,
+//! `cloud=aws`, `team=triggers`).
+//!
+//! The metadata is recorded in `results/metadata.json` and merged into the
+//! published run file; the keys selected for filtering are also copied into the
+//! run's manifest entry, so the dashboard can filter runs without loading them.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Metadata of a run, by key.
+pub type RunMeta = BTreeMap<String, String>;
+
+/// Environment variable listing the metadata keys copied into manifest entries
+/// (comma-separated), overriding `DEFAULT_MANIFEST_KEYS`.
+pub const MANIFEST_KEYS_ENV: &str = "STREAMSTRESS_MANIFEST_META_KEYS";
+
+/// Metadata keys copied into manifest entries by default.
+pub const DEFAULT_MANIFEST_KEYS: &[&str] = &["ocp", "cloud", "platform", "team", "operator_branch"];
+
+static META: OnceLock<RunMeta> = OnceLock::new();
+
+/// One `--meta key=value` flag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetaPair {
+    pub key: String,
+    pub value: String,
+}
+
+/// Parse `key=value`. Used by clap's value_parser for --meta.
+pub fn parse_meta(s: &str) -> std::result::Result<MetaPair, String> {
+    let Some((key, value)) = s.split_once('=') else {
+        return Err(format!("Expected KEY=VALUE, got '{}'", s));
+    };
+    let (key, value) = (key.trim(), value.trim());
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+        return Err(format!("Invalid metadata key '{}' (letters, digits, '_', '-' and '.' only)", key));
+    }
+    Ok(MetaPair { key: key.to_string(), value: value.to_string() })
+}
+
+/// Set the run's metadata from the `--meta` flags; a repeated key keeps its last
+/// value. Call once at startup of the commands that take `--meta`.
+pub fn init(pairs: &[MetaPair]) {
+    let _ = META.set(pairs.iter().map(|p| (p.key.clone(), p.value.clone())).collect());
+}
+
+/// The run's metadata (empty when no `--meta` was given).
+pub fn current() -> RunMeta {
+    META.get().cloned().unwrap_or_default()
+}
+
+/// `--meta` arguments reproducing the run's metadata in a child process
+/// (in-cluster Job or batch date run).
+pub fn to_args() -> Vec<String> {
+    current()
+        .into_iter()
+        .flat_map(|(key, value)| ["--meta".to_string(), format!("{}={}", key, value)])
+        .collect()
+}
+
+/// Metadata keys copied into manifest entries.
+pub fn manifest_keys() -> Vec<String> {
+    match std::env::var(MANIFEST_KEYS_ENV) {
+        Ok(keys) => keys.split(',').map(str::trim).filter(|k| !k.is_empty()).map(String::from).collect(),
+        Err(_) => DEFAULT_MANIFEST_KEYS.iter().map(|k| k.to_string()).collect(),
+    }
+}
+
+/// The entries of `meta` whose key is in `keys`.
+pub fn select(meta: &RunMeta, keys: &[String]) -> RunMeta {
+    meta.iter()
+        .filter(|(key, _)| keys.contains(key))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// Record the run's metadata in `results/metadata.json` under `output_dir`, for
+/// runs that do not write the full run metadata (Konflux). Keeps other fields.
+pub fn record(output_dir: &Path) -> Result<()> {
+    let meta = current();
+    if meta.is_empty() {
+        return Ok(());
+    }
+    let results_dir = output_dir.join("results");
+    std::fs::create_dir_all(&results_dir).context("Failed to create results directory")?;
+    let path = results_dir.join("metadata.json");
+    let mut doc = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        .filter(|v| v.is_object())
+        .unwrap_or_else(|| serde_json::json!({}));
+    doc["meta"] = serde_json::json!(meta);
+    std::fs::write(&path, serde_json::to_string_pretty(&doc)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_meta() {
+        assert_eq!(parse_meta("ocp=4.17").unwrap(), MetaPair { key: "ocp".into(), value: "4.17".into() });
+        assert_eq!(parse_meta(" note = a=b ").unwrap().value, "a=b");
+        assert_eq!(parse_meta("flag=").unwrap().value, "");
+        assert!(parse_meta("ocp").is_err());
+        assert!(parse_meta("=aws").is_err());
+        assert!(parse_meta("my key=x").is_err());
+    }
+
+    #[test]
+    fn test_select() {
+        let meta: RunMeta = [("ocp", "4.17"), ("cloud", "aws"), ("ticket", "SRVKP-1")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let selected = select(&meta, &["ocp".to_string(), "team".to_string()]);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected["ocp"], "4.17");
+    }
+}
@@ -0,0 +1,133 @@
+//! Guard rails against modifying production clusters.
+//!
+//! Deploys patch the operator Deployment and delete InstallerSets, and setup
+//! installs and reconfigures cluster resources. Before any of that, the cluster
+//! is compared against `config/safety.toml`: an ingress domain or ClusterVersion
+//! label that looks like production stops the command unless
+//! `--i-know-what-im-doing` is passed.
+
+use anyhow::bail;
+use kube::api::{Api, ApiResource, DynamicObject};
+use std::collections::BTreeMap;
+
+use crate::{config, k8s, warnings};
+
+/// What identifies a cluster for the production checks.
+#[derive(Debug, Default)]
+pub struct ClusterIdentity {
+    /// Ingress domain that routes (including the console) are created under
+    pub domain: Option<String>,
+    /// Labels on the ClusterVersion
+    pub labels: BTreeMap<String, String>,
+}
+
+/// Refuse `action` on a production-looking cluster unless `allow` is set.
+/// A cluster that cannot be reached is let through: the command fails on its own.
+pub fn guard(action: &str, allow: bool) -> anyhow::Result<()> {
+    let cfg = config::load_safety_config(&config::default_safety_config_path())?;
+    let Ok(identity) = read_cluster_identity() else {
+        return Ok(());
+    };
+    let reasons = production_reasons(&identity, &cfg);
+    if reasons.is_empty() {
+        return Ok(());
+    }
+    let cluster = identity.domain.as_deref().unwrap_or("the current cluster");
+    if allow {
+        warnings::warn(format!("{} looks like a production cluster ({}); continuing because of --i-know-what-im-doing", cluster, reasons.join("; ")));
+        return Ok(());
+    }
+    bail!(
+        "Refusing to {} on {}: it looks like a production cluster ({}).\n\
+         streamstress modifies the cluster (e.g. patches the operator Deployment and deletes InstallerSets). \
+         If the cluster is safe to modify, \
+         add its domain to allowed_domains in {}, or pass --i-know-what-im-doing.",
+        action,
+        cluster,
+        reasons.join("; "),
+        config::default_safety_config_path().display()
+    )
+}
+
+/// Why `identity` looks like production; empty if it does not or its domain is allowed.
+pub fn production_reasons(identity: &ClusterIdentity, cfg: &config::SafetyConfig) -> Vec<String> {
+    if let Some(ref domain) = identity.domain {
+        let allowed = cfg
+            .allowed_domains
+            .iter()
+            .any(|a| domain == a || domain.ends_with(&format!(".{}", a)));
+        if allowed {
+            return Vec::new();
+        }
+    }
+
+    let mut reasons = Vec::new();
+    if let Some(ref domain) = identity.domain {
+        for pattern in &cfg.production_domain_patterns {
+            if regex::Regex::new(pattern).is_ok_and(|re| re.is_match(domain)) {
+                reasons.push(format!("domain {} matches '{}'", domain, pattern));
+            }
+        }
+    }
+    for label in &cfg.production_labels {
+        if let Some((key, value)) = label.split_once('=')
+            && identity.labels.get(key).is_some_and(|v| v == value)
+        {
+            reasons.push(format!("ClusterVersion is labeled {}", label));
+        }
+    }
+    reasons
+}
+
+/// Ingress domain and ClusterVersion labels of the current cluster. Either may
+/// be missing (e.g. without read access); only connecting is an error.
+fn read_cluster_identity() -> anyhow::Result<ClusterIdentity> {
+    let (rt, client) = k8s::create_kube_client()?;
+    let config_api = |kind: &str, plural: &str| -> Api<DynamicObject> {
+        let ar = ApiResource {
+            group: "config.openshift.io".into(),
+            version: "v1".into(),
+            api_version: "config.openshift.io/v1".into(),
+            kind: kind.into(),
+            plural: plural.into(),
+        };
+        Api::all_with(client.clone(), &ar)
+    };
+
+    let domain = rt
+        .block_on(config_api("Ingress", "ingresses").get("cluster"))
+        .ok()
+        .and_then(|ingress| ingress.data.pointer("/spec/domain").and_then(|d| d.as_str()).map(String::from));
+    let labels = rt
+        .block_on(config_api("ClusterVersion", "clusterversions").get("version"))
+        .map(|cv| cv.metadata.labels.unwrap_or_default())
+        .unwrap_or_default();
+    Ok(ClusterIdentity { domain, labels: labels.into_iter().collect() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(domain: &str, labels: &[(&str, &str)]) -> ClusterIdentity {
+        ClusterIdentity {
+            domain: Some(domain.to_string()),
+            labels: labels.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+
+    #[test]
+    fn test_production_reasons() {
+        let mut cfg = config::SafetyConfig::default();
+        assert!(production_reasons(&identity("apps.ci-ln-abc.example.com", &[]), &cfg).is_empty());
+        assert!(production_reasons(&identity("apps.product-demo.example.com", &[]), &cfg).is_empty());
+        assert_eq!(production_reasons(&identity("apps.prod-east.example.com", &[]), &cfg).len(), 1);
+        assert_eq!(
+            production_reasons(&identity("apps.ci.example.com", &[("environment", "production")]), &cfg),
+            ["ClusterVersion is labeled environment=production"]
+        );
+
+        cfg.allowed_domains = vec!["prod-east.example.com".to_string()];
+        assert!(production_reasons(&identity("apps.prod-east.example.com", &[("env", "prod")]), &cfg).is_empty());
+    }
+}
@@ -0,0 +1,288 @@
+//! A namespace-scoped user for the release tests (`--scoped-user`).
+//!
+//! Some release-tests specs check what a non-admin user can and cannot do and
+//! are skipped when no such user is configured. With `--scoped-user` the test
+//! phase creates the `streamstress-scoped` namespace, a ServiceAccount in it
+//! bound to a ClusterRole (`edit` unless `--scoped-user-role` says otherwise)
+//! in that namespace only, and a kubeconfig carrying a short-lived token from
+//! the TokenRequest API. gauge gets the kubeconfig path and the namespace as
+//! `STREAMSTRESS_SCOPED_KUBECONFIG` and `STREAMSTRESS_SCOPED_NAMESPACE`; all of
+//! it is deleted again once the tests finish.
+
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use k8s_openapi::api::authentication::v1::TokenRequest;
+use k8s_openapi::api::core::v1::{Namespace, ServiceAccount};
+use k8s_openapi::api::rbac::v1::RoleBinding;
+use kube::api::{Api, DeleteParams, PostParams};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::{audit, cassette, k8s, warnings};
+
+/// Namespace the scoped user is confined to.
+pub const NAMESPACE: &str = "streamstress-scoped";
+
+/// ServiceAccount (and RoleBinding) of the scoped user.
+pub const SERVICE_ACCOUNT: &str = "streamstress-scoped";
+
+/// ClusterRole bound in the namespace unless --scoped-user-role is given.
+pub const DEFAULT_ROLE: &str = "edit";
+
+/// Env var holding the scoped kubeconfig path for the specs.
+pub const KUBECONFIG_ENV: &str = "STREAMSTRESS_SCOPED_KUBECONFIG";
+
+/// Env var holding the namespace the scoped user may use.
+pub const NAMESPACE_ENV: &str = "STREAMSTRESS_SCOPED_NAMESPACE";
+
+/// Lifetime requested for the token; long enough for a full test run.
+const TOKEN_EXPIRATION_SECS: i64 = 4 * 3600;
+
+/// ClusterRole to bind, when --scoped-user is on.
+static ROLE: OnceLock<Option<String>> = OnceLock::new();
+
+/// Set whether a scoped user is provisioned for the tests, and its ClusterRole.
+/// Call once at startup.
+pub fn init(enabled: bool, role: &str) {
+    let _ = ROLE.set(enabled.then(|| role.to_string()));
+}
+
+/// CLI args recreating this setting (for self-invocation and in-cluster Jobs).
+pub fn to_args() -> Vec<String> {
+    match ROLE.get().and_then(Option::as_deref) {
+        Some(role) if role == DEFAULT_ROLE => vec!["--scoped-user".to_string()],
+        Some(role) => vec!["--scoped-user".to_string(), "--scoped-user-role".to_string(), role.to_string()],
+        None => Vec::new(),
+    }
+}
+
+/// Whether --scoped-user is on.
+pub fn enabled() -> bool {
+    ROLE.get().is_some_and(Option::is_some)
+}
+
+/// A provisioned scoped user; pass it to `cleanup` when the tests are done.
+pub struct ScopedUser {
+    pub kubeconfig: PathBuf,
+    created_namespace: bool,
+}
+
+impl ScopedUser {
+    /// Env vars handing the scoped user to the specs.
+    pub fn env(&self) -> Vec<(String, String)> {
+        vec![
+            (KUBECONFIG_ENV.to_string(), self.kubeconfig.display().to_string()),
+            (NAMESPACE_ENV.to_string(), NAMESPACE.to_string()),
+        ]
+    }
+}
+
+/// Create the namespace, ServiceAccount and RoleBinding and write a kubeconfig
+/// for the ServiceAccount to `dir`. What was created is removed again on error.
+pub async fn provision(dir: &Path) -> Result<ScopedUser> {
+    let role = ROLE.get().and_then(Option::as_deref).unwrap_or(DEFAULT_ROLE);
+    let client = k8s::client().await.context("Failed to connect to the cluster")?;
+
+    let ns_api: Api<Namespace> = Api::all(client.clone());
+    let ns: Namespace = serde_json::from_value(json!({
+        "apiVersion": "v1",
+        "kind": "Namespace",
+        "metadata": { "name": NAMESPACE, "labels": { "app": "streamstress" } }
+    }))?;
+    let pp = PostParams::default();
+    let result = k8s::retry("create Namespace", || ns_api.create(&pp, &ns)).await;
+    audit::record_api("create", "Namespace", None, NAMESPACE, result.is_ok());
+    let created_namespace = match result {
+        Ok(_) => true,
+        Err(kube::Error::Api(ae)) if ae.code == 409 => false,
+        Err(e) => return Err(e).context("Failed to create the scoped user namespace"),
+    };
+    let user = ScopedUser { kubeconfig: dir.join("scoped-kubeconfig"), created_namespace };
+
+    match create_user(&client, role, &user.kubeconfig).await {
+        Ok(()) => {
+            eprintln!("Scoped user {}/{} bound to {}", NAMESPACE, SERVICE_ACCOUNT, role);
+            Ok(user)
+        }
+        Err(e) => {
+            cleanup(user).await;
+            Err(e)
+        }
+    }
+}
+
+async fn create_user(client: &kube::Client, role: &str, kubeconfig: &Path) -> Result<()> {
+    let pp = PostParams::default();
+
+    let sa_api: Api<ServiceAccount> = Api::namespaced(client.clone(), NAMESPACE);
+    let sa: ServiceAccount = serde_json::from_value(json!({
+        "apiVersion": "v1",
+        "kind": "ServiceAccount",
+        "metadata": { "name": SERVICE_ACCOUNT, "namespace": NAMESPACE, "labels": { "app": "streamstress" } }
+    }))?;
+    let result = k8s::retry("create ServiceAccount", || sa_api.create(&pp, &sa)).await;
+    audit::record_api("create", "ServiceAccount", Some(NAMESPACE), SERVICE_ACCOUNT, result.is_ok());
+    match result {
+        Ok(_) => {}
+        Err(kube::Error::Api(ae)) if ae.code == 409 => {}
+        Err(e) => return Err(e).context("Failed to create the scoped user ServiceAccount"),
+    }
+
+    let rb_api: Api<RoleBinding> = Api::namespaced(client.clone(), NAMESPACE);
+    let rb: RoleBinding = serde_json::from_value(role_binding_manifest(role))?;
+    let result = k8s::retry("create RoleBinding", || rb_api.create(&pp, &rb)).await;
+    audit::record_api("create", "RoleBinding", Some(NAMESPACE), SERVICE_ACCOUNT, result.is_ok());
+    match result {
+        Ok(_) => {}
+        Err(kube::Error::Api(ae)) if ae.code == 409 => {}
+        Err(e) => return Err(e).with_context(|| format!("Failed to bind {} to the scoped user", role)),
+    }
+
+    let request: TokenRequest = serde_json::from_value(json!({
+        "apiVersion": "authentication.k8s.io/v1",
+        "kind": "TokenRequest",
+        "spec": { "audiences": [], "expirationSeconds": TOKEN_EXPIRATION_SECS }
+    }))?;
+    let result = k8s::retry("create ServiceAccount token", || sa_api.create_token_request(SERVICE_ACCOUNT, &pp, &request)).await;
+    audit::record_api("create", "ServiceAccount/token", Some(NAMESPACE), SERVICE_ACCOUNT, result.is_ok());
+    let token = result
+        .context("Failed to request a token for the scoped user")?
+        .status
+        .map(|s| s.token)
+        .context("TokenRequest returned no token")?;
+
+    let (server, ca) = match kube::Config::infer().await {
+        Ok(cfg) => (cfg.cluster_url.to_string(), if cfg.accept_invalid_certs { None } else { cfg.root_cert }),
+        // Nothing talks to the cluster when replaying; the kubeconfig only has to exist
+        Err(_) if cassette::replaying() => ("https://replayed.invalid".to_string(), None),
+        Err(e) => return Err(e).context("Failed to read the cluster address from the kubeconfig"),
+    };
+    let ca_pem = ca.map(|certs| certs.iter().map(Vec::as_slice).map(pem_certificate).collect::<String>());
+    let doc = kubeconfig(&server, ca_pem.as_deref(), &token, NAMESPACE);
+    write_private(kubeconfig, &serde_yaml::to_string(&doc)?)
+}
+
+/// Delete the RoleBinding and ServiceAccount, and the namespace if `provision`
+/// created it. Failures only warn.
+pub async fn cleanup(user: ScopedUser) {
+    if let Err(e) = std::fs::remove_file(&user.kubeconfig) && e.kind() != std::io::ErrorKind::NotFound {
+        warnings::warn(format!("Failed to remove {}: {e}", user.kubeconfig.display()));
+    }
+    let client = match k8s::client().await {
+        Ok(c) => c,
+        Err(e) => {
+            warnings::warn(format!("Could not connect to the cluster to remove the scoped user: {e}"));
+            return;
+        }
+    };
+    let dp = DeleteParams::default();
+    let deleted = |kind: &str, result: kube::Result<()>| match result {
+        Ok(()) => {}
+        Err(kube::Error::Api(ae)) if ae.code == 404 => {}
+        Err(e) => warnings::warn(format!("Failed to delete scoped user {} in {}: {e}", kind, NAMESPACE)),
+    };
+
+    let rb_api: Api<RoleBinding> = Api::namespaced(client.clone(), NAMESPACE);
+    let result = k8s::retry("delete RoleBinding", || rb_api.delete(SERVICE_ACCOUNT, &dp)).await.map(|_| ());
+    audit::record_api("delete", "RoleBinding", Some(NAMESPACE), SERVICE_ACCOUNT, result.is_ok());
+    deleted("RoleBinding", result);
+
+    let sa_api: Api<ServiceAccount> = Api::namespaced(client.clone(), NAMESPACE);
+    let result = k8s::retry("delete ServiceAccount", || sa_api.delete(SERVICE_ACCOUNT, &dp)).await.map(|_| ());
+    audit::record_api("delete", "ServiceAccount", Some(NAMESPACE), SERVICE_ACCOUNT, result.is_ok());
+    deleted("ServiceAccount", result);
+
+    if user.created_namespace {
+        let ns_api: Api<Namespace> = Api::all(client);
+        let result = k8s::retry("delete Namespace", || ns_api.delete(NAMESPACE, &dp)).await.map(|_| ());
+        audit::record_api("delete", "Namespace", None, NAMESPACE, result.is_ok());
+        deleted("Namespace", result);
+    }
+}
+
+/// RoleBinding of `role` to the scoped ServiceAccount, within its namespace.
+fn role_binding_manifest(role: &str) -> Value {
+    json!({
+        "apiVersion": "rbac.authorization.k8s.io/v1",
+        "kind": "RoleBinding",
+        "metadata": { "name": SERVICE_ACCOUNT, "namespace": NAMESPACE, "labels": { "app": "streamstress" } },
+        "roleRef": { "apiGroup": "rbac.authorization.k8s.io", "kind": "ClusterRole", "name": role },
+        "subjects": [{ "kind": "ServiceAccount", "name": SERVICE_ACCOUNT, "namespace": NAMESPACE }]
+    })
+}
+
+/// A kubeconfig with one context: `token` against `server`, defaulting to
+/// `namespace`. Without a CA the server certificate is not verified, as with
+/// the kubeconfig the run itself uses.
+fn kubeconfig(server: &str, ca_pem: Option<&str>, token: &str, namespace: &str) -> Value {
+    let mut cluster = json!({ "server": server });
+    match ca_pem {
+        Some(pem) => cluster["certificate-authority-data"] = json!(STANDARD.encode(pem)),
+        None => cluster["insecure-skip-tls-verify"] = json!(true),
+    }
+    json!({
+        "apiVersion": "v1",
+        "kind": "Config",
+        "clusters": [{ "name": "cluster", "cluster": cluster }],
+        "users": [{ "name": SERVICE_ACCOUNT, "user": { "token": token } }],
+        "contexts": [{
+            "name": SERVICE_ACCOUNT,
+            "context": { "cluster": "cluster", "user": SERVICE_ACCOUNT, "namespace": namespace }
+        }],
+        "current-context": SERVICE_ACCOUNT
+    })
+}
+
+/// PEM encoding of a DER certificate.
+fn pem_certificate(der: &[u8]) -> String {
+    let b64 = STANDARD.encode(der);
+    let mut out = String::from("-----BEGIN CERTIFICATE-----\n");
+    for line in b64.as_bytes().chunks(64) {
+        out.push_str(std::str::from_utf8(line).expect("base64 is ASCII"));
+        out.push('\n');
+    }
+    out.push_str("-----END CERTIFICATE-----\n");
+    out
+}
+
+/// Write a file holding a credential, readable by its owner only.
+fn write_private(path: &Path, contents: &str) -> Result<()> {
+    std::fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kubeconfig() {
+        let doc = kubeconfig("https://api.example:6443", Some("PEM"), "tok", "ns");
+        assert_eq!(doc["clusters"][0]["cluster"]["server"], "https://api.example:6443");
+        assert_eq!(doc["clusters"][0]["cluster"]["certificate-authority-data"], STANDARD.encode("PEM"));
+        assert_eq!(doc["users"][0]["user"]["token"], "tok");
+        assert_eq!(doc["contexts"][0]["context"]["namespace"], "ns");
+        assert_eq!(doc["current-context"], SERVICE_ACCOUNT);
+
+        let insecure = kubeconfig("https://api.example:6443", None, "tok", "ns");
+        assert_eq!(insecure["clusters"][0]["cluster"]["insecure-skip-tls-verify"], true);
+        assert!(insecure["clusters"][0]["cluster"].get("certificate-authority-data").is_none());
+    }
+
+    #[test]
+    fn test_pem_certificate() {
+        let pem = pem_certificate(&[0u8; 60]);
+        let lines: Vec<&str> = pem.lines().collect();
+        assert_eq!(lines.first(), Some(&"-----BEGIN CERTIFICATE-----"));
+        assert_eq!(lines.last(), Some(&"-----END CERTIFICATE-----"));
+        assert_eq!(lines[1].len(), 64);
+        assert_eq!(lines.len(), 4);
+    }
+}
@@ -0,0 +1,227 @@
+//! `streamstress self-update`: replace the running binary with a release build.
+//!
+//! Release assets are named `streamstress-<os>-<arch>` (plus `.exe` on Windows)
+//! and downloaded with `gh release download`, so the same gh authentication used
+//! elsewhere applies. Each release also carries a `SHA256SUMS` asset, signed
+//! keylessly by the release workflow; the signature is checked with cosign and
+//! the download against its checksum before the new binary is ever run. It must
+//! then report the expected version before it replaces the current one.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+use crate::exec;
+use crate::github;
+use crate::platform;
+use crate::signing;
+use crate::workspace;
+
+/// Repository that publishes streamstress releases.
+pub const RELEASE_REPO: &str = "openshift-pipelines/ocp-midstreamer";
+
+/// Checksum asset listing every binary of a release, as written by `sha256sum`.
+pub const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+
+/// OIDC issuer of the GitHub Actions workflow that signs `SHA256SUMS`.
+const SIGNER_OIDC_ISSUER: &str = "https://token.actions.githubusercontent.com";
+
+/// Certificate identity of the release workflow run that signed `tag`.
+fn signer_identity(tag: &str) -> String {
+    format!("https://github.com/{}/.github/workflows/release-binaries.yml@refs/tags/{}", RELEASE_REPO, tag)
+}
+
+/// Release asset for this platform, e.g. `streamstress-linux-x86_64`.
+pub fn asset_name() -> String {
+    format!(
+        "streamstress-{}-{}{}",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        std::env::consts::EXE_SUFFIX
+    )
+}
+
+/// Release tag for a version: `0.1.7` and `v0.1.7` both become `v0.1.7`.
+pub fn normalize_tag(version: &str) -> String {
+    format!("v{}", version.trim().trim_start_matches('v'))
+}
+
+/// Version from `streamstress --version` output (`streamstress 0.1.7`).
+fn parse_version_output(output: &str) -> Option<&str> {
+    output.split_whitespace().nth(1)
+}
+
+/// Checksum of `asset` in `sha256sum` output (`<hex>  <name>`, or `<hex> *<name>`
+/// for binary mode).
+fn expected_checksum<'a>(sums: &'a str, asset: &str) -> Option<&'a str> {
+    sums.lines().find_map(|line| {
+        let (hex, name) = line.trim_end().split_once(char::is_whitespace)?;
+        (name.trim_start().trim_start_matches('*') == asset).then_some(hex)
+    })
+}
+
+/// Check the signature on `SHA256SUMS` and that `downloaded` matches its entry.
+fn verify_download(dir: &Path, asset: &str, tag: &str) -> Result<()> {
+    let sums_path = dir.join(CHECKSUMS_ASSET);
+    let identity = signing::Identity {
+        key: None,
+        certificate_identity: Some(signer_identity(tag)),
+        certificate_oidc_issuer: Some(SIGNER_OIDC_ISSUER.to_string()),
+    };
+    signing::verify_file(&sums_path, None, &identity)
+        .with_context(|| format!("{} of release {} is not signed by its release workflow", CHECKSUMS_ASSET, tag))?;
+
+    let sums = fs::read_to_string(&sums_path).with_context(|| format!("Failed to read {}", sums_path.display()))?;
+    let expected = expected_checksum(&sums, asset)
+        .with_context(|| format!("{} of release {} has no entry for {}", CHECKSUMS_ASSET, tag, asset))?;
+    let actual = platform::sha256_file(&dir.join(asset))?;
+    if !actual.eq_ignore_ascii_case(expected) {
+        anyhow::bail!("Checksum mismatch for {}: got {}, {} lists {}", asset, actual, CHECKSUMS_ASSET, expected);
+    }
+    Ok(())
+}
+
+fn latest_release_tag() -> Result<String> {
+    let endpoint = format!("repos/{}/releases/latest", RELEASE_REPO);
+    let resp = github::api_request("GET", &endpoint, None, &[])?;
+    if !resp.is_success() {
+        anyhow::bail!("Failed to query latest release of {} (HTTP {})", RELEASE_REPO, resp.status);
+    }
+    resp.json()?["tag_name"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("Latest release of {} has no tag", RELEASE_REPO))
+}
+
+/// Update to `version` (default: latest release). With `check_only`, just report.
+pub fn run_self_update(version: Option<&str>, check_only: bool) -> Result<()> {
+    let current = normalize_tag(env!("CARGO_PKG_VERSION"));
+    let target = match version {
+        Some(v) => normalize_tag(v),
+        None => latest_release_tag()?,
+    };
+
+    if check_only {
+        println!("Current: {}  Available: {}", current, target);
+        return Ok(());
+    }
+    if target == current {
+        eprintln!("Already at {}.", current);
+        return Ok(());
+    }
+
+    platform::require_tool("cosign", "self-update")?;
+    let asset = asset_name();
+    let sums_bundle = format!("{}{}", CHECKSUMS_ASSET, signing::BUNDLE_SUFFIX);
+    let download_dir = workspace::scoped("self-update").context("Failed to create download directory")?;
+    let dir = download_dir.path().to_string_lossy().to_string();
+    eprintln!("Downloading {} from {} release {}...", asset, RELEASE_REPO, target);
+    exec::run_cmd(
+        "gh",
+        &[
+            "release",
+            "download",
+            &target,
+            "--repo",
+            RELEASE_REPO,
+            "--pattern",
+            &asset,
+            "--pattern",
+            CHECKSUMS_ASSET,
+            "--pattern",
+            &sums_bundle,
+            "--dir",
+            &dir,
+        ],
+    )
+    .with_context(|| format!("No {}, {} or {} asset in release {}", asset, CHECKSUMS_ASSET, sums_bundle, target))?;
+
+    verify_download(download_dir.path(), &asset, &target)?;
+    let downloaded = download_dir.path().join(&asset);
+    make_executable(&downloaded)?;
+    let check = exec::run_cmd(&downloaded.to_string_lossy(), &["--version"])
+        .context("Downloaded binary does not run on this platform")?;
+    let reported = parse_version_output(&check.stdout).map(normalize_tag);
+    if reported.as_deref() != Some(target.as_str()) {
+        anyhow::bail!(
+            "Downloaded binary reports {:?}, expected {}",
+            check.stdout.trim(),
+            target
+        );
+    }
+
+    let exe = std::env::current_exe().context("Failed to locate the running binary")?;
+    replace_exe(&downloaded, &exe)?;
+    eprintln!("Updated {} from {} to {}.", exe.display(), current, target);
+    Ok(())
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+        .with_context(|| format!("Failed to chmod {}", path.display()))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Swap `new` in for `exe`. The copy lands next to `exe` first so the final
+/// rename stays on one filesystem; Windows cannot overwrite a running binary,
+/// so the old one is moved aside there.
+fn replace_exe(new: &Path, exe: &Path) -> Result<()> {
+    let staged = exe.with_extension("new");
+    fs::copy(new, &staged).with_context(|| format!("Failed to write {}", staged.display()))?;
+    make_executable(&staged)?;
+    if cfg!(windows) {
+        let old = exe.with_extension("old");
+        let _ = fs::remove_file(&old);
+        fs::rename(exe, &old).with_context(|| format!("Failed to move aside {}", exe.display()))?;
+    }
+    fs::rename(&staged, exe).with_context(|| format!("Failed to replace {}", exe.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tag_and_version_output() {
+        assert_eq!(normalize_tag("0.1.7"), "v0.1.7");
+        assert_eq!(normalize_tag("v0.1.7"), "v0.1.7");
+        assert_eq!(parse_version_output("streamstress 0.1.7\n"), Some("0.1.7"));
+        assert_eq!(parse_version_output(""), None);
+    }
+
+    #[test]
+    fn test_expected_checksum() {
+        let sums = "aa11  streamstress-linux-x86_64\nbb22 *streamstress-windows-x86_64.exe\n";
+        assert_eq!(expected_checksum(sums, "streamstress-linux-x86_64"), Some("aa11"));
+        assert_eq!(expected_checksum(sums, "streamstress-windows-x86_64.exe"), Some("bb22"));
+        assert_eq!(expected_checksum(sums, "streamstress-linux"), None);
+        assert_eq!(expected_checksum("", "streamstress-linux-x86_64"), None);
+    }
+
+    #[test]
+    fn test_verify_download_needs_signature() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("streamstress-linux-x86_64"), "binary").unwrap();
+        fs::write(dir.path().join(CHECKSUMS_ASSET), "00  streamstress-linux-x86_64\n").unwrap();
+        let err = verify_download(dir.path(), "streamstress-linux-x86_64", "v0.1.7").unwrap_err();
+        assert!(format!("{err:#}").contains("No signature bundle"), "{err:#}");
+    }
+
+    #[test]
+    fn test_replace_exe() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = dir.path().join("streamstress");
+        let new = dir.path().join("download");
+        fs::write(&exe, "old").unwrap();
+        fs::write(&new, "new").unwrap();
+        replace_exe(&new, &exe).unwrap();
+        assert_eq!(fs::read_to_string(&exe).unwrap(), "new");
+        assert!(!exe.with_extension("new").exists());
+    }
+}
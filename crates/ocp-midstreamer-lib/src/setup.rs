@@ -0,0 +1,772 @@
+use anyhow::{bail, Context};
+use k8s_openapi::api::core::v1::Namespace;
+use k8s_openapi::api::storage::v1::StorageClass;
+use kube::api::{Api, ApiResource, DynamicObject, ListParams, Patch, PatchParams, PostParams};
+use kube::Client;
+use serde_json::json;
+use std::sync::OnceLock;
+use tokio::runtime::Runtime;
+
+use crate::config::OperatorConfig;
+use crate::{audit, confirm, exec, k8s, progress, registry, warnings};
+
+/// Storage auto-setup configures for the internal image registry.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq)]
+pub enum RegistryStorage {
+    /// Node-local emptyDir (images are lost when the registry pod restarts)
+    #[default]
+    EmptyDir,
+    /// PersistentVolumeClaim from the default StorageClass, falling back to emptyDir if there is none
+    Pvc,
+}
+
+/// Namespace and name of the operator Subscription setup creates.
+const OPERATOR_NAMESPACE: &str = "openshift-operators";
+const SUBSCRIPTION_NAME: &str = "openshift-pipelines-operator";
+
+static REGISTRY_STORAGE: OnceLock<RegistryStorage> = OnceLock::new();
+static OPERATOR: OnceLock<OperatorConfig> = OnceLock::new();
+
+/// Set the registry storage and operator Subscription auto-setup should configure. Call once at startup.
+pub fn init(storage: RegistryStorage, operator: OperatorConfig) {
+    let _ = REGISTRY_STORAGE.set(storage);
+    let _ = OPERATOR.set(operator);
+}
+
+pub fn registry_storage() -> RegistryStorage {
+    REGISTRY_STORAGE.get().copied().unwrap_or_default()
+}
+
+pub fn operator_config() -> OperatorConfig {
+    OPERATOR.get().cloned().unwrap_or_default()
+}
+
+/// `--operator-*` arguments reproducing the operator settings in a child process,
+/// for those differing from the defaults.
+pub fn operator_args() -> Vec<String> {
+    let (cfg, default) = (operator_config(), OperatorConfig::default());
+    let mut args = Vec::new();
+    for (flag, value, default) in [
+        ("--operator-channel", &cfg.channel, &default.channel),
+        ("--operator-source", &cfg.source, &default.source),
+        ("--operator-source-namespace", &cfg.source_namespace, &default.source_namespace),
+    ] {
+        if value != default {
+            args.extend([flag.to_string(), value.clone()]);
+        }
+    }
+    if let Some(csv) = cfg.starting_csv {
+        args.extend(["--operator-starting-csv".to_string(), csv]);
+    }
+    if cfg.approve_install_plans {
+        args.push("--approve-install-plans".to_string());
+    }
+    args
+}
+
+/// A step of `streamstress setup`, selectable with `--only`/`--skip`.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum SetupStep {
+    /// Internal image registry: managed state, default route, storage
+    Registry,
+    /// Image namespace and image-puller RBAC
+    Namespace,
+    /// OpenShift Pipelines operator Subscription, waiting until it is Available
+    Operator,
+    /// TektonConfig CR
+    Tektonconfig,
+}
+
+impl SetupStep {
+    pub const ALL: [SetupStep; 4] = [SetupStep::Registry, SetupStep::Namespace, SetupStep::Operator, SetupStep::Tektonconfig];
+
+    fn name(self) -> &'static str {
+        match self {
+            SetupStep::Registry => "registry",
+            SetupStep::Namespace => "namespace",
+            SetupStep::Operator => "operator",
+            SetupStep::Tektonconfig => "tektonconfig",
+        }
+    }
+
+    /// The actions of the step, each with its spinner label and warning prefix.
+    fn actions(self) -> Vec<(&'static str, &'static str, StepFn)> {
+        match self {
+            SetupStep::Registry => vec![
+                ("Ensuring image registry route", "Registry route setup", ensure_registry_route),
+                ("Waiting for registry route", "Registry route wait", wait_for_registry_route),
+            ],
+            SetupStep::Namespace => vec![("Ensuring namespace and RBAC", "Namespace/RBAC setup", ensure_namespace_rbac)],
+            SetupStep::Operator => vec![
+                ("Ensuring OpenShift Pipelines operator", "Operator install", ensure_operator_installed),
+                ("Waiting for operator ready (up to 5 min)", "Operator ready wait", wait_for_operator_ready),
+            ],
+            SetupStep::Tektonconfig => vec![("Ensuring TektonConfig CR", "TektonConfig setup", ensure_tektonconfig)],
+        }
+    }
+}
+
+type StepFn = fn(&Runtime, &Client) -> anyhow::Result<()>;
+
+/// Steps to run: `only` (all when empty) minus `skip`, in setup order.
+pub fn select_steps(only: &[SetupStep], skip: &[SetupStep]) -> Vec<SetupStep> {
+    SetupStep::ALL
+        .into_iter()
+        .filter(|s| only.is_empty() || only.contains(s))
+        .filter(|s| !skip.contains(s))
+        .collect()
+}
+
+/// Outcome of one setup step; `error` holds the first failure of its actions.
+#[derive(Debug)]
+pub struct StepOutcome {
+    pub step: SetupStep,
+    pub error: Option<String>,
+    pub elapsed: std::time::Duration,
+}
+
+/// Run `steps` in order with partial-failure continuation: a failing action
+/// is warned about and the remaining ones still run.
+fn run_steps(rt: &Runtime, client: &Client, steps: &[SetupStep]) -> Vec<StepOutcome> {
+    steps
+        .iter()
+        .map(|&step| {
+            let start = std::time::Instant::now();
+            let mut error = None;
+            for (label, prefix, action) in step.actions() {
+                let pb = progress::stage_spinner(label);
+                match action(rt, client) {
+                    Ok(()) => progress::finish_spinner(&pb, true),
+                    Err(e) => {
+                        let msg = format!("{prefix}: {e:#}");
+                        warnings::warn(&msg);
+                        error.get_or_insert(msg);
+                        progress::finish_spinner(&pb, false);
+                    }
+                }
+            }
+            StepOutcome { step, error, elapsed: start.elapsed() }
+        })
+        .collect()
+}
+
+/// Run all auto-setup steps with partial-failure continuation.
+/// Each step is attempted independently; failures are warned but do not abort.
+pub fn run_auto_setup() -> anyhow::Result<()> {
+    let (rt, client) = crate::k8s::create_kube_client()?;
+
+    let mut warnings: Vec<String> = run_steps(&rt, &client, &SetupStep::ALL)
+        .into_iter()
+        .filter_map(|o| o.error)
+        .collect();
+
+    if let Some(msg) = registry_storage_warning() {
+        warnings::warn(&msg);
+        warnings.push(msg);
+    }
+
+    if !warnings.is_empty() {
+        eprintln!("\nAuto-setup completed with {} warning(s):", warnings.len());
+        for w in &warnings {
+            eprintln!("  - {w}");
+        }
+    } else {
+        eprintln!("\nAuto-setup completed successfully.");
+    }
+
+    Ok(())
+}
+
+/// Warning for emptyDir registry storage on a node low on disk.
+fn registry_storage_warning() -> Option<String> {
+    let health = registry::storage_health().ok()?;
+    (health.is_empty_dir() && health.low_disk()).then(|| {
+        format!(
+            "Registry uses emptyDir storage and node {} is low on disk ({} free); use --registry-storage pvc",
+            health.node.as_deref().unwrap_or("?"),
+            registry::format_bytes(health.available_bytes.unwrap_or(0))
+        )
+    })
+}
+
+/// `streamstress setup`: run the selected steps and print a summary table.
+/// Returns whether every step succeeded.
+pub fn run_setup(only: &[SetupStep], skip: &[SetupStep]) -> anyhow::Result<bool> {
+    let steps = select_steps(only, skip);
+    if steps.is_empty() {
+        bail!("No setup steps selected");
+    }
+    let (rt, client) = crate::k8s::create_kube_client()?;
+    let outcomes = run_steps(&rt, &client, &steps);
+
+    eprintln!();
+    eprintln!("  {:<14} {:<6} {:>8}  DETAIL", "STEP", "STATUS", "TIME");
+    for step in SetupStep::ALL {
+        match outcomes.iter().find(|o| o.step == step) {
+            Some(o) => eprintln!(
+                "  {:<14} {:<6} {:>7.1}s  {}",
+                step.name(),
+                if o.error.is_some() { "FAIL" } else { "OK" },
+                o.elapsed.as_secs_f64(),
+                o.error.as_deref().unwrap_or("")
+            ),
+            None => eprintln!("  {:<14} {:<6} {:>8}", step.name(), "SKIP", "-"),
+        }
+    }
+    if steps.contains(&SetupStep::Registry) && let Some(msg) = registry_storage_warning() {
+        warnings::warn(&msg);
+    }
+
+    Ok(outcomes.iter().all(|o| o.error.is_none()))
+}
+
+/// Label on the cluster resources setup creates, so `setup --uninstall` only removes its own.
+const CREATED_BY_LABEL: (&str, &str) = ("app", "streamstress");
+
+fn tektonconfig_resource() -> ApiResource {
+    ApiResource {
+        group: "operator.tekton.dev".into(),
+        version: "v1alpha1".into(),
+        api_version: "operator.tekton.dev/v1alpha1".into(),
+        kind: "TektonConfig".into(),
+        plural: "tektonconfigs".into(),
+    }
+}
+
+fn subscription_resource() -> ApiResource {
+    ApiResource {
+        group: "operators.coreos.com".into(),
+        version: "v1alpha1".into(),
+        api_version: "operators.coreos.com/v1alpha1".into(),
+        kind: "Subscription".into(),
+        plural: "subscriptions".into(),
+    }
+}
+
+/// A resource `setup --uninstall` may delete, and what it found on the cluster.
+struct SetupResource {
+    api: Api<DynamicObject>,
+    kind: &'static str,
+    namespace: Option<&'static str>,
+    name: &'static str,
+    /// None when absent; otherwise whether it carries setup's label
+    created_by_setup: Option<bool>,
+}
+
+impl SetupResource {
+    fn display(&self) -> String {
+        match self.namespace {
+            Some(ns) => format!("{} {}/{}", self.kind, ns, self.name),
+            None => format!("{} {}", self.kind, self.name),
+        }
+    }
+}
+
+/// The TektonConfig and operator Subscription, as found on the cluster.
+fn setup_resources(rt: &Runtime, client: &Client) -> anyhow::Result<Vec<SetupResource>> {
+    let targets = [
+        (Api::<DynamicObject>::all_with(client.clone(), &tektonconfig_resource()), "TektonConfig", None, "config"),
+        (
+            Api::namespaced_with(client.clone(), OPERATOR_NAMESPACE, &subscription_resource()),
+            "Subscription",
+            Some(OPERATOR_NAMESPACE),
+            SUBSCRIPTION_NAME,
+        ),
+    ];
+    let mut resources = Vec::new();
+    for (api, kind, namespace, name) in targets {
+        let existing = k8s::block_on_retry(rt, &format!("get {kind}"), || api.get_opt(name))
+            .with_context(|| format!("Failed to get {kind} {name}"))?;
+        let created_by_setup = existing.map(|obj| {
+            obj.metadata
+                .labels
+                .as_ref()
+                .and_then(|l| l.get(CREATED_BY_LABEL.0))
+                .is_some_and(|v| v == CREATED_BY_LABEL.1)
+        });
+        resources.push(SetupResource { api, kind, namespace, name, created_by_setup });
+    }
+    Ok(resources)
+}
+
+/// What `remove_setup_resources` would delete, one line each (for `confirm`).
+pub fn uninstall_changes(rt: &Runtime, client: &Client) -> anyhow::Result<Vec<String>> {
+    Ok(setup_resources(rt, client)?
+        .iter()
+        .filter(|r| r.created_by_setup == Some(true))
+        .map(|r| format!("delete {}", r.display()))
+        .collect())
+}
+
+/// Delete the TektonConfig and operator Subscription setup created, without asking.
+/// Resources without setup's label were not created by it and are left alone.
+pub fn remove_setup_resources(rt: &Runtime, client: &Client) -> anyhow::Result<()> {
+    for SetupResource { api, kind, namespace, name, created_by_setup } in setup_resources(rt, client)? {
+        match created_by_setup {
+            None => eprintln!("  {kind} {name} not found."),
+            Some(false) => eprintln!("  {kind} {name} was not created by setup; leaving it."),
+            Some(true) => {
+                let dp = kube::api::DeleteParams::default();
+                let result = k8s::block_on_retry(rt, &format!("delete {kind}"), || api.delete(name, &dp));
+                audit::record_api("delete", kind, namespace, name, result.is_ok());
+                result.with_context(|| format!("Failed to delete {kind} {name}"))?;
+                eprintln!("  Deleted {kind} {name}.");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `streamstress setup --uninstall`: list the TektonConfig and operator
+/// Subscription setup created, confirm, and delete them. The operator's CSV
+/// stays installed, as OLM does not remove it with the Subscription.
+pub fn uninstall() -> anyhow::Result<()> {
+    let (rt, client) = crate::k8s::create_kube_client()?;
+    if confirm::required() {
+        let changes = uninstall_changes(&rt, &client)?;
+        if !changes.is_empty() {
+            confirm::confirm("uninstall the operator", &changes)?;
+        }
+    }
+    remove_setup_resources(&rt, &client)
+}
+
+/// Ensure the internal image registry is configured with a default route.
+/// Patches the image-registry config to Managed state, enables defaultRoute,
+/// and configures storage: emptyDir if none is set, or with `--registry-storage pvc`
+/// a PVC from the default StorageClass (replacing emptyDir).
+pub fn ensure_registry_route(rt: &Runtime, client: &Client) -> anyhow::Result<()> {
+    let ar = ApiResource {
+        group: "imageregistry.operator.openshift.io".into(),
+        version: "v1".into(),
+        api_version: "imageregistry.operator.openshift.io/v1".into(),
+        kind: "Config".into(),
+        plural: "configs".into(),
+    };
+
+    let api: Api<DynamicObject> = Api::all_with(client.clone(), &ar);
+    let config = k8s::block_on_retry(rt, "get image registry config", || api.get("cluster"))
+        .context("Failed to get image registry config")?;
+
+    let spec = config.data.get("spec");
+
+    let mgmt_state = spec
+        .and_then(|s| s.get("managementState"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+
+    let default_route = spec
+        .and_then(|s| s.get("defaultRoute"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let storage_empty = spec
+        .and_then(|s| s.get("storage"))
+        .map(|v| v.is_null() || (v.is_object() && v.as_object().unwrap().is_empty()))
+        .unwrap_or(true);
+
+    let storage_empty_dir = spec
+        .and_then(|s| s.get("storage"))
+        .and_then(|s| s.get("emptyDir"))
+        .is_some_and(|v| !v.is_null());
+
+    let mut patch = serde_json::Map::new();
+    let mut spec_patch = serde_json::Map::new();
+
+    if mgmt_state == "Removed" {
+        spec_patch.insert("managementState".into(), json!("Managed"));
+    }
+    if !default_route {
+        spec_patch.insert("defaultRoute".into(), json!(true));
+    }
+    let want_pvc = registry_storage() == RegistryStorage::Pvc && (storage_empty || storage_empty_dir);
+    let storage_class = if want_pvc { default_storage_class(rt, client)? } else { None };
+    match storage_class {
+        Some(sc) => {
+            // An empty claim makes the registry operator create image-registry-storage.
+            // RWO volumes cannot be shared between rolling replicas.
+            eprintln!("  Using PVC storage (StorageClass {sc}) for the image registry.");
+            spec_patch.insert("storage".into(), json!({"emptyDir": null, "pvc": {"claim": ""}}));
+            spec_patch.insert("rolloutStrategy".into(), json!("Recreate"));
+            spec_patch.insert("replicas".into(), json!(1));
+        }
+        None => {
+            if want_pvc {
+                warnings::warn("No default StorageClass found; image registry stays on emptyDir storage.");
+            }
+            if storage_empty {
+                spec_patch.insert("storage".into(), json!({"emptyDir": {}}));
+            }
+        }
+    }
+
+    if spec_patch.is_empty() {
+        eprintln!("  Image registry already configured.");
+        return Ok(());
+    }
+
+    patch.insert("spec".into(), json!(spec_patch));
+
+    let (pp, patch) = (PatchParams::default(), Patch::Merge(json!(patch)));
+    let result = k8s::block_on_retry(rt, "patch image registry config", || api.patch("cluster", &pp, &patch));
+    audit::record_api("patch", "configs.imageregistry.operator.openshift.io", None, "cluster", result.is_ok());
+    result.context("Failed to patch image registry config")?;
+
+    eprintln!("  Patched image registry config.");
+    Ok(())
+}
+
+/// Name of the cluster's default StorageClass, if one is annotated as default.
+fn default_storage_class(rt: &Runtime, client: &Client) -> anyhow::Result<Option<String>> {
+    let api: Api<StorageClass> = Api::all(client.clone());
+    let lp = ListParams::default();
+    let classes = k8s::block_on_retry(rt, "list StorageClasses", || api.list(&lp))
+        .context("Failed to list StorageClasses")?;
+    Ok(classes.items.into_iter().find_map(|sc| {
+        let is_default = sc
+            .metadata
+            .annotations
+            .as_ref()
+            .and_then(|a| a.get("storageclass.kubernetes.io/is-default-class"))
+            .is_some_and(|v| v == "true");
+        if is_default { sc.metadata.name } else { None }
+    }))
+}
+
+/// Wait for the default-route Route to appear in openshift-image-registry.
+/// Polls for up to 30 seconds.
+pub fn wait_for_registry_route(_rt: &Runtime, _client: &Client) -> anyhow::Result<()> {
+    let timeout = std::time::Duration::from_secs(30);
+    let interval = std::time::Duration::from_secs(2);
+    let start = std::time::Instant::now();
+
+    loop {
+        let result = exec::run_cmd_unchecked(
+            "oc",
+            &[
+                "get",
+                "route",
+                "default-route",
+                "-n",
+                "openshift-image-registry",
+                "-o",
+                "name",
+            ],
+        )?;
+
+        if result.exit_code == 0 && result.stdout.contains("route") {
+            eprintln!("  Registry route is available.");
+            return Ok(());
+        }
+
+        if start.elapsed() > timeout {
+            bail!("Timed out waiting for registry route (30s)");
+        }
+
+        std::thread::sleep(interval);
+    }
+}
+
+/// Ensure the image namespace exists and has image-puller RBAC for all authenticated users.
+pub fn ensure_namespace_rbac(rt: &Runtime, client: &Client) -> anyhow::Result<()> {
+    let ns_name = registry::DEFAULT_NAMESPACE;
+
+    // Create namespace if it doesn't exist
+    let ns_api: Api<Namespace> = Api::all(client.clone());
+    match k8s::block_on_retry(rt, "get Namespace", || ns_api.get(ns_name)) {
+        Ok(_) => {
+            eprintln!("  Namespace {ns_name} already exists.");
+        }
+        Err(kube::Error::Api(resp)) if resp.code == 404 => {
+            let ns: Namespace = serde_json::from_value(json!({
+                "apiVersion": "v1",
+                "kind": "Namespace",
+                "metadata": {
+                    "name": ns_name
+                }
+            }))?;
+            let pp = PostParams::default();
+            let result = k8s::block_on_retry(rt, "create Namespace", || ns_api.create(&pp, &ns));
+            audit::record_api("create", "Namespace", None, ns_name, result.is_ok());
+            result.with_context(|| format!("Failed to create namespace {ns_name}"))?;
+            eprintln!("  Created namespace {ns_name}.");
+        }
+        Err(e) => return Err(e).context(format!("Failed to check namespace {ns_name}")),
+    }
+
+    // Ensure image-puller RBAC (reuses pattern from deploy/operator.rs)
+    crate::deploy::operator::ensure_image_pull_rbac(rt, client, ns_name)?;
+
+    Ok(())
+}
+
+/// Ensure the OpenShift Pipelines operator is installed via OLM Subscription.
+/// If TektonConfig already exists, the operator is already installed — skip.
+pub fn ensure_operator_installed(rt: &Runtime, client: &Client) -> anyhow::Result<()> {
+    // Check if TektonConfig already exists (operator fully installed)
+    let tc_api: Api<DynamicObject> = Api::all_with(client.clone(), &tektonconfig_resource());
+    if k8s::block_on_retry(rt, "get TektonConfig", || tc_api.get("config")).is_ok() {
+        eprintln!("  TektonConfig already exists — operator is installed.");
+        return Ok(());
+    }
+
+    // Check if Subscription already exists
+    let sub_api: Api<DynamicObject> =
+        Api::namespaced_with(client.clone(), OPERATOR_NAMESPACE, &subscription_resource());
+
+    let cfg = operator_config();
+    if let Ok(existing) = k8s::block_on_retry(rt, "get operator Subscription", || sub_api.get(SUBSCRIPTION_NAME)) {
+        let channel = existing.data["spec"]["channel"].as_str().unwrap_or("");
+        if channel != cfg.channel {
+            warnings::warn(format!(
+                "Operator Subscription is on channel '{channel}', not '{}'; it is left as is (`setup --uninstall` removes one setup created)",
+                cfg.channel
+            ));
+        }
+        eprintln!("  Subscription already exists — waiting for operator.");
+        return Ok(());
+    }
+
+    // Create Subscription
+    let sub: DynamicObject = serde_json::from_value(subscription_manifest(&cfg))?;
+
+    let pp = PostParams::default();
+    let result = k8s::block_on_retry(rt, "create operator Subscription", || sub_api.create(&pp, &sub));
+    audit::record_api("create", "Subscription", Some(OPERATOR_NAMESPACE), SUBSCRIPTION_NAME, result.is_ok());
+    result.context("Failed to create OpenShift Pipelines operator Subscription")?;
+
+    eprintln!("  Created operator Subscription (channel {}, source {}).", cfg.channel, cfg.source);
+    Ok(())
+}
+
+/// The operator Subscription for `cfg`.
+fn subscription_manifest(cfg: &OperatorConfig) -> serde_json::Value {
+    let mut sub = json!({
+        "apiVersion": "operators.coreos.com/v1alpha1",
+        "kind": "Subscription",
+        "metadata": {
+            "name": SUBSCRIPTION_NAME,
+            "namespace": OPERATOR_NAMESPACE,
+            "labels": { CREATED_BY_LABEL.0: CREATED_BY_LABEL.1 }
+        },
+        "spec": {
+            "channel": cfg.channel,
+            "name": "openshift-pipelines-operator-rh",
+            "source": cfg.source,
+            "sourceNamespace": cfg.source_namespace,
+            "installPlanApproval": "Automatic"
+        }
+    });
+    if let Some(csv) = &cfg.starting_csv {
+        sub["spec"]["startingCSV"] = json!(csv);
+    }
+    sub
+}
+
+/// Wait for the operator deployment to become Available.
+/// Checks known namespaces and deployment names for up to 5 minutes.
+pub fn wait_for_operator_ready(rt: &Runtime, client: &Client) -> anyhow::Result<()> {
+    use k8s_openapi::api::apps::v1::Deployment;
+
+    let timeout = std::time::Duration::from_secs(300);
+    let interval = std::time::Duration::from_secs(5);
+    let start = std::time::Instant::now();
+
+    let namespaces = &[
+        "openshift-pipelines",
+        "openshift-operators",
+        "tekton-pipelines",
+    ];
+    let deployment_names = &["openshift-pipelines-operator", "tekton-operator"];
+
+    let approve = operator_config().approve_install_plans;
+    loop {
+        // Under installPlanApproval: Manual the deployment never appears until a plan is approved
+        // (best effort: without access to InstallPlans, wait as before)
+        for plan in pending_install_plans(rt, client).unwrap_or_default() {
+            if !approve {
+                bail!(
+                    "InstallPlan {plan} in {OPERATOR_NAMESPACE} needs manual approval. Approve it with\n  \
+                     oc patch installplan {plan} -n {OPERATOR_NAMESPACE} --type merge -p '{{\"spec\":{{\"approved\":true}}}}'\n\
+                     or rerun with --approve-install-plans"
+                );
+            }
+            approve_install_plan(rt, client, &plan)?;
+        }
+
+        for ns in namespaces {
+            let api: Api<Deployment> = Api::namespaced(client.clone(), ns);
+            for name in deployment_names {
+                if let Ok(dep) = k8s::block_on_retry(rt, "get operator Deployment", || api.get(name))
+                    && let Some(status) = &dep.status
+                    && let Some(conditions) = &status.conditions
+                {
+                    for cond in conditions {
+                        if cond.type_ == "Available" && cond.status == "True" {
+                            eprintln!("  Operator deployment {name} is Available in {ns}.");
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+        }
+
+        if start.elapsed() > timeout {
+            bail!("Timed out waiting for operator deployment to become Available (5 min)");
+        }
+
+        std::thread::sleep(interval);
+    }
+}
+
+fn installplan_resource() -> ApiResource {
+    ApiResource {
+        group: "operators.coreos.com".into(),
+        version: "v1alpha1".into(),
+        api_version: "operators.coreos.com/v1alpha1".into(),
+        kind: "InstallPlan".into(),
+        plural: "installplans".into(),
+    }
+}
+
+/// Names of the operator's InstallPlans in the operator namespace waiting for approval.
+fn pending_install_plans(rt: &Runtime, client: &Client) -> anyhow::Result<Vec<String>> {
+    let api: Api<DynamicObject> = Api::namespaced_with(client.clone(), OPERATOR_NAMESPACE, &installplan_resource());
+    let lp = ListParams::default();
+    let plans = k8s::block_on_retry(rt, "list InstallPlans", || api.list(&lp)).context("Failed to list InstallPlans")?;
+    Ok(plans.items.iter().filter(|p| awaits_approval(p)).filter_map(|p| p.metadata.name.clone()).collect())
+}
+
+/// Whether `plan` is an unapproved InstallPlan for the pipelines operator.
+fn awaits_approval(plan: &DynamicObject) -> bool {
+    let spec = &plan.data["spec"];
+    let for_operator = spec["clusterServiceVersionNames"]
+        .as_array()
+        .is_some_and(|names| names.iter().filter_map(|n| n.as_str()).any(|n| n.starts_with(SUBSCRIPTION_NAME)));
+    let pending = spec["approved"].as_bool() == Some(false) || plan.data["status"]["phase"] == "RequiresApproval";
+    for_operator && pending
+}
+
+fn approve_install_plan(rt: &Runtime, client: &Client, name: &str) -> anyhow::Result<()> {
+    let api: Api<DynamicObject> = Api::namespaced_with(client.clone(), OPERATOR_NAMESPACE, &installplan_resource());
+    let (pp, patch) = (PatchParams::default(), Patch::Merge(json!({"spec": {"approved": true}})));
+    let result = k8s::block_on_retry(rt, "approve InstallPlan", || api.patch(name, &pp, &patch));
+    audit::record_api("patch", "InstallPlan", Some(OPERATOR_NAMESPACE), name, result.is_ok());
+    result.with_context(|| format!("Failed to approve InstallPlan {name}"))?;
+    eprintln!("  Approved InstallPlan {name}.");
+    Ok(())
+}
+
+/// Ensure the TektonConfig CR exists. If the operator was just installed,
+/// the CRD may not be registered yet — retries with backoff.
+pub fn ensure_tektonconfig(rt: &Runtime, client: &Client) -> anyhow::Result<()> {
+    let api: Api<DynamicObject> = Api::all_with(client.clone(), &tektonconfig_resource());
+
+    // Check if already exists
+    if k8s::block_on_retry(rt, "get TektonConfig", || api.get("config")).is_ok() {
+        eprintln!("  TektonConfig 'config' already exists.");
+        return Ok(());
+    }
+
+    let tc: DynamicObject = serde_json::from_value(json!({
+        "apiVersion": "operator.tekton.dev/v1alpha1",
+        "kind": "TektonConfig",
+        "metadata": {
+            "name": "config",
+            "labels": { CREATED_BY_LABEL.0: CREATED_BY_LABEL.1 }
+        },
+        "spec": {
+            "targetNamespace": "openshift-pipelines",
+            "profile": "all"
+        }
+    }))?;
+
+    // Retry with backoff in case CRD isn't registered yet
+    let max_retries = 6;
+    let mut backoff = std::time::Duration::from_secs(5);
+
+    for attempt in 1..=max_retries {
+        let pp = PostParams::default();
+        let result = k8s::block_on_retry(rt, "create TektonConfig", || api.create(&pp, &tc));
+        audit::record_api("create", "TektonConfig", None, "config", result.is_ok());
+        match result {
+            Ok(_) => {
+                eprintln!("  Created TektonConfig 'config'.");
+                return Ok(());
+            }
+            Err(e) if attempt < max_retries => {
+                eprintln!(
+                    "  Attempt {attempt}/{max_retries} to create TektonConfig failed: {e}. Retrying in {}s...",
+                    backoff.as_secs()
+                );
+                std::thread::sleep(backoff);
+                backoff *= 2;
+            }
+            Err(e) => {
+                return Err(e).context("Failed to create TektonConfig after retries");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_steps() {
+        assert_eq!(select_steps(&[], &[]), SetupStep::ALL.to_vec());
+        // Setup order regardless of the order given
+        assert_eq!(
+            select_steps(&[SetupStep::Tektonconfig, SetupStep::Registry], &[]),
+            vec![SetupStep::Registry, SetupStep::Tektonconfig]
+        );
+        assert_eq!(
+            select_steps(&[], &[SetupStep::Operator, SetupStep::Namespace]),
+            vec![SetupStep::Registry, SetupStep::Tektonconfig]
+        );
+        assert!(select_steps(&[SetupStep::Operator], &[SetupStep::Operator]).is_empty());
+    }
+
+    #[test]
+    fn test_awaits_approval() {
+        let plan = |v: serde_json::Value| -> DynamicObject { serde_json::from_value(v).unwrap() };
+        let manual = plan(json!({
+            "apiVersion": "operators.coreos.com/v1alpha1", "kind": "InstallPlan",
+            "metadata": {"name": "install-abc12", "namespace": "openshift-operators"},
+            "spec": {"approval": "Manual", "approved": false,
+                     "clusterServiceVersionNames": ["openshift-pipelines-operator-rh.v1.15.2"]},
+            "status": {"phase": "RequiresApproval"}
+        }));
+        assert!(awaits_approval(&manual));
+
+        let mut approved = manual.clone();
+        approved.data["spec"]["approved"] = json!(true);
+        approved.data["status"]["phase"] = json!("Complete");
+        assert!(!awaits_approval(&approved));
+
+        let mut other = manual.clone();
+        other.data["spec"]["clusterServiceVersionNames"] = json!(["serverless-operator.v1.33.0"]);
+        assert!(!awaits_approval(&other));
+    }
+
+    #[test]
+    fn test_subscription_manifest() {
+        let sub = subscription_manifest(&OperatorConfig::default());
+        assert_eq!(sub["spec"]["channel"], "latest");
+        assert_eq!(sub["spec"]["source"], "redhat-operators");
+        assert!(sub["spec"].get("startingCSV").is_none());
+
+        // Unset keys keep their defaults
+        let cfg: OperatorConfig = toml::from_str(
+            "channel = \"pipelines-1.15\"\nstarting_csv = \"openshift-pipelines-operator-rh.v1.15.2\"",
+        )
+        .unwrap();
+        let sub = subscription_manifest(&cfg);
+        assert_eq!(sub["spec"]["channel"], "pipelines-1.15");
+        assert_eq!(sub["spec"]["sourceNamespace"], "openshift-marketplace");
+        assert_eq!(sub["spec"]["startingCSV"], "openshift-pipelines-operator-rh.v1.15.2");
+    }
+}
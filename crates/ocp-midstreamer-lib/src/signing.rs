@@ -0,0 +1,148 @@
+//! Detached sigstore signatures for published run files and Konflux SNAPSHOTs.
+//!
+//! cosign signs the exact bytes of a file into a bundle (signature, certificate
+//! or key reference, and transparency log entry) kept next to it as
+//! `<file>.sigstore.json`, so dashboard consumers can check a run with
+//! `streamstress verify-run` (or `cosign verify-blob`) before relying on it.
+
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::exec;
+use crate::workspace;
+
+/// Suffix of the bundle file signing `<file>`.
+pub const BUNDLE_SUFFIX: &str = ".sigstore.json";
+
+/// Environment variable holding the cosign key reference in-cluster Jobs sign
+/// their published runs with (e.g. `k8s://openshift-pipelines/streamstress-cosign`).
+pub const SIGNING_KEY_ENV: &str = "STREAMSTRESS_SIGNING_KEY";
+
+/// How to sign: with a cosign key reference (file, KMS URI or `k8s://ns/secret`),
+/// or keyless through the OIDC flow cosign starts (ambient credentials in CI).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Signer {
+    pub key: Option<String>,
+}
+
+impl Signer {
+    /// Signer for `--sign`/`--sign-key` flags; None without `--sign`.
+    pub fn from_flags(sign: bool, key: Option<String>) -> Option<Self> {
+        sign.then_some(Signer { key })
+    }
+
+    /// Key-based signer from `SIGNING_KEY_ENV`, for unattended publishes.
+    pub fn from_env() -> Option<Self> {
+        std::env::var(SIGNING_KEY_ENV)
+            .ok()
+            .filter(|k| !k.is_empty())
+            .map(|key| Signer { key: Some(key) })
+    }
+
+    /// Sign `content` and return the bundle JSON.
+    pub fn sign_blob(&self, content: &[u8]) -> Result<String> {
+        let dir = workspace::scoped("signing").context("Failed to create signing directory")?;
+        let blob = dir.path().join("blob");
+        let bundle = dir.path().join("bundle.json");
+        fs::write(&blob, content).context("Failed to write blob to sign")?;
+
+        let bundle_arg = bundle.to_string_lossy().into_owned();
+        let blob_arg = blob.to_string_lossy().into_owned();
+        let mut args = vec!["sign-blob", "--yes", "--bundle", &bundle_arg];
+        if let Some(ref key) = self.key {
+            args.extend(["--key", key.as_str()]);
+        }
+        args.push(&blob_arg);
+        // Keyless signing may wait on a browser login
+        exec::run_cmd_timeout("cosign", &args, exec::DEFAULT_TIMEOUT).context("cosign sign-blob failed")?;
+
+        fs::read_to_string(&bundle).context("cosign did not write a signature bundle")
+    }
+
+    /// Sign the file at `path`, writing the bundle next to it. Returns the bundle path.
+    pub fn sign_file(&self, path: &Path) -> Result<PathBuf> {
+        let content = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let bundle = self.sign_blob(&content)?;
+        let bundle_path = bundle_path(path);
+        fs::write(&bundle_path, bundle).with_context(|| format!("Failed to write {}", bundle_path.display()))?;
+        Ok(bundle_path)
+    }
+}
+
+/// Bundle file that signs `path`.
+pub fn bundle_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(BUNDLE_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Who a signature must come from: a public key, or for keyless signatures the
+/// certificate identity (e.g. a workflow URL) and its OIDC issuer.
+#[derive(Debug, Clone, Default)]
+pub struct Identity {
+    pub key: Option<String>,
+    pub certificate_identity: Option<String>,
+    pub certificate_oidc_issuer: Option<String>,
+}
+
+impl Identity {
+    /// cosign verify-blob arguments selecting this identity.
+    fn args(&self) -> Result<Vec<String>> {
+        match self {
+            Identity { key: Some(key), .. } => Ok(vec!["--key".to_string(), key.clone()]),
+            Identity { certificate_identity: Some(id), certificate_oidc_issuer: Some(issuer), .. } => Ok(vec![
+                "--certificate-identity".to_string(),
+                id.clone(),
+                "--certificate-oidc-issuer".to_string(),
+                issuer.clone(),
+            ]),
+            _ => bail!("Give --key, or --certificate-identity and --certificate-oidc-issuer for keyless signatures"),
+        }
+    }
+}
+
+/// Verify `path` against its bundle (default: `<path>.sigstore.json`).
+pub fn verify_file(path: &Path, bundle: Option<&Path>, identity: &Identity) -> Result<()> {
+    let bundle = bundle.map(Path::to_path_buf).unwrap_or_else(|| bundle_path(path));
+    if !bundle.exists() {
+        bail!("No signature bundle at {} ({} is unsigned?)", bundle.display(), path.display());
+    }
+    let bundle_arg = bundle.to_string_lossy().into_owned();
+    let path_arg = path.to_string_lossy().into_owned();
+    let identity_args = identity.args()?;
+
+    let mut args = vec!["verify-blob", "--bundle", &bundle_arg];
+    args.extend(identity_args.iter().map(String::as_str));
+    args.push(&path_arg);
+    let result = exec::run_cmd_unchecked_timeout("cosign", &args, exec::API_TIMEOUT)?;
+    if result.exit_code != 0 {
+        bail!("Signature of {} does not verify: {}", path.display(), result.stderr.trim());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_path() {
+        assert_eq!(bundle_path(Path::new("runs/2026-01-01T00-00-00.json")), Path::new("runs/2026-01-01T00-00-00.json.sigstore.json"));
+        assert_eq!(bundle_path(Path::new("snapshot.json")), Path::new("snapshot.json.sigstore.json"));
+    }
+
+    #[test]
+    fn test_identity_args() {
+        let key = Identity { key: Some("cosign.pub".into()), ..Default::default() };
+        assert_eq!(key.args().unwrap(), ["--key", "cosign.pub"]);
+        let keyless = Identity {
+            key: None,
+            certificate_identity: Some("https://github.com/o/r/.github/workflows/nightly.yml@refs/heads/main".into()),
+            certificate_oidc_issuer: Some("https://token.actions.githubusercontent.com".into()),
+        };
+        assert_eq!(keyless.args().unwrap()[0], "--certificate-identity");
+        assert!(Identity { certificate_identity: Some("x".into()), ..Default::default() }.args().is_err());
+        assert_eq!(Signer::from_flags(false, Some("k".into())), None);
+    }
+}
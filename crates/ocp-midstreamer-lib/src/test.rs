@@ -0,0 +1,1047 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::{BufRead, BufReader, Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::thread;
+
+use crate::audit;
+use crate::cassette;
+use crate::config;
+use crate::events;
+use crate::exec;
+use crate::gaugeevents;
+use crate::gitops;
+use crate::logscan;
+use crate::profile;
+use crate::profileexport;
+use crate::progress;
+use crate::results;
+use crate::scopeduser;
+use crate::testref;
+use crate::warnings;
+use crate::workspace;
+
+/// Plugins release-tests cannot run without, pinned or not.
+const REQUIRED_GAUGE_PLUGINS: &[&str] = &["go", "xml-report"];
+
+/// Installed gauge core version and plugin versions (a plugin may have several).
+#[derive(Debug, Default, PartialEq)]
+struct GaugeVersions {
+    gauge: String,
+    plugins: Vec<(String, String)>,
+}
+
+impl GaugeVersions {
+    fn plugin_versions(&self, name: &str) -> Vec<&str> {
+        self.plugins.iter().filter(|(n, _)| n == name).map(|(_, v)| v.as_str()).collect()
+    }
+}
+
+/// Parse `gauge version --machine-readable`, falling back to the plain output
+/// ("Gauge version: 1.6.3" and one "name (version)" line per plugin).
+fn parse_gauge_versions(output: &str) -> GaugeVersions {
+    if let Ok(json) = serde_json::from_str::<serde_json::Value>(output) {
+        let str_of = |v: &serde_json::Value, key: &str| v.get(key).and_then(|s| s.as_str()).unwrap_or("").to_string();
+        return GaugeVersions {
+            gauge: str_of(&json, "version"),
+            plugins: json
+                .get("plugins")
+                .and_then(|p| p.as_array())
+                .into_iter()
+                .flatten()
+                .map(|p| (str_of(p, "name"), str_of(p, "version")))
+                .collect(),
+        };
+    }
+    let mut versions = GaugeVersions::default();
+    for line in output.lines().map(str::trim) {
+        if let Some(v) = line.strip_prefix("Gauge version:") {
+            versions.gauge = v.trim().to_string();
+        } else if let Some((name, rest)) = line.split_once(" (")
+            && let Some(version) = rest.strip_suffix(')')
+        {
+            versions.plugins.push((name.trim().to_string(), version.trim().to_string()));
+        }
+    }
+    versions
+}
+
+/// What to do about one plugin to match its pin.
+#[derive(Debug, PartialEq)]
+enum PluginAction {
+    /// Install this version (missing, or pinned version absent).
+    Install(String),
+    /// Install the latest version (missing, no pin).
+    InstallLatest,
+    /// Remove other versions so gauge picks the pinned one (it uses the newest).
+    Uninstall(Vec<String>),
+}
+
+/// Actions bringing `plugin` in line with `pin` given the installed versions.
+fn plugin_actions(installed: &[&str], pin: Option<&str>) -> Vec<PluginAction> {
+    let mut actions = Vec::new();
+    match pin {
+        None if installed.is_empty() => actions.push(PluginAction::InstallLatest),
+        None => {}
+        Some(pin) => {
+            if !installed.contains(&pin) {
+                actions.push(PluginAction::Install(pin.to_string()));
+            }
+            let others: Vec<String> = installed.iter().filter(|v| **v != pin).map(|v| v.to_string()).collect();
+            if !others.is_empty() {
+                actions.push(PluginAction::Uninstall(others));
+            }
+        }
+    }
+    actions
+}
+
+/// Verify the gauge binary exists and its plugins match the versions pinned in
+/// `config/gauge.toml`, installing or switching plugins that differ. Plugin
+/// skew shows up as obscure runner failures, so it is fixed before every run.
+fn preflight_check() -> Result<()> {
+    which::which("gauge").context(
+        "gauge binary not found. Install from https://docs.gauge.org/getting_started/installing-gauge",
+    )?;
+
+    let pins = config::load_gauge_config(&config::default_gauge_config_path())?;
+    let version_result = exec::run_cmd("gauge", &["version", "--machine-readable"])?;
+    let installed = parse_gauge_versions(&version_result.stdout);
+
+    if let Some(ref pinned) = pins.gauge
+        && installed.gauge.trim_start_matches('v') != pinned.trim_start_matches('v')
+    {
+        warnings::warn(format!(
+            "gauge {} is installed but release-tests is pinned to {} (see {})",
+            installed.gauge,
+            pinned,
+            config::default_gauge_config_path().display()
+        ));
+    }
+
+    let mut plugins: Vec<&str> = REQUIRED_GAUGE_PLUGINS.to_vec();
+    plugins.extend(pins.plugins.keys().map(String::as_str).filter(|p| !REQUIRED_GAUGE_PLUGINS.contains(p)));
+    for plugin in plugins {
+        let pin = pins.plugins.get(plugin).map(String::as_str);
+        for action in plugin_actions(&installed.plugin_versions(plugin), pin) {
+            match action {
+                PluginAction::InstallLatest => {
+                    eprintln!("  Installing gauge plugin {}", plugin);
+                    exec::run_cmd_timeout("gauge", &["install", plugin], GAUGE_INSTALL_TIMEOUT)
+                        .with_context(|| format!("Failed to install gauge plugin {plugin}"))?;
+                }
+                PluginAction::Install(version) => {
+                    eprintln!("  Installing gauge plugin {} {}", plugin, version);
+                    exec::run_cmd_timeout("gauge", &["install", plugin, "--version", &version], GAUGE_INSTALL_TIMEOUT)
+                        .with_context(|| format!("Failed to install gauge plugin {plugin} {version}"))?;
+                }
+                PluginAction::Uninstall(versions) => {
+                    for version in versions {
+                        eprintln!("  Removing gauge plugin {} {} (pinned: {})", plugin, version, pin.unwrap_or(""));
+                        exec::run_cmd_timeout("gauge", &["uninstall", plugin, "--version", &version], GAUGE_INSTALL_TIMEOUT)
+                            .with_context(|| format!("Failed to uninstall gauge plugin {plugin} {version}"))?;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Gauge plugin installs download from GitHub releases.
+const GAUGE_INSTALL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Default release-tests repository; see `testref::repo` for the one in use.
+pub const RELEASE_TESTS_REPO: &str = "https://github.com/openshift-pipelines/release-tests.git";
+
+/// Clone the release-tests repository into work_dir/release-tests at `git_ref`
+/// (branch, tag or commit SHA), showing retries on `stage`.
+fn clone_release_tests(work_dir: &Path, git_ref: &str, stage: &progress::Stage) -> Result<PathBuf> {
+    let dest = work_dir.join("release-tests");
+    let repo_url = testref::repo();
+    gitops::clone(repo_url, &dest, Some(git_ref), Some(stage))
+        .with_context(|| format!("Failed to clone release-tests repository {repo_url} at '{git_ref}'"))?;
+    Ok(dest)
+}
+
+/// Arguments for `gauge run`, selecting specs by `tags` and/or scenario names.
+pub fn gauge_run_args(tags: Option<&str>, scenarios: &[String]) -> Vec<String> {
+    let mut args: Vec<String> = vec!["run".into(), "--log-level=debug".into(), "--verbose".into()];
+    if let Some(tags) = tags {
+        args.push("--tags".into());
+        args.push(tags.to_string());
+    }
+    for scenario in scenarios {
+        args.push("--scenario".into());
+        args.push(scenario.clone());
+    }
+    args.push("specs/".into());
+    args
+}
+
+/// Run gauge tests with piped output, teeing to both terminal and log files.
+/// Gauge reports machine-readable events, which are rendered as console text
+/// and give the spec boundaries for the profiler and event collector and the
+/// scenario spans written to `logs/scenario-timeline.json`.
+/// Returns exit code.
+fn run_gauge_tests(test_dir: &Path, args: &[String], env: &[(String, String)], output_dir: &Path, profiler: Option<Arc<profile::MetricsCollector>>, events: Option<Arc<events::EventCollector>>) -> Result<i32> {
+    let logs_dir = output_dir.join("logs");
+    fs::create_dir_all(&logs_dir).context("Failed to create logs directory")?;
+
+    tracing::info!("Running {} in {}", results::gauge_command_line(args), test_dir.display());
+    let start = std::time::Instant::now();
+    let mut cmd = Command::new("gauge");
+    cmd.args(args)
+    .arg("--machine-readable")
+    .envs(env.iter().map(|(k, v)| (k, v)))
+    .current_dir(test_dir)
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped());
+
+    // Replaying a cassette: gauge's recorded output goes through the same path
+    let replayed = if cassette::replaying() { Some(cassette::replay_process(&cmd)?) } else { None };
+    let (mut child, child_stdout, child_stderr): (Option<Child>, Box<dyn Read + Send>, Box<dyn Read + Send>) = match &replayed {
+        Some(recorded) => (
+            None,
+            Box::new(Cursor::new(recorded.stdout.clone().into_bytes())),
+            Box::new(Cursor::new(recorded.stderr.clone().into_bytes())),
+        ),
+        None => {
+            let mut child = cmd.spawn().context("Failed to execute gauge")?;
+            let stdout = child.stdout.take().expect("stdout was piped");
+            let stderr = child.stderr.take().expect("stderr was piped");
+            (Some(child), Box::new(stdout), Box::new(stderr))
+        }
+    };
+
+    // Runtime handle for the profiler's async notify_spec_event, taken here
+    // since the stdout thread below runs outside the runtime
+    let rt = profiler.as_ref().and_then(|_| tokio::runtime::Handle::try_current().ok());
+
+    // Tee stdout: render gauge's events, print and collect the text, and pass spec boundaries on
+    let profiler_clone = profiler.clone();
+    let stdout_handle = thread::spawn(move || {
+        let reader = BufReader::new(child_stdout);
+        let mut collected = String::new();
+        let mut raw_events = String::new();
+        let mut tracker = gaugeevents::Tracker::default();
+        for line in reader.lines() {
+            match line {
+                Ok(l) => {
+                    let handled = tracker.handle(&l);
+                    if let Some(event) = handled.boundary {
+                        if let Some(ev) = &events {
+                            ev.notify_spec_event(&event);
+                        }
+                        if let (Some(p), Some(handle)) = (&profiler_clone, &rt) {
+                            let p = p.clone();
+                            handle.block_on(p.notify_spec_event(event));
+                        }
+                    }
+                    for text in &handled.text {
+                        println!("{}", text);
+                        collected.push_str(text);
+                        collected.push('\n');
+                    }
+                    raw_events.push_str(&l);
+                    raw_events.push('\n');
+                }
+                Err(e) => {
+                    eprintln!("Error reading stdout: {e}");
+                    break;
+                }
+            }
+        }
+        (collected, raw_events, tracker.spans)
+    });
+
+    // Tee stderr: print to terminal and collect
+    let stderr_handle = thread::spawn(move || {
+        let reader = BufReader::new(child_stderr);
+        let mut collected = String::new();
+        for line in reader.lines() {
+            match line {
+                Ok(l) => {
+                    eprintln!("{}", l);
+                    collected.push_str(&l);
+                    collected.push('\n');
+                }
+                Err(e) => {
+                    eprintln!("Error reading stderr: {e}");
+                    break;
+                }
+            }
+        }
+        collected
+    });
+
+    let code = match child.as_mut() {
+        Some(child) => {
+            let code = child.wait().context("Failed to wait for gauge process")?.code();
+            audit::record_process(&cmd, code, start.elapsed());
+            code
+        }
+        None => replayed.map(|r| r.exit_code),
+    };
+
+    let (stdout_content, raw_events, timeline) = stdout_handle.join().unwrap_or_default();
+    let stderr_content = stderr_handle.join().unwrap_or_default();
+    if child.is_some() {
+        cassette::record_process(&cmd, code, raw_events.as_bytes(), stderr_content.as_bytes());
+    }
+
+    fs::write(logs_dir.join("test-stdout.log"), &stdout_content)
+        .context("Failed to write test-stdout.log")?;
+    fs::write(logs_dir.join("test-stderr.log"), &stderr_content)
+        .context("Failed to write test-stderr.log")?;
+    fs::write(logs_dir.join("gauge-events.jsonl"), &raw_events)
+        .context("Failed to write gauge-events.jsonl")?;
+    fs::write(gaugeevents::timeline_path(output_dir), serde_json::to_string_pretty(&timeline)?)
+        .context("Failed to write scenario-timeline.json")?;
+
+    Ok(code.unwrap_or(-1))
+}
+
+/// Print the tail of gauge's internal and Go runner logs after a failed run.
+fn dump_gauge_logs(test_dir: &Path) {
+    let gauge_log = test_dir.join("logs").join("gauge.log");
+    if gauge_log.exists() && let Ok(content) = fs::read_to_string(&gauge_log) {
+        eprintln!("\n=== Gauge internal log ({}) ===", gauge_log.display());
+        // Print last 80 lines to avoid flooding
+        let lines: Vec<&str> = content.lines().collect();
+        let start = lines.len().saturating_sub(80);
+        for line in &lines[start..] {
+            eprintln!("{}", line);
+        }
+        eprintln!("=== End gauge log ===\n");
+    }
+    // Also check for gauge's Go runner log
+    let go_runner_log = test_dir.join("logs").join("gauge-go.log");
+    if go_runner_log.exists() && let Ok(content) = fs::read_to_string(&go_runner_log) {
+        eprintln!("\n=== Gauge Go runner log ===");
+        let lines: Vec<&str> = content.lines().collect();
+        let start = lines.len().saturating_sub(40);
+        for line in &lines[start..] {
+            eprintln!("{}", line);
+        }
+        eprintln!("=== End Go runner log ===\n");
+    }
+}
+
+/// Find the JUnit XML report file in gauge's output directory.
+fn find_junit_xml(test_dir: &Path) -> Option<PathBuf> {
+    let xml_report_dir = test_dir.join("reports").join("xml-report");
+    if !xml_report_dir.exists() {
+        return None;
+    }
+
+    // Look for result.xml first, then any .xml file
+    let result_xml = xml_report_dir.join("result.xml");
+    if result_xml.exists() {
+        return Some(result_xml);
+    }
+
+    // Fallback: any .xml file
+    if let Ok(entries) = fs::read_dir(&xml_report_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("xml") {
+                return Some(path);
+            }
+        }
+    }
+
+    None
+}
+
+/// Set up profiling: connect to cluster, check metrics availability, collect capacity and baseline, start collector.
+/// Returns None if metrics are unavailable (with warnings printed).
+async fn setup_profiler() -> Result<Option<(kube::Client, profile::ClusterCapacity, profile::ResourceSnapshot, profile::MetricsCollector)>> {
+    let client = crate::k8s::client().await
+        .context("Could not connect to cluster for profiling")?;
+
+    if !profile::check_metrics_available(&client).await? {
+        warnings::warn("Metrics server not available, skipping profiling");
+        return Ok(None);
+    }
+
+    let cluster = profile::collect_cluster_capacity(&client).await
+        .context("Failed to collect cluster capacity")?;
+    let baseline = profile::collect_baseline(&client).await
+        .context("Failed to collect baseline")?;
+    let collector = profile::MetricsCollector::start(client.clone());
+
+    Ok(Some((client, cluster, baseline, collector)))
+}
+
+/// gauge's runner_connection_timeout for a normal run, in milliseconds.
+const RUNNER_CONNECTION_TIMEOUT_MS: u64 = 3_600_000;
+
+/// runner_connection_timeout for the single retry after the Go runner timed out.
+const RETRY_RUNNER_CONNECTION_TIMEOUT_MS: u64 = 7_200_000;
+
+/// Upper bound for pre-warming the module cache before the retry.
+const MODULE_DOWNLOAD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30 * 60);
+
+/// Output fragments (lowercased) gauge prints when the language runner never connected.
+const RUNNER_TIMEOUT_SIGNATURES: &[&str] = &[
+    "timed out connecting to",
+    "runner connection timed out",
+    "timed out waiting for runner",
+    "failed to start a runner",
+];
+
+/// Set gauge's runner_connection_timeout in GAUGE_HOME config.
+/// The default 30s is too short: gauge's Go runner must download+compile all
+/// release-tests Go dependencies on first run in the container.
+/// Note: the project's env/default/default.properties is NOT used for this setting.
+fn ensure_runner_timeout(timeout_ms: u64) {
+    if let Some(gauge_home) = std::env::var_os("GAUGE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| crate::platform::home_dir().map(|h| h.join(".gauge")))
+    {
+        let config_dir = gauge_home.join("config");
+        let config_file = config_dir.join("gauge.properties");
+        let setting = format!("runner_connection_timeout = {timeout_ms}");
+        let _ = fs::create_dir_all(&config_dir);
+        if config_file.exists() {
+            if let Ok(content) = fs::read_to_string(&config_file)
+                && !content.lines().any(|l| l.trim() == setting)
+            {
+                let mut updated: String = content
+                    .lines()
+                    .filter(|l| !l.trim_start().starts_with("runner_connection_timeout"))
+                    .map(|l| format!("{l}\n"))
+                    .collect();
+                updated.push_str(&setting);
+                updated.push('\n');
+                let _ = fs::write(&config_file, updated);
+                eprintln!("Set {} in {}", setting, config_file.display());
+            }
+        } else {
+            let _ = fs::write(&config_file, format!("{setting}\n"));
+            eprintln!("Created {} with {}", config_file.display(), setting);
+        }
+    }
+}
+
+/// Whether gauge's output shows the Go runner never connected (as opposed to failing specs).
+fn runner_timed_out(output: &str) -> bool {
+    let output = output.to_lowercase();
+    RUNNER_TIMEOUT_SIGNATURES.iter().any(|sig| output.contains(sig))
+}
+
+/// Read everything gauge wrote for the last run: captured stdout/stderr and its internal log.
+fn collect_gauge_output(test_dir: &Path, output_dir: &Path) -> String {
+    [
+        output_dir.join("logs").join("test-stdout.log"),
+        output_dir.join("logs").join("test-stderr.log"),
+        test_dir.join("logs").join("gauge.log"),
+    ]
+    .iter()
+    .filter_map(|p| fs::read_to_string(p).ok())
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Facts that usually explain a Go runner connection timeout.
+struct RunnerDiagnosis {
+    goproxy: String,
+    /// Proxy URL checked and the outcome: response time or the error.
+    proxy_check: Option<(String, std::result::Result<std::time::Duration, String>)>,
+    /// How long `go mod download` took in the release-tests checkout, or why it failed.
+    module_download: std::result::Result<std::time::Duration, String>,
+}
+
+impl RunnerDiagnosis {
+    fn report(&self) -> String {
+        let mut lines = vec![format!("GOPROXY: {}", if self.goproxy.is_empty() { "(unset)" } else { &self.goproxy })];
+        match &self.proxy_check {
+            Some((url, Ok(elapsed))) => lines.push(format!("Network: {} reachable in {:.1}s", url, elapsed.as_secs_f64())),
+            Some((url, Err(e))) => lines.push(format!("Network: {} NOT reachable ({}); check egress/proxy settings", url, e)),
+            None => lines.push("Network: no module proxy configured (GOPROXY=direct/off); modules are fetched from VCS hosts".to_string()),
+        }
+        match &self.module_download {
+            Ok(elapsed) => lines.push(format!("Module download: `go mod download` took {:.0}s", elapsed.as_secs_f64())),
+            Err(e) => lines.push(format!("Module download: `go mod download` failed: {}", e)),
+        }
+        lines.join("\n")
+    }
+}
+
+/// First proxy URL in a GOPROXY list, skipping the `direct`/`off` keywords.
+fn first_proxy_url(goproxy: &str) -> Option<&str> {
+    goproxy
+        .split([',', '|'])
+        .map(str::trim)
+        .find(|p| !p.is_empty() && *p != "direct" && *p != "off")
+}
+
+/// Probe the module proxy and pre-warm the module cache in the release-tests
+/// checkout, so the retry's runner only has to compile.
+fn diagnose_runner_timeout(test_dir: &Path) -> RunnerDiagnosis {
+    let goproxy = exec::run_cmd("go", &["env", "GOPROXY"])
+        .map(|r| r.stdout.trim().to_string())
+        .unwrap_or_default();
+
+    let proxy_check = first_proxy_url(&goproxy).map(|url| {
+        let outcome = match exec::run_cmd_unchecked_timeout("curl", &["-sS", "-o", "/dev/null", "--max-time", "15", url], exec::API_TIMEOUT) {
+            Ok(r) if r.exit_code == 0 => Ok(r.duration),
+            Ok(r) => Err(r.stderr.trim().to_string()),
+            Err(e) => Err(format!("{e:#}")),
+        };
+        (url.to_string(), outcome)
+    });
+
+    eprintln!("  Pre-warming Go module cache for release-tests...");
+    let dir = test_dir.to_string_lossy();
+    let module_download = match exec::run_cmd_unchecked_timeout("go", &["-C", &dir, "mod", "download"], MODULE_DOWNLOAD_TIMEOUT) {
+        Ok(r) if r.timed_out => Err(format!("timed out after {}s", MODULE_DOWNLOAD_TIMEOUT.as_secs())),
+        Ok(r) if r.exit_code == 0 => Ok(r.duration),
+        Ok(r) => Err(r.stderr.trim().to_string()),
+        Err(e) => Err(format!("{e:#}")),
+    };
+
+    RunnerDiagnosis { goproxy, proxy_check, module_download }
+}
+
+/// Run gauge, retrying once when the Go runner timed out connecting: the module
+/// cache is pre-warmed, the connection timeout raised, and a diagnosis printed.
+/// A second timeout is returned as an error carrying the diagnosis.
+fn run_gauge_with_retry(test_dir: &Path, args: &[String], env: &[(String, String)], output_dir: &Path, profiler: Option<Arc<profile::MetricsCollector>>, events: Option<Arc<events::EventCollector>>) -> Result<i32> {
+    let exit_code = run_gauge_tests(test_dir, args, env, output_dir, profiler.clone(), events.clone())?;
+    if exit_code == 0 {
+        return Ok(0);
+    }
+    if !runner_timed_out(&collect_gauge_output(test_dir, output_dir)) {
+        dump_gauge_logs(test_dir);
+        return Ok(exit_code);
+    }
+
+    eprintln!();
+    warnings::warn("gauge's Go runner timed out connecting; diagnosing and retrying once");
+    let diagnosis = diagnose_runner_timeout(test_dir);
+    for line in diagnosis.report().lines() {
+        eprintln!("  {}", line);
+    }
+    ensure_runner_timeout(RETRY_RUNNER_CONNECTION_TIMEOUT_MS);
+
+    let exit_code = run_gauge_tests(test_dir, args, env, output_dir, profiler, events)?;
+    ensure_runner_timeout(RUNNER_CONNECTION_TIMEOUT_MS);
+    if exit_code != 0 {
+        dump_gauge_logs(test_dir);
+        if runner_timed_out(&collect_gauge_output(test_dir, output_dir)) {
+            anyhow::bail!(
+                "gauge's Go runner timed out connecting again after pre-warming modules and raising runner_connection_timeout to {}s\n{}",
+                RETRY_RUNNER_CONNECTION_TIMEOUT_MS / 1000,
+                diagnosis.report()
+            );
+        }
+    }
+    Ok(exit_code)
+}
+
+/// Outcome of one test hook step, written to `results/test-hooks.json`.
+#[derive(Debug, serde::Serialize)]
+pub struct HookStatus {
+    pub name: String,
+    /// "setup" or "teardown"
+    pub phase: &'static str,
+    pub ok: bool,
+    pub duration_secs: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Test hooks that were set up, plus the status of every step run so far.
+struct ActiveHooks {
+    base_dir: PathBuf,
+    set_up: Vec<config::TestHook>,
+    statuses: Vec<HookStatus>,
+}
+
+/// Apply the manifests and run the setup scripts of `config/test-hooks.toml`.
+/// A failed required hook tears down the hooks already set up and fails the
+/// test phase; a failed optional hook only warns.
+fn setup_test_hooks(test_dir: &Path, output_dir: &Path) -> Result<ActiveHooks> {
+    let path = config::default_test_hooks_config_path();
+    let cfg = config::load_test_hooks_config(&path)?;
+    let mut active = ActiveHooks {
+        base_dir: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+        set_up: Vec::new(),
+        statuses: Vec::new(),
+    };
+    for hook in cfg.hooks {
+        let result = run_hook_step(&hook, "setup", &active.base_dir, test_dir, output_dir);
+        let failed = result.as_ref().err().map(|e| format!("{e:#}"));
+        active.statuses.push(hook_status(&hook, "setup", &result));
+        match failed {
+            None => active.set_up.push(hook),
+            Some(e) if hook.required => {
+                teardown_test_hooks(active, test_dir, output_dir);
+                anyhow::bail!("Test hook '{}' failed to set up: {}", hook.name, e);
+            }
+            Some(e) => warnings::warn(format!("Optional test hook '{}' failed to set up: {}", hook.name, e)),
+        }
+    }
+    Ok(active)
+}
+
+/// Tear down the hooks that were set up, in reverse order, then report the
+/// status of every hook step. Teardown failures only warn.
+fn teardown_test_hooks(mut active: ActiveHooks, test_dir: &Path, output_dir: &Path) {
+    while let Some(hook) = active.set_up.pop() {
+        if hook.manifest.is_none() && hook.teardown.is_none() {
+            continue;
+        }
+        let result = run_hook_step(&hook, "teardown", &active.base_dir, test_dir, output_dir);
+        if let Err(e) = &result {
+            warnings::warn(format!("Test hook '{}' failed to tear down: {e:#}", hook.name));
+        }
+        active.statuses.push(hook_status(&hook, "teardown", &result));
+    }
+    if active.statuses.is_empty() {
+        return;
+    }
+
+    println!("\nTest hooks:");
+    for s in &active.statuses {
+        println!(
+            "  {:<8} {:<24} {:<8} {:.1}s",
+            if s.ok { "ok" } else { "FAILED" },
+            s.name,
+            s.phase,
+            s.duration_secs
+        );
+    }
+    let results_dir = output_dir.join("results");
+    let written = fs::create_dir_all(&results_dir)
+        .map_err(anyhow::Error::from)
+        .and_then(|_| Ok(serde_json::to_string_pretty(&active.statuses)?))
+        .and_then(|json| Ok(fs::write(results_dir.join("test-hooks.json"), json)?));
+    if let Err(e) = written {
+        warnings::warn(format!("Could not write test hook statuses: {e:#}"));
+    }
+}
+
+fn hook_status(hook: &config::TestHook, phase: &'static str, result: &Result<std::time::Duration>) -> HookStatus {
+    HookStatus {
+        name: hook.name.clone(),
+        phase,
+        ok: result.is_ok(),
+        duration_secs: result.as_ref().map(|d| d.as_secs_f64()).unwrap_or_default(),
+        error: result.as_ref().err().map(|e| format!("{e:#}")),
+    }
+}
+
+/// Run the `phase` step of `hook`: `oc apply`/`oc delete` of its manifest, or
+/// its script, with output saved to `logs/hooks/<name>-<phase>.log`.
+fn run_hook_step(hook: &config::TestHook, phase: &str, base_dir: &Path, test_dir: &Path, output_dir: &Path) -> Result<std::time::Duration> {
+    let pb = progress::stage_spinner(&format!("Test hook {} ({})", hook.name, phase));
+    let timeout = std::time::Duration::from_secs(hook.timeout_secs);
+    let result = match (&hook.manifest, phase) {
+        (Some(manifest), "setup") => {
+            let manifest = base_dir.join(manifest).display().to_string();
+            exec::run_cmd_unchecked_timeout("oc", &["apply", "-f", &manifest], timeout)
+        }
+        (Some(manifest), _) => {
+            let manifest = base_dir.join(manifest).display().to_string();
+            exec::run_cmd_unchecked_timeout("oc", &["delete", "-f", &manifest, "--ignore-not-found"], timeout)
+        }
+        (None, _) => {
+            let script = if phase == "setup" { &hook.setup } else { &hook.teardown };
+            let script = base_dir.join(script.as_deref().unwrap_or_default()).display().to_string();
+            // Scripts find the checkout and results through the environment
+            let output_env = format!("STREAMSTRESS_OUTPUT_DIR={}", output_dir.display());
+            let tests_env = format!("RELEASE_TESTS_DIR={}", test_dir.display());
+            exec::run_cmd_unchecked_timeout("env", &[&output_env, &tests_env, "sh", &script], timeout)
+        }
+    };
+
+    let outcome = result.and_then(|r| {
+        let log_dir = output_dir.join("logs/hooks");
+        let log = log_dir.join(format!("{}-{}.log", hook.name, phase));
+        let saved = fs::create_dir_all(&log_dir)
+            .and_then(|_| fs::write(&log, format!("{}{}", exec::redact(&r.stdout), exec::redact(&r.stderr))));
+        if let Err(e) = saved {
+            warnings::warn(format!("Could not write {}: {e}", log.display()));
+        }
+        if r.timed_out {
+            anyhow::bail!("timed out after {}s (log: {})", hook.timeout_secs, log.display());
+        }
+        if r.exit_code != 0 {
+            let last = r.stderr.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or_default();
+            anyhow::bail!("exit {}: {} (log: {})", r.exit_code, exec::redact(last.trim()), log.display());
+        }
+        Ok(r.duration)
+    });
+    progress::finish_spinner(&pb, outcome.is_ok());
+    outcome
+}
+
+/// Orchestrate the full test execution flow:
+/// 1. Preflight checks (gauge binary + plugins)
+/// 2. Clone release-tests repo
+/// 3. Set up the test hooks (test data the specs need) and, with
+///    `--scoped-user`, the namespace-scoped user
+/// 4. Run gauge tests with log capture, then tear the hooks down
+/// 5. Parse results, print summary, write JSON
+///
+/// `synthetic` holds checks run outside gauge (e.g. Chains signing), added to
+/// the results as tests.
+///
+/// Returns Ok(true) if tests passed, Ok(false) if tests failed.
+pub async fn run_tests(
+    tags: &str,
+    release_tests_ref: &str,
+    output_dir: &Path,
+    profile: bool,
+    synthetic: &[results::TestCaseResult],
+) -> Result<bool> {
+    // Stage 1: Preflight checks
+    let pb = progress::stage_spinner("Preflight checks");
+    preflight_check()?;
+    progress::finish_spinner(&pb, true);
+
+    // Stage 2: Clone release-tests
+    let pb = progress::stage_spinner("Clone release-tests");
+    let temp_dir = workspace::scoped("release-tests")?;
+    let test_dir = clone_release_tests(temp_dir.path(), release_tests_ref, &pb)?;
+    progress::finish_spinner(&pb, true);
+
+    ensure_runner_timeout(RUNNER_CONNECTION_TIMEOUT_MS);
+
+    // Stage 2.25: Seed test data
+    let hooks = setup_test_hooks(&test_dir, output_dir)?;
+
+    // Stage 2.3: Scoped user for the permission-sensitive specs
+    let scoped_user = if scopeduser::enabled() {
+        match scopeduser::provision(temp_dir.path()).await {
+            Ok(user) => Some(user),
+            Err(e) => {
+                teardown_test_hooks(hooks, &test_dir, output_dir);
+                return Err(e.context("Failed to provision the scoped user (--scoped-user)"));
+            }
+        }
+    } else {
+        None
+    };
+
+    // Stage 2.5: Set up profiler if requested
+    let mut profiling_ctx: Option<(kube::Client, profile::ClusterCapacity, profile::ResourceSnapshot, Arc<profile::MetricsCollector>)> = None;
+
+    if profile {
+        match setup_profiler().await {
+            Ok(Some((client, cluster, baseline, collector))) => {
+                let arc_collector = Arc::new(collector);
+                profiling_ctx = Some((client, cluster, baseline, arc_collector));
+            }
+            Ok(None) => {} // warnings already printed
+            Err(e) => {
+                warnings::warn(format!("Profiling setup failed: {e:#}, continuing without profiling"));
+            }
+        }
+    }
+
+    // Stage 3: Run gauge tests (streaming with log capture)
+    println!("Running Gauge tests with tags: {tags}");
+    let profiler_for_gauge = profiling_ctx.as_ref().map(|(_, _, _, c)| c.clone());
+    let test_window_start = chrono::Utc::now();
+    let event_collector = match crate::k8s::client().await {
+        Ok(client) => Some(Arc::new(events::EventCollector::start(client))),
+        Err(e) => {
+            warnings::warn(format!("Could not connect to cluster to collect events: {e}"));
+            None
+        }
+    };
+    let scoped_env = scoped_user.as_ref().map(scopeduser::ScopedUser::env).unwrap_or_default();
+    let exit_code = run_gauge_with_retry(&test_dir, &gauge_run_args(Some(tags), &[]), &scoped_env, output_dir, profiler_for_gauge, event_collector.clone());
+    if let Some(collector) = event_collector {
+        match Arc::try_unwrap(collector) {
+            Ok(c) => {
+                let records = c.stop().await;
+                if let Err(e) = events::write_timeline(output_dir, &records) {
+                    warnings::warn(format!("Failed to write events timeline: {e:#}"));
+                }
+            }
+            Err(_) => warnings::warn("Could not finalize event collector (still in use)"),
+        }
+    }
+    if let Some(user) = scoped_user {
+        scopeduser::cleanup(user).await;
+    }
+    teardown_test_hooks(hooks, &test_dir, output_dir);
+    let exit_code = exit_code?;
+
+    // Stage 3.5: Finalize profiling if active
+    if let Some((_client, cluster, baseline, collector)) = profiling_ctx {
+        // Need to unwrap the Arc to call stop (which takes self)
+        match Arc::try_unwrap(collector) {
+            Ok(c) => {
+                match c.stop().await {
+                    Ok((specs, samples)) => {
+                        // Find peak spec for parallelism calculation
+                        let peak_cpu = specs.iter().map(|s| s.cpu.p95).max().unwrap_or(0);
+                        let peak_mem = specs.iter().map(|s| s.memory.p95).max().unwrap_or(0);
+                        let peak_spec_name = specs.iter()
+                            .max_by_key(|s| s.cpu.p95)
+                            .map(|s| s.spec_name.clone())
+                            .unwrap_or_else(|| "none".to_string());
+
+                        let safety_margin = 20;
+                        let max_parallel = profile::calculate_max_parallelism(
+                            cluster.allocatable_cpu_millicores,
+                            baseline.cpu_millicores,
+                            peak_cpu,
+                            safety_margin,
+                        );
+
+                        // Determine limiting resource
+                        let avail_cpu = cluster.allocatable_cpu_millicores.saturating_sub(baseline.cpu_millicores);
+                        let avail_mem = cluster.allocatable_memory_bytes.saturating_sub(baseline.memory_bytes);
+                        let cpu_parallel = (avail_cpu * 80 / 100).checked_div(peak_cpu).unwrap_or(u64::MAX);
+                        let mem_parallel = (avail_mem * 80 / 100).checked_div(peak_mem).unwrap_or(u64::MAX);
+                        let limiting = if cpu_parallel <= mem_parallel { "cpu" } else { "memory" };
+
+                        let recommendation = profile::ParallelismRecommendation {
+                            max_parallel_specs: max_parallel,
+                            limiting_resource: limiting.to_string(),
+                            safety_margin_percent: safety_margin,
+                            reasoning: format!(
+                                "Based on {} specs profiled, peak spec '{}' uses {}m CPU / {}Mi memory. \
+                                 With {}% safety margin, {} is the limiting resource.",
+                                specs.len(), peak_spec_name,
+                                peak_cpu, peak_mem / (1024 * 1024),
+                                safety_margin, limiting
+                            ),
+                        };
+
+                        let resource_profile = profile::ResourceProfile {
+                            run_timestamp: crate::timestamp::now_rfc3339(),
+                            cluster: cluster.clone(),
+                            baseline: baseline.clone(),
+                            specs: specs.clone(),
+                            recommendation: recommendation.clone(),
+                        };
+
+                        // Write resource-profile.json
+                        let results_dir = output_dir.join("results");
+                        let _ = fs::create_dir_all(&results_dir);
+                        let profile_path = results_dir.join("resource-profile.json");
+                        match serde_json::to_string_pretty(&resource_profile) {
+                            Ok(json) => {
+                                if let Err(e) = fs::write(&profile_path, &json) {
+                                    warnings::warn(format!("Failed to write resource profile: {e:#}"));
+                                }
+                            }
+                            Err(e) => warnings::warn(format!("Failed to serialize resource profile: {e:#}")),
+                        }
+
+                        // Print summary
+                        println!("\nResource Profile:");
+                        println!("  Cluster: {} nodes, {}m CPU, {}Mi memory allocatable",
+                            cluster.node_count,
+                            cluster.allocatable_cpu_millicores,
+                            cluster.allocatable_memory_bytes / (1024 * 1024));
+                        println!("  Baseline: {}m CPU, {}Mi memory, {} pods",
+                            baseline.cpu_millicores,
+                            baseline.memory_bytes / (1024 * 1024),
+                            baseline.pod_count);
+                        println!("  Specs profiled: {}", specs.len());
+                        if !specs.is_empty() {
+                            println!("  Peak spec: {} ({}m CPU, {}Mi memory)",
+                                peak_spec_name, peak_cpu, peak_mem / (1024 * 1024));
+                        }
+                        println!("  Recommended parallelism: {} (limited by {})",
+                            max_parallel, limiting);
+                        println!("  Profile written to: {}", profile_path.display());
+                        if let Err(e) = profileexport::export(&results_dir, &samples) {
+                            warnings::warn(format!("Failed to export resource samples: {e:#}"));
+                        }
+                    }
+                    Err(e) => warnings::warn(format!("Failed to collect profiling results: {e:#}")),
+                }
+            }
+            Err(_) => warnings::warn("Could not finalize profiler (still in use)"),
+        }
+    }
+
+    // Stage 3.75: Scan controller logs for errors logged during the tests
+    let log_errors = if exit_code == 0 {
+        Vec::new()
+    } else {
+        let dir = output_dir.to_path_buf();
+        match tokio::task::spawn_blocking(move || logscan::scan(test_window_start, &dir)).await {
+            Ok(Ok(errors)) => errors,
+            Ok(Err(e)) => {
+                warnings::warn(format!("Controller log scan failed: {e:#}"));
+                Vec::new()
+            }
+            Err(e) => {
+                warnings::warn(format!("Controller log scan panicked: {e}"));
+                Vec::new()
+            }
+        }
+    };
+
+    // Stage 4: Parse results and write output
+    write_test_results(&test_dir, output_dir, synthetic, &log_errors)?;
+
+    Ok(exit_code == 0 && synthetic.iter().all(|t| t.passed))
+}
+
+/// Re-run only `scenarios` from release-tests at `release_tests_ref` against the
+/// current deployment, writing the delta results under `output_dir`.
+/// Returns Ok(true) if every re-run scenario passed.
+pub fn rerun_scenarios(scenarios: &[String], release_tests_ref: &str, output_dir: &Path) -> Result<bool> {
+    preflight_check()?;
+
+    let pb = progress::stage_spinner("Clone release-tests");
+    let temp_dir = workspace::scoped("release-tests")?;
+    let test_dir = clone_release_tests(temp_dir.path(), release_tests_ref, &pb)?;
+    progress::finish_spinner(&pb, true);
+    ensure_runner_timeout(RUNNER_CONNECTION_TIMEOUT_MS);
+
+    let hooks = setup_test_hooks(&test_dir, output_dir)?;
+    println!("Re-running {} failed scenario(s)", scenarios.len());
+    let exit_code = run_gauge_with_retry(&test_dir, &gauge_run_args(None, scenarios), &[], output_dir, None, None);
+    teardown_test_hooks(hooks, &test_dir, output_dir);
+    let exit_code = exit_code?;
+    write_test_results(&test_dir, output_dir, &[], &[])?;
+    Ok(exit_code == 0)
+}
+
+/// Write results for a run whose test suite was skipped after a failed probe:
+/// only the synthetic tests.
+pub fn write_synthetic_results(output_dir: &Path, synthetic: &[results::TestCaseResult]) -> Result<()> {
+    let results_dir = output_dir.join("results");
+    fs::create_dir_all(&results_dir).context("Failed to create results directory")?;
+    let mut result = results::TestRunResult {
+        total: 0,
+        passed: 0,
+        failed: 0,
+        errors: 0,
+        duration_secs: 0.0,
+        source: None,
+        tests: Vec::new(),
+    };
+    results::add_tests(&mut result, synthetic);
+    let categorized = results::categorize_results(&result);
+    results::print_categorized_results(&categorized);
+    let json_path = results::write_results(&categorized, &results_dir)?;
+    println!("Results written to {}", json_path.display());
+    Ok(())
+}
+
+/// Parse gauge's JUnit XML (or its stdout log as a fallback) from a finished run
+/// in `test_dir`, attach `log_errors` to the failed scenarios they were logged
+/// during, add the `synthetic` tests, print the summary, and write results under
+/// `output_dir`.
+fn write_test_results(test_dir: &Path, output_dir: &Path, synthetic: &[results::TestCaseResult], log_errors: &[logscan::LogExcerpt]) -> Result<()> {
+    let results_dir = output_dir.join("results");
+    fs::create_dir_all(&results_dir).context("Failed to create results directory")?;
+    let timeline = gaugeevents::read_timeline(output_dir);
+    let ended = chrono::Utc::now();
+
+    match find_junit_xml(test_dir) {
+        Some(xml_path) => {
+            // Copy junit.xml to output
+            let dest_xml = results_dir.join("junit.xml");
+            fs::copy(&xml_path, &dest_xml)
+                .with_context(|| format!("Failed to copy JUnit XML to {}", dest_xml.display()))?;
+
+            // Parse and display results
+            match results::parse_junit_xml(&xml_path) {
+                Ok(mut result) => {
+                    gaugeevents::apply_durations(&mut result, &timeline);
+                    logscan::attach(&mut result.tests, &timeline, log_errors, ended);
+                    results::add_tests(&mut result, synthetic);
+                    let categorized = results::categorize_results(&result);
+                    results::print_categorized_results(&categorized);
+
+                    let json_path = results::write_results(&categorized, &results_dir)?;
+
+                    println!("Results written to {}", json_path.display());
+                    println!("Logs written to {}/logs/", output_dir.display());
+                }
+                Err(e) => {
+                    warnings::warn(format!("Failed to parse JUnit XML: {e:#}"));
+                    eprintln!("Raw XML copied to {}", dest_xml.display());
+                }
+            }
+        }
+        None => {
+            // Fallback: parse Gauge stdout log
+            let stdout_log = output_dir.join("logs/test-stdout.log");
+            if stdout_log.exists() {
+                match results::parse_gauge_stdout(&stdout_log) {
+                    Ok(mut result) => {
+                        gaugeevents::apply_durations(&mut result, &timeline);
+                    logscan::attach(&mut result.tests, &timeline, log_errors, ended);
+                        results::add_tests(&mut result, synthetic);
+                        let categorized = results::categorize_results(&result);
+                        results::print_categorized_results(&categorized);
+
+                        let json_path = results::write_results(&categorized, &results_dir)?;
+
+                        println!("Results written to {}", json_path.display());
+                        println!("Logs written to {}/logs/", output_dir.display());
+                    }
+                    Err(e) => {
+                        warnings::warn(format!("Failed to parse Gauge stdout: {e:#}"));
+                    }
+                }
+            } else {
+                warnings::warn(
+                    "No JUnit XML or Gauge stdout log found. No results to parse."
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gauge_versions() {
+        let json = r#"{"version":"1.6.3","commitHash":"abc","plugins":[{"name":"go","version":"0.4.0"},{"name":"go","version":"0.5.0"},{"name":"html-report","version":"4.3.1"}]}"#;
+        let v = parse_gauge_versions(json);
+        assert_eq!(v.gauge, "1.6.3");
+        assert_eq!(v.plugin_versions("go"), ["0.4.0", "0.5.0"]);
+        assert!(v.plugin_versions("xml-report").is_empty());
+
+        let text = "Gauge version: 1.6.3\nCommit Hash: abc\n\nPlugins\n-------\ngo (0.4.0)\nxml-report (0.5.1)\n";
+        let v = parse_gauge_versions(text);
+        assert_eq!(v.gauge, "1.6.3");
+        assert_eq!(v.plugin_versions("xml-report"), ["0.5.1"]);
+    }
+
+    #[test]
+    fn test_runner_timeout_detection() {
+        assert!(runner_timed_out("Failed to start gauge API: Timed out connecting to go runner"));
+        assert!(runner_timed_out("[ERROR] Runner connection timed out after 30000ms"));
+        assert!(!runner_timed_out("Specifications:\t3 executed\t2 passed\t1 failed"));
+        assert_eq!(first_proxy_url("https://proxy.golang.org,direct"), Some("https://proxy.golang.org"));
+        assert_eq!(first_proxy_url("direct|https://goproxy.corp"), Some("https://goproxy.corp"));
+        assert_eq!(first_proxy_url("off"), None);
+    }
+
+    #[test]
+    fn test_plugin_actions() {
+        assert_eq!(plugin_actions(&[], None), [PluginAction::InstallLatest]);
+        assert!(plugin_actions(&["0.5.0"], None).is_empty());
+        assert!(plugin_actions(&["0.4.0"], Some("0.4.0")).is_empty());
+        assert_eq!(
+            plugin_actions(&["0.5.0"], Some("0.4.0")),
+            [PluginAction::Install("0.4.0".into()), PluginAction::Uninstall(vec!["0.5.0".into()])]
+        );
+        assert_eq!(plugin_actions(&["0.4.0", "0.5.0"], Some("0.4.0")), [PluginAction::Uninstall(vec!["0.5.0".into()])]);
+    }
+}
@@ -0,0 +1,183 @@
+//! Default release-tests ref for the installed operator.
+//!
+//! release-tests keeps a `release-vX.Y` branch per OpenShift Pipelines
+//! version; testing an older operator with `master` fails on features it does
+//! not have yet. Without `--release-tests-ref`, the operator version is read
+//! from the TektonConfig (or the operator's CSV) and the matching branch is
+//! used when release-tests has it, else `master`.
+//!
+//! The repository is openshift-pipelines/release-tests unless
+//! `--release-tests-repo` or `release_tests_repo` in `config/gauge.toml` names
+//! a fork, e.g. one with experimental specs. Branches are looked up and cloned
+//! from that repository the same way.
+
+use anyhow::Result;
+use kube::api::{Api, ApiResource, DynamicObject, ListParams};
+use serde::Serialize;
+use std::sync::OnceLock;
+
+use crate::{config, gitops, k8s, test, warnings};
+
+/// Branch used when the operator version is unknown or has no branch.
+pub const DEFAULT_REF: &str = "master";
+
+static REPO: OnceLock<String> = OnceLock::new();
+
+/// Set the release-tests repository: `flag` (--release-tests-repo), else
+/// `release_tests_repo` in config/gauge.toml, else openshift-pipelines/release-tests.
+/// Call once at startup.
+pub fn init(flag: Option<&str>) -> Result<()> {
+    let repo = match flag {
+        Some(url) => url.to_string(),
+        None => config::load_gauge_config(&config::default_gauge_config_path())?
+            .release_tests_repo
+            .unwrap_or_else(|| test::RELEASE_TESTS_REPO.to_string()),
+    };
+    let _ = REPO.set(repo);
+    Ok(())
+}
+
+/// The release-tests repository URL.
+pub fn repo() -> &'static str {
+    REPO.get().map(String::as_str).unwrap_or(test::RELEASE_TESTS_REPO)
+}
+
+/// CLI args recreating this setting (for self-invocation and in-cluster Jobs),
+/// whose config may not name the same fork.
+pub fn to_args() -> Vec<String> {
+    if repo() == test::RELEASE_TESTS_REPO {
+        Vec::new()
+    } else {
+        vec!["--release-tests-repo".to_string(), repo().to_string()]
+    }
+}
+
+/// The release-tests ref of a run and how it was chosen; recorded in run metadata.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReleaseTestsRef {
+    /// Repository the ref is from
+    pub repo: String,
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    /// "flag" (--release-tests-ref), "operator" (matched the operator version) or "default"
+    pub source: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operator_version: Option<String>,
+}
+
+impl ReleaseTestsRef {
+    /// The ref given on the command line, or the default without looking at the cluster.
+    pub fn given(flag: Option<&str>) -> Self {
+        match flag {
+            Some(r) => ReleaseTestsRef { repo: repo().to_string(), git_ref: r.to_string(), source: "flag", operator_version: None },
+            None => ReleaseTestsRef {
+                repo: repo().to_string(),
+                git_ref: DEFAULT_REF.to_string(),
+                source: "default",
+                operator_version: None,
+            },
+        }
+    }
+
+    /// Whether the ref was given explicitly; an automatic one is detected again where it is used.
+    pub fn is_explicit(&self) -> bool {
+        self.source == "flag"
+    }
+}
+
+/// The ref from `flag`, else the branch matching the installed operator's version.
+/// Blocking: creates its own runtime for the cluster calls.
+pub fn resolve(flag: Option<&str>) -> ReleaseTestsRef {
+    if flag.is_some() {
+        return ReleaseTestsRef::given(flag);
+    }
+    let version = match operator_version() {
+        Ok(Some(v)) => v,
+        Ok(None) => {
+            eprintln!("Operator version unknown; using release-tests {}", DEFAULT_REF);
+            return ReleaseTestsRef::given(None);
+        }
+        Err(e) => {
+            warnings::warn(format!("Could not detect the operator version: {e:#}; using release-tests {}", DEFAULT_REF));
+            return ReleaseTestsRef::given(None);
+        }
+    };
+    let branch = branch_for_version(&version).filter(|b| branch_exists(b));
+    match branch {
+        Some(branch) => {
+            eprintln!("Operator {}: using release-tests branch {}", version, branch);
+            ReleaseTestsRef { repo: repo().to_string(), git_ref: branch, source: "operator", operator_version: Some(version) }
+        }
+        None => {
+            eprintln!("Operator {}: {} has no matching branch; using {}", version, repo(), DEFAULT_REF);
+            ReleaseTestsRef {
+                repo: repo().to_string(),
+                git_ref: DEFAULT_REF.to_string(),
+                source: "default",
+                operator_version: Some(version),
+            }
+        }
+    }
+}
+
+/// release-tests branch for an operator version: "1.15.2" -> "release-v1.15".
+fn branch_for_version(version: &str) -> Option<String> {
+    let mut parts = version.trim().trim_start_matches('v').split(['.', '-']);
+    let major: u32 = parts.next()?.parse().ok()?;
+    let minor: u32 = parts.next()?.parse().ok()?;
+    Some(format!("release-v{}.{}", major, minor))
+}
+
+fn branch_exists(branch: &str) -> bool {
+    gitops::branch_exists(repo(), branch)
+}
+
+/// Installed operator version: TektonConfig `status.version`, else the version of
+/// the openshift-pipelines-operator CSV. None if neither is there.
+fn operator_version() -> Result<Option<String>> {
+    let (rt, client) = k8s::create_kube_client()?;
+    let tc_ar = ApiResource {
+        group: "operator.tekton.dev".into(),
+        version: "v1alpha1".into(),
+        api_version: "operator.tekton.dev/v1alpha1".into(),
+        kind: "TektonConfig".into(),
+        plural: "tektonconfigs".into(),
+    };
+    let tc_api: Api<DynamicObject> = Api::all_with(client.clone(), &tc_ar);
+    if let Ok(Some(tc)) = k8s::block_on_retry(&rt, "get TektonConfig", || tc_api.get_opt("config"))
+        && let Some(v) = tc.data["status"]["version"].as_str().filter(|v| !v.is_empty())
+    {
+        return Ok(Some(v.to_string()));
+    }
+
+    let csv_ar = ApiResource {
+        group: "operators.coreos.com".into(),
+        version: "v1alpha1".into(),
+        api_version: "operators.coreos.com/v1alpha1".into(),
+        kind: "ClusterServiceVersion".into(),
+        plural: "clusterserviceversions".into(),
+    };
+    let csv_api: Api<DynamicObject> = Api::namespaced_with(client, "openshift-operators", &csv_ar);
+    let lp = ListParams::default();
+    let csvs = k8s::block_on_retry(&rt, "list ClusterServiceVersions", || csv_api.list(&lp))?;
+    Ok(csvs
+        .items
+        .iter()
+        .find(|c| c.metadata.name.as_deref().is_some_and(|n| n.starts_with("openshift-pipelines-operator")))
+        .and_then(|c| c.data["spec"]["version"].as_str())
+        .map(String::from))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_branch_for_version() {
+        assert_eq!(branch_for_version("1.15.2").as_deref(), Some("release-v1.15"));
+        assert_eq!(branch_for_version("v1.18.0-rc1").as_deref(), Some("release-v1.18"));
+        assert_eq!(branch_for_version("devel"), None);
+        assert!(ReleaseTestsRef::given(Some("main")).is_explicit());
+        assert_eq!(ReleaseTestsRef::given(None).git_ref, DEFAULT_REF);
+    }
+}
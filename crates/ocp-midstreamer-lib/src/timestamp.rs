@@ -0,0 +1,86 @@
+//! Timestamp helpers shared across the crate.
+//!
+//! All human- and machine-readable timestamps are RFC3339 in UTC with second
+//! precision (e.g. `2025-02-05T14:03:07Z`). Run, batch and matrix ids and file
+//! names carry the same timestamp in its filename-safe ISO 8601 basic form
+//! (`20250205T140307Z`), built by [`id`].
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, NaiveTime, SecondsFormat, Utc};
+
+/// Current time as an RFC3339 UTC string.
+pub fn now_rfc3339() -> String {
+    to_rfc3339(Utc::now())
+}
+
+/// Format a UTC time as RFC3339 with second precision and a `Z` suffix.
+pub fn to_rfc3339(t: DateTime<Utc>) -> String {
+    t.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Current time as Unix seconds, for Kubernetes object names and image tags.
+pub fn unix_now() -> i64 {
+    Utc::now().timestamp()
+}
+
+/// Filename-safe form of an RFC3339 timestamp: `2025-02-05T14:03:07Z` -> `20250205T140307Z`.
+pub fn compact(timestamp: &str) -> String {
+    match DateTime::parse_from_rfc3339(timestamp) {
+        Ok(t) => t.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string(),
+        Err(_) => timestamp.chars().filter(|c| c.is_ascii_alphanumeric()).collect(),
+    }
+}
+
+/// Id of something started at an RFC3339 `timestamp`: `<prefix>-<compact timestamp>`,
+/// e.g. `matrix-20250205T140307Z`.
+pub fn id(prefix: &str, timestamp: &str) -> String {
+    format!("{}-{}", prefix, compact(timestamp))
+}
+
+/// Run id derived from an RFC3339 timestamp: `2025-02-05T14:03:07Z` -> `run-20250205T140307Z`.
+pub fn run_id(timestamp: &str) -> String {
+    id("run", timestamp)
+}
+
+/// Parse a `YYYY-MM-DD` date.
+pub fn parse_date(date: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .with_context(|| format!("Invalid date '{}'. Use YYYY-MM-DD format.", date))
+}
+
+/// Last second of `date` in UTC, used as the cutoff for `--as-of` resolution.
+/// Computed on the UTC calendar regardless of the local timezone.
+pub fn end_of_day_utc(date: &str) -> Result<DateTime<Utc>> {
+    let end = NaiveTime::from_hms_opt(23, 59, 59).expect("valid time");
+    Ok(parse_date(date)?.and_time(end).and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_end_of_day_utc() {
+        let t = end_of_day_utc("2025-02-05").unwrap();
+        assert_eq!(to_rfc3339(t), "2025-02-05T23:59:59Z");
+    }
+
+    #[test]
+    fn test_end_of_day_rejects_invalid_date() {
+        assert!(end_of_day_utc("2025-02-30").is_err());
+        assert!(end_of_day_utc("02/05/2025").is_err());
+    }
+
+    #[test]
+    fn test_run_id() {
+        assert_eq!(run_id("2025-02-05T14:03:07Z"), "run-20250205T140307Z");
+        assert_eq!(id("matrix", "2025-02-05T15:03:07+01:00"), "matrix-20250205T140307Z");
+    }
+
+    #[test]
+    fn test_now_rfc3339_format() {
+        let now = now_rfc3339();
+        assert!(DateTime::parse_from_rfc3339(&now).is_ok());
+        assert!(now.ends_with('Z'));
+    }
+}
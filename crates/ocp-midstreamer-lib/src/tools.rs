@@ -0,0 +1,176 @@
+//! Versions and checksums of the external tools a run uses (`--require-pinned-tools`).
+//!
+//! ko, gauge, oc, opm, skopeo and buildah found on PATH are recorded with their
+//! version and the sha256 of their binary under `tools` in `results/metadata.json`
+//! and the published run, so runs from different runners can be compared. Pins
+//! in `config/tools.toml` are checked against them: a mismatch is a warning, or
+//! an error with `--require-pinned-tools`.
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use crate::config::ToolPin;
+use crate::{config, exec, platform, warnings};
+
+/// Tools recorded, with the arguments printing their version.
+const TRACKED: &[(&str, &[&str])] = &[
+    ("ko", &["version"]),
+    ("gauge", &["version"]),
+    ("oc", &["version", "--client"]),
+    ("opm", &["version"]),
+    ("skopeo", &["--version"]),
+    ("buildah", &["--version"]),
+];
+
+/// One tool found on PATH.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ToolRecord {
+    pub name: String,
+    pub path: String,
+    /// Version parsed from its version output; None when it printed none
+    pub version: Option<String>,
+    /// sha256 of the binary
+    pub sha256: String,
+}
+
+static REQUIRE_PINNED: OnceLock<bool> = OnceLock::new();
+
+static INVENTORY: OnceLock<Vec<ToolRecord>> = OnceLock::new();
+
+/// Set whether pin mismatches fail the run (--require-pinned-tools). Call once at startup.
+pub fn init(require_pinned: bool) {
+    let _ = REQUIRE_PINNED.set(require_pinned);
+}
+
+/// CLI args recreating this setting (for self-invocation and in-cluster Jobs).
+pub fn to_args() -> Vec<String> {
+    if REQUIRE_PINNED.get().copied().unwrap_or_default() {
+        vec!["--require-pinned-tools".to_string()]
+    } else {
+        Vec::new()
+    }
+}
+
+/// The tracked tools on PATH, probed once per process.
+pub fn inventory() -> &'static [ToolRecord] {
+    INVENTORY.get_or_init(|| TRACKED.iter().filter_map(|(name, args)| probe(name, args)).collect())
+}
+
+fn probe(name: &str, version_args: &[&str]) -> Option<ToolRecord> {
+    let path = which::which(name).ok()?;
+    let version = exec::run_cmd_unchecked_timeout(name, version_args, exec::API_TIMEOUT)
+        .ok()
+        .and_then(|out| parse_version(&format!("{}\n{}", out.stdout, out.stderr)));
+    let sha256 = match platform::sha256_file(&path) {
+        Ok(sum) => sum,
+        Err(e) => {
+            warnings::warn(format!("Could not checksum {}: {e:#}", path.display()));
+            return None;
+        }
+    };
+    Some(ToolRecord { name: name.to_string(), path: path.display().to_string(), version, sha256 })
+}
+
+/// First version number in a tool's version output, without a leading `v`
+/// (`Client Version: 4.17.3` -> `4.17.3`, `OpmVersion:"v1.47.0"` -> `1.47.0`).
+fn parse_version(output: &str) -> Option<String> {
+    static RE: OnceLock<regex::Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| regex::Regex::new(r"v?(\d+\.\d+(?:\.\d+)?(?:[-+][0-9A-Za-z.-]+)?)").expect("Invalid regex"));
+    re.captures(output).map(|c| c[1].to_string())
+}
+
+/// How `tools` differ from `pins`: pinned tools that are missing, or whose
+/// version or checksum is not the pinned one.
+pub fn mismatches(tools: &[ToolRecord], pins: &BTreeMap<String, ToolPin>) -> Vec<String> {
+    let mut out = Vec::new();
+    for (name, pin) in pins {
+        let Some(tool) = tools.iter().find(|t| &t.name == name) else {
+            out.push(format!("{} is pinned but not on PATH", name));
+            continue;
+        };
+        if let Some(ref want) = pin.version {
+            let want = want.trim_start_matches('v');
+            if tool.version.as_deref() != Some(want) {
+                out.push(format!("{} is {}, pinned to {}", name, tool.version.as_deref().unwrap_or("an unknown version"), want));
+            }
+        }
+        if let Some(ref want) = pin.sha256 && !tool.sha256.eq_ignore_ascii_case(want) {
+            out.push(format!("{} ({}) has sha256 {}, pinned to {}", name, tool.path, tool.sha256, want));
+        }
+    }
+    out
+}
+
+/// Check the tools on PATH against `config/tools.toml`. Mismatches are warned
+/// about, or fail with --require-pinned-tools.
+pub fn verify() -> Result<()> {
+    let pins = config::load_tool_pins(&config::default_tool_pins_path())?;
+    let required = REQUIRE_PINNED.get().copied().unwrap_or_default();
+    if required && pins.tools.is_empty() {
+        bail!("--require-pinned-tools: no pins in {}", config::default_tool_pins_path().display());
+    }
+    let found = mismatches(inventory(), &pins.tools);
+    if found.is_empty() {
+        return Ok(());
+    }
+    if required {
+        bail!("Tools do not match their pins (--require-pinned-tools):\n  {}", found.join("\n  "));
+    }
+    for m in found {
+        warnings::warn(format!("Tool drift: {}", m));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("Client Version: 4.17.3\nKustomize Version: v5.0.4").as_deref(), Some("4.17.3"));
+        assert_eq!(parse_version("Gauge version: 1.6.3\nCommit Hash: 1a2b3c").as_deref(), Some("1.6.3"));
+        assert_eq!(
+            parse_version(r#"Version: version.Version{OpmVersion:"v1.47.0", GitCommit:"abc"}"#).as_deref(),
+            Some("1.47.0")
+        );
+        assert_eq!(parse_version("buildah version 1.37.5 (image-spec 1.1.0)").as_deref(), Some("1.37.5"));
+        assert_eq!(parse_version("v0.17.1-rc.1").as_deref(), Some("0.17.1-rc.1"));
+        assert_eq!(parse_version("devel"), None);
+    }
+
+    #[test]
+    fn test_mismatches() {
+        let tool = |name: &str, version: Option<&str>, sha256: &str| ToolRecord {
+            name: name.to_string(),
+            path: format!("/usr/bin/{}", name),
+            version: version.map(str::to_string),
+            sha256: sha256.to_string(),
+        };
+        let tools = vec![tool("ko", Some("0.17.1"), "aa"), tool("gauge", None, "bb")];
+        let pin = |version: Option<&str>, sha256: Option<&str>| ToolPin {
+            version: version.map(str::to_string),
+            sha256: sha256.map(str::to_string),
+        };
+
+        let ok = BTreeMap::from([("ko".to_string(), pin(Some("v0.17.1"), Some("AA")))]);
+        assert!(mismatches(&tools, &ok).is_empty());
+
+        let pins = BTreeMap::from([
+            ("ko".to_string(), pin(Some("0.18.0"), Some("cc"))),
+            ("gauge".to_string(), pin(Some("1.6.3"), None)),
+            ("opm".to_string(), pin(None, None)),
+        ]);
+        assert_eq!(
+            mismatches(&tools, &pins),
+            vec![
+                "gauge is an unknown version, pinned to 1.6.3".to_string(),
+                "ko is 0.17.1, pinned to 0.18.0".to_string(),
+                "ko (/usr/bin/ko) has sha256 aa, pinned to cc".to_string(),
+                "opm is pinned but not on PATH".to_string(),
+            ]
+        );
+    }
+}
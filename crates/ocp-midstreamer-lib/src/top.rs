@@ -0,0 +1,160 @@
+//! `streamstress top`: live CPU/memory per pod in the tekton namespaces.
+//!
+//! Polls PodMetrics (via the profile module) and redraws a table with each
+//! pod's current usage, the change since the previous poll, and the peak seen
+//! since `top` started. Independent of test runs; meant for eyeballing a
+//! deployed upstream stack while debugging.
+
+use anyhow::{Result, bail};
+use console::{Style, Term};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use crate::profile::{self, PodUsage};
+
+/// Usage history of one pod.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct PodStats {
+    cpu: u64,
+    memory: u64,
+    cpu_delta: i64,
+    memory_delta: i64,
+    peak_cpu: u64,
+    peak_memory: u64,
+    /// Seen in the latest poll; pods that went away are kept, greyed out
+    live: bool,
+}
+
+/// Fold a poll into the per-pod stats, keyed by `namespace/pod`.
+fn update(stats: &mut BTreeMap<String, PodStats>, usage: &[PodUsage]) {
+    for s in stats.values_mut() {
+        s.live = false;
+    }
+    for u in usage {
+        let entry = stats.entry(format!("{}/{}", u.namespace, u.pod));
+        let known = matches!(entry, std::collections::btree_map::Entry::Occupied(_));
+        let s = entry.or_default();
+        s.cpu_delta = if known { u.cpu_millicores as i64 - s.cpu as i64 } else { 0 };
+        s.memory_delta = if known { u.memory_bytes as i64 - s.memory as i64 } else { 0 };
+        s.cpu = u.cpu_millicores;
+        s.memory = u.memory_bytes;
+        s.peak_cpu = s.peak_cpu.max(u.cpu_millicores);
+        s.peak_memory = s.peak_memory.max(u.memory_bytes);
+        s.live = true;
+    }
+}
+
+fn mib(bytes: i64) -> i64 {
+    bytes / (1024 * 1024)
+}
+
+fn signed(n: i64) -> String {
+    if n > 0 { format!("+{}", n) } else { n.to_string() }
+}
+
+/// Render the table, live pods by CPU descending, then pods that went away.
+fn render(stats: &BTreeMap<String, PodStats>) -> String {
+    let mut rows: Vec<(&String, &PodStats)> = stats.iter().collect();
+    rows.sort_by_key(|(_, s)| (!s.live, std::cmp::Reverse(s.cpu)));
+    let width = rows.iter().map(|(k, _)| k.len()).max().unwrap_or(3).max(3);
+
+    let dim = Style::new().dim();
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:<width$}  {:>7} {:>7} {:>7}  {:>8} {:>8} {:>8}",
+        "POD", "CPU(m)", "Δ", "PEAK", "MEM(Mi)", "Δ", "PEAK"
+    );
+    for (name, s) in rows {
+        let line = format!(
+            "{:<width$}  {:>7} {:>7} {:>7}  {:>8} {:>8} {:>8}",
+            name,
+            s.cpu,
+            signed(s.cpu_delta),
+            s.peak_cpu,
+            mib(s.memory as i64),
+            signed(mib(s.memory_delta)),
+            mib(s.peak_memory as i64)
+        );
+        let _ = writeln!(out, "{}", if s.live { line } else { dim.apply_to(line).to_string() });
+    }
+    let total_cpu: u64 = stats.values().filter(|s| s.live).map(|s| s.cpu).sum();
+    let total_memory: u64 = stats.values().filter(|s| s.live).map(|s| s.memory).sum();
+    let _ = writeln!(
+        out,
+        "{:<width$}  {:>7} {:>7} {:>7}  {:>8}",
+        "TOTAL",
+        total_cpu,
+        "",
+        "",
+        mib(total_memory as i64)
+    );
+    out
+}
+
+/// Poll every `interval` and redraw; stops after `count` polls (0 = until interrupted).
+pub async fn run(client: &kube::Client, namespaces: &[String], interval: Duration, count: u32) -> Result<()> {
+    if !profile::check_metrics_available(client).await? {
+        bail!("PodMetrics are not available on this cluster (is metrics-server running?)");
+    }
+    let term = Term::stdout();
+    let mut stats: BTreeMap<String, PodStats> = BTreeMap::new();
+    let mut polls = 0;
+    loop {
+        let mut usage = Vec::new();
+        for ns in namespaces {
+            usage.extend(profile::collect_pod_usage(client, ns).await?);
+        }
+        update(&mut stats, &usage);
+        polls += 1;
+
+        if term.is_term() {
+            let _ = term.clear_screen();
+        }
+        println!(
+            "streamstress top — {} — every {}s, {}",
+            namespaces.join(", "),
+            interval.as_secs(),
+            crate::timestamp::now_rfc3339()
+        );
+        print!("{}", render(&stats));
+        if !term.is_term() {
+            println!();
+        }
+
+        if count > 0 && polls >= count {
+            return Ok(());
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_and_render() {
+        let usage = |pod: &str, cpu: u64, mem_mib: u64| PodUsage {
+            namespace: "openshift-pipelines".into(),
+            pod: pod.into(),
+            cpu_millicores: cpu,
+            memory_bytes: mem_mib * 1024 * 1024,
+        };
+        let mut stats = BTreeMap::new();
+        update(&mut stats, &[usage("controller", 50, 100), usage("webhook", 10, 40)]);
+        update(&mut stats, &[usage("controller", 30, 120)]);
+
+        let controller = &stats["openshift-pipelines/controller"];
+        assert_eq!((controller.cpu, controller.cpu_delta, controller.peak_cpu), (30, -20, 50));
+        assert_eq!(mib(controller.memory_delta), 20);
+        assert!(!stats["openshift-pipelines/webhook"].live);
+
+        let table = render(&stats);
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(lines[1].contains("openshift-pipelines/controller") && lines[1].contains("-20"));
+        assert!(lines[2].contains("webhook"));
+        assert!(lines[3].starts_with("TOTAL") && lines[3].contains("30"));
+    }
+}
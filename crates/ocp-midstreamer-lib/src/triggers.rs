@@ -0,0 +1,277 @@
+//! Triggers EventListener probe.
+//!
+//! A broken triggers deployment otherwise surfaces as dozens of gauge failures
+//! after a 40-minute suite. Before the tests, a run deploys a minimal
+//! EventListener and TriggerTemplate, sends it an event through the API
+//! server's service proxy, and checks the TaskRun it creates completes. A
+//! failed probe is reported as a synthetic test and the suite is skipped.
+
+use anyhow::{Context, Result, bail};
+use k8s_openapi::api::core::v1::ServiceAccount;
+use k8s_openapi::api::rbac::v1::{ClusterRoleBinding, RoleBinding};
+use kube::api::{Api, ApiResource, DeleteParams, DynamicObject, ListParams, Patch, PatchParams};
+use serde_json::{Value, json};
+use std::time::{Duration, Instant};
+
+use crate::{audit, exec, k8s, results, timestamp, warnings};
+
+/// Spec name of the synthetic test in results.
+pub const SPEC: &str = "Triggers EventListener probe";
+
+/// Namespace the EventListener and the TaskRuns it creates live in.
+const PROBE_NAMESPACE: &str = "streamstress-triggers-probe";
+
+/// Name of the EventListener, TriggerTemplate, ServiceAccount and bindings.
+const PROBE_NAME: &str = "streamstress-probe";
+
+/// ClusterRoles the triggers install provides for EventListener service accounts.
+const EL_ROLE: &str = "tekton-triggers-eventlistener-roles";
+const EL_CLUSTER_ROLE: &str = "tekton-triggers-eventlistener-clusterroles";
+
+/// Budget for each of: EventListener ready, event accepted, TaskRun complete.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(120);
+
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Run the probe and report it as a synthetic test.
+pub fn probe() -> results::TestCaseResult {
+    let started = Instant::now();
+    let outcome = run_probe();
+    match &outcome {
+        Ok(taskrun) => eprintln!("  EventListener created TaskRun {}, which succeeded", taskrun),
+        Err(e) => eprintln!("  Triggers EventListener probe FAILED: {e:#}"),
+    }
+    results::TestCaseResult {
+        spec: SPEC.to_string(),
+        scenario: "Event sent to an EventListener creates a TaskRun that succeeds".to_string(),
+        passed: outcome.is_ok(),
+        duration_secs: started.elapsed().as_secs_f64(),
+        error_message: outcome.err().map(|e| format!("{e:#}")),
+        components: vec!["triggers".to_string()],
+        log_excerpts: Vec::new(),
+    }
+}
+
+fn run_probe() -> Result<String> {
+    let (rt, client) = k8s::create_kube_client()?;
+    k8s::ensure_namespace(&rt, &client, PROBE_NAMESPACE)?;
+    ensure_service_account(&rt, &client)?;
+
+    let pp = PatchParams::apply("streamstress").force();
+    let templates: Api<DynamicObject> = Api::namespaced_with(client.clone(), PROBE_NAMESPACE, &triggers_resource("TriggerTemplate", "triggertemplates"));
+    let patch = Patch::Apply(trigger_template());
+    let result = k8s::block_on_retry(&rt, "apply TriggerTemplate", || templates.patch(PROBE_NAME, &pp, &patch));
+    audit::record_api("apply", "TriggerTemplate", Some(PROBE_NAMESPACE), PROBE_NAME, result.is_ok());
+    result.context("Failed to apply the probe TriggerTemplate (is triggers installed?)")?;
+
+    let listeners: Api<DynamicObject> = Api::namespaced_with(client.clone(), PROBE_NAMESPACE, &triggers_resource("EventListener", "eventlisteners"));
+    let patch = Patch::Apply(event_listener());
+    let result = k8s::block_on_retry(&rt, "apply EventListener", || listeners.patch(PROBE_NAME, &pp, &patch));
+    audit::record_api("apply", "EventListener", Some(PROBE_NAMESPACE), PROBE_NAME, result.is_ok());
+    result.context("Failed to apply the probe EventListener")?;
+    eprintln!("  Applied EventListener {}/{}; waiting for it to become ready", PROBE_NAMESPACE, PROBE_NAME);
+
+    let outcome = wait_for_listener(&rt, &listeners).and_then(|_| {
+        let event_id = send_event()?;
+        eprintln!("  Event {} accepted; waiting for the TaskRun", event_id);
+        wait_for_taskrun(&rt, &client, &event_id)
+    });
+
+    // The EventListener's Deployment is the only costly leftover
+    let dp = DeleteParams::default();
+    let deleted = k8s::block_on_retry(&rt, "delete EventListener", || listeners.delete(PROBE_NAME, &dp));
+    audit::record_api("delete", "EventListener", Some(PROBE_NAMESPACE), PROBE_NAME, deleted.is_ok());
+    if deleted.is_err() {
+        warnings::warn(format!("Could not delete EventListener {}/{}", PROBE_NAMESPACE, PROBE_NAME));
+    }
+    outcome
+}
+
+fn triggers_resource(kind: &str, plural: &str) -> ApiResource {
+    ApiResource {
+        group: "triggers.tekton.dev".into(),
+        version: "v1beta1".into(),
+        api_version: "triggers.tekton.dev/v1beta1".into(),
+        kind: kind.into(),
+        plural: plural.into(),
+    }
+}
+
+/// TriggerTemplate creating a TaskRun that echoes the event's message.
+fn trigger_template() -> Value {
+    json!({
+        "apiVersion": "triggers.tekton.dev/v1beta1",
+        "kind": "TriggerTemplate",
+        "metadata": {"name": PROBE_NAME, "labels": {"app": "streamstress"}},
+        "spec": {
+            "params": [{"name": "message"}],
+            "resourcetemplates": [{
+                "apiVersion": "tekton.dev/v1",
+                "kind": "TaskRun",
+                "metadata": {"generateName": "streamstress-probe-", "labels": {"app": "streamstress"}},
+                "spec": {"taskSpec": {"steps": [{
+                    "name": "echo",
+                    "image": "registry.access.redhat.com/ubi9/ubi-minimal",
+                    "script": "echo \"$(tt.params.message)\""
+                }]}}
+            }]
+        }
+    })
+}
+
+/// EventListener binding the event body's `message` to the template.
+fn event_listener() -> Value {
+    json!({
+        "apiVersion": "triggers.tekton.dev/v1beta1",
+        "kind": "EventListener",
+        "metadata": {"name": PROBE_NAME, "labels": {"app": "streamstress"}},
+        "spec": {
+            "serviceAccountName": PROBE_NAME,
+            "triggers": [{
+                "name": "probe",
+                "bindings": [{"name": "message", "value": "$(body.message)"}],
+                "template": {"ref": PROBE_NAME}
+            }]
+        }
+    })
+}
+
+/// ServiceAccount for the EventListener, bound to the roles the triggers install ships.
+fn ensure_service_account(rt: &tokio::runtime::Runtime, client: &kube::Client) -> Result<()> {
+    let sa: ServiceAccount = serde_json::from_value(json!({
+        "metadata": {"name": PROBE_NAME, "labels": {"app": "streamstress"}}
+    }))?;
+    k8s::create_if_missing(rt, Api::namespaced(client.clone(), PROBE_NAMESPACE), &sa)?;
+
+    let subjects = json!([{"kind": "ServiceAccount", "name": PROBE_NAME, "namespace": PROBE_NAMESPACE}]);
+    let rb: RoleBinding = serde_json::from_value(json!({
+        "metadata": {"name": PROBE_NAME, "labels": {"app": "streamstress"}},
+        "roleRef": {"apiGroup": "rbac.authorization.k8s.io", "kind": "ClusterRole", "name": EL_ROLE},
+        "subjects": subjects
+    }))?;
+    k8s::create_if_missing(rt, Api::namespaced(client.clone(), PROBE_NAMESPACE), &rb)?;
+
+    let crb: ClusterRoleBinding = serde_json::from_value(json!({
+        "metadata": {"name": PROBE_NAMESPACE, "labels": {"app": "streamstress"}},
+        "roleRef": {"apiGroup": "rbac.authorization.k8s.io", "kind": "ClusterRole", "name": EL_CLUSTER_ROLE},
+        "subjects": subjects
+    }))?;
+    k8s::create_if_missing(rt, Api::all(client.clone()), &crb)
+}
+
+/// Poll until the EventListener reports Ready (its Deployment and Service are up).
+fn wait_for_listener(rt: &tokio::runtime::Runtime, api: &Api<DynamicObject>) -> Result<()> {
+    let started = Instant::now();
+    loop {
+        let listener = k8s::block_on_retry(rt, "get EventListener", || api.get(PROBE_NAME))?;
+        let ready = listener
+            .data
+            .pointer("/status/conditions")
+            .and_then(|c| c.as_array())
+            .and_then(|c| c.iter().find(|c| c["type"] == "Ready"));
+        if ready.is_some_and(|c| c["status"] == "True") {
+            return Ok(());
+        }
+        if started.elapsed() > PROBE_TIMEOUT {
+            let reason = ready.and_then(|c| c["message"].as_str()).unwrap_or("no Ready condition");
+            bail!(
+                "EventListener {} not ready within {}s ({}); check the el-{} pods and the tekton-triggers-controller logs",
+                PROBE_NAME,
+                PROBE_TIMEOUT.as_secs(),
+                reason,
+                PROBE_NAME
+            );
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// POST an event to the EventListener's Service through the API server proxy, which
+/// works both from a workstation and from the in-cluster Job; returns the event id.
+fn send_event() -> Result<String> {
+    let body = tempfile::NamedTempFile::new()?;
+    std::fs::write(body.path(), json!({"message": format!("streamstress probe {}", timestamp::now_rfc3339())}).to_string())?;
+    let body_path = body.path().to_string_lossy().to_string();
+    let path = format!("/api/v1/namespaces/{}/services/el-{}:http-listener/proxy/", PROBE_NAMESPACE, PROBE_NAME);
+
+    // The Service can lag behind the Ready condition by a few seconds
+    let started = Instant::now();
+    loop {
+        let result = exec::run_cmd_unchecked_timeout("oc", &["create", "--raw", &path, "-f", &body_path], POLL_INTERVAL * 10)?;
+        if result.exit_code == 0 {
+            return parse_event_id(&result.stdout);
+        }
+        if started.elapsed() > PROBE_TIMEOUT {
+            bail!("EventListener did not accept the event: {}", result.stderr.trim());
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Event id from the EventListener's response to an event.
+fn parse_event_id(response: &str) -> Result<String> {
+    let json: Value = serde_json::from_str(response.trim())
+        .with_context(|| format!("Unexpected EventListener response: {}", response.trim()))?;
+    json["eventID"]
+        .as_str()
+        .map(String::from)
+        .with_context(|| format!("EventListener response has no eventID: {}", response.trim()))
+}
+
+/// Wait for the TaskRun created for `event_id` to succeed; returns its name.
+fn wait_for_taskrun(rt: &tokio::runtime::Runtime, client: &kube::Client, event_id: &str) -> Result<String> {
+    let ar = ApiResource {
+        group: "tekton.dev".into(),
+        version: "v1".into(),
+        api_version: "tekton.dev/v1".into(),
+        kind: "TaskRun".into(),
+        plural: "taskruns".into(),
+    };
+    let api: Api<DynamicObject> = Api::namespaced_with(client.clone(), PROBE_NAMESPACE, &ar);
+    let lp = ListParams::default().labels(&format!("triggers.tekton.dev/triggers-eventid={}", event_id));
+    let started = Instant::now();
+    loop {
+        let runs = k8s::block_on_retry(rt, "list TaskRuns", || api.list(&lp))?;
+        let run = runs.items.first();
+        let succeeded = run
+            .and_then(|r| r.data.pointer("/status/conditions"))
+            .and_then(|c| c.as_array())
+            .and_then(|c| c.iter().find(|c| c["type"] == "Succeeded"));
+        let name = run.and_then(|r| r.metadata.name.clone()).unwrap_or_default();
+        match succeeded.and_then(|c| c["status"].as_str()) {
+            Some("True") => return Ok(name),
+            Some("False") => bail!(
+                "TaskRun {} failed: {}",
+                name,
+                succeeded.and_then(|c| c["message"].as_str()).unwrap_or("unknown reason")
+            ),
+            _ => {}
+        }
+        if started.elapsed() > PROBE_TIMEOUT {
+            if run.is_some() {
+                bail!("TaskRun {} did not complete within {}s", name, PROBE_TIMEOUT.as_secs());
+            }
+            bail!(
+                "EventListener created no TaskRun for event {} within {}s; check the el-{} pod logs",
+                event_id,
+                PROBE_TIMEOUT.as_secs(),
+                PROBE_NAME
+            );
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_event_id() {
+        let response = r#"{"eventListener":"streamstress-probe","namespace":"streamstress-triggers-probe","eventListenerUID":"abc","eventID":"7f3c"}"#;
+        assert_eq!(parse_event_id(response).unwrap(), "7f3c");
+        assert!(parse_event_id(r#"{"eventListener":"x"}"#).is_err());
+        assert!(parse_event_id("<html>503</html>").is_err());
+        assert_eq!(event_listener()["spec"]["triggers"][0]["template"]["ref"], PROBE_NAME);
+    }
+}
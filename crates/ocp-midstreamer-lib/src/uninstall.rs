@@ -0,0 +1,320 @@
+//! `streamstress uninstall`: remove what streamstress put on a cluster.
+//!
+//! Steps run in order and independently, a failing one is warned about and
+//! the rest still run:
+//! 1. operator: IMAGE_ env vars streamstress applied to the operator Deployment
+//!    released, the rest back to the values in its CSV, and InstallerSets
+//!    deleted so the operator re-renders them
+//! 2. tekton-upstream: the image namespace (with its image-puller RoleBinding)
+//! 3. jobs: in-cluster Jobs, their ConfigMaps, ServiceAccount, ClusterRoleBinding,
+//!    minimal ClusterRole, publish, registry and bundle push Secrets and Go cache PVC
+//! 4. konflux: the standalone release-test Pipeline and its PipelineRuns
+//! 5. with `--operator`: the Subscription and TektonConfig auto-setup created
+//!
+//! On a terminal, everything it will delete or revert is listed first and
+//! needs a yes (`--yes` skips the prompt).
+
+use anyhow::{Context, Result};
+use k8s_openapi::api::apps::v1::Deployment;
+use k8s_openapi::api::batch::v1::Job;
+use k8s_openapi::api::core::v1::{ConfigMap, EnvVar, Namespace, PersistentVolumeClaim, Secret, ServiceAccount};
+use k8s_openapi::api::rbac::v1::{ClusterRole, ClusterRoleBinding};
+use kube::api::{Api, ApiResource, DeleteParams, DynamicObject, ListParams, PostParams, PropagationPolicy};
+use kube::Client;
+use tokio::runtime::Runtime;
+
+use crate::deploy::operator;
+use crate::{audit, bundle, confirm, incluster, k8s, progress, rbac, registry, setup, warnings};
+
+/// Namespace the in-cluster Jobs run in.
+const JOB_NAMESPACE: &str = "openshift-pipelines";
+
+/// Name suffix `konflux --trigger` gives the standalone copy of the release-test pipeline.
+const STANDALONE_SUFFIX: &str = "-standalone";
+
+/// Name prefix of the PipelineRuns `konflux --trigger` creates.
+const PIPELINERUN_PREFIX: &str = "streamstress-test-";
+
+/// Remove the streamstress footprint; `pipeline_namespace` is where `konflux --trigger`
+/// ran, `remove_operator` also removes what auto-setup installed.
+/// Returns whether every step succeeded.
+pub fn run_uninstall(pipeline_namespace: &str, remove_operator: bool) -> Result<bool> {
+    let (rt, client) = k8s::create_kube_client()?;
+    if confirm::required() {
+        let changes = planned_changes(&rt, &client, pipeline_namespace, remove_operator)?;
+        confirm::confirm("uninstall streamstress", &changes)?;
+    }
+
+    type Step<'a> = (&'static str, Box<dyn Fn() -> Result<()> + 'a>);
+    let mut steps: Vec<Step> = vec![
+        ("Reverting operator images", Box::new(|| revert_operator_images(&rt, &client))),
+        ("Deleting image namespace", Box::new(|| delete_image_namespace(&rt, &client))),
+        ("Deleting in-cluster Job resources", Box::new(|| delete_job_resources(&rt, &client))),
+        ("Deleting Konflux standalone pipeline", Box::new(|| delete_konflux_pipeline(&rt, &client, pipeline_namespace))),
+    ];
+    if remove_operator {
+        steps.push((
+            "Removing operator Subscription and TektonConfig",
+            Box::new(|| setup::remove_setup_resources(&rt, &client)),
+        ));
+    }
+
+    let mut failed = 0;
+    for (label, step) in &steps {
+        let pb = progress::stage_spinner(label);
+        match step() {
+            Ok(()) => progress::finish_spinner(&pb, true),
+            Err(e) => {
+                progress::finish_spinner(&pb, false);
+                warnings::warn(format!("{label}: {e:#}"));
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        eprintln!("\nUninstall completed with {failed} failed step(s).");
+    } else {
+        eprintln!("\nUninstall completed successfully.");
+    }
+    Ok(failed == 0)
+}
+
+/// Everything `run_uninstall` deletes or reverts, one line each (for `confirm`).
+/// An operator that cannot be found is left out, as its step will only warn.
+pub fn planned_changes(rt: &Runtime, client: &Client, pipeline_namespace: &str, remove_operator: bool) -> Result<Vec<String>> {
+    let mut changes = Vec::new();
+    if let Ok((namespace, name)) = operator::find_operator_deployment(rt, client) {
+        changes.push(format!("restore the IMAGE_ env vars of Deployment {}/{} from its CSV", namespace, name));
+        let sets_api = operator::installer_set_api(client);
+        let lp = ListParams::default();
+        let sets = k8s::block_on_retry(rt, "list TektonInstallerSets", || sets_api.list(&lp))
+            .context("Failed to list TektonInstallerSets")?;
+        changes.extend(
+            sets.items
+                .iter()
+                .filter_map(|s| s.metadata.name.as_deref())
+                .map(|name| format!("delete TektonInstallerSet {}", name)),
+        );
+    }
+    changes.push(format!("delete Namespace {} (with its image-puller RoleBinding)", registry::DEFAULT_NAMESPACE));
+    changes.push(format!(
+        "delete streamstress Jobs, ConfigMaps, ServiceAccount, Secrets and Go cache PVC in {}, and ClusterRoleBinding {} and ClusterRole {}",
+        JOB_NAMESPACE, rbac::CLUSTER_ROLE_BINDING, rbac::MINIMAL_CLUSTER_ROLE
+    ));
+    changes.push(format!(
+        "delete {}* PipelineRuns and *{} Pipelines in {}",
+        PIPELINERUN_PREFIX, STANDALONE_SUFFIX, pipeline_namespace
+    ));
+    if remove_operator {
+        changes.extend(setup::uninstall_changes(rt, client)?);
+    }
+    Ok(changes)
+}
+
+/// The operator container's env with every IMAGE_ var as in `original` (the CSV):
+/// changed ones restored, added ones dropped, removed ones put back.
+fn restored_env(current: &[EnvVar], original: &[EnvVar]) -> Vec<EnvVar> {
+    let is_image = |e: &&EnvVar| e.name.starts_with("IMAGE_");
+    let mut env: Vec<EnvVar> = current
+        .iter()
+        .filter_map(|e| {
+            if !e.name.starts_with("IMAGE_") {
+                return Some(e.clone());
+            }
+            original.iter().find(|o| o.name == e.name).cloned()
+        })
+        .collect();
+    for o in original.iter().filter(is_image) {
+        if !env.iter().any(|e| e.name == o.name) {
+            env.push(o.clone());
+        }
+    }
+    env
+}
+
+/// Env of the operator container as the CSV that owns `deployment_name` declares it.
+fn csv_operator_env(rt: &Runtime, client: &Client, namespace: &str, deployment_name: &str) -> Result<Vec<EnvVar>> {
+    let ar = ApiResource {
+        group: "operators.coreos.com".into(),
+        version: "v1alpha1".into(),
+        api_version: "operators.coreos.com/v1alpha1".into(),
+        kind: "ClusterServiceVersion".into(),
+        plural: "clusterserviceversions".into(),
+    };
+    let api: Api<DynamicObject> = Api::namespaced_with(client.clone(), namespace, &ar);
+    let lp = ListParams::default();
+    let csvs = k8s::block_on_retry(rt, "list ClusterServiceVersions", || api.list(&lp))
+        .context("Failed to list ClusterServiceVersions")?;
+    let deployment = csvs
+        .items
+        .iter()
+        .filter_map(|c| c.data["spec"]["install"]["spec"]["deployments"].as_array())
+        .flatten()
+        .find(|d| d["name"] == deployment_name)
+        .with_context(|| format!("No CSV in {namespace} declares Deployment {deployment_name}"))?;
+    let container = deployment["spec"]["template"]["spec"]["containers"]
+        .as_array()
+        .and_then(|c| c.iter().find(|c| c["name"] == operator::LIFECYCLE_CONTAINER))
+        .with_context(|| format!("CSV Deployment {deployment_name} has no container {}", operator::LIFECYCLE_CONTAINER))?;
+    serde_json::from_value(container.get("env").cloned().unwrap_or_default()).context("Failed to parse CSV container env")
+}
+
+fn revert_operator_images(rt: &Runtime, client: &Client) -> Result<()> {
+    let (namespace, name) = operator::find_operator_deployment(rt, client)?;
+    let original = csv_operator_env(rt, client, &namespace, &name)?;
+
+    // Drop what streamstress applied; vars it took over from OLM are restored below
+    let released = operator::release_image_env(rt, client, &namespace, &name)?;
+    if !released.is_empty() {
+        eprintln!("  Released {} IMAGE_ env var(s) applied by {}.", released.len(), operator::FIELD_MANAGER);
+    }
+
+    let api: Api<Deployment> = Api::namespaced(client.clone(), &namespace);
+    let mut dep = k8s::block_on_retry(rt, "get operator Deployment", || api.get(&name))
+        .with_context(|| format!("Failed to get Deployment {}/{}", namespace, name))?;
+    let container = dep
+        .spec
+        .as_mut()
+        .and_then(|s| s.template.spec.as_mut())
+        .and_then(|s| s.containers.iter_mut().find(|c| c.name == operator::LIFECYCLE_CONTAINER))
+        .with_context(|| format!("Container '{}' not found in Deployment", operator::LIFECYCLE_CONTAINER))?;
+    let current = container.env.clone().unwrap_or_default();
+    let restored = restored_env(&current, &original);
+    if restored == current {
+        eprintln!("  Operator images already match the CSV.");
+        return Ok(());
+    }
+    container.env = Some(restored);
+
+    let pp = PostParams::default();
+    let result = k8s::block_on_retry(rt, "replace operator Deployment", || api.replace(&name, &pp, &dep));
+    audit::record_api("replace", "Deployment", Some(&namespace), &name, result.is_ok());
+    result.with_context(|| format!("Failed to restore IMAGE_ env vars of Deployment {}/{}", namespace, name))?;
+    eprintln!("  Restored the operator's IMAGE_ env vars from its CSV.");
+
+    // The operator renders InstallerSets from the env vars; recreate them all
+    let sets_api = operator::installer_set_api(client);
+    let lp = ListParams::default();
+    let sets = k8s::block_on_retry(rt, "list TektonInstallerSets", || sets_api.list(&lp))
+        .context("Failed to list TektonInstallerSets")?;
+    for name in sets.items.iter().filter_map(|s| s.metadata.name.as_deref()) {
+        let dp = DeleteParams::default();
+        let result = k8s::block_on_retry(rt, "delete TektonInstallerSet", || sets_api.delete(name, &dp));
+        audit::record_api("delete", "TektonInstallerSet", None, name, result.is_ok());
+        if let Err(e) = result {
+            warnings::warn(format!("Failed to delete InstallerSet {}: {}", name, e));
+        }
+    }
+    eprintln!("  Deleted {} InstallerSet(s) for the operator to re-render.", sets.items.len());
+    Ok(())
+}
+
+fn delete_image_namespace(rt: &Runtime, client: &Client) -> Result<()> {
+    let api: Api<Namespace> = Api::all(client.clone());
+    delete_named(rt, &api, "Namespace", None, registry::DEFAULT_NAMESPACE)
+}
+
+fn delete_job_resources(rt: &Runtime, client: &Client) -> Result<()> {
+    let ns = JOB_NAMESPACE;
+    let lp = ListParams::default().labels("app=streamstress");
+
+    let jobs: Api<Job> = Api::namespaced(client.clone(), ns);
+    let list = k8s::block_on_retry(rt, "list Jobs", || jobs.list(&lp)).context("Failed to list streamstress Jobs")?;
+    // Background propagation also removes the Jobs' pods
+    let dp = DeleteParams { propagation_policy: Some(PropagationPolicy::Background), ..DeleteParams::default() };
+    for name in list.items.iter().filter_map(|j| j.metadata.name.as_deref()) {
+        let result = k8s::block_on_retry(rt, "delete Job", || jobs.delete(name, &dp));
+        audit::record_api("delete", "Job", Some(ns), name, result.is_ok());
+        result.with_context(|| format!("Failed to delete Job {name}"))?;
+    }
+    eprintln!("  Deleted {} Job(s).", list.items.len());
+
+    let config_maps: Api<ConfigMap> = Api::namespaced(client.clone(), ns);
+    let list = k8s::block_on_retry(rt, "list ConfigMaps", || config_maps.list(&lp)).context("Failed to list streamstress ConfigMaps")?;
+    for name in list.items.iter().filter_map(|c| c.metadata.name.as_deref()) {
+        delete_named(rt, &config_maps, "ConfigMap", Some(ns), name)?;
+    }
+
+    delete_named(rt, &Api::<ServiceAccount>::namespaced(client.clone(), ns), "ServiceAccount", Some(ns), rbac::SERVICE_ACCOUNT)?;
+    delete_named(rt, &Api::<Secret>::namespaced(client.clone(), ns), "Secret", Some(ns), incluster::PUBLISH_SECRET)?;
+    delete_named(rt, &Api::<Secret>::namespaced(client.clone(), ns), "Secret", Some(ns), incluster::SMTP_SECRET)?;
+    delete_named(rt, &Api::<Secret>::namespaced(client.clone(), ns), "Secret", Some(ns), incluster::REGISTRY_AUTH_SECRET)?;
+    delete_named(rt, &Api::<Secret>::namespaced(client.clone(), ns), "Secret", Some(ns), bundle::BUNDLE_PUSH_SECRET)?;
+    delete_named(rt, &Api::<PersistentVolumeClaim>::namespaced(client.clone(), ns), "PersistentVolumeClaim", Some(ns), incluster::GO_CACHE_PVC)?;
+    delete_named(rt, &Api::<ClusterRoleBinding>::all(client.clone()), "ClusterRoleBinding", None, rbac::CLUSTER_ROLE_BINDING)?;
+    delete_named(rt, &Api::<ClusterRole>::all(client.clone()), "ClusterRole", None, rbac::MINIMAL_CLUSTER_ROLE)
+}
+
+fn delete_konflux_pipeline(rt: &Runtime, client: &Client, namespace: &str) -> Result<()> {
+    let resource = |kind: &str, plural: &str| ApiResource {
+        group: "tekton.dev".into(),
+        version: "v1".into(),
+        api_version: "tekton.dev/v1".into(),
+        kind: kind.into(),
+        plural: plural.into(),
+    };
+    let lp = ListParams::default();
+    for (kind, plural, matches) in [
+        ("PipelineRun", "pipelineruns", (|n: &str| n.starts_with(PIPELINERUN_PREFIX)) as fn(&str) -> bool),
+        ("Pipeline", "pipelines", |n: &str| n.ends_with(STANDALONE_SUFFIX)),
+    ] {
+        let api: Api<DynamicObject> = Api::namespaced_with(client.clone(), namespace, &resource(kind, plural));
+        let list = match k8s::block_on_retry(rt, &format!("list {kind}s"), || api.list(&lp)) {
+            Ok(list) => list,
+            // Tekton CRDs not installed
+            Err(kube::Error::Api(resp)) if resp.code == 404 => continue,
+            Err(e) => return Err(e).with_context(|| format!("Failed to list {kind}s in {namespace}")),
+        };
+        for name in list.items.iter().filter_map(|o| o.metadata.name.as_deref()).filter(|n| matches(n)) {
+            delete_named(rt, &api, kind, Some(namespace), name)?;
+        }
+    }
+    Ok(())
+}
+
+/// Delete `name`, treating an already absent object as done.
+fn delete_named<K>(rt: &Runtime, api: &Api<K>, kind: &str, namespace: Option<&str>, name: &str) -> Result<()>
+where
+    K: kube::Resource + Clone + serde::de::DeserializeOwned + std::fmt::Debug,
+{
+    let dp = DeleteParams::default();
+    match k8s::block_on_retry(rt, &format!("delete {kind}"), || api.delete(name, &dp)) {
+        Ok(_) => {
+            audit::record_api("delete", kind, namespace, name, true);
+            eprintln!("  Deleted {kind} {name}.");
+            Ok(())
+        }
+        Err(kube::Error::Api(resp)) if resp.code == 404 => Ok(()),
+        Err(e) => {
+            audit::record_api("delete", kind, namespace, name, false);
+            Err(e).with_context(|| format!("Failed to delete {kind} {name}"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restored_env() {
+        let var = |name: &str, value: &str| EnvVar { name: name.into(), value: Some(value.into()), value_from: None };
+        let original = vec![
+            var("IMAGE_PIPELINES_CONTROLLER", "registry.redhat.io/pipelines-controller@sha256:aaa"),
+            var("IMAGE_TRIGGERS_WEBHOOK", "registry.redhat.io/triggers-webhook@sha256:bbb"),
+            var("OPERATOR_NAME", "openshift-pipelines-operator"),
+        ];
+        let current = vec![
+            var("IMAGE_PIPELINES_CONTROLLER", "image-registry.openshift-image-registry.svc:5000/tekton-upstream/controller:latest"),
+            var("OPERATOR_NAME", "openshift-pipelines-operator"),
+            var("IMAGE_PIPELINES_NEW_THING", "image-registry.openshift-image-registry.svc:5000/tekton-upstream/new:latest"),
+            var("KUBERNETES_MIN_VERSION", "v1.0.0"),
+        ];
+        let restored = restored_env(&current, &original);
+        let names: Vec<&str> = restored.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["IMAGE_PIPELINES_CONTROLLER", "OPERATOR_NAME", "KUBERNETES_MIN_VERSION", "IMAGE_TRIGGERS_WEBHOOK"]);
+        assert_eq!(restored[0], original[0]);
+        assert_eq!(restored_env(&original, &original), original);
+    }
+}
@@ -0,0 +1,108 @@
+//! Deduplicated warning output.
+//!
+//! Long runs hit the same problem over and over (a failing metrics poll, a
+//! flaky API call), which used to print one WARNING line per occurrence. A
+//! warning is now printed the first time it is seen and then only as
+//! "…repeated N times" at 10, 100, 1000, … occurrences; every distinct warning
+//! and its count is listed in the summary at the end of a run and recorded in
+//! `results/metadata.json`.
+
+use serde::Serialize;
+use std::fmt::Display;
+use std::sync::Mutex;
+
+/// A distinct warning and how often it was emitted.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WarningCount {
+    pub message: String,
+    pub count: u64,
+}
+
+/// Warnings in order of first occurrence.
+static WARNINGS: Mutex<Vec<WarningCount>> = Mutex::new(Vec::new());
+
+/// Print `message` as a WARNING unless it was printed before; repeats are
+/// counted and reported at powers of ten.
+pub fn warn(message: impl Display) {
+    let message = message.to_string();
+    let count = {
+        let mut warnings = WARNINGS.lock().unwrap_or_else(|e| e.into_inner());
+        record(&mut warnings, &message)
+    };
+    if let Some(line) = line_for(&message, count) {
+        eprintln!("{}", line);
+    }
+}
+
+/// Count `message` and return its number of occurrences so far.
+fn record(warnings: &mut Vec<WarningCount>, message: &str) -> u64 {
+    match warnings.iter_mut().find(|w| w.message == message) {
+        Some(w) => {
+            w.count += 1;
+            w.count
+        }
+        None => {
+            warnings.push(WarningCount { message: message.to_string(), count: 1 });
+            1
+        }
+    }
+}
+
+/// The line to print for the `count`th occurrence of `message`, if any.
+fn line_for(message: &str, count: u64) -> Option<String> {
+    if count == 1 {
+        Some(format!("WARNING: {}", message))
+    } else if count >= 10 && is_power_of_ten(count) {
+        Some(format!("WARNING: {} (…repeated {} times)", message, count))
+    } else {
+        None
+    }
+}
+
+fn is_power_of_ten(n: u64) -> bool {
+    n > 0 && 10u64.pow(n.ilog10()) == n
+}
+
+/// Every distinct warning emitted so far, in order of first occurrence.
+pub fn summary() -> Vec<WarningCount> {
+    WARNINGS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Print the warnings summary section; nothing if there were no warnings.
+pub fn print_summary() {
+    let warnings = summary();
+    if warnings.is_empty() {
+        return;
+    }
+    let total: u64 = warnings.iter().map(|w| w.count).sum();
+    eprintln!("\n=== Warnings ({} distinct, {} total) ===", warnings.len(), total);
+    for w in &warnings {
+        if w.count > 1 {
+            eprintln!("  {} (x{})", w.message, w.count);
+        } else {
+            eprintln!("  {}", w.message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_and_rate_limit() {
+        let mut warnings = Vec::new();
+        let printed: Vec<u64> = (0..150)
+            .map(|_| record(&mut warnings, "metrics poll failed"))
+            .filter(|&n| line_for("metrics poll failed", n).is_some())
+            .collect();
+        assert_eq!(printed, [1, 10, 100]);
+        assert_eq!(line_for("x", 10).unwrap(), "WARNING: x (…repeated 10 times)");
+
+        record(&mut warnings, "other");
+        assert_eq!(warnings, [
+            WarningCount { message: "metrics poll failed".to_string(), count: 150 },
+            WarningCount { message: "other".to_string(), count: 1 },
+        ]);
+    }
+}
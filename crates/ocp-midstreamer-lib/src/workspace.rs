@@ -0,0 +1,193 @@
+//! Per-run scratch workspace for clones, build contexts and other temp files.
+//!
+//! Every process gets its own root under the system temp directory
+//! (`streamstress-ws-<pid>-<nanos>`), so concurrent runs never share a path.
+//! Modules take scoped directories from it with `scoped`, removed when dropped;
+//! the root goes at exit (`cleanup`). Roots left behind by a crashed or killed
+//! run are swept when the next run starts. With `--keep-workdir` nothing is
+//! removed and the root is printed at exit, for debugging a build or clone;
+//! a `.keep` marker in it exempts it from the sweep until it is deleted by hand.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// Prefix of workspace roots in the system temp directory.
+const ROOT_PREFIX: &str = "streamstress-ws-";
+
+/// Marker file in a root kept with `--keep-workdir`; such roots are never swept.
+const KEEP_MARKER: &str = ".keep";
+
+/// Age after which a root is swept where process liveness cannot be checked (no /proc).
+const STALE_AGE: Duration = Duration::from_secs(2 * 24 * 3600);
+
+static ROOT: Mutex<Option<PathBuf>> = Mutex::new(None);
+static KEEP: AtomicBool = AtomicBool::new(false);
+static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Set whether scratch directories are kept (`--keep-workdir`). Call once at startup.
+pub fn init(keep: bool) {
+    KEEP.store(keep, Ordering::Relaxed);
+}
+
+pub fn keep() -> bool {
+    KEEP.load(Ordering::Relaxed)
+}
+
+/// A directory in the workspace, removed with its content when dropped
+/// (unless `--keep-workdir`).
+#[derive(Debug)]
+pub struct ScopedDir {
+    path: PathBuf,
+}
+
+impl ScopedDir {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for ScopedDir {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ScopedDir {
+    fn drop(&mut self) {
+        if !keep() {
+            let _ = std::fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+/// Create a new directory in this run's workspace, named after `name`
+/// (e.g. `pipeline-clone-3`) so a kept workspace is easy to find one's way in.
+pub fn scoped(name: &str) -> Result<ScopedDir> {
+    let path = root()?.join(format!("{}-{}", name, NEXT_ID.fetch_add(1, Ordering::Relaxed)));
+    std::fs::create_dir_all(&path).with_context(|| format!("Failed to create {}", path.display()))?;
+    Ok(ScopedDir { path })
+}
+
+/// This run's workspace root, created (after sweeping stale roots) on first use.
+fn root() -> Result<PathBuf> {
+    let mut root = ROOT.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(ref path) = *root {
+        return Ok(path.clone());
+    }
+    let temp = std::env::temp_dir();
+    sweep_stale(&temp);
+    let nanos = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default();
+    let path = temp.join(format!("{}{}-{}", ROOT_PREFIX, std::process::id(), nanos));
+    std::fs::create_dir(&path).with_context(|| format!("Failed to create workspace {}", path.display()))?;
+    *root = Some(path.clone());
+    Ok(path)
+}
+
+/// Remove this run's workspace, or print where it was kept. Call before exiting.
+pub fn cleanup() {
+    let root = ROOT.lock().unwrap_or_else(|e| e.into_inner()).take();
+    let Some(root) = root else {
+        return;
+    };
+    if keep() {
+        if let Err(e) = std::fs::write(root.join(KEEP_MARKER), "") {
+            crate::warnings::warn(format!("Failed to mark {} as kept: {}", root.display(), e));
+        }
+        eprintln!("Kept workspace {}", root.display());
+    } else {
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
+
+/// Remove workspace roots in `temp` whose process is gone, except kept ones.
+fn sweep_stale(temp: &Path) {
+    let Ok(entries) = std::fs::read_dir(temp) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some(pid) = root_pid(&name) else {
+            continue;
+        };
+        if entry.path().join(KEEP_MARKER).exists() {
+            continue;
+        }
+        let age = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.elapsed().ok())
+            .unwrap_or_default();
+        if is_stale(pid, age) {
+            let _ = std::fs::remove_dir_all(entry.path());
+        }
+    }
+}
+
+/// Pid of the process owning a workspace root named `name`.
+fn root_pid(name: &str) -> Option<u32> {
+    name.strip_prefix(ROOT_PREFIX)?.split('-').next()?.parse().ok()
+}
+
+fn is_stale(pid: u32, age: Duration) -> bool {
+    if pid == std::process::id() {
+        return false;
+    }
+    let proc = Path::new("/proc");
+    if proc.is_dir() {
+        !proc.join(pid.to_string()).exists()
+    } else {
+        age > STALE_AGE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_pid() {
+        assert_eq!(root_pid("streamstress-ws-4242-1735689600000000000"), Some(4242));
+        assert_eq!(root_pid("streamstress-perf"), None);
+        assert_eq!(root_pid("streamstress-ws-x-1"), None);
+    }
+
+    #[test]
+    fn test_sweep_spares_kept_roots() {
+        let temp = tempfile::tempdir().unwrap();
+        // pid 0 is never a user process, so both roots look abandoned
+        let stale = temp.path().join(format!("{}0-1", ROOT_PREFIX));
+        let kept = temp.path().join(format!("{}0-2", ROOT_PREFIX));
+        std::fs::create_dir_all(stale.join("pipeline-clone-1")).unwrap();
+        std::fs::create_dir_all(kept.join("pipeline-clone-1")).unwrap();
+        std::fs::write(kept.join(KEEP_MARKER), "").unwrap();
+        backdate(&stale);
+        backdate(&kept);
+
+        sweep_stale(temp.path());
+        assert!(!stale.exists());
+        assert!(kept.join("pipeline-clone-1").exists());
+    }
+
+    /// Backdate `dir` past STALE_AGE, for hosts without /proc.
+    fn backdate(dir: &Path) {
+        let old = SystemTime::now() - STALE_AGE * 2;
+        std::fs::File::open(dir).unwrap().set_modified(old).unwrap();
+    }
+
+    #[test]
+    fn test_scoped_dirs_are_distinct_and_removed() {
+        let a = scoped("clone").unwrap();
+        let b = scoped("clone").unwrap();
+        assert_ne!(a.path(), b.path());
+        assert!(a.path().starts_with(root().unwrap()));
+        let path = a.path().to_path_buf();
+        std::fs::write(path.join("file"), "x").unwrap();
+        drop(a);
+        assert!(!path.exists());
+        assert!(!is_stale(std::process::id(), STALE_AGE * 2));
+    }
+}
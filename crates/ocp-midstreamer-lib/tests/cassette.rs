@@ -0,0 +1,44 @@
+//! Replaying a recorded cassette (`--replay`). The cassette is process-wide,
+//! so this binary replays a single cassette in one test.
+
+use ocp_midstreamer_lib::{cassette, exec, k8s, setup};
+use serde_json::json;
+
+#[test]
+fn test_replay() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = json!({
+        "apiVersion": "operator.tekton.dev/v1alpha1",
+        "kind": "TektonConfig",
+        "metadata": {"name": "config"},
+        "spec": {"profile": "all"}
+    });
+    let api = [
+        json!({"method": "GET", "uri": "/apis/operator.tekton.dev/v1alpha1/tektonconfigs/config", "status": 200, "response": config.to_string()}),
+    ];
+    let commands = [
+        json!({"program": "oc", "args": ["whoami"], "exit_code": 0, "stdout": "kube:admin\n", "stderr": ""}),
+        json!({"program": "oc", "args": ["whoami"], "exit_code": 1, "stdout": "", "stderr": "Unauthorized"}),
+    ];
+    let lines = |entries: &[serde_json::Value]| entries.iter().map(|e| format!("{}\n", e)).collect::<String>();
+    std::fs::write(dir.path().join("api.jsonl"), lines(&api)).unwrap();
+    std::fs::write(dir.path().join("commands.jsonl"), lines(&commands)).unwrap();
+
+    cassette::init(cassette::Mode::Replay, dir.path()).unwrap();
+    assert!(cassette::replaying());
+
+    // Recorded runs of the same command are returned in order
+    assert_eq!(exec::run_cmd("oc", &["whoami"]).unwrap().stdout, "kube:admin\n");
+    let err = exec::run_cmd("oc", &["whoami"]).unwrap_err();
+    assert!(err.to_string().contains("Unauthorized"), "{err:#}");
+    let err = exec::run_cmd("oc", &["whoami"]).unwrap_err();
+    assert!(err.to_string().contains("not in the cassette"), "{err:#}");
+
+    let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    let client = rt.block_on(k8s::client()).unwrap();
+    // The TektonConfig exists in the recording, so nothing is created
+    setup::ensure_tektonconfig(&rt, &client).unwrap();
+    // Requests missing from the cassette fail without being retried
+    let err = setup::ensure_namespace_rbac(&rt, &client).unwrap_err();
+    assert!(format!("{err:#}").contains("is not in the cassette"), "{err:#}");
+}
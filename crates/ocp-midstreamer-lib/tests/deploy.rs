@@ -0,0 +1,225 @@
+//! Operator Deployment env applies and TektonInstallerSet deletion against the
+//! fake API server.
+
+mod fakecluster;
+
+use fakecluster::FakeCluster;
+use ocp_midstreamer_lib::deploy::operator;
+use serde_json::{Value, json};
+
+const DEPLOYMENTS: &str = "/apis/apps/v1/namespaces/openshift-operators/deployments";
+const OPERATOR: &str = "/apis/apps/v1/namespaces/openshift-operators/deployments/openshift-pipelines-operator";
+const INSTALLER_SETS: &str = "/apis/operator.tekton.dev/v1alpha1/tektoninstallersets";
+
+fn operator_deployment(env: Value) -> Value {
+    json!({
+        "apiVersion": "apps/v1",
+        "kind": "Deployment",
+        "metadata": {"name": "openshift-pipelines-operator", "namespace": "openshift-operators"},
+        "spec": {
+            "selector": {"matchLabels": {"app": "openshift-pipelines-operator"}},
+            "template": {
+                "metadata": {"labels": {"app": "openshift-pipelines-operator"}},
+                "spec": {
+                    "containers": [
+                        {"name": "openshift-pipelines-operator", "image": "operator:1"},
+                        {"name": operator::LIFECYCLE_CONTAINER, "image": "lifecycle:1", "env": env}
+                    ]
+                }
+            }
+        }
+    })
+}
+
+fn lifecycle_env(cluster: &FakeCluster) -> Value {
+    let dep = cluster.get(DEPLOYMENTS, "openshift-pipelines-operator").unwrap();
+    dep["spec"]["template"]["spec"]["containers"][1]["env"].clone()
+}
+
+fn mappings(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+#[test]
+fn test_find_operator_deployment() {
+    let cluster = FakeCluster::new();
+    cluster.insert(DEPLOYMENTS, operator_deployment(json!([])));
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    let (ns, name) = operator::find_operator_deployment(&rt, &client).unwrap();
+    assert_eq!((ns.as_str(), name.as_str()), ("openshift-operators", "openshift-pipelines-operator"));
+}
+
+#[test]
+fn test_patch_operator_deployment_env_merges() {
+    let cluster = FakeCluster::new();
+    cluster.insert(
+        DEPLOYMENTS,
+        operator_deployment(json!([
+            {"name": "IMAGE_PIPELINES_CONTROLLER", "value": "registry.redhat.io/controller:1.20"},
+            {"name": "IMAGE_PIPELINES_WEBHOOK", "valueFrom": {"configMapKeyRef": {"name": "images", "key": "webhook"}}},
+            {"name": "AUTOINSTALL_COMPONENTS", "value": "true"}
+        ])),
+    );
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    let upstream = mappings(&[
+        ("IMAGE_PIPELINES_CONTROLLER", "tekton-upstream/controller:abc"),
+        ("IMAGE_PIPELINES_WEBHOOK", "tekton-upstream/webhook:abc"),
+        ("IMAGE_PIPELINES_EVENTS", "tekton-upstream/events:abc"),
+    ]);
+    operator::patch_operator_deployment_env(&rt, &client, "openshift-operators", "openshift-pipelines-operator", &upstream)
+        .unwrap();
+
+    // Existing keys updated in place (valueFrom dropped), others kept, new keys appended
+    assert_eq!(
+        lifecycle_env(&cluster),
+        json!([
+            {"name": "IMAGE_PIPELINES_CONTROLLER", "value": "tekton-upstream/controller:abc"},
+            {"name": "IMAGE_PIPELINES_WEBHOOK", "value": "tekton-upstream/webhook:abc"},
+            {"name": "AUTOINSTALL_COMPONENTS", "value": "true"},
+            {"name": "IMAGE_PIPELINES_EVENTS", "value": "tekton-upstream/events:abc"}
+        ])
+    );
+    assert_eq!(
+        operator::read_operator_image_env(&rt, &client, "openshift-operators", "openshift-pipelines-operator").unwrap(),
+        upstream
+    );
+}
+
+#[test]
+fn test_patch_operator_deployment_env_keeps_earlier_vars() {
+    let cluster = FakeCluster::new();
+    cluster.insert(DEPLOYMENTS, operator_deployment(json!([{"name": "AUTOINSTALL_COMPONENTS", "value": "true"}])));
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    let pipeline = mappings(&[("IMAGE_PIPELINES_CONTROLLER", "tekton-upstream/controller:abc")]);
+    let triggers = mappings(&[("IMAGE_TRIGGERS_CONTROLLER", "tekton-upstream/triggers:abc")]);
+    for m in [&pipeline, &triggers] {
+        operator::patch_operator_deployment_env(&rt, &client, "openshift-operators", "openshift-pipelines-operator", m)
+            .unwrap();
+    }
+
+    // The second apply carries the first one's var, so the API server keeps it
+    let applies: Vec<_> = cluster.requests().into_iter().filter(|r| r.method == "PATCH").collect();
+    assert_eq!(applies.len(), 2);
+    assert!(applies[1].query.contains(&format!("fieldManager={}", operator::FIELD_MANAGER)));
+    assert_eq!(
+        applies[1].body.as_ref().unwrap()["spec"]["template"]["spec"]["containers"][0]["env"],
+        json!([
+            {"name": "IMAGE_PIPELINES_CONTROLLER", "value": "tekton-upstream/controller:abc"},
+            {"name": "IMAGE_TRIGGERS_CONTROLLER", "value": "tekton-upstream/triggers:abc"}
+        ])
+    );
+    let dep = serde_json::from_value(cluster.get(DEPLOYMENTS, "openshift-pipelines-operator").unwrap()).unwrap();
+    assert_eq!(
+        operator::owned_env(&dep, operator::FIELD_MANAGER),
+        vec!["IMAGE_PIPELINES_CONTROLLER", "IMAGE_TRIGGERS_CONTROLLER"]
+    );
+}
+
+#[test]
+fn test_patch_operator_deployment_env_forces_conflicts() {
+    let cluster = FakeCluster::new();
+    cluster.insert(DEPLOYMENTS, operator_deployment(json!([])));
+    cluster.fail("PATCH", OPERATOR, 409, 1);
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    let upstream = mappings(&[("IMAGE_TRIGGERS_CONTROLLER", "tekton-upstream/triggers:abc")]);
+    operator::patch_operator_deployment_env(&rt, &client, "openshift-operators", "openshift-pipelines-operator", &upstream)
+        .unwrap();
+
+    // Fields owned by another manager are taken over with a forced apply
+    let applies: Vec<_> = cluster.requests().into_iter().filter(|r| r.method == "PATCH").collect();
+    assert_eq!(applies.len(), 2);
+    assert!(!applies[0].query.contains("force=true"));
+    assert!(applies[1].query.contains("force=true"));
+    assert_eq!(lifecycle_env(&cluster)[0]["value"], "tekton-upstream/triggers:abc");
+}
+
+#[test]
+fn test_patch_operator_deployment_env_gives_up_on_conflicts() {
+    let cluster = FakeCluster::new();
+    cluster.insert(DEPLOYMENTS, operator_deployment(json!([])));
+    cluster.fail("PATCH", OPERATOR, 409, 10);
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    let upstream = mappings(&[("IMAGE_TRIGGERS_CONTROLLER", "tekton-upstream/triggers:abc")]);
+    let err = operator::patch_operator_deployment_env(&rt, &client, "openshift-operators", "openshift-pipelines-operator", &upstream)
+        .unwrap_err();
+    assert!(err.to_string().contains("Failed to update Deployment"), "{err:#}");
+    assert_eq!(cluster.count("PATCH", OPERATOR), 2);
+}
+
+#[test]
+fn test_patch_operator_deployment_env_missing_container() {
+    let cluster = FakeCluster::new();
+    let mut dep = operator_deployment(json!([]));
+    dep["spec"]["template"]["spec"]["containers"].as_array_mut().unwrap().pop();
+    cluster.insert(DEPLOYMENTS, dep);
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    let err = operator::patch_operator_deployment_env(&rt, &client, "openshift-operators", "openshift-pipelines-operator", &[])
+        .unwrap_err();
+    assert!(err.to_string().contains(operator::LIFECYCLE_CONTAINER), "{err:#}");
+    assert_eq!(cluster.count("PATCH", OPERATOR), 0);
+}
+
+fn installer_set(name: &str) -> Value {
+    json!({
+        "apiVersion": "operator.tekton.dev/v1alpha1",
+        "kind": "TektonInstallerSet",
+        "metadata": {"name": name},
+        "spec": {}
+    })
+}
+
+#[test]
+fn test_delete_installer_sets() {
+    let cluster = FakeCluster::new();
+    for name in [
+        "pipeline-main-deployment-x7k2p",
+        "pipeline-main-static-9qw4r",
+        "pipeline-post-m2n8v",
+        "pipeline-pre-c5t1z",
+        "pipelines-as-code-main-deployment-h3j6d",
+        "triggers-main-deployment-b8f2k",
+    ] {
+        cluster.insert(INSTALLER_SETS, installer_set(name));
+    }
+    // A failed delete is a warning, not an error
+    cluster.fail("DELETE", &format!("{}/pipeline-pre-c5t1z", INSTALLER_SETS), 403, 1);
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    assert_eq!(operator::delete_installer_sets(&rt, &client, "pipeline", None).unwrap(), 3);
+    assert_eq!(
+        cluster.names(INSTALLER_SETS),
+        vec![
+            "pipeline-pre-c5t1z",
+            "pipelines-as-code-main-deployment-h3j6d",
+            "triggers-main-deployment-b8f2k",
+        ]
+    );
+}
+
+#[test]
+fn test_delete_installer_sets_prefix_override() {
+    let cluster = FakeCluster::new();
+    cluster.insert(INSTALLER_SETS, installer_set("manualapprovalgate-main-deployment-4xv9s"));
+    cluster.insert(INSTALLER_SETS, installer_set("manual-approval-gate-config"));
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    assert_eq!(
+        operator::delete_installer_sets(&rt, &client, "manual-approval-gate", Some("manualapprovalgate")).unwrap(),
+        1
+    );
+    assert_eq!(cluster.names(INSTALLER_SETS), vec!["manual-approval-gate-config"]);
+}
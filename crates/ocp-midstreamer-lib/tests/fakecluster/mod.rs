@@ -0,0 +1,362 @@
+//! In-memory stand-in for the Kubernetes API server, served to `kube::Client`
+//! through a tower service, so the phases that talk to a cluster can be tested
+//! without one.
+//!
+//! Objects are kept as JSON per collection path. GET, list (with label
+//! selectors), POST, PUT (with resourceVersion conflicts), PATCH (merge,
+//! strategic merge or server-side apply, which records its field manager in
+//! `managedFields`) and DELETE behave closely enough to the real API server
+//! for the create/get/replace/delete flows in this crate. Every request is
+//! recorded, and failures can be injected per method and path.
+
+// Each test binary uses a different subset of the helpers.
+#![allow(dead_code)]
+
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+
+use kube::client::Body;
+use serde_json::{Value, json};
+
+/// A request the fake server received.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    /// Path without the query string
+    pub path: String,
+    pub query: String,
+    pub body: Option<Value>,
+}
+
+/// Answer the next `remaining` requests matching `method` and `path` with `code`.
+struct Failure {
+    method: String,
+    path: String,
+    code: u16,
+    remaining: usize,
+}
+
+#[derive(Default)]
+struct State {
+    /// Collection path -> object name -> object
+    objects: BTreeMap<String, BTreeMap<String, Value>>,
+    requests: Vec<Request>,
+    failures: Vec<Failure>,
+    resource_version: u64,
+}
+
+impl State {
+    fn next_resource_version(&mut self) -> String {
+        self.resource_version += 1;
+        self.resource_version.to_string()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct FakeCluster {
+    state: Arc<Mutex<State>>,
+}
+
+impl FakeCluster {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A client talking to this cluster. The client's buffer worker is spawned
+    /// on `rt`, which must then drive every call made with it.
+    pub fn client(&self, rt: &tokio::runtime::Runtime) -> kube::Client {
+        let _guard = rt.enter();
+        let state = self.state.clone();
+        let service = tower::service_fn(move |req: http::Request<Body>| {
+            let state = state.clone();
+            async move { Ok::<_, Infallible>(handle(&state, req).await) }
+        });
+        kube::Client::new(service, "default")
+    }
+
+    /// Store `object` in `collection` (e.g. "/apis/apps/v1/namespaces/ns/deployments").
+    pub fn insert(&self, collection: &str, mut object: Value) {
+        let mut state = self.state.lock().unwrap();
+        let name = object_name(&object).expect("object without metadata.name");
+        object["metadata"]["resourceVersion"] = json!(state.next_resource_version());
+        state.objects.entry(collection.to_string()).or_default().insert(name, object);
+    }
+
+    /// The object `name` in `collection`, if present.
+    pub fn get(&self, collection: &str, name: &str) -> Option<Value> {
+        let state = self.state.lock().unwrap();
+        state.objects.get(collection).and_then(|c| c.get(name)).cloned()
+    }
+
+    /// Names of the objects in `collection`, sorted.
+    pub fn names(&self, collection: &str) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        state.objects.get(collection).map(|c| c.keys().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Fail the next `times` `method` requests for `path` with HTTP `code`.
+    pub fn fail(&self, method: &str, path: &str, code: u16, times: usize) {
+        self.state.lock().unwrap().failures.push(Failure {
+            method: method.to_string(),
+            path: path.to_string(),
+            code,
+            remaining: times,
+        });
+    }
+
+    /// Every request received so far, in order.
+    pub fn requests(&self) -> Vec<Request> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Number of `method` requests received for `path`.
+    pub fn count(&self, method: &str, path: &str) -> usize {
+        self.requests().iter().filter(|r| r.method == method && r.path == path).count()
+    }
+}
+
+/// A current-thread runtime like the one `k8s::create_kube_client` returns.
+pub fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap()
+}
+
+async fn handle(state: &Mutex<State>, req: http::Request<Body>) -> http::Response<Body> {
+    let method = req.method().to_string();
+    let path = req.uri().path().to_string();
+    let query = req.uri().query().unwrap_or_default().to_string();
+    let content_type = req
+        .headers()
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let bytes = req.into_body().collect_bytes().await.unwrap_or_default();
+    let body: Option<Value> = serde_json::from_slice(&bytes).ok();
+
+    let mut state = state.lock().unwrap();
+    state.requests.push(Request { method: method.clone(), path: path.clone(), query: query.clone(), body: body.clone() });
+
+    if let Some(failure) = state
+        .failures
+        .iter_mut()
+        .find(|f| f.remaining > 0 && f.method == method && f.path == path)
+    {
+        failure.remaining -= 1;
+        return status(failure.code, "Injected", "injected failure");
+    }
+
+    let Some((collection, name)) = split_path(&path) else {
+        return status(404, "NotFound", &format!("no route for {}", path));
+    };
+
+    match (method.as_str(), name) {
+        ("GET", Some(name)) => match state.objects.get(&collection).and_then(|c| c.get(&name)) {
+            Some(object) => respond(200, object),
+            None => not_found(&name),
+        },
+        ("GET", None) => {
+            let selector = label_selector(&query);
+            let items: Vec<Value> = state
+                .objects
+                .get(&collection)
+                .map(|c| c.values().filter(|o| matches_labels(o, &selector)).cloned().collect())
+                .unwrap_or_default();
+            respond(200, &json!({"apiVersion": "v1", "kind": "List", "metadata": {}, "items": items}))
+        }
+        ("POST", None) => {
+            let Some(mut object) = body else {
+                return status(400, "BadRequest", "missing body");
+            };
+            let Some(name) = object_name(&object) else {
+                return status(422, "Invalid", "metadata.name is required");
+            };
+            if state.objects.get(&collection).is_some_and(|c| c.contains_key(&name)) {
+                return status(409, "AlreadyExists", &format!("\"{}\" already exists", name));
+            }
+            object["metadata"]["resourceVersion"] = json!(state.next_resource_version());
+            state.objects.entry(collection).or_default().insert(name, object.clone());
+            respond(201, &object)
+        }
+        ("PUT", Some(name)) => {
+            let Some(mut object) = body else {
+                return status(400, "BadRequest", "missing body");
+            };
+            let Some(current) = state.objects.get(&collection).and_then(|c| c.get(&name)) else {
+                return not_found(&name);
+            };
+            let sent = &object["metadata"]["resourceVersion"];
+            if !sent.is_null() && *sent != current["metadata"]["resourceVersion"] {
+                return status(409, "Conflict", "the object has been modified");
+            }
+            object["metadata"]["resourceVersion"] = json!(state.next_resource_version());
+            state.objects.entry(collection).or_default().insert(name, object.clone());
+            respond(200, &object)
+        }
+        ("PATCH", Some(name)) => {
+            let Some(patch) = body else {
+                return status(400, "BadRequest", "missing body");
+            };
+            let current = state.objects.get(&collection).and_then(|c| c.get(&name)).cloned();
+            let mut object = match current {
+                Some(object) => object,
+                // Server-side apply creates missing objects
+                None if content_type.starts_with("application/apply-patch") => json!({}),
+                None => return not_found(&name),
+            };
+            // Strategic merge and apply merge lists of named items (containers,
+            // env) by name; a field manager's earlier fields are not dropped
+            let keyed = !content_type.starts_with("application/merge-patch");
+            merge(&mut object, &patch, keyed);
+            if let Some(manager) = content_type
+                .starts_with("application/apply-patch")
+                .then(|| query_param(&query, "fieldManager"))
+                .flatten()
+            {
+                record_manager(&mut object, &manager, &patch);
+            }
+            object["metadata"]["resourceVersion"] = json!(state.next_resource_version());
+            state.objects.entry(collection).or_default().insert(name, object.clone());
+            respond(200, &object)
+        }
+        ("DELETE", Some(name)) => match state.objects.get_mut(&collection).and_then(|c| c.remove(&name)) {
+            Some(object) => respond(200, &object),
+            None => not_found(&name),
+        },
+        _ => status(405, "MethodNotAllowed", &format!("{} {} is not supported", method, path)),
+    }
+}
+
+/// Split an API path into its collection path and, for single objects, the
+/// object name. Handles core (`/api/v1`) and group (`/apis/<group>/<version>`)
+/// resources, namespaced or cluster-scoped.
+fn split_path(path: &str) -> Option<(String, Option<String>)> {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let prefix_len = match segments.first() {
+        Some(&"api") => 2,
+        Some(&"apis") => 3,
+        _ => return None,
+    };
+    let rest = segments.get(prefix_len..)?;
+    let collection_len = if rest.len() >= 3 && rest[0] == "namespaces" { 3 } else { 1 };
+    if rest.len() < collection_len || rest.len() > collection_len + 1 {
+        return None;
+    }
+    let collection = format!("/{}", segments[..prefix_len + collection_len].join("/"));
+    Some((collection, rest.get(collection_len).map(|n| n.to_string())))
+}
+
+/// `key=value` pairs of the labelSelector query parameter.
+fn label_selector(query: &str) -> Vec<(String, String)> {
+    let Some(selector) = query.split('&').find_map(|p| p.strip_prefix("labelSelector=")) else {
+        return Vec::new();
+    };
+    selector
+        .replace("%3D", "=")
+        .replace("%2C", ",")
+        .split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn matches_labels(object: &Value, selector: &[(String, String)]) -> bool {
+    selector
+        .iter()
+        .all(|(k, v)| object["metadata"]["labels"][k].as_str() == Some(v.as_str()))
+}
+
+fn object_name(object: &Value) -> Option<String> {
+    object["metadata"]["name"].as_str().map(str::to_string)
+}
+
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .find_map(|p| p.strip_prefix(name)?.strip_prefix('='))
+        .map(str::to_string)
+}
+
+/// RFC 7386 JSON merge patch; with `keyed`, lists of objects with a name are
+/// merged item by item, as strategic merge and server-side apply do.
+fn merge(target: &mut Value, patch: &Value, keyed: bool) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                if value.is_null() {
+                    target.remove(key);
+                } else {
+                    merge(target.entry(key.clone()).or_insert(Value::Null), value, keyed);
+                }
+            }
+        }
+        (Value::Array(target), Value::Array(patch)) if keyed && patch.iter().all(|i| i["name"].is_string()) => {
+            for item in patch {
+                match target.iter_mut().find(|t| t["name"] == item["name"]) {
+                    Some(existing) => merge(existing, item, keyed),
+                    None => target.push(item.clone()),
+                }
+            }
+        }
+        (target, patch) => *target = patch.clone(),
+    }
+}
+
+/// Set `manager`'s entry in `metadata.managedFields` to the fields of `applied`.
+fn record_manager(object: &mut Value, manager: &str, applied: &Value) {
+    let mut fields = fields_v1(applied);
+    for key in ["f:apiVersion", "f:kind", "f:metadata"] {
+        fields.as_object_mut().unwrap().remove(key);
+    }
+    let entry = json!({"manager": manager, "operation": "Apply", "fieldsType": "FieldsV1", "fieldsV1": fields});
+    let managed = object["metadata"]["managedFields"].as_array().cloned().unwrap_or_default();
+    let mut managed: Vec<Value> = managed.into_iter().filter(|m| m["manager"] != manager).collect();
+    managed.push(entry);
+    object["metadata"]["managedFields"] = Value::Array(managed);
+}
+
+/// The `fieldsV1` set of `value`: `f:<field>` per field, `k:{"name":...}` per named list item.
+fn fields_v1(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(map.iter().map(|(k, v)| (format!("f:{}", k), fields_v1(v))).collect()),
+        Value::Array(items) if !items.is_empty() && items.iter().all(|i| i["name"].is_string()) => Value::Object(
+            items
+                .iter()
+                .map(|item| {
+                    let mut fields = fields_v1(item);
+                    fields.as_object_mut().unwrap().insert(".".to_string(), json!({}));
+                    (format!("k:{}", json!({"name": item["name"]})), fields)
+                })
+                .collect(),
+        ),
+        _ => json!({}),
+    }
+}
+
+fn respond(code: u16, body: &Value) -> http::Response<Body> {
+    http::Response::builder()
+        .status(code)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(body).unwrap()))
+        .unwrap()
+}
+
+fn not_found(name: &str) -> http::Response<Body> {
+    status(404, "NotFound", &format!("\"{}\" not found", name))
+}
+
+/// A `Status` failure as the API server returns it.
+fn status(code: u16, reason: &str, message: &str) -> http::Response<Body> {
+    respond(
+        code,
+        &json!({
+            "kind": "Status",
+            "apiVersion": "v1",
+            "metadata": {},
+            "status": "Failure",
+            "message": message,
+            "reason": reason,
+            "code": code,
+        }),
+    )
+}
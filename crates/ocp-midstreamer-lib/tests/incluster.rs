@@ -0,0 +1,126 @@
+//! In-cluster Job creation and ServiceAccount RBAC against the fake API server.
+
+mod fakecluster;
+
+use fakecluster::FakeCluster;
+use ocp_midstreamer_lib::confirm;
+use ocp_midstreamer_lib::incluster::{self, GoModCache, JobGoEnv, JobProfile, PublishEnv};
+use ocp_midstreamer_lib::rbac::{self, RbacProfile};
+use serde_json::json;
+
+const JOBS: &str = "/apis/batch/v1/namespaces/streamstress/jobs";
+const SERVICE_ACCOUNTS: &str = "/api/v1/namespaces/streamstress/serviceaccounts";
+const CLUSTER_ROLES: &str = "/apis/rbac.authorization.k8s.io/v1/clusterroles";
+const CLUSTER_ROLE_BINDINGS: &str = "/apis/rbac.authorization.k8s.io/v1/clusterrolebindings";
+
+#[test]
+fn test_create_job() {
+    let cluster = FakeCluster::new();
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    let args = vec!["run".to_string(), "--components".to_string(), "pipeline".to_string()];
+    let publish = PublishEnv {
+        github_token: Some("token".to_string()),
+        github_repository: Some("openshift-pipelines/ocp-midstreamer".to_string()),
+        ..Default::default()
+    };
+    let go_env = JobGoEnv { mod_cache: GoModCache::Pvc, goproxy: None };
+    let name = rt
+        .block_on(incluster::create_job(&client, "streamstress", "registry/streamstress:v1", &args, &publish, &go_env, &JobProfile::RUN))
+        .unwrap();
+
+    assert!(name.starts_with("streamstress-"), "{name}");
+    let job = cluster.get(JOBS, &name).unwrap();
+    let pod = &job["spec"]["template"]["spec"];
+    assert_eq!(pod["serviceAccountName"], rbac::SERVICE_ACCOUNT);
+    assert_eq!(pod["restartPolicy"], "Never");
+    let container = &pod["containers"][0];
+    assert_eq!(container["image"], "registry/streamstress:v1");
+    assert_eq!(container["args"], json!(args));
+
+    let env = container["env"].as_array().unwrap();
+    let var = |name: &str| env.iter().find(|e| e["name"] == name).cloned();
+    assert_eq!(var("JOB_NAME").unwrap()["value"], name);
+    // The token comes from the publish Secret, never inline
+    let token = var("GITHUB_TOKEN").unwrap();
+    assert!(token.get("value").is_none());
+    assert_eq!(token["valueFrom"]["secretKeyRef"]["name"], incluster::PUBLISH_SECRET);
+    assert_eq!(var("GITHUB_REPOSITORY").unwrap()["value"], "openshift-pipelines/ocp-midstreamer");
+    assert!(var("GOMODCACHE").is_some());
+    assert_eq!(pod["volumes"][0]["persistentVolumeClaim"]["claimName"], incluster::GO_CACHE_PVC);
+}
+
+#[test]
+fn test_create_job_konflux_profile() {
+    let cluster = FakeCluster::new();
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    let profile = JobProfile::konflux(3600, true);
+    let name = rt
+        .block_on(incluster::create_job(&client, "streamstress", "image", &[], &PublishEnv::default(), &JobGoEnv::default(), &profile))
+        .unwrap();
+
+    let job = cluster.get(JOBS, &name).unwrap();
+    assert_eq!(job["spec"]["activeDeadlineSeconds"], profile.deadline_secs);
+    let container = &job["spec"]["template"]["spec"]["containers"][0];
+    assert_eq!(container["securityContext"]["privileged"], true);
+    assert_eq!(container["volumeMounts"][0]["name"], "registry-auth");
+}
+
+#[test]
+fn test_create_job_failure() {
+    let cluster = FakeCluster::new();
+    cluster.fail("POST", JOBS, 403, 1);
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    let err = rt
+        .block_on(incluster::create_job(&client, "streamstress", "image", &[], &PublishEnv::default(), &JobGoEnv::default(), &JobProfile::RUN))
+        .unwrap_err();
+    assert!(err.to_string().contains("Failed to create Job"), "{err:#}");
+    assert!(cluster.names(JOBS).is_empty());
+}
+
+#[test]
+fn test_ensure_service_account_minimal() {
+    // Binding a cluster role asks first when the tests run on a terminal
+    confirm::init(true);
+    let cluster = FakeCluster::new();
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    rt.block_on(incluster::ensure_service_account(&client, "streamstress", RbacProfile::Minimal)).unwrap();
+
+    assert_eq!(cluster.names(SERVICE_ACCOUNTS), vec![rbac::SERVICE_ACCOUNT]);
+    let role = cluster.get(CLUSTER_ROLES, rbac::MINIMAL_CLUSTER_ROLE).unwrap();
+    assert!(!role["rules"].as_array().unwrap().is_empty());
+    let binding = cluster.get(CLUSTER_ROLE_BINDINGS, rbac::CLUSTER_ROLE_BINDING).unwrap();
+    assert_eq!(binding["roleRef"]["name"], rbac::MINIMAL_CLUSTER_ROLE);
+    assert_eq!(binding["subjects"][0]["namespace"], "streamstress");
+}
+
+#[test]
+fn test_ensure_service_account_rebinds_profile() {
+    // Binding a cluster role asks first when the tests run on a terminal
+    confirm::init(true);
+    let cluster = FakeCluster::new();
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    rt.block_on(incluster::ensure_service_account(&client, "streamstress", RbacProfile::ClusterAdmin)).unwrap();
+    assert!(cluster.names(CLUSTER_ROLES).is_empty());
+    // An existing ServiceAccount is fine; the binding's immutable roleRef is recreated
+    rt.block_on(incluster::ensure_service_account(&client, "streamstress", RbacProfile::Minimal)).unwrap();
+
+    let binding_path = format!("{}/{}", CLUSTER_ROLE_BINDINGS, rbac::CLUSTER_ROLE_BINDING);
+    assert_eq!(cluster.count("DELETE", &binding_path), 1);
+    let binding = cluster.get(CLUSTER_ROLE_BINDINGS, rbac::CLUSTER_ROLE_BINDING).unwrap();
+    assert_eq!(binding["roleRef"]["name"], rbac::MINIMAL_CLUSTER_ROLE);
+
+    // Same profile again: nothing to change
+    rt.block_on(incluster::ensure_service_account(&client, "streamstress", RbacProfile::Minimal)).unwrap();
+    assert_eq!(cluster.count("DELETE", &binding_path), 1);
+    assert_eq!(cluster.count("POST", CLUSTER_ROLE_BINDINGS), 2);
+}
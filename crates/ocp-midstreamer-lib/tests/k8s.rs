@@ -0,0 +1,44 @@
+//! Shared `k8s` helpers against the fake API server.
+
+mod fakecluster;
+
+use fakecluster::FakeCluster;
+use ocp_midstreamer_lib::k8s;
+use serde_json::json;
+
+const NAMESPACES: &str = "/api/v1/namespaces";
+
+#[test]
+fn test_ensure_namespace_creates() {
+    let cluster = FakeCluster::new();
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    k8s::ensure_namespace(&rt, &client, "streamstress-probe").unwrap();
+
+    let ns = cluster.get(NAMESPACES, "streamstress-probe").unwrap();
+    assert_eq!(ns["metadata"]["labels"]["app"], "streamstress");
+}
+
+#[test]
+fn test_ensure_namespace_existing() {
+    let cluster = FakeCluster::new();
+    cluster.insert(NAMESPACES, json!({"apiVersion": "v1", "kind": "Namespace", "metadata": {"name": "streamstress-probe"}}));
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    k8s::ensure_namespace(&rt, &client, "streamstress-probe").unwrap();
+
+    assert_eq!(cluster.count("POST", NAMESPACES), 0);
+}
+
+#[test]
+fn test_ensure_namespace_create_fails() {
+    let cluster = FakeCluster::new();
+    cluster.fail("POST", NAMESPACES, 403, 1);
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    let err = k8s::ensure_namespace(&rt, &client, "streamstress-probe").unwrap_err();
+    assert!(err.to_string().contains("Failed to create Namespace streamstress-probe"), "{err:#}");
+}
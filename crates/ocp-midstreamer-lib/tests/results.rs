@@ -0,0 +1,66 @@
+//! Report parsing and categorization on recorded release-tests output
+//! (testdata/results).
+
+use std::path::{Path, PathBuf};
+
+use ocp_midstreamer_lib::results::{self, FailureCategory};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/results").join(name)
+}
+
+#[test]
+fn test_parse_junit_xml() {
+    let result = results::parse_junit_xml(&fixture("junit.xml")).unwrap();
+
+    assert_eq!((result.total, result.passed, result.failed), (4, 2, 2));
+    assert!((result.duration_secs - 420.25).abs() < 1e-9);
+    let resolver = &result.tests[1];
+    assert_eq!(resolver.spec, "Verify pipelines");
+    assert_eq!(resolver.scenario, "Run pipeline with resolver: PIPELINES-03-TC04");
+    assert_eq!(
+        resolver.error_message.as_deref(),
+        Some("pipelinerun resolver-pipeline-run failed: TaskRun git-clone timed out")
+    );
+    // <error> counts as a failure too
+    assert!(!result.tests[3].passed);
+}
+
+#[test]
+fn test_parse_gauge_stdout() {
+    let result = results::parse_gauge_stdout(&fixture("gauge-stdout.log")).unwrap();
+
+    assert_eq!((result.total, result.passed, result.failed), (4, 2, 2));
+    assert!((result.duration_secs - 942.123).abs() < 1e-9);
+    let scenarios: Vec<(&str, &str, bool)> =
+        result.tests.iter().map(|t| (t.spec.as_str(), t.scenario.as_str(), t.passed)).collect();
+    assert_eq!(
+        scenarios,
+        vec![
+            ("Verify pipelines", "Run sample pipeline: PIPELINES-03-TC01", true),
+            ("Verify pipelines", "Run pipeline with resolver: PIPELINES-03-TC04", false),
+            ("Verify triggers", "Create EventListener: PIPELINES-05-TC01", true),
+            ("Verify triggers", "Verify interceptor with secret: PIPELINES-05-TC07", false),
+        ]
+    );
+    // Multi-line error messages end at the stack trace
+    assert_eq!(
+        result.tests[1].error_message.as_deref(),
+        Some("pipelinerun resolver-pipeline-run failed:\nTaskRun git-clone timed out")
+    );
+}
+
+#[test]
+fn test_categorize_fixtures() {
+    let junit = results::categorize_results(&results::parse_junit_xml(&fixture("junit.xml")).unwrap());
+    let categories: Vec<(FailureCategory, usize)> = junit.categories.iter().map(|c| (c.category.clone(), c.count)).collect();
+    assert!(categories.contains(&(FailureCategory::UpstreamRegression, 1)));
+    assert!(categories.contains(&(FailureCategory::MissingComponent, 1)));
+
+    let gauge = results::categorize_results(&results::parse_gauge_stdout(&fixture("gauge-stdout.log")).unwrap());
+    let config_gap = gauge.categories.iter().find(|c| c.category == FailureCategory::ConfigGap).unwrap();
+    assert_eq!(config_gap.tests, vec!["Verify triggers::Verify interceptor with secret: PIPELINES-05-TC07"]);
+
+    let triggers = gauge.components.iter().find(|c| c.component == "triggers").unwrap();
+    assert_eq!((triggers.total, triggers.passed, triggers.failed), (2, 1, 1));
+}
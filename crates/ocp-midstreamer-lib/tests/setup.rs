@@ -0,0 +1,119 @@
+//! `setup` steps against the fake API server.
+
+mod fakecluster;
+
+use fakecluster::FakeCluster;
+use ocp_midstreamer_lib::{registry, setup};
+use serde_json::json;
+
+const NAMESPACES: &str = "/api/v1/namespaces";
+const TEKTONCONFIGS: &str = "/apis/operator.tekton.dev/v1alpha1/tektonconfigs";
+const SUBSCRIPTIONS: &str = "/apis/operators.coreos.com/v1alpha1/namespaces/openshift-operators/subscriptions";
+
+fn role_bindings() -> String {
+    format!("/apis/rbac.authorization.k8s.io/v1/namespaces/{}/rolebindings", registry::DEFAULT_NAMESPACE)
+}
+
+#[test]
+fn test_ensure_namespace_rbac_creates() {
+    let cluster = FakeCluster::new();
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    setup::ensure_namespace_rbac(&rt, &client).unwrap();
+
+    assert_eq!(cluster.names(NAMESPACES), vec![registry::DEFAULT_NAMESPACE]);
+    let binding = cluster.get(&role_bindings(), "image-puller-all-authenticated").unwrap();
+    assert_eq!(binding["roleRef"]["name"], "system:image-puller");
+    assert_eq!(binding["subjects"][0]["name"], "system:authenticated");
+}
+
+#[test]
+fn test_ensure_namespace_rbac_idempotent() {
+    let cluster = FakeCluster::new();
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    setup::ensure_namespace_rbac(&rt, &client).unwrap();
+    let created = cluster.requests().iter().filter(|r| r.method == "POST").count();
+    setup::ensure_namespace_rbac(&rt, &client).unwrap();
+
+    assert_eq!(created, 2);
+    assert_eq!(cluster.requests().iter().filter(|r| r.method == "POST").count(), 2);
+}
+
+#[test]
+fn test_ensure_namespace_rbac_forbidden() {
+    let cluster = FakeCluster::new();
+    cluster.fail("GET", &format!("{}/{}", NAMESPACES, registry::DEFAULT_NAMESPACE), 403, 1);
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    let err = setup::ensure_namespace_rbac(&rt, &client).unwrap_err();
+    assert!(err.to_string().contains("Failed to check namespace"), "{err:#}");
+    assert!(cluster.names(NAMESPACES).is_empty());
+}
+
+#[test]
+fn test_ensure_tektonconfig_creates() {
+    let cluster = FakeCluster::new();
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    setup::ensure_tektonconfig(&rt, &client).unwrap();
+
+    let config = cluster.get(TEKTONCONFIGS, "config").unwrap();
+    assert_eq!(config["spec"], json!({"targetNamespace": "openshift-pipelines", "profile": "all"}));
+    // Labelled so `uninstall` knows streamstress created it
+    assert_eq!(config["metadata"]["labels"]["app"], "streamstress");
+}
+
+#[test]
+fn test_ensure_tektonconfig_existing() {
+    let cluster = FakeCluster::new();
+    cluster.insert(
+        TEKTONCONFIGS,
+        json!({
+            "apiVersion": "operator.tekton.dev/v1alpha1",
+            "kind": "TektonConfig",
+            "metadata": {"name": "config"},
+            "spec": {"targetNamespace": "openshift-pipelines", "profile": "basic"}
+        }),
+    );
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    setup::ensure_tektonconfig(&rt, &client).unwrap();
+
+    assert_eq!(cluster.count("POST", TEKTONCONFIGS), 0);
+    assert_eq!(cluster.get(TEKTONCONFIGS, "config").unwrap()["spec"]["profile"], "basic");
+}
+
+#[test]
+fn test_uninstall_only_removes_setup_resources() {
+    let cluster = FakeCluster::new();
+    cluster.insert(
+        TEKTONCONFIGS,
+        json!({
+            "apiVersion": "operator.tekton.dev/v1alpha1",
+            "kind": "TektonConfig",
+            "metadata": {"name": "config", "labels": {"app": "streamstress"}}
+        }),
+    );
+    cluster.insert(
+        SUBSCRIPTIONS,
+        json!({
+            "apiVersion": "operators.coreos.com/v1alpha1",
+            "kind": "Subscription",
+            "metadata": {"name": "openshift-pipelines-operator", "namespace": "openshift-operators"}
+        }),
+    );
+    let rt = fakecluster::runtime();
+    let client = cluster.client(&rt);
+
+    assert_eq!(setup::uninstall_changes(&rt, &client).unwrap(), vec!["delete TektonConfig config"]);
+    setup::remove_setup_resources(&rt, &client).unwrap();
+
+    assert!(cluster.get(TEKTONCONFIGS, "config").is_none());
+    assert!(cluster.get(SUBSCRIPTIONS, "openshift-pipelines-operator").is_some());
+}
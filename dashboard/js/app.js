@@ -7,6 +7,7 @@ import { initFilters, loadFromUrl, filterRuns, filterTests, getState } from "./f
 const MAX_RUNS = 10;
 
 let _allRuns = [];
+let _trendRuns = null;
 
 /**
  * Detect if ?sample query parameter is present.
@@ -128,6 +129,80 @@ async function loadRuns(manifest) {
   return runs;
 }
 
+/**
+ * Add an option per batch id found in the manifest to the batch filter,
+ * most recent batch first.
+ */
+function populateBatchFilter(manifest) {
+  const select = document.getElementById('filter-batch');
+  if (!select) return;
+  const batches = new Map();
+  for (const entry of manifest.runs || []) {
+    if (!entry.batch_id) continue;
+    const b = batches.get(entry.batch_id) || { runs: 0, latest: '' };
+    b.runs += 1;
+    const date = entry.date || entry.timestamp || '';
+    if (date > b.latest) b.latest = date;
+    batches.set(entry.batch_id, b);
+  }
+  [...batches.entries()]
+    .sort((a, b) => b[1].latest.localeCompare(a[1].latest))
+    .forEach(([id, b]) => {
+      const opt = document.createElement('option');
+      opt.value = id;
+      opt.textContent = id + ' (' + b.runs + ' runs)';
+      select.appendChild(opt);
+    });
+}
+
+/**
+ * Add an option per `key=value` of run metadata found in the manifest
+ * (the keys publish copies into entries) to the metadata filter.
+ */
+function populateMetaFilter(manifest) {
+  const select = document.getElementById('filter-meta');
+  if (!select) return;
+  const counts = new Map();
+  for (const entry of manifest.runs || []) {
+    for (const [key, value] of Object.entries(entry.meta || {})) {
+      const pair = key + '=' + value;
+      counts.set(pair, (counts.get(pair) || 0) + 1);
+    }
+  }
+  [...counts.entries()]
+    .sort((a, b) => a[0].localeCompare(b[0]))
+    .forEach(([pair, runs]) => {
+      const opt = document.createElement('option');
+      opt.value = pair;
+      opt.textContent = pair + ' (' + runs + ' runs)';
+      select.appendChild(opt);
+    });
+}
+
+/**
+ * Load run summaries from the pre-computed index written by publish
+ * (runs/index/latest.json). Returns null when the index is not available,
+ * e.g. for trees published before indexes existed or in sample mode.
+ */
+async function loadTrendIndex() {
+  if (isSampleMode()) return null;
+  try {
+    const resp = await fetch(dataBasePath() + 'index/latest.json');
+    if (!resp.ok) return null;
+    const index = await resp.json();
+    return (index.runs || []).map(r => normalizeRun({ ...r, date: r.timestamp }));
+  } catch (_) {
+    return null;
+  }
+}
+
+/**
+ * Whether any filter narrows tests within a run (summaries only cover whole runs).
+ */
+function hasTestFilters(state) {
+  return Boolean(state.category || state.status || state.component || state.search);
+}
+
 /**
  * Ensure chart container divs exist inside #trend-charts.
  */
@@ -180,8 +255,14 @@ function renderWithFilters(state) {
   const descRuns = runsWithFilteredTests.slice().sort((a, b) => new Date(b.date) - new Date(a.date));
   const ascRuns = runsWithFilteredTests.slice().sort((a, b) => new Date(a.date) - new Date(b.date));
 
-  renderTrendChart(ascRuns, '#pass-rate-chart');
-  renderCategoryChart(ascRuns, '#category-chart');
+  // Without test-level filters, trends come from the index and cover far more
+  // runs than are loaded in full for the tables
+  const trendRuns = _trendRuns && !hasTestFilters(state)
+    ? filterRuns(_trendRuns, state).slice().sort((a, b) => new Date(a.date) - new Date(b.date))
+    : ascRuns;
+
+  renderTrendChart(trendRuns, '#pass-rate-chart');
+  renderCategoryChart(trendRuns, '#category-chart');
   renderRunTables(descRuns, '#run-tables');
 }
 
@@ -196,7 +277,7 @@ document.addEventListener('DOMContentLoaded', async () => {
       return;
     }
     const manifest = await resp.json();
-    _allRuns = await loadRuns(manifest);
+    [_allRuns, _trendRuns] = await Promise.all([loadRuns(manifest), loadTrendIndex()]);
 
     // Expose run data globally for timeline module
     window.runData = _allRuns;
@@ -206,6 +287,9 @@ document.addEventListener('DOMContentLoaded', async () => {
       return;
     }
 
+    populateBatchFilter(manifest);
+    populateMetaFilter(manifest);
+
     // Load URL state first, then init filters with reactive callback
     const restoredState = loadFromUrl();
     initFilters(renderWithFilters);
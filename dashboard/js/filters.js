@@ -7,6 +7,8 @@ const state = {
   dateFrom: null,
   dateTo: null,
   search: "",
+  batch: null,
+  meta: null,
   selectedRuns: [],
 };
 
@@ -35,6 +37,8 @@ export function initFilters(onChangeCallback) {
   bind("filter-date-from", "dateFrom", "change");
   bind("filter-date-to", "dateTo", "change");
   bind("filter-search", "search");
+  bind("filter-batch", "batch", "change");
+  bind("filter-meta", "meta", "change");
 
   // Listen for hash changes (back/forward navigation)
   window.addEventListener("hashchange", () => {
@@ -63,6 +67,8 @@ export function syncToUrl(s) {
   if (s.dateFrom) params.set("dateFrom", s.dateFrom);
   if (s.dateTo) params.set("dateTo", s.dateTo);
   if (s.search) params.set("search", s.search);
+  if (s.batch) params.set("batch", s.batch);
+  if (s.meta) params.set("meta", s.meta);
   if (s.selectedRuns && s.selectedRuns.length > 0) {
     params.set("runs", s.selectedRuns.join(","));
   }
@@ -90,6 +96,8 @@ export function loadFromUrl() {
   setIfPresent("dateFrom");
   setIfPresent("dateTo");
   state.search = params.get("search") || "";
+  setIfPresent("batch");
+  setIfPresent("meta");
 
   const runsParam = params.get("runs");
   state.selectedRuns = runsParam ? runsParam.split(",") : [];
@@ -101,11 +109,18 @@ export function loadFromUrl() {
 }
 
 /**
- * Filter runs by dateFrom/dateTo.
+ * Filter runs by dateFrom/dateTo, batch id (runs of one `run --date-range`)
+ * and a `key=value` of the run's `--meta` metadata.
  */
 export function filterRuns(runs, s) {
   if (!runs) return [];
   return runs.filter((r) => {
+    if (s.batch && r.batch_id !== s.batch) return false;
+    if (s.meta) {
+      const i = s.meta.indexOf("=");
+      const key = s.meta.slice(0, i), value = s.meta.slice(i + 1);
+      if ((r.meta || {})[key] !== value) return false;
+    }
     const d = new Date(r.date);
     if (s.dateFrom && d < new Date(s.dateFrom)) return false;
     if (s.dateTo && d > new Date(s.dateTo + "T23:59:59")) return false;
@@ -152,4 +167,6 @@ function _syncDom() {
   setVal("filter-date-from", state.dateFrom);
   setVal("filter-date-to", state.dateTo);
   setVal("filter-search", state.search);
+  setVal("filter-batch", state.batch);
+  setVal("filter-meta", state.meta);
 }
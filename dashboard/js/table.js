@@ -149,6 +149,15 @@ export function renderRunTables(runs, containerSelector) {
     header.appendChild(badge);
     header.appendChild(counts);
 
+    // Per-component pass counts ("triggers is red, pipeline is green")
+    for (const c of (run.components || [])) {
+      const chip = document.createElement('span');
+      chip.className = 'component-badge ' + (c.failed > 0 ? 'component-fail' : 'component-pass');
+      chip.textContent = c.component + ' ' + c.passed + '/' + c.total;
+      chip.title = c.component + ': ' + Number(c.pass_rate || 0).toFixed(1) + '% passed';
+      header.appendChild(chip);
+    }
+
     if (run.duration) {
       const dur = document.createElement('span');
       dur.className = 'run-duration';
@@ -1,6 +1,7 @@
 #!/bin/bash
 # Container entrypoint for streamstress CI Jobs.
-# Runs streamstress, then publishes results if configured.
+# Sets up a kubeconfig, then runs streamstress (which publishes results itself
+# when GITHUB_TOKEN and GITHUB_REPOSITORY are set).
 
 set -euo pipefail
 
@@ -34,6 +35,16 @@ KUBEEOF
     echo "Created kubeconfig from ServiceAccount token"
 fi
 
+# Registry credentials for Jobs that push images (konflux --in-cluster). The
+# Secret mount is read-only, so copy it where oc registry login can add to it.
+REGISTRY_AUTH="/var/run/streamstress/registry-auth/config.json"
+if [ -f "$REGISTRY_AUTH" ]; then
+    mkdir -p /root/.docker /root/.config/containers
+    cp "$REGISTRY_AUTH" /root/.docker/config.json
+    cp "$REGISTRY_AUTH" /root/.config/containers/auth.json
+    echo "Installed registry credentials from Secret"
+fi
+
 # Run streamstress with all passed arguments
 echo "Running: streamstress $*"
 EXIT_CODE=0
@@ -42,10 +53,4 @@ streamstress "$@" || EXIT_CODE=$?
 echo ""
 echo "streamstress exited with code: $EXIT_CODE"
 
-# Publish results if GitHub env vars are set
-if [ -n "${GITHUB_TOKEN:-}" ] && [ -n "${GITHUB_REPOSITORY:-}" ]; then
-    echo ""
-    /usr/local/bin/publish-to-gh-pages.sh || echo "Warning: publish failed (non-fatal)"
-fi
-
 exit $EXIT_CODE
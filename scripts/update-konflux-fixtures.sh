@@ -0,0 +1,45 @@
+#!/bin/bash
+# Vendor .konflux/tekton/release-test-pipeline.yaml from the operator repo into
+# crates/ocp-midstreamer-lib/testdata/konflux/<dir>/ for each branch below, and
+# record the commit each copy came from in testdata/konflux/README.md.
+#
+# Afterwards regenerate the goldens and review them by hand:
+#   UPDATE_GOLDEN=1 cargo test -p ocp-midstreamer-lib test_standalone_pipeline_golden
+#   git diff crates/ocp-midstreamer-lib/testdata/konflux
+
+set -euo pipefail
+
+REPO="${OPERATOR_REPO:-https://github.com/openshift-pipelines/operator.git}"
+PIPELINE=".konflux/tekton/release-test-pipeline.yaml"
+ROOT="$(cd "$(dirname "$0")/.." && pwd)/crates/ocp-midstreamer-lib/testdata/konflux"
+
+# <fixture dir>:<operator branch>
+BRANCHES=(
+    "main:main"
+    "release-v1.16:release-v1.16.x"
+    "release-v1.15:release-v1.15.x"
+)
+
+WORK="$(mktemp -d)"
+trap 'rm -rf "$WORK"' EXIT
+
+git -C "$WORK" init --quiet
+rows=""
+for entry in "${BRANCHES[@]}"; do
+    dir="${entry%%:*}"
+    branch="${entry#*:}"
+    git -C "$WORK" fetch --quiet --depth 1 "$REPO" "$branch"
+    sha="$(git -C "$WORK" rev-parse FETCH_HEAD)"
+    mkdir -p "$ROOT/$dir"
+    git -C "$WORK" show "FETCH_HEAD:$PIPELINE" > "$ROOT/$dir/release-test-pipeline.yaml"
+    rows+="| \`$dir/\` | \`$branch\` | \`$sha\` |"$'\n'
+    echo "$dir: $branch @ $sha"
+done
+
+# Replace the table between the markers in the README
+awk -v rows="$rows" '
+    /<!-- sources:begin -->/ { print; printf "%s", "| Fixture | Operator branch | Source commit |\n|---|---|---|\n" rows; skip = 1; next }
+    /<!-- sources:end -->/ { skip = 0 }
+    !skip
+' "$ROOT/README.md" > "$WORK/README.md"
+mv "$WORK/README.md" "$ROOT/README.md"
@@ -2,20 +2,138 @@ use clap::{Parser, Subcommand};
 
 
 #[derive(Parser, Debug)]
-#[command(name = "streamstress", about = "OpenShift Pipelines upstream regression detection CLI")]
+#[command(name = "streamstress", version, about = "OpenShift Pipelines upstream regression detection CLI")]
 pub struct Cli {
-    /// Enable verbose output
-    #[arg(long, global = true)]
-    pub verbose: bool,
+    /// More output: -v shows every cluster change, -vv every external command,
+    /// -vvv command output and full Kubernetes API payloads
+    #[arg(long, short = 'v', global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Per-module log filters on top of -v, e.g. "deploy=debug,kube=warn"
+    /// (streamstress modules, or the kube, hyper, tower and rustls crates)
+    #[arg(long, global = true, value_name = "MODULE=LEVEL,...")]
+    pub log: Option<String>,
 
     /// Disable automatic cluster setup (registry route, operator install)
     #[arg(long, global = true)]
     pub no_auto_setup: bool,
 
+    /// Append a JSONL record of every external command and Kubernetes mutation to this file
+    #[arg(long, global = true)]
+    pub audit_log: Option<std::path::PathBuf>,
+
+    /// Record every Kubernetes API response and external command output of the run
+    /// into this directory, for replaying it with --replay
+    #[arg(long, global = true, value_name = "DIR", conflicts_with = "replay")]
+    pub record: Option<std::path::PathBuf>,
+
+    /// Answer Kubernetes API calls and external commands from a directory written by
+    /// --record instead of the cluster and the shell
+    #[arg(long, global = true, value_name = "DIR")]
+    pub replay: Option<std::path::PathBuf>,
+
+    /// Suppress progress output (spinners and stage lines)
+    #[arg(long, short = 'q', global = true)]
+    pub quiet: bool,
+
+    /// Print plain timestamped stage lines instead of spinners (default when stderr is not a terminal)
+    #[arg(long, global = true)]
+    pub no_progress: bool,
+
+    /// Keep this run's scratch workspace (clones, build contexts) instead of removing
+    /// it at exit; its path is printed
+    #[arg(long, global = true)]
+    pub keep_workdir: bool,
+
+    /// Storage for the internal image registry when auto-setup configures it.
+    /// "pvc" provisions a PVC from the default StorageClass (if the cluster has one).
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub registry_storage: crate::setup::RegistryStorage,
+
+    /// Channel of the operator Subscription auto-setup creates (e.g. pipelines-1.15;
+    /// default from config/operator.toml, else "latest")
+    #[arg(long, global = true)]
+    pub operator_channel: Option<String>,
+
+    /// CatalogSource auto-setup installs the operator from (e.g. a pre-release catalog)
+    #[arg(long, global = true)]
+    pub operator_source: Option<String>,
+
+    /// Namespace of the --operator-source CatalogSource
+    #[arg(long, global = true)]
+    pub operator_source_namespace: Option<String>,
+
+    /// CSV auto-setup installs first (e.g. openshift-pipelines-operator-rh.v1.15.2)
+    #[arg(long, global = true)]
+    pub operator_starting_csv: Option<String>,
+
+    /// Approve the operator's InstallPlans waiting for manual approval during auto-setup
+    /// (otherwise setup stops and names them)
+    #[arg(long, global = true)]
+    pub approve_install_plans: bool,
+
+    /// Resolve --as-of dates only from the on-disk cache (and git ls-remote),
+    /// without calling the GitHub API
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// Modify a cluster even though it looks like production (see config/safety.toml)
+    #[arg(long, global = true)]
+    pub i_know_what_im_doing: bool,
+
+    /// Do not ask before patching the operator, deleting InstallerSets or binding
+    /// cluster roles (only asked on a terminal)
+    #[arg(long, short = 'y', global = true, visible_alias = "non-interactive")]
+    pub yes: bool,
+
+    /// Start cluster-modifying commands without first checking the user's permissions
+    #[arg(long, global = true)]
+    pub skip_permission_check: bool,
+
+    /// Retries for Kubernetes API calls that fail transiently (throttling, 5xx,
+    /// dropped connections), with exponential backoff; 0 disables retrying
+    #[arg(long, global = true, default_value_t = crate::k8s::DEFAULT_API_RETRIES)]
+    pub api_retries: u32,
+
+    /// Fail when ko, gauge, oc, opm, skopeo or buildah differ from their pins in
+    /// config/tools.toml (otherwise a warning)
+    #[arg(long, global = true)]
+    pub require_pinned_tools: bool,
+
+    /// release-tests repository (or fork) to clone the specs from; overrides
+    /// release_tests_repo in config/gauge.toml
+    #[arg(long, global = true, value_name = "URL")]
+    pub release_tests_repo: Option<String>,
+
+    /// Fetch repositories under PREFIX from MIRROR first (PREFIX=MIRROR, repeatable),
+    /// falling back to the original URL; adds to [mirrors] in config/git.toml
+    #[arg(long, global = true, value_name = "PREFIX=MIRROR")]
+    pub git_mirror: Vec<String>,
+
+    /// Give the release tests a namespace-scoped ServiceAccount kubeconfig
+    /// (STREAMSTRESS_SCOPED_KUBECONFIG), so specs checking non-admin permissions run
+    #[arg(long, global = true)]
+    pub scoped_user: bool,
+
+    /// ClusterRole the --scoped-user ServiceAccount gets in its namespace
+    #[arg(long, global = true, default_value = crate::scopeduser::DEFAULT_ROLE, requires = "scoped_user")]
+    pub scoped_user_role: String,
+
+    /// Components `run` builds at once (default: as many as the host's CPUs and memory
+    /// hold, about 2 CPUs and 2 GiB per build); the others wait for a slot
+    #[arg(long, global = true, value_parser = clap::value_parser!(u32).range(1..))]
+    pub build_parallel: Option<u32>,
+
+    /// Output format for check, status, deploy, konflux, and run --dry-run
+    #[arg(long, short = 'o', global = true, value_enum, default_value_t)]
+    pub output: crate::output::OutputFormat,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
+// `Run` carries every run flag; the enum is parsed once per process
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Check tool prerequisites (oc, ko, git, go)
@@ -23,6 +141,39 @@ pub enum Commands {
         /// Auto-fix issues that are marked [auto-fixable] (registry route, operator install)
         #[arg(long)]
         fix: bool,
+
+        /// Instead of the checks, verify the current user may do what these commands need
+        /// (comma-separated: setup, deploy, test, run, uninstall) and print the RBAC to grant
+        #[arg(long, value_enum, value_delimiter = ',', conflicts_with = "fix")]
+        permissions: Vec<crate::access::Command>,
+    },
+
+    /// Prepare the cluster: image registry, namespace/RBAC, operator, TektonConfig.
+    /// The same steps run automatically before other commands unless --no-auto-setup.
+    Setup {
+        /// Run only these steps (comma-separated: registry, namespace, operator, tektonconfig)
+        #[arg(long, value_enum, value_delimiter = ',', conflicts_with = "uninstall")]
+        only: Vec<crate::setup::SetupStep>,
+
+        /// Skip these steps (comma-separated)
+        #[arg(long, value_enum, value_delimiter = ',', conflicts_with = "uninstall")]
+        skip: Vec<crate::setup::SetupStep>,
+
+        /// Delete the operator Subscription and TektonConfig that setup created
+        #[arg(long)]
+        uninstall: bool,
+    },
+
+    /// Remove streamstress from the cluster: revert the operator's images, delete the
+    /// tekton-upstream namespace, in-cluster Job resources and the Konflux standalone pipeline
+    Uninstall {
+        /// Namespace `konflux --trigger` ran the standalone pipeline in
+        #[arg(long, default_value = "streamstress-test")]
+        pipeline_namespace: String,
+
+        /// Also remove the operator Subscription and TektonConfig that auto-setup created
+        #[arg(long)]
+        operator: bool,
     },
 
     /// Build Tekton component images and push to OCP internal registry
@@ -41,17 +192,48 @@ pub enum Commands {
         /// Resolves to the last commit before end-of-day UTC.
         #[arg(long, value_parser = crate::component::validate_date_format)]
         as_of: Option<String>,
+
+        /// Patches to apply to the cloned source before building
+        /// (e.g. "pipeline=fix.diff" or "pipeline=pr/123"; comma-separated, applied in order)
+        #[arg(long, value_parser = crate::patch::parse_patch_spec, value_delimiter = ',')]
+        patches: Vec<crate::patch::ComponentPatch>,
+
+        /// Build with this Go version (e.g. 1.23.4) instead of the one go.mod requires.
+        /// Selected via GOTOOLCHAIN; needs Go 1.21+ on PATH.
+        #[arg(long, value_parser = crate::gotoolchain::parse_go_version_arg)]
+        go_version: Option<crate::gotoolchain::GoVersion>,
+
+        /// Also compile against these Go versions before building (e.g. "1.22,1.23").
+        /// Any failure fails the component's build.
+        #[arg(long, value_parser = crate::gotoolchain::parse_go_version_arg, value_delimiter = ',')]
+        go_matrix: Vec<crate::gotoolchain::GoVersion>,
     },
 
     /// Deploy upstream-built images to the OpenShift Pipelines operator
+    #[command(subcommand_negates_reqs = true)]
     Deploy {
         /// Tekton component to deploy (default: pipeline)
         #[arg(long, default_value = "pipeline")]
         component: String,
 
-        /// OCP internal registry URL (e.g. default-route-openshift-image-registry.apps.example.com/tekton-ci)
+        /// Registry the images were pushed to: the OCP internal registry route
+        /// (e.g. default-route-openshift-image-registry.apps.example.com/tekton-ci)
+        /// or an external registry (e.g. quay.io/my-org)
+        #[arg(long, required = true)]
+        registry: Option<String>,
+
+        /// Docker config JSON with credentials for a private external registry.
+        /// Default: REGISTRY_USERNAME/REGISTRY_PASSWORD, else the local docker/podman login.
         #[arg(long)]
-        registry: String,
+        pull_secret: Option<String>,
+
+        /// Deploy this pullspec for an operator IMAGE_ env var instead of the image in
+        /// --registry (e.g. IMAGE_PIPELINES_WEBHOOK=quay.io/me/webhook@sha256:...). Repeatable.
+        #[arg(long = "image-override", value_name = "ENV_VAR=PULLSPEC", value_parser = crate::deploy::mapping::parse_image_override)]
+        image_overrides: Vec<crate::deploy::mapping::ImageOverride>,
+
+        #[command(subcommand)]
+        command: Option<DeployCommands>,
     },
 
     /// Run Gauge e2e tests from the release-tests repository
@@ -60,9 +242,10 @@ pub enum Commands {
         #[arg(long, default_value = "e2e")]
         tags: String,
 
-        /// Git ref for release-tests repo (branch, tag, or commit)
-        #[arg(long, default_value = "master")]
-        release_tests_ref: String,
+        /// Git ref for release-tests repo (branch, tag, or commit).
+        /// Default: the release-vX.Y branch of the installed operator's version, else master
+        #[arg(long)]
+        release_tests_ref: Option<String>,
 
         /// Output directory for logs and results
         #[arg(long, default_value = "./test-output")]
@@ -75,7 +258,8 @@ pub enum Commands {
 
     /// Build, deploy, and test multiple Tekton components in one command
     Run {
-        /// Components to process (e.g. "pipeline,triggers" or "pipeline:pr/123,triggers:v0.28.0")
+        /// Components to process (e.g. "pipeline,triggers" or "pipeline:pr/123,triggers:v0.28.0").
+        /// Groups from the [groups] table in components.toml (e.g. "core") and "all" expand in place.
         #[arg(long)]
         components: Option<String>,
 
@@ -96,17 +280,20 @@ pub enum Commands {
         #[arg(long)]
         dry_run: bool,
 
-        /// Output dry-run plan as JSON (requires --dry-run)
+        /// Output dry-run plan as JSON (requires --dry-run; same as --output json)
         #[arg(long, requires = "dry_run")]
         json: bool,
 
-        /// Gauge tags to filter tests (default: "e2e")
+        /// Gauge tags to filter tests (default: "e2e"). "auto" selects the specs of the
+        /// requested components (`auto_tags`/`test_tags` in components.toml); "impacted"
+        /// the specs affected by upstream changes since the previous run (config/impact.toml)
         #[arg(long, default_value = "e2e")]
         tags: String,
 
-        /// Git ref for release-tests repo (branch, tag, or commit)
-        #[arg(long, default_value = "master")]
-        release_tests_ref: String,
+        /// Git ref for release-tests repo (branch, tag, or commit).
+        /// Default: the release-vX.Y branch of the installed operator's version, else master
+        #[arg(long)]
+        release_tests_ref: Option<String>,
 
         /// Output directory for logs and results
         #[arg(long, default_value = "./test-output")]
@@ -120,6 +307,28 @@ pub enum Commands {
         #[arg(long, hide = true)]
         skip_build: bool,
 
+        /// Skip build and deploy and run the tests here against whatever is already
+        /// deployed, for iterating on tests. Results are not published.
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "date_range", "dry_run", "image", "image_tag", "image_builder",
+                "patches", "go_version", "go_matrix", "prune_keep", "source",
+            ]
+        )]
+        skip_deploy: bool,
+
+        /// Build and deploy from here, then stop without running tests. The cluster is
+        /// left on the upstream images, for manual exploratory testing.
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "skip_deploy", "date_range", "dry_run", "image", "image_tag", "image_builder",
+                "perf", "profile", "rbac_profile",
+            ]
+        )]
+        deploy_only: bool,
+
         /// Collect per-spec resource usage metrics during test execution
         #[arg(long)]
         profile: bool,
@@ -130,6 +339,17 @@ pub enum Commands {
         #[arg(long)]
         image: Option<String>,
 
+        /// Tag of the cached streamstress-cli image for the in-cluster Job
+        /// (default: this CLI's version). The image must already exist unless
+        /// the tag equals this CLI's version.
+        #[arg(long, conflicts_with = "image")]
+        image_tag: Option<String>,
+
+        /// How to build the CLI image when it is not cached: podman, buildah, docker,
+        /// or openshift (in-cluster binary build). Default: first tool found, else openshift.
+        #[arg(long, value_enum, default_value_t, conflicts_with = "image")]
+        image_builder: crate::incluster::ImageBuilder,
+
         /// Run performance tests from openshift-pipelines/performance repo.
         /// Can be combined with functional tests or run standalone.
         #[arg(long)]
@@ -144,16 +364,165 @@ pub enum Commands {
         /// Defaults to main.
         #[arg(long)]
         perf_ref: Option<String>,
+
+        /// Permissions granted to the in-cluster Job's ServiceAccount.
+        /// Use "minimal" where cluster-admin cannot be granted.
+        #[arg(long, value_enum, default_value_t)]
+        rbac_profile: crate::rbac::RbacProfile,
+
+        /// Patches to apply to cloned upstream source before building
+        /// (e.g. "pipeline=fix.diff,triggers=pr/123"). Recorded in results metadata.
+        #[arg(long, value_parser = crate::patch::parse_patch_spec, value_delimiter = ',', conflicts_with = "image")]
+        patches: Vec<crate::patch::ComponentPatch>,
+
+        /// Build with this Go version (e.g. 1.23.4) instead of the one go.mod requires.
+        /// Selected via GOTOOLCHAIN; needs Go 1.21+ on PATH.
+        #[arg(long, value_parser = crate::gotoolchain::parse_go_version_arg)]
+        go_version: Option<crate::gotoolchain::GoVersion>,
+
+        /// Also compile against these Go versions before building (e.g. "1.22,1.23").
+        /// Any failure fails the component's build.
+        #[arg(long, value_parser = crate::gotoolchain::parse_go_version_arg, value_delimiter = ',')]
+        go_matrix: Vec<crate::gotoolchain::GoVersion>,
+
+        /// Where component images come from: "nightly" skips the build and deploys Tekton's
+        /// published nightly images (the nightly of --as-of when available); "release" deploys
+        /// the upstream release named by the component's ref (e.g. pipeline:v0.62.0).
+        /// Per component: "pipeline=release,triggers=source". Default: source.
+        #[arg(long, value_parser = crate::imagesource::parse_source_spec, value_delimiter = ',')]
+        source: Vec<crate::imagesource::SourceSpec>,
+
+        /// Deploy this pullspec for an operator IMAGE_ env var instead of building it
+        /// (e.g. IMAGE_PIPELINES_WEBHOOK=quay.io/me/webhook@sha256:...). Repeatable.
+        #[arg(long = "image-override", value_name = "ENV_VAR=PULLSPEC", value_parser = crate::deploy::mapping::parse_image_override)]
+        image_overrides: Vec<crate::deploy::mapping::ImageOverride>,
+
+        /// Before building, prune imagestreams in the tekton-upstream namespace to the
+        /// N most recent tags and revisions per image
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        prune_keep: Option<u32>,
+
+        /// Reuse release-tests' Go modules across in-cluster Jobs instead of downloading
+        /// them in every Job: "image" bakes them into the CLI image for --release-tests-ref,
+        /// "pvc" keeps module and build caches on a PersistentVolumeClaim.
+        #[arg(long, value_enum, default_value_t, conflicts_with_all = ["skip_deploy", "deploy_only"])]
+        go_mod_cache: crate::incluster::GoModCache,
+
+        /// GOPROXY for the gauge Go runner in the in-cluster Job (e.g. an internal mirror)
+        #[arg(long, conflicts_with_all = ["skip_deploy", "deploy_only"])]
+        goproxy: Option<String>,
+
+        /// Take the cluster lock even if another run holds it (e.g. one that was killed)
+        #[arg(long, conflicts_with = "date_range")]
+        force_unlock: bool,
+
+        /// When chains is deployed, sign a sample TaskRun and verify the signature or
+        /// attestation with cosign before the tests; reported as a synthetic test
+        #[arg(long, conflicts_with_all = ["date_range", "deploy_only", "dry_run"])]
+        verify_chains: bool,
+
+        /// When pipelines-as-code is deployed, push a commit to this GitHub test repository
+        /// (owner/repo) and check PaC triggers a PipelineRun that succeeds; reported as a
+        /// synthetic test. Needs a token with repo and admin:repo_hook scopes
+        #[arg(long, value_name = "OWNER/REPO", conflicts_with_all = ["date_range", "deploy_only", "dry_run"])]
+        pac_smoke: Option<String>,
+
+        /// Skip the EventListener probe that runs before the tests when triggers is deployed
+        #[arg(long)]
+        skip_triggers_probe: bool,
+
+        /// Skip waiting for the pipelines webhook to admit a dry-run PipelineRun
+        /// before the tests
+        #[arg(long)]
+        skip_admission_probe: bool,
+
+        /// After the tests, report namespaces, PipelineRuns, TaskRuns and PVCs created
+        /// since before the tests and still present (results/cluster-leaks.json)
+        #[arg(long, conflicts_with_all = ["date_range", "deploy_only", "dry_run"])]
+        check_leaks: bool,
+
+        /// When some component builds fail, deploy and test the ones that built instead of
+        /// aborting. Specs tagged for a failed component (`test_tags` in components.toml)
+        /// are skipped, and the failed builds are recorded in results metadata
+        #[arg(long, conflicts_with_all = ["date_range", "image", "dry_run"])]
+        continue_on_build_failure: bool,
+
+        /// Where to build the components: local (clone and build here, pushing through the
+        /// registry route) or cluster (the in-cluster Job clones and builds with its ko and Go,
+        /// pushing to the internal registry directly). Not for docker-built components or
+        /// patches from local files
+        #[arg(long, value_enum, default_value_t, conflicts_with_all = ["deploy_only", "skip_deploy", "image"])]
+        build_where: crate::build::BuildWhere,
+
+        /// Components whose build failed in the run that created this Job (used by in-cluster Jobs)
+        #[arg(long, hide = true, value_delimiter = ',')]
+        failed_builds: Vec<String>,
+
+        /// Attach metadata to the run (e.g. ocp=4.17, cloud=aws, team=triggers). Recorded in
+        /// results metadata and the published run; repeatable
+        #[arg(long, value_name = "KEY=VALUE", value_parser = crate::runmeta::parse_meta)]
+        meta: Vec<crate::runmeta::MetaPair>,
+    },
+
+    /// Run the same components across OCP versions and operator release branches
+    Matrix {
+        /// OCP versions to test on (e.g. "4.16,4.17"); each needs a --context
+        #[arg(long, required = true, value_delimiter = ',')]
+        ocp: Vec<String>,
+
+        /// Operator release branches to test (e.g. "release-v1.17,release-v1.18"). Each
+        /// cell tests with this release-tests branch and, when auto-setup installs the
+        /// operator, its pipelines-X.Y channel
+        #[arg(long, required = true, value_delimiter = ',')]
+        operator_branch: Vec<String>,
+
+        /// Kube context of the cluster running an OCP version (e.g. 4.17=ci-417). Repeatable
+        #[arg(long = "context", value_name = "OCP=CONTEXT", value_parser = crate::matrix::parse_context)]
+        contexts: Vec<crate::matrix::ContextSpec>,
+
+        /// Components to process in every cell (same syntax as `run --components`)
+        #[arg(long)]
+        components: Option<String>,
+
+        /// Gauge tags to filter tests (default: "e2e")
+        #[arg(long, default_value = "e2e")]
+        tags: String,
+
+        /// Output directory: one subdirectory per cell plus matrix-summary.json
+        #[arg(long, default_value = "./matrix-output")]
+        output_dir: String,
+
+        /// Attach metadata to every run of the matrix (e.g. team=triggers); repeatable
+        #[arg(long, value_name = "KEY=VALUE", value_parser = crate::runmeta::parse_meta)]
+        meta: Vec<crate::runmeta::MetaPair>,
+
+        /// Publish every cell with results to the dashboard when the matrix finishes, and
+        /// matrix-summary.json to matrix/<matrix id>.json on gh-pages. Without it, publish
+        /// each cell with `streamstress publish --output-dir <output-dir>/<cell>`
+        #[arg(long)]
+        publish: bool,
+
+        /// Git remote URL for --publish (default: origin URL of current repo)
+        #[arg(long, requires = "publish")]
+        remote: Option<String>,
+
+        /// Human-readable label for the published runs
+        #[arg(long, requires = "publish")]
+        label: Option<String>,
     },
 
     /// Re-analyze test results from a previous run
     Results {
         /// Directory containing test output (logs/ and results/ subdirs)
-        #[arg(long, default_value = "./test-output")]
+        #[arg(long, default_value = "./test-output", global = true)]
         output_dir: String,
+
+        #[command(subcommand)]
+        command: Option<ResultsCommands>,
     },
 
-    /// Show status of running/completed streamstress Jobs
+    /// Show status of running/completed streamstress Jobs, the cluster lock, and the
+    /// readiness of TektonConfig, TektonPipeline and TektonTrigger
     Status,
 
     /// Stream logs from a streamstress Job pod
@@ -163,11 +532,27 @@ pub enum Commands {
         job: Option<String>,
     },
 
+    /// Live CPU/memory per pod in the tekton namespaces, with deltas and peaks
+    Top {
+        /// Namespace to watch (repeatable)
+        #[arg(long = "namespace", short = 'n', default_values_t = ["openshift-pipelines".to_string()])]
+        namespaces: Vec<String>,
+
+        /// Seconds between polls
+        #[arg(long, default_value = "5", value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+
+        /// Stop after this many polls (default: until interrupted)
+        #[arg(long, default_value = "0")]
+        count: u32,
+    },
+
     /// Build Konflux-compatible SNAPSHOT and optionally trigger standalone release-test-pipeline
+    #[command(subcommand_negates_reqs = true)]
     Konflux {
         /// External registry for pushing images (e.g. quay.io/streamstress)
-        #[arg(long)]
-        registry: String,
+        #[arg(long, required = true)]
+        registry: Option<String>,
 
         /// Operator repo branch to clone (e.g. main, release-v1.16)
         #[arg(long, default_value = "main")]
@@ -194,6 +579,11 @@ pub enum Commands {
         #[arg(long)]
         trigger: bool,
 
+        /// Don't smoke-test the built FBC index on the cluster (CatalogSource, package
+        /// channel, Subscription resolution) before generating the SNAPSHOT
+        #[arg(long)]
+        skip_catalog_smoke: bool,
+
         /// Namespace to run the pipeline in
         #[arg(long, default_value = "streamstress-test")]
         pipeline_namespace: String,
@@ -201,6 +591,40 @@ pub enum Commands {
         /// Timeout in seconds for pipeline completion (default: 3600 = 1 hour)
         #[arg(long, default_value = "3600")]
         timeout: u64,
+
+        /// How to build and push the bundle and FBC index images: buildah, or openshift
+        /// (binary BuildConfig in openshift-pipelines, for hosts without buildah such as
+        /// macOS). Default: buildah if found, else openshift.
+        #[arg(long, value_enum, default_value_t)]
+        bundle_builder: crate::bundle::BundleBuilder,
+
+        /// Sign snapshot.json with cosign into snapshot.json.sigstore.json.
+        /// Keyless unless --sign-key is given
+        #[arg(long)]
+        sign: bool,
+
+        /// cosign key reference to sign the SNAPSHOT with
+        #[arg(long, requires = "sign")]
+        sign_key: Option<String>,
+
+        /// Run the builds, SNAPSHOT generation and --trigger in a streamstress Job
+        /// in openshift-pipelines instead of on this machine (see `status`, `logs`).
+        /// Registry credentials are copied from the local auth file into a Secret
+        #[arg(long)]
+        in_cluster: bool,
+
+        /// How to build the CLI image for --in-cluster when it is not cached: podman, buildah,
+        /// docker, or openshift (in-cluster binary build). Default: first tool found, else openshift.
+        #[arg(long, value_enum, default_value_t, requires = "in_cluster")]
+        image_builder: crate::incluster::ImageBuilder,
+
+        /// Attach metadata to the run (e.g. ocp=4.17, cloud=aws). Recorded in
+        /// results/metadata.json and the published run; repeatable
+        #[arg(long, value_name = "KEY=VALUE", value_parser = crate::runmeta::parse_meta)]
+        meta: Vec<crate::runmeta::MetaPair>,
+
+        #[command(subcommand)]
+        command: Option<KonfluxCommands>,
     },
 
     /// Publish test results to gh-pages branch for dashboard
@@ -216,5 +640,325 @@ pub enum Commands {
         /// Human-readable label for this run
         #[arg(long)]
         label: Option<String>,
+
+        /// Id of the published run (default: run-<timestamp>-<random suffix>);
+        /// refused if the dashboard already has a run with this id
+        #[arg(long, value_parser = crate::publish::parse_run_id)]
+        run_id: Option<String>,
+
+        /// Publish through the GitHub API (gh, using GITHUB_TOKEN) instead of cloning gh-pages
+        #[arg(long, conflicts_with = "dry_run")]
+        api: bool,
+
+        /// Write the would-be gh-pages tree to --dry-run-dir instead of pushing
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Directory for the --dry-run gh-pages tree (must be empty or absent)
+        #[arg(long, default_value = "./gh-pages-preview", requires = "dry_run")]
+        dry_run_dir: String,
+
+        /// Publish the run data as is, without redacting tokens, identities and
+        /// cluster hostnames (see config/scrub.toml)
+        #[arg(long)]
+        no_scrub: bool,
+
+        /// Sign the run file with cosign and publish the sigstore bundle next to it
+        /// (runs/<id>.json.sigstore.json). Keyless unless --sign-key is given
+        #[arg(long)]
+        sign: bool,
+
+        /// cosign key reference to sign with (file, KMS URI or k8s://namespace/secret)
+        #[arg(long, requires = "sign")]
+        sign_key: Option<String>,
+
+        /// Attach metadata to the published run, over the run's own --meta
+        /// (e.g. cloud=aws); repeatable
+        #[arg(long, value_name = "KEY=VALUE", value_parser = crate::runmeta::parse_meta)]
+        meta: Vec<crate::runmeta::MetaPair>,
+
+        #[command(subcommand)]
+        command: Option<PublishCommands>,
+    },
+
+    /// Verify the sigstore signature of a published run file or a Konflux SNAPSHOT
+    VerifyRun {
+        /// Run JSON (e.g. runs/<id>.json from gh-pages) or snapshot.json to verify
+        file: String,
+
+        /// Signature bundle (default: <file>.sigstore.json)
+        #[arg(long)]
+        bundle: Option<String>,
+
+        /// Public key the file was signed with
+        #[arg(long, conflicts_with_all = ["certificate_identity", "certificate_oidc_issuer"])]
+        key: Option<String>,
+
+        /// Identity in the keyless signing certificate (e.g. the publishing workflow's URL)
+        #[arg(long, requires = "certificate_oidc_issuer")]
+        certificate_identity: Option<String>,
+
+        /// OIDC issuer of the keyless signing certificate
+        /// (e.g. https://token.actions.githubusercontent.com)
+        #[arg(long, requires = "certificate_identity")]
+        certificate_oidc_issuer: Option<String>,
+    },
+
+    /// Download the most recent published run matching a baseline selector into an
+    /// output directory layout, for comparing a run against it
+    Baseline {
+        /// latest, latest-green, label:<label>, meta:<key>=<value> or id:<run id>
+        #[arg(value_parser = crate::baseline::parse_baseline)]
+        baseline: crate::baseline::Baseline,
+
+        /// Where to put the run (default: the streamstress cache, reused across calls)
+        #[arg(long)]
+        output_dir: Option<String>,
+
+        /// Repository the dashboard is published to (default: as for `publish --api`)
+        #[arg(long)]
+        remote: Option<String>,
+    },
+
+    /// Pack a test output directory (results, logs, metadata) into a checksummed tarball,
+    /// e.g. to carry a run out of a disconnected lab
+    ExportBundle {
+        /// Directory containing test output (logs/ and results/ subdirs)
+        #[arg(long, default_value = "./test-output")]
+        output_dir: String,
+
+        /// Bundle to write (default: streamstress-bundle-<run id>.tar.gz)
+        #[arg(long)]
+        file: Option<String>,
+    },
+
+    /// Verify and unpack a bundle from `export-bundle`, then optionally re-analyze or publish it
+    ImportBundle {
+        /// Bundle to import
+        bundle: String,
+
+        /// Directory to unpack the run into (must be empty or absent)
+        #[arg(long, default_value = "./imported-run")]
+        output_dir: String,
+
+        /// Re-parse and re-categorize the results, as `streamstress results` does
+        #[arg(long)]
+        analyze: bool,
+
+        /// Publish the run to the dashboard, as `streamstress publish` does
+        #[arg(long)]
+        publish: bool,
+
+        /// Git remote URL for --publish (default: origin URL of current repo)
+        #[arg(long, requires = "publish")]
+        remote: Option<String>,
+
+        /// Human-readable label for the published run
+        #[arg(long, requires = "publish")]
+        label: Option<String>,
+    },
+
+    /// Preview the results dashboard locally
+    Dashboard {
+        #[command(subcommand)]
+        command: DashboardCommands,
+    },
+
+    /// Inspect the RBAC used by in-cluster Jobs
+    Rbac {
+        #[command(subcommand)]
+        command: RbacCommands,
+    },
+
+    /// Work with the resource profiles of `--profile` runs
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommands,
+    },
+
+    /// Email run summaries over SMTP (config/email.toml, SMTP_USERNAME / SMTP_PASSWORD)
+    Email {
+        #[command(subcommand)]
+        command: EmailCommands,
+    },
+
+    /// Replace this binary with a release build from GitHub (via gh)
+    SelfUpdate {
+        /// Release version to install (default: latest)
+        #[arg(long)]
+        version: Option<String>,
+
+        /// Only print the current and available versions
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ResultsCommands {
+    /// Print the gauge command that re-runs only the failed scenarios, or run it
+    RerunFailed {
+        /// Run the failed scenarios against the current deployment (no build or deploy)
+        /// instead of printing the command
+        #[arg(long)]
+        execute: bool,
+
+        /// release-tests git ref to run with --execute
+        #[arg(long, default_value = "master")]
+        release_tests_ref: String,
+
+        /// Where --execute writes the delta results (default: <output-dir>/rerun)
+        #[arg(long)]
+        rerun_dir: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PublishCommands {
+    /// Remove a published run from the dashboard (manifest entry, run file, indexes)
+    Delete {
+        /// Id of the run (e.g. run-20250301T120000Z)
+        #[arg(long)]
+        run_id: String,
+
+        /// Git remote URL (default: origin URL of current repo)
+        #[arg(long)]
+        remote: Option<String>,
+
+        /// Update gh-pages through the GitHub API (gh, using GITHUB_TOKEN) instead of cloning it
+        #[arg(long)]
+        api: bool,
+    },
+    /// Change the label of a published run
+    Relabel {
+        /// Id of the run (e.g. run-20250301T120000Z)
+        #[arg(long)]
+        run_id: String,
+
+        /// New label
+        #[arg(long)]
+        label: String,
+
+        /// Git remote URL (default: origin URL of current repo)
+        #[arg(long)]
+        remote: Option<String>,
+
+        /// Update gh-pages through the GitHub API (gh, using GITHUB_TOKEN) instead of cloning it
+        #[arg(long)]
+        api: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DeployCommands {
+    /// Write the operator's IMAGE_ env vars and the InstallerSet images to a file
+    ExportState {
+        /// State file to write
+        file: String,
+    },
+
+    /// Re-apply a state file from `export-state` (e.g. to reproduce another cluster)
+    ImportState {
+        /// State file to read
+        file: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DashboardCommands {
+    /// Serve a gh-pages tree (e.g. from `publish --dry-run`) on localhost
+    Serve {
+        /// Directory containing the gh-pages tree
+        #[arg(long, default_value = "./gh-pages-preview")]
+        dir: String,
+
+        /// Port to listen on
+        #[arg(long, default_value = "8080")]
+        port: u16,
+
+        /// Address to bind (keep on loopback unless you mean to share the preview)
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RbacCommands {
+    /// Print the ServiceAccount/ClusterRole/ClusterRoleBinding manifests for review
+    Print {
+        /// RBAC profile to render
+        #[arg(long, value_enum, default_value = "minimal")]
+        profile: crate::rbac::RbacProfile,
+
+        /// Namespace the in-cluster Job runs in
+        #[arg(long, default_value = "openshift-pipelines")]
+        namespace: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum KonfluxCommands {
+    /// Print the standalone pipeline `--trigger` would apply, with the changes made
+    /// to the operator's release-test-pipeline.yaml on stderr
+    Render {
+        /// Operator repo checkout to read .konflux/tekton/release-test-pipeline.yaml from
+        #[arg(long, default_value = "./konflux-output/operator")]
+        operator_dir: String,
+
+        /// Pipeline YAML to transform instead of the operator checkout's
+        #[arg(long, conflicts_with = "operator_dir")]
+        file: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum EmailCommands {
+    /// Email the summary of a run now, whatever the configured mode
+    Send {
+        /// Directory containing test output (logs/ and results/ subdirs)
+        #[arg(long, default_value = "./test-output")]
+        output_dir: String,
+
+        /// Human-readable label for the run, added to the subject
+        #[arg(long)]
+        label: Option<String>,
+
+        /// Print the message instead of sending it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Send the summaries queued by `mode = "digest"` as one email and empty the queue
+    Digest {
+        /// Print the message instead of sending it (the queue is kept)
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileCommands {
+    /// Compare the resource profiles of two runs: per-spec CPU/memory deltas, new top
+    /// consumers and baseline drift. Exits 1 when a spec regressed
+    Diff {
+        /// Output directory (or resource-profile.json) of the reference run. With
+        /// --baseline, the run to compare with the baseline
+        run_a: String,
+
+        /// Output directory (or resource-profile.json) of the run to compare
+        #[arg(required_unless_present = "baseline", conflicts_with = "baseline")]
+        run_b: Option<String>,
+
+        /// Flag specs whose p95 CPU or memory grew by more than this many percent
+        #[arg(long, default_value_t = 20.0)]
+        threshold: f64,
+
+        /// Use the most recent matching published run as the reference run, downloaded
+        /// from gh-pages: latest, latest-green, label:<label>, meta:<key>=<value> or id:<run id>
+        #[arg(long, value_parser = crate::baseline::parse_baseline)]
+        baseline: Option<crate::baseline::Baseline>,
+
+        /// Repository the dashboard is published to (default: as for `publish --api`)
+        #[arg(long, requires = "baseline")]
+        remote: Option<String>,
     },
 }
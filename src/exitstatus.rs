@@ -0,0 +1,50 @@
+//! How a command ended. Command handlers return an `ExitStatus` up to `main`,
+//! the only place that exits the process, so the run's workspace, locks and
+//! profilers are released on every path before the exit code is set.
+
+/// Outcome of a command; `code` gives the exit codes listed in the README.
+/// Ordered by severity, so `combine` keeps the worse of two outcomes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ExitStatus {
+    /// All tests passed, or the command succeeded
+    Success,
+    /// Some tests failed (or a check, verification or comparison did)
+    Failure,
+    /// Build or infrastructure error
+    Error,
+}
+
+impl ExitStatus {
+    pub fn code(self) -> i32 {
+        match self {
+            ExitStatus::Success => 0,
+            ExitStatus::Failure => 1,
+            ExitStatus::Error => 2,
+        }
+    }
+
+    /// Success if `passed`, else Failure.
+    pub fn from_passed(passed: bool) -> Self {
+        if passed { ExitStatus::Success } else { ExitStatus::Failure }
+    }
+
+    /// The worse of two outcomes (e.g. functional and performance tests):
+    /// an error wins over a failure.
+    pub fn combine(self, other: Self) -> Self {
+        self.max(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_status() {
+        assert_eq!(ExitStatus::Success.combine(ExitStatus::Success), ExitStatus::Success);
+        assert_eq!(ExitStatus::Success.combine(ExitStatus::Failure), ExitStatus::Failure);
+        assert_eq!(ExitStatus::Error.combine(ExitStatus::Failure), ExitStatus::Error);
+        assert_eq!(ExitStatus::from_passed(true).code(), 0);
+        assert_eq!(ExitStatus::from_passed(false).code(), 1);
+    }
+}
@@ -1,96 +1,278 @@
-mod batch;
-mod build;
-mod bundle;
-mod callback;
-mod check;
 mod cli;
-mod component;
-mod config;
-mod deploy;
-mod dryrun;
-mod exec;
-mod github;
-mod incluster;
-mod k8s;
-mod konflux;
-mod perf;
-mod profile;
-mod progress;
-mod publish;
-mod registry;
-mod results;
-mod setup;
-mod snapshot;
-mod test;
-mod types;
+mod exitstatus;
+
+use ocp_midstreamer_lib::{
+    access, admission, audit, baseline, batch, build, bundle, callback, cassette, catalogsmoke, chains, check,
+    component, config, confirm, dashboard, deploy, dryrun, email, github, gitops, gotoolchain, imagesource,
+    imagestream, impact, incluster, k8s, konflux, leaks, lock, logging, matrix, output, pac, patch, perf, platform,
+    profile, profilediff, progress, publish, rbac, refcheck, registry, results, runbundle, runmeta, safety,
+    scopeduser, selfupdate, setup, signing, snapshot, test, testref, timestamp, tools, top, triggers, uninstall,
+    warnings, workspace,
+};
 
 use clap::Parser;
-use cli::{Cli, Commands};
+use cli::{Cli, Commands, DashboardCommands, DeployCommands, EmailCommands, KonfluxCommands, ProfileCommands, PublishCommands, RbacCommands, ResultsCommands};
+use exitstatus::ExitStatus;
 
 #[tokio::main]
 async fn main() {
-    let cli = Cli::parse();
+    let status = run(Cli::parse()).await;
+    workspace::cleanup();
+    std::process::exit(status.code())
+}
+
+/// Run the command of `cli`. Everything the command holds (scratch directories,
+/// locks, profilers) is released by the time it returns.
+async fn run(cli: Cli) -> ExitStatus {
+    if let Err(e) = logging::init(cli.verbose, cli.log.as_deref()) {
+        eprintln!("Error: {e:#}");
+        return ExitStatus::Error;
+    }
+    progress::init(cli.quiet, cli.no_progress);
+    confirm::init(cli.yes);
+    let mut operator = match config::load_operator_config(&config::default_operator_config_path()) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Error: {e:#}");
+            return ExitStatus::Error;
+        }
+    };
+    if let Some(c) = &cli.operator_channel {
+        operator.channel = c.clone();
+    }
+    if let Some(s) = &cli.operator_source {
+        operator.source = s.clone();
+    }
+    if let Some(ns) = &cli.operator_source_namespace {
+        operator.source_namespace = ns.clone();
+    }
+    if let Some(csv) = &cli.operator_starting_csv {
+        operator.starting_csv = Some(csv.clone());
+    }
+    if cli.approve_install_plans {
+        operator.approve_install_plans = true;
+    }
+    setup::init(cli.registry_storage, operator);
+    github::init(cli.offline);
+    k8s::init(cli.api_retries);
+    build::init(cli.build_parallel.map(|n| n as usize));
+    tools::init(cli.require_pinned_tools);
+    scopeduser::init(cli.scoped_user, &cli.scoped_user_role);
+    if let Err(e) = testref::init(cli.release_tests_repo.as_deref()) {
+        eprintln!("Error: {e:#}");
+        return ExitStatus::Error;
+    }
+    if let Err(e) = gitops::init(&cli.git_mirror) {
+        eprintln!("Error: {e:#}");
+        return ExitStatus::Error;
+    }
+    workspace::init(cli.keep_workdir);
+
+    if let Some(ref path) = cli.audit_log && let Err(e) = audit::init(path) {
+        eprintln!("Error: {e:#}");
+        return ExitStatus::Error;
+    }
+    let cassette = match (&cli.record, &cli.replay) {
+        (Some(dir), _) => Some((cassette::Mode::Record, dir)),
+        (None, Some(dir)) => Some((cassette::Mode::Replay, dir)),
+        (None, None) => None,
+    };
+    if let Some((mode, dir)) = cassette && let Err(e) = cassette::init(mode, dir) {
+        eprintln!("Error: {e:#}");
+        return ExitStatus::Error;
+    }
+
+    // Inside a streamstress Job: report our version for `status`/`logs` to compare
+    if incluster::is_incluster()
+        && let Ok(job_name) = std::env::var("JOB_NAME")
+        && let Err(e) = incluster::record_job_version(&job_name).await
+    {
+        warnings::warn(format!("Could not record Job version: {e:#}"));
+    }
 
     match cli.command {
-        Commands::Check { fix } => {
-            match check::run_check(cli.verbose) {
+        Commands::Check { fix: _, permissions } if !permissions.is_empty() => {
+            let result = tokio::task::spawn_blocking(move || {
+                access::missing(&access::requirements(&permissions)).map(|m| (m, access::current_user()))
+            }).await.expect("spawn_blocking panicked");
+            match result {
+                Ok((missing, _)) if missing.is_empty() => {
+                    eprintln!("All permissions the commands need are granted.");
+                    return ExitStatus::Success;
+                }
+                Ok((missing, user)) => {
+                    access::print_report(&missing);
+                    match access::render_grant_yaml(&missing, &user) {
+                        Ok(yaml) => {
+                            eprintln!("\nRBAC granting them to {} (apply as cluster admin):", user);
+                            print!("{}", yaml);
+                        }
+                        Err(e) => eprintln!("Error: {e:#}"),
+                    }
+                    return ExitStatus::Failure;
+                }
+                Err(e) => {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
+                }
+            }
+        }
+        Commands::Check { fix, permissions: _ } => {
+            match check::run_check(cli.output) {
                 Ok(true) => {
                     if fix {
                         eprintln!("\nAll checks passed, nothing to fix.");
                     }
-                    std::process::exit(0);
+                    return ExitStatus::Success;
                 }
                 Ok(false) => {
                     if fix {
+                        if let Err(status) = guard_cluster("run auto-setup", cli.i_know_what_im_doing).await {
+                            return status;
+                        }
                         eprintln!("\nRunning auto-setup to fix issues...");
                         let result = tokio::task::spawn_blocking(|| {
                             setup::run_auto_setup()
                         }).await.expect("spawn_blocking panicked");
                         if let Err(e) = result {
                             eprintln!("Auto-setup error: {e:#}");
-                            std::process::exit(2);
+                            return ExitStatus::Error;
                         }
-                        std::process::exit(0);
+                        return ExitStatus::Success;
                     }
-                    std::process::exit(1);
+                    return ExitStatus::Failure;
+                }
+                Err(e) => {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
+                }
+            }
+        }
+        Commands::Setup { only, skip, uninstall } => {
+            let action = if uninstall { "uninstall the operator" } else { "run setup" };
+            if let Err(status) = guard_cluster(action, cli.i_know_what_im_doing).await {
+                return status;
+            }
+            if !uninstall
+                && let Err(status) = preflight_permissions(vec![access::Command::Setup], cli.skip_permission_check).await
+            {
+                return status;
+            }
+            let result = tokio::task::spawn_blocking(move || {
+                if uninstall {
+                    setup::uninstall().map(|()| true)
+                } else {
+                    setup::run_setup(&only, &skip)
+                }
+            }).await.expect("spawn_blocking panicked");
+            match result {
+                Ok(passed) => return ExitStatus::from_passed(passed),
+                Err(e) => {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
                 }
+            }
+        }
+        Commands::Uninstall { pipeline_namespace, operator } => {
+            if let Err(status) = guard_cluster("uninstall streamstress", cli.i_know_what_im_doing).await {
+                return status;
+            }
+            if let Err(status) = preflight_permissions(vec![access::Command::Uninstall], cli.skip_permission_check).await {
+                return status;
+            }
+            let result = tokio::task::spawn_blocking(move || {
+                uninstall::run_uninstall(&pipeline_namespace, operator)
+            }).await.expect("spawn_blocking panicked");
+            match result {
+                Ok(passed) => return ExitStatus::from_passed(passed),
                 Err(e) => {
                     eprintln!("Error: {e:#}");
-                    std::process::exit(2);
+                    return ExitStatus::Error;
                 }
             }
         }
-        Commands::Build { component, registry, as_of: _ } => {
+        Commands::Build { component, registry, as_of: _, patches, go_version, go_matrix } => {
+            let go = gotoolchain::GoOptions { version: go_version, matrix: go_matrix };
+            if let Err(status) = verify_tools() {
+                return status;
+            }
             if !cli.no_auto_setup {
+                if let Err(status) = guard_cluster("run auto-setup", cli.i_know_what_im_doing).await {
+                    return status;
+                }
                 let result = tokio::task::spawn_blocking(|| {
                     setup::run_auto_setup()
                 }).await;
                 match result {
                     Ok(Ok(())) => {}
-                    Ok(Err(e)) => eprintln!("WARNING: Auto-setup had errors: {e:#}"),
-                    Err(e) => eprintln!("WARNING: Auto-setup panicked: {e}"),
+                    Ok(Err(e)) => warnings::warn(format!("Auto-setup had errors: {e:#}")),
+                    Err(e) => warnings::warn(format!("Auto-setup panicked: {e}")),
                 }
             }
-            match run_build(&component, registry.as_deref()) {
-                Ok(_) => std::process::exit(0),
+            match run_build(&component, registry.as_deref(), &patches, &go) {
+                Ok(_) => return ExitStatus::Success,
                 Err(e) => {
                     eprintln!("Error: {e:#}");
-                    std::process::exit(2);
+                    return ExitStatus::Error;
+                }
+            }
+        }
+        Commands::Deploy { command: Some(command), .. } => {
+            if matches!(command, DeployCommands::ImportState { .. })
+                && let Err(status) = guard_cluster("import deploy state", cli.i_know_what_im_doing).await
+            {
+                return status;
+            }
+            let verbose = cli.verbose > 0;
+            let format = cli.output;
+            let result = tokio::task::spawn_blocking(move || match command {
+                DeployCommands::ExportState { file } => deploy::state::export_state(std::path::Path::new(&file))
+                    .and_then(|state| if format.is_structured() { output::print(format, &state) } else { Ok(()) }),
+                DeployCommands::ImportState { file } => deploy::state::import_state(std::path::Path::new(&file), verbose)
+                    .and_then(|report| if format.is_structured() { output::print(format, &report) } else { Ok(()) }),
+            }).await;
+            match result {
+                Ok(Ok(())) => return ExitStatus::Success,
+                Ok(Err(e)) => {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
+                }
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    return ExitStatus::Error;
                 }
             }
         }
         Commands::Deploy {
             component,
             registry,
+            pull_secret,
+            image_overrides,
+            command: None,
         } => {
+            // clap requires --registry unless a subcommand is given
+            let Some(registry) = registry else {
+                eprintln!("Error: --registry is required");
+                return ExitStatus::Error;
+            };
+            if let Err(status) = guard_cluster("deploy", cli.i_know_what_im_doing).await {
+                return status;
+            }
+            let mut needed = vec![access::Command::Deploy];
+            if !cli.no_auto_setup {
+                needed.insert(0, access::Command::Setup);
+            }
+            if let Err(status) = preflight_permissions(needed, cli.skip_permission_check).await {
+                return status;
+            }
             if !cli.no_auto_setup {
                 let result = tokio::task::spawn_blocking(|| {
                     setup::run_auto_setup()
                 }).await;
                 match result {
                     Ok(Ok(())) => {}
-                    Ok(Err(e)) => eprintln!("WARNING: Auto-setup had errors: {e:#}"),
-                    Err(e) => eprintln!("WARNING: Auto-setup panicked: {e}"),
+                    Ok(Err(e)) => warnings::warn(format!("Auto-setup had errors: {e:#}")),
+                    Err(e) => warnings::warn(format!("Auto-setup panicked: {e}")),
                 }
             }
             // Placeholder: in production, built_images comes from the build phase output.
@@ -99,23 +281,29 @@ async fn main() {
                 Ok(names) => names,
                 Err(e) => {
                     eprintln!("Error: {e:#}");
-                    std::process::exit(2);
+                    return ExitStatus::Error;
                 }
             };
             eprintln!("Note: using image names from config (placeholder until build phase integration)");
-            let verbose = cli.verbose;
+            let verbose = cli.verbose > 0;
             let result = tokio::task::spawn_blocking(move || {
-                deploy::run_deploy(&component, &registry, &built_images, verbose)
+                deploy::run_deploy(&component, &registry, &built_images, &image_overrides, pull_secret.as_deref(), verbose)
             }).await;
             match result {
-                Ok(Ok(_)) => std::process::exit(0),
+                Ok(Ok(report)) => {
+                    if cli.output.is_structured() && let Err(e) = output::print(cli.output, &report) {
+                        eprintln!("Error: {e:#}");
+                        return ExitStatus::Error;
+                    }
+                    return ExitStatus::Success;
+                }
                 Ok(Err(e)) => {
                     eprintln!("Error: {e:#}");
-                    std::process::exit(2);
+                    return ExitStatus::Error;
                 }
                 Err(e) => {
                     eprintln!("Error: {e}");
-                    std::process::exit(2);
+                    return ExitStatus::Error;
                 }
             }
         }
@@ -125,12 +313,18 @@ async fn main() {
             output_dir,
             profile,
         } => {
-            match test::run_tests(&tags, &release_tests_ref, std::path::Path::new(&output_dir), cli.verbose, profile).await {
-                Ok(true) => std::process::exit(0),
-                Ok(false) => std::process::exit(1),
+            if let Err(status) = preflight_permissions(vec![access::Command::Test], cli.skip_permission_check).await {
+                return status;
+            }
+            if let Err(status) = verify_tools() {
+                return status;
+            }
+            let release_tests = resolve_release_tests_ref(release_tests_ref).await;
+            match test::run_tests(&tags, &release_tests.git_ref, std::path::Path::new(&output_dir), profile, &[]).await {
+                Ok(passed) => return ExitStatus::from_passed(passed),
                 Err(e) => {
                     eprintln!("Error: {e:#}");
-                    std::process::exit(2);
+                    return ExitStatus::Error;
                 }
             }
         }
@@ -145,35 +339,108 @@ async fn main() {
             output_dir,
             registry,
             skip_build,
+            skip_deploy,
+            deploy_only,
             profile,
             image,
+            image_tag,
+            image_builder,
             perf,
             perf_scenario,
             perf_ref,
+            rbac_profile,
+            patches,
+            go_version,
+            go_matrix,
+            prune_keep,
+            source,
+            image_overrides,
+            go_mod_cache,
+            goproxy,
+            force_unlock,
+            verify_chains,
+            pac_smoke,
+            skip_triggers_probe,
+            skip_admission_probe,
+            check_leaks,
+            continue_on_build_failure,
+            failed_builds,
+            build_where,
+            meta,
         } => {
+            runmeta::init(&meta);
+            if !dry_run && let Err(status) = verify_tools() {
+                return status;
+            }
+            // Dry runs only touch the cluster through auto-setup
+            if (!dry_run || !cli.no_auto_setup)
+                && let Err(status) = guard_cluster("run", cli.i_know_what_im_doing).await
+            {
+                return status;
+            }
+            let mut needed = Vec::new();
+            if !cli.no_auto_setup {
+                needed.push(access::Command::Setup);
+            }
+            if !dry_run {
+                needed.push(access::Command::Run);
+            }
+            if let Err(status) = preflight_permissions(needed, cli.skip_permission_check).await {
+                return status;
+            }
+            let go = gotoolchain::GoOptions { version: go_version, matrix: go_matrix };
+            let job_go_env = incluster::JobGoEnv { mod_cache: go_mod_cache, goproxy };
+            let prune_keep = prune_keep.map(|k| k as usize);
+            // Without --release-tests-ref, test with the branch of the installed operator
+            // (not needed to print a plan or to deploy only)
+            let release_tests = if dry_run || deploy_only {
+                testref::ReleaseTestsRef::given(release_tests_ref.as_deref())
+            } else {
+                resolve_release_tests_ref(release_tests_ref).await
+            };
+            let opts = RunOptions {
+                release_tests: &release_tests,
+                output_dir: &output_dir,
+                registry_override: registry.as_deref(),
+                verbose: cli.verbose > 0,
+                profile,
+                no_auto_setup: cli.no_auto_setup,
+                dry_run,
+                format: if json { output::OutputFormat::Json } else { cli.output },
+                as_of: as_of.as_deref(),
+                image_override: image.as_deref(),
+                image_tag: image_tag.as_deref(),
+                image_builder,
+                rbac_profile,
+                patches: &patches,
+                go: &go,
+                prune_keep,
+                deploy_only,
+                sources: &source,
+                image_overrides: &image_overrides,
+                go_env: &job_go_env,
+                force_unlock,
+                verify_chains,
+                pac_smoke: pac_smoke.as_deref(),
+                skip_triggers_probe,
+                skip_admission_probe,
+                check_leaks,
+                continue_on_build_failure,
+                build_where,
+            };
+
             // Handle --date-range for batch historical runs
             if let Some(ref range) = date_range {
-                let exit_code = run_batch_historical(
-                    range,
-                    &components,
-                    &release_tests_ref,
-                    &output_dir,
-                    skip_build,
-                    registry.as_deref(),
-                    cli.verbose,
-                    profile,
-                    cli.no_auto_setup,
-                    dry_run,
-                );
-                std::process::exit(exit_code);
+                let status = run_batch_historical(range, &components, skip_build, cli.i_know_what_im_doing, &opts);
+                return status;
             }
 
             let mut specs = match components {
-                Some(ref s) => match component::parse_component_specs(s) {
+                Some(ref s) => match component::parse_component_specs(s, &config::load_groups()) {
                     Ok(v) => v,
                     Err(e) => {
                         eprintln!("Error: {e}");
-                        std::process::exit(2);
+                        return ExitStatus::Error;
                     }
                 },
                 None => component::default_specs(),
@@ -184,106 +451,319 @@ async fn main() {
                 component::apply_as_of_date(&mut specs, date);
             }
 
-            if !cli.no_auto_setup && !skip_build && !incluster::is_incluster() {
+            // --tags auto: only the specs of the requested components;
+            // --tags impacted: only the specs affected by upstream changes since the previous run
+            let tags = if tags == "impacted" && !dry_run {
+                match (config::load_config(&config::default_config_path()), config::load_impact_config(&config::default_impact_config_path())) {
+                    (Ok(cfg), Ok(impact_cfg)) => impact::impacted_tags(&specs, &cfg.components, &impact_cfg.rules),
+                    (Err(e), _) | (_, Err(e)) => {
+                        eprintln!("Error loading config: {e:#}");
+                        return ExitStatus::Error;
+                    }
+                }
+            } else if tags == "auto" {
+                match config::load_config(&config::default_config_path()) {
+                    Ok(cfg) => {
+                        let names: Vec<String> = specs.iter().map(|s| s.name.clone()).collect();
+                        let resolved = config::auto_tags(&cfg, &names);
+                        eprintln!("--tags auto: {}", resolved);
+                        resolved
+                    }
+                    Err(e) => {
+                        eprintln!("Error loading config: {e:#}");
+                        return ExitStatus::Error;
+                    }
+                }
+            } else {
+                tags
+            };
+
+            // Fail on mistyped refs now rather than when their clone fails; the
+            // in-cluster Job was validated by the run that created it
+            if !incluster::is_incluster() {
+                let mut checks = Vec::new();
+                if !skip_build && !skip_deploy {
+                    match config::load_config(&config::default_config_path()) {
+                        Ok(cfg) => checks.extend(refcheck::component_checks(&specs, &cfg.components)),
+                        Err(e) => {
+                            eprintln!("Error loading config: {e:#}");
+                            return ExitStatus::Error;
+                        }
+                    }
+                }
+                if !deploy_only {
+                    checks.push(refcheck::RefCheck::new("--release-tests-ref", testref::repo(), &release_tests.git_ref));
+                }
+                if let Some(r) = perf_ref.as_deref().filter(|_| perf) {
+                    checks.push(refcheck::RefCheck::new("--perf-ref", perf::PERF_REPO, r));
+                }
+                if let Err(e) = refcheck::validate_refs(&checks) {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
+                }
+            }
+
+            if !cli.no_auto_setup && !skip_build && !skip_deploy && !incluster::is_incluster() {
                 let result = tokio::task::spawn_blocking(|| {
                     setup::run_auto_setup()
                 }).await;
                 match result {
                     Ok(Ok(())) => {}
-                    Ok(Err(e)) => eprintln!("WARNING: Auto-setup had errors: {e:#}"),
-                    Err(e) => eprintln!("WARNING: Auto-setup panicked: {e}"),
+                    Ok(Err(e)) => warnings::warn(format!("Auto-setup had errors: {e:#}")),
+                    Err(e) => warnings::warn(format!("Auto-setup panicked: {e}")),
                 }
             }
 
-            if skip_build {
-                // In-cluster mode: skip clone/build, go straight to deploy+test
-                let mut exit_code = run_deploy_and_test(&specs, &tags, &release_tests_ref, &output_dir, registry.as_deref(), cli.verbose, profile, cli.no_auto_setup, as_of.as_deref()).await;
+            if deploy_only && (skip_build || incluster::is_incluster()) {
+                // Images are already built: deploy them and stop before tests
+                let lock = match acquire_cluster_lock(force_unlock).await {
+                    Ok(lock) => lock,
+                    Err(status) => return status,
+                };
+                let status = match deploy_phase(&specs, &source, &image_overrides, registry.as_deref(), cli.verbose > 0, cli.no_auto_setup).await {
+                    Ok(reports) => print_deploy_summary(&specs, &reports),
+                    Err(status) => status,
+                };
+                lock.release().await;
+                warnings::print_summary();
+                return status;
+            }
+
+            if skip_build || skip_deploy {
+                // In-cluster mode: skip clone/build, go straight to deploy+test.
+                // --skip-deploy: test the current deployment right here (no Job).
+                // Either way the deployment must not change under the tests.
+                let lock = match acquire_cluster_lock(force_unlock).await {
+                    Ok(lock) => lock,
+                    Err(status) => return status,
+                };
+                let mut status = run_deploy_and_test(&specs, &tags, &opts, skip_deploy, &failed_builds).await;
 
                 // Run performance tests if --perf is set
                 if perf {
-                    let perf_exit = run_perf_tests_standalone(&output_dir, &perf_scenario, perf_ref.as_deref(), cli.verbose, profile).await;
-                    exit_code = combine_exit_codes(exit_code, perf_exit);
+                    let perf_status = run_perf_tests_standalone(&output_dir, &perf_scenario, perf_ref.as_deref(), cli.verbose > 0, profile).await;
+                    status = status.combine(perf_status);
                 }
 
-                // Publish results directly to gh-pages if configured
-                callback::maybe_publish_results();
-                std::process::exit(exit_code);
+                // Publish results directly to gh-pages if configured; test-iteration
+                // runs are not meant for the dashboard
+                if skip_deploy {
+                    eprintln!("Not publishing results of a --skip-deploy run.");
+                } else {
+                    callback::maybe_publish_results(&output_dir).await;
+                    email::maybe_report(&output_dir).await;
+                }
+                lock.release().await;
+                warnings::print_summary();
+                return status;
             }
 
             if incluster::is_incluster() {
+                // --build-where cluster: build here first, pushing to the internal registry
+                let (mut tags, mut failed_builds) = (tags, failed_builds);
+                if build_where == build::BuildWhere::Cluster {
+                    let mut cfg = match config::load_config(&config::default_config_path()) {
+                        Ok(c) => c,
+                        Err(e) => {
+                            eprintln!("Error loading config: {e:#}");
+                            return ExitStatus::Error;
+                        }
+                    };
+                    match build_phase(&mut specs, &mut tags, &mut cfg, incluster::INTERNAL_REGISTRY, &opts).await {
+                        Ok(failed) => failed_builds.extend(failed),
+                        Err(status) => {
+                            warnings::print_summary();
+                            return status;
+                        }
+                    }
+                }
+
                 // Already in-cluster: run deploy+test directly (don't re-wrap)
-                let mut exit_code = run_deploy_and_test(&specs, &tags, &release_tests_ref, &output_dir, registry.as_deref(), cli.verbose, profile, cli.no_auto_setup, as_of.as_deref()).await;
+                let lock = match acquire_cluster_lock(force_unlock).await {
+                    Ok(lock) => lock,
+                    Err(status) => return status,
+                };
+                let mut status = run_deploy_and_test(&specs, &tags, &opts, false, &failed_builds).await;
 
                 // Run performance tests if --perf is set
                 if perf {
-                    let perf_exit = run_perf_tests_standalone(&output_dir, &perf_scenario, perf_ref.as_deref(), cli.verbose, profile).await;
-                    exit_code = combine_exit_codes(exit_code, perf_exit);
+                    let perf_status = run_perf_tests_standalone(&output_dir, &perf_scenario, perf_ref.as_deref(), cli.verbose > 0, profile).await;
+                    status = status.combine(perf_status);
                 }
 
                 // Publish results directly to gh-pages if configured
-                callback::maybe_publish_results();
-                std::process::exit(exit_code);
+                callback::maybe_publish_results(&output_dir).await;
+                email::maybe_report(&output_dir).await;
+                lock.release().await;
+                warnings::print_summary();
+                return status;
             }
 
             // Normal mode: build locally, then create in-cluster Job for deploy+test
             // Note: perf flags are NOT passed to in-cluster Job yet (would need incluster module changes)
             // For now, perf tests run only in skip_build or is_incluster paths
-            let exit_code = run_multi(specs, dry_run, json, &tags, &release_tests_ref, &output_dir, registry.as_deref(), cli.verbose, as_of.as_deref(), image.as_deref()).await;
-            std::process::exit(exit_code);
+            let status = run_multi(specs, &tags, &opts).await;
+            warnings::print_summary();
+            return status;
         }
-        Commands::Results { output_dir } => {
-            let output_path = std::path::Path::new(&output_dir);
-            let results_dir = output_path.join("results");
-            if let Err(e) = std::fs::create_dir_all(&results_dir) {
-                eprintln!("Error creating results directory: {e:#}");
-                std::process::exit(2);
+        Commands::Matrix { ocp, operator_branch, contexts, components, tags, output_dir, meta, publish, remote, label } => {
+            runmeta::init(&meta);
+            let cells = match matrix::cells(&ocp, &operator_branch, &contexts) {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
+                }
+            };
+            let publish = publish.then(|| matrix::PublishOptions { remote, label });
+            let opts = MatrixOptions {
+                components: components.as_deref(),
+                tags: &tags,
+                output_dir: &output_dir,
+                publish: publish.as_ref(),
+                explicit_channel: cli.operator_channel.is_some(),
+                no_auto_setup: cli.no_auto_setup,
+                i_know_what_im_doing: cli.i_know_what_im_doing,
+            };
+            run_matrix(&cells, &opts)
+        }
+        Commands::Results { output_dir, command: Some(ResultsCommands::RerunFailed { execute, release_tests_ref, rerun_dir }) } => {
+            let results_path = std::path::Path::new(&output_dir).join("results/results.json");
+            let original = match results::read_results_json(&results_path) {
+                Ok(r) => r,
+                Err(e) => {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
+                }
+            };
+            let scenarios = results::failed_scenarios(&original);
+            if scenarios.is_empty() {
+                eprintln!("No failed tests in {}", results_path.display());
+                return ExitStatus::Success;
             }
 
-            // Try JUnit XML first, then fall back to Gauge stdout
-            let junit_path = results_dir.join("junit.xml");
-            let stdout_path = output_path.join("logs/test-stdout.log");
+            if !execute {
+                eprintln!("# {} failed scenario(s); run from a release-tests checkout:", scenarios.len());
+                println!("{}", results::gauge_command_line(&test::gauge_run_args(None, &scenarios)));
+                return ExitStatus::Success;
+            }
 
-            let parse_result = if junit_path.exists() {
-                results::parse_junit_xml(&junit_path)
-            } else if stdout_path.exists() {
-                results::parse_gauge_stdout(&stdout_path)
-            } else {
-                eprintln!("No test results found in {}", output_dir);
-                eprintln!("Expected: {}/results/junit.xml or {}/logs/test-stdout.log", output_dir, output_dir);
-                std::process::exit(2);
+            let rerun_dir = rerun_dir.unwrap_or_else(|| format!("{}/rerun", output_dir));
+            let rerun_path = std::path::PathBuf::from(&rerun_dir);
+            let rerun_ref = release_tests_ref.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                test::rerun_scenarios(&scenarios, &rerun_ref, &rerun_path)
+            }).await;
+            let all_passed = match result {
+                Ok(Ok(passed)) => passed,
+                Ok(Err(e)) => {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
+                }
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    return ExitStatus::Error;
+                }
             };
 
-            match parse_result {
-                Ok(result) => {
-                    let categorized = results::categorize_results(&result);
-                    results::print_categorized_results(&categorized);
-
-                    let json_path = results_dir.join("results.json");
-                    if let Err(e) = results::write_categorized_json(&categorized, &json_path) {
-                        eprintln!("Error writing JSON: {e:#}");
-                        std::process::exit(2);
+            // Fold the delta into a merged copy of the original results
+            let delta_path = std::path::Path::new(&rerun_dir).join("results/results.json");
+            match results::read_results_json(&delta_path) {
+                Ok(delta) => {
+                    let merged = results::categorize_results(&results::merge_results(&original, &delta));
+                    let merged_dir = std::path::Path::new(&rerun_dir).join("merged/results");
+                    let written = std::fs::create_dir_all(&merged_dir)
+                        .map_err(anyhow::Error::from)
+                        .and_then(|_| results::write_results(&merged, &merged_dir));
+                    match written {
+                        Ok(path) => println!(
+                            "Merged results ({}/{} passed) written to {}",
+                            merged.result.passed,
+                            merged.result.total,
+                            path.display()
+                        ),
+                        Err(e) => warnings::warn(format!("Failed to write merged results: {e:#}")),
                     }
+                }
+                Err(e) => warnings::warn(format!("No delta results to merge: {e:#}")),
+            }
+            return ExitStatus::from_passed(all_passed);
+        }
+        Commands::Results { output_dir, command: None } => {
+            match analyze_output_dir(&output_dir) {
+                Ok(json_path) => {
                     println!("Results written to {}", json_path.display());
-                    std::process::exit(0);
+                    return ExitStatus::Success;
                 }
                 Err(e) => {
-                    eprintln!("Error parsing test results: {e:#}");
-                    std::process::exit(2);
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
                 }
             }
         }
+        Commands::ExportBundle { output_dir, file } => {
+            match runbundle::export_bundle(std::path::Path::new(&output_dir), file.as_deref().map(std::path::Path::new)) {
+                Ok(path) => println!("{}", path.display()),
+                Err(e) => {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
+                }
+            }
+        }
+        Commands::ImportBundle { bundle, output_dir, analyze, publish, remote, label } => {
+            if let Err(e) = runbundle::import_bundle(std::path::Path::new(&bundle), std::path::Path::new(&output_dir)) {
+                eprintln!("Error: {e:#}");
+                return ExitStatus::Error;
+            }
+            if analyze {
+                match analyze_output_dir(&output_dir) {
+                    Ok(json_path) => println!("Results written to {}", json_path.display()),
+                    Err(e) => {
+                        eprintln!("Error: {e:#}");
+                        return ExitStatus::Error;
+                    }
+                }
+            }
+            if publish
+                && let Err(e) = publish::publish(&output_dir, remote.as_deref(), label.as_deref(), None, None, true, None)
+            {
+                eprintln!("Error: {e:#}");
+                return ExitStatus::Error;
+            }
+        }
         Commands::Status => {
-            let client = match kube::Client::try_default().await {
+            let client = match k8s::client().await {
                 Ok(c) => c,
                 Err(e) => {
                     eprintln!("Error connecting to cluster: {e:#}");
-                    std::process::exit(2);
+                    return ExitStatus::Error;
                 }
             };
             let namespace = "openshift-pipelines";
-            if let Err(e) = incluster::show_status(&client, namespace).await {
+            if let Err(e) = incluster::show_status(&client, namespace, cli.output).await {
                 eprintln!("Error: {e:#}");
-                std::process::exit(2);
+                return ExitStatus::Error;
+            }
+        }
+        Commands::Konflux { command: Some(KonfluxCommands::Render { operator_dir, file }), .. } => {
+            let standalone = match konflux::render(std::path::Path::new(&operator_dir), file.as_deref()) {
+                Ok(s) => s,
+                Err(e) => {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
+                }
+            };
+            let printed = if cli.output.is_structured() {
+                output::print(cli.output, &standalone)
+            } else {
+                for change in &standalone.changes {
+                    eprintln!("  {}", change);
+                }
+                serde_yaml::to_string(&standalone.pipeline).map(|y| print!("{}", y)).map_err(Into::into)
+            };
+            if let Err(e) = printed {
+                eprintln!("Error: {e:#}");
+                return ExitStatus::Error;
             }
         }
         Commands::Konflux {
@@ -294,9 +774,68 @@ async fn main() {
             refs,
             as_of,
             trigger,
+            skip_catalog_smoke,
             pipeline_namespace,
             timeout,
+            bundle_builder,
+            sign,
+            sign_key,
+            in_cluster,
+            image_builder,
+            meta,
+            command: None,
         } => {
+            runmeta::init(&meta);
+            // clap requires --registry unless a subcommand is given
+            let Some(registry) = registry else {
+                eprintln!("Error: --registry is required");
+                return ExitStatus::Error;
+            };
+            if (trigger || !cli.no_auto_setup || !skip_catalog_smoke || in_cluster)
+                && let Err(status) = guard_cluster("run konflux", cli.i_know_what_im_doing).await
+            {
+                return status;
+            }
+            if in_cluster && sign && sign_key.is_none() {
+                eprintln!("Error: keyless signing needs an interactive login; give --sign-key with --in-cluster");
+                return ExitStatus::Error;
+            }
+            if in_cluster && !incluster::is_incluster() {
+                let mut job_args = vec![
+                    "konflux".to_string(),
+                    "--registry".to_string(), registry.clone(),
+                    "--operator-branch".to_string(), operator_branch,
+                    "--output-dir".to_string(), output_dir,
+                    "--components".to_string(), components,
+                    "--pipeline-namespace".to_string(), pipeline_namespace,
+                    "--timeout".to_string(), timeout.to_string(),
+                    "--bundle-builder".to_string(), bundle_builder.as_arg().to_string(),
+                    // Auto-setup runs here, before the CLI image is built
+                    "--no-auto-setup".to_string(),
+                ];
+                if let Some(refs) = refs {
+                    job_args.push("--refs".to_string());
+                    job_args.push(refs);
+                }
+                if let Some(date) = as_of {
+                    job_args.push("--as-of".to_string());
+                    job_args.push(date);
+                }
+                if trigger {
+                    job_args.push("--trigger".to_string());
+                }
+                if skip_catalog_smoke {
+                    job_args.push("--skip-catalog-smoke".to_string());
+                }
+                if let Some(key) = sign_key.clone() {
+                    job_args.push("--sign".to_string());
+                    job_args.push("--sign-key".to_string());
+                    job_args.push(key);
+                }
+                job_args.extend(runmeta::to_args());
+                job_args.extend(logging::to_args());
+                return run_konflux_in_cluster(job_args, image_builder, timeout, cli.no_auto_setup).await;
+            }
             let output_path = std::path::Path::new(&output_dir);
             std::fs::create_dir_all(output_path).expect("Failed to create output directory");
 
@@ -305,6 +844,14 @@ async fn main() {
 
             // Check if we already have a snapshot (skip build phase)
             let need_build = !snapshot_path.exists();
+            let mut report = konflux::KonfluxReport {
+                snapshot: snapshot_path.display().to_string(),
+                index_image: None,
+                catalog_smoke: None,
+                pipeline_run: None,
+                task_timings: Vec::new(),
+                suites: Vec::new(),
+            };
 
             if need_build {
                 eprintln!("\n=== Building Konflux SNAPSHOT ===\n");
@@ -316,17 +863,17 @@ async fn main() {
                     }).await;
                     match result {
                         Ok(Ok(())) => {}
-                        Ok(Err(e)) => eprintln!("WARNING: Auto-setup had errors: {e:#}"),
-                        Err(e) => eprintln!("WARNING: Auto-setup panicked: {e}"),
+                        Ok(Err(e)) => warnings::warn(format!("Auto-setup had errors: {e:#}")),
+                        Err(e) => warnings::warn(format!("Auto-setup panicked: {e}")),
                     }
                 }
 
                 // Parse component specs (refs can be embedded like "pipeline:v0.60.0,triggers")
-                let mut specs = match component::parse_component_specs(&components) {
+                let mut specs = match component::parse_component_specs(&components, &config::load_groups()) {
                     Ok(v) => v,
                     Err(e) => {
                         eprintln!("Error parsing components: {e}");
-                        std::process::exit(2);
+                        return ExitStatus::Error;
                     }
                 };
 
@@ -348,13 +895,27 @@ async fn main() {
                     component::apply_as_of_date(&mut specs, date);
                 }
 
+                let cfg = match config::load_config(&config::default_config_path()) {
+                    Ok(c) => c,
+                    Err(e) => {
+                        eprintln!("Error loading config: {e:#}");
+                        return ExitStatus::Error;
+                    }
+                };
+                let mut checks = refcheck::component_checks(&specs, &cfg.components);
+                checks.push(refcheck::RefCheck::new("operator branch", bundle::OPERATOR_REPO, &operator_branch));
+                if let Err(e) = refcheck::validate_refs(&checks) {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
+                }
+
                 // Step 1: Build upstream images and push to external registry
                 eprintln!("Step 1: Building upstream images...");
                 let mut all_image_refs: std::collections::HashMap<String, String> = std::collections::HashMap::new();
 
                 for spec in &specs {
                     eprintln!("\n  Building {}...", spec.name);
-                    match build::run_build_with_refs(&spec.name, Some(&registry), &spec.git_ref) {
+                    match build::run_build_with_refs(&spec.name, Some(&registry), &spec.git_ref, &gotoolchain::GoOptions::default()) {
                         Ok(refs) => {
                             for (name, pullspec) in refs {
                                 all_image_refs.insert(name, pullspec);
@@ -362,7 +923,7 @@ async fn main() {
                         }
                         Err(e) => {
                             eprintln!("Error building {}: {e:#}", spec.name);
-                            std::process::exit(2);
+                            return ExitStatus::Error;
                         }
                     }
                 }
@@ -375,7 +936,7 @@ async fn main() {
                     Ok(d) => d,
                     Err(e) => {
                         eprintln!("Error cloning operator: {e:#}");
-                        std::process::exit(2);
+                        return ExitStatus::Error;
                     }
                 };
 
@@ -383,59 +944,87 @@ async fn main() {
                 if operator_dir_path.exists() {
                     let _ = std::fs::remove_dir_all(&operator_dir_path);
                 }
-                let copy_result = std::process::Command::new("cp")
-                    .args(["-r", temp_operator_dir.to_str().unwrap(), operator_dir_path.to_str().unwrap()])
-                    .status();
-                if copy_result.is_err() || !copy_result.unwrap().success() {
-                    eprintln!("WARNING: Failed to copy operator dir to output");
+                if let Err(e) = platform::copy_dir_recursive(temp_operator_dir.path(), &operator_dir_path) {
+                    warnings::warn(format!("Failed to copy operator dir to output: {e:#}"));
                 }
 
                 // Step 3: Patch CSV with upstream images
                 eprintln!("\nStep 3: Patching CSV with upstream images...");
-                if let Err(e) = bundle::patch_csv(&temp_operator_dir, &all_image_refs) {
+                if let Err(e) = bundle::patch_csv(temp_operator_dir.path(), &all_image_refs) {
                     eprintln!("Error patching CSV: {e:#}");
-                    std::process::exit(2);
+                    return ExitStatus::Error;
                 }
 
                 // Generate timestamp tag
-                let tag = format!("upstream-{}", std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs());
+                let tag = format!("upstream-{}", timestamp::unix_now());
 
                 // Step 4: Build bundle image
                 eprintln!("\nStep 4: Building operator bundle image...");
-                let bundle_pullspec = match bundle::build_bundle_image(&temp_operator_dir, &registry, &tag) {
+                let bundle_pullspec = match bundle::build_bundle_image(temp_operator_dir.path(), &registry, &tag, bundle_builder) {
                     Ok(p) => p,
                     Err(e) => {
                         eprintln!("Error building bundle: {e:#}");
-                        std::process::exit(2);
+                        return ExitStatus::Error;
                     }
                 };
 
                 // Step 5: Build FBC index image
                 eprintln!("\nStep 5: Building FBC index image...");
-                let index_pullspec = match bundle::build_index_image(&bundle_pullspec, &registry, &tag) {
+                let index_pullspec = match bundle::build_index_image(&bundle_pullspec, &registry, &tag, bundle_builder) {
                     Ok(p) => p,
                     Err(e) => {
                         eprintln!("Error building index: {e:#}");
-                        std::process::exit(2);
+                        return ExitStatus::Error;
                     }
                 };
 
-                // Step 6: Generate SNAPSHOT
-                eprintln!("\nStep 6: Generating SNAPSHOT...");
+                // Step 6: Check the index is installable before handing it to Konflux
+                if skip_catalog_smoke {
+                    eprintln!("\nStep 6: Skipping catalog smoke test (--skip-catalog-smoke)");
+                } else {
+                    eprintln!("\nStep 6: Smoke-testing the FBC index on the cluster...");
+                    let index = index_pullspec.clone();
+                    match tokio::task::spawn_blocking(move || catalogsmoke::smoke_test(&index)).await {
+                        Ok(Ok(smoke)) => {
+                            eprintln!("  Catalog is installable ({:.0}s)", smoke.duration_secs);
+                            report.catalog_smoke = Some(smoke);
+                        }
+                        Ok(Err(e)) => {
+                            eprintln!("Error: catalog smoke test failed: {e:#}");
+                            eprintln!("Fix the bundle or catalog, or rerun with --skip-catalog-smoke to generate the SNAPSHOT anyway.");
+                            return ExitStatus::Error;
+                        }
+                        Err(e) => {
+                            eprintln!("Error: catalog smoke test panicked: {e}");
+                            return ExitStatus::Error;
+                        }
+                    }
+                }
+
+                // Step 7: Generate SNAPSHOT
+                eprintln!("\nStep 7: Generating SNAPSHOT...");
                 if let Err(e) = snapshot::generate_snapshot(&index_pullspec, &snapshot_path) {
                     eprintln!("Error generating snapshot: {e:#}");
-                    std::process::exit(2);
+                    return ExitStatus::Error;
+                }
+
+                if let Some(signer) = signing::Signer::from_flags(sign, sign_key.clone()) {
+                    eprintln!("\nSigning SNAPSHOT...");
+                    match signer.sign_file(&snapshot_path) {
+                        Ok(bundle) => eprintln!("  Signature: {}", bundle.display()),
+                        Err(e) => {
+                            eprintln!("Error signing snapshot: {e:#}");
+                            return ExitStatus::Error;
+                        }
+                    }
                 }
 
                 eprintln!("\n=== SNAPSHOT generated successfully ===");
                 eprintln!("  Output: {}", snapshot_path.display());
                 eprintln!("  Index: {}", index_pullspec);
+                report.index_image = Some(index_pullspec);
 
-                // Cleanup temp dir
-                let _ = std::fs::remove_dir_all(&temp_operator_dir);
+                drop(temp_operator_dir);
             } else {
                 eprintln!("Using existing snapshot at {}", snapshot_path.display());
             }
@@ -445,7 +1034,7 @@ async fn main() {
                 if !operator_dir_path.exists() {
                     eprintln!("Error: operator directory not found at {}", operator_dir_path.display());
                     eprintln!("Run without --trigger first to generate the SNAPSHOT and operator clone.");
-                    std::process::exit(2);
+                    return ExitStatus::Error;
                 }
 
                 eprintln!("\n=== Triggering standalone release-test-pipeline ===");
@@ -457,7 +1046,7 @@ async fn main() {
                     Ok(name) => name,
                     Err(e) => {
                         eprintln!("Error triggering pipeline: {e:#}");
-                        std::process::exit(2);
+                        return ExitStatus::Error;
                     }
                 };
 
@@ -466,113 +1055,481 @@ async fn main() {
                     Ok(r) => r,
                     Err(e) => {
                         eprintln!("Error waiting for pipeline: {e:#}");
-                        std::process::exit(2);
+                        return ExitStatus::Error;
                     }
                 };
 
-                let duration_min = result.duration.as_secs() / 60;
-                println!("\nPipelineRun: {}", result.name);
-                println!("Status: {:?}", result.status);
-                println!("Reason: {}", result.reason);
-                println!("Duration: {}m {}s", duration_min, result.duration.as_secs() % 60);
+                if !cli.output.is_structured() {
+                    let duration_min = result.duration.as_secs() / 60;
+                    println!("\nPipelineRun: {}", result.name);
+                    println!("Status: {:?}", result.status);
+                    println!("Reason: {}", result.reason);
+                    println!("Duration: {}m {}s", duration_min, result.duration.as_secs() % 60);
+                }
+
+                // Record how long each task took, to track which ones get slower
+                match konflux::collect_task_timings(&pr_name, &pipeline_namespace) {
+                    Ok(timings) if !timings.is_empty() => {
+                        if !cli.output.is_structured() {
+                            konflux::print_task_timings(&timings);
+                        }
+                        match konflux::save_task_timings(&timings, output_path) {
+                            Ok(path) => eprintln!("Task timings saved to {}", path.display()),
+                            Err(e) => warnings::warn(format!("Failed to save task timings: {e:#}")),
+                        }
+                        report.task_timings = timings;
+                    }
+                    Ok(_) => eprintln!("No TaskRuns found for PipelineRun {}.", pr_name),
+                    Err(e) => warnings::warn(format!("Failed to collect task timings: {e:#}")),
+                }
 
                 // Collect results from pipeline task logs (regardless of pass/fail)
                 if result.status != konflux::PipelineRunStatus::Timeout {
                     eprintln!("\n=== Collecting pipeline results ===");
-                    match konflux::collect_results(&pr_name, &pipeline_namespace, output_path) {
+                    let konflux_cfg = match config::load_konflux_config(&config::default_konflux_config_path()) {
+                        Ok(c) => c,
+                        Err(e) => {
+                            warnings::warn(format!("{e:#}; using the default Konflux task names"));
+                            config::KonfluxConfig::default()
+                        }
+                    };
+                    match konflux::collect_results(&pr_name, &pipeline_namespace, output_path, &konflux_cfg) {
                         Ok(task_results) => {
                             if !task_results.is_empty() {
-                                konflux::print_pipeline_summary(&task_results);
+                                if cli.output.is_structured() {
+                                    report.suites = konflux::suite_summaries(&task_results);
+                                } else {
+                                    konflux::print_pipeline_summary(&task_results);
+                                }
 
                                 if let Err(e) = konflux::save_konflux_results(
                                     &task_results, &snapshot_path, output_path,
                                 ) {
-                                    eprintln!("WARNING: Failed to save results: {e:#}");
+                                    warnings::warn(format!("Failed to save results: {e:#}"));
                                 } else {
                                     eprintln!(
                                         "\nResults saved to {}/results/results.json",
                                         output_dir
                                     );
-                                    eprintln!(
-                                        "Run `streamstress publish --output-dir {}` to update dashboard.",
-                                        output_dir
-                                    );
+                                    if let Err(e) = runmeta::record(output_path) {
+                                        warnings::warn(format!("Failed to record run metadata: {e:#}"));
+                                    }
+                                    if incluster::is_incluster() {
+                                        // The Job's output dir goes away with its pod
+                                        callback::maybe_publish_results(&output_dir).await;
+                                    } else {
+                                        eprintln!(
+                                            "Run `streamstress publish --output-dir {}` to update dashboard.",
+                                            output_dir
+                                        );
+                                    }
                                 }
-                            } else {
-                                eprintln!("No test results collected from pipeline tasks.");
                             }
                         }
                         Err(e) => {
-                            eprintln!("WARNING: Failed to collect pipeline results: {e:#}");
+                            warnings::warn(format!("Failed to collect pipeline results: {e:#}"));
                         }
                     }
                 }
 
-                match result.status {
-                    konflux::PipelineRunStatus::Succeeded => std::process::exit(0),
-                    konflux::PipelineRunStatus::Failed => std::process::exit(1),
-                    konflux::PipelineRunStatus::Timeout => std::process::exit(2),
+                let status = match result.status {
+                    konflux::PipelineRunStatus::Succeeded => ExitStatus::Success,
+                    konflux::PipelineRunStatus::Failed => ExitStatus::Failure,
+                    konflux::PipelineRunStatus::Timeout => ExitStatus::Error,
+                };
+                report.pipeline_run = Some(result);
+                if cli.output.is_structured() && let Err(e) = output::print(cli.output, &report) {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
                 }
+                return status;
             } else {
                 eprintln!("\nTo trigger the pipeline, run:");
                 eprintln!("  streamstress konflux --registry {} --trigger --output-dir {}", registry, output_dir);
-                std::process::exit(0);
+                if cli.output.is_structured() && let Err(e) = output::print(cli.output, &report) {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
+                }
+                return ExitStatus::Success;
+            }
+        }
+        Commands::Publish { command: Some(command), .. } => {
+            let (run_id, edit, remote, api) = match command {
+                PublishCommands::Delete { run_id, remote, api } => (run_id, publish::RunEdit::Delete, remote, api),
+                PublishCommands::Relabel { run_id, label, remote, api } => (run_id, publish::RunEdit::Relabel(label), remote, api),
+            };
+            let result = if api {
+                publish::edit_published_run_via_api(&run_id, &edit, remote.as_deref())
+            } else {
+                publish::edit_published_run(&run_id, &edit, remote.as_deref())
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {e:#}");
+                return ExitStatus::Error;
+            }
+        }
+        Commands::Publish { output_dir, remote, label, run_id, api, dry_run, dry_run_dir, no_scrub, sign, sign_key, meta, command: None } => {
+            runmeta::init(&meta);
+            let dry_run_dir = dry_run.then(|| std::path::PathBuf::from(&dry_run_dir));
+            let signer = signing::Signer::from_flags(sign, sign_key);
+            let result = if api {
+                publish::publish_via_api(&output_dir, remote.as_deref(), label.as_deref(), run_id.as_deref(), !no_scrub, signer.as_ref())
+            } else {
+                publish::publish(&output_dir, remote.as_deref(), label.as_deref(), run_id.as_deref(), dry_run_dir.as_deref(), !no_scrub, signer.as_ref())
+            };
+            match result {
+                Ok(_) => return ExitStatus::Success,
+                Err(e) => {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
+                }
+            }
+        }
+        Commands::Baseline { baseline, output_dir, remote } => {
+            let resolved = tokio::task::spawn_blocking(move || {
+                baseline::resolve(&baseline, remote.as_deref(), output_dir.as_deref().map(std::path::Path::new))
+            }).await;
+            match resolved {
+                Ok(Ok(b)) => {
+                    eprintln!("Baseline run {} ({}, {})", b.run_id, if b.label.is_empty() { "no label" } else { &b.label }, b.timestamp);
+                    println!("{}", b.dir.display());
+                }
+                Ok(Err(e)) => {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
+                }
+                Err(e) => {
+                    eprintln!("Error: baseline download panicked: {e}");
+                    return ExitStatus::Error;
+                }
             }
         }
-        Commands::Publish { output_dir, remote, label } => {
-            match publish::publish(&output_dir, remote.as_deref(), label.as_deref()) {
-                Ok(()) => std::process::exit(0),
+        Commands::VerifyRun { file, bundle, key, certificate_identity, certificate_oidc_issuer } => {
+            let identity = signing::Identity { key, certificate_identity, certificate_oidc_issuer };
+            let path = std::path::Path::new(&file);
+            match signing::verify_file(path, bundle.as_deref().map(std::path::Path::new), &identity) {
+                Ok(()) => println!("{}: signature verified", file),
                 Err(e) => {
                     eprintln!("Error: {e:#}");
-                    std::process::exit(2);
+                    return ExitStatus::Failure;
                 }
             }
         }
         Commands::Logs { job } => {
-            let client = match kube::Client::try_default().await {
+            let client = match k8s::client().await {
                 Ok(c) => c,
                 Err(e) => {
                     eprintln!("Error connecting to cluster: {e:#}");
-                    std::process::exit(2);
+                    return ExitStatus::Error;
                 }
             };
             let namespace = "openshift-pipelines";
             if let Err(e) = incluster::stream_job_logs(&client, namespace, job.as_deref()).await {
                 eprintln!("Error: {e:#}");
-                std::process::exit(2);
+                return ExitStatus::Error;
+            }
+        }
+        Commands::Top { namespaces, interval, count } => {
+            let client = match k8s::client().await {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("Error connecting to cluster: {e:#}");
+                    return ExitStatus::Error;
+                }
+            };
+            if let Err(e) = top::run(&client, &namespaces, std::time::Duration::from_secs(interval), count).await {
+                eprintln!("Error: {e:#}");
+                return ExitStatus::Error;
+            }
+        }
+        Commands::Dashboard { command } => match command {
+            DashboardCommands::Serve { dir, port, bind } => {
+                let result = tokio::task::spawn_blocking(move || {
+                    dashboard::serve(std::path::Path::new(&dir), &bind, port)
+                }).await;
+                match result {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => {
+                        eprintln!("Error: {e:#}");
+                        return ExitStatus::Error;
+                    }
+                    Err(e) => {
+                        eprintln!("Error: {e}");
+                        return ExitStatus::Error;
+                    }
+                }
+            }
+        },
+        Commands::Rbac { command } => match command {
+            RbacCommands::Print { profile, namespace } => {
+                match rbac::render_yaml(profile, &namespace) {
+                    Ok(yaml) => print!("{}", yaml),
+                    Err(e) => {
+                        eprintln!("Error: {e:#}");
+                        return ExitStatus::Error;
+                    }
+                }
+            }
+        },
+        Commands::Profile { command: ProfileCommands::Diff { run_a, run_b, threshold, baseline, remote } } => {
+            // clap requires a second run unless --baseline is given
+            let (run_a, run_b) = match baseline {
+                Some(baseline) => {
+                    let resolved = tokio::task::spawn_blocking(move || baseline::resolve(&baseline, remote.as_deref(), None)).await;
+                    match resolved {
+                        Ok(Ok(b)) => (b.dir.display().to_string(), run_a),
+                        Ok(Err(e)) => {
+                            eprintln!("Error: {e:#}");
+                            return ExitStatus::Error;
+                        }
+                        Err(e) => {
+                            eprintln!("Error: baseline download panicked: {e}");
+                            return ExitStatus::Error;
+                        }
+                    }
+                }
+                None => (run_a, run_b.unwrap_or_default()),
+            };
+            let diff = match profilediff::diff_runs(std::path::Path::new(&run_a), std::path::Path::new(&run_b), threshold) {
+                Ok(d) => d,
+                Err(e) => {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
+                }
+            };
+            if cli.output.is_structured() {
+                if let Err(e) = output::print(cli.output, &diff) {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
+                }
+            } else {
+                profilediff::print_table(&diff);
+            }
+            return ExitStatus::from_passed(diff.regressions() == 0);
+        }
+        Commands::Email { command } => {
+            let result = match command {
+                EmailCommands::Send { output_dir, label, dry_run } => {
+                    email::send_run(std::path::Path::new(&output_dir), label.as_deref(), dry_run)
+                }
+                EmailCommands::Digest { dry_run } => email::load_config().and_then(|cfg| {
+                    let sent = email::send_digest(&cfg, dry_run)?;
+                    match sent {
+                        0 => eprintln!("No summaries queued in {}", cfg.digest_file),
+                        n if !dry_run => eprintln!("Sent a digest of {} run(s) to {}", n, cfg.to.join(", ")),
+                        _ => {}
+                    }
+                    Ok(())
+                }),
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {e:#}");
+                return ExitStatus::Error;
+            }
+        }
+        Commands::SelfUpdate { version, check } => {
+            let result = tokio::task::spawn_blocking(move || {
+                selfupdate::run_self_update(version.as_deref(), check)
+            }).await;
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => {
+                    eprintln!("Error: {e:#}");
+                    return ExitStatus::Error;
+                }
+                Err(e) => {
+                    eprintln!("Error: self-update panicked: {e}");
+                    return ExitStatus::Error;
+                }
             }
         }
     }
+    ExitStatus::Success
 }
 
-/// Deploy and test only (used in-cluster where builds already happened locally).
-async fn run_deploy_and_test(
+/// Parse and categorize the test results in `output_dir` (JUnit XML, else gauge
+/// stdout) and write results/results.json. Returns its path.
+fn analyze_output_dir(output_dir: &str) -> anyhow::Result<std::path::PathBuf> {
+    use anyhow::Context;
+
+    let output_path = std::path::Path::new(output_dir);
+    let results_dir = output_path.join("results");
+    std::fs::create_dir_all(&results_dir).context("Failed to create results directory")?;
+
+    // Try JUnit XML first, then fall back to Gauge stdout
+    let junit_path = results_dir.join("junit.xml");
+    let stdout_path = output_path.join("logs/test-stdout.log");
+
+    let result = if junit_path.exists() {
+        results::parse_junit_xml(&junit_path)
+    } else if stdout_path.exists() {
+        results::parse_gauge_stdout(&stdout_path)
+    } else {
+        anyhow::bail!(
+            "No test results found in {}\nExpected: {}/results/junit.xml or {}/logs/test-stdout.log",
+            output_dir, output_dir, output_dir
+        );
+    }
+    .context("Failed to parse test results")?;
+
+    let categorized = results::categorize_results(&result);
+    results::print_categorized_results(&categorized);
+    results::write_results(&categorized, &results_dir).context("Failed to write results")
+}
+
+/// Check up front that the current user has the permissions `commands` need;
+/// on missing ones, report them all and fail with an error status. Skipped
+/// in-cluster, where the Job's ServiceAccount is bound to what it needs.
+async fn preflight_permissions(commands: Vec<access::Command>, skip: bool) -> Result<(), ExitStatus> {
+    if skip || commands.is_empty() || incluster::is_incluster() {
+        return Ok(());
+    }
+    let names: Vec<String> = commands
+        .iter()
+        .filter_map(|c| clap::ValueEnum::to_possible_value(c).map(|v| v.get_name().to_string()))
+        .collect();
+    let result = tokio::task::spawn_blocking(move || access::missing(&access::requirements(&commands))).await;
+    match result {
+        Ok(Ok(missing)) if missing.is_empty() => {}
+        Ok(Ok(missing)) => {
+            access::print_report(&missing);
+            eprintln!(
+                "\nThe RBAC to grant is printed by `streamstress check --permissions {}`; \
+                 pass --skip-permission-check to try anyway.",
+                names.join(",")
+            );
+            return Err(ExitStatus::Error);
+        }
+        Ok(Err(e)) => warnings::warn(format!("Could not check permissions: {e:#}")),
+        Err(e) => warnings::warn(format!("Permission check panicked: {e}")),
+    }
+    Ok(())
+}
+
+/// Check the tools on PATH against config/tools.toml; fails only with --require-pinned-tools.
+fn verify_tools() -> Result<(), ExitStatus> {
+    tools::verify().map_err(|e| {
+        eprintln!("Error: {e:#}");
+        ExitStatus::Error
+    })
+}
+
+/// Fail unless the cluster may be modified by `action` (see `safety`). In-cluster
+/// Jobs were checked by the run that created them.
+async fn guard_cluster(action: &'static str, allow: bool) -> Result<(), ExitStatus> {
+    if incluster::is_incluster() {
+        return Ok(());
+    }
+    let result = tokio::task::spawn_blocking(move || safety::guard(action, allow)).await;
+    match result {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => {
+            eprintln!("Error: {e:#}");
+            Err(ExitStatus::Error)
+        }
+        Err(e) => {
+            eprintln!("Error: {e}");
+            Err(ExitStatus::Error)
+        }
+    }
+}
+
+/// `konflux --in-cluster`: run auto-setup and build the CLI image here, then hand
+/// `job_args` to a Job in openshift-pipelines.
+async fn run_konflux_in_cluster(job_args: Vec<String>, image_builder: incluster::ImageBuilder, timeout: u64, no_auto_setup: bool) -> ExitStatus {
+    if !no_auto_setup {
+        match tokio::task::spawn_blocking(setup::run_auto_setup).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warnings::warn(format!("Auto-setup had errors: {e:#}")),
+            Err(e) => warnings::warn(format!("Auto-setup panicked: {e}")),
+        }
+    }
+    let registry_route = match registry::get_registry_route() {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Error: {e:#}");
+            return ExitStatus::Error;
+        }
+    };
+    if let Err(e) = registry::registry_login(&registry_route) {
+        eprintln!("Error logging into registry: {e:#}");
+        return ExitStatus::Error;
+    }
+
+    eprintln!("\n=== Creating in-cluster Job for the Konflux build ===");
+    let result = tokio::task::spawn_blocking(move || {
+        incluster::run_konflux_incluster(&registry_route, "openshift-pipelines", &job_args, image_builder, timeout)
+    }).await;
+    match result {
+        Ok(Ok(())) => ExitStatus::Success,
+        Ok(Err(e)) => { eprintln!("Error creating in-cluster Job: {e:#}"); ExitStatus::Error }
+        Err(e) => { eprintln!("Error: in-cluster task panicked: {e}"); ExitStatus::Error }
+    }
+}
+
+/// Take the cluster lock (see `lock`) for a run that deploys or tests from this
+/// process. In-cluster Jobs wait for it; local runs fail if it is held.
+async fn acquire_cluster_lock(force: bool) -> Result<lock::ClusterLock, ExitStatus> {
+    let wait = incluster::is_incluster().then_some(lock::JOB_WAIT_TIMEOUT);
+    let result = match k8s::client().await {
+        Ok(client) => lock::acquire(&client, "openshift-pipelines", force, wait).await,
+        Err(e) => Err(anyhow::Error::from(e).context("Failed to connect to cluster")),
+    };
+    result.map_err(|e| {
+        eprintln!("Error: {e:#}");
+        ExitStatus::Error
+    })
+}
+
+/// Before submitting a Job: tell the user if it will queue behind another run's lock.
+async fn report_cluster_lock(force: bool) {
+    let Ok(client) = k8s::client().await else {
+        return;
+    };
+    if let Ok(Some(holder)) = lock::current_holder(&client, "openshift-pipelines").await
+        && !holder.expired && !force
+    {
+        eprintln!("Cluster lock is held by {}; the Job will wait for it (see `streamstress status`)", holder.identity);
+    }
+}
+
+/// Auto-setup, then deploy the built (or nightly, per `sources`) images for
+/// `specs` in dependency order, with `image_overrides` in place of the images of
+/// their env vars. Returns the reports of the components that deployed; errors
+/// carry the status to return.
+async fn deploy_phase(
     specs: &[component::ComponentSpec],
-    tags: &str,
-    release_tests_ref: &str,
-    output_dir: &str,
+    sources: &[imagesource::SourceSpec],
+    image_overrides: &[deploy::mapping::ImageOverride],
     registry_override: Option<&str>,
     verbose: bool,
-    profile: bool,
     no_auto_setup: bool,
-    as_of: Option<&str>,
-) -> i32 {
+) -> Result<Vec<deploy::DeployReport>, ExitStatus> {
     if !no_auto_setup {
         let result = tokio::task::spawn_blocking(|| {
             setup::run_auto_setup()
         }).await;
         match result {
             Ok(Ok(())) => {}
-            Ok(Err(e)) => eprintln!("WARNING: Auto-setup had errors: {e:#}"),
-            Err(e) => eprintln!("WARNING: Auto-setup panicked: {e}"),
+            Ok(Err(e)) => warnings::warn(format!("Auto-setup had errors: {e:#}")),
+            Err(e) => warnings::warn(format!("Auto-setup panicked: {e}")),
         }
     }
 
-    let _cfg = match config::load_config(&config::default_config_path()) {
+    let cfg = match config::load_config(&config::default_config_path()) {
         Ok(c) => c,
         Err(e) => {
             eprintln!("Error loading config: {e:#}");
-            return 2;
+            return Err(ExitStatus::Error);
+        }
+    };
+
+    let names: Vec<String> = specs.iter().map(|s| s.name.clone()).collect();
+    let groups = match config::deploy_groups(&cfg, &names) {
+        Ok(g) => g,
+        Err(e) => {
+            eprintln!("Error: {e:#}");
+            return Err(ExitStatus::Error);
         }
     };
 
@@ -582,87 +1539,427 @@ async fn run_deploy_and_test(
             Ok(r) => r,
             Err(e) => {
                 eprintln!("Error: {e:#}");
-                return 2;
+                return Err(ExitStatus::Error);
+            }
+        },
+    };
+
+    // Deploy phase: groups in dependency order, components within a group in parallel
+    eprintln!("\n=== Deploying (in-cluster) ===");
+    let mut reports = Vec::new();
+    let mut failed: std::collections::HashSet<String> = std::collections::HashSet::new();
+    // Overrides of env vars no component maps go with the first deploy
+    let mut include_unowned = true;
+    for (i, group) in groups.iter().enumerate() {
+        if groups.len() > 1 {
+            eprintln!("\n--- Deploy group {}/{}: {} ---", i + 1, groups.len(), group.join(", "));
+        }
+        let mut set = tokio::task::JoinSet::new();
+        for name in group {
+            let blocked_by = cfg.components.get(name).and_then(|c| {
+                c.depends_on.iter().find(|d| failed.contains(d.as_str())).cloned()
+            });
+            if let Some(dep) = blocked_by {
+                warnings::warn(format!("Skipping deploy of {}: dependency {} failed to deploy", name, dep));
+                failed.insert(name.clone());
+                continue;
+            }
+            let overrides = deploy::mapping::overrides_for(&cfg, name, &names, image_overrides, include_unowned);
+            include_unowned = false;
+            let source = imagesource::source_for(sources, name);
+            if source != imagesource::ImageSource::Source {
+                let comp_name = name.clone();
+                let spec = specs.iter().find(|s| &s.name == name);
+                let git_ref = spec.and_then(|s| s.git_ref.clone());
+                let as_of = spec.and_then(|s| s.as_of_date.clone());
+                set.spawn_blocking(move || {
+                    let result = deploy::run_deploy_published(&comp_name, source, git_ref.as_deref(), as_of.as_deref(), &overrides, verbose);
+                    (comp_name, result)
+                });
+                continue;
+            }
+            let image_names = match load_image_names_from_config(name) {
+                Ok(names) => names,
+                Err(e) => {
+                    warnings::warn(format!("Could not load images for {}: {e:#}", name));
+                    failed.insert(name.clone());
+                    continue;
+                }
+            };
+            let comp_name = name.clone();
+            let registry_route = registry_route.clone();
+            set.spawn_blocking(move || {
+                let result = deploy::run_deploy(&comp_name, &registry_route, &image_names, &overrides, None, verbose);
+                (comp_name, result)
+            });
+        }
+        while let Some(joined) = set.join_next().await {
+            match joined {
+                Ok((_, Ok(report))) => reports.push(report),
+                Ok((name, Err(e))) => {
+                    warnings::warn(format!("Deploy failed for {}: {e:#}", name));
+                    failed.insert(name);
+                }
+                Err(e) => warnings::warn(format!("Deploy task panicked: {e}")),
+            }
+        }
+    }
+    Ok(reports)
+}
+
+/// Print what a `--deploy-only` run left on the cluster. Fails with an error
+/// status if any component did not deploy.
+fn print_deploy_summary(specs: &[component::ComponentSpec], reports: &[deploy::DeployReport]) -> ExitStatus {
+    println!("\n=== Deployed (tests not run) ===");
+    let mut status = ExitStatus::Success;
+    for spec in specs {
+        let Some(report) = reports.iter().find(|r| r.component == spec.name) else {
+            println!("\n{}: NOT DEPLOYED", spec.name);
+            status = ExitStatus::Error;
+            continue;
+        };
+        let reconciled = if report.reconciled { "reconciled" } else { "NOT reconciled" };
+        let version = report
+            .published_release
+            .clone()
+            .unwrap_or_else(|| spec.git_ref.as_deref().unwrap_or("HEAD").to_string());
+        println!(
+            "\n{} ({}) via {}/{}, {}",
+            spec.name,
+            version,
+            report.namespace,
+            report.deployment,
+            reconciled
+        );
+        for m in &report.mappings {
+            println!("  {:<40} {}", m.env_var, m.image);
+        }
+    }
+    println!("\nThe cluster stays on these images until the operator is redeployed or reinstalled.");
+    status
+}
+
+/// The `run` flags, shared by the batch, build, deploy-and-test and metadata steps.
+#[derive(Clone, Copy)]
+struct RunOptions<'a> {
+    release_tests: &'a testref::ReleaseTestsRef,
+    output_dir: &'a str,
+    registry_override: Option<&'a str>,
+    verbose: bool,
+    profile: bool,
+    no_auto_setup: bool,
+    dry_run: bool,
+    format: output::OutputFormat,
+    as_of: Option<&'a str>,
+    /// --image: a pre-built image replacing the build phase
+    image_override: Option<&'a str>,
+    image_tag: Option<&'a str>,
+    image_builder: incluster::ImageBuilder,
+    rbac_profile: rbac::RbacProfile,
+    patches: &'a [patch::ComponentPatch],
+    go: &'a gotoolchain::GoOptions,
+    prune_keep: Option<usize>,
+    deploy_only: bool,
+    sources: &'a [imagesource::SourceSpec],
+    image_overrides: &'a [deploy::mapping::ImageOverride],
+    go_env: &'a incluster::JobGoEnv,
+    force_unlock: bool,
+    verify_chains: bool,
+    pac_smoke: Option<&'a str>,
+    skip_triggers_probe: bool,
+    skip_admission_probe: bool,
+    check_leaks: bool,
+    continue_on_build_failure: bool,
+    build_where: build::BuildWhere,
+}
+
+/// Deploy and test only (used in-cluster where builds already happened locally).
+/// With `skip_deploy`, test whatever is currently deployed.
+async fn run_deploy_and_test(
+    specs: &[component::ComponentSpec],
+    tags: &str,
+    opts: &RunOptions<'_>,
+    skip_deploy: bool,
+    failed_builds: &[String],
+) -> ExitStatus {
+    let RunOptions {
+        release_tests,
+        output_dir,
+        registry_override,
+        verbose,
+        profile,
+        no_auto_setup,
+        as_of,
+        patches,
+        sources,
+        image_overrides,
+        verify_chains,
+        pac_smoke,
+        skip_triggers_probe,
+        skip_admission_probe,
+        check_leaks,
+        ..
+    } = *opts;
+    let mut reports = Vec::new();
+    if skip_deploy {
+        eprintln!("\n=== Skipping build and deploy: testing the current deployment ===");
+    } else {
+        match deploy_phase(specs, sources, image_overrides, registry_override, verbose, no_auto_setup).await {
+            Ok(r) => reports = r,
+            Err(code) => return code,
+        }
+    }
+
+    if !reports.is_empty() {
+        write_manifest_drift(output_dir, &reports);
+        check_image_coverage(output_dir, &reports).await;
+    }
+
+    // --check-leaks: snapshot what the probes and tests may leave behind
+    let leak_baseline = if check_leaks {
+        match tokio::task::spawn_blocking(leaks::capture).await {
+            Ok(Ok(state)) => Some(state),
+            Ok(Err(e)) => {
+                warnings::warn(format!("Skipping the leak check: cluster snapshot failed: {e:#}"));
+                None
+            }
+            Err(e) => {
+                warnings::warn(format!("Skipping the leak check: cluster snapshot panicked: {e}"));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Webhook admission: PipelineRuns created before the webhook serves again fail
+    let mut synthetic = Vec::new();
+    let admission = if skip_admission_probe {
+        None
+    } else {
+        eprintln!("\n=== Waiting for the pipelines webhook to admit PipelineRuns ===");
+        match tokio::task::spawn_blocking(admission::wait_ready).await {
+            Ok(Ok(readiness)) => {
+                if readiness.ready {
+                    eprintln!("  Webhook admitted a dry-run PipelineRun after {:.1}s ({} attempt(s))", readiness.time_to_ready_secs, readiness.attempts);
+                } else {
+                    eprintln!("  Webhook admission readiness FAILED: {}", readiness.error.as_deref().unwrap_or("not ready"));
+                    synthetic.push(readiness.as_test());
+                }
+                Some(readiness)
+            }
+            Ok(Err(e)) => {
+                warnings::warn(format!("Could not check webhook admission readiness: {e:#}"));
+                None
+            }
+            Err(e) => {
+                warnings::warn(format!("Webhook admission readiness check panicked: {e}"));
+                None
             }
-        },
+        }
     };
-
-    // Deploy phase
-    eprintln!("\n=== Deploying (in-cluster) ===");
-    for spec in specs {
-        let image_names = match load_image_names_from_config(&spec.name) {
-            Ok(names) => names,
-            Err(e) => {
-                eprintln!("WARNING: Could not load images for {}: {e:#}", spec.name);
-                continue;
+    let admission_failed = admission.as_ref().is_some_and(|a| !a.ready);
+
+    // Triggers sanity probe: a broken triggers deployment fails the run before the suite
+    if !admission_failed && !skip_triggers_probe && specs.iter().any(|s| s.name == "triggers") {
+        eprintln!("\n=== Probing Triggers EventListener ===");
+        match tokio::task::spawn_blocking(triggers::probe).await {
+            Ok(test) => synthetic.push(test),
+            Err(e) => warnings::warn(format!("Triggers EventListener probe panicked: {e}")),
+        }
+    }
+    let probe_failed = synthetic.iter().any(|t| t.spec == triggers::SPEC && !t.passed);
+
+    // Chains signing check, reported with the test results
+    if verify_chains && specs.iter().any(|s| s.name == "chains") {
+        eprintln!("\n=== Verifying Chains signing ===");
+        match tokio::task::spawn_blocking(chains::verify_signing).await {
+            Ok(test) => synthetic.push(test),
+            Err(e) => warnings::warn(format!("Chains signing verification panicked: {e}")),
+        }
+    } else if verify_chains {
+        eprintln!("Skipping Chains signing verification: chains is not among the components");
+    }
+    if let Some(repo) = pac_smoke {
+        if specs.iter().any(|s| s.name == "pipelines-as-code") {
+            eprintln!("\n=== Pipelines-as-Code smoke test ({}) ===", repo);
+            let repo = repo.to_string();
+            match tokio::task::spawn_blocking(move || pac::smoke_test(&repo)).await {
+                Ok(test) => synthetic.push(test),
+                Err(e) => warnings::warn(format!("Pipelines-as-Code smoke test panicked: {e}")),
             }
-        };
-        let comp_name = spec.name.clone();
-        let registry_route = registry_route.clone();
-        let result = tokio::task::spawn_blocking(move || {
-            deploy::run_deploy(&comp_name, &registry_route, &image_names, verbose)
-        })
-        .await;
-        match result {
-            Ok(Ok(())) => {}
-            Ok(Err(e)) => eprintln!("WARNING: Deploy failed for {}: {e:#}", spec.name),
-            Err(e) => eprintln!("WARNING: Deploy panicked for {}: {e}", spec.name),
+        } else {
+            eprintln!("Skipping Pipelines-as-Code smoke test: pipelines-as-code is not among the components");
         }
     }
 
     // Test phase
-    eprintln!("\n=== Running tests (in-cluster) ===");
-    let test_result = test::run_tests(tags, release_tests_ref, std::path::Path::new(output_dir), verbose, profile).await;
+    let test_result = if admission_failed || probe_failed {
+        let probe = if admission_failed { "Webhook admission readiness check" } else { "Triggers EventListener probe" };
+        eprintln!("\n{} failed; skipping the test suite", probe);
+        test::write_synthetic_results(std::path::Path::new(output_dir), &synthetic).map(|_| false)
+    } else {
+        eprintln!("\n=== Running tests (in-cluster) ===");
+        test::run_tests(tags, &release_tests.git_ref, std::path::Path::new(output_dir), profile, &synthetic).await
+    };
 
-    // Write as-of metadata for dashboard tracking if --as-of was used
-    if let Some(date) = as_of {
-        write_as_of_metadata(output_dir, date, specs);
+    if let Some(before) = leak_baseline {
+        eprintln!("\n=== Checking for leaked cluster resources ===");
+        let dir = std::path::PathBuf::from(output_dir);
+        match tokio::task::spawn_blocking(move || leaks::check(&before, &dir)).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => warnings::warn(format!("Leak check failed: {e:#}")),
+            Err(e) => warnings::warn(format!("Leak check panicked: {e}")),
+        }
+    }
+
+    // Write run metadata for dashboard tracking if --as-of, --patches, --source or
+    // --image-override was used, to record failed builds, the release-tests branch
+    // picked for the operator or its fork, or the webhook's time to ready, or to
+    // record the warnings of the run
+    if as_of.is_some() || !patches.is_empty() || !sources.is_empty() || !image_overrides.is_empty()
+        || !failed_builds.is_empty() || !release_tests.is_explicit() || release_tests.repo != test::RELEASE_TESTS_REPO
+        || admission.is_some() || !warnings::summary().is_empty()
+    {
+        write_run_metadata(specs, opts, &reports, failed_builds, admission.as_ref());
     }
 
     match test_result {
-        Ok(true) => 0,
-        Ok(false) => 1,
+        Ok(passed) => ExitStatus::from_passed(passed),
         Err(e) => {
             eprintln!("Error running tests: {e:#}");
-            1
+            ExitStatus::Failure
+        }
+    }
+}
+
+/// Resolve the release-tests ref (see `testref::resolve`) off the async runtime.
+async fn resolve_release_tests_ref(flag: Option<String>) -> testref::ReleaseTestsRef {
+    let fallback = testref::ReleaseTestsRef::given(flag.as_deref());
+    match tokio::task::spawn_blocking(move || testref::resolve(flag.as_deref())).await {
+        Ok(r) => r,
+        Err(e) => {
+            warnings::warn(format!("Release-tests ref detection panicked: {e}"));
+            fallback
+        }
+    }
+}
+
+/// Write `results/manifest-drift.json`: per component, how the operator-rendered
+/// manifests changed across the deploy.
+fn write_manifest_drift(output_dir: &str, reports: &[deploy::DeployReport]) {
+    let drift: std::collections::BTreeMap<&str, &deploy::drift::ManifestDrift> = reports
+        .iter()
+        .filter_map(|r| r.drift.as_ref().map(|d| (r.component.as_str(), d)))
+        .collect();
+    let results_dir = std::path::Path::new(output_dir).join("results");
+    let path = results_dir.join("manifest-drift.json");
+    let written = std::fs::create_dir_all(&results_dir)
+        .map_err(anyhow::Error::from)
+        .and_then(|_| Ok(serde_json::to_string_pretty(&drift)?))
+        .and_then(|json| Ok(std::fs::write(&path, json)?));
+    match written {
+        Ok(()) => eprintln!("Wrote manifest drift to {}", path.display()),
+        Err(e) => warnings::warn(format!("Could not write manifest drift: {e:#}")),
+    }
+}
+
+/// Compare the IMAGE_ env vars the deploys set with all those of the installed
+/// operator CSV, warn about released images left in place, and write
+/// `results/image-coverage.json`. A failed check is a warning.
+async fn check_image_coverage(output_dir: &str, reports: &[deploy::DeployReport]) {
+    let overridden = deploy::coverage::overridden_env(reports);
+    let deployed: Vec<String> = reports.iter().map(|r| r.component.clone()).collect();
+    let namespace = reports[0].namespace.clone();
+    let result = tokio::task::spawn_blocking(move || -> anyhow::Result<deploy::coverage::ImageCoverage> {
+        let (rt, client) = k8s::create_kube_client()?;
+        let (csv, csv_env) = deploy::coverage::read_csv_image_env(&rt, &client, &namespace)?;
+        let cfg = config::load_config(&config::default_config_path())?;
+        Ok(deploy::coverage::coverage(&csv, &csv_env, &overridden, &cfg))
+    }).await;
+    let coverage = match result {
+        Ok(Ok(c)) => c,
+        Ok(Err(e)) => {
+            warnings::warn(format!("Could not check operator image coverage: {e:#}"));
+            return;
+        }
+        Err(e) => {
+            warnings::warn(format!("Operator image coverage check panicked: {e}"));
+            return;
         }
+    };
+    deploy::coverage::print_coverage(&coverage, &deployed);
+
+    let path = std::path::Path::new(output_dir).join("results/image-coverage.json");
+    let written = serde_json::to_string_pretty(&coverage)
+        .map_err(anyhow::Error::from)
+        .and_then(|json| Ok(std::fs::write(&path, json)?));
+    if let Err(e) = written {
+        warnings::warn(format!("Could not write image coverage: {e:#}"));
     }
 }
 
-/// Write as-of metadata file for dashboard tracking.
+/// Write run metadata file for dashboard tracking.
 ///
-/// Creates `results/metadata.json` with as_of_date and resolved component refs.
-/// This is read by the publish command to include in run data.
-fn write_as_of_metadata(output_dir: &str, as_of: &str, specs: &[component::ComponentSpec]) {
+/// Creates `results/metadata.json` with the as-of date (if any), resolved component
+/// refs and image sources (with the published release deployed), applied patches, the
+/// `--image-override` pullspecs, the components left out because their build failed, the release-tests ref and how it was
+/// chosen, the webhook admission readiness, the `--meta` metadata, the versions and checksums of the tools used, and the
+/// warnings emitted so far with their counts. This is read by the publish command to include in run data.
+fn write_run_metadata(
+    specs: &[component::ComponentSpec],
+    opts: &RunOptions<'_>,
+    reports: &[deploy::DeployReport],
+    failed_builds: &[String],
+    admission: Option<&admission::AdmissionReadiness>,
+) {
+    let RunOptions { output_dir, as_of, patches, sources, image_overrides, release_tests, .. } = *opts;
     let output_path = std::path::Path::new(output_dir);
     let results_dir = output_path.join("results");
     if std::fs::create_dir_all(&results_dir).is_err() {
-        eprintln!("WARNING: Could not create results directory for metadata");
+        warnings::warn("Could not create results directory for metadata");
         return;
     }
 
     let meta_path = results_dir.join("metadata.json");
     let meta = serde_json::json!({
+        "generated_at": timestamp::now_rfc3339(),
         "as_of_date": as_of,
+        "as_of_cutoff": as_of.and_then(|d| timestamp::end_of_day_utc(d).ok()).map(timestamp::to_rfc3339),
         "resolved_components": specs.iter().map(|s| {
+            let published_release = reports
+                .iter()
+                .find(|r| r.component == s.name)
+                .and_then(|r| r.published_release.as_deref());
             serde_json::json!({
                 "name": s.name,
                 "git_ref": s.git_ref.as_deref().unwrap_or("HEAD"),
-                "as_of_date": s.as_of_date
+                "as_of_date": s.as_of_date,
+                "source": imagesource::source_for(sources, &s.name).as_str(),
+                "published_release": published_release
             })
-        }).collect::<Vec<_>>()
+        }).collect::<Vec<_>>(),
+        "patches": patches.iter().map(patch::PatchRecord::from).collect::<Vec<_>>(),
+        "image_overrides": image_overrides.iter().map(|o| serde_json::json!({"env_var": o.env_var, "image": o.image})).collect::<Vec<_>>(),
+        "failed_builds": failed_builds,
+        "release_tests": release_tests,
+        "admission": admission,
+        "batch_id": batch::current_batch_id(),
+        "meta": runmeta::current(),
+        "tools": tools::inventory(),
+        "warnings": warnings::summary()
     });
 
     match serde_json::to_string_pretty(&meta) {
         Ok(json_str) => {
             if let Err(e) = std::fs::write(&meta_path, json_str) {
-                eprintln!("WARNING: Could not write metadata.json: {e}");
+                warnings::warn(format!("Could not write metadata.json: {e}"));
             } else {
-                eprintln!("Wrote as-of metadata to {}", meta_path.display());
+                eprintln!("Wrote run metadata to {}", meta_path.display());
             }
         }
         Err(e) => {
-            eprintln!("WARNING: Could not serialize metadata: {e}");
+            warnings::warn(format!("Could not serialize metadata: {e}"));
         }
     }
 }
@@ -677,7 +1974,12 @@ fn load_image_names_from_config(component: &str) -> anyhow::Result<Vec<String>>
     Ok(comp.images.keys().cloned().collect())
 }
 
-fn run_build(component: &str, external_registry: Option<&str>) -> anyhow::Result<()> {
+fn run_build(
+    component: &str,
+    external_registry: Option<&str>,
+    patches: &[patch::ComponentPatch],
+    go: &gotoolchain::GoOptions,
+) -> anyhow::Result<()> {
     // Stage 1: Registry setup
     let pb = progress::stage_spinner("Registry setup");
     let route = registry::get_registry_route()?;
@@ -688,11 +1990,21 @@ fn run_build(component: &str, external_registry: Option<&str>) -> anyhow::Result
 
     // Stage 2: Clone upstream source
     let pb = progress::stage_spinner("Clone upstream source");
-    let temp_dir = tempfile::tempdir()?;
+    let temp_dir = workspace::scoped(&format!("{component}-clone"))?;
     let repo_url = format!("https://github.com/tektoncd/{}.git", component);
-    build::clone_repo(&repo_url, temp_dir.path())?;
+    gitops::clone(&repo_url, temp_dir.path(), None, Some(&pb))?;
     progress::finish_spinner(&pb, true);
 
+    for p in patches.iter().filter(|p| p.component != component) {
+        warnings::warn(format!("Ignoring patch {} (building {})", p.to_arg(), component));
+    }
+    let component_patches = patch::for_component(patches, component);
+    if !component_patches.is_empty() {
+        let pb = progress::stage_spinner("Apply patches");
+        patch::apply_patches(&repo_url, temp_dir.path(), &component_patches)?;
+        progress::finish_spinner(&pb, true);
+    }
+
     // Stage 3: Build images with ko
     let cfg = config::load_config(&config::default_config_path())?;
     let comp_cfg = cfg
@@ -706,6 +2018,9 @@ fn run_build(component: &str, external_registry: Option<&str>) -> anyhow::Result
         &registry_target,
         &comp_cfg.import_paths,
         external_registry,
+        &comp_cfg.ko.for_build(component, None),
+        go,
+        None,
     )?;
     progress::finish_spinner(&pb, true);
 
@@ -722,30 +2037,85 @@ fn run_build(component: &str, external_registry: Option<&str>) -> anyhow::Result
 }
 
 /// Multi-component orchestration: build all in parallel, then create in-cluster Job for deploy+test.
-/// Returns exit code: 0=success, 2=error.
-async fn run_multi(
-    specs: Vec<component::ComponentSpec>,
-    dry_run: bool,
-    json_output: bool,
-    tags: &str,
-    release_tests_ref: &str,
-    output_dir: &str,
-    registry_override: Option<&str>,
-    _verbose: bool,
-    as_of: Option<&str>,
-    image_override: Option<&str>,
-) -> i32 {
-    let cfg = match config::load_config(&config::default_config_path()) {
+/// With `deploy_only`, deploy from here instead and stop before tests.
+/// Returns Success once the Job is created, or the deploy outcome with `deploy_only`.
+async fn run_multi(mut specs: Vec<component::ComponentSpec>, tags: &str, opts: &RunOptions<'_>) -> ExitStatus {
+    let RunOptions {
+        release_tests,
+        output_dir,
+        registry_override,
+        verbose,
+        dry_run,
+        format,
+        as_of,
+        image_override,
+        image_tag,
+        image_builder,
+        rbac_profile,
+        patches,
+        go,
+        prune_keep,
+        deploy_only,
+        sources,
+        image_overrides,
+        go_env,
+        force_unlock,
+        verify_chains,
+        pac_smoke,
+        skip_triggers_probe,
+        skip_admission_probe,
+        check_leaks,
+        continue_on_build_failure,
+        build_where,
+        ..
+    } = *opts;
+    let mut cfg = match config::load_config(&config::default_config_path()) {
         Ok(c) => c,
         Err(e) => {
             eprintln!("Error loading config: {e:#}");
-            return 2;
+            return ExitStatus::Error;
         }
     };
 
     // Dry-run: just print the plan
     if dry_run {
-        return print_dry_run_plan(&specs, &cfg, json_output, as_of);
+        return print_dry_run_plan(&specs, &cfg, format, as_of);
+    }
+
+    let (published_specs, source_specs): (Vec<_>, Vec<_>) = specs
+        .iter()
+        .cloned()
+        .partition(|s| imagesource::source_for(sources, &s.name) != imagesource::ImageSource::Source);
+    if build_where == build::BuildWhere::Cluster {
+        let blockers = build::cluster_build_blockers(&source_specs, &cfg.components, patches);
+        if !blockers.is_empty() {
+            eprintln!("Error: cannot build in the cluster: {} (use --build-where local)", blockers.join("; "));
+            return ExitStatus::Error;
+        }
+    }
+    for s in &published_specs {
+        let comp = cfg.components.get(&s.name);
+        match imagesource::source_for(sources, &s.name) {
+            imagesource::ImageSource::Nightly => {
+                if comp.is_none_or(|c| c.nightly.is_none()) {
+                    eprintln!("Error: {} has no nightly release; use --source {}=source", s.name, s.name);
+                    return ExitStatus::Error;
+                }
+                if s.git_ref.is_some() {
+                    warnings::warn(format!("Ignoring ref {:?} for {}: deploying its nightly images", s.git_ref, s.name));
+                }
+            }
+            _ => {
+                if comp.is_none_or(|c| c.release.is_none()) {
+                    eprintln!("Error: {} has no upstream releases configured; use --source {}=source", s.name, s.name);
+                    return ExitStatus::Error;
+                }
+                if s.git_ref.is_none() {
+                    eprintln!("Error: --source release needs a version for {0} (e.g. --components {0}:v1.2.3)", s.name);
+                    return ExitStatus::Error;
+                }
+            }
+        }
     }
 
     // When --image is provided, skip build phase entirely and use pre-built image
@@ -764,9 +2134,13 @@ async fn run_multi(
             "run".to_string(),
             "--components".to_string(), spec_str,
             "--tags".to_string(), tags.to_string(),
-            "--release-tests-ref".to_string(), release_tests_ref.to_string(),
             "--output-dir".to_string(), output_dir.to_string(),
         ];
+        // An automatic release-tests ref is detected again by the Job
+        if release_tests.is_explicit() {
+            cli_args.push("--release-tests-ref".to_string());
+            cli_args.push(release_tests.git_ref.clone());
+        }
         if let Some(reg) = registry_override {
             cli_args.push("--registry".to_string());
             cli_args.push(reg.to_string());
@@ -775,16 +2149,39 @@ async fn run_multi(
             cli_args.push("--as-of".to_string());
             cli_args.push(date.to_string());
         }
+        cli_args.extend(imagesource::to_args(sources));
+        cli_args.extend(deploy::mapping::to_args(image_overrides));
+        cli_args.extend(runmeta::to_args());
+        cli_args.extend(logging::to_args());
+        cli_args.extend(tools::to_args());
+        cli_args.extend(scopeduser::to_args());
+        cli_args.extend(testref::to_args());
+        cli_args.extend(gitops::to_args());
+        if force_unlock {
+            cli_args.push("--force-unlock".to_string());
+        }
+        report_cluster_lock(force_unlock).await;
 
         let img_clone = img.to_string();
+        let go_env = go_env.clone();
+        let release_tests_ref = release_tests.git_ref.clone();
         // Registry route not needed when using pre-built image, pass empty string
         let result = tokio::task::spawn_blocking(move || {
-            incluster::run_incluster("", "openshift-pipelines", &cli_args, Some(&img_clone))
+            let job = incluster::JobOptions {
+                image_override: Some(&img_clone),
+                image_tag: None,
+                image_builder,
+                rbac_profile,
+                go_env: &go_env,
+                release_tests_ref: &release_tests_ref,
+                builds: false,
+            };
+            incluster::run_incluster("", "openshift-pipelines", &cli_args, &job)
         }).await;
         return match result {
-            Ok(Ok(())) => 0,
-            Ok(Err(e)) => { eprintln!("Error creating in-cluster Job: {e:#}"); 2 }
-            Err(e) => { eprintln!("Error: in-cluster task panicked: {e}"); 2 }
+            Ok(Ok(())) => ExitStatus::Success,
+            Ok(Err(e)) => { eprintln!("Error creating in-cluster Job: {e:#}"); ExitStatus::Error }
+            Err(e) => { eprintln!("Error: in-cluster task panicked: {e}"); ExitStatus::Error }
         };
     }
 
@@ -795,44 +2192,32 @@ async fn run_multi(
             Ok(r) => r,
             Err(e) => {
                 eprintln!("Error: {e:#}");
-                return 2;
+                return ExitStatus::Error;
             }
         },
     };
 
-    if let Err(e) = registry::ensure_namespace(registry::DEFAULT_NAMESPACE) {
-        eprintln!("Error ensuring namespace: {e:#}");
-        return 2;
-    }
-    if let Err(e) = registry::registry_login(&registry_route) {
-        eprintln!("Error logging into registry: {e:#}");
-        return 2;
-    }
-
-    let registry_target = format!("{}/{}", registry_route, registry::DEFAULT_NAMESPACE);
-
-    // Build phase: build all components in parallel
-    eprintln!("\n=== Building components in parallel ===");
-    let results = build::build_components_parallel(&specs, &cfg.components, &registry_target).await;
-
-    let mut all_images: Vec<(String, Vec<String>)> = Vec::new();
-    let mut build_failed = false;
-
-    for (name, result) in results {
-        match result {
-            Ok(images) => {
-                eprintln!("  {} built {} images", name, images.len());
-                all_images.push((name, images));
-            }
-            Err(e) => {
-                eprintln!("  {} FAILED: {e:#}", name);
-                build_failed = true;
-            }
+    // --build-where cluster: the in-cluster Job builds before it deploys
+    let mut tags = tags.to_string();
+    let failed_builds = if build_where == build::BuildWhere::Cluster {
+        eprintln!("\nBuilding in the in-cluster Job (--build-where cluster)");
+        Vec::new()
+    } else {
+        match build_phase(&mut specs, &mut tags, &mut cfg, &registry_route, opts).await {
+            Ok(failed) => failed,
+            Err(status) => return status,
         }
-    }
+    };
 
-    if build_failed {
-        return 2;
+    // --deploy-only: deploy from here (auto-setup already ran) and leave testing to the user
+    if deploy_only {
+        let lock = acquire_cluster_lock(force_unlock).await;
+        let status = match deploy_phase(&specs, sources, image_overrides, Some(&registry_route), verbose, true).await {
+            Ok(reports) => print_deploy_summary(&specs, &reports),
+            Err(status) => status,
+        };
+        lock.release().await;
+        return status;
     }
 
     // Deploy+test phase: create in-cluster Job instead of running locally
@@ -847,9 +2232,12 @@ async fn run_multi(
         "run".to_string(),
         "--components".to_string(), spec_str,
         "--tags".to_string(), tags.to_string(),
-        "--release-tests-ref".to_string(), release_tests_ref.to_string(),
         "--output-dir".to_string(), output_dir.to_string(),
     ];
+    if release_tests.is_explicit() {
+        cli_args.push("--release-tests-ref".to_string());
+        cli_args.push(release_tests.git_ref.clone());
+    }
     if let Some(reg) = registry_override {
         cli_args.push("--registry".to_string());
         cli_args.push(reg.to_string());
@@ -858,32 +2246,230 @@ async fn run_multi(
         cli_args.push("--as-of".to_string());
         cli_args.push(date.to_string());
     }
+    // Patches were applied to the local build and the Job only records them in metadata,
+    // unless the Job builds (--build-where cluster)
+    if !patches.is_empty() {
+        cli_args.push("--patches".to_string());
+        cli_args.push(patches.iter().map(|p| p.to_arg()).collect::<Vec<_>>().join(","));
+    }
+    // Nightly and release components are resolved and deployed by the Job
+    cli_args.extend(imagesource::to_args(sources));
+    cli_args.extend(deploy::mapping::to_args(image_overrides));
+    cli_args.extend(runmeta::to_args());
+    cli_args.extend(logging::to_args());
+    cli_args.extend(tools::to_args());
+    cli_args.extend(scopeduser::to_args());
+    cli_args.extend(testref::to_args());
+    cli_args.extend(gitops::to_args());
+    if force_unlock {
+        cli_args.push("--force-unlock".to_string());
+    }
+    if verify_chains {
+        cli_args.push("--verify-chains".to_string());
+    }
+    if let Some(repo) = pac_smoke {
+        cli_args.push("--pac-smoke".to_string());
+        cli_args.push(repo.to_string());
+    }
+    if skip_triggers_probe {
+        cli_args.push("--skip-triggers-probe".to_string());
+    }
+    if skip_admission_probe {
+        cli_args.push("--skip-admission-probe".to_string());
+    }
+    if check_leaks {
+        cli_args.push("--check-leaks".to_string());
+    }
+    if !failed_builds.is_empty() {
+        cli_args.push("--failed-builds".to_string());
+        cli_args.push(failed_builds.join(","));
+    }
+    let builds = build_where == build::BuildWhere::Cluster;
+    if builds {
+        cli_args.extend(["--build-where".to_string(), "cluster".to_string()]);
+        if let Some(v) = go.version {
+            cli_args.push("--go-version".to_string());
+            cli_args.push(v.to_string());
+        }
+        if !go.matrix.is_empty() {
+            cli_args.push("--go-matrix".to_string());
+            cli_args.push(go.matrix.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(","));
+        }
+        if let Some(keep) = prune_keep {
+            cli_args.push("--prune-keep".to_string());
+            cli_args.push(keep.to_string());
+        }
+        if continue_on_build_failure {
+            cli_args.push("--continue-on-build-failure".to_string());
+        }
+        cli_args.extend(build::to_args());
+    }
+    report_cluster_lock(force_unlock).await;
 
     let registry_route_clone = registry_route.clone();
+    let image_tag = image_tag.map(str::to_string);
+    let go_env = go_env.clone();
+    let release_tests_ref = release_tests.git_ref.clone();
     let result = tokio::task::spawn_blocking(move || {
-        incluster::run_incluster(&registry_route_clone, "openshift-pipelines", &cli_args, None)
+        let job = incluster::JobOptions {
+            image_override: None,
+            image_tag: image_tag.as_deref(),
+            image_builder,
+            rbac_profile,
+            go_env: &go_env,
+            release_tests_ref: &release_tests_ref,
+            builds,
+        };
+        incluster::run_incluster(&registry_route_clone, "openshift-pipelines", &cli_args, &job)
     }).await;
     match result {
-        Ok(Ok(())) => 0,
-        Ok(Err(e)) => { eprintln!("Error creating in-cluster Job: {e:#}"); 2 }
-        Err(e) => { eprintln!("Error: in-cluster task panicked: {e}"); 2 }
+        Ok(Ok(())) => ExitStatus::Success,
+        Ok(Err(e)) => { eprintln!("Error creating in-cluster Job: {e:#}"); ExitStatus::Error }
+        Err(e) => { eprintln!("Error: in-cluster task panicked: {e}"); ExitStatus::Error }
+    }
+}
+
+/// Build the components of `specs` that are built from source, pushing to
+/// `registry` (the registry route, or the internal registry in a Job). Failed
+/// builds abort unless `continue_on_build_failure`: then their components are
+/// dropped from `specs`, their specs from `tags`, and they are returned.
+async fn build_phase(
+    specs: &mut Vec<component::ComponentSpec>,
+    tags: &mut String,
+    cfg: &mut config::Config,
+    registry: &str,
+    opts: &RunOptions<'_>,
+) -> Result<Vec<String>, ExitStatus> {
+    let RunOptions { sources, image_overrides, patches, go, prune_keep, output_dir, continue_on_build_failure, .. } = *opts;
+    let (published_specs, mut build_specs): (Vec<_>, Vec<_>) = specs
+        .iter()
+        .cloned()
+        .partition(|s| imagesource::source_for(sources, &s.name) != imagesource::ImageSource::Source);
+
+    if let Err(e) = registry::ensure_namespace(registry::DEFAULT_NAMESPACE) {
+        eprintln!("Error ensuring namespace: {e:#}");
+        return Err(ExitStatus::Error);
+    }
+    if let Err(e) = registry::registry_login(registry) {
+        eprintln!("Error logging into registry: {e:#}");
+        return Err(ExitStatus::Error);
+    }
+
+    let registry_target = format!("{}/{}", registry, registry::DEFAULT_NAMESPACE);
+    imagestream::preflight(registry::DEFAULT_NAMESPACE, prune_keep);
+
+    // --image-override: overridden images are not built, nor components left with none
+    let mut prebuilt = Vec::new();
+    build_specs.retain(|s| {
+        let builds = cfg.components.get_mut(&s.name)
+            .is_none_or(|c| deploy::mapping::skip_overridden_builds(c, image_overrides));
+        if !builds {
+            prebuilt.push(s.name.clone());
+        }
+        builds
+    });
+    if !prebuilt.is_empty() {
+        eprintln!("\nUsing --image-override images (no build) for: {}", prebuilt.join(", "));
+    }
+
+    if !published_specs.is_empty() {
+        let names: Vec<String> = published_specs
+            .iter()
+            .map(|s| format!("{} ({})", s.name, imagesource::source_for(sources, &s.name).as_str()))
+            .collect();
+        eprintln!("\nUsing published images (no build) for: {}", names.join(", "));
+    }
+
+    let mut failed_builds: Vec<String> = Vec::new();
+    if !build_specs.is_empty() {
+        // Build phase: build all components in parallel
+        eprintln!("\n=== Building components in parallel ===");
+        let builds = build::build_components_parallel(
+            &build_specs,
+            &cfg.components,
+            &registry_target,
+            patches,
+            go,
+            std::path::Path::new(output_dir),
+        )
+        .await;
+
+        let mut build_failed = false;
+        for b in &builds {
+            match &b.result {
+                Ok(images) => eprintln!("  {} built {} images", b.component, images.len()),
+                Err(e) => {
+                    eprintln!("  {} FAILED: {e:#} (log: {})", b.component, b.log.display());
+                    build_failed = true;
+                }
+            }
+        }
+        build::print_build_summary(&builds);
+        match build::write_build_manifest(std::path::Path::new(output_dir), &builds) {
+            Ok(path) => eprintln!("Build manifest: {}", path.display()),
+            Err(e) => warnings::warn(format!("Failed to write build manifest: {e:#}")),
+        }
+
+        if build_failed {
+            if !continue_on_build_failure {
+                return Err(ExitStatus::Error);
+            }
+            failed_builds = builds.iter().filter(|b| b.result.is_err()).map(|b| b.component.clone()).collect();
+        }
+    }
+
+    // --continue-on-build-failure: go on with the components that built, skipping their specs
+    if !failed_builds.is_empty() {
+        specs.retain(|s| !failed_builds.contains(&s.name));
+        if specs.is_empty() {
+            eprintln!("Error: no component built successfully");
+            return Err(ExitStatus::Error);
+        }
+        let skipped_tags: Vec<String> = failed_builds
+            .iter()
+            .filter_map(|name| cfg.components.get(name))
+            .flat_map(|c| c.test_tags.iter().cloned())
+            .collect();
+        *tags = component::exclude_tags(tags, &skipped_tags);
+        warnings::warn(format!(
+            "Continuing without {} (build failed); testing with tags: {}",
+            failed_builds.join(", "),
+            tags
+        ));
     }
+
+    Ok(failed_builds)
 }
 
 /// Print the dry-run execution plan using the dryrun module.
 fn print_dry_run_plan(
     specs: &[component::ComponentSpec],
     cfg: &config::Config,
-    json_output: bool,
+    format: output::OutputFormat,
     as_of: Option<&str>,
-) -> i32 {
+) -> ExitStatus {
     let resolved = dryrun::resolve_components_with_date(specs, &cfg.components, as_of);
-    if json_output {
-        dryrun::print_json(&resolved);
+    if format.is_structured() {
+        if let Err(e) = output::print(format, &resolved) {
+            eprintln!("Error: {e:#}");
+            return ExitStatus::Error;
+        }
     } else {
         dryrun::print_table(&resolved);
+        let names: Vec<String> = specs.iter().map(|s| s.name.clone()).collect();
+        match config::deploy_groups(cfg, &names) {
+            Ok(groups) if groups.len() > 1 => {
+                let order: Vec<String> = groups.iter().map(|g| format!("[{}]", g.join(", "))).collect();
+                println!("\nDeploy order: {}", order.join(" -> "));
+            }
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("Error: {e:#}");
+                return ExitStatus::Error;
+            }
+        }
     }
-    0
+    ExitStatus::Success
 }
 
 /// Run performance tests standalone (after functional tests).
@@ -896,7 +2482,7 @@ async fn run_perf_tests_standalone(
     perf_ref: Option<&str>,
     verbose: bool,
     profile: bool,
-) -> i32 {
+) -> ExitStatus {
     eprintln!("\n========================================");
     eprintln!("PERFORMANCE TESTS");
     eprintln!("========================================\n");
@@ -906,17 +2492,23 @@ async fn run_perf_tests_standalone(
         Ok(s) => s,
         Err(e) => {
             eprintln!("Invalid perf scenario: {}", e);
-            return 2;
+            return ExitStatus::Error;
         }
     };
 
-    // Clone performance repo to temp dir
-    let temp_dir = std::env::temp_dir().join("streamstress-perf");
-    let perf_repo_dir = match perf::clone_perf_repo(&temp_dir, perf_ref) {
+    // Clone performance repo into the workspace
+    let temp_dir = match workspace::scoped("perf") {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("Failed to create perf workspace: {e:#}");
+            return ExitStatus::Error;
+        }
+    };
+    let perf_repo_dir = match perf::clone_perf_repo(temp_dir.path(), perf_ref) {
         Ok(d) => d,
         Err(e) => {
             eprintln!("Failed to clone performance repo: {}", e);
-            return 2;
+            return ExitStatus::Error;
         }
     };
 
@@ -924,7 +2516,7 @@ async fn run_perf_tests_standalone(
     let perf_output_dir = std::path::Path::new(output_dir).join("perf");
     if let Err(e) = std::fs::create_dir_all(&perf_output_dir) {
         eprintln!("Failed to create perf output directory: {}", e);
-        return 2;
+        return ExitStatus::Error;
     }
 
     // Start resource profiling if requested
@@ -932,7 +2524,7 @@ async fn run_perf_tests_standalone(
         match start_perf_profiling().await {
             Ok(p) => Some(p),
             Err(e) => {
-                eprintln!("WARNING: Failed to start profiling: {}", e);
+                warnings::warn(format!("Failed to start profiling: {}", e));
                 None
             }
         }
@@ -944,21 +2536,19 @@ async fn run_perf_tests_standalone(
     let perf_result = perf::run_perf_tests(&perf_repo_dir, &scenario, &perf_output_dir, verbose);
 
     // Stop profiling and collect resource data
-    if let Some(p) = profiler {
-        if let Ok(resource_data) = stop_perf_profiling(p).await {
-            let resource_path = perf_output_dir.join("resource-profile.json");
-            if let Ok(json) = serde_json::to_string_pretty(&resource_data) {
-                if let Err(e) = std::fs::write(&resource_path, json) {
-                    eprintln!("WARNING: Failed to write resource profile: {}", e);
-                }
-            }
+    if let Some(p) = profiler && let Ok(resource_data) = stop_perf_profiling(p).await {
+        let resource_path = perf_output_dir.join("resource-profile.json");
+        if let Ok(json) = serde_json::to_string_pretty(&resource_data)
+            && let Err(e) = std::fs::write(&resource_path, json)
+        {
+            warnings::warn(format!("Failed to write resource profile: {}", e));
         }
     }
 
     match perf_result {
         Ok(result) => {
             if let Err(e) = perf::write_perf_results(&result, &perf_output_dir) {
-                eprintln!("WARNING: Failed to write perf results: {}", e);
+                warnings::warn(format!("Failed to write perf results: {}", e));
             }
 
             eprintln!("\nPerformance Test Results:");
@@ -975,18 +2565,18 @@ async fn run_perf_tests_standalone(
                 eprintln!("  P95 Latency: {:.2}s", p95);
             }
 
-            if result.passed { 0 } else { 1 }
+            ExitStatus::from_passed(result.passed)
         }
         Err(e) => {
             eprintln!("Performance test error: {}", e);
-            2
+            ExitStatus::Error
         }
     }
 }
 
 /// Start resource profiling for performance tests.
 async fn start_perf_profiling() -> anyhow::Result<profile::MetricsCollector> {
-    let client = kube::Client::try_default().await?;
+    let client = k8s::client().await?;
 
     // Check if metrics API is available
     if !profile::check_metrics_available(&client).await? {
@@ -998,21 +2588,7 @@ async fn start_perf_profiling() -> anyhow::Result<profile::MetricsCollector> {
 
 /// Stop profiling and return collected spec profiles.
 async fn stop_perf_profiling(collector: profile::MetricsCollector) -> anyhow::Result<Vec<profile::SpecProfile>> {
-    collector.stop().await
-}
-
-/// Combine exit codes from functional and performance tests.
-///
-/// Returns:
-/// - 0 if both passed
-/// - 1 if any test failed
-/// - 2 if any error occurred
-fn combine_exit_codes(func_exit: i32, perf_exit: i32) -> i32 {
-    match (func_exit, perf_exit) {
-        (0, 0) => 0,           // Both passed
-        (2, _) | (_, 2) => 2,  // Any error
-        _ => 1,                 // Any failure
-    }
+    collector.stop().await.map(|(specs, _)| specs)
 }
 
 /// Run batch historical tests for a date range.
@@ -1023,33 +2599,109 @@ fn combine_exit_codes(func_exit: i32, perf_exit: i32) -> i32 {
 fn run_batch_historical(
     range: &batch::DateRange,
     components: &Option<String>,
-    release_tests_ref: &str,
-    output_dir: &str,
     skip_build: bool,
-    registry: Option<&str>,
-    verbose: bool,
-    profile: bool,
-    no_auto_setup: bool,
-    dry_run: bool,
-) -> i32 {
+    i_know_what_im_doing: bool,
+    opts: &RunOptions<'_>,
+) -> ExitStatus {
+    let RunOptions {
+        release_tests,
+        output_dir,
+        registry_override: registry,
+        no_auto_setup,
+        dry_run,
+        format,
+        patches,
+        go,
+        prune_keep,
+        image_tag,
+        image_builder,
+        sources,
+        image_overrides,
+        go_env,
+        build_where,
+        ..
+    } = *opts;
     let dates = batch::generate_dates(range);
     let mut progress = batch::BatchProgress::new(dates.len());
 
     if dry_run {
+        let cfg = match config::load_config(&config::default_config_path()) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("Error loading config: {e:#}");
+                return ExitStatus::Error;
+            }
+        };
+        let specs = match components {
+            Some(s) => match component::parse_component_specs(s, &config::load_groups()) {
+                Ok(v) => v,
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    return ExitStatus::Error;
+                }
+            },
+            None => component::default_specs(),
+        };
+        let date_strs: Vec<String> = dates.iter().map(|d| d.format("%Y-%m-%d").to_string()).collect();
+        let date_refs: Vec<Option<&str>> = date_strs.iter().map(|d| Some(d.as_str())).collect();
+        let resolved = dryrun::resolve_components_for_dates(&specs, &cfg.components, &date_refs);
+
+        if format.is_structured() {
+            let plan = serde_json::json!({
+                "start": range.start.format("%Y-%m-%d").to_string(),
+                "end": range.end.format("%Y-%m-%d").to_string(),
+                "components": components,
+                "dates": date_strs,
+                "resolved": date_strs.iter().zip(&resolved).map(|(date, r)| {
+                    serde_json::json!({"date": date, "components": r})
+                }).collect::<Vec<_>>(),
+            });
+            if let Err(e) = output::print(format, &plan) {
+                eprintln!("Error: {e:#}");
+                return ExitStatus::Error;
+            }
+            return ExitStatus::Success;
+        }
+
         eprintln!("\n=== BATCH HISTORICAL RUN (DRY-RUN) ===");
         eprintln!("Date range: {} to {}", range.start, range.end);
         eprintln!("Total dates: {}", dates.len());
         eprintln!("Components: {:?}", components);
-        eprintln!("\nWould process dates:");
-        for date in &dates {
-            eprintln!("  {}", date.format("%Y-%m-%d"));
+        for (date, r) in date_strs.iter().zip(&resolved) {
+            println!("\n{}:", date);
+            dryrun::print_table(r);
+        }
+        return ExitStatus::Success;
+    }
+
+    // Refs are the same for every date: a mistyped one would fail each run in turn
+    let mut checks = vec![refcheck::RefCheck::new("--release-tests-ref", testref::repo(), &release_tests.git_ref)];
+    if let Some(s) = components.as_deref().filter(|_| !skip_build) {
+        let specs = match component::parse_component_specs(s, &config::load_groups()) {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return ExitStatus::Error;
+            }
+        };
+        match config::load_config(&config::default_config_path()) {
+            Ok(cfg) => checks.extend(refcheck::component_checks(&specs, &cfg.components)),
+            Err(e) => {
+                eprintln!("Error loading config: {e:#}");
+                return ExitStatus::Error;
+            }
         }
-        return 0;
+    }
+    if let Err(e) = refcheck::validate_refs(&checks) {
+        eprintln!("Error: {e:#}");
+        return ExitStatus::Error;
     }
 
+    let batch_id = batch::batch_id(range);
     eprintln!("\n=== BATCH HISTORICAL RUN ===");
     eprintln!("Date range: {} to {}", range.start, range.end);
     eprintln!("Total dates: {}", dates.len());
+    eprintln!("Batch id: {}", batch_id);
 
     for date in dates {
         let date_str = date.format("%Y-%m-%d").to_string();
@@ -1069,11 +2721,13 @@ fn run_batch_historical(
             "run".to_string(),
             "--as-of".to_string(),
             date_str.clone(),
-            "--release-tests-ref".to_string(),
-            release_tests_ref.to_string(),
             "--output-dir".to_string(),
             date_output_dir,
         ];
+        if release_tests.is_explicit() {
+            args.push("--release-tests-ref".to_string());
+            args.push(release_tests.git_ref.clone());
+        }
 
         if let Some(c) = components {
             args.push("--components".to_string());
@@ -1089,14 +2743,83 @@ fn run_batch_historical(
         if no_auto_setup {
             args.push("--no-auto-setup".to_string());
         }
+        if github::is_offline() {
+            args.push("--offline".to_string());
+        }
+        if i_know_what_im_doing {
+            args.push("--i-know-what-im-doing".to_string());
+        }
+        if k8s::api_retries() != k8s::DEFAULT_API_RETRIES {
+            args.push("--api-retries".to_string());
+            args.push(k8s::api_retries().to_string());
+        }
+        if setup::registry_storage() == setup::RegistryStorage::Pvc {
+            args.push("--registry-storage".to_string());
+            args.push("pvc".to_string());
+        }
+        args.extend(setup::operator_args());
+        if !patches.is_empty() {
+            args.push("--patches".to_string());
+            args.push(patches.iter().map(|p| p.to_arg()).collect::<Vec<_>>().join(","));
+        }
+        if let Some(v) = go.version {
+            args.push("--go-version".to_string());
+            args.push(v.to_string());
+        }
+        if !go.matrix.is_empty() {
+            args.push("--go-matrix".to_string());
+            args.push(go.matrix.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(","));
+        }
+        if let Some(keep) = prune_keep {
+            args.push("--prune-keep".to_string());
+            args.push(keep.to_string());
+        }
+        if let Some(tag) = image_tag {
+            args.push("--image-tag".to_string());
+            args.push(tag.to_string());
+        }
+        if image_builder != incluster::ImageBuilder::Auto {
+            args.push("--image-builder".to_string());
+            args.push(clap::ValueEnum::to_possible_value(&image_builder).map(|v| v.get_name().to_string()).unwrap_or_default());
+        }
+        args.extend(imagesource::to_args(sources));
+        args.extend(deploy::mapping::to_args(image_overrides));
+        args.extend(go_env.to_args());
+        if build_where == build::BuildWhere::Cluster {
+            args.push("--build-where".to_string());
+            args.push("cluster".to_string());
+        }
+        args.extend(runmeta::to_args());
+        args.extend(build::to_args());
+        args.extend(tools::to_args());
+        args.extend(scopeduser::to_args());
+        args.extend(testref::to_args());
+        args.extend(gitops::to_args());
 
         // Execute via subprocess (self-invocation)
         let mut cmd = std::process::Command::new(std::env::current_exe().unwrap());
-        cmd.args(&args);
-        if verbose {
-            cmd.arg("--verbose");
+        cmd.args(&args).env(batch::BATCH_ID_ENV, &batch_id);
+        cmd.args(logging::to_args());
+        cmd.args(confirm::to_args());
+        if let Some(path) = audit::path() {
+            cmd.arg("--audit-log").arg(path);
+        }
+        if let Some(dir) = cassette::record_dir() {
+            cmd.arg("--record").arg(dir);
         }
-        let status = cmd.status();
+        if workspace::keep() {
+            cmd.arg("--keep-workdir");
+        }
+        match progress::mode() {
+            progress::Mode::Quiet => {
+                cmd.arg("--quiet");
+            }
+            progress::Mode::Plain => {
+                cmd.arg("--no-progress");
+            }
+            progress::Mode::Spinner => {}
+        }
+        let status = audit::status(&mut cmd);
 
         let exit_code = match status {
             Ok(s) => s.code().unwrap_or(2),
@@ -1107,17 +2830,215 @@ fn run_batch_historical(
         };
 
         progress.record_result(exit_code);
-        let _ = profile; // Silence unused warning - profile passed to subprocess
     }
 
     progress.print_summary();
 
-    // Return overall exit code
+    let summary = batch::build_summary(&batch_id, range, &progress.outcomes, std::path::Path::new(output_dir));
+    println!("{}", batch::render_summary(&summary));
+    match batch::write_summary(std::path::Path::new(output_dir), &summary) {
+        Ok(path) => eprintln!("Batch summary: {}", path.display()),
+        Err(e) => warnings::warn(format!("Failed to write batch summary: {e:#}")),
+    }
+
     if progress.errors > 0 {
-        2
+        ExitStatus::Error
     } else if progress.failed > 0 {
-        1
+        ExitStatus::Failure
+    } else {
+        ExitStatus::Success
+    }
+}
+
+/// The `matrix` flags, passed on to the `run` of every cell.
+#[derive(Clone, Copy)]
+struct MatrixOptions<'a> {
+    components: Option<&'a str>,
+    tags: &'a str,
+    output_dir: &'a str,
+    publish: Option<&'a matrix::PublishOptions>,
+    /// --operator-channel was given, so cells do not derive one from their branch
+    explicit_channel: bool,
+    no_auto_setup: bool,
+    i_know_what_im_doing: bool,
+}
+
+/// Run every cell of a matrix: one thread per OCP version, its operator branches
+/// one after another, each a self-invoked `run` against that version's cluster.
+fn run_matrix(cells: &[matrix::Cell], opts: &MatrixOptions<'_>) -> ExitStatus {
+    let MatrixOptions { components, tags, output_dir, publish, explicit_channel, no_auto_setup, i_know_what_im_doing } = *opts;
+    // A mistyped branch would fail the cell on every cluster
+    let mut branches: Vec<&str> = cells.iter().map(|c| c.operator_branch.as_str()).collect();
+    branches.sort_unstable();
+    branches.dedup();
+    let checks: Vec<_> = branches
+        .iter()
+        .map(|b| refcheck::RefCheck::new("--operator-branch", testref::repo(), b))
+        .collect();
+    if let Err(e) = refcheck::validate_refs(&checks) {
+        eprintln!("Error: {e:#}");
+        return ExitStatus::Error;
+    }
+
+    if confirm::required() {
+        let changes: Vec<String> = cells
+            .iter()
+            .map(|c| format!("deploy {} on context {} (OCP {}, {})", components.unwrap_or("the default components"), c.context, c.ocp, c.operator_branch))
+            .collect();
+        if let Err(e) = confirm::confirm("run the matrix", &changes) {
+            eprintln!("Error: {e:#}");
+            return ExitStatus::Error;
+        }
+    }
+
+    let matrix_id = matrix::matrix_id();
+    eprintln!("\n=== MATRIX RUN ===");
+    eprintln!("Matrix id: {}", matrix_id);
+    eprintln!("Cells: {}", cells.len());
+    let output = std::path::Path::new(output_dir);
+
+    let run_cell = |cell: &matrix::Cell| -> i32 {
+        let cell_dir = output.join(cell.dir_name());
+        let kubeconfig = match matrix::write_kubeconfig(&cell.context, &cell_dir) {
+            Ok(p) => p,
+            Err(e) => {
+                eprintln!("ERROR: OCP {} / {}: {e:#}", cell.ocp, cell.operator_branch);
+                return 2;
+            }
+        };
+        let log = match std::fs::File::create(cell_dir.join("matrix.log")) {
+            Ok(f) => f,
+            Err(e) => {
+                eprintln!("ERROR: Failed to create {}: {}", cell_dir.join("matrix.log").display(), e);
+                return 2;
+            }
+        };
+
+        let mut args = vec![
+            "run".to_string(),
+            "--tags".to_string(),
+            tags.to_string(),
+            "--output-dir".to_string(),
+            cell_dir.display().to_string(),
+            "--release-tests-ref".to_string(),
+            cell.operator_branch.clone(),
+        ];
+        if let Some(c) = components {
+            args.push("--components".to_string());
+            args.push(c.to_string());
+        }
+        if no_auto_setup {
+            args.push("--no-auto-setup".to_string());
+        }
+        if github::is_offline() {
+            args.push("--offline".to_string());
+        }
+        if i_know_what_im_doing {
+            args.push("--i-know-what-im-doing".to_string());
+        }
+        if k8s::api_retries() != k8s::DEFAULT_API_RETRIES {
+            args.push("--api-retries".to_string());
+            args.push(k8s::api_retries().to_string());
+        }
+        args.extend(setup::operator_args());
+        args.extend(build::to_args());
+        args.extend(tools::to_args());
+        args.extend(scopeduser::to_args());
+        args.extend(testref::to_args());
+        args.extend(gitops::to_args());
+        if let Some(channel) = matrix::operator_channel(&cell.operator_branch).filter(|_| !explicit_channel) {
+            args.push("--operator-channel".to_string());
+            args.push(channel);
+        }
+        // The cell's coordinates win over the same keys in --meta
+        args.extend(runmeta::to_args());
+        args.push("--meta".to_string());
+        args.push(format!("{}={}", matrix::OCP_META_KEY, cell.ocp));
+        args.push("--meta".to_string());
+        args.push(format!("{}={}", matrix::BRANCH_META_KEY, cell.operator_branch));
+
+        let mut cmd = std::process::Command::new(std::env::current_exe().unwrap());
+        cmd.args(&args).env(batch::BATCH_ID_ENV, &matrix_id).env("KUBECONFIG", &kubeconfig);
+        cmd.args(logging::to_args());
+        // Cells have no terminal to ask on: the matrix asked for all of them
+        cmd.args(confirm::to_args()).stdin(std::process::Stdio::null());
+        if let Some(path) = audit::path() {
+            cmd.arg("--audit-log").arg(path);
+        }
+        if let Some(dir) = cassette::record_dir() {
+            cmd.arg("--record").arg(dir);
+        }
+        if workspace::keep() {
+            cmd.arg("--keep-workdir");
+        }
+        cmd.arg("--no-progress");
+        match log.try_clone() {
+            Ok(err) => {
+                cmd.stdout(log).stderr(err);
+            }
+            Err(e) => {
+                eprintln!("ERROR: Failed to redirect output to matrix.log: {}", e);
+                return 2;
+            }
+        }
+
+        eprintln!("  OCP {} / {}: started (log: {})", cell.ocp, cell.operator_branch, cell_dir.join("matrix.log").display());
+        let exit_code = match audit::status(&mut cmd) {
+            Ok(s) => s.code().unwrap_or(2),
+            Err(e) => {
+                eprintln!("ERROR: Failed to execute for OCP {} / {}: {}", cell.ocp, cell.operator_branch, e);
+                2
+            }
+        };
+        eprintln!("  OCP {} / {}: exit code {}", cell.ocp, cell.operator_branch, exit_code);
+        exit_code
+    };
+
+    let mut by_ocp: Vec<Vec<&matrix::Cell>> = Vec::new();
+    for cell in cells {
+        match by_ocp.iter_mut().find(|group| group[0].ocp == cell.ocp) {
+            Some(group) => group.push(cell),
+            None => by_ocp.push(vec![cell]),
+        }
+    }
+    let outcomes: Vec<(matrix::Cell, i32)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = by_ocp
+            .iter()
+            .map(|group| scope.spawn(|| group.iter().map(|cell| ((*cell).clone(), run_cell(cell))).collect::<Vec<_>>()))
+            .collect();
+        handles.into_iter().flat_map(|h| h.join().unwrap_or_default()).collect()
+    });
+
+    let mut summary = matrix::build_summary(&matrix_id, components, &outcomes, output);
+    if let Some(opts) = publish {
+        // Each cell's metadata.json carries the matrix id and its coordinates
+        for cell in summary.cells.iter_mut().filter(|c| c.total.is_some()) {
+            let cell_dir = output.join(&cell.output_dir);
+            match publish::publish(&cell_dir.display().to_string(), opts.remote.as_deref(), opts.label.as_deref(), None, None, true, None) {
+                Ok(run_id) => cell.run_id = Some(run_id),
+                Err(e) => warnings::warn(format!("Failed to publish OCP {} / {}: {e:#}", cell.ocp, cell.operator_branch)),
+            }
+        }
+    }
+    println!("{}", matrix::render_summary(&summary));
+    match matrix::write_summary(output, &summary) {
+        Ok(path) => eprintln!("Matrix summary: {}", path.display()),
+        Err(e) => warnings::warn(format!("Failed to write matrix summary: {e:#}")),
+    }
+    if let Some(opts) = publish {
+        let published = serde_json::to_string_pretty(&summary)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| publish::publish_file(&matrix::published_summary_path(&matrix_id), &content, opts.remote.as_deref()));
+        if let Err(e) = published {
+            warnings::warn(format!("Failed to publish the matrix summary: {e:#}"));
+        }
+    }
+
+    if summary.cells.iter().any(|c| c.status == "error") {
+        ExitStatus::Error
+    } else if summary.cells.iter().any(|c| c.status == "failed") {
+        ExitStatus::Failure
     } else {
-        0
+        ExitStatus::Success
     }
 }